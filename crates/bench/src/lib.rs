@@ -145,6 +145,7 @@ pub fn run_pipeline_once(batch: &[SearcherInputTx<'_>]) -> usize {
     let config = SearcherConfig {
         min_score: 7_500,
         max_candidates: 128,
+        ..SearcherConfig::default()
     };
     rank_opportunity_batch(batch, config).candidates.len()
 }
@@ -502,6 +503,9 @@ fn decoded_event(seq_id: u64, transaction: &ValidatedTransaction) -> EventEnvelo
         ingest_ts_mono_ns: transaction.observed_at_mono_ns,
         source_id: transaction.source_id.clone(),
         payload: EventPayload::TxDecoded(transaction.decoded.clone()),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
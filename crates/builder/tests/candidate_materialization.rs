@@ -85,6 +85,7 @@ fn opportunity(
             urgency_component: 0,
             structural_component: 0,
             strategy_bonus: 0,
+            l1_fee_penalty: 0,
         },
         reasons: vec!["test".to_owned()],
     }
@@ -100,6 +101,7 @@ fn simulation_batch(tx_results: Vec<TxSimulationResult>) -> SimulationBatchResul
             base_fee_wei: 1_000_000_000,
             coinbase: [0x55; 20],
             state_root: [0xaa; 32],
+            op_l1_fee: None,
         },
         tx_results,
         final_state_diff_hash: [0xbb; 32],
@@ -114,6 +116,8 @@ fn sim_result(hash: [u8; 32], success: bool, gas_used: u64) -> TxSimulationResul
         state_diff_hash: [0xcc; 32],
         fail_category: (!success).then_some(sim_engine::SimulationFailCategory::Revert),
         trace_id: 7,
+        l1_data_fee_wei: None,
+        gas_cost_wei: u128::from(gas_used) * 1_000_000_000,
     }
 }
 
@@ -0,0 +1,104 @@
+//! Bounds-checked Solidity ABI readers and word encoders shared by decoders.
+//!
+//! Offsets inside ABI payloads are attacker-controlled, so every reader
+//! returns `None` instead of panicking when a word or tail falls outside the
+//! buffer. Callers map `None` onto their own error types.
+
+use crate::Address;
+
+/// Width of one ABI word in bytes.
+pub const WORD: usize = 32;
+
+/// Returns the word starting at `offset`.
+pub fn abi_word(data: &[u8], offset: usize) -> Option<&[u8; WORD]> {
+    data.get(offset..offset.checked_add(WORD)?)?.try_into().ok()
+}
+
+/// Reads a word that must fit in a `usize`, such as an offset or length.
+pub fn abi_usize(data: &[u8], offset: usize) -> Option<usize> {
+    let word = abi_word(data, offset)?;
+    if word[..24].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    usize::try_from(u64::from_be_bytes(word[24..].try_into().ok()?)).ok()
+}
+
+/// Reads a `uint` word, returning `None` when it does not fit in 128 bits.
+pub fn abi_u128(data: &[u8], offset: usize) -> Option<u128> {
+    let (high, low) = unpack_u128_pair(abi_word(data, offset)?);
+    (high == 0).then_some(low)
+}
+
+/// Reads the low 20 bytes of the word at `offset` as an address.
+pub fn abi_address(data: &[u8], offset: usize) -> Option<Address> {
+    abi_word(data, offset)?[12..].try_into().ok()
+}
+
+/// Reads a length-prefixed `bytes` value whose length word is at `offset`.
+pub fn abi_bytes(data: &[u8], offset: usize) -> Option<&[u8]> {
+    let len = abi_usize(data, offset)?;
+    let start = offset.checked_add(WORD)?;
+    data.get(start..start.checked_add(len)?)
+}
+
+/// Reads a dynamic `bytes` value through the head word at `head_offset`.
+pub fn abi_dynamic_bytes(data: &[u8], head_offset: usize) -> Option<&[u8]> {
+    abi_bytes(data, abi_usize(data, head_offset)?)
+}
+
+/// Reads element `index` of a `bytes[]` array whose length word is at
+/// `array_offset`.
+pub fn abi_array_bytes(data: &[u8], array_offset: usize, index: usize) -> Option<&[u8]> {
+    let elements = array_offset.checked_add(WORD)?;
+    let element_offset = abi_usize(data, elements.checked_add(index.checked_mul(WORD)?)?)?;
+    abi_bytes(data, elements.checked_add(element_offset)?)
+}
+
+/// Encodes an address as a left-padded word.
+pub fn address_word(address: Address) -> [u8; WORD] {
+    let mut word = [0_u8; WORD];
+    word[12..].copy_from_slice(&address);
+    word
+}
+
+/// Encodes a `uint` as a big-endian word.
+pub fn u128_word(value: u128) -> [u8; WORD] {
+    let mut word = [0_u8; WORD];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Packs two 128-bit values into one word, `high` first.
+pub fn pack_u128_pair(high: u128, low: u128) -> [u8; WORD] {
+    let mut word = [0_u8; WORD];
+    word[..16].copy_from_slice(&high.to_be_bytes());
+    word[16..].copy_from_slice(&low.to_be_bytes());
+    word
+}
+
+/// Splits one word into its high and low 128-bit halves.
+pub fn unpack_u128_pair(word: &[u8; WORD]) -> (u128, u128) {
+    let mut high = [0_u8; 16];
+    let mut low = [0_u8; 16];
+    high.copy_from_slice(&word[..16]);
+    low.copy_from_slice(&word[16..]);
+    (u128::from_be_bytes(high), u128::from_be_bytes(low))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readers_reject_out_of_bounds_offsets() {
+        let mut data = u128_word(WORD as u128).to_vec();
+        data.extend_from_slice(&u128_word(3));
+        data.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+
+        assert_eq!(abi_dynamic_bytes(&data, 0), Some(&[0xaa, 0xbb, 0xcc][..]));
+        assert_eq!(abi_usize(&data, usize::MAX - 4), None);
+        assert_eq!(abi_bytes(&data[..2 * WORD + 2], WORD), None);
+        assert_eq!(abi_u128(&pack_u128_pair(1, 2), 0), None);
+        assert_eq!(abi_address(&address_word([0x11; 20]), 0), Some([0x11; 20]));
+    }
+}
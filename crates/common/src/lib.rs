@@ -2,6 +2,8 @@
 
 #![forbid(unsafe_code)]
 
+pub mod abi;
+
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
@@ -154,6 +156,113 @@ impl Deref for StrategyId {
     }
 }
 
+/// OP-stack deposit transaction type (`0x7e`).
+pub const OP_DEPOSIT_TX_TYPE: u8 = 0x7e;
+/// Arbitrum L1-to-L2 ETH deposit transaction type.
+pub const ARBITRUM_DEPOSIT_TX_TYPE: u8 = 0x64;
+/// Arbitrum unsigned L1-originated transaction type.
+pub const ARBITRUM_UNSIGNED_TX_TYPE: u8 = 0x65;
+/// Arbitrum L1 contract-originated transaction type.
+pub const ARBITRUM_CONTRACT_TX_TYPE: u8 = 0x66;
+/// Arbitrum retryable-ticket redeem transaction type.
+pub const ARBITRUM_RETRY_TX_TYPE: u8 = 0x68;
+/// Arbitrum retryable-ticket submission transaction type.
+pub const ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE: u8 = 0x69;
+/// Arbitrum ArbOS internal transaction type.
+pub const ARBITRUM_INTERNAL_TX_TYPE: u8 = 0x6a;
+
+/// Execution-layer family used to interpret network-specific transaction types.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainFamily {
+    #[default]
+    Ethereum,
+    OpStack,
+    Arbitrum,
+}
+
+impl ChainFamily {
+    /// Looks up the family of a well-known chain id, falling back to plain
+    /// Ethereum. Chains declare their family in their chain config; this table
+    /// only covers chains whose config does not.
+    pub fn from_chain_id(chain_id: Option<u64>) -> Self {
+        match chain_id {
+            // Optimism, Base, Zora, Mode, World Chain, Unichain and their testnets.
+            Some(10 | 8453 | 7_777_777 | 34_443 | 480 | 130 | 11_155_420 | 84_532) => Self::OpStack,
+            // Arbitrum One, Arbitrum Nova and Arbitrum Sepolia.
+            Some(42_161 | 42_170 | 421_614) => Self::Arbitrum,
+            _ => Self::Ethereum,
+        }
+    }
+
+    /// Returns true when `tx_type` is a protocol-originated transaction on this
+    /// family. Such transactions never enter the public mempool.
+    pub fn is_system_tx_type(self, tx_type: u8) -> bool {
+        match self {
+            Self::Ethereum => false,
+            Self::OpStack => tx_type == OP_DEPOSIT_TX_TYPE,
+            Self::Arbitrum => matches!(
+                tx_type,
+                ARBITRUM_DEPOSIT_TX_TYPE
+                    | ARBITRUM_UNSIGNED_TX_TYPE
+                    | ARBITRUM_CONTRACT_TX_TYPE
+                    | ARBITRUM_RETRY_TX_TYPE
+                    | ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE
+                    | ARBITRUM_INTERNAL_TX_TYPE
+            ),
+        }
+    }
+}
+
+/// Network-specific fields carried by OP-stack and Arbitrum transactions.
+///
+/// Every field is optional because providers only populate the subset that
+/// applies to the transaction type and node implementation.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct L2TxFields {
+    /// OP-stack deposit source hash.
+    #[serde(default)]
+    pub source_hash: Option<[u8; 32]>,
+    /// OP-stack ETH minted on L2 by a deposit.
+    #[serde(default)]
+    pub mint_wei: Option<u128>,
+    /// OP-stack pre-Regolith system deposit flag.
+    #[serde(default)]
+    pub is_system_tx: Option<bool>,
+    /// OP-stack L1 data fee charged to the transaction.
+    #[serde(default)]
+    pub l1_fee_wei: Option<u128>,
+    #[serde(default)]
+    pub l1_gas_used: Option<u64>,
+    #[serde(default)]
+    pub l1_gas_price_wei: Option<u128>,
+    #[serde(default)]
+    pub l1_blob_base_fee_wei: Option<u128>,
+    #[serde(default)]
+    pub l1_base_fee_scalar: Option<u32>,
+    #[serde(default)]
+    pub l1_blob_base_fee_scalar: Option<u32>,
+    /// Arbitrum delayed-inbox request id.
+    #[serde(default)]
+    pub request_id: Option<[u8; 32]>,
+    /// Arbitrum retryable ticket id.
+    #[serde(default)]
+    pub ticket_id: Option<[u8; 32]>,
+    #[serde(default)]
+    pub refund_to: Option<Address>,
+    #[serde(default)]
+    pub l1_base_fee_wei: Option<u128>,
+    #[serde(default)]
+    pub max_refund_wei: Option<u128>,
+}
+
+impl L2TxFields {
+    /// Returns true when no network-specific field is populated.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
 /// Threshold configuration for translating runtime metrics into alert booleans.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct AlertThresholdConfig {
@@ -213,6 +322,7 @@ pub fn evaluate_alerts(
     snapshot: &MetricSnapshot,
     thresholds: &AlertThresholdConfig,
 ) -> AlertDecisions {
+    #[allow(clippy::manual_checked_ops)]
     let decode_failure_bps = if snapshot.tx_decode_total == 0 {
         0
     } else {
@@ -225,6 +335,7 @@ pub fn evaluate_alerts(
         let ratio = snapshot.tx_per_sec_current as f64 / snapshot.tx_per_sec_baseline as f64;
         ((1.0 - ratio.clamp(0.0, 1.0)) * 100.0).round() as u8
     };
+    #[allow(clippy::manual_checked_ops)]
     let queue_saturation_percent = if snapshot.queue_depth_capacity == 0 {
         0
     } else {
@@ -244,7 +355,10 @@ pub fn evaluate_alerts(
 
 #[cfg(test)]
 mod tests {
-    use super::{AlertThresholdConfig, MetricSnapshot, SourceId, evaluate_alerts};
+    use super::{
+        AlertThresholdConfig, ChainFamily, MetricSnapshot, OP_DEPOSIT_TX_TYPE, SourceId,
+        evaluate_alerts,
+    };
 
     #[test]
     fn source_id_display_matches_inner_value() {
@@ -252,6 +366,26 @@ mod tests {
         assert_eq!(source.to_string(), "peer-1");
     }
 
+    #[test]
+    fn chain_family_classifies_system_tx_types() {
+        assert_eq!(ChainFamily::from_chain_id(Some(8453)), ChainFamily::OpStack);
+        assert_eq!(
+            ChainFamily::from_chain_id(Some(42_161)),
+            ChainFamily::Arbitrum
+        );
+        assert_eq!(ChainFamily::from_chain_id(None), ChainFamily::Ethereum);
+        assert_eq!(
+            ChainFamily::from_chain_id(Some(999_999)),
+            ChainFamily::Ethereum
+        );
+
+        assert!(ChainFamily::OpStack.is_system_tx_type(OP_DEPOSIT_TX_TYPE));
+        assert!(!ChainFamily::OpStack.is_system_tx_type(0x02));
+        assert!(ChainFamily::Arbitrum.is_system_tx_type(0x6a));
+        assert!(!ChainFamily::Arbitrum.is_system_tx_type(OP_DEPOSIT_TX_TYPE));
+        assert!(!ChainFamily::Ethereum.is_system_tx_type(OP_DEPOSIT_TX_TYPE));
+    }
+
     #[test]
     fn evaluate_alerts_flags_expected_conditions() {
         let thresholds = AlertThresholdConfig::default();
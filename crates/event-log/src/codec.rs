@@ -0,0 +1,1316 @@
+//! Compact binary codec for event envelopes.
+//!
+//! Each event is written as one self-delimiting frame:
+//!
+//! ```text
+//! frame   := schema_version:u8 body_len:varint body
+//! body    := field*
+//! field   := key:varint value
+//! key     := field_number << 3 | wire_type
+//! ```
+//!
+//! Wire type `0` carries an unsigned LEB128 varint (signed values are zigzag
+//! encoded) and wire type `2` carries a length-prefixed byte string or nested
+//! message. Decoders skip unknown field numbers and fill absent fields with
+//! zero values, so new fields can be appended without a schema version bump.
+//! Optional fields are only written when present. Field numbers are part of the
+//! on-disk contract and must never be reused.
+//!
+//! The payload message is a one-of: its field number selects the
+//! [`EventPayload`] variant. An unknown variant number has no representation,
+//! so the frame fails with [`CodecError::UnknownPayload`] instead of being
+//! skipped; readers must be upgraded before writers emit a new variant.
+
+use crate::schema::{
+    EventSchemaRegistry, UpcastError, to_versioned_json_writer, upcast_binary_event,
+};
+use crate::{
+    AssemblyDecisionApplied, AssemblyDecisionKind, BundleSubmitted, CandidateQueued,
+    ChainCheckpoint, DropReason, EntryPointVersion, EventEnvelope, EventPayload, HashLink,
+    OppDetected, SimCompleted, SimDispatched, SimulationStatus, TxBlocked, TxConfirmed, TxDecoded,
+    TxDropped, TxDroppedWire, TxFetched, TxReady, TxReorged, TxReplaced, TxSeen, UserOpSeen,
+};
+use common::SourceId;
+use std::io::{BufRead, Read};
+
+/// Schema version written as the first byte of every binary frame.
+pub const BINARY_SCHEMA_VERSION: u8 = 1;
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_LEN: u8 = 2;
+const MAX_VARINT_BYTES: usize = 19;
+
+/// Errors raised while decoding binary or JSON event streams.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum CodecError {
+    #[error("binary event frame is truncated")]
+    Truncated,
+    #[error("unsupported binary event schema version {0}")]
+    UnsupportedVersion(u8),
+    #[error("unsupported wire type {wire_type} for field number {field}")]
+    UnsupportedWireType { field: u32, wire_type: u8 },
+    #[error("invalid value for field '{field}'")]
+    InvalidValue { field: &'static str },
+    #[error("event envelope has no payload")]
+    MissingPayload,
+    #[error("unknown event payload variant {0}")]
+    UnknownPayload(u32),
+    #[error("json event decode failed: {0}")]
+    Json(String),
+    #[error("json event upcast failed: {0}")]
+    Upcast(#[from] UpcastError),
+    #[error("event stream read failed: {0}")]
+    Io(String),
+}
+
+/// Serialization used for persisted or exported event streams.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum EventEncoding {
+    /// Newline-delimited JSON stamped with the event schema version, kept for
+    /// debugging and external tooling.
+    #[default]
+    Json,
+    /// Length-prefixed binary frames produced by [`encode_event`].
+    Binary,
+}
+
+impl EventEncoding {
+    /// Appends one encoded event, including its record delimiter, to `out`.
+    pub fn encode_into(self, event: &EventEnvelope, out: &mut Vec<u8>) -> Result<(), CodecError> {
+        match self {
+            Self::Json => {
+                to_versioned_json_writer(&mut *out, event)
+                    .map_err(|err| CodecError::Json(err.to_string()))?;
+                out.push(b'\n');
+            }
+            Self::Binary => encode_event_into(event, out),
+        }
+        Ok(())
+    }
+
+    /// Detects the encoding of a stream from its first non-whitespace byte.
+    ///
+    /// JSON records always open with `{`, which can never be a valid binary
+    /// schema version, so mixed deployments can read either format.
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'{') | None => Self::Json,
+            Some(_) => Self::Binary,
+        }
+    }
+}
+
+/// Decodes every event in a stream, auto-detecting JSON lines or binary frames.
+///
+/// JSON records are upcast to the current event schema version on the way in,
+/// and binary frames get the same changes in meaning applied after decoding.
+pub fn decode_event_stream(bytes: &[u8]) -> Result<Vec<EventEnvelope>, CodecError> {
+    match EventEncoding::detect(bytes) {
+        EventEncoding::Json => {
+            let registry = EventSchemaRegistry::builtin();
+            bytes
+                .split(|byte| *byte == b'\n')
+                .filter(|line| !line.trim_ascii().is_empty())
+                .map(|line| registry.decode_json_slice(line).map_err(CodecError::from))
+                .collect()
+        }
+        EventEncoding::Binary => {
+            let mut events = Vec::new();
+            let mut rest = bytes;
+            while !rest.is_empty() {
+                let (mut event, consumed) = decode_event(rest)?;
+                upcast_binary_event(&mut event);
+                events.push(event);
+                rest = &rest[consumed..];
+            }
+            Ok(events)
+        }
+    }
+}
+
+/// Decodes a JSON array of event records, upcasting each element.
+pub fn decode_event_json_array(bytes: &[u8]) -> Result<Vec<EventEnvelope>, CodecError> {
+    let records: Vec<Box<serde_json::value::RawValue>> =
+        serde_json::from_slice(bytes).map_err(|err| CodecError::Json(err.to_string()))?;
+    let registry = EventSchemaRegistry::builtin();
+    records
+        .iter()
+        .map(|record| {
+            registry
+                .decode_json_slice(record.get().as_bytes())
+                .map_err(CodecError::from)
+        })
+        .collect()
+}
+
+/// Decodes events one record at a time from a reader, auto-detecting JSON
+/// lines or binary frames like [`decode_event_stream`].
+///
+/// Only the record being decoded is buffered, so logs larger than memory can
+/// be consumed. The iterator stops after the first error.
+pub struct EventStreamReader<R> {
+    reader: R,
+    encoding: Option<EventEncoding>,
+    record: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: BufRead> EventStreamReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            encoding: None,
+            record: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// Encoding detected from the first record, once one has been read.
+    pub fn encoding(&self) -> Option<EventEncoding> {
+        self.encoding
+    }
+
+    fn detect(&mut self) -> Result<Option<EventEncoding>, CodecError> {
+        if let Some(encoding) = self.encoding {
+            return Ok(Some(encoding));
+        }
+        loop {
+            let buf = self.reader.fill_buf().map_err(io_error)?;
+            let Some(&first) = buf.first() else {
+                return Ok(None);
+            };
+            if first.is_ascii_whitespace() {
+                self.reader.consume(1);
+                continue;
+            }
+            let encoding = EventEncoding::detect(&[first]);
+            self.encoding = Some(encoding);
+            return Ok(Some(encoding));
+        }
+    }
+
+    fn next_json(&mut self) -> Result<Option<EventEnvelope>, CodecError> {
+        loop {
+            self.record.clear();
+            if self
+                .reader
+                .read_until(b'\n', &mut self.record)
+                .map_err(io_error)?
+                == 0
+            {
+                return Ok(None);
+            }
+            let line = self.record.trim_ascii();
+            if !line.is_empty() {
+                return EventSchemaRegistry::builtin()
+                    .decode_json_slice(line)
+                    .map(Some)
+                    .map_err(CodecError::from);
+            }
+        }
+    }
+
+    fn next_binary(&mut self) -> Result<Option<EventEnvelope>, CodecError> {
+        self.record.clear();
+        let mut byte = [0_u8; 1];
+        if self.reader.read(&mut byte).map_err(io_error)? == 0 {
+            return Ok(None);
+        }
+        self.record.push(byte[0]);
+        loop {
+            if self.record.len() > MAX_VARINT_BYTES {
+                return Err(CodecError::InvalidValue { field: "varint" });
+            }
+            self.reader.read_exact(&mut byte).map_err(truncated)?;
+            self.record.push(byte[0]);
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let (body_len, _) = read_varint(&self.record[1..])?;
+        let body_len = u64::try_from(body_len).map_err(|_| CodecError::Truncated)?;
+        let header_len = self.record.len();
+        (&mut self.reader)
+            .take(body_len)
+            .read_to_end(&mut self.record)
+            .map_err(io_error)?;
+        if ((self.record.len() - header_len) as u64) < body_len {
+            return Err(CodecError::Truncated);
+        }
+        let (mut event, _) = decode_event(&self.record)?;
+        upcast_binary_event(&mut event);
+        Ok(Some(event))
+    }
+}
+
+impl<R: BufRead> Iterator for EventStreamReader<R> {
+    type Item = Result<EventEnvelope, CodecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let next = match self.detect() {
+            Ok(Some(EventEncoding::Json)) => self.next_json(),
+            Ok(Some(EventEncoding::Binary)) => self.next_binary(),
+            Ok(None) => Ok(None),
+            Err(err) => Err(err),
+        };
+        self.failed = next.is_err();
+        next.transpose()
+    }
+}
+
+fn io_error(err: std::io::Error) -> CodecError {
+    CodecError::Io(err.to_string())
+}
+
+fn truncated(err: std::io::Error) -> CodecError {
+    if err.kind() == std::io::ErrorKind::UnexpectedEof {
+        CodecError::Truncated
+    } else {
+        io_error(err)
+    }
+}
+
+/// Encodes one event as a binary frame.
+pub fn encode_event(event: &EventEnvelope) -> Vec<u8> {
+    let mut out = Vec::with_capacity(128);
+    encode_event_into(event, &mut out);
+    out
+}
+
+/// Appends one event as a binary frame to `out`.
+pub fn encode_event_into(event: &EventEnvelope, out: &mut Vec<u8>) {
+    encode_frame(event, true, out);
+}
+
+/// Appends the frame an event would have without its hash link; this is the
+/// canonical input to the hash chain digest.
+pub(crate) fn encode_unlinked_event_into(event: &EventEnvelope, out: &mut Vec<u8>) {
+    encode_frame(event, false, out);
+}
+
+fn encode_frame(event: &EventEnvelope, include_hash_link: bool, out: &mut Vec<u8>) {
+    let mut body = Vec::with_capacity(128);
+    let mut writer = MessageWriter(&mut body);
+    writer.uint(1, event.seq_id);
+    writer.int(2, event.ingest_ts_unix_ms);
+    writer.uint(3, event.ingest_ts_mono_ns);
+    writer.string(4, event.source_id.as_str());
+    writer.message(5, |writer| encode_payload(writer, &event.payload));
+    if include_hash_link && let Some(link) = &event.hash_link {
+        writer.message(6, |writer| {
+            writer.bytes(1, &link.prev_digest);
+            writer.bytes(2, &link.digest);
+        });
+    }
+    writer.opt_uint(7, event.chain_id);
+    writer.opt_uint(8, event.chain_seq_id);
+
+    out.push(BINARY_SCHEMA_VERSION);
+    put_varint(out, body.len() as u128);
+    out.extend_from_slice(&body);
+}
+
+/// Decodes the first binary frame in `bytes`, returning the event and the
+/// number of bytes consumed.
+pub fn decode_event(bytes: &[u8]) -> Result<(EventEnvelope, usize), CodecError> {
+    let (&version, rest) = bytes.split_first().ok_or(CodecError::Truncated)?;
+    if version != BINARY_SCHEMA_VERSION {
+        return Err(CodecError::UnsupportedVersion(version));
+    }
+    let (body_len, len_bytes) = read_varint(rest)?;
+    let body_len = usize::try_from(body_len).map_err(|_| CodecError::Truncated)?;
+    let body_start = 1 + len_bytes;
+    let body_end = body_start
+        .checked_add(body_len)
+        .ok_or(CodecError::Truncated)?;
+    let body = bytes
+        .get(body_start..body_end)
+        .ok_or(CodecError::Truncated)?;
+
+    let mut event = EventEnvelope {
+        seq_id: 0,
+        ingest_ts_unix_ms: 0,
+        ingest_ts_mono_ns: 0,
+        source_id: SourceId::default(),
+        payload: EventPayload::TxDropped(TxDropped::new([0; 32], DropReason::Other(String::new()))),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
+    };
+    let mut payload = None;
+    let mut fields = FieldReader::new(body);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => event.seq_id = value.uint("EventEnvelope.seq_id")?,
+            2 => event.ingest_ts_unix_ms = value.int("EventEnvelope.ingest_ts_unix_ms")?,
+            3 => event.ingest_ts_mono_ns = value.uint("EventEnvelope.ingest_ts_mono_ns")?,
+            4 => event.source_id = SourceId(value.string("EventEnvelope.source_id")?),
+            5 => payload = Some(decode_payload(value.bytes("EventEnvelope.payload")?)?),
+            6 => event.hash_link = Some(decode_hash_link(value.bytes("EventEnvelope.hash_link")?)?),
+            7 => event.chain_id = Some(value.uint("EventEnvelope.chain_id")?),
+            8 => event.chain_seq_id = Some(value.uint("EventEnvelope.chain_seq_id")?),
+            _ => {}
+        }
+    }
+    event.payload = payload.ok_or(CodecError::MissingPayload)?;
+    Ok((event, body_end))
+}
+
+fn encode_payload(writer: &mut MessageWriter<'_>, payload: &EventPayload) {
+    match payload {
+        EventPayload::TxSeen(e) => writer.message(1, |w| {
+            w.bytes(1, &e.hash);
+            w.string(2, &e.peer_id);
+            w.int(3, e.seen_at_unix_ms);
+            w.uint(4, e.seen_at_mono_ns);
+        }),
+        EventPayload::TxFetched(e) => writer.message(2, |w| {
+            w.bytes(1, &e.hash);
+            w.int(2, e.fetched_at_unix_ms);
+        }),
+        EventPayload::TxDecoded(e) => writer.message(3, |w| {
+            w.bytes(1, &e.hash);
+            w.uint(2, e.tx_type);
+            w.bytes(3, &e.sender);
+            w.uint(4, e.nonce);
+            w.opt_uint(5, e.chain_id);
+            w.opt_bytes(6, e.to.as_ref());
+            w.opt_uint(7, e.value_wei);
+            w.opt_uint(8, e.gas_limit);
+            w.opt_uint(9, e.gas_price_wei);
+            w.opt_uint(10, e.max_fee_per_gas_wei);
+            w.opt_uint(11, e.max_priority_fee_per_gas_wei);
+            w.opt_uint(12, e.max_fee_per_blob_gas_wei);
+            w.opt_uint(13, e.calldata_len);
+        }),
+        EventPayload::TxReady(e) => writer.message(4, |w| {
+            w.bytes(1, &e.hash);
+            w.bytes(2, &e.sender);
+            w.uint(3, e.nonce);
+        }),
+        EventPayload::TxBlocked(e) => writer.message(5, |w| {
+            w.bytes(1, &e.hash);
+            w.bytes(2, &e.sender);
+            w.uint(3, e.nonce);
+            w.opt_uint(4, e.expected_nonce);
+        }),
+        EventPayload::CandidateQueued(e) => writer.message(6, |w| {
+            w.string(1, &e.candidate_id);
+            w.bytes(2, &e.tx_hash);
+            for hash in &e.member_tx_hashes {
+                w.bytes(3, hash);
+            }
+            w.opt_uint(4, e.chain_id);
+            w.string(5, &e.strategy);
+            w.uint(6, e.score);
+            w.string(7, &e.protocol);
+            w.string(8, &e.category);
+            w.string(9, &e.feature_engine_version);
+            w.string(10, &e.scorer_version);
+            w.string(11, &e.strategy_version);
+            for reason in &e.reasons {
+                w.string(12, reason);
+            }
+            w.int(13, e.detected_unix_ms);
+        }),
+        EventPayload::SimDispatched(e) => writer.message(7, |w| {
+            w.string(1, &e.candidate_id);
+            w.bytes(2, &e.tx_hash);
+            for hash in &e.member_tx_hashes {
+                w.bytes(3, hash);
+            }
+            w.uint(4, e.block_number);
+        }),
+        EventPayload::OppDetected(e) => writer.message(8, |w| {
+            w.bytes(1, &e.hash);
+            w.string(2, &e.strategy);
+            w.uint(3, e.score);
+            w.string(4, &e.protocol);
+            w.string(5, &e.category);
+            w.string(6, &e.feature_engine_version);
+            w.string(7, &e.scorer_version);
+            w.string(8, &e.strategy_version);
+            for reason in &e.reasons {
+                w.string(9, reason);
+            }
+        }),
+        EventPayload::SimCompleted(e) => writer.message(9, |w| {
+            w.bytes(1, &e.hash);
+            w.string(2, &e.sim_id);
+            w.string(3, e.status.as_str());
+            w.string(4, &e.feature_engine_version);
+            w.string(5, &e.scorer_version);
+            w.string(6, &e.strategy_version);
+            if let Some(category) = &e.fail_category {
+                w.string(7, category.as_str());
+            }
+            w.opt_uint(8, e.latency_ms);
+            w.opt_uint(9, e.tx_count);
+            w.opt_uint(10, e.l1_data_fee_wei);
+            w.opt_uint(11, e.gas_cost_wei);
+        }),
+        EventPayload::AssemblyDecisionApplied(e) => writer.message(10, |w| {
+            w.string(1, &e.candidate_id);
+            w.bytes(2, &e.tx_hash);
+            w.string(3, e.decision.as_str());
+            for candidate_id in &e.replaced_candidate_ids {
+                w.string(4, candidate_id);
+            }
+            if let Some(reason) = &e.reason {
+                w.string(5, reason);
+            }
+            w.uint(6, e.block_number);
+        }),
+        EventPayload::BundleSubmitted(e) => writer.message(11, |w| {
+            w.bytes(1, &e.hash);
+            w.string(2, &e.bundle_id);
+            w.string(3, &e.sim_id);
+            w.string(4, &e.relay);
+            w.uint(5, e.accepted);
+            w.string(6, &e.feature_engine_version);
+            w.string(7, &e.scorer_version);
+            w.string(8, &e.strategy_version);
+        }),
+        EventPayload::TxReplaced(e) => writer.message(12, |w| {
+            w.bytes(1, &e.hash);
+            w.bytes(2, &e.replaced_by);
+        }),
+        EventPayload::TxDropped(e) => writer.message(13, |w| {
+            w.bytes(1, &e.hash);
+            w.string(2, &TxDroppedWire::from(e).reason);
+        }),
+        EventPayload::TxConfirmedProvisional(e) => {
+            writer.message(14, |w| encode_tx_confirmed(w, e));
+        }
+        EventPayload::TxConfirmedFinal(e) => writer.message(15, |w| encode_tx_confirmed(w, e)),
+        EventPayload::TxReorged(e) => writer.message(16, |w| {
+            w.bytes(1, &e.hash);
+            w.bytes(2, &e.old_block_hash);
+            w.bytes(3, &e.new_block_hash);
+        }),
+        EventPayload::UserOpSeen(e) => writer.message(17, |w| {
+            w.bytes(1, &e.user_op_hash);
+            w.bytes(2, &e.entry_point);
+            w.uint(
+                3,
+                match e.entry_point_version {
+                    EntryPointVersion::V06 => 6_u8,
+                    EntryPointVersion::V07 => 7,
+                },
+            );
+            w.bytes(4, &e.sender);
+            w.bytes(5, &e.nonce);
+            w.opt_uint(6, e.chain_id);
+            w.opt_bytes(7, e.factory.as_ref());
+            w.opt_bytes(8, e.paymaster.as_ref());
+            w.uint(9, e.call_gas_limit);
+            w.uint(10, e.verification_gas_limit);
+            w.uint(11, e.pre_verification_gas);
+            w.uint(12, e.max_fee_per_gas_wei);
+            w.uint(13, e.max_priority_fee_per_gas_wei);
+            w.uint(14, e.call_data_len);
+            w.opt_bytes(15, e.bundle_tx_hash.as_ref());
+            if !e.call_data.is_empty() {
+                w.bytes(16, &e.call_data);
+            }
+        }),
+        EventPayload::ChainCheckpoint(e) => writer.message(18, |w| {
+            w.uint(1, e.covered_seq_id);
+            w.bytes(2, &e.covered_digest);
+            w.string(3, &e.key_id);
+            w.bytes(4, &e.signature);
+        }),
+    }
+}
+
+fn encode_tx_confirmed(writer: &mut MessageWriter<'_>, confirmed: &TxConfirmed) {
+    writer.bytes(1, &confirmed.hash);
+    writer.uint(2, confirmed.block_number);
+    writer.bytes(3, &confirmed.block_hash);
+}
+
+fn decode_payload(data: &[u8]) -> Result<EventPayload, CodecError> {
+    let mut fields = FieldReader::new(data);
+    let mut payload = None;
+    while let Some((field, value)) = fields.next_field()? {
+        let body = value.bytes("EventPayload")?;
+        payload = Some(match field {
+            1 => EventPayload::TxSeen(decode_tx_seen(body)?),
+            2 => EventPayload::TxFetched(decode_tx_fetched(body)?),
+            3 => EventPayload::TxDecoded(decode_tx_decoded(body)?),
+            4 => EventPayload::TxReady(decode_tx_ready(body)?),
+            5 => EventPayload::TxBlocked(decode_tx_blocked(body)?),
+            6 => EventPayload::CandidateQueued(decode_candidate_queued(body)?),
+            7 => EventPayload::SimDispatched(decode_sim_dispatched(body)?),
+            8 => EventPayload::OppDetected(decode_opp_detected(body)?),
+            9 => EventPayload::SimCompleted(decode_sim_completed(body)?),
+            10 => EventPayload::AssemblyDecisionApplied(decode_assembly_decision(body)?),
+            11 => EventPayload::BundleSubmitted(decode_bundle_submitted(body)?),
+            12 => EventPayload::TxReplaced(decode_tx_replaced(body)?),
+            13 => EventPayload::TxDropped(decode_tx_dropped(body)?),
+            14 => EventPayload::TxConfirmedProvisional(decode_tx_confirmed(body)?),
+            15 => EventPayload::TxConfirmedFinal(decode_tx_confirmed(body)?),
+            16 => EventPayload::TxReorged(decode_tx_reorged(body)?),
+            17 => EventPayload::UserOpSeen(decode_user_op_seen(body)?),
+            18 => EventPayload::ChainCheckpoint(decode_chain_checkpoint(body)?),
+            other => return Err(CodecError::UnknownPayload(other)),
+        });
+    }
+    payload.ok_or(CodecError::MissingPayload)
+}
+
+fn decode_tx_seen(data: &[u8]) -> Result<TxSeen, CodecError> {
+    let mut out = TxSeen {
+        hash: [0; 32],
+        peer_id: String::new(),
+        seen_at_unix_ms: 0,
+        seen_at_mono_ns: 0,
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("TxSeen.hash")?,
+            2 => out.peer_id = value.string("TxSeen.peer_id")?,
+            3 => out.seen_at_unix_ms = value.int("TxSeen.seen_at_unix_ms")?,
+            4 => out.seen_at_mono_ns = value.uint("TxSeen.seen_at_mono_ns")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_tx_fetched(data: &[u8]) -> Result<TxFetched, CodecError> {
+    let mut out = TxFetched {
+        hash: [0; 32],
+        fetched_at_unix_ms: 0,
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("TxFetched.hash")?,
+            2 => out.fetched_at_unix_ms = value.int("TxFetched.fetched_at_unix_ms")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_tx_decoded(data: &[u8]) -> Result<TxDecoded, CodecError> {
+    let mut out = TxDecoded {
+        hash: [0; 32],
+        tx_type: 0,
+        sender: [0; 20],
+        nonce: 0,
+        chain_id: None,
+        to: None,
+        value_wei: None,
+        gas_limit: None,
+        gas_price_wei: None,
+        max_fee_per_gas_wei: None,
+        max_priority_fee_per_gas_wei: None,
+        max_fee_per_blob_gas_wei: None,
+        calldata_len: None,
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("TxDecoded.hash")?,
+            2 => out.tx_type = value.uint("TxDecoded.tx_type")?,
+            3 => out.sender = value.fixed("TxDecoded.sender")?,
+            4 => out.nonce = value.uint("TxDecoded.nonce")?,
+            5 => out.chain_id = Some(value.uint("TxDecoded.chain_id")?),
+            6 => out.to = Some(value.fixed("TxDecoded.to")?),
+            7 => out.value_wei = Some(value.uint("TxDecoded.value_wei")?),
+            8 => out.gas_limit = Some(value.uint("TxDecoded.gas_limit")?),
+            9 => out.gas_price_wei = Some(value.uint("TxDecoded.gas_price_wei")?),
+            10 => out.max_fee_per_gas_wei = Some(value.uint("TxDecoded.max_fee_per_gas_wei")?),
+            11 => {
+                out.max_priority_fee_per_gas_wei =
+                    Some(value.uint("TxDecoded.max_priority_fee_per_gas_wei")?)
+            }
+            12 => {
+                out.max_fee_per_blob_gas_wei =
+                    Some(value.uint("TxDecoded.max_fee_per_blob_gas_wei")?)
+            }
+            13 => out.calldata_len = Some(value.uint("TxDecoded.calldata_len")?),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_tx_ready(data: &[u8]) -> Result<TxReady, CodecError> {
+    let mut out = TxReady {
+        hash: [0; 32],
+        sender: [0; 20],
+        nonce: 0,
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("TxReady.hash")?,
+            2 => out.sender = value.fixed("TxReady.sender")?,
+            3 => out.nonce = value.uint("TxReady.nonce")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_tx_blocked(data: &[u8]) -> Result<TxBlocked, CodecError> {
+    let mut out = TxBlocked {
+        hash: [0; 32],
+        sender: [0; 20],
+        nonce: 0,
+        expected_nonce: None,
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("TxBlocked.hash")?,
+            2 => out.sender = value.fixed("TxBlocked.sender")?,
+            3 => out.nonce = value.uint("TxBlocked.nonce")?,
+            4 => out.expected_nonce = Some(value.uint("TxBlocked.expected_nonce")?),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_candidate_queued(data: &[u8]) -> Result<CandidateQueued, CodecError> {
+    let mut out = CandidateQueued {
+        candidate_id: String::new(),
+        tx_hash: [0; 32],
+        member_tx_hashes: Vec::new(),
+        chain_id: None,
+        strategy: String::new(),
+        score: 0,
+        protocol: String::new(),
+        category: String::new(),
+        feature_engine_version: String::new(),
+        scorer_version: String::new(),
+        strategy_version: String::new(),
+        reasons: Vec::new(),
+        detected_unix_ms: 0,
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.candidate_id = value.string("CandidateQueued.candidate_id")?,
+            2 => out.tx_hash = value.fixed("CandidateQueued.tx_hash")?,
+            3 => out
+                .member_tx_hashes
+                .push(value.fixed("CandidateQueued.member_tx_hashes")?),
+            4 => out.chain_id = Some(value.uint("CandidateQueued.chain_id")?),
+            5 => out.strategy = value.string("CandidateQueued.strategy")?,
+            6 => out.score = value.uint("CandidateQueued.score")?,
+            7 => out.protocol = value.string("CandidateQueued.protocol")?,
+            8 => out.category = value.string("CandidateQueued.category")?,
+            9 => {
+                out.feature_engine_version =
+                    value.string("CandidateQueued.feature_engine_version")?
+            }
+            10 => out.scorer_version = value.string("CandidateQueued.scorer_version")?,
+            11 => out.strategy_version = value.string("CandidateQueued.strategy_version")?,
+            12 => out.reasons.push(value.string("CandidateQueued.reasons")?),
+            13 => out.detected_unix_ms = value.int("CandidateQueued.detected_unix_ms")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_sim_dispatched(data: &[u8]) -> Result<SimDispatched, CodecError> {
+    let mut out = SimDispatched {
+        candidate_id: String::new(),
+        tx_hash: [0; 32],
+        member_tx_hashes: Vec::new(),
+        block_number: 0,
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.candidate_id = value.string("SimDispatched.candidate_id")?,
+            2 => out.tx_hash = value.fixed("SimDispatched.tx_hash")?,
+            3 => out
+                .member_tx_hashes
+                .push(value.fixed("SimDispatched.member_tx_hashes")?),
+            4 => out.block_number = value.uint("SimDispatched.block_number")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_opp_detected(data: &[u8]) -> Result<OppDetected, CodecError> {
+    let mut out = OppDetected {
+        hash: [0; 32],
+        strategy: String::new(),
+        score: 0,
+        protocol: String::new(),
+        category: String::new(),
+        feature_engine_version: String::new(),
+        scorer_version: String::new(),
+        strategy_version: String::new(),
+        reasons: Vec::new(),
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("OppDetected.hash")?,
+            2 => out.strategy = value.string("OppDetected.strategy")?,
+            3 => out.score = value.uint("OppDetected.score")?,
+            4 => out.protocol = value.string("OppDetected.protocol")?,
+            5 => out.category = value.string("OppDetected.category")?,
+            6 => out.feature_engine_version = value.string("OppDetected.feature_engine_version")?,
+            7 => out.scorer_version = value.string("OppDetected.scorer_version")?,
+            8 => out.strategy_version = value.string("OppDetected.strategy_version")?,
+            9 => out.reasons.push(value.string("OppDetected.reasons")?),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_sim_completed(data: &[u8]) -> Result<SimCompleted, CodecError> {
+    let mut out = SimCompleted {
+        hash: [0; 32],
+        sim_id: String::new(),
+        status: SimulationStatus::Other(String::new()),
+        feature_engine_version: String::new(),
+        scorer_version: String::new(),
+        strategy_version: String::new(),
+        fail_category: None,
+        latency_ms: None,
+        tx_count: None,
+        l1_data_fee_wei: None,
+        gas_cost_wei: None,
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("SimCompleted.hash")?,
+            2 => out.sim_id = value.string("SimCompleted.sim_id")?,
+            3 => out.status = value.string("SimCompleted.status")?.into(),
+            4 => {
+                out.feature_engine_version = value.string("SimCompleted.feature_engine_version")?
+            }
+            5 => out.scorer_version = value.string("SimCompleted.scorer_version")?,
+            6 => out.strategy_version = value.string("SimCompleted.strategy_version")?,
+            7 => out.fail_category = Some(value.string("SimCompleted.fail_category")?.into()),
+            8 => out.latency_ms = Some(value.uint("SimCompleted.latency_ms")?),
+            9 => out.tx_count = Some(value.uint("SimCompleted.tx_count")?),
+            10 => out.l1_data_fee_wei = Some(value.uint("SimCompleted.l1_data_fee_wei")?),
+            11 => out.gas_cost_wei = Some(value.uint("SimCompleted.gas_cost_wei")?),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_assembly_decision(data: &[u8]) -> Result<AssemblyDecisionApplied, CodecError> {
+    let mut out = AssemblyDecisionApplied {
+        candidate_id: String::new(),
+        tx_hash: [0; 32],
+        decision: AssemblyDecisionKind::Other(String::new()),
+        replaced_candidate_ids: Vec::new(),
+        reason: None,
+        block_number: 0,
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.candidate_id = value.string("AssemblyDecisionApplied.candidate_id")?,
+            2 => out.tx_hash = value.fixed("AssemblyDecisionApplied.tx_hash")?,
+            3 => out.decision = value.string("AssemblyDecisionApplied.decision")?.into(),
+            4 => out
+                .replaced_candidate_ids
+                .push(value.string("AssemblyDecisionApplied.replaced_candidate_ids")?),
+            5 => out.reason = Some(value.string("AssemblyDecisionApplied.reason")?),
+            6 => out.block_number = value.uint("AssemblyDecisionApplied.block_number")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_bundle_submitted(data: &[u8]) -> Result<BundleSubmitted, CodecError> {
+    let mut out = BundleSubmitted {
+        hash: [0; 32],
+        bundle_id: String::new(),
+        sim_id: String::new(),
+        relay: String::new(),
+        accepted: false,
+        feature_engine_version: String::new(),
+        scorer_version: String::new(),
+        strategy_version: String::new(),
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("BundleSubmitted.hash")?,
+            2 => out.bundle_id = value.string("BundleSubmitted.bundle_id")?,
+            3 => out.sim_id = value.string("BundleSubmitted.sim_id")?,
+            4 => out.relay = value.string("BundleSubmitted.relay")?,
+            5 => out.accepted = value.bool("BundleSubmitted.accepted")?,
+            6 => {
+                out.feature_engine_version =
+                    value.string("BundleSubmitted.feature_engine_version")?
+            }
+            7 => out.scorer_version = value.string("BundleSubmitted.scorer_version")?,
+            8 => out.strategy_version = value.string("BundleSubmitted.strategy_version")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_tx_replaced(data: &[u8]) -> Result<TxReplaced, CodecError> {
+    let mut out = TxReplaced {
+        hash: [0; 32],
+        replaced_by: [0; 32],
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("TxReplaced.hash")?,
+            2 => out.replaced_by = value.fixed("TxReplaced.replaced_by")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_tx_dropped(data: &[u8]) -> Result<TxDropped, CodecError> {
+    let mut hash = [0; 32];
+    let mut reason = String::new();
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => hash = value.fixed("TxDropped.hash")?,
+            2 => reason = value.string("TxDropped.reason")?,
+            _ => {}
+        }
+    }
+    Ok(TxDropped::from(TxDroppedWire { hash, reason }))
+}
+
+fn decode_tx_confirmed(data: &[u8]) -> Result<TxConfirmed, CodecError> {
+    let mut out = TxConfirmed {
+        hash: [0; 32],
+        block_number: 0,
+        block_hash: [0; 32],
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("TxConfirmed.hash")?,
+            2 => out.block_number = value.uint("TxConfirmed.block_number")?,
+            3 => out.block_hash = value.fixed("TxConfirmed.block_hash")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_tx_reorged(data: &[u8]) -> Result<TxReorged, CodecError> {
+    let mut out = TxReorged {
+        hash: [0; 32],
+        old_block_hash: [0; 32],
+        new_block_hash: [0; 32],
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.hash = value.fixed("TxReorged.hash")?,
+            2 => out.old_block_hash = value.fixed("TxReorged.old_block_hash")?,
+            3 => out.new_block_hash = value.fixed("TxReorged.new_block_hash")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_user_op_seen(data: &[u8]) -> Result<UserOpSeen, CodecError> {
+    let mut out = UserOpSeen {
+        user_op_hash: [0; 32],
+        entry_point: [0; 20],
+        entry_point_version: EntryPointVersion::V06,
+        sender: [0; 20],
+        nonce: [0; 32],
+        chain_id: None,
+        factory: None,
+        paymaster: None,
+        call_gas_limit: 0,
+        verification_gas_limit: 0,
+        pre_verification_gas: 0,
+        max_fee_per_gas_wei: 0,
+        max_priority_fee_per_gas_wei: 0,
+        call_data_len: 0,
+        bundle_tx_hash: None,
+        call_data: Vec::new(),
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.user_op_hash = value.fixed("UserOpSeen.user_op_hash")?,
+            2 => out.entry_point = value.fixed("UserOpSeen.entry_point")?,
+            3 => {
+                out.entry_point_version =
+                    match value.uint::<u8>("UserOpSeen.entry_point_version")? {
+                        6 => EntryPointVersion::V06,
+                        7 => EntryPointVersion::V07,
+                        _ => {
+                            return Err(CodecError::InvalidValue {
+                                field: "UserOpSeen.entry_point_version",
+                            });
+                        }
+                    }
+            }
+            4 => out.sender = value.fixed("UserOpSeen.sender")?,
+            5 => out.nonce = value.fixed("UserOpSeen.nonce")?,
+            6 => out.chain_id = Some(value.uint("UserOpSeen.chain_id")?),
+            7 => out.factory = Some(value.fixed("UserOpSeen.factory")?),
+            8 => out.paymaster = Some(value.fixed("UserOpSeen.paymaster")?),
+            9 => out.call_gas_limit = value.uint("UserOpSeen.call_gas_limit")?,
+            10 => out.verification_gas_limit = value.uint("UserOpSeen.verification_gas_limit")?,
+            11 => out.pre_verification_gas = value.uint("UserOpSeen.pre_verification_gas")?,
+            12 => out.max_fee_per_gas_wei = value.uint("UserOpSeen.max_fee_per_gas_wei")?,
+            13 => {
+                out.max_priority_fee_per_gas_wei =
+                    value.uint("UserOpSeen.max_priority_fee_per_gas_wei")?
+            }
+            14 => out.call_data_len = value.uint("UserOpSeen.call_data_len")?,
+            15 => out.bundle_tx_hash = Some(value.fixed("UserOpSeen.bundle_tx_hash")?),
+            16 => out.call_data = value.bytes("UserOpSeen.call_data")?.to_vec(),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_chain_checkpoint(data: &[u8]) -> Result<ChainCheckpoint, CodecError> {
+    let mut out = ChainCheckpoint {
+        covered_seq_id: 0,
+        covered_digest: [0; 32],
+        key_id: String::new(),
+        signature: Vec::new(),
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.covered_seq_id = value.uint("ChainCheckpoint.covered_seq_id")?,
+            2 => out.covered_digest = value.fixed("ChainCheckpoint.covered_digest")?,
+            3 => out.key_id = value.string("ChainCheckpoint.key_id")?,
+            4 => out.signature = value.bytes("ChainCheckpoint.signature")?.to_vec(),
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+fn decode_hash_link(data: &[u8]) -> Result<HashLink, CodecError> {
+    let mut out = HashLink {
+        prev_digest: [0; 32],
+        digest: [0; 32],
+    };
+    let mut fields = FieldReader::new(data);
+    while let Some((field, value)) = fields.next_field()? {
+        match field {
+            1 => out.prev_digest = value.fixed("HashLink.prev_digest")?,
+            2 => out.digest = value.fixed("HashLink.digest")?,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+struct MessageWriter<'a>(&'a mut Vec<u8>);
+
+impl MessageWriter<'_> {
+    fn key(&mut self, field: u32, wire_type: u8) {
+        put_varint(self.0, (u128::from(field) << 3) | u128::from(wire_type));
+    }
+
+    fn uint(&mut self, field: u32, value: impl Into<u128>) {
+        self.key(field, WIRE_VARINT);
+        put_varint(self.0, value.into());
+    }
+
+    fn opt_uint<T: Into<u128>>(&mut self, field: u32, value: Option<T>) {
+        if let Some(value) = value {
+            self.uint(field, value);
+        }
+    }
+
+    fn int(&mut self, field: u32, value: i64) {
+        // Zigzag keeps small negative timestamps and offsets short.
+        self.uint(field, ((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn bytes(&mut self, field: u32, value: &[u8]) {
+        self.key(field, WIRE_LEN);
+        put_varint(self.0, value.len() as u128);
+        self.0.extend_from_slice(value);
+    }
+
+    fn opt_bytes<const N: usize>(&mut self, field: u32, value: Option<&[u8; N]>) {
+        if let Some(value) = value {
+            self.bytes(field, value);
+        }
+    }
+
+    fn string(&mut self, field: u32, value: &str) {
+        self.bytes(field, value.as_bytes());
+    }
+
+    fn message(&mut self, field: u32, encode: impl FnOnce(&mut MessageWriter<'_>)) {
+        let mut nested = Vec::new();
+        encode(&mut MessageWriter(&mut nested));
+        self.bytes(field, &nested);
+    }
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8]) -> Result<(u128, usize), CodecError> {
+    let mut value = 0_u128;
+    for (index, byte) in bytes.iter().take(MAX_VARINT_BYTES).enumerate() {
+        let chunk = u128::from(byte & 0x7f);
+        let shift = 7 * index as u32;
+        if shift >= 128 || (chunk << shift) >> shift != chunk {
+            return Err(CodecError::InvalidValue { field: "varint" });
+        }
+        value |= chunk << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value, index + 1));
+        }
+    }
+    if bytes.len() < MAX_VARINT_BYTES {
+        Err(CodecError::Truncated)
+    } else {
+        Err(CodecError::InvalidValue { field: "varint" })
+    }
+}
+
+enum FieldValue<'a> {
+    Varint(u128),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> FieldValue<'a> {
+    fn uint<T: TryFrom<u128>>(self, field: &'static str) -> Result<T, CodecError> {
+        match self {
+            Self::Varint(value) => {
+                T::try_from(value).map_err(|_| CodecError::InvalidValue { field })
+            }
+            Self::Bytes(_) => Err(CodecError::InvalidValue { field }),
+        }
+    }
+
+    fn int(self, field: &'static str) -> Result<i64, CodecError> {
+        let raw: u64 = self.uint(field)?;
+        Ok(((raw >> 1) as i64) ^ -((raw & 1) as i64))
+    }
+
+    fn bool(self, field: &'static str) -> Result<bool, CodecError> {
+        match self.uint::<u8>(field)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(CodecError::InvalidValue { field }),
+        }
+    }
+
+    fn bytes(self, field: &'static str) -> Result<&'a [u8], CodecError> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes),
+            Self::Varint(_) => Err(CodecError::InvalidValue { field }),
+        }
+    }
+
+    fn fixed<const N: usize>(self, field: &'static str) -> Result<[u8; N], CodecError> {
+        self.bytes(field)?
+            .try_into()
+            .map_err(|_| CodecError::InvalidValue { field })
+    }
+
+    fn string(self, field: &'static str) -> Result<String, CodecError> {
+        String::from_utf8(self.bytes(field)?.to_vec())
+            .map_err(|_| CodecError::InvalidValue { field })
+    }
+}
+
+struct FieldReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> FieldReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn next_field(&mut self) -> Result<Option<(u32, FieldValue<'a>)>, CodecError> {
+        if self.pos >= self.data.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let field =
+            u32::try_from(key >> 3).map_err(|_| CodecError::InvalidValue { field: "field key" })?;
+        let wire_type = (key & 0x7) as u8;
+        let value = match wire_type {
+            WIRE_VARINT => FieldValue::Varint(self.varint()?),
+            WIRE_LEN => {
+                let len = usize::try_from(self.varint()?).map_err(|_| CodecError::Truncated)?;
+                let end = self.pos.checked_add(len).ok_or(CodecError::Truncated)?;
+                let bytes = self.data.get(self.pos..end).ok_or(CodecError::Truncated)?;
+                self.pos = end;
+                FieldValue::Bytes(bytes)
+            }
+            _ => return Err(CodecError::UnsupportedWireType { field, wire_type }),
+        };
+        Ok(Some((field, value)))
+    }
+
+    fn varint(&mut self) -> Result<u128, CodecError> {
+        let (value, consumed) = read_varint(&self.data[self.pos..])?;
+        self.pos += consumed;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dropped(seq_id: u64) -> EventEnvelope {
+        EventEnvelope {
+            seq_id,
+            ingest_ts_unix_ms: -5,
+            ingest_ts_mono_ns: 9,
+            source_id: SourceId::new("codec"),
+            payload: EventPayload::TxDropped(TxDropped::new(
+                [seq_id as u8; 32],
+                DropReason::Duplicate,
+            )),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        }
+    }
+
+    #[test]
+    fn varint_round_trips_u128_extremes() {
+        for value in [0_u128, 1, 127, 128, u64::MAX as u128, u128::MAX] {
+            let mut out = Vec::new();
+            put_varint(&mut out, value);
+            assert_eq!(read_varint(&out), Ok((value, out.len())));
+        }
+        assert_eq!(read_varint(&[0x80, 0x80]), Err(CodecError::Truncated));
+    }
+
+    #[test]
+    fn chain_fields_round_trip_through_binary_frame() {
+        let event = EventEnvelope {
+            chain_id: Some(8453),
+            chain_seq_id: Some(17),
+            ..dropped(5)
+        };
+        let frame = encode_event(&event);
+        assert_eq!(decode_event(&frame), Ok((event, frame.len())));
+    }
+
+    #[test]
+    fn decoder_skips_unknown_fields_from_newer_writers() {
+        let mut frame = encode_event(&dropped(3));
+        let body_len = frame.len() - 2;
+        // Append an unknown varint field 99 and an unknown bytes field 100.
+        let mut extra = Vec::new();
+        MessageWriter(&mut extra).uint(99, 7_u8);
+        MessageWriter(&mut extra).bytes(100, b"future");
+        frame.extend_from_slice(&extra);
+        frame[1] = (body_len + extra.len()) as u8;
+
+        let (decoded, consumed) = decode_event(&frame).expect("decode with unknown fields");
+        assert_eq!(decoded, dropped(3));
+        assert_eq!(consumed, frame.len());
+    }
+
+    #[test]
+    fn rejects_unknown_payload_variant() {
+        let mut body = Vec::new();
+        let mut writer = MessageWriter(&mut body);
+        writer.uint(1, 1_u8);
+        writer.message(5, |writer| writer.bytes(99, b"future"));
+        let mut frame = vec![BINARY_SCHEMA_VERSION];
+        put_varint(&mut frame, body.len() as u128);
+        frame.extend_from_slice(&body);
+
+        assert_eq!(
+            decode_event(&frame).map(|(event, _)| event),
+            Err(CodecError::UnknownPayload(99))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_schema_version_and_truncated_frames() {
+        let mut frame = encode_event(&dropped(1));
+        assert_eq!(
+            decode_event(&frame[..frame.len() - 1]).map(|(event, _)| event),
+            Err(CodecError::Truncated)
+        );
+        frame[0] = 9;
+        assert_eq!(
+            decode_event(&frame).map(|(event, _)| event),
+            Err(CodecError::UnsupportedVersion(9))
+        );
+    }
+
+    #[test]
+    fn stream_decoder_detects_json_and_binary() {
+        let events = vec![dropped(1), dropped(2)];
+        for encoding in [EventEncoding::Json, EventEncoding::Binary] {
+            let mut out = Vec::new();
+            for event in &events {
+                encoding.encode_into(event, &mut out).expect("encode");
+            }
+            assert_eq!(EventEncoding::detect(&out), encoding);
+            assert_eq!(decode_event_stream(&out).expect("decode stream"), events);
+        }
+        assert!(decode_event_stream(b"").expect("empty stream").is_empty());
+    }
+
+    #[test]
+    fn stream_reader_decodes_records_incrementally_and_stops_on_truncation() {
+        let events = vec![dropped(1), dropped(2), dropped(3)];
+        for encoding in [EventEncoding::Json, EventEncoding::Binary] {
+            let mut out = b"\n".to_vec();
+            for event in &events {
+                encoding.encode_into(event, &mut out).expect("encode");
+            }
+            // A tiny buffer forces records to straddle refills.
+            let reader = EventStreamReader::new(std::io::BufReader::with_capacity(3, &out[..]));
+            assert_eq!(
+                reader.collect::<Result<Vec<_>, _>>().expect("decode"),
+                events
+            );
+
+            let mut reader = EventStreamReader::new(&out[..out.len() - 2]);
+            assert_eq!(reader.next(), Some(Ok(dropped(1))));
+            assert_eq!(reader.encoding(), Some(encoding));
+            assert_eq!(reader.next(), Some(Ok(dropped(2))));
+            assert!(matches!(reader.next(), Some(Err(_))));
+            assert_eq!(reader.next(), None);
+        }
+        assert_eq!(EventStreamReader::new(&b" \n"[..]).next(), None);
+    }
+}
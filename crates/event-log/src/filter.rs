@@ -0,0 +1,1097 @@
+//! Filter expressions over [`EventEnvelope`] streams.
+//!
+//! A filter combines `field op value` and `field in (value, ...)` predicates
+//! with `and`, `or`, `not` and parentheses, for example:
+//!
+//! ```text
+//! type in (TxDecoded, CandidateQueued) and chain_id = 8453 and score > 5000
+//! ```
+//!
+//! Expressions are parsed once into a typed matcher; literals are checked
+//! against the field they are compared with, so `score > high` or a short
+//! `sender` address fails at parse time instead of silently matching nothing.
+//! A predicate on a field the event does not carry is false, including `!=`.
+//!
+//! Besides envelopes, any read-model row implementing [`FilterSubject`] can be
+//! matched, so HTTP handlers filter projections with the same expressions.
+
+use crate::{EventEnvelope, EventPayload};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Error returned when a filter expression does not parse.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[error("invalid event filter at byte {position}: {message}")]
+pub struct FilterParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+/// Longest expression, in bytes, [`EventFilter::parse`] accepts.
+pub const MAX_FILTER_LEN: usize = 4_096;
+
+/// Deepest nesting of parentheses and `not` [`EventFilter::parse`] accepts.
+pub const MAX_FILTER_DEPTH: usize = 64;
+
+/// Compiled filter expression.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventFilter {
+    source: String,
+    expr: Expr,
+}
+
+/// Necessary conditions extracted from a filter's top-level `and` terms.
+///
+/// Every matching event satisfies each populated hint, so stores can use
+/// them to narrow a scan; events inside the hints still need [`EventFilter::matches`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FilterHints {
+    pub event_types: Option<BTreeSet<&'static str>>,
+    pub chain_ids: Option<BTreeSet<u64>>,
+    pub min_seq_id: Option<u64>,
+    pub max_seq_id: Option<u64>,
+    /// Inclusive `ingest_ts_unix_ms` bounds.
+    pub min_ts_unix_ms: Option<i64>,
+    pub max_ts_unix_ms: Option<i64>,
+}
+
+impl EventFilter {
+    /// Parses and compiles a filter expression.
+    pub fn parse(input: &str) -> Result<Self, FilterParseError> {
+        if input.len() > MAX_FILTER_LEN {
+            return Err(parse_error(
+                MAX_FILTER_LEN,
+                format!("expression longer than {MAX_FILTER_LEN} bytes"),
+            ));
+        }
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            index: 0,
+            end: input.len(),
+            depth: 0,
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(parse_error(token.position, "unexpected trailing input"));
+        }
+        Ok(Self {
+            source: input.trim().to_owned(),
+            expr,
+        })
+    }
+
+    /// Returns a filter matching events on `chain_id`.
+    pub fn chain(chain_id: u64) -> Self {
+        Self {
+            source: format!("chain_id = {chain_id}"),
+            expr: Expr::Compare {
+                field: FilterField::ChainId,
+                op: CompareOp::Eq,
+                value: Literal::Number(i128::from(chain_id)),
+            },
+        }
+    }
+
+    /// Returns a filter matching events ingested within an inclusive
+    /// unix-ms range; an open side is unbounded.
+    pub fn time_range(from_ts_unix_ms: Option<i64>, to_ts_unix_ms: Option<i64>) -> Self {
+        let bound = |op, ts: i64| Expr::Compare {
+            field: FilterField::Timestamp,
+            op,
+            value: Literal::Number(i128::from(ts)),
+        };
+        let mut terms = Vec::with_capacity(2);
+        let mut source = Vec::with_capacity(2);
+        if let Some(from) = from_ts_unix_ms {
+            terms.push(bound(CompareOp::Ge, from));
+            source.push(format!("ts >= {from}"));
+        }
+        if let Some(to) = to_ts_unix_ms {
+            terms.push(bound(CompareOp::Le, to));
+            source.push(format!("ts <= {to}"));
+        }
+        Self {
+            source: source.join(" and "),
+            expr: Expr::And(terms),
+        }
+    }
+
+    /// Returns a filter matching any of the named event types, compared
+    /// case-insensitively.
+    pub fn event_types<S: AsRef<str>>(names: &[S]) -> Result<Self, FilterParseError> {
+        let mut canonical = Vec::with_capacity(names.len());
+        for name in names {
+            let name = name.as_ref().trim();
+            match canonical_type(name) {
+                "" => return Err(parse_error(0, format!("unknown event type '{name}'"))),
+                known => canonical.push(known),
+            }
+        }
+        Ok(Self {
+            source: format!("type in ({})", canonical.join(", ")),
+            expr: Expr::In {
+                field: FilterField::Type,
+                values: canonical
+                    .into_iter()
+                    .map(|name| Literal::Text(name.to_owned()))
+                    .collect(),
+            },
+        })
+    }
+
+    /// Returns the expression text the filter was parsed from.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns a filter matching events that satisfy both `self` and `other`.
+    #[must_use]
+    pub fn and(self, other: EventFilter) -> Self {
+        let mut terms = Vec::with_capacity(2);
+        for expr in [self.expr, other.expr] {
+            match expr {
+                Expr::And(inner) => terms.extend(inner),
+                expr => terms.push(expr),
+            }
+        }
+        Self {
+            source: format!("({}) and ({})", self.source, other.source),
+            expr: Expr::And(terms),
+        }
+    }
+
+    /// Returns whether `subject` satisfies the filter.
+    pub fn matches<S: FilterSubject + ?Sized>(&self, subject: &S) -> bool {
+        self.expr.eval(subject)
+    }
+
+    /// Returns scan hints derived from the filter.
+    pub fn hints(&self) -> FilterHints {
+        let mut hints = FilterHints::default();
+        match &self.expr {
+            Expr::And(terms) => terms.iter().for_each(|term| hints.narrow(term)),
+            term => hints.narrow(term),
+        }
+        hints
+    }
+}
+
+impl FromStr for EventFilter {
+    type Err = FilterParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse(input)
+    }
+}
+
+impl Display for EventFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FilterHints {
+    fn narrow(&mut self, term: &Expr) {
+        match term {
+            Expr::Compare {
+                field: FilterField::Type,
+                op: CompareOp::Eq,
+                value: Literal::Text(name),
+            } => intersect(&mut self.event_types, [canonical_type(name)].into_iter()),
+            Expr::In {
+                field: FilterField::Type,
+                values,
+            } => intersect(
+                &mut self.event_types,
+                values.iter().map(|value| match value {
+                    Literal::Text(name) => canonical_type(name),
+                    _ => "",
+                }),
+            ),
+            Expr::Compare {
+                field: FilterField::ChainId,
+                op: CompareOp::Eq,
+                value: Literal::Number(chain_id),
+            } => {
+                if let Ok(chain_id) = u64::try_from(*chain_id) {
+                    intersect(&mut self.chain_ids, [chain_id].into_iter());
+                }
+            }
+            Expr::In {
+                field: FilterField::ChainId,
+                values,
+            } => {
+                let chain_ids = values
+                    .iter()
+                    .filter_map(|value| match value {
+                        Literal::Number(chain_id) => u64::try_from(*chain_id).ok(),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                if chain_ids.len() == values.len() {
+                    intersect(&mut self.chain_ids, chain_ids.into_iter());
+                }
+            }
+            Expr::Compare {
+                field: FilterField::SeqId,
+                op,
+                value: Literal::Number(seq_id),
+            } => narrow_bounds(&mut self.min_seq_id, &mut self.max_seq_id, *op, *seq_id),
+            Expr::Compare {
+                field: FilterField::Timestamp,
+                op,
+                value: Literal::Number(ts),
+            } => narrow_bounds(&mut self.min_ts_unix_ms, &mut self.max_ts_unix_ms, *op, *ts),
+            Expr::And(terms) => terms.iter().for_each(|term| self.narrow(term)),
+            _ => {}
+        }
+    }
+}
+
+/// Tightens inclusive `[min, max]` bounds with one `field op value` term.
+fn narrow_bounds<T: TryFrom<i128> + Ord + Copy>(
+    min: &mut Option<T>,
+    max: &mut Option<T>,
+    op: CompareOp,
+    value: i128,
+) {
+    let (lower, upper) = match op {
+        CompareOp::Eq => (Some(value), Some(value)),
+        CompareOp::Gt => (Some(value.saturating_add(1)), None),
+        CompareOp::Ge => (Some(value), None),
+        CompareOp::Lt => (None, Some(value.saturating_sub(1))),
+        CompareOp::Le => (None, Some(value)),
+        CompareOp::Ne => (None, None),
+    };
+    if let Some(lower) = lower.and_then(|lower| T::try_from(lower).ok()) {
+        *min = Some(min.map_or(lower, |current| current.max(lower)));
+    }
+    if let Some(upper) = upper.and_then(|upper| T::try_from(upper).ok()) {
+        *max = Some(max.map_or(upper, |current| current.min(upper)));
+    }
+}
+
+fn intersect<T: Ord + Copy>(slot: &mut Option<BTreeSet<T>>, values: impl Iterator<Item = T>) {
+    let values = values.collect::<BTreeSet<_>>();
+    *slot = Some(match slot.take() {
+        Some(current) => current.intersection(&values).copied().collect(),
+        None => values,
+    });
+}
+
+fn canonical_type(name: &str) -> &'static str {
+    EventPayload::TYPE_NAMES
+        .iter()
+        .find(|candidate| candidate.eq_ignore_ascii_case(name))
+        .copied()
+        .unwrap_or("")
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare {
+        field: FilterField,
+        op: CompareOp,
+        value: Literal,
+    },
+    In {
+        field: FilterField,
+        values: Vec<Literal>,
+    },
+}
+
+impl Expr {
+    fn eval<S: FilterSubject + ?Sized>(&self, subject: &S) -> bool {
+        match self {
+            Expr::And(terms) => terms.iter().all(|term| term.eval(subject)),
+            Expr::Or(terms) => terms.iter().any(|term| term.eval(subject)),
+            Expr::Not(term) => !term.eval(subject),
+            Expr::Compare { field, op, value } => subject
+                .filter_value(*field)
+                .is_some_and(|actual| actual.compare(*op, value)),
+            Expr::In { field, values } => subject.filter_value(*field).is_some_and(|actual| {
+                values
+                    .iter()
+                    .any(|value| actual.compare(CompareOp::Eq, value))
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Literal {
+    Number(i128),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FieldKind {
+    Number,
+    Text,
+    Bytes(usize),
+}
+
+/// Field a filter predicate reads, named as in expressions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FilterField {
+    Type,
+    SeqId,
+    ChainId,
+    ChainSeqId,
+    Source,
+    Timestamp,
+    Hash,
+    Sender,
+    To,
+    Nonce,
+    Score,
+    Value,
+    GasLimit,
+    BlockNumber,
+    TxType,
+    Strategy,
+    Protocol,
+    Category,
+    PeerId,
+    Reason,
+    Status,
+    Decision,
+}
+
+impl FilterField {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "type" => Self::Type,
+            "seq_id" => Self::SeqId,
+            "chain_id" => Self::ChainId,
+            "chain_seq_id" => Self::ChainSeqId,
+            "source" | "source_id" => Self::Source,
+            "ts" | "ingest_ts_unix_ms" => Self::Timestamp,
+            "hash" => Self::Hash,
+            "sender" => Self::Sender,
+            "to" => Self::To,
+            "nonce" => Self::Nonce,
+            "score" => Self::Score,
+            "value" | "value_wei" => Self::Value,
+            "gas_limit" => Self::GasLimit,
+            "block_number" => Self::BlockNumber,
+            "tx_type" => Self::TxType,
+            "strategy" => Self::Strategy,
+            "protocol" => Self::Protocol,
+            "category" => Self::Category,
+            "peer" | "peer_id" => Self::PeerId,
+            "reason" => Self::Reason,
+            "status" => Self::Status,
+            "decision" => Self::Decision,
+            _ => return None,
+        })
+    }
+
+    fn kind(self) -> FieldKind {
+        match self {
+            Self::Type
+            | Self::Source
+            | Self::Strategy
+            | Self::Protocol
+            | Self::Category
+            | Self::PeerId
+            | Self::Reason
+            | Self::Status
+            | Self::Decision => FieldKind::Text,
+            Self::Hash => FieldKind::Bytes(32),
+            Self::Sender | Self::To => FieldKind::Bytes(20),
+            Self::SeqId
+            | Self::ChainId
+            | Self::ChainSeqId
+            | Self::Timestamp
+            | Self::Nonce
+            | Self::Score
+            | Self::Value
+            | Self::GasLimit
+            | Self::BlockNumber
+            | Self::TxType => FieldKind::Number,
+        }
+    }
+}
+
+/// Values a subject exposes to a filter.
+///
+/// Implementations return `None` for fields they do not carry, which makes
+/// every predicate on that field false.
+pub trait FilterSubject {
+    fn filter_value(&self, field: FilterField) -> Option<FilterValue<'_>>;
+}
+
+impl FilterSubject for EventEnvelope {
+    fn filter_value(&self, field: FilterField) -> Option<FilterValue<'_>> {
+        let event = self;
+        let payload = &event.payload;
+        match field {
+            FilterField::Type => Some(FilterValue::Text(payload.type_name())),
+            FilterField::SeqId => Some(FilterValue::Number(i128::from(event.seq_id))),
+            FilterField::ChainId => event
+                .chain_id
+                .or(match payload {
+                    EventPayload::TxDecoded(e) => e.chain_id,
+                    EventPayload::CandidateQueued(e) => e.chain_id,
+                    EventPayload::UserOpSeen(e) => e.chain_id,
+                    _ => None,
+                })
+                .map(|chain_id| FilterValue::Number(i128::from(chain_id))),
+            FilterField::ChainSeqId => event
+                .chain_seq_id
+                .map(|chain_seq_id| FilterValue::Number(i128::from(chain_seq_id))),
+            FilterField::Source => Some(FilterValue::Text(event.source_id.as_str())),
+            FilterField::Timestamp => {
+                Some(FilterValue::Number(i128::from(event.ingest_ts_unix_ms)))
+            }
+            FilterField::Hash => Some(FilterValue::Hash(payload.primary_hash())),
+            FilterField::Sender => match payload {
+                EventPayload::TxDecoded(e) => Some(e.sender),
+                EventPayload::TxReady(e) => Some(e.sender),
+                EventPayload::TxBlocked(e) => Some(e.sender),
+                EventPayload::UserOpSeen(e) => Some(e.sender),
+                _ => None,
+            }
+            .map(FilterValue::Address),
+            FilterField::To => match payload {
+                EventPayload::TxDecoded(e) => e.to.map(FilterValue::Address),
+                _ => None,
+            },
+            FilterField::Nonce => match payload {
+                EventPayload::TxDecoded(e) => Some(e.nonce),
+                EventPayload::TxReady(e) => Some(e.nonce),
+                EventPayload::TxBlocked(e) => Some(e.nonce),
+                _ => None,
+            }
+            .map(|nonce| FilterValue::Number(i128::from(nonce))),
+            FilterField::Score => match payload {
+                EventPayload::CandidateQueued(e) => Some(e.score),
+                EventPayload::OppDetected(e) => Some(e.score),
+                _ => None,
+            }
+            .map(|score| FilterValue::Number(i128::from(score))),
+            FilterField::Value => match payload {
+                EventPayload::TxDecoded(e) => e
+                    .value_wei
+                    .map(|value| FilterValue::Number(i128::try_from(value).unwrap_or(i128::MAX))),
+                _ => None,
+            },
+            FilterField::GasLimit => match payload {
+                EventPayload::TxDecoded(e) => e
+                    .gas_limit
+                    .map(|gas_limit| FilterValue::Number(i128::from(gas_limit))),
+                _ => None,
+            },
+            FilterField::BlockNumber => match payload {
+                EventPayload::SimDispatched(e) => Some(e.block_number),
+                EventPayload::AssemblyDecisionApplied(e) => Some(e.block_number),
+                EventPayload::TxConfirmedProvisional(e) | EventPayload::TxConfirmedFinal(e) => {
+                    Some(e.block_number)
+                }
+                _ => None,
+            }
+            .map(|block_number| FilterValue::Number(i128::from(block_number))),
+            FilterField::TxType => match payload {
+                EventPayload::TxDecoded(e) => Some(FilterValue::Number(i128::from(e.tx_type))),
+                _ => None,
+            },
+            FilterField::Strategy => match payload {
+                EventPayload::CandidateQueued(e) => Some(e.strategy.as_str()),
+                EventPayload::OppDetected(e) => Some(e.strategy.as_str()),
+                _ => None,
+            }
+            .map(FilterValue::Text),
+            FilterField::Protocol => match payload {
+                EventPayload::CandidateQueued(e) => Some(e.protocol.as_str()),
+                EventPayload::OppDetected(e) => Some(e.protocol.as_str()),
+                _ => None,
+            }
+            .map(FilterValue::Text),
+            FilterField::Category => match payload {
+                EventPayload::CandidateQueued(e) => Some(e.category.as_str()),
+                EventPayload::OppDetected(e) => Some(e.category.as_str()),
+                _ => None,
+            }
+            .map(FilterValue::Text),
+            FilterField::PeerId => match payload {
+                EventPayload::TxSeen(e) => Some(FilterValue::Text(e.peer_id.as_str())),
+                _ => None,
+            },
+            FilterField::Reason => match payload {
+                EventPayload::TxDropped(e) => Some(FilterValue::Text(e.reason.as_str())),
+                _ => None,
+            },
+            FilterField::Status => match payload {
+                EventPayload::SimCompleted(e) => Some(FilterValue::Text(e.status.as_str())),
+                _ => None,
+            },
+            FilterField::Decision => match payload {
+                EventPayload::AssemblyDecisionApplied(e) => {
+                    Some(FilterValue::Text(e.decision.as_str()))
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Field value handed to a filter; numbers, text and fixed-size byte strings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FilterValue<'a> {
+    Number(i128),
+    Text(&'a str),
+    Hash([u8; 32]),
+    Address([u8; 20]),
+}
+
+impl FilterValue<'_> {
+    fn compare(&self, op: CompareOp, literal: &Literal) -> bool {
+        let ordering = match (self, literal) {
+            (FilterValue::Number(actual), Literal::Number(expected)) => actual.cmp(expected),
+            (FilterValue::Text(actual), Literal::Text(expected)) => {
+                return match op {
+                    CompareOp::Eq => actual.eq_ignore_ascii_case(expected),
+                    CompareOp::Ne => !actual.eq_ignore_ascii_case(expected),
+                    _ => false,
+                };
+            }
+            (FilterValue::Hash(actual), Literal::Bytes(expected)) => {
+                actual.as_slice().cmp(expected.as_slice())
+            }
+            (FilterValue::Address(actual), Literal::Bytes(expected)) => {
+                actual.as_slice().cmp(expected.as_slice())
+            }
+            _ => return false,
+        };
+        match op {
+            CompareOp::Eq => ordering.is_eq(),
+            CompareOp::Ne => ordering.is_ne(),
+            CompareOp::Lt => ordering.is_lt(),
+            CompareOp::Le => ordering.is_le(),
+            CompareOp::Gt => ordering.is_gt(),
+            CompareOp::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Number(String),
+    Hex(String),
+    Quoted(String),
+    Op(CompareOp),
+    LParen,
+    RParen,
+    Comma,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn parse_error(position: usize, message: impl Into<String>) -> FilterParseError {
+    FilterParseError {
+        position,
+        message: message.into(),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, FilterParseError> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        let byte = bytes[index];
+        let kind = match byte {
+            b if b.is_ascii_whitespace() => {
+                index += 1;
+                continue;
+            }
+            b'(' => {
+                index += 1;
+                TokenKind::LParen
+            }
+            b')' => {
+                index += 1;
+                TokenKind::RParen
+            }
+            b',' => {
+                index += 1;
+                TokenKind::Comma
+            }
+            b'=' => {
+                index += if bytes.get(index + 1) == Some(&b'=') {
+                    2
+                } else {
+                    1
+                };
+                TokenKind::Op(CompareOp::Eq)
+            }
+            b'!' if bytes.get(index + 1) == Some(&b'=') => {
+                index += 2;
+                TokenKind::Op(CompareOp::Ne)
+            }
+            b'<' | b'>' => {
+                let or_equal = bytes.get(index + 1) == Some(&b'=');
+                index += if or_equal { 2 } else { 1 };
+                TokenKind::Op(match (byte, or_equal) {
+                    (b'<', false) => CompareOp::Lt,
+                    (b'<', true) => CompareOp::Le,
+                    (_, false) => CompareOp::Gt,
+                    (_, true) => CompareOp::Ge,
+                })
+            }
+            b'"' | b'\'' => {
+                let close = input[index + 1..]
+                    .find(byte as char)
+                    .ok_or_else(|| parse_error(start, "unterminated string"))?;
+                let text = input[index + 1..index + 1 + close].to_owned();
+                index += close + 2;
+                TokenKind::Quoted(text)
+            }
+            b'0' if matches!(bytes.get(index + 1), Some(b'x' | b'X')) => {
+                index += 2;
+                while index < bytes.len() && bytes[index].is_ascii_hexdigit() {
+                    index += 1;
+                }
+                TokenKind::Hex(input[start + 2..index].to_owned())
+            }
+            b if b.is_ascii_digit() || b == b'-' => {
+                index += 1;
+                while index < bytes.len() && (bytes[index].is_ascii_digit() || bytes[index] == b'_')
+                {
+                    index += 1;
+                }
+                TokenKind::Number(input[start..index].replace('_', ""))
+            }
+            b if b.is_ascii_alphabetic() || b == b'_' => {
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric()
+                        || matches!(bytes[index], b'_' | b'.' | b'-' | b':'))
+                {
+                    index += 1;
+                }
+                TokenKind::Word(input[start..index].to_owned())
+            }
+            _ => {
+                let found = input[index..].chars().next().unwrap_or_default();
+                return Err(parse_error(
+                    start,
+                    format!("unexpected character '{found}'"),
+                ));
+            }
+        };
+        tokens.push(Token {
+            kind,
+            position: start,
+        });
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn position(&self) -> usize {
+        self.peek().map_or(self.end, |token| token.position)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token, FilterParseError> {
+        let token = self
+            .tokens
+            .get(self.index)
+            .cloned()
+            .ok_or_else(|| parse_error(self.end, format!("expected {expected}")))?;
+        self.index += 1;
+        Ok(token)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matched = matches!(
+            self.peek(),
+            Some(Token { kind: TokenKind::Word(word), .. }) if word.eq_ignore_ascii_case(keyword)
+        );
+        if matched {
+            self.index += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> Result<(), FilterParseError> {
+        let position = self.position();
+        if self.next(expected)?.kind == kind {
+            Ok(())
+        } else {
+            Err(parse_error(position, format!("expected {expected}")))
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, FilterParseError> {
+        let mut terms = vec![self.parse_and()?];
+        while self.eat_keyword("or") {
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::Or(terms)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, FilterParseError> {
+        let mut terms = vec![self.parse_unary()?];
+        while self.eat_keyword("and") {
+            terms.push(self.parse_unary()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::And(terms)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, FilterParseError> {
+        let position = self.position();
+        if self.eat_keyword("not") {
+            let term = self.nested(position, Self::parse_unary)?;
+            return Ok(Expr::Not(Box::new(term)));
+        }
+        if self.peek().map(|token| &token.kind) == Some(&TokenKind::LParen) {
+            self.index += 1;
+            let expr = self.nested(position, Self::parse_or)?;
+            self.expect(TokenKind::RParen, "')'")?;
+            return Ok(expr);
+        }
+        self.parse_predicate()
+    }
+
+    /// Runs `parse` one nesting level deeper, bounding recursion on hostile input.
+    fn nested(
+        &mut self,
+        position: usize,
+        parse: fn(&mut Self) -> Result<Expr, FilterParseError>,
+    ) -> Result<Expr, FilterParseError> {
+        if self.depth == MAX_FILTER_DEPTH {
+            return Err(parse_error(
+                position,
+                format!("expression nested deeper than {MAX_FILTER_DEPTH} levels"),
+            ));
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn parse_predicate(&mut self) -> Result<Expr, FilterParseError> {
+        let position = self.position();
+        let Token {
+            kind: TokenKind::Word(name),
+            ..
+        } = self.next("a field name")?
+        else {
+            return Err(parse_error(position, "expected a field name"));
+        };
+        let field = FilterField::from_name(&name)
+            .ok_or_else(|| parse_error(position, format!("unknown field '{name}'")))?;
+
+        if self.eat_keyword("in") {
+            self.expect(TokenKind::LParen, "'(' after 'in'")?;
+            let mut values = vec![self.parse_literal(field)?];
+            loop {
+                let position = self.position();
+                match self.next("',' or ')'")?.kind {
+                    TokenKind::Comma => values.push(self.parse_literal(field)?),
+                    TokenKind::RParen => break,
+                    _ => return Err(parse_error(position, "expected ',' or ')'")),
+                }
+            }
+            return Ok(Expr::In { field, values });
+        }
+
+        let position = self.position();
+        let TokenKind::Op(op) = self.next("a comparison operator")?.kind else {
+            return Err(parse_error(position, "expected a comparison operator"));
+        };
+        if field.kind() != FieldKind::Number && !matches!(op, CompareOp::Eq | CompareOp::Ne) {
+            return Err(parse_error(
+                position,
+                format!("field '{name}' only supports '=' and '!='"),
+            ));
+        }
+        let value = self.parse_literal(field)?;
+        Ok(Expr::Compare { field, op, value })
+    }
+
+    fn parse_literal(&mut self, field: FilterField) -> Result<Literal, FilterParseError> {
+        let position = self.position();
+        let token = self.next("a value")?;
+        let invalid = |expected: &str| parse_error(position, format!("expected {expected}"));
+        match (field.kind(), token.kind) {
+            (FieldKind::Number, TokenKind::Number(digits)) => digits
+                .parse::<i128>()
+                .map(Literal::Number)
+                .map_err(|_| invalid("a number")),
+            (FieldKind::Number, TokenKind::Hex(digits)) => u128::from_str_radix(&digits, 16)
+                .ok()
+                .and_then(|value| i128::try_from(value).ok())
+                .map(Literal::Number)
+                .ok_or_else(|| invalid("a number")),
+            (FieldKind::Number, _) => Err(invalid("a number")),
+            (FieldKind::Bytes(len), TokenKind::Hex(digits)) => match hex::decode(&digits) {
+                Ok(bytes) if bytes.len() == len => Ok(Literal::Bytes(bytes)),
+                _ => Err(invalid(&format!("a {len}-byte 0x-prefixed hex value"))),
+            },
+            (FieldKind::Bytes(len), _) => {
+                Err(invalid(&format!("a {len}-byte 0x-prefixed hex value")))
+            }
+            (FieldKind::Text, TokenKind::Word(text) | TokenKind::Quoted(text))
+            | (FieldKind::Text, TokenKind::Number(text)) => {
+                if field == FilterField::Type && canonical_type(&text).is_empty() {
+                    return Err(parse_error(
+                        position,
+                        format!("unknown event type '{text}'"),
+                    ));
+                }
+                Ok(Literal::Text(text))
+            }
+            (FieldKind::Text, _) => Err(invalid("a text value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CandidateQueued, TxDecoded, TxSeen};
+    use common::SourceId;
+
+    fn envelope(seq_id: u64, payload: EventPayload) -> EventEnvelope {
+        EventEnvelope {
+            seq_id,
+            ingest_ts_unix_ms: 1_700_000_000_000,
+            ingest_ts_mono_ns: seq_id,
+            source_id: SourceId::new("rpc-base"),
+            payload,
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        }
+    }
+
+    fn decoded(seq_id: u64, sender: u8, chain_id: u64) -> EventEnvelope {
+        envelope(
+            seq_id,
+            EventPayload::TxDecoded(TxDecoded {
+                hash: [seq_id as u8; 32],
+                tx_type: 2,
+                sender: [sender; 20],
+                nonce: seq_id,
+                chain_id: Some(chain_id),
+                to: None,
+                value_wei: Some(1_000),
+                gas_limit: Some(21_000),
+                gas_price_wei: None,
+                max_fee_per_gas_wei: None,
+                max_priority_fee_per_gas_wei: None,
+                max_fee_per_blob_gas_wei: None,
+                calldata_len: None,
+            }),
+        )
+    }
+
+    fn queued(seq_id: u64, score: u32) -> EventEnvelope {
+        envelope(
+            seq_id,
+            EventPayload::CandidateQueued(CandidateQueued {
+                candidate_id: format!("cand-{seq_id}"),
+                tx_hash: [seq_id as u8; 32],
+                member_tx_hashes: Vec::new(),
+                chain_id: Some(8453),
+                strategy: "SandwichCandidate".to_owned(),
+                score,
+                protocol: "uniswap-v2".to_owned(),
+                category: "swap".to_owned(),
+                feature_engine_version: String::new(),
+                scorer_version: String::new(),
+                strategy_version: String::new(),
+                reasons: Vec::new(),
+                detected_unix_ms: 0,
+            }),
+        )
+    }
+
+    #[test]
+    fn compiled_filter_matches_type_sender_chain_and_score() {
+        let sender = format!("0x{}", "ab".repeat(20));
+        let filter = EventFilter::parse(&format!(
+            "type in (TxDecoded, CandidateQueued) and chain_id = 8453 \
+             and (sender = {sender} or score > 5000)"
+        ))
+        .expect("parse filter");
+
+        assert!(filter.matches(&decoded(1, 0xab, 8453)));
+        assert!(!filter.matches(&decoded(2, 0xab, 1)));
+        assert!(!filter.matches(&decoded(3, 0xcd, 8453)));
+        assert!(filter.matches(&queued(4, 9_000)));
+        assert!(!filter.matches(&queued(5, 10)));
+        assert!(!filter.matches(&envelope(
+            6,
+            EventPayload::TxSeen(TxSeen {
+                hash: [6; 32],
+                peer_id: "peer".to_owned(),
+                seen_at_unix_ms: 0,
+                seen_at_mono_ns: 0,
+            }),
+        )));
+    }
+
+    #[test]
+    fn missing_fields_never_match_and_not_inverts() {
+        let filter = EventFilter::parse("score != 1").expect("parse");
+        assert!(!filter.matches(&decoded(1, 1, 1)));
+        let filter = EventFilter::parse("not type = txdecoded").expect("parse");
+        assert!(!filter.matches(&decoded(1, 1, 1)));
+        assert!(filter.matches(&queued(2, 1)));
+    }
+
+    #[test]
+    fn hints_capture_top_level_type_chain_and_seq_bounds() {
+        let filter = EventFilter::parse(
+            "type in (TxDecoded, TxSeen) and type = TXDECODED and chain_id = 8453 \
+             and seq_id > 10 and seq_id <= 20",
+        )
+        .expect("parse");
+        let hints = filter.hints();
+        assert_eq!(hints.event_types, Some(BTreeSet::from(["TxDecoded"])));
+        assert_eq!(hints.chain_ids, Some(BTreeSet::from([8453])));
+        assert_eq!((hints.min_seq_id, hints.max_seq_id), (Some(11), Some(20)));
+
+        let combined = EventFilter::parse("type = TxDecoded or type = TxSeen")
+            .expect("parse")
+            .and(EventFilter::parse("chain_id = 1 and seq_id >= 4").expect("parse"));
+        assert_eq!(
+            combined.as_str(),
+            "(type = TxDecoded or type = TxSeen) and (chain_id = 1 and seq_id >= 4)"
+        );
+        assert_eq!(combined.hints().chain_ids, Some(BTreeSet::from([1])));
+        assert_eq!(combined.hints().min_seq_id, Some(4));
+
+        let hints = EventFilter::parse("type = TxSeen or chain_id = 1")
+            .expect("parse")
+            .hints();
+        assert_eq!(hints, FilterHints::default());
+    }
+
+    #[test]
+    fn time_range_filter_matches_inclusive_bounds_and_narrows_hints() {
+        let at = |ts: i64| EventEnvelope {
+            ingest_ts_unix_ms: ts,
+            ..decoded(1, 1, 1)
+        };
+        let filter = EventFilter::time_range(Some(100), Some(200));
+        assert_eq!(filter.as_str(), "ts >= 100 and ts <= 200");
+        assert!(filter.matches(&at(100)));
+        assert!(filter.matches(&at(200)));
+        assert!(!filter.matches(&at(99)));
+        assert!(!filter.matches(&at(201)));
+        assert!(EventFilter::time_range(None, None).matches(&at(1)));
+
+        let hints = EventFilter::parse("chain_id = 1 and ts > 150")
+            .expect("parse")
+            .and(filter)
+            .hints();
+        assert_eq!(
+            (hints.min_ts_unix_ms, hints.max_ts_unix_ms),
+            (Some(151), Some(200))
+        );
+        assert_eq!(hints.chain_ids, Some(BTreeSet::from([1])));
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_offending_token() {
+        let cases = [
+            ("score > high", 8, "expected a number"),
+            ("sender = 0x1234", 9, "20-byte"),
+            ("type = TxMined", 7, "unknown event type"),
+            ("colour = red", 0, "unknown field"),
+            ("strategy > x", 9, "only supports"),
+            ("type in (TxSeen", 15, "expected ',' or ')'"),
+            ("score = 1 score = 2", 10, "trailing input"),
+        ];
+        for (input, position, message) in cases {
+            let err = EventFilter::parse(input).expect_err(input);
+            assert_eq!(err.position, position, "{input}: {err}");
+            assert!(err.message.contains(message), "{input}: {err}");
+        }
+    }
+
+    #[test]
+    fn nesting_deeper_than_the_limit_is_rejected() {
+        let nested = |depth: usize| format!("{}score > 1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(EventFilter::parse(&nested(MAX_FILTER_DEPTH)).is_ok());
+        let err = EventFilter::parse(&nested(MAX_FILTER_DEPTH + 1)).expect_err("too deep");
+        assert_eq!(err.position, MAX_FILTER_DEPTH);
+        assert!(err.message.contains("nested deeper"), "{err}");
+
+        let err = EventFilter::parse(&"not ".repeat(1_000)).expect_err("deep not chain");
+        assert!(err.message.contains("nested deeper"), "{err}");
+    }
+
+    #[test]
+    fn expressions_longer_than_the_limit_are_rejected_before_tokenizing() {
+        let long = format!("source = {}", "a".repeat(MAX_FILTER_LEN));
+        let err = EventFilter::parse(&long).expect_err("too long");
+        assert_eq!(err.position, MAX_FILTER_LEN);
+        assert!(err.message.contains("longer than"), "{err}");
+    }
+
+    #[test]
+    fn typed_constructors_match_their_parsed_equivalents() {
+        let event = decoded(1, 1, 8453);
+        let chain = EventFilter::chain(8453);
+        assert_eq!(chain, EventFilter::parse("chain_id = 8453").expect("parse"));
+        assert!(chain.matches(&event));
+        assert!(!EventFilter::chain(1).matches(&event));
+
+        let types = EventFilter::event_types(&["txdecoded", " TxSeen "]).expect("types");
+        assert_eq!(types.as_str(), "type in (TxDecoded, TxSeen)");
+        assert!(types.matches(&event));
+        assert!(!types.matches(&queued(2, 1)));
+        assert_eq!(
+            types.hints().event_types,
+            Some(BTreeSet::from(["TxDecoded", "TxSeen"]))
+        );
+        assert!(EventFilter::event_types(&["TxMined"]).is_err());
+    }
+}
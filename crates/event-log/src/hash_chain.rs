@@ -0,0 +1,739 @@
+//! Tamper-evident hash chaining of the event stream.
+//!
+//! Each linked event stores the digest of its predecessor and its own digest:
+//!
+//! ```text
+//! digest_n := sha256("mempulse.event-chain.v1" || digest_{n-1} || frame_n)
+//! ```
+//!
+//! where `frame_n` is the binary codec frame of the event without its link, so
+//! the digest does not depend on whether the event was persisted as JSON or
+//! binary. A chain starts from [`GENESIS_DIGEST`]. Editing, dropping or
+//! reordering any event breaks every later link, and periodic
+//! [`ChainCheckpoint`] events sign the current head with an Ed25519 key, so a
+//! verifier holding only the public key can tell a rewritten chain from the
+//! original.
+
+use crate::codec::encode_unlinked_event_into;
+use crate::{ChainCheckpoint, EventEnvelope, EventPayload};
+use ring::signature::{ED25519, Ed25519KeyPair, KeyPair, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::Arc;
+
+/// SHA-256 digest of one link in the event hash chain.
+pub type ChainDigest = [u8; 32];
+
+/// Predecessor digest of the first event in a chain.
+pub const GENESIS_DIGEST: ChainDigest = [0; 32];
+
+const CHAIN_DOMAIN: &[u8] = b"mempulse.event-chain.v1";
+const CHECKPOINT_DOMAIN: &[u8] = b"mempulse.chain-checkpoint.v1";
+
+/// Position of an event in the hash chain.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct HashLink {
+    pub prev_digest: ChainDigest,
+    pub digest: ChainDigest,
+}
+
+/// Computes the chain digest of `event` given its predecessor's digest. Any
+/// link already present on `event` is ignored.
+pub fn event_digest(prev_digest: &ChainDigest, event: &EventEnvelope) -> ChainDigest {
+    let mut frame = Vec::with_capacity(256);
+    encode_unlinked_event_into(event, &mut frame);
+    let mut hasher = Sha256::new();
+    hasher.update(CHAIN_DOMAIN);
+    hasher.update(prev_digest);
+    hasher.update(&frame);
+    hasher.finalize().into()
+}
+
+/// Ed25519 key pair used to sign chain checkpoints.
+#[derive(Clone)]
+pub struct CheckpointSigner {
+    key_id: String,
+    key_pair: Arc<Ed25519KeyPair>,
+}
+
+/// Rejected checkpoint signing key material.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[error("invalid ed25519 checkpoint key: {0}")]
+pub struct CheckpointKeyError(String);
+
+impl CheckpointSigner {
+    /// Creates a signer from a 32-byte Ed25519 seed; `key_id` is recorded on
+    /// every checkpoint so keys can be rotated.
+    pub fn from_seed(key_id: impl Into<String>, seed: &[u8]) -> Result<Self, CheckpointKeyError> {
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(seed)
+            .map_err(|err| CheckpointKeyError(err.to_string()))?;
+        Ok(Self {
+            key_id: key_id.into(),
+            key_pair: Arc::new(key_pair),
+        })
+    }
+
+    /// Creates a signer from a PKCS#8 v2 encoded Ed25519 key pair.
+    pub fn from_pkcs8(key_id: impl Into<String>, pkcs8: &[u8]) -> Result<Self, CheckpointKeyError> {
+        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8)
+            .map_err(|err| CheckpointKeyError(err.to_string()))?;
+        Ok(Self {
+            key_id: key_id.into(),
+            key_pair: Arc::new(key_pair),
+        })
+    }
+
+    /// Returns the identifier recorded on checkpoints signed by this key.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Returns the public half of this key, which is all a verifier needs.
+    pub fn verifier(&self) -> CheckpointVerifier {
+        CheckpointVerifier::new(
+            self.key_id.clone(),
+            self.key_pair.public_key().as_ref().to_vec(),
+        )
+    }
+
+    /// Signs a chain head.
+    pub fn sign(&self, covered_seq_id: u64, covered_digest: &ChainDigest) -> Vec<u8> {
+        let message = checkpoint_message(&self.key_id, covered_seq_id, covered_digest);
+        self.key_pair.sign(&message).as_ref().to_vec()
+    }
+}
+
+impl PartialEq for CheckpointSigner {
+    fn eq(&self, other: &Self) -> bool {
+        self.key_id == other.key_id
+            && self.key_pair.public_key().as_ref() == other.key_pair.public_key().as_ref()
+    }
+}
+
+impl Eq for CheckpointSigner {}
+
+impl fmt::Debug for CheckpointSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckpointSigner")
+            .field("key_id", &self.key_id)
+            .field("key_pair", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Ed25519 public key used to verify chain checkpoints.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckpointVerifier {
+    key_id: String,
+    public_key: Vec<u8>,
+    checkpoint_interval: u64,
+}
+
+impl CheckpointVerifier {
+    /// Creates a verifier for checkpoints recorded under `key_id`, expecting
+    /// the default [`HashChainConfig`] checkpoint interval.
+    pub fn new(key_id: impl Into<String>, public_key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key_id: key_id.into(),
+            public_key: public_key.into(),
+            checkpoint_interval: HashChainConfig::default().checkpoint_interval,
+        }
+    }
+
+    /// Sets the writer's checkpoint interval, the most linked events allowed
+    /// without a verified checkpoint covering them.
+    pub fn with_checkpoint_interval(mut self, checkpoint_interval: u64) -> Self {
+        self.checkpoint_interval = checkpoint_interval;
+        self
+    }
+
+    /// Returns the checkpoint interval this verifier enforces.
+    pub fn checkpoint_interval(&self) -> u64 {
+        self.checkpoint_interval
+    }
+
+    /// Returns the identifier of checkpoints this key can verify.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Returns the raw 32-byte Ed25519 public key.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Checks a checkpoint's signature. Checkpoints signed under a different
+    /// key id never verify.
+    pub fn verify(&self, checkpoint: &ChainCheckpoint) -> bool {
+        if checkpoint.key_id != self.key_id {
+            return false;
+        }
+        let message = checkpoint_message(
+            &self.key_id,
+            checkpoint.covered_seq_id,
+            &checkpoint.covered_digest,
+        );
+        UnparsedPublicKey::new(&ED25519, &self.public_key)
+            .verify(&message, &checkpoint.signature)
+            .is_ok()
+    }
+}
+
+fn checkpoint_message(key_id: &str, covered_seq_id: u64, covered_digest: &ChainDigest) -> Vec<u8> {
+    let mut message =
+        Vec::with_capacity(CHECKPOINT_DOMAIN.len() + key_id.len() + 8 + covered_digest.len());
+    message.extend_from_slice(CHECKPOINT_DOMAIN);
+    message.extend_from_slice(key_id.as_bytes());
+    message.extend_from_slice(&covered_seq_id.to_be_bytes());
+    message.extend_from_slice(covered_digest);
+    message
+}
+
+/// Hash chain settings for the storage writer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HashChainConfig {
+    /// Linked events between signed checkpoints; `0` disables checkpoints.
+    pub checkpoint_interval: u64,
+    /// Key used to sign checkpoints; checkpoints are skipped without one.
+    pub signer: Option<CheckpointSigner>,
+}
+
+impl Default for HashChainConfig {
+    fn default() -> Self {
+        Self {
+            checkpoint_interval: 1_024,
+            signer: None,
+        }
+    }
+}
+
+/// Persistable position of an [`EventHashChain`], so a restarted writer keeps
+/// extending the same chain.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct HashChainHead {
+    pub digest: ChainDigest,
+    /// Sequence id of the event carrying `digest`; `None` before the first
+    /// link.
+    pub seq_id: Option<u64>,
+    #[serde(default)]
+    pub linked_since_checkpoint: u64,
+}
+
+impl HashChainHead {
+    /// Moves the head onto a linked event persisted after it; unlinked and
+    /// older events are ignored.
+    pub fn advance(&mut self, event: &EventEnvelope) {
+        let Some(link) = event.hash_link else {
+            return;
+        };
+        if self.seq_id.is_some_and(|seq_id| event.seq_id <= seq_id) {
+            return;
+        }
+        self.digest = link.digest;
+        self.seq_id = Some(event.seq_id);
+        self.linked_since_checkpoint = match event.payload {
+            EventPayload::ChainCheckpoint(_) => 0,
+            _ => self.linked_since_checkpoint.saturating_add(1),
+        };
+    }
+}
+
+/// Running head of an event hash chain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventHashChain {
+    config: HashChainConfig,
+    head: ChainDigest,
+    head_seq_id: Option<u64>,
+    linked_since_checkpoint: u64,
+}
+
+impl EventHashChain {
+    /// Starts a new chain from [`GENESIS_DIGEST`].
+    pub fn new(config: HashChainConfig) -> Self {
+        Self {
+            config,
+            head: GENESIS_DIGEST,
+            head_seq_id: None,
+            linked_since_checkpoint: 0,
+        }
+    }
+
+    /// Continues an existing chain from a persisted head, or starts a new one
+    /// when there is none.
+    pub fn resume(config: HashChainConfig, head: Option<HashChainHead>) -> Self {
+        let mut chain = Self::new(config);
+        if let Some(head) = head {
+            chain.head = head.digest;
+            chain.head_seq_id = head.seq_id;
+            chain.linked_since_checkpoint = head.linked_since_checkpoint;
+        }
+        chain
+    }
+
+    /// Returns the current position for persisting alongside the event log.
+    pub fn snapshot(&self) -> HashChainHead {
+        HashChainHead {
+            digest: self.head,
+            seq_id: self.head_seq_id,
+            linked_since_checkpoint: self.linked_since_checkpoint,
+        }
+    }
+
+    /// Returns the digest of the most recently linked event.
+    pub fn head(&self) -> ChainDigest {
+        self.head
+    }
+
+    /// Links a sequenced event onto the chain head.
+    pub fn link(&mut self, event: &mut EventEnvelope) {
+        let digest = event_digest(&self.head, event);
+        event.hash_link = Some(HashLink {
+            prev_digest: self.head,
+            digest,
+        });
+        self.head = digest;
+        self.head_seq_id = Some(event.seq_id);
+        if !matches!(event.payload, EventPayload::ChainCheckpoint(_)) {
+            self.linked_since_checkpoint = self.linked_since_checkpoint.saturating_add(1);
+        }
+    }
+
+    /// Returns a signed checkpoint payload for the current head once the
+    /// configured interval has elapsed.
+    pub fn checkpoint_payload(&mut self) -> Option<EventPayload> {
+        let interval = self.config.checkpoint_interval;
+        if interval == 0 || self.linked_since_checkpoint < interval {
+            return None;
+        }
+        let signer = self.config.signer.as_ref()?;
+        let covered_seq_id = self.head_seq_id?;
+        self.linked_since_checkpoint = 0;
+        Some(EventPayload::ChainCheckpoint(ChainCheckpoint {
+            covered_seq_id,
+            covered_digest: self.head,
+            key_id: signer.key_id().to_owned(),
+            signature: signer.sign(covered_seq_id, &self.head),
+        }))
+    }
+}
+
+/// Why verification stopped at an event.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChainBreakKind {
+    #[error("event has no hash link")]
+    MissingLink,
+    #[error("chain restarted from genesis after its first event")]
+    UnexpectedGenesis,
+    #[error("previous digest does not match the preceding event")]
+    PrevDigestMismatch {
+        expected: ChainDigest,
+        found: ChainDigest,
+    },
+    #[error("recomputed digest does not match the stored digest")]
+    DigestMismatch {
+        expected: ChainDigest,
+        found: ChainDigest,
+    },
+    #[error("checkpoint does not cover the preceding event")]
+    CheckpointMismatch,
+    #[error("checkpoint signature from key '{key_id}' is invalid")]
+    BadCheckpointSignature { key_id: String },
+    #[error("checkpoint signed by unexpected key '{key_id}'")]
+    UnknownCheckpointKey { key_id: String },
+    #[error("more than {interval} linked events without a verified checkpoint")]
+    CheckpointOverdue { interval: u64 },
+    #[error("no checkpoint verified against the supplied key")]
+    NoVerifiedCheckpoint,
+}
+
+/// First broken link found by [`verify_hash_chain`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, thiserror::Error)]
+#[error("hash chain broken at event #{index} (seq_id {seq_id}): {kind}")]
+pub struct ChainBreak {
+    /// Zero-based position of the event in the verified input.
+    pub index: usize,
+    pub seq_id: u64,
+    pub kind: ChainBreakKind,
+}
+
+/// Outcome of verifying an event stream.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ChainVerifyReport {
+    pub events_checked: u64,
+    pub linked_events: u64,
+    pub checkpoints_verified: u64,
+    /// Checkpoints left unchecked because no verifier was supplied.
+    pub checkpoints_unverified: u64,
+    pub first_break: Option<ChainBreak>,
+}
+
+impl ChainVerifyReport {
+    /// Returns `true` when no broken link was found. Without a verifier this
+    /// only shows the chain is self-consistent, since anyone can rebuild it.
+    pub fn is_intact(&self) -> bool {
+        self.first_break.is_none()
+    }
+}
+
+/// Verifies events in stream order and stops at the first broken link.
+///
+/// The first event anchors the chain, so a verified slice of a longer stream
+/// is accepted as long as it is internally consistent; checkpoints signed by
+/// `verifier`'s key pin it to the original writer. Unlinked events and chains
+/// restarting from [`GENESIS_DIGEST`] part-way through are breaks.
+///
+/// With a verifier, every checkpoint must carry its key id and a valid
+/// signature, at least one must be present, and no more than the verifier's
+/// checkpoint interval of events may go uncovered, so a chain re-hashed from
+/// genesis with its checkpoints stripped or re-signed is rejected.
+pub fn verify_hash_chain(
+    events: &[EventEnvelope],
+    verifier: Option<&CheckpointVerifier>,
+) -> ChainVerifyReport {
+    let mut report = ChainVerifyReport::default();
+    let mut head: Option<(u64, ChainDigest)> = None;
+    let mut uncovered = 0u64;
+
+    for (index, event) in events.iter().enumerate() {
+        report.events_checked += 1;
+        let broken = |kind| ChainBreak {
+            index,
+            seq_id: event.seq_id,
+            kind,
+        };
+        let Some(link) = event.hash_link else {
+            report.first_break = Some(broken(ChainBreakKind::MissingLink));
+            break;
+        };
+
+        if let Some((_, head_digest)) = head
+            && link.prev_digest != head_digest
+        {
+            let kind = if link.prev_digest == GENESIS_DIGEST {
+                ChainBreakKind::UnexpectedGenesis
+            } else {
+                ChainBreakKind::PrevDigestMismatch {
+                    expected: head_digest,
+                    found: link.prev_digest,
+                }
+            };
+            report.first_break = Some(broken(kind));
+            break;
+        }
+
+        let digest = event_digest(&link.prev_digest, event);
+        if digest != link.digest {
+            report.first_break = Some(broken(ChainBreakKind::DigestMismatch {
+                expected: digest,
+                found: link.digest,
+            }));
+            break;
+        }
+
+        if let EventPayload::ChainCheckpoint(checkpoint) = &event.payload {
+            let covers_head = checkpoint.covered_digest == link.prev_digest
+                && head.is_none_or(|(seq_id, _)| seq_id == checkpoint.covered_seq_id);
+            if !covers_head {
+                report.first_break = Some(broken(ChainBreakKind::CheckpointMismatch));
+                break;
+            }
+            match verifier {
+                Some(verifier) if verifier.key_id() != checkpoint.key_id => {
+                    report.first_break = Some(broken(ChainBreakKind::UnknownCheckpointKey {
+                        key_id: checkpoint.key_id.clone(),
+                    }));
+                    break;
+                }
+                Some(verifier) => {
+                    if !verifier.verify(checkpoint) {
+                        report.first_break = Some(broken(ChainBreakKind::BadCheckpointSignature {
+                            key_id: checkpoint.key_id.clone(),
+                        }));
+                        break;
+                    }
+                    report.checkpoints_verified += 1;
+                    uncovered = 0;
+                }
+                None => report.checkpoints_unverified += 1,
+            }
+        } else if let Some(verifier) = verifier {
+            uncovered += 1;
+            if uncovered > verifier.checkpoint_interval() {
+                report.first_break = Some(broken(ChainBreakKind::CheckpointOverdue {
+                    interval: verifier.checkpoint_interval(),
+                }));
+                break;
+            }
+        }
+
+        report.linked_events += 1;
+        head = Some((event.seq_id, link.digest));
+    }
+
+    if verifier.is_some()
+        && report.first_break.is_none()
+        && report.checkpoints_verified == 0
+        && let Some(last) = events.last()
+    {
+        report.first_break = Some(ChainBreak {
+            index: events.len() - 1,
+            seq_id: last.seq_id,
+            kind: ChainBreakKind::NoVerifiedCheckpoint,
+        });
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GlobalSequencer, TxSeen};
+    use common::SourceId;
+
+    fn seen(hash_seed: u8) -> EventEnvelope {
+        EventEnvelope {
+            seq_id: 0,
+            ingest_ts_unix_ms: 1_700_000_000_000 + i64::from(hash_seed),
+            ingest_ts_mono_ns: u64::from(hash_seed),
+            source_id: SourceId::new("chain-test"),
+            payload: EventPayload::TxSeen(TxSeen {
+                hash: [hash_seed; 32],
+                peer_id: "peer".to_owned(),
+                seen_at_unix_ms: 1_700_000_000_000,
+                seen_at_mono_ns: u64::from(hash_seed),
+            }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        }
+    }
+
+    fn chained(count: u8, config: HashChainConfig) -> Vec<EventEnvelope> {
+        let mut sequencer = GlobalSequencer::default().with_hash_chain(EventHashChain::new(config));
+        let mut events = Vec::new();
+        for seed in 1..=count {
+            events.push(sequencer.assign(seen(seed)));
+            if let Some(checkpoint) = sequencer.next_checkpoint(0, 0, SourceId::new("hash-chain")) {
+                events.push(checkpoint);
+            }
+        }
+        events
+    }
+
+    fn signer(seed: u8) -> CheckpointSigner {
+        CheckpointSigner::from_seed("k1", &[seed; 32]).expect("seed")
+    }
+
+    fn signed_config() -> HashChainConfig {
+        HashChainConfig {
+            checkpoint_interval: 2,
+            signer: Some(signer(1)),
+        }
+    }
+
+    #[test]
+    fn sequencer_links_events_and_emits_signed_checkpoints() {
+        let events = chained(4, signed_config());
+        assert_eq!(events.len(), 6);
+        assert_eq!(
+            events[0].hash_link.map(|link| link.prev_digest),
+            Some(GENESIS_DIGEST)
+        );
+        assert!(matches!(
+            events[2].payload,
+            EventPayload::ChainCheckpoint(_)
+        ));
+        assert_eq!(
+            events.iter().map(|event| event.seq_id).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+
+        let report = verify_hash_chain(&events, Some(&signer(1).verifier()));
+        assert!(report.is_intact(), "{report:?}");
+        assert_eq!(report.linked_events, 6);
+        assert_eq!(report.checkpoints_verified, 2);
+    }
+
+    #[test]
+    fn verifier_pinpoints_edited_and_dropped_events() {
+        let mut edited = chained(5, HashChainConfig::default());
+        if let EventPayload::TxSeen(seen) = &mut edited[2].payload {
+            seen.peer_id = "forged".to_owned();
+        }
+        let report = verify_hash_chain(&edited, None);
+        let first_break = report.first_break.expect("edit detected");
+        assert_eq!((first_break.index, first_break.seq_id), (2, 3));
+        assert!(matches!(
+            first_break.kind,
+            ChainBreakKind::DigestMismatch { .. }
+        ));
+
+        let mut dropped = chained(5, HashChainConfig::default());
+        dropped.remove(1);
+        let first_break = verify_hash_chain(&dropped, None)
+            .first_break
+            .expect("gap detected");
+        assert_eq!((first_break.index, first_break.seq_id), (1, 3));
+        assert!(matches!(
+            first_break.kind,
+            ChainBreakKind::PrevDigestMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn verifier_rejects_checkpoint_signed_with_wrong_key() {
+        let events = chained(2, signed_config());
+        let wrong = signer(2).verifier();
+        let first_break = verify_hash_chain(&events, Some(&wrong))
+            .first_break
+            .expect("bad signature");
+        assert_eq!(first_break.index, 2);
+        assert_eq!(
+            first_break.kind,
+            ChainBreakKind::BadCheckpointSignature {
+                key_id: "k1".to_owned()
+            }
+        );
+
+        let report = verify_hash_chain(&events, None);
+        assert!(report.is_intact());
+        assert_eq!(report.checkpoints_unverified, 1);
+    }
+
+    /// Rebuilds the chain from genesis over `events`, as a forger holding no
+    /// signing key would.
+    fn rehashed(events: impl IntoIterator<Item = EventEnvelope>) -> Vec<EventEnvelope> {
+        let mut chain = EventHashChain::new(HashChainConfig::default());
+        events
+            .into_iter()
+            .map(|mut event| {
+                chain.link(&mut event);
+                event
+            })
+            .collect()
+    }
+
+    #[test]
+    fn verifier_rejects_rehashed_chain_without_checkpoints() {
+        let original = chained(4, signed_config());
+        let verifier = signer(1).verifier().with_checkpoint_interval(2);
+        let forged = rehashed(
+            original
+                .into_iter()
+                .filter(|event| !matches!(event.payload, EventPayload::ChainCheckpoint(_)))
+                .map(|mut event| {
+                    if let EventPayload::TxSeen(seen) = &mut event.payload {
+                        seen.peer_id = "forged".to_owned();
+                    }
+                    event
+                }),
+        );
+        // Unsigned, the forgery is indistinguishable from a genuine chain.
+        assert!(verify_hash_chain(&forged, None).is_intact());
+
+        let first_break = verify_hash_chain(&forged, Some(&verifier))
+            .first_break
+            .expect("overdue checkpoint");
+        assert_eq!(first_break.index, 2);
+        assert_eq!(
+            first_break.kind,
+            ChainBreakKind::CheckpointOverdue { interval: 2 }
+        );
+
+        let first_break = verify_hash_chain(&forged[..1], Some(&verifier))
+            .first_break
+            .expect("no checkpoint");
+        assert_eq!(first_break.kind, ChainBreakKind::NoVerifiedCheckpoint);
+    }
+
+    #[test]
+    fn verifier_rejects_checkpoints_signed_under_another_key_id() {
+        let foreign = CheckpointSigner::from_seed("k2", &[7; 32]).expect("seed");
+        let events = chained(
+            4,
+            HashChainConfig {
+                checkpoint_interval: 2,
+                signer: Some(foreign),
+            },
+        );
+        let first_break = verify_hash_chain(&events, Some(&signer(1).verifier()))
+            .first_break
+            .expect("foreign key");
+        assert_eq!(first_break.index, 2);
+        assert_eq!(
+            first_break.kind,
+            ChainBreakKind::UnknownCheckpointKey {
+                key_id: "k2".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn verifier_accepts_a_tail_within_the_checkpoint_interval() {
+        let events = chained(5, signed_config());
+        let verifier = signer(1).verifier().with_checkpoint_interval(2);
+        let report = verify_hash_chain(&events, Some(&verifier));
+        assert!(report.is_intact(), "{report:?}");
+        assert_eq!(report.checkpoints_verified, 2);
+    }
+
+    #[test]
+    fn verifier_rejects_unlinked_prefix_and_mid_stream_genesis() {
+        let mut events = vec![seen(9)];
+        events.extend(chained(2, HashChainConfig::default()));
+        let first_break = verify_hash_chain(&events, None)
+            .first_break
+            .expect("unlinked prefix");
+        assert_eq!(first_break.index, 0);
+        assert_eq!(first_break.kind, ChainBreakKind::MissingLink);
+
+        let mut restarted = chained(2, HashChainConfig::default());
+        restarted.push(
+            GlobalSequencer::from_latest_seq_id(Some(2))
+                .with_hash_chain(EventHashChain::new(HashChainConfig::default()))
+                .assign(seen(3)),
+        );
+        let first_break = verify_hash_chain(&restarted, None)
+            .first_break
+            .expect("restart detected");
+        assert_eq!((first_break.index, first_break.seq_id), (2, 3));
+        assert_eq!(first_break.kind, ChainBreakKind::UnexpectedGenesis);
+
+        let mut resumed = chained(2, HashChainConfig::default());
+        let mut head = HashChainHead::default();
+        resumed.iter().for_each(|event| head.advance(event));
+        resumed.push(
+            GlobalSequencer::from_latest_seq_id(Some(2))
+                .with_hash_chain(EventHashChain::resume(
+                    HashChainConfig::default(),
+                    Some(head),
+                ))
+                .assign(seen(3)),
+        );
+        assert_eq!(resumed, chained(3, HashChainConfig::default()));
+        assert!(verify_hash_chain(&resumed, None).is_intact());
+    }
+
+    #[test]
+    fn digest_is_independent_of_storage_encoding() {
+        let events = chained(3, HashChainConfig::default());
+        let mut json = Vec::new();
+        let mut binary = Vec::new();
+        for event in &events {
+            crate::EventEncoding::Json
+                .encode_into(event, &mut json)
+                .expect("json");
+            crate::EventEncoding::Binary
+                .encode_into(event, &mut binary)
+                .expect("binary");
+        }
+        for bytes in [json, binary] {
+            let decoded = crate::decode_event_stream(&bytes).expect("decode");
+            assert_eq!(decoded, events);
+            assert!(verify_hash_chain(&decoded, None).is_intact());
+        }
+    }
+}
@@ -3,9 +3,35 @@
 
 #![forbid(unsafe_code)]
 
+pub mod codec;
+pub mod filter;
+pub mod hash_chain;
+pub mod outcome;
+pub mod schema;
+
 use common::{Address, BlockHash, PeerId, SourceId, TxHash};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+pub use codec::{
+    BINARY_SCHEMA_VERSION, CodecError, EventEncoding, EventStreamReader, decode_event,
+    decode_event_json_array, decode_event_stream, encode_event, encode_event_into,
+};
+pub use filter::{
+    EventFilter, FilterField, FilterHints, FilterParseError, FilterSubject, FilterValue,
+    MAX_FILTER_DEPTH, MAX_FILTER_LEN,
+};
+pub use hash_chain::{
+    ChainBreak, ChainBreakKind, ChainDigest, ChainVerifyReport, CheckpointKeyError,
+    CheckpointSigner, CheckpointVerifier, EventHashChain, GENESIS_DIGEST, HashChainConfig,
+    HashChainHead, HashLink, event_digest, verify_hash_chain,
+};
+pub use outcome::{AssemblyDecisionKind, DropReason, SimFailCategory, SimulationStatus};
+pub use schema::{
+    CURRENT_EVENT_SCHEMA_VERSION, EventSchemaRegistry, SCHEMA_VERSION_KEY, UpcastError, Upcaster,
+    to_versioned_json_writer, upcast_binary_event,
+};
 
 /// Top-level event envelope carrying ingest metadata and one typed payload.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -15,6 +41,17 @@ pub struct EventEnvelope {
     pub ingest_ts_mono_ns: u64,
     pub source_id: SourceId,
     pub payload: EventPayload,
+    /// Chain the event belongs to; `None` for chain-agnostic events and for
+    /// logs written before the field existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    /// Position in the per-chain stream, assigned alongside `seq_id` for
+    /// events with a `chain_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_seq_id: Option<u64>,
+    /// Hash-chain link, set when the writer runs with a hash chain enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_link: Option<HashLink>,
 }
 
 impl EventEnvelope {
@@ -29,15 +66,22 @@ impl EventEnvelope {
     }
 }
 
-/// Monotonic sequencer for assigning globally ordered event ids.
+/// Monotonic sequencer for assigning globally ordered event ids, plus one
+/// gap-free sequence per chain.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct GlobalSequencer {
     next_seq_id: u64,
+    latest_chain_seq_ids: BTreeMap<u64, u64>,
+    hash_chain: Option<EventHashChain>,
 }
 
 impl Default for GlobalSequencer {
     fn default() -> Self {
-        Self { next_seq_id: 1 }
+        Self {
+            next_seq_id: 1,
+            latest_chain_seq_ids: BTreeMap::new(),
+            hash_chain: None,
+        }
     }
 }
 
@@ -45,7 +89,54 @@ impl GlobalSequencer {
     /// Creates a sequencer that continues after the supplied latest sequence id.
     pub fn from_latest_seq_id(latest_seq_id: Option<u64>) -> Self {
         let next_seq_id = latest_seq_id.unwrap_or(0).saturating_add(1).max(1);
-        Self { next_seq_id }
+        Self {
+            next_seq_id,
+            ..Self::default()
+        }
+    }
+
+    /// Continues per-chain sequences after the supplied `(chain_id, latest
+    /// chain_seq_id)` pairs.
+    pub fn with_latest_chain_seq_ids(
+        mut self,
+        latest_chain_seq_ids: impl IntoIterator<Item = (u64, u64)>,
+    ) -> Self {
+        self.latest_chain_seq_ids.extend(latest_chain_seq_ids);
+        self
+    }
+
+    /// Returns the latest global sequence id assigned, if any.
+    pub fn latest_seq_id(&self) -> Option<u64> {
+        self.next_seq_id.checked_sub(1).filter(|seq_id| *seq_id > 0)
+    }
+
+    /// Returns the latest per-chain sequence id assigned for every chain.
+    pub fn latest_chain_seq_ids(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.latest_chain_seq_ids
+            .iter()
+            .map(|(chain_id, chain_seq_id)| (*chain_id, *chain_seq_id))
+    }
+
+    /// Returns the latest per-chain sequence id assigned for `chain_id`.
+    pub fn latest_chain_seq_id(&self, chain_id: u64) -> Option<u64> {
+        self.latest_chain_seq_ids.get(&chain_id).copied()
+    }
+
+    /// Moves past sequence ids taken outside this sequencer, such as by
+    /// backfilled history, so later assignments cannot collide with them.
+    /// Positions never move backwards.
+    pub fn skip_past(
+        &mut self,
+        latest_seq_id: Option<u64>,
+        latest_chain_seq_ids: impl IntoIterator<Item = (u64, u64)>,
+    ) {
+        if let Some(latest_seq_id) = latest_seq_id {
+            self.next_seq_id = self.next_seq_id.max(latest_seq_id.saturating_add(1));
+        }
+        for (chain_id, chain_seq_id) in latest_chain_seq_ids {
+            let latest = self.latest_chain_seq_ids.entry(chain_id).or_insert(0);
+            *latest = (*latest).max(chain_seq_id);
+        }
     }
 
     /// Reserves and returns the next global sequence id.
@@ -55,11 +146,53 @@ impl GlobalSequencer {
         seq_id
     }
 
-    /// Assigns the next sequence id to an event envelope.
+    /// Enables hash chaining for every event assigned from now on.
+    pub fn with_hash_chain(mut self, hash_chain: EventHashChain) -> Self {
+        self.hash_chain = Some(hash_chain);
+        self
+    }
+
+    /// Returns the hash chain maintained by this sequencer, if enabled.
+    pub fn hash_chain(&self) -> Option<&EventHashChain> {
+        self.hash_chain.as_ref()
+    }
+
+    /// Assigns the next global sequence id, and the next per-chain sequence id
+    /// when the event carries a `chain_id`, then links the event into the hash
+    /// chain when one is enabled.
     pub fn assign(&mut self, mut event: EventEnvelope) -> EventEnvelope {
         event.seq_id = self.next_seq_id();
+        event.chain_seq_id = event.chain_id.map(|chain_id| {
+            let latest = self.latest_chain_seq_ids.entry(chain_id).or_insert(0);
+            *latest = latest.saturating_add(1);
+            *latest
+        });
+        if let Some(hash_chain) = self.hash_chain.as_mut() {
+            hash_chain.link(&mut event);
+        }
         event
     }
+
+    /// Returns a sequenced, linked checkpoint event when the hash chain has
+    /// linked enough events since the previous one.
+    pub fn next_checkpoint(
+        &mut self,
+        ingest_ts_unix_ms: i64,
+        ingest_ts_mono_ns: u64,
+        source_id: SourceId,
+    ) -> Option<EventEnvelope> {
+        let payload = self.hash_chain.as_mut()?.checkpoint_payload()?;
+        Some(self.assign(EventEnvelope {
+            seq_id: 0,
+            ingest_ts_unix_ms,
+            ingest_ts_mono_ns,
+            source_id,
+            payload,
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        }))
+    }
 }
 
 /// Stable sort key for event envelopes.
@@ -90,9 +223,57 @@ pub enum EventPayload {
     TxConfirmedProvisional(TxConfirmed),
     TxConfirmedFinal(TxConfirmed),
     TxReorged(TxReorged),
+    UserOpSeen(UserOpSeen),
+    ChainCheckpoint(ChainCheckpoint),
 }
 
 impl EventPayload {
+    /// Variant names accepted wherever callers filter by payload type.
+    pub const TYPE_NAMES: [&'static str; 18] = [
+        "TxSeen",
+        "TxFetched",
+        "TxDecoded",
+        "TxReady",
+        "TxBlocked",
+        "CandidateQueued",
+        "SimDispatched",
+        "OppDetected",
+        "SimCompleted",
+        "AssemblyDecisionApplied",
+        "BundleSubmitted",
+        "TxReplaced",
+        "TxDropped",
+        "TxConfirmedProvisional",
+        "TxConfirmedFinal",
+        "TxReorged",
+        "UserOpSeen",
+        "ChainCheckpoint",
+    ];
+
+    /// Returns the variant name, matching the serialized `type` tag.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            EventPayload::TxSeen(_) => "TxSeen",
+            EventPayload::TxFetched(_) => "TxFetched",
+            EventPayload::TxDecoded(_) => "TxDecoded",
+            EventPayload::TxReady(_) => "TxReady",
+            EventPayload::TxBlocked(_) => "TxBlocked",
+            EventPayload::CandidateQueued(_) => "CandidateQueued",
+            EventPayload::SimDispatched(_) => "SimDispatched",
+            EventPayload::OppDetected(_) => "OppDetected",
+            EventPayload::SimCompleted(_) => "SimCompleted",
+            EventPayload::AssemblyDecisionApplied(_) => "AssemblyDecisionApplied",
+            EventPayload::BundleSubmitted(_) => "BundleSubmitted",
+            EventPayload::TxReplaced(_) => "TxReplaced",
+            EventPayload::TxDropped(_) => "TxDropped",
+            EventPayload::TxConfirmedProvisional(_) => "TxConfirmedProvisional",
+            EventPayload::TxConfirmedFinal(_) => "TxConfirmedFinal",
+            EventPayload::TxReorged(_) => "TxReorged",
+            EventPayload::UserOpSeen(_) => "UserOpSeen",
+            EventPayload::ChainCheckpoint(_) => "ChainCheckpoint",
+        }
+    }
+
     /// Returns the primary transaction hash associated with the payload.
     pub fn primary_hash(&self) -> TxHash {
         match self {
@@ -112,6 +293,9 @@ impl EventPayload {
             EventPayload::TxConfirmedProvisional(e) => e.hash,
             EventPayload::TxConfirmedFinal(e) => e.hash,
             EventPayload::TxReorged(e) => e.hash,
+            EventPayload::UserOpSeen(e) => e.user_op_hash,
+            // Checkpoints cover a chain position, not a transaction.
+            EventPayload::ChainCheckpoint(_) => [0; 32],
         }
     }
 }
@@ -233,16 +417,22 @@ pub struct OppDetected {
 pub struct SimCompleted {
     pub hash: TxHash,
     pub sim_id: String,
-    pub status: String,
+    pub status: SimulationStatus,
     pub feature_engine_version: String,
     pub scorer_version: String,
     pub strategy_version: String,
     #[serde(default)]
-    pub fail_category: Option<String>,
+    pub fail_category: Option<SimFailCategory>,
     #[serde(default)]
     pub latency_ms: Option<u64>,
     #[serde(default)]
     pub tx_count: Option<u32>,
+    /// OP-stack L1 data fee summed over the simulated transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_data_fee_wei: Option<u128>,
+    /// Total simulated gas cost, L2 execution plus L1 data fee.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_cost_wei: Option<u128>,
 }
 
 /// Builder assembly decision applied to one candidate.
@@ -250,7 +440,7 @@ pub struct SimCompleted {
 pub struct AssemblyDecisionApplied {
     pub candidate_id: String,
     pub tx_hash: TxHash,
-    pub decision: String,
+    pub decision: AssemblyDecisionKind,
     #[serde(default)]
     pub replaced_candidate_ids: Vec<String>,
     #[serde(default)]
@@ -279,10 +469,70 @@ pub struct TxReplaced {
 }
 
 /// Drop classification for a transaction hash.
+///
+/// On the wire `reason` and `detail` share one `reason` string joined by the
+/// first `;`, which keeps logs written before the split readable.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(from = "TxDroppedWire", into = "TxDroppedWire")]
 pub struct TxDropped {
     pub hash: TxHash,
-    pub reason: String,
+    pub reason: DropReason,
+    /// Free-form context such as the ingest lane and queue depth.
+    pub detail: Option<String>,
+}
+
+impl TxDropped {
+    /// Creates a drop record without detail.
+    pub fn new(hash: TxHash, reason: DropReason) -> Self {
+        Self {
+            hash,
+            reason,
+            detail: None,
+        }
+    }
+}
+
+/// Wire form of [`TxDropped`].
+///
+/// Legacy reason aliases such as `QueueFull` parse to their typed variant and
+/// are written back in the canonical spelling.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct TxDroppedWire {
+    pub(crate) hash: TxHash,
+    pub(crate) reason: String,
+}
+
+impl From<TxDroppedWire> for TxDropped {
+    fn from(value: TxDroppedWire) -> Self {
+        let (reason, detail) = match value.reason.split_once(';') {
+            Some((reason, detail)) => (DropReason::from(reason), Some(detail.to_owned())),
+            None => (DropReason::from(value.reason), None),
+        };
+        Self {
+            hash: value.hash,
+            reason,
+            detail,
+        }
+    }
+}
+
+impl From<&TxDropped> for TxDroppedWire {
+    fn from(value: &TxDropped) -> Self {
+        let reason = match &value.detail {
+            Some(detail) => format!("{};{detail}", value.reason),
+            None => value.reason.as_str().to_owned(),
+        };
+        Self {
+            hash: value.hash,
+            reason,
+        }
+    }
+}
+
+impl From<TxDropped> for TxDroppedWire {
+    fn from(value: TxDropped) -> Self {
+        Self::from(&value)
+    }
 }
 
 /// Confirmation record for a transaction hash.
@@ -301,6 +551,79 @@ pub struct TxReorged {
     pub new_block_hash: BlockHash,
 }
 
+/// ERC-4337 EntryPoint release a user operation was packed for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryPointVersion {
+    V06,
+    V07,
+}
+
+/// ERC-4337 user operation observed in a bundler alt-mempool or unpacked from a
+/// `handleOps` bundle transaction.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct UserOpSeen {
+    pub user_op_hash: TxHash,
+    pub entry_point: Address,
+    pub entry_point_version: EntryPointVersion,
+    pub sender: Address,
+    /// Big-endian `uint256` nonce; the upper 192 bits are the nonce key.
+    pub nonce: [u8; 32],
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+    #[serde(default)]
+    pub factory: Option<Address>,
+    #[serde(default)]
+    pub paymaster: Option<Address>,
+    pub call_gas_limit: u128,
+    pub verification_gas_limit: u128,
+    pub pre_verification_gas: u128,
+    pub max_fee_per_gas_wei: u128,
+    pub max_priority_fee_per_gas_wei: u128,
+    #[serde(default)]
+    pub call_data_len: u32,
+    /// `handleOps` transaction that carried the operation on-chain, once seen.
+    #[serde(default)]
+    pub bundle_tx_hash: Option<TxHash>,
+    /// Account `callData`, kept so inner calls can be re-classified on replay.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub call_data: Vec<u8>,
+}
+
+/// Signed commitment to the hash-chain head, emitted periodically by the
+/// storage writer so a verifier can anchor trust without replaying history.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChainCheckpoint {
+    /// Sequence id of the last event covered by this checkpoint.
+    pub covered_seq_id: u64,
+    /// Chain digest of that event.
+    pub covered_digest: ChainDigest,
+    pub key_id: String,
+    /// Ed25519 signature over the key id, covered sequence id and digest.
+    pub signature: Vec<u8>,
+}
+
+/// Returns the events of one chain with a per-chain sequence id strictly after
+/// `after_chain_seq_id`, in per-chain order.
+pub fn chain_events_after(
+    events: &[EventEnvelope],
+    chain_id: u64,
+    after_chain_seq_id: u64,
+) -> Vec<EventEnvelope> {
+    let mut out = events
+        .iter()
+        .filter(|event| event.chain_id == Some(chain_id))
+        .filter(|event| {
+            event
+                .chain_seq_id
+                .is_some_and(|chain_seq_id| chain_seq_id > after_chain_seq_id)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    out.sort_by_key(|event| event.chain_seq_id);
+    out
+}
+
 /// Compares two events using the canonical deterministic order.
 pub fn cmp_deterministic(a: &EventEnvelope, b: &EventEnvelope) -> Ordering {
     a.order_key().cmp(&b.order_key())
@@ -332,6 +655,9 @@ mod tests {
                     hash: hash(2),
                     fetched_at_unix_ms: 1_700_000_001_000,
                 }),
+                chain_id: None,
+                chain_seq_id: None,
+                hash_link: None,
             },
             EventEnvelope {
                 seq_id: 9,
@@ -342,6 +668,9 @@ mod tests {
                     hash: hash(3),
                     fetched_at_unix_ms: 1_700_000_000_999,
                 }),
+                chain_id: None,
+                chain_seq_id: None,
+                hash_link: None,
             },
             EventEnvelope {
                 seq_id: 10,
@@ -352,6 +681,9 @@ mod tests {
                     hash: hash(4),
                     fetched_at_unix_ms: 1_700_000_001_001,
                 }),
+                chain_id: None,
+                chain_seq_id: None,
+                hash_link: None,
             },
         ];
 
@@ -362,6 +694,54 @@ mod tests {
         assert_eq!(events[2].source_id, SourceId::new("peer-b"));
     }
 
+    #[test]
+    fn sequencer_assigns_gap_free_per_chain_sequences() {
+        let fetched = |chain_id: Option<u64>, seed: u8| EventEnvelope {
+            seq_id: 0,
+            ingest_ts_unix_ms: 1_700_000_000_000,
+            ingest_ts_mono_ns: u64::from(seed),
+            source_id: SourceId::new("rpc"),
+            payload: EventPayload::TxFetched(TxFetched {
+                hash: hash(seed),
+                fetched_at_unix_ms: 1_700_000_000_000,
+            }),
+            chain_id,
+            chain_seq_id: None,
+            hash_link: None,
+        };
+        let mut sequencer =
+            GlobalSequencer::from_latest_seq_id(Some(10)).with_latest_chain_seq_ids([(8453, 4)]);
+        let events = [
+            fetched(Some(1), 1),
+            fetched(Some(8453), 2),
+            fetched(None, 3),
+            fetched(Some(1), 4),
+        ]
+        .map(|event| sequencer.assign(event));
+
+        assert_eq!(
+            events.each_ref().map(|event| event.seq_id),
+            [11, 12, 13, 14]
+        );
+        assert_eq!(
+            events.each_ref().map(|event| event.chain_seq_id),
+            [Some(1), Some(5), None, Some(2)]
+        );
+        assert_eq!(sequencer.latest_chain_seq_id(1), Some(2));
+
+        let mainnet = chain_events_after(&events, 1, 1);
+        assert_eq!(mainnet.len(), 1);
+        assert_eq!(mainnet[0].seq_id, 14);
+    }
+
+    #[test]
+    fn envelope_without_chain_fields_still_decodes() {
+        let legacy = r#"{"seq_id":1,"ingest_ts_unix_ms":1,"ingest_ts_mono_ns":1,"source_id":"rpc","payload":{"type":"TxFetched","data":{"hash":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],"fetched_at_unix_ms":1}}}"#;
+        let event: EventEnvelope = from_str(legacy).expect("decode legacy envelope");
+        assert_eq!((event.chain_id, event.chain_seq_id), (None, None));
+        assert!(!to_string(&event).expect("encode").contains("chain_id"));
+    }
+
     #[test]
     fn event_payload_round_trip_json() {
         let event = EventEnvelope {
@@ -375,6 +755,9 @@ mod tests {
                 seen_at_unix_ms: 1_700_000_123_456,
                 seen_at_mono_ns: 9_999_999,
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         };
 
         let encoded = to_string(&event).expect("serialize event");
@@ -401,6 +784,9 @@ mod tests {
                 strategy_version: "strategy.sandwich.v1".to_owned(),
                 reasons: vec!["mev_score=90*120".to_owned()],
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         };
 
         let encoded = to_string(&event).expect("serialize event");
@@ -410,6 +796,44 @@ mod tests {
         assert_eq!(decoded.payload.primary_hash(), hash(9));
     }
 
+    #[test]
+    fn user_op_seen_payload_round_trip_json() {
+        let event = EventEnvelope {
+            seq_id: 5,
+            ingest_ts_unix_ms: 1_700_000_323_456,
+            ingest_ts_mono_ns: 5_555_555,
+            source_id: SourceId::new("bundler-base"),
+            payload: EventPayload::UserOpSeen(UserOpSeen {
+                user_op_hash: hash(11),
+                entry_point: [0x44; 20],
+                entry_point_version: EntryPointVersion::V07,
+                sender: [0x55; 20],
+                nonce: [0; 32],
+                chain_id: Some(8453),
+                factory: None,
+                paymaster: Some([0x66; 20]),
+                call_gas_limit: 100_000,
+                verification_gas_limit: 150_000,
+                pre_verification_gas: 50_000,
+                max_fee_per_gas_wei: 2_000_000_000,
+                max_priority_fee_per_gas_wei: 1_000_000,
+                call_data_len: 4,
+                bundle_tx_hash: Some(hash(12)),
+                call_data: vec![0xb6, 0x1d, 0x27, 0xf6],
+            }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        };
+
+        let encoded = to_string(&event).expect("serialize event");
+        assert!(encoded.contains("\"entry_point_version\":\"v07\""));
+        let decoded: EventEnvelope = from_str(&encoded).expect("deserialize event");
+
+        assert_eq!(event, decoded);
+        assert_eq!(decoded.payload.primary_hash(), hash(11));
+    }
+
     #[test]
     fn global_sequencer_assigns_monotonic_ids_and_can_resume() {
         let mut fresh = GlobalSequencer::default();
@@ -420,4 +844,17 @@ mod tests {
         assert_eq!(resumed.next_seq_id(), 42);
         assert_eq!(resumed.next_seq_id(), 43);
     }
+
+    #[test]
+    fn global_sequencer_skips_past_external_ids_without_moving_back() {
+        let mut sequencer =
+            GlobalSequencer::from_latest_seq_id(Some(10)).with_latest_chain_seq_ids([(1, 7)]);
+        sequencer.skip_past(Some(100), [(1, 3), (8453, 20)]);
+        assert_eq!(sequencer.latest_seq_id(), Some(100));
+        assert_eq!(sequencer.latest_chain_seq_id(1), Some(7));
+        assert_eq!(sequencer.latest_chain_seq_id(8453), Some(20));
+
+        sequencer.skip_past(Some(5), []);
+        assert_eq!(sequencer.next_seq_id(), 101);
+    }
 }
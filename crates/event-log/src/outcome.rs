@@ -0,0 +1,156 @@
+//! Typed outcome labels carried by event payloads.
+//!
+//! Each label serializes as the same plain string older logs stored. Parsing
+//! recognizes the canonical spelling plus any legacy aliases listed for a
+//! variant; values read through an alias are written back in the canonical
+//! spelling, and unknown values land in `Other` and re-encode byte-for-byte.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Declares a label enum with an `Other(String)` fallback, string
+/// conversions in both directions, and optional legacy aliases per variant.
+macro_rules! outcome_label {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $( $variant:ident => $label:literal $(| $alias:literal)* , )+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+        #[serde(from = "String", into = "String")]
+        pub enum $name {
+            $( $variant, )+
+            /// Unrecognized label, preserved verbatim.
+            Other(String),
+        }
+
+        impl $name {
+            /// Returns the wire label.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $( Self::$variant => $label, )+
+                    Self::Other(label) => label,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $( $label $(| $alias)* => Self::$variant, )+
+                    other => Self::Other(other.to_owned()),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                match Self::from(value.as_str()) {
+                    Self::Other(_) => Self::Other(value),
+                    known => known,
+                }
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                match value {
+                    $name::Other(label) => label,
+                    known => known.as_str().to_owned(),
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+outcome_label! {
+    /// Result of simulating a candidate.
+    pub enum SimulationStatus {
+        Ok => "ok",
+        Failed => "failed",
+        StateError => "state_error",
+        Timeout => "timeout",
+    }
+}
+
+outcome_label! {
+    /// Failure class reported for an unsuccessful simulation.
+    pub enum SimFailCategory {
+        Revert => "revert",
+        OutOfGas => "out_of_gas",
+        NonceMismatch => "nonce_mismatch",
+        StateMismatch => "state_mismatch",
+        StateRpc => "state_rpc",
+        StateTimeout => "state_timeout",
+        Unknown => "unknown",
+    }
+}
+
+outcome_label! {
+    /// Builder decision applied to a candidate during block assembly.
+    pub enum AssemblyDecisionKind {
+        Inserted => "inserted",
+        Rejected => "rejected",
+        Replaced => "replaced",
+    }
+}
+
+outcome_label! {
+    /// Why a transaction left the pending pool without being mined.
+    ///
+    /// The RPC and devp2p ingest lanes write the PascalCase spellings.
+    pub enum DropReason {
+        Duplicate => "duplicate" | "Duplicate",
+        QueueFull => "queue_full" | "QueueFull",
+        UnderpricedReplacement => "underpriced_replacement",
+        SenderLimitReached => "sender_limit_reached",
+        Evicted => "evicted",
+        Included => "included",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_labels_round_trip_through_json_strings() {
+        let status: SimulationStatus = serde_json::from_str(r#""state_error""#).expect("status");
+        assert_eq!(status, SimulationStatus::StateError);
+        assert_eq!(
+            serde_json::to_string(&status).expect("encode"),
+            r#""state_error""#
+        );
+        assert_eq!(
+            AssemblyDecisionKind::from("inserted"),
+            AssemblyDecisionKind::Inserted
+        );
+        assert_eq!(
+            SimFailCategory::from("out_of_gas"),
+            SimFailCategory::OutOfGas
+        );
+    }
+
+    #[test]
+    fn legacy_aliases_parse_to_typed_variants() {
+        assert_eq!(DropReason::from("QueueFull"), DropReason::QueueFull);
+        assert_eq!(DropReason::from("Duplicate"), DropReason::Duplicate);
+    }
+
+    #[test]
+    fn unknown_spellings_are_preserved_verbatim() {
+        for label in ["evicted_low_tip", "Evicted", ""] {
+            let reason = DropReason::from(label);
+            assert_eq!(reason, DropReason::Other(label.to_owned()));
+            assert_eq!(String::from(reason), label);
+        }
+    }
+}
@@ -0,0 +1,463 @@
+//! Versioned JSON event schema with per-version upcasters.
+//!
+//! JSON records carry a top-level `schema_version` next to the envelope
+//! fields. Records written before the registry existed have no version key and
+//! are treated as version 1. On read, each record is upcast one version at a
+//! time until it reaches [`CURRENT_EVENT_SCHEMA_VERSION`], and only then
+//! deserialized into [`EventEnvelope`].
+//!
+//! Additive fields keep using `#[serde(default)]`; an upcaster is only needed
+//! for renames, splits or changes in meaning. Binary frames carry their own
+//! codec version byte and are decoded by field number, see [`crate::codec`];
+//! [`upcast_binary_event`] applies the same changes in meaning to them.
+//!
+//! | version | change |
+//! |---------|--------|
+//! | 1 | legacy records without `schema_version` |
+//! | 2 | `member_tx_hashes` on `CandidateQueued`/`SimDispatched` always lists the members; an empty list in v1 meant the single `tx_hash` |
+
+use crate::{EventEnvelope, EventPayload, HashLink};
+use common::SourceId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// Schema version stamped on every JSON record written by this build.
+pub const CURRENT_EVENT_SCHEMA_VERSION: u32 = 2;
+/// Top-level JSON key holding a record's schema version.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Rewrites a JSON record in place from version `n` to version `n + 1`.
+pub type Upcaster = fn(&mut Value) -> Result<(), UpcastError>;
+
+/// Errors raised while upcasting or decoding versioned JSON records.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum UpcastError {
+    #[error("event record is not a json object")]
+    NotAnObject,
+    #[error("invalid schema_version value")]
+    InvalidVersion,
+    #[error("event schema version {found} is newer than supported version {supported}")]
+    FutureVersion { found: u32, supported: u32 },
+    #[error("no upcaster registered for event schema version {0}")]
+    MissingUpcaster(u32),
+    #[error("upcast from event schema version {version} failed: {reason}")]
+    Failed { version: u32, reason: String },
+    #[error("json event decode failed: {0}")]
+    Json(String),
+}
+
+/// Registry of upcasters keyed by the version they upgrade from.
+#[derive(Clone, Debug)]
+pub struct EventSchemaRegistry {
+    current_version: u32,
+    upcasters: BTreeMap<u32, Upcaster>,
+}
+
+impl EventSchemaRegistry {
+    /// Creates an empty registry targeting `current_version`.
+    pub fn new(current_version: u32) -> Self {
+        Self {
+            current_version: current_version.max(1),
+            upcasters: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the registry shipped with this build, with all upcasters
+    /// up to [`CURRENT_EVENT_SCHEMA_VERSION`] registered.
+    pub fn builtin() -> &'static Self {
+        static REGISTRY: OnceLock<EventSchemaRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(|| {
+            let mut registry = Self::new(CURRENT_EVENT_SCHEMA_VERSION);
+            registry.register(1, upcast_v1_to_v2);
+            registry
+        })
+    }
+
+    /// Registers the upcaster that moves records from `from_version` to
+    /// `from_version + 1`, replacing any previous one.
+    pub fn register(&mut self, from_version: u32, upcaster: Upcaster) -> &mut Self {
+        self.upcasters.insert(from_version, upcaster);
+        self
+    }
+
+    /// Returns the version records are upcast to.
+    pub fn current_version(&self) -> u32 {
+        self.current_version
+    }
+
+    /// Returns the schema version of a raw record; unversioned records are v1.
+    pub fn record_version(&self, record: &Value) -> Result<u32, UpcastError> {
+        let object = record.as_object().ok_or(UpcastError::NotAnObject)?;
+        match object.get(SCHEMA_VERSION_KEY) {
+            None | Some(Value::Null) => Ok(1),
+            Some(value) => value
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or(UpcastError::InvalidVersion),
+        }
+    }
+
+    /// Upcasts a record in place to the current version and stamps it.
+    pub fn upcast(&self, record: &mut Value) -> Result<(), UpcastError> {
+        let version = self.record_version(record)?;
+        self.apply_upcasters(record, version)?;
+        if let Some(object) = record.as_object_mut() {
+            object.insert(
+                SCHEMA_VERSION_KEY.to_owned(),
+                Value::from(self.current_version),
+            );
+        }
+        Ok(())
+    }
+
+    /// Upcasts a raw record and deserializes it into an envelope.
+    pub fn decode_json(&self, mut record: Value) -> Result<EventEnvelope, UpcastError> {
+        self.upcast(&mut record)?;
+        decode_upcast_value(record)
+    }
+
+    /// Parses and upcasts one JSON record in a single pass over `bytes`.
+    ///
+    /// The payload is kept as raw JSON until the version is known. `Value`
+    /// cannot hold integers wider than `u64`, so the payload is only decoded
+    /// from a `Value` when an upcaster actually rewrote it.
+    pub fn decode_json_slice(&self, bytes: &[u8]) -> Result<EventEnvelope, UpcastError> {
+        let raw: RawRecord<'_> = serde_json::from_slice(bytes).map_err(json_error)?;
+        let version = raw.schema_version.unwrap_or(1);
+        if version == self.current_version {
+            let payload = serde_json::from_str(raw.payload.get()).map_err(json_error)?;
+            return Ok(envelope_from_parts(raw.fields, payload));
+        }
+
+        let payload: Value = serde_json::from_str(raw.payload.get()).map_err(json_error)?;
+        let mut record = serde_json::to_value(&raw.fields).map_err(json_error)?;
+        record
+            .as_object_mut()
+            .ok_or(UpcastError::NotAnObject)?
+            .insert("payload".to_owned(), payload.clone());
+        self.apply_upcasters(&mut record, version)?;
+        let Value::Object(mut fields) = record else {
+            return Err(UpcastError::NotAnObject);
+        };
+        let payload = match fields.remove("payload").filter(|upcast| *upcast != payload) {
+            Some(rewritten) => decode_upcast_payload(rewritten)?,
+            None => serde_json::from_str(raw.payload.get()).map_err(json_error)?,
+        };
+        let fields = serde_json::from_value(Value::Object(fields)).map_err(json_error)?;
+        Ok(envelope_from_parts(fields, payload))
+    }
+
+    fn apply_upcasters(&self, record: &mut Value, mut version: u32) -> Result<(), UpcastError> {
+        if version > self.current_version {
+            return Err(UpcastError::FutureVersion {
+                found: version,
+                supported: self.current_version,
+            });
+        }
+        while version < self.current_version {
+            let upcaster = self
+                .upcasters
+                .get(&version)
+                .ok_or(UpcastError::MissingUpcaster(version))?;
+            upcaster(record)?;
+            version += 1;
+        }
+        Ok(())
+    }
+}
+
+/// JSON record with the payload left as raw JSON until its version is known.
+#[derive(Deserialize)]
+struct RawRecord<'a> {
+    #[serde(default)]
+    schema_version: Option<u32>,
+    #[serde(borrow)]
+    payload: &'a RawValue,
+    #[serde(flatten)]
+    fields: EnvelopeFields,
+}
+
+/// [`EventEnvelope`] fields other than the payload.
+#[derive(Deserialize, Serialize)]
+struct EnvelopeFields {
+    seq_id: u64,
+    ingest_ts_unix_ms: i64,
+    ingest_ts_mono_ns: u64,
+    source_id: SourceId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chain_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chain_seq_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash_link: Option<HashLink>,
+}
+
+fn envelope_from_parts(fields: EnvelopeFields, payload: EventPayload) -> EventEnvelope {
+    EventEnvelope {
+        seq_id: fields.seq_id,
+        ingest_ts_unix_ms: fields.ingest_ts_unix_ms,
+        ingest_ts_mono_ns: fields.ingest_ts_mono_ns,
+        source_id: fields.source_id,
+        payload,
+        chain_id: fields.chain_id,
+        chain_seq_id: fields.chain_seq_id,
+        hash_link: fields.hash_link,
+    }
+}
+
+fn decode_upcast_value(record: Value) -> Result<EventEnvelope, UpcastError> {
+    let Value::Object(mut fields) = record else {
+        return Err(UpcastError::NotAnObject);
+    };
+    let payload = fields
+        .remove("payload")
+        .ok_or_else(|| UpcastError::Json("missing payload object".to_owned()))?;
+    let fields = serde_json::from_value(Value::Object(fields)).map_err(json_error)?;
+    Ok(envelope_from_parts(fields, decode_upcast_payload(payload)?))
+}
+
+fn decode_upcast_payload(payload: Value) -> Result<EventPayload, UpcastError> {
+    serde_json::from_value(payload).map_err(json_error)
+}
+
+fn json_error(err: serde_json::Error) -> UpcastError {
+    UpcastError::Json(err.to_string())
+}
+
+/// Envelope serialized together with its schema version.
+#[derive(Serialize)]
+struct VersionedEnvelope<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    event: Cow<'a, EventEnvelope>,
+}
+
+/// Serializes an envelope as a JSON record stamped with the current version.
+///
+/// Payloads still using a v1 shorthand are written in their current form, so
+/// a record never claims a version whose invariants it breaks.
+pub fn to_versioned_json_writer<W: std::io::Write>(
+    writer: W,
+    event: &EventEnvelope,
+) -> serde_json::Result<()> {
+    let mut event = Cow::Borrowed(event);
+    if needs_member_fill(&event.payload) {
+        fill_member_tx_hashes(&mut event.to_mut().payload);
+    }
+    serde_json::to_writer(
+        writer,
+        &VersionedEnvelope {
+            schema_version: CURRENT_EVENT_SCHEMA_VERSION,
+            event,
+        },
+    )
+}
+
+/// Applies the changes in meaning made by the JSON upcasters to an event
+/// decoded from a binary frame.
+///
+/// Binary frames are versioned by field number rather than by
+/// [`CURRENT_EVENT_SCHEMA_VERSION`], so a frame from an older writer can
+/// still carry a v1 shorthand.
+pub fn upcast_binary_event(event: &mut EventEnvelope) {
+    fill_member_tx_hashes(&mut event.payload);
+}
+
+fn needs_member_fill(payload: &EventPayload) -> bool {
+    match payload {
+        EventPayload::CandidateQueued(queued) => queued.member_tx_hashes.is_empty(),
+        EventPayload::SimDispatched(dispatched) => dispatched.member_tx_hashes.is_empty(),
+        _ => false,
+    }
+}
+
+/// Typed form of [`upcast_v1_to_v2`].
+fn fill_member_tx_hashes(payload: &mut EventPayload) {
+    match payload {
+        EventPayload::CandidateQueued(queued) if queued.member_tx_hashes.is_empty() => {
+            queued.member_tx_hashes.push(queued.tx_hash);
+        }
+        EventPayload::SimDispatched(dispatched) if dispatched.member_tx_hashes.is_empty() => {
+            dispatched.member_tx_hashes.push(dispatched.tx_hash);
+        }
+        _ => {}
+    }
+}
+
+fn upcast_v1_to_v2(record: &mut Value) -> Result<(), UpcastError> {
+    let failed = |reason: &str| UpcastError::Failed {
+        version: 1,
+        reason: reason.to_owned(),
+    };
+    let payload = record
+        .get_mut("payload")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| failed("missing payload object"))?;
+    if !matches!(
+        payload.get("type").and_then(Value::as_str),
+        Some("CandidateQueued" | "SimDispatched")
+    ) {
+        return Ok(());
+    }
+    let data = payload
+        .get_mut("data")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| failed("missing payload data object"))?;
+    let is_empty = match data.get("member_tx_hashes") {
+        None | Some(Value::Null) => true,
+        Some(Value::Array(members)) => members.is_empty(),
+        Some(_) => return Err(failed("member_tx_hashes is not an array")),
+    };
+    if is_empty {
+        let tx_hash = data
+            .get("tx_hash")
+            .cloned()
+            .ok_or_else(|| failed("missing tx_hash"))?;
+        data.insert("member_tx_hashes".to_owned(), Value::Array(vec![tx_hash]));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventPayload, SimDispatched};
+    use common::SourceId;
+    use serde_json::json;
+
+    fn legacy_candidate_record() -> Value {
+        json!({
+            "seq_id": 7,
+            "ingest_ts_unix_ms": 1_700_000_000_000_i64,
+            "ingest_ts_mono_ns": 70,
+            "source_id": "legacy",
+            "payload": {
+                "type": "CandidateQueued",
+                "data": {
+                    "candidate_id": "cand-7",
+                    "tx_hash": vec![7_u8; 32],
+                    "strategy": "BackrunCandidate",
+                    "score": 10,
+                    "detected_unix_ms": 1_700_000_000_000_i64
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn unversioned_record_is_upcast_to_current_version() {
+        let registry = EventSchemaRegistry::builtin();
+        let record = legacy_candidate_record();
+        assert_eq!(registry.record_version(&record), Ok(1));
+
+        let event = registry.decode_json(record).expect("decode legacy record");
+        let EventPayload::CandidateQueued(queued) = event.payload else {
+            panic!("expected CandidateQueued");
+        };
+        assert_eq!(queued.member_tx_hashes, vec![[7_u8; 32]]);
+        assert_eq!(queued.chain_id, None);
+        assert!(queued.reasons.is_empty());
+    }
+
+    fn sim_dispatched(member_tx_hashes: Vec<[u8; 32]>) -> EventEnvelope {
+        EventEnvelope {
+            seq_id: 3,
+            ingest_ts_unix_ms: 1_700_000_000_003,
+            ingest_ts_mono_ns: 30,
+            source_id: SourceId::new("test"),
+            payload: EventPayload::SimDispatched(SimDispatched {
+                candidate_id: "cand-3".to_owned(),
+                tx_hash: [3; 32],
+                member_tx_hashes,
+                block_number: 9,
+            }),
+            chain_id: Some(8453),
+            chain_seq_id: Some(2),
+            hash_link: Some(HashLink {
+                prev_digest: [1; 32],
+                digest: [2; 32],
+            }),
+        }
+    }
+
+    #[test]
+    fn versioned_json_round_trips_without_upcast() {
+        let event = sim_dispatched(vec![[3; 32], [4; 32]]);
+        let mut bytes = Vec::new();
+        to_versioned_json_writer(&mut bytes, &event).expect("encode");
+        let record: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(
+            record[SCHEMA_VERSION_KEY],
+            Value::from(CURRENT_EVENT_SCHEMA_VERSION)
+        );
+
+        let decoded = EventSchemaRegistry::builtin()
+            .decode_json_slice(&bytes)
+            .expect("decode");
+        assert_eq!(decoded, event);
+    }
+
+    #[test]
+    fn writer_and_binary_reader_fill_v1_member_shorthand() {
+        let shorthand = sim_dispatched(Vec::new());
+        let expected = sim_dispatched(vec![[3; 32]]);
+
+        let mut bytes = Vec::new();
+        to_versioned_json_writer(&mut bytes, &shorthand).expect("encode");
+        let record: Value = serde_json::from_slice(&bytes).expect("json");
+        assert_eq!(
+            record["payload"]["data"]["member_tx_hashes"],
+            serde_json::to_value([[3_u8; 32]]).expect("members")
+        );
+        assert_eq!(
+            EventSchemaRegistry::builtin().decode_json_slice(&bytes),
+            Ok(expected.clone())
+        );
+
+        let frames = crate::encode_event(&shorthand);
+        assert_eq!(crate::decode_event_stream(&frames), Ok(vec![expected]));
+    }
+
+    #[test]
+    fn untouched_legacy_record_keeps_wide_integers() {
+        let line = format!(
+            r#"{{"seq_id":1,"ingest_ts_unix_ms":1,"ingest_ts_mono_ns":1,"source_id":"legacy","payload":{{"type":"TxDecoded","data":{{"hash":{hash:?},"tx_type":2,"sender":{sender:?},"nonce":0,"value_wei":{value}}}}}}}"#,
+            hash = [1_u8; 32],
+            sender = [2_u8; 20],
+            value = u128::MAX,
+        );
+        let event = EventSchemaRegistry::builtin()
+            .decode_json_slice(line.as_bytes())
+            .expect("decode legacy tx");
+        let EventPayload::TxDecoded(decoded) = event.payload else {
+            panic!("expected TxDecoded");
+        };
+        assert_eq!(decoded.value_wei, Some(u128::MAX));
+    }
+
+    #[test]
+    fn future_and_gapped_versions_are_rejected() {
+        let registry = EventSchemaRegistry::builtin();
+        let mut record = legacy_candidate_record();
+        record[SCHEMA_VERSION_KEY] = Value::from(CURRENT_EVENT_SCHEMA_VERSION + 1);
+        assert_eq!(
+            registry.upcast(&mut record),
+            Err(UpcastError::FutureVersion {
+                found: CURRENT_EVENT_SCHEMA_VERSION + 1,
+                supported: CURRENT_EVENT_SCHEMA_VERSION,
+            })
+        );
+
+        let mut gapped = EventSchemaRegistry::new(3);
+        gapped.register(1, upcast_v1_to_v2);
+        let mut record = legacy_candidate_record();
+        assert_eq!(
+            gapped.upcast(&mut record),
+            Err(UpcastError::MissingUpcaster(2))
+        );
+    }
+}
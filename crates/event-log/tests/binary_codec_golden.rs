@@ -0,0 +1,310 @@
+use common::{Address, SourceId, TxHash};
+use event_log::{
+    AssemblyDecisionApplied, AssemblyDecisionKind, BundleSubmitted, CandidateQueued, DropReason,
+    EntryPointVersion, EventEnvelope, EventPayload, OppDetected, SimCompleted, SimDispatched,
+    SimFailCategory, SimulationStatus, TxBlocked, TxConfirmed, TxDecoded, TxDropped, TxFetched,
+    TxReady, TxReorged, TxReplaced, TxSeen, UserOpSeen, decode_event, decode_event_stream,
+    encode_event,
+};
+use std::path::PathBuf;
+
+// Golden frames are written once per schema version and never regenerated for
+// an older version: every later decoder must still read them unchanged.
+const GOLDEN_V1: &str = "tests/golden/binary_codec_v1.hex";
+const ENV_UPDATE_GOLDEN: &str = "EVENT_LOG_UPDATE_GOLDEN";
+
+fn hash(value: u8) -> TxHash {
+    [value; 32]
+}
+
+fn address(value: u8) -> Address {
+    [value; 20]
+}
+
+fn envelope(seq_id: u64, payload: EventPayload) -> EventEnvelope {
+    EventEnvelope {
+        seq_id,
+        ingest_ts_unix_ms: 1_700_000_000_000 + seq_id as i64,
+        ingest_ts_mono_ns: seq_id * 1_000,
+        source_id: SourceId::new("golden"),
+        payload,
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
+    }
+}
+
+fn fixture_events() -> Vec<EventEnvelope> {
+    let confirmed = TxConfirmed {
+        hash: hash(0x0e),
+        block_number: 19_000_000,
+        block_hash: hash(0xbe),
+    };
+    vec![
+        envelope(
+            1,
+            EventPayload::TxSeen(TxSeen {
+                hash: hash(0x01),
+                peer_id: "peer-1".to_owned(),
+                seen_at_unix_ms: 1_700_000_000_001,
+                seen_at_mono_ns: 1_000,
+            }),
+        ),
+        envelope(
+            2,
+            EventPayload::TxFetched(TxFetched {
+                hash: hash(0x02),
+                fetched_at_unix_ms: -1,
+            }),
+        ),
+        envelope(
+            3,
+            EventPayload::TxDecoded(TxDecoded {
+                hash: hash(0x03),
+                tx_type: 2,
+                sender: address(0xaa),
+                nonce: 7,
+                chain_id: Some(1),
+                to: Some(address(0xbb)),
+                value_wei: Some(u128::MAX),
+                gas_limit: Some(21_000),
+                gas_price_wei: None,
+                max_fee_per_gas_wei: Some(30_000_000_000),
+                max_priority_fee_per_gas_wei: Some(0),
+                max_fee_per_blob_gas_wei: None,
+                calldata_len: Some(0),
+            }),
+        ),
+        envelope(
+            4,
+            EventPayload::TxReady(TxReady {
+                hash: hash(0x04),
+                sender: address(0xaa),
+                nonce: 7,
+            }),
+        ),
+        envelope(
+            5,
+            EventPayload::TxBlocked(TxBlocked {
+                hash: hash(0x05),
+                sender: address(0xaa),
+                nonce: 9,
+                expected_nonce: Some(8),
+            }),
+        ),
+        envelope(
+            6,
+            EventPayload::CandidateQueued(CandidateQueued {
+                candidate_id: "cand-6".to_owned(),
+                tx_hash: hash(0x06),
+                member_tx_hashes: vec![hash(0x06), hash(0x16)],
+                chain_id: Some(8453),
+                strategy: "SandwichCandidate".to_owned(),
+                score: 12_345,
+                protocol: "uniswap-v2".to_owned(),
+                category: "swap".to_owned(),
+                feature_engine_version: "feature-engine.v1".to_owned(),
+                scorer_version: "scorer.v1".to_owned(),
+                strategy_version: "strategy.v1".to_owned(),
+                reasons: vec!["a".to_owned(), "b".to_owned()],
+                detected_unix_ms: 1_700_000_000_006,
+            }),
+        ),
+        envelope(
+            7,
+            EventPayload::SimDispatched(SimDispatched {
+                candidate_id: "cand-6".to_owned(),
+                tx_hash: hash(0x06),
+                member_tx_hashes: Vec::new(),
+                block_number: 19_000_001,
+            }),
+        ),
+        envelope(
+            8,
+            EventPayload::OppDetected(OppDetected {
+                hash: hash(0x08),
+                strategy: "BackrunCandidate".to_owned(),
+                score: 99,
+                protocol: "uniswap-v3".to_owned(),
+                category: "swap".to_owned(),
+                feature_engine_version: "feature-engine.v1".to_owned(),
+                scorer_version: "scorer.v1".to_owned(),
+                strategy_version: "strategy.v1".to_owned(),
+                reasons: vec!["mev_score=90".to_owned()],
+            }),
+        ),
+        envelope(
+            9,
+            EventPayload::SimCompleted(SimCompleted {
+                hash: hash(0x09),
+                sim_id: "sim-9".to_owned(),
+                status: SimulationStatus::Failed,
+                feature_engine_version: "feature-engine.v1".to_owned(),
+                scorer_version: "scorer.v1".to_owned(),
+                strategy_version: "strategy.v1".to_owned(),
+                fail_category: Some(SimFailCategory::Revert),
+                latency_ms: Some(12),
+                tx_count: None,
+                l1_data_fee_wei: None,
+                gas_cost_wei: None,
+            }),
+        ),
+        envelope(
+            10,
+            EventPayload::AssemblyDecisionApplied(AssemblyDecisionApplied {
+                candidate_id: "cand-10".to_owned(),
+                tx_hash: hash(0x0a),
+                decision: AssemblyDecisionKind::Other("replace".to_owned()),
+                replaced_candidate_ids: vec!["cand-6".to_owned()],
+                reason: None,
+                block_number: 19_000_002,
+            }),
+        ),
+        envelope(
+            11,
+            EventPayload::BundleSubmitted(BundleSubmitted {
+                hash: hash(0x0b),
+                bundle_id: "bundle-11".to_owned(),
+                sim_id: "sim-9".to_owned(),
+                relay: "flashbots".to_owned(),
+                accepted: true,
+                feature_engine_version: "feature-engine.v1".to_owned(),
+                scorer_version: "scorer.v1".to_owned(),
+                strategy_version: "strategy.v1".to_owned(),
+            }),
+        ),
+        envelope(
+            12,
+            EventPayload::TxReplaced(TxReplaced {
+                hash: hash(0x0c),
+                replaced_by: hash(0x1c),
+            }),
+        ),
+        envelope(
+            13,
+            EventPayload::TxDropped(TxDropped {
+                hash: hash(0x0d),
+                reason: DropReason::Duplicate,
+                detail: Some("lane=rpc".to_owned()),
+            }),
+        ),
+        envelope(14, EventPayload::TxConfirmedProvisional(confirmed.clone())),
+        envelope(15, EventPayload::TxConfirmedFinal(confirmed)),
+        envelope(
+            16,
+            EventPayload::TxReorged(TxReorged {
+                hash: hash(0x10),
+                old_block_hash: hash(0xbe),
+                new_block_hash: hash(0xbf),
+            }),
+        ),
+        envelope(
+            17,
+            EventPayload::UserOpSeen(UserOpSeen {
+                user_op_hash: hash(0x11),
+                entry_point: address(0xe7),
+                entry_point_version: EntryPointVersion::V07,
+                sender: address(0x5e),
+                nonce: hash(0x00),
+                chain_id: Some(10),
+                factory: None,
+                paymaster: Some(address(0x9a)),
+                call_gas_limit: 100_000,
+                verification_gas_limit: 150_000,
+                pre_verification_gas: 50_000,
+                max_fee_per_gas_wei: 1_000_000,
+                max_priority_fee_per_gas_wei: 1_000,
+                call_data_len: 196,
+                bundle_tx_hash: Some(hash(0x12)),
+                call_data: Vec::new(),
+            }),
+        ),
+    ]
+}
+
+fn golden_path(relative: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(line: &str) -> Vec<u8> {
+    (0..line.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&line[idx..idx + 2], 16).expect("golden hex"))
+        .collect()
+}
+
+fn read_golden(relative: &str) -> Vec<Vec<u8>> {
+    std::fs::read_to_string(golden_path(relative))
+        .expect("read golden file")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(decode_hex)
+        .collect()
+}
+
+#[test]
+fn v1_golden_frames_decode_to_fixture_events() {
+    if std::env::var_os(ENV_UPDATE_GOLDEN).is_some() {
+        let mut out = String::from("# event-log binary codec schema v1; one frame per line\n");
+        for event in fixture_events() {
+            out.push_str(&encode_hex(&encode_event(&event)));
+            out.push('\n');
+        }
+        std::fs::write(golden_path(GOLDEN_V1), out).expect("write golden file");
+    }
+
+    let frames = read_golden(GOLDEN_V1);
+    let fixtures = fixture_events();
+    assert_eq!(frames.len(), fixtures.len());
+    for (frame, expected) in frames.iter().zip(&fixtures) {
+        let (decoded, consumed) = decode_event(frame).expect("decode golden frame");
+        assert_eq!(&decoded, expected);
+        assert_eq!(consumed, frame.len());
+    }
+}
+
+#[test]
+fn current_encoder_reproduces_v1_golden_bytes() {
+    let frames = read_golden(GOLDEN_V1);
+    let fixtures = fixture_events();
+    assert_eq!(frames.len(), fixtures.len());
+    // v1 wrote the `Duplicate` drop reason alias; it is re-encoded in its
+    // canonical spelling, which has the same length.
+    let alias = encode_hex(b"Duplicate;");
+    let canonical = encode_hex(b"duplicate;");
+    for (frame, event) in frames.iter().zip(fixtures) {
+        assert_eq!(
+            encode_hex(&encode_event(&event)),
+            encode_hex(frame).replace(&alias, &canonical),
+            "seq_id={}",
+            event.seq_id
+        );
+    }
+}
+
+#[test]
+fn binary_stream_is_smaller_than_json_lines() {
+    let events = fixture_events();
+    let binary = events.iter().flat_map(encode_event).collect::<Vec<_>>();
+    let json = events
+        .iter()
+        .map(|event| serde_json::to_string(event).expect("encode json"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // The stream reader fills the v1 member shorthand on seq 7.
+    let mut expected = events.clone();
+    if let EventPayload::SimDispatched(dispatched) = &mut expected[6].payload {
+        dispatched.member_tx_hashes = vec![dispatched.tx_hash];
+    }
+    assert_eq!(
+        decode_event_stream(&binary).expect("decode binary"),
+        expected
+    );
+    assert!(binary.len() * 2 < json.len());
+}
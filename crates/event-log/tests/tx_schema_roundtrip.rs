@@ -1,6 +1,7 @@
 use common::{Address, SourceId, TxHash};
 use event_log::{
-    AssemblyDecisionApplied, CandidateQueued, EventEnvelope, EventPayload, SimDispatched, TxDecoded,
+    AssemblyDecisionApplied, AssemblyDecisionKind, CandidateQueued, DropReason, EventEnvelope,
+    EventPayload, SimCompleted, SimDispatched, SimFailCategory, SimulationStatus, TxDecoded,
 };
 
 fn hash(value: u8) -> TxHash {
@@ -33,6 +34,9 @@ fn tx_schema_roundtrip_preserves_extended_fields() {
             max_fee_per_blob_gas_wei: Some(3),
             calldata_len: Some(196),
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     };
 
     let encoded = serde_json::to_vec(&envelope).expect("serialize envelope");
@@ -67,7 +71,7 @@ fn tx_schema_roundtrip_includes_candidate_and_builder_lifecycle_payloads() {
     let applied = EventPayload::AssemblyDecisionApplied(AssemblyDecisionApplied {
         candidate_id: "cand-1".to_owned(),
         tx_hash: hash(0x11),
-        decision: "inserted".to_owned(),
+        decision: AssemblyDecisionKind::Inserted,
         replaced_candidate_ids: Vec::new(),
         reason: None,
         block_number: 42,
@@ -79,3 +83,57 @@ fn tx_schema_roundtrip_includes_candidate_and_builder_lifecycle_payloads() {
         assert_eq!(decoded, payload);
     }
 }
+
+#[test]
+fn legacy_string_labels_decode_to_typed_enums_and_reencode_canonically() {
+    let legacy = [
+        r#"{"type":"TxDropped","data":{"hash":[7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7],"reason":"queue_full;lane=rpc;queue=rpc.pending_batch"}}"#,
+        r#"{"type":"TxDropped","data":{"hash":[7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7],"reason":"evicted_low_tip"}}"#,
+        r#"{"type":"TxDropped","data":{"hash":[7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7],"reason":"QueueFull;lane=p2p"}}"#,
+        r#"{"type":"SimCompleted","data":{"hash":[7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7],"sim_id":"sim-1","status":"failed","feature_engine_version":"f","scorer_version":"s","strategy_version":"t","fail_category":"out_of_gas","latency_ms":null,"tx_count":null}}"#,
+    ];
+
+    let decoded = legacy
+        .iter()
+        .map(|json| serde_json::from_str::<EventPayload>(json).expect("decode legacy payload"))
+        .collect::<Vec<_>>();
+
+    let EventPayload::TxDropped(dropped) = &decoded[0] else {
+        panic!("expected TxDropped");
+    };
+    assert_eq!(dropped.reason, DropReason::QueueFull);
+    assert_eq!(
+        dropped.detail.as_deref(),
+        Some("lane=rpc;queue=rpc.pending_batch")
+    );
+    let EventPayload::TxDropped(dropped) = &decoded[1] else {
+        panic!("expected TxDropped");
+    };
+    assert_eq!(
+        dropped.reason,
+        DropReason::Other("evicted_low_tip".to_owned())
+    );
+    let EventPayload::TxDropped(dropped) = &decoded[2] else {
+        panic!("expected TxDropped");
+    };
+    assert_eq!(dropped.reason, DropReason::QueueFull);
+    assert_eq!(dropped.detail.as_deref(), Some("lane=p2p"));
+    let EventPayload::SimCompleted(SimCompleted {
+        status,
+        fail_category,
+        ..
+    }) = &decoded[3]
+    else {
+        panic!("expected SimCompleted");
+    };
+    assert_eq!(status, &SimulationStatus::Failed);
+    assert_eq!(fail_category, &Some(SimFailCategory::OutOfGas));
+
+    // Canonical spellings re-encode unchanged; legacy aliases are written
+    // back canonically.
+    let canonical = legacy[2].replace("QueueFull", "queue_full");
+    let expected = [legacy[0], legacy[1], canonical.as_str(), legacy[3]];
+    for (json, payload) in expected.iter().zip(&decoded) {
+        assert_eq!(&serde_json::to_string(payload).expect("encode"), json);
+    }
+}
@@ -3,6 +3,7 @@
 #![forbid(unsafe_code)]
 
 pub mod registry;
+pub mod user_op;
 
 use common::{Address, TxHash};
 use event_log::TxDecoded;
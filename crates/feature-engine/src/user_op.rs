@@ -0,0 +1,316 @@
+//! Inner-call classification for ERC-4337 user operations.
+//!
+//! A user operation's `callData` targets the smart account itself; the
+//! MEV-relevant intent lives in the calls the account forwards. Common account
+//! execute entrypoints are unwrapped so each inner call is classified like a
+//! top-level transaction.
+
+use crate::{FeatureAnalysis, FeatureInput, analyze_transaction};
+use common::abi::{WORD, abi_address, abi_array_bytes, abi_bytes, abi_u128, abi_usize};
+use common::{Address, TxHash};
+use event_log::UserOpSeen;
+
+/// `execute(address,uint256,bytes)` on SimpleAccount-style accounts.
+pub const EXECUTE_SELECTOR: [u8; 4] = [0xb6, 0x1d, 0x27, 0xf6];
+/// `executeBatch(address[],bytes[])` on v0.6 SimpleAccount.
+pub const EXECUTE_BATCH_SELECTOR: [u8; 4] = [0x18, 0xdf, 0xb3, 0xc7];
+/// `executeBatch(address[],uint256[],bytes[])` on v0.7 SimpleAccount.
+pub const EXECUTE_BATCH_WITH_VALUE_SELECTOR: [u8; 4] = [0x47, 0xe1, 0xda, 0x2a];
+
+/// One call a smart account forwards while executing a user operation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InnerCall<'a> {
+    pub to: Address,
+    pub value_wei: u128,
+    pub calldata: &'a [u8],
+}
+
+/// Inner call paired with its feature analysis.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeaturedInnerCall {
+    pub to: Address,
+    pub value_wei: u128,
+    pub calldata_len: usize,
+    pub analysis: FeatureAnalysis,
+}
+
+/// User operation paired with the analysis of every inner call.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeaturedUserOperation {
+    pub user_op_hash: TxHash,
+    pub sender: Address,
+    /// `handleOps` transaction that carried the operation, once known.
+    pub bundle_tx_hash: Option<TxHash>,
+    pub inner_calls: Vec<FeaturedInnerCall>,
+}
+
+impl FeaturedUserOperation {
+    /// Returns the highest MEV score across inner calls.
+    pub fn max_mev_score(&self) -> u16 {
+        self.inner_calls
+            .iter()
+            .map(|call| call.analysis.mev_score)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// Unwraps the calls a smart account forwards from user operation `callData`.
+///
+/// Unknown account entrypoints and malformed payloads fall back to a single
+/// call against the account itself so the operation is still classified.
+pub fn decode_inner_calls(sender: Address, call_data: &[u8]) -> Vec<InnerCall<'_>> {
+    decode_known_execute(call_data).unwrap_or_else(|| {
+        vec![InnerCall {
+            to: sender,
+            value_wei: 0,
+            calldata: call_data,
+        }]
+    })
+}
+
+/// Classifies each inner call of a user operation from its persisted
+/// `callData`.
+pub fn analyze_user_operation(op: &UserOpSeen) -> FeaturedUserOperation {
+    let gas_limit = u64::try_from(op.call_gas_limit).unwrap_or(u64::MAX);
+    let inner_calls = decode_inner_calls(op.sender, &op.call_data)
+        .into_iter()
+        .map(|call| FeaturedInnerCall {
+            to: call.to,
+            value_wei: call.value_wei,
+            calldata_len: call.calldata.len(),
+            analysis: analyze_transaction(FeatureInput {
+                to: Some(&call.to),
+                calldata: call.calldata,
+                // User operations always carry EIP-1559 style fee fields.
+                tx_type: 2,
+                chain_id: op.chain_id,
+                gas_limit: Some(gas_limit),
+                value_wei: Some(call.value_wei),
+                gas_price_wei: None,
+                max_fee_per_gas_wei: Some(op.max_fee_per_gas_wei),
+                max_priority_fee_per_gas_wei: Some(op.max_priority_fee_per_gas_wei),
+                max_fee_per_blob_gas_wei: None,
+            }),
+        })
+        .collect();
+
+    FeaturedUserOperation {
+        user_op_hash: op.user_op_hash,
+        sender: op.sender,
+        bundle_tx_hash: op.bundle_tx_hash,
+        inner_calls,
+    }
+}
+
+fn decode_known_execute(call_data: &[u8]) -> Option<Vec<InnerCall<'_>>> {
+    let selector: [u8; 4] = call_data.get(..4)?.try_into().ok()?;
+    let args = &call_data[4..];
+    match selector {
+        EXECUTE_SELECTOR => Some(vec![InnerCall {
+            to: abi_address(args, 0)?,
+            value_wei: abi_u128(args, WORD)?,
+            calldata: abi_bytes(args, abi_usize(args, 2 * WORD)?)?,
+        }]),
+        EXECUTE_BATCH_SELECTOR => {
+            let targets = abi_usize(args, 0)?;
+            let payloads = abi_usize(args, WORD)?;
+            let count = abi_usize(args, targets)?;
+            if abi_usize(args, payloads)? != count {
+                return None;
+            }
+            (0..count)
+                .map(|index| {
+                    Some(InnerCall {
+                        to: abi_address(args, targets + WORD * (index + 1))?,
+                        value_wei: 0,
+                        calldata: abi_array_bytes(args, payloads, index)?,
+                    })
+                })
+                .collect()
+        }
+        EXECUTE_BATCH_WITH_VALUE_SELECTOR => {
+            let targets = abi_usize(args, 0)?;
+            let values = abi_usize(args, WORD)?;
+            let payloads = abi_usize(args, 2 * WORD)?;
+            let count = abi_usize(args, targets)?;
+            let value_count = abi_usize(args, values)?;
+            // SimpleAccount v0.7 allows an empty value array to mean zero value.
+            if (value_count != 0 && value_count != count) || abi_usize(args, payloads)? != count {
+                return None;
+            }
+            (0..count)
+                .map(|index| {
+                    let value_wei = if value_count == 0 {
+                        0
+                    } else {
+                        abi_u128(args, values + WORD * (index + 1))?
+                    };
+                    Some(InnerCall {
+                        to: abi_address(args, targets + WORD * (index + 1))?,
+                        value_wei,
+                        calldata: abi_array_bytes(args, payloads, index)?,
+                    })
+                })
+                .collect()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::abi::address_word;
+    use event_log::EntryPointVersion;
+    use sha3::{Digest, Keccak256};
+
+    const UNISWAP_V2_ROUTER: Address = [
+        0x7a, 0x25, 0x0d, 0x56, 0x30, 0xb4, 0xcf, 0x53, 0x97, 0x39, 0xdf, 0x2c, 0x5d, 0xac, 0xb4,
+        0xc6, 0x59, 0xf2, 0x48, 0x8d,
+    ];
+
+    fn selector(signature: &str) -> [u8; 4] {
+        let digest = Keccak256::digest(signature.as_bytes());
+        [digest[0], digest[1], digest[2], digest[3]]
+    }
+
+    fn word(value: usize) -> [u8; 32] {
+        let mut out = [0_u8; 32];
+        out[24..].copy_from_slice(&(value as u64).to_be_bytes());
+        out
+    }
+
+    fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+        let mut out = word(bytes.len()).to_vec();
+        out.extend_from_slice(bytes);
+        out.resize(WORD + bytes.len().div_ceil(WORD) * WORD, 0);
+        out
+    }
+
+    fn encode_bytes_array(items: &[&[u8]]) -> Vec<u8> {
+        let encoded = items
+            .iter()
+            .map(|item| encode_bytes(item))
+            .collect::<Vec<_>>();
+        let mut out = word(items.len()).to_vec();
+        let mut offset = items.len() * WORD;
+        for item in &encoded {
+            out.extend_from_slice(&word(offset));
+            offset += item.len();
+        }
+        out.extend(encoded.concat());
+        out
+    }
+
+    fn user_op(chain_id: u64) -> UserOpSeen {
+        UserOpSeen {
+            user_op_hash: [0x42; 32],
+            entry_point: [0x01; 20],
+            entry_point_version: EntryPointVersion::V07,
+            sender: [0x11; 20],
+            nonce: [0; 32],
+            chain_id: Some(chain_id),
+            factory: None,
+            paymaster: None,
+            call_gas_limit: 300_000,
+            verification_gas_limit: 100_000,
+            pre_verification_gas: 50_000,
+            max_fee_per_gas_wei: 65_000_000_000,
+            max_priority_fee_per_gas_wei: 4_000_000_000,
+            call_data_len: 0,
+            bundle_tx_hash: None,
+            call_data: Vec::new(),
+        }
+    }
+
+    fn user_op_with_call_data(chain_id: u64, call_data: Vec<u8>) -> UserOpSeen {
+        UserOpSeen {
+            call_data_len: call_data.len() as u32,
+            call_data,
+            ..user_op(chain_id)
+        }
+    }
+
+    #[test]
+    fn execute_selectors_match_account_abi() {
+        assert_eq!(selector("execute(address,uint256,bytes)"), EXECUTE_SELECTOR);
+        assert_eq!(
+            selector("executeBatch(address[],bytes[])"),
+            EXECUTE_BATCH_SELECTOR
+        );
+        assert_eq!(
+            selector("executeBatch(address[],uint256[],bytes[])"),
+            EXECUTE_BATCH_WITH_VALUE_SELECTOR
+        );
+    }
+
+    #[test]
+    fn classifies_swap_wrapped_in_single_execute() {
+        let swap = [0x38, 0xed, 0x17, 0x39, 0, 0, 0, 0];
+        let mut call_data = EXECUTE_SELECTOR.to_vec();
+        call_data.extend_from_slice(&address_word(UNISWAP_V2_ROUTER));
+        call_data.extend_from_slice(&word(7));
+        call_data.extend_from_slice(&word(3 * WORD));
+        call_data.extend(encode_bytes(&swap));
+
+        let featured = analyze_user_operation(&user_op_with_call_data(1, call_data));
+
+        assert_eq!(featured.inner_calls.len(), 1);
+        let call = &featured.inner_calls[0];
+        assert_eq!(call.to, UNISWAP_V2_ROUTER);
+        assert_eq!(call.value_wei, 7);
+        assert_eq!(call.analysis.protocol, "uniswap-v2");
+        assert_eq!(call.analysis.category, "swap");
+        assert_eq!(featured.max_mev_score(), call.analysis.mev_score);
+    }
+
+    #[test]
+    fn classifies_each_call_in_batch_with_values() {
+        let token = [0xab; 20];
+        let approve = [0x09, 0x5e, 0xa7, 0xb3, 0, 0];
+        let swap = [0x38, 0xed, 0x17, 0x39, 0, 0];
+        let targets = [address_word(token), address_word(UNISWAP_V2_ROUTER)].concat();
+        let values = [word(0), word(5)].concat();
+
+        let mut tail = word(2).to_vec();
+        tail.extend_from_slice(&targets);
+        let values_offset = 3 * WORD + tail.len();
+        tail.extend_from_slice(&word(2));
+        tail.extend_from_slice(&values);
+        let payloads_offset = 3 * WORD + tail.len();
+        tail.extend(encode_bytes_array(&[&approve, &swap]));
+
+        let mut call_data = EXECUTE_BATCH_WITH_VALUE_SELECTOR.to_vec();
+        call_data.extend_from_slice(&word(3 * WORD));
+        call_data.extend_from_slice(&word(values_offset));
+        call_data.extend_from_slice(&word(payloads_offset));
+        call_data.extend(tail);
+
+        let featured = analyze_user_operation(&user_op_with_call_data(1, call_data));
+        let categories = featured
+            .inner_calls
+            .iter()
+            .map(|call| (call.analysis.category, call.value_wei))
+            .collect::<Vec<_>>();
+        assert_eq!(categories, vec![("approval", 0), ("swap", 5)]);
+    }
+
+    #[test]
+    fn unknown_account_entrypoint_falls_back_to_sender_call() {
+        let op = user_op(8453);
+        let calls = decode_inner_calls(op.sender, &[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(
+            calls,
+            vec![InnerCall {
+                to: op.sender,
+                value_wei: 0,
+                calldata: &[0xde, 0xad, 0xbe, 0xef],
+            }]
+        );
+
+        // Truncated execute payloads are treated the same way.
+        let truncated = [EXECUTE_SELECTOR.as_slice(), &[0_u8; 8]].concat();
+        assert_eq!(decode_inner_calls(op.sender, &truncated)[0].to, op.sender);
+    }
+}
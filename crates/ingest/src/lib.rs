@@ -10,6 +10,7 @@ pub mod devp2p_runtime;
 pub mod p2p;
 pub mod rpc;
 pub mod tx_decode;
+pub mod user_op;
 
 type SharedError = Arc<dyn StdError + Send + Sync>;
 
@@ -68,3 +69,4 @@ pub use devp2p_runtime::*;
 pub use p2p::*;
 pub use rpc::*;
 pub use tx_decode::*;
+pub use user_op::*;
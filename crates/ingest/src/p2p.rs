@@ -2,7 +2,7 @@
 
 use ahash::RandomState;
 use common::{PeerId, SourceId, TxHash};
-use event_log::{EventEnvelope, EventPayload, TxDecoded, TxDropped, TxFetched, TxSeen};
+use event_log::{DropReason, EventEnvelope, EventPayload, TxDecoded, TxDropped, TxFetched, TxSeen};
 use hashbrown::HashMap;
 use std::collections::VecDeque;
 
@@ -16,6 +16,9 @@ pub struct P2pIngestConfig {
     pub fetch_queue_capacity: usize,
     /// Maximum number of first-seen hashes retained for deduplication.
     pub max_seen_hashes: usize,
+    /// Chain the peers gossip for; stamped on every emitted envelope so the
+    /// storage writer assigns per-chain sequence ids.
+    pub chain_id: Option<u64>,
 }
 
 impl Default for P2pIngestConfig {
@@ -23,6 +26,7 @@ impl Default for P2pIngestConfig {
         Self {
             fetch_queue_capacity: 4_096,
             max_seen_hashes: 250_000,
+            chain_id: None,
         }
     }
 }
@@ -89,6 +93,7 @@ impl P2pIngestService {
             config: P2pIngestConfig {
                 fetch_queue_capacity: config.fetch_queue_capacity.max(1),
                 max_seen_hashes: config.max_seen_hashes.max(1),
+                chain_id: config.chain_id,
             },
             source_id,
             seq_id: 1,
@@ -124,12 +129,12 @@ impl P2pIngestService {
                     now_mono_ns,
                     EventPayload::TxDropped(TxDropped {
                         hash,
-                        reason: self.drop_reason(
-                            "Duplicate",
+                        reason: DropReason::Duplicate,
+                        detail: Some(self.drop_detail(
                             "p2p.fetch",
                             &peer_id,
                             self.fetch_queue.len(),
-                        ),
+                        )),
                     }),
                 ));
                 self.propagation_delays_by_peer
@@ -149,12 +154,12 @@ impl P2pIngestService {
                     now_mono_ns,
                     EventPayload::TxDropped(TxDropped {
                         hash,
-                        reason: self.drop_reason(
-                            "QueueFull",
+                        reason: DropReason::QueueFull,
+                        detail: Some(self.drop_detail(
                             "p2p.fetch",
                             &peer_id,
                             self.fetch_queue.len(),
-                        ),
+                        )),
                     }),
                 ));
                 continue;
@@ -236,7 +241,7 @@ impl P2pIngestService {
                     tx_type: tx.tx_type,
                     sender: tx.sender,
                     nonce: tx.nonce,
-                    chain_id: None,
+                    chain_id: self.config.chain_id,
                     to: None,
                     value_wei: None,
                     gas_limit: None,
@@ -289,15 +294,9 @@ impl P2pIngestService {
         &self.metrics
     }
 
-    fn drop_reason(
-        &self,
-        reason: &str,
-        queue_name: &str,
-        peer_id: &PeerId,
-        depth_current: usize,
-    ) -> String {
+    fn drop_detail(&self, queue_name: &str, peer_id: &PeerId, depth_current: usize) -> String {
         format!(
-            "{reason};lane=p2p;source={};peer={peer_id};queue={queue_name};depth_current={depth_current};depth_peak={}",
+            "lane=p2p;source={};peer={peer_id};queue={queue_name};depth_current={depth_current};depth_peak={}",
             self.source_id.0, self.metrics.queue_depth_peak
         )
     }
@@ -316,6 +315,9 @@ impl P2pIngestService {
             ingest_ts_mono_ns: now_mono_ns,
             source_id: self.source_id.clone(),
             payload,
+            chain_id: self.config.chain_id,
+            chain_seq_id: None,
+            hash_link: None,
         }
     }
 }
@@ -334,6 +336,7 @@ mod tests {
             P2pIngestConfig {
                 fetch_queue_capacity: 8,
                 max_seen_hashes: 128,
+                chain_id: Some(1),
             },
             SourceId::new("p2p"),
         );
@@ -345,6 +348,7 @@ mod tests {
             10,
         );
         assert_eq!(first.len(), 1);
+        assert_eq!(first[0].chain_id, Some(1));
 
         let second = service.handle_new_pooled_transaction_hashes(
             "peer-b".to_owned(),
@@ -367,6 +371,7 @@ mod tests {
             P2pIngestConfig {
                 fetch_queue_capacity: 8,
                 max_seen_hashes: 128,
+                chain_id: None,
             },
             SourceId::new("p2p"),
         );
@@ -389,8 +394,8 @@ mod tests {
             duplicate[0].payload,
             EventPayload::TxDropped(ref dropped)
                 if dropped.hash == hash(1)
-                    && dropped.reason.contains("Duplicate")
-                    && dropped.reason.contains("queue=p2p.fetch")
+                    && dropped.reason == DropReason::Duplicate
+                    && dropped.detail.as_deref().is_some_and(|detail| detail.contains("queue=p2p.fetch"))
         ));
     }
 
@@ -400,6 +405,7 @@ mod tests {
             P2pIngestConfig {
                 fetch_queue_capacity: 1,
                 max_seen_hashes: 128,
+                chain_id: None,
             },
             SourceId::new("p2p"),
         );
@@ -423,8 +429,8 @@ mod tests {
             second[0].payload,
             EventPayload::TxDropped(ref dropped)
                 if dropped.hash == hash(2)
-                    && dropped.reason.contains("QueueFull")
-                    && dropped.reason.contains("queue=p2p.fetch")
+                    && dropped.reason == DropReason::QueueFull
+                    && dropped.detail.as_deref().is_some_and(|detail| detail.contains("queue=p2p.fetch"))
         ));
         assert_eq!(service.metrics().queue_dropped_total, 1);
     }
@@ -457,6 +463,7 @@ mod tests {
             P2pIngestConfig {
                 fetch_queue_capacity: 3,
                 max_seen_hashes: 128,
+                chain_id: None,
             },
             SourceId::new("p2p"),
         );
@@ -488,6 +495,7 @@ mod tests {
             P2pIngestConfig {
                 fetch_queue_capacity: 8,
                 max_seen_hashes: 2,
+                chain_id: None,
             },
             SourceId::new("p2p"),
         );
@@ -4,7 +4,7 @@ use crate::IngestError;
 use ahash::RandomState;
 use auto_impl::auto_impl;
 use common::{SourceId, TxHash};
-use event_log::{EventEnvelope, EventPayload, TxDecoded, TxDropped, TxFetched, TxSeen};
+use event_log::{DropReason, EventEnvelope, EventPayload, TxDecoded, TxDropped, TxFetched, TxSeen};
 use hashbrown::{HashMap, HashSet};
 use std::collections::VecDeque;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -21,6 +21,9 @@ pub struct RpcIngestConfig {
     /// Maximum number of hashes accepted from a single pending-hash poll before
     /// the remainder are dropped as backpressure.
     pub pending_batch_capacity: usize,
+    /// Chain the provider serves; stamped on every emitted envelope so the
+    /// storage writer assigns per-chain sequence ids.
+    pub chain_id: Option<u64>,
 }
 
 impl Default for RpcIngestConfig {
@@ -28,6 +31,7 @@ impl Default for RpcIngestConfig {
         Self {
             max_seen_hashes: 250_000,
             pending_batch_capacity: 4_096,
+            chain_id: None,
         }
     }
 }
@@ -119,6 +123,7 @@ where
             config: RpcIngestConfig {
                 max_seen_hashes: config.max_seen_hashes.max(1),
                 pending_batch_capacity: config.pending_batch_capacity.max(1),
+                chain_id: config.chain_id,
             },
             seen_hashes: FastSet::default(),
             seen_order: VecDeque::new(),
@@ -143,12 +148,12 @@ where
             if idx >= self.config.pending_batch_capacity {
                 events.push(self.new_event(EventPayload::TxDropped(TxDropped {
                     hash,
-                    reason: self.drop_reason(
-                        "QueueFull",
+                    reason: DropReason::QueueFull,
+                    detail: Some(self.drop_detail(
                         "rpc.pending_batch",
                         depth_current,
                         self.config.pending_batch_capacity,
-                    ),
+                    )),
                 })));
                 continue;
             }
@@ -158,12 +163,12 @@ where
             if !self.remember_hash(hash) {
                 events.push(self.new_event(EventPayload::TxDropped(TxDropped {
                     hash,
-                    reason: self.drop_reason(
-                        "Duplicate",
+                    reason: DropReason::Duplicate,
+                    detail: Some(self.drop_detail(
                         "rpc.pending_batch",
                         depth_current,
                         self.config.pending_batch_capacity,
-                    ),
+                    )),
                 })));
                 continue;
             }
@@ -192,7 +197,7 @@ where
                     tx_type: tx.tx_type,
                     sender: [0_u8; 20],
                     nonce: 0,
-                    chain_id: None,
+                    chain_id: self.config.chain_id,
                     to: None,
                     value_wei: None,
                     gas_limit: None,
@@ -208,15 +213,9 @@ where
         Ok(events)
     }
 
-    fn drop_reason(
-        &self,
-        reason: &str,
-        queue_name: &str,
-        depth_current: usize,
-        depth_peak: usize,
-    ) -> String {
+    fn drop_detail(&self, queue_name: &str, depth_current: usize, depth_peak: usize) -> String {
         format!(
-            "{reason};lane=rpc;source={};queue={queue_name};depth_current={depth_current};depth_peak={depth_peak}",
+            "lane=rpc;source={};queue={queue_name};depth_current={depth_current};depth_peak={depth_peak}",
             self.source_id.as_str()
         )
     }
@@ -246,6 +245,9 @@ where
             ingest_ts_mono_ns: self.clock.now_mono_ns(),
             source_id: self.source_id.clone(),
             payload,
+            chain_id: self.config.chain_id,
+            chain_seq_id: None,
+            hash_link: None,
         }
     }
 }
@@ -380,8 +382,8 @@ mod tests {
                 event.payload,
                 EventPayload::TxDropped(ref dropped)
                     if dropped.hash == hash(1)
-                        && dropped.reason.contains("Duplicate")
-                        && dropped.reason.contains("queue=rpc.pending_batch")
+                        && dropped.reason == DropReason::Duplicate
+                        && dropped.detail.as_deref().is_some_and(|detail| detail.contains("queue=rpc.pending_batch"))
             )
         }));
     }
@@ -415,8 +417,8 @@ mod tests {
                 event.payload,
                 EventPayload::TxDropped(ref dropped)
                     if dropped.hash == hash(7)
-                        && dropped.reason.contains("Duplicate")
-                        && dropped.reason.contains("queue=rpc.pending_batch")
+                        && dropped.reason == DropReason::Duplicate
+                        && dropped.detail.as_deref().is_some_and(|detail| detail.contains("queue=rpc.pending_batch"))
             )
         }));
     }
@@ -435,6 +437,7 @@ mod tests {
             RpcIngestConfig {
                 max_seen_hashes: 2,
                 pending_batch_capacity: 16,
+                chain_id: Some(8453),
             },
         );
 
@@ -451,6 +454,11 @@ mod tests {
             })
             .collect();
         assert_eq!(seen_hashes, vec![hash(1)]);
+        assert!(
+            fourth_batch
+                .iter()
+                .all(|event| event.chain_id == Some(8453))
+        );
     }
 
     #[test]
@@ -467,6 +475,7 @@ mod tests {
             RpcIngestConfig {
                 max_seen_hashes: 16,
                 pending_batch_capacity: 2,
+                chain_id: None,
             },
         );
 
@@ -481,8 +490,13 @@ mod tests {
 
         assert_eq!(dropped.len(), 1);
         assert_eq!(dropped[0].hash, hash(3));
-        assert!(dropped[0].reason.contains("QueueFull"));
-        assert!(dropped[0].reason.contains("queue=rpc.pending_batch"));
+        assert!(dropped[0].reason == DropReason::QueueFull);
+        assert!(
+            dropped[0]
+                .detail
+                .as_deref()
+                .is_some_and(|detail| detail.contains("queue=rpc.pending_batch"))
+        );
     }
 
     #[test]
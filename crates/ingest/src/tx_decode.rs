@@ -1,6 +1,10 @@
 //! Helpers for normalizing raw transaction inputs into typed decoded records.
 
-use common::{Address, TxHash};
+use common::{
+    ARBITRUM_CONTRACT_TX_TYPE, ARBITRUM_DEPOSIT_TX_TYPE, ARBITRUM_INTERNAL_TX_TYPE,
+    ARBITRUM_RETRY_TX_TYPE, ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE, ARBITRUM_UNSIGNED_TX_TYPE, Address,
+    ChainFamily, L2TxFields, OP_DEPOSIT_TX_TYPE, TxHash,
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -12,6 +16,48 @@ pub enum TxType {
     Eip2930,
     Eip1559,
     Eip4844,
+    /// OP-stack L1-to-L2 deposit (`0x7e`).
+    OpDeposit,
+    ArbitrumDeposit,
+    ArbitrumUnsigned,
+    ArbitrumContract,
+    ArbitrumRetry,
+    ArbitrumSubmitRetryable,
+    ArbitrumInternal,
+}
+
+impl TxType {
+    /// Returns the EIP-2718 type byte used on the wire.
+    pub fn type_byte(self) -> u8 {
+        match self {
+            Self::Legacy => 0x00,
+            Self::Eip2930 => 0x01,
+            Self::Eip1559 => 0x02,
+            Self::Eip4844 => 0x03,
+            Self::OpDeposit => OP_DEPOSIT_TX_TYPE,
+            Self::ArbitrumDeposit => ARBITRUM_DEPOSIT_TX_TYPE,
+            Self::ArbitrumUnsigned => ARBITRUM_UNSIGNED_TX_TYPE,
+            Self::ArbitrumContract => ARBITRUM_CONTRACT_TX_TYPE,
+            Self::ArbitrumRetry => ARBITRUM_RETRY_TX_TYPE,
+            Self::ArbitrumSubmitRetryable => ARBITRUM_SUBMIT_RETRYABLE_TX_TYPE,
+            Self::ArbitrumInternal => ARBITRUM_INTERNAL_TX_TYPE,
+        }
+    }
+
+    /// Returns the chain family that defines this type, or `None` for the
+    /// Ethereum types every EVM chain accepts.
+    pub fn family(self) -> Option<ChainFamily> {
+        match self {
+            Self::Legacy | Self::Eip2930 | Self::Eip1559 | Self::Eip4844 => None,
+            Self::OpDeposit => Some(ChainFamily::OpStack),
+            Self::ArbitrumDeposit
+            | Self::ArbitrumUnsigned
+            | Self::ArbitrumContract
+            | Self::ArbitrumRetry
+            | Self::ArbitrumSubmitRetryable
+            | Self::ArbitrumInternal => Some(ChainFamily::Arbitrum),
+        }
+    }
 }
 
 /// Provider-facing transaction input before hex parsing and fee normalization.
@@ -32,6 +78,11 @@ pub struct RawTxInput {
     pub max_priority_fee_per_gas: Option<u128>,
     pub max_fee_per_blob_gas: Option<u128>,
     pub calldata: Option<String>,
+    #[serde(default)]
+    pub l2_fields: Option<L2TxFields>,
+    /// Family declared by the chain's config; the chain id table is used when absent.
+    #[serde(default)]
+    pub chain_family: Option<ChainFamily>,
 }
 
 /// Canonical fee representation derived from the transaction type.
@@ -56,6 +107,7 @@ pub struct DecodedTx {
     pub gas_limit: u64,
     pub fees: NormalizedFees,
     pub calldata: Vec<u8>,
+    pub l2_fields: Option<L2TxFields>,
 }
 
 impl DecodedTx {
@@ -69,6 +121,16 @@ impl DecodedTx {
                 let priority = self.fees.max_priority_fee_per_gas?;
                 Some(max_fee.min(base_fee.saturating_add(priority)))
             }
+            // Deposits buy their L2 gas on L1 and internal txs are free.
+            TxType::OpDeposit | TxType::ArbitrumDeposit | TxType::ArbitrumInternal => Some(0),
+            // Arbitrum has no priority tips, so the base fee is paid up to the cap.
+            TxType::ArbitrumUnsigned
+            | TxType::ArbitrumContract
+            | TxType::ArbitrumRetry
+            | TxType::ArbitrumSubmitRetryable => {
+                let cap = self.fees.max_fee_per_gas.or(self.fees.gas_price)?;
+                Some(cap.min(base_fee))
+            }
         }
     }
 }
@@ -85,6 +147,8 @@ pub enum DecodeError {
     },
     #[error("missing required fee field '{field}' for tx type")]
     MissingFeeField { field: &'static str },
+    #[error("tx type {tx_type:#04x} is not defined on chain {chain_id}")]
+    UnsupportedTxType { tx_type: u8, chain_id: u64 },
     #[error("json decode failed: {0}")]
     JsonDecode(#[from] serde_json::Error),
 }
@@ -92,6 +156,20 @@ pub enum DecodeError {
 /// Decodes one provider-facing transaction payload into the normalized
 /// representation used by the rest of the runtime.
 pub fn decode_from_raw(input: RawTxInput) -> Result<DecodedTx, DecodeError> {
+    // Network-specific types are only valid on the family that defines them,
+    // so an unexpected `0x7e` on mainnet is rejected instead of being treated
+    // as a deposit.
+    if let Some(family) = input.tx_type.family()
+        && family
+            != input
+                .chain_family
+                .unwrap_or_else(|| ChainFamily::from_chain_id(Some(input.chain_id)))
+    {
+        return Err(DecodeError::UnsupportedTxType {
+            tx_type: input.tx_type.type_byte(),
+            chain_id: input.chain_id,
+        });
+    }
     let fees = normalize_fees(&input)?;
     let hash = parse_fixed_hex::<32>(&input.hash, "hash")?;
     let sender = parse_fixed_hex::<20>(&input.sender, "sender")?;
@@ -123,6 +201,7 @@ pub fn decode_from_raw(input: RawTxInput) -> Result<DecodedTx, DecodeError> {
         gas_limit: input.gas_limit,
         fees,
         calldata,
+        l2_fields: input.l2_fields.filter(|fields| !fields.is_empty()),
     })
 }
 
@@ -176,10 +255,24 @@ fn normalize_fees(input: &RawTxInput) -> Result<NormalizedFees, DecodeError> {
                 max_fee_per_blob_gas: Some(blob_fee),
             })
         }
+        // System transactions are not priced like user transactions; keep
+        // whatever the provider reported without requiring any fee field.
+        TxType::OpDeposit
+        | TxType::ArbitrumDeposit
+        | TxType::ArbitrumUnsigned
+        | TxType::ArbitrumContract
+        | TxType::ArbitrumRetry
+        | TxType::ArbitrumSubmitRetryable
+        | TxType::ArbitrumInternal => Ok(NormalizedFees {
+            gas_price: input.gas_price,
+            max_fee_per_gas: input.max_fee_per_gas,
+            max_priority_fee_per_gas: None,
+            max_fee_per_blob_gas: None,
+        }),
     }
 }
 
-fn parse_fixed_hex<const N: usize>(
+pub(crate) fn parse_fixed_hex<const N: usize>(
     value: &str,
     field: &'static str,
 ) -> Result<[u8; N], DecodeError> {
@@ -192,7 +285,7 @@ fn parse_fixed_hex<const N: usize>(
     Ok(out)
 }
 
-fn parse_variable_hex(value: &str, field: &'static str) -> Result<Vec<u8>, DecodeError> {
+pub(crate) fn parse_variable_hex(value: &str, field: &'static str) -> Result<Vec<u8>, DecodeError> {
     let trimmed = value.strip_prefix("0x").unwrap_or(value);
     if trimmed.is_empty() {
         return Ok(Vec::new());
@@ -230,6 +323,8 @@ mod tests {
             max_priority_fee_per_gas: Some(3),
             max_fee_per_blob_gas: Some(5),
             calldata: Some("0xaabbccdd".to_owned()),
+            l2_fields: None,
+            chain_family: None,
         }
     }
 
@@ -269,6 +364,82 @@ mod tests {
         assert_eq!(decoded.effective_gas_price(10), Some(13));
     }
 
+    #[test]
+    fn rejects_network_types_on_chains_that_do_not_define_them() {
+        let mut deposit = sample_raw(TxType::OpDeposit);
+        deposit.chain_id = 1;
+        assert!(matches!(
+            decode_from_raw(deposit),
+            Err(DecodeError::UnsupportedTxType {
+                tx_type: 0x7e,
+                chain_id: 1
+            })
+        ));
+
+        let mut retry = sample_raw(TxType::ArbitrumRetry);
+        retry.chain_id = 8453;
+        assert!(matches!(
+            decode_from_raw(retry),
+            Err(DecodeError::UnsupportedTxType {
+                tx_type: 0x68,
+                chain_id: 8453
+            })
+        ));
+
+        let mut retry = sample_raw(TxType::ArbitrumRetry);
+        retry.chain_id = 42_161;
+        assert_eq!(
+            decode_from_raw(retry).expect("arbitrum retry").tx_type,
+            TxType::ArbitrumRetry
+        );
+
+        let mut dynamic_fee = sample_raw(TxType::Eip1559);
+        dynamic_fee.chain_id = 42_161;
+        assert!(decode_from_raw(dynamic_fee).is_ok());
+    }
+
+    #[test]
+    fn configured_chain_family_takes_precedence_over_chain_id_table() {
+        let mut deposit = sample_raw(TxType::OpDeposit);
+        deposit.chain_id = 901;
+        assert!(matches!(
+            decode_from_raw(deposit.clone()),
+            Err(DecodeError::UnsupportedTxType {
+                tx_type: 0x7e,
+                chain_id: 901
+            })
+        ));
+
+        deposit.chain_family = Some(ChainFamily::OpStack);
+        assert_eq!(
+            decode_from_raw(deposit).expect("op deposit").tx_type,
+            TxType::OpDeposit
+        );
+    }
+
+    #[test]
+    fn decodes_op_deposit_without_fee_fields() {
+        let mut input = sample_raw(TxType::OpDeposit);
+        input.chain_id = 8453;
+        input.gas_price = None;
+        input.max_fee_per_gas = None;
+        input.max_priority_fee_per_gas = None;
+        input.max_fee_per_blob_gas = None;
+        input.l2_fields = Some(L2TxFields {
+            source_hash: Some([0x44; 32]),
+            mint_wei: Some(1_000),
+            is_system_tx: Some(false),
+            ..L2TxFields::default()
+        });
+
+        let decoded = decode_from_raw(input).expect("deposit decode");
+        assert_eq!(decoded.tx_type, TxType::OpDeposit);
+        assert_eq!(decoded.effective_gas_price(100), Some(0));
+        let fields = decoded.l2_fields.expect("l2 fields");
+        assert_eq!(fields.source_hash, Some([0x44; 32]));
+        assert_eq!(fields.mint_wei, Some(1_000));
+    }
+
     #[test]
     fn requires_blob_fee_for_eip4844() {
         let mut input = sample_raw(TxType::Eip4844);
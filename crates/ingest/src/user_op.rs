@@ -0,0 +1,888 @@
+//! ERC-4337 user operation ingestion from bundler alt-mempools.
+//!
+//! User operations never enter the public tx pool; bundlers gossip them and
+//! later land them on-chain inside an EntryPoint `handleOps` transaction. This
+//! module decodes both sides so each operation is emitted once when first seen
+//! and again, linked to its bundle transaction, once that bundle is decoded.
+
+use crate::IngestError;
+use crate::rpc::IngestClock;
+use crate::tx_decode::{DecodeError, parse_fixed_hex, parse_variable_hex};
+use ahash::RandomState;
+use auto_impl::auto_impl;
+use common::abi::{
+    WORD, abi_address, abi_dynamic_bytes, abi_usize, abi_word, address_word, pack_u128_pair,
+    u128_word, unpack_u128_pair,
+};
+use common::{Address, SourceId, TxHash};
+use event_log::{EntryPointVersion, EventEnvelope, EventPayload, UserOpSeen};
+use hashbrown::HashSet;
+use serde::Deserialize;
+use sha3::{Digest, Keccak256};
+use std::collections::VecDeque;
+use thiserror::Error;
+
+type FastSet<T> = HashSet<T, RandomState>;
+type Result<T> = std::result::Result<T, IngestError>;
+
+/// Canonical EntryPoint v0.6 deployment address.
+pub const ENTRY_POINT_V06_ADDRESS: Address = [
+    0x5f, 0xf1, 0x37, 0xd4, 0xb0, 0xfd, 0xcd, 0x49, 0xdc, 0xa3, 0x0c, 0x7c, 0xf5, 0x7e, 0x57, 0x8a,
+    0x02, 0x6d, 0x27, 0x89,
+];
+/// Canonical EntryPoint v0.7 deployment address.
+pub const ENTRY_POINT_V07_ADDRESS: Address = [
+    0x00, 0x00, 0x00, 0x00, 0x71, 0x72, 0x7d, 0xe2, 0x2e, 0x5e, 0x9d, 0x8b, 0xaf, 0x0e, 0xda, 0xc6,
+    0xf3, 0x7d, 0xa0, 0x32,
+];
+/// `handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)`.
+pub const HANDLE_OPS_V06_SELECTOR: [u8; 4] = [0x1f, 0xad, 0x94, 0x8c];
+/// `handleOps((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes)[],address)`.
+pub const HANDLE_OPS_V07_SELECTOR: [u8; 4] = [0x76, 0x5e, 0x82, 0x7f];
+
+/// Returns the EntryPoint release deployed at `address`, if it is a known one.
+pub fn entry_point_version(address: Address) -> Option<EntryPointVersion> {
+    match address {
+        ENTRY_POINT_V06_ADDRESS => Some(EntryPointVersion::V06),
+        ENTRY_POINT_V07_ADDRESS => Some(EntryPointVersion::V07),
+        _ => None,
+    }
+}
+
+/// Errors raised while decoding bundler payloads or `handleOps` calldata.
+#[derive(Debug, Error)]
+pub enum UserOpDecodeError {
+    #[error(transparent)]
+    Field(#[from] DecodeError),
+    #[error("calldata is not a supported handleOps call")]
+    NotHandleOps,
+    #[error("malformed ABI data at offset {offset}")]
+    MalformedAbi { offset: usize },
+    #[error("value for field '{field}' does not fit in 128 bits")]
+    Overflow { field: &'static str },
+}
+
+/// User operation fields normalized across EntryPoint releases.
+///
+/// v0.7 packed fields are kept unpacked here: `init_code` is `factory ||
+/// factoryData` and `paymaster_and_data` is `paymaster || verificationGas ||
+/// postOpGas || paymasterData`, which is exactly the on-chain packing.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UserOperation {
+    pub version: EntryPointVersion,
+    pub sender: Address,
+    pub nonce: [u8; 32],
+    pub init_code: Vec<u8>,
+    pub call_data: Vec<u8>,
+    pub call_gas_limit: u128,
+    pub verification_gas_limit: u128,
+    pub pre_verification_gas: u128,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+    pub paymaster_and_data: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl UserOperation {
+    /// Returns the account factory when the operation deploys its sender.
+    pub fn factory(&self) -> Option<Address> {
+        leading_address(&self.init_code)
+    }
+
+    /// Returns the paymaster sponsoring the operation, if any.
+    pub fn paymaster(&self) -> Option<Address> {
+        leading_address(&self.paymaster_and_data)
+    }
+
+    /// Computes the EntryPoint `getUserOpHash` value for this operation.
+    pub fn hash(&self, entry_point: Address, chain_id: u64) -> TxHash {
+        let mut packed = Vec::with_capacity(10 * WORD);
+        packed.extend_from_slice(&address_word(self.sender));
+        packed.extend_from_slice(&self.nonce);
+        packed.extend_from_slice(&keccak(&self.init_code));
+        packed.extend_from_slice(&keccak(&self.call_data));
+        match self.version {
+            EntryPointVersion::V06 => {
+                packed.extend_from_slice(&u128_word(self.call_gas_limit));
+                packed.extend_from_slice(&u128_word(self.verification_gas_limit));
+                packed.extend_from_slice(&u128_word(self.pre_verification_gas));
+                packed.extend_from_slice(&u128_word(self.max_fee_per_gas));
+                packed.extend_from_slice(&u128_word(self.max_priority_fee_per_gas));
+            }
+            EntryPointVersion::V07 => {
+                packed.extend_from_slice(&pack_u128_pair(
+                    self.verification_gas_limit,
+                    self.call_gas_limit,
+                ));
+                packed.extend_from_slice(&u128_word(self.pre_verification_gas));
+                packed.extend_from_slice(&pack_u128_pair(
+                    self.max_priority_fee_per_gas,
+                    self.max_fee_per_gas,
+                ));
+            }
+        }
+        packed.extend_from_slice(&keccak(&self.paymaster_and_data));
+
+        let mut outer = Vec::with_capacity(3 * WORD);
+        outer.extend_from_slice(&keccak(&packed));
+        outer.extend_from_slice(&address_word(entry_point));
+        outer.extend_from_slice(&u128_word(u128::from(chain_id)));
+        keccak(&outer)
+    }
+
+    /// Builds the event-log payload for this operation.
+    pub fn to_event(
+        &self,
+        entry_point: Address,
+        chain_id: u64,
+        bundle_tx_hash: Option<TxHash>,
+    ) -> UserOpSeen {
+        UserOpSeen {
+            user_op_hash: self.hash(entry_point, chain_id),
+            entry_point,
+            entry_point_version: self.version,
+            sender: self.sender,
+            nonce: self.nonce,
+            chain_id: Some(chain_id),
+            factory: self.factory(),
+            paymaster: self.paymaster(),
+            call_gas_limit: self.call_gas_limit,
+            verification_gas_limit: self.verification_gas_limit,
+            pre_verification_gas: self.pre_verification_gas,
+            max_fee_per_gas_wei: self.max_fee_per_gas,
+            max_priority_fee_per_gas_wei: self.max_priority_fee_per_gas,
+            call_data_len: self.call_data.len() as u32,
+            bundle_tx_hash,
+            call_data: self.call_data.clone(),
+        }
+    }
+}
+
+/// Bundler JSON-RPC user operation in either the v0.6 or unpacked v0.7 shape.
+///
+/// Quantity fields are `0x`-prefixed hex as returned by `eth_sendUserOperation`
+/// and `debug_bundler_dumpMempool`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcUserOperation {
+    pub sender: String,
+    pub nonce: String,
+    #[serde(default)]
+    pub init_code: Option<String>,
+    #[serde(default)]
+    pub factory: Option<String>,
+    #[serde(default)]
+    pub factory_data: Option<String>,
+    pub call_data: String,
+    pub call_gas_limit: String,
+    pub verification_gas_limit: String,
+    pub pre_verification_gas: String,
+    pub max_fee_per_gas: String,
+    pub max_priority_fee_per_gas: String,
+    #[serde(default)]
+    pub paymaster_and_data: Option<String>,
+    #[serde(default)]
+    pub paymaster: Option<String>,
+    #[serde(default)]
+    pub paymaster_verification_gas_limit: Option<String>,
+    #[serde(default)]
+    pub paymaster_post_op_gas_limit: Option<String>,
+    #[serde(default)]
+    pub paymaster_data: Option<String>,
+    #[serde(default)]
+    pub signature: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MempoolEntry {
+    Wrapped {
+        #[serde(rename = "userOp")]
+        user_op: RpcUserOperation,
+    },
+    Bare(RpcUserOperation),
+}
+
+/// Normalizes one bundler JSON-RPC user operation for the given EntryPoint
+/// release.
+pub fn decode_rpc_user_operation(
+    version: EntryPointVersion,
+    op: &RpcUserOperation,
+) -> std::result::Result<UserOperation, UserOpDecodeError> {
+    let (init_code, paymaster_and_data) = match version {
+        EntryPointVersion::V06 => (
+            optional_hex(op.init_code.as_deref(), "initCode")?,
+            optional_hex(op.paymaster_and_data.as_deref(), "paymasterAndData")?,
+        ),
+        EntryPointVersion::V07 => {
+            let mut init_code = Vec::new();
+            if let Some(factory) = op.factory.as_deref().filter(|value| !is_empty_hex(value)) {
+                init_code.extend_from_slice(&parse_fixed_hex::<20>(factory, "factory")?);
+                init_code.extend(optional_hex(op.factory_data.as_deref(), "factoryData")?);
+            }
+            let mut paymaster_and_data = Vec::new();
+            if let Some(paymaster) = op.paymaster.as_deref().filter(|value| !is_empty_hex(value)) {
+                paymaster_and_data
+                    .extend_from_slice(&parse_fixed_hex::<20>(paymaster, "paymaster")?);
+                paymaster_and_data.extend_from_slice(
+                    &optional_quantity(
+                        op.paymaster_verification_gas_limit.as_deref(),
+                        "paymasterVerificationGasLimit",
+                    )?
+                    .to_be_bytes(),
+                );
+                paymaster_and_data.extend_from_slice(
+                    &optional_quantity(
+                        op.paymaster_post_op_gas_limit.as_deref(),
+                        "paymasterPostOpGasLimit",
+                    )?
+                    .to_be_bytes(),
+                );
+                paymaster_and_data
+                    .extend(optional_hex(op.paymaster_data.as_deref(), "paymasterData")?);
+            }
+            (init_code, paymaster_and_data)
+        }
+    };
+
+    Ok(UserOperation {
+        version,
+        sender: parse_fixed_hex::<20>(&op.sender, "sender")?,
+        nonce: parse_quantity_word(&op.nonce, "nonce")?,
+        init_code,
+        call_data: parse_variable_hex(&op.call_data, "callData")?,
+        call_gas_limit: parse_quantity(&op.call_gas_limit, "callGasLimit")?,
+        verification_gas_limit: parse_quantity(&op.verification_gas_limit, "verificationGasLimit")?,
+        pre_verification_gas: parse_quantity(&op.pre_verification_gas, "preVerificationGas")?,
+        max_fee_per_gas: parse_quantity(&op.max_fee_per_gas, "maxFeePerGas")?,
+        max_priority_fee_per_gas: parse_quantity(
+            &op.max_priority_fee_per_gas,
+            "maxPriorityFeePerGas",
+        )?,
+        paymaster_and_data,
+        signature: parse_variable_hex(&op.signature, "signature")?,
+    })
+}
+
+/// Decodes a `debug_bundler_dumpMempool` result, accepting both bare user
+/// operations and `{ "userOp": ... }` wrapped entries.
+pub fn decode_bundler_mempool_dump(
+    version: EntryPointVersion,
+    json: &str,
+) -> std::result::Result<Vec<UserOperation>, UserOpDecodeError> {
+    let entries: Vec<MempoolEntry> = serde_json::from_str(json).map_err(DecodeError::from)?;
+    entries
+        .iter()
+        .map(|entry| match entry {
+            MempoolEntry::Wrapped { user_op } | MempoolEntry::Bare(user_op) => {
+                decode_rpc_user_operation(version, user_op)
+            }
+        })
+        .collect()
+}
+
+/// Decodes the user operations carried by an EntryPoint `handleOps` call.
+pub fn decode_handle_ops(
+    calldata: &[u8],
+) -> std::result::Result<Vec<UserOperation>, UserOpDecodeError> {
+    let version = match calldata.get(..4) {
+        Some(selector) if selector == HANDLE_OPS_V06_SELECTOR => EntryPointVersion::V06,
+        Some(selector) if selector == HANDLE_OPS_V07_SELECTOR => EntryPointVersion::V07,
+        _ => return Err(UserOpDecodeError::NotHandleOps),
+    };
+    let args = &calldata[4..];
+    let ops_offset = read(0, abi_usize(args, 0))?;
+    let count = read(ops_offset, abi_usize(args, ops_offset))?;
+    let elements = ops_offset + WORD;
+
+    // Each bound check below is against attacker-controlled offsets, so every
+    // read goes through the checked helpers rather than slicing directly.
+    let mut ops = Vec::with_capacity(count.min(args.len() / WORD));
+    for index in 0..count {
+        let head = elements + index * WORD;
+        let tuple_offset = elements.saturating_add(read(head, abi_usize(args, head))?);
+        let tuple = args
+            .get(tuple_offset..)
+            .ok_or(UserOpDecodeError::MalformedAbi {
+                offset: tuple_offset,
+            })?;
+        ops.push(decode_packed_user_op(version, tuple)?);
+    }
+    Ok(ops)
+}
+
+fn decode_packed_user_op(
+    version: EntryPointVersion,
+    tuple: &[u8],
+) -> std::result::Result<UserOperation, UserOpDecodeError> {
+    let sender = read(0, abi_address(tuple, 0))?;
+    let nonce = *read(WORD, abi_word(tuple, WORD))?;
+    let init_code = read_bytes(tuple, 2 * WORD)?;
+    let call_data = read_bytes(tuple, 3 * WORD)?;
+
+    match version {
+        EntryPointVersion::V06 => Ok(UserOperation {
+            version,
+            sender,
+            nonce,
+            init_code,
+            call_data,
+            call_gas_limit: read_u128(tuple, 4 * WORD, "callGasLimit")?,
+            verification_gas_limit: read_u128(tuple, 5 * WORD, "verificationGasLimit")?,
+            pre_verification_gas: read_u128(tuple, 6 * WORD, "preVerificationGas")?,
+            max_fee_per_gas: read_u128(tuple, 7 * WORD, "maxFeePerGas")?,
+            max_priority_fee_per_gas: read_u128(tuple, 8 * WORD, "maxPriorityFeePerGas")?,
+            paymaster_and_data: read_bytes(tuple, 9 * WORD)?,
+            signature: read_bytes(tuple, 10 * WORD)?,
+        }),
+        EntryPointVersion::V07 => {
+            let (verification_gas_limit, call_gas_limit) =
+                unpack_u128_pair(read(4 * WORD, abi_word(tuple, 4 * WORD))?);
+            let (max_priority_fee_per_gas, max_fee_per_gas) =
+                unpack_u128_pair(read(6 * WORD, abi_word(tuple, 6 * WORD))?);
+            Ok(UserOperation {
+                version,
+                sender,
+                nonce,
+                init_code,
+                call_data,
+                call_gas_limit,
+                verification_gas_limit,
+                pre_verification_gas: read_u128(tuple, 5 * WORD, "preVerificationGas")?,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                paymaster_and_data: read_bytes(tuple, 7 * WORD)?,
+                signature: read_bytes(tuple, 8 * WORD)?,
+            })
+        }
+    }
+}
+
+/// User operation observed by a bundler source together with its EntryPoint.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingUserOp {
+    pub entry_point: Address,
+    pub user_op: UserOperation,
+}
+
+/// Source of alt-mempool user operations, such as a bundler RPC poller or a
+/// p2p mempool subscriber.
+#[auto_impl(&mut, Box)]
+pub trait UserOpProvider {
+    /// Returns the next batch of user operations observed by the provider.
+    fn pending_user_ops(&mut self) -> Result<Vec<PendingUserOp>>;
+}
+
+/// In-memory provider used by tests and as a stand-in for the p2p mempool.
+#[derive(Default)]
+pub struct InMemoryUserOpProvider {
+    batches: VecDeque<Vec<PendingUserOp>>,
+}
+
+impl InMemoryUserOpProvider {
+    /// Creates a provider from pre-seeded user operation batches.
+    pub fn new(batches: Vec<Vec<PendingUserOp>>) -> Self {
+        Self {
+            batches: batches.into_iter().collect(),
+        }
+    }
+
+    /// Queues one more batch, e.g. from a gossip message.
+    pub fn push_batch(&mut self, batch: Vec<PendingUserOp>) {
+        self.batches.push_back(batch);
+    }
+}
+
+impl UserOpProvider for InMemoryUserOpProvider {
+    fn pending_user_ops(&mut self) -> Result<Vec<PendingUserOp>> {
+        Ok(self.batches.pop_front().unwrap_or_default())
+    }
+}
+
+/// Tuning knobs for the user operation ingest loop.
+#[derive(Clone, Debug)]
+pub struct UserOpIngestConfig {
+    /// Chain id folded into user operation hashes.
+    pub chain_id: u64,
+    /// Maximum number of user operation hashes retained for dedup.
+    pub max_seen_user_ops: usize,
+}
+
+impl Default for UserOpIngestConfig {
+    fn default() -> Self {
+        Self {
+            chain_id: 1,
+            max_seen_user_ops: 250_000,
+        }
+    }
+}
+
+/// Ingest service that turns alt-mempool user operations and `handleOps`
+/// bundles into `UserOpSeen` events.
+pub struct UserOpIngestService<P, C> {
+    provider: P,
+    clock: C,
+    source_id: SourceId,
+    config: UserOpIngestConfig,
+    seen_user_ops: FastSet<TxHash>,
+    seen_order: VecDeque<TxHash>,
+    next_seq_id: u64,
+}
+
+impl<P, C> UserOpIngestService<P, C>
+where
+    P: UserOpProvider,
+    C: IngestClock,
+{
+    /// Creates a service with explicit chain and dedup settings.
+    pub fn with_config(
+        provider: P,
+        source_id: SourceId,
+        clock: C,
+        config: UserOpIngestConfig,
+    ) -> Self {
+        Self {
+            provider,
+            clock,
+            source_id,
+            config: UserOpIngestConfig {
+                max_seen_user_ops: config.max_seen_user_ops.max(1),
+                ..config
+            },
+            seen_user_ops: FastSet::default(),
+            seen_order: VecDeque::new(),
+            next_seq_id: 1,
+        }
+    }
+
+    /// Returns the provider so callers can feed batches they fetched themselves.
+    pub fn provider_mut(&mut self) -> &mut P {
+        &mut self.provider
+    }
+
+    /// Polls the provider once and emits events for operations not seen before.
+    pub fn process_pending_user_ops(&mut self) -> Result<Vec<EventEnvelope>> {
+        let pending = self
+            .provider
+            .pending_user_ops()
+            .map_err(IngestError::into_rpc_connect)?;
+
+        let mut events = Vec::new();
+        for PendingUserOp {
+            entry_point,
+            user_op,
+        } in pending
+        {
+            let seen = user_op.to_event(entry_point, self.config.chain_id, None);
+            // Bundlers re-gossip operations until they land, so repeats are
+            // expected and are skipped silently rather than reported as drops.
+            if self.remember_user_op(seen.user_op_hash) {
+                events.push(self.new_event(EventPayload::UserOpSeen(seen)));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Links user operations to the bundle transaction that carried them.
+    ///
+    /// Returns no events for transactions that are not `handleOps` calls to a
+    /// known EntryPoint. Every bundled operation is emitted, including ones
+    /// that never passed through the observed alt-mempool.
+    pub fn process_bundle_transaction(
+        &mut self,
+        tx_hash: TxHash,
+        to: Option<Address>,
+        calldata: &[u8],
+    ) -> std::result::Result<Vec<EventEnvelope>, UserOpDecodeError> {
+        let Some(entry_point) = to.filter(|address| entry_point_version(*address).is_some()) else {
+            return Ok(Vec::new());
+        };
+        let ops = match decode_handle_ops(calldata) {
+            Ok(ops) => ops,
+            Err(UserOpDecodeError::NotHandleOps) => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut events = Vec::with_capacity(ops.len());
+        for op in ops {
+            let seen = op.to_event(entry_point, self.config.chain_id, Some(tx_hash));
+            self.remember_user_op(seen.user_op_hash);
+            events.push(self.new_event(EventPayload::UserOpSeen(seen)));
+        }
+        Ok(events)
+    }
+
+    fn remember_user_op(&mut self, hash: TxHash) -> bool {
+        if !self.seen_user_ops.insert(hash) {
+            return false;
+        }
+        self.seen_order.push_back(hash);
+        while self.seen_order.len() > self.config.max_seen_user_ops {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen_user_ops.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn new_event(&mut self, payload: EventPayload) -> EventEnvelope {
+        let seq_id = self.next_seq_id;
+        self.next_seq_id += 1;
+
+        EventEnvelope {
+            seq_id,
+            ingest_ts_unix_ms: self.clock.now_unix_ms(),
+            ingest_ts_mono_ns: self.clock.now_mono_ns(),
+            source_id: self.source_id.clone(),
+            payload,
+            chain_id: Some(self.config.chain_id),
+            chain_seq_id: None,
+            hash_link: None,
+        }
+    }
+}
+
+fn keccak(bytes: &[u8]) -> [u8; 32] {
+    Keccak256::digest(bytes).into()
+}
+
+fn leading_address(bytes: &[u8]) -> Option<Address> {
+    let mut out = [0_u8; 20];
+    out.copy_from_slice(bytes.get(..20)?);
+    Some(out)
+}
+
+/// Maps a failed bounds-checked ABI read at `offset` onto a decode error.
+fn read<T>(offset: usize, value: Option<T>) -> std::result::Result<T, UserOpDecodeError> {
+    value.ok_or(UserOpDecodeError::MalformedAbi { offset })
+}
+
+fn read_u128(
+    data: &[u8],
+    offset: usize,
+    field: &'static str,
+) -> std::result::Result<u128, UserOpDecodeError> {
+    let (high, low) = unpack_u128_pair(read(offset, abi_word(data, offset))?);
+    if high != 0 {
+        return Err(UserOpDecodeError::Overflow { field });
+    }
+    Ok(low)
+}
+
+fn read_bytes(data: &[u8], head_offset: usize) -> std::result::Result<Vec<u8>, UserOpDecodeError> {
+    read(head_offset, abi_dynamic_bytes(data, head_offset)).map(<[u8]>::to_vec)
+}
+
+fn is_empty_hex(value: &str) -> bool {
+    value.strip_prefix("0x").unwrap_or(value).is_empty()
+}
+
+fn optional_hex(
+    value: Option<&str>,
+    field: &'static str,
+) -> std::result::Result<Vec<u8>, UserOpDecodeError> {
+    match value {
+        Some(value) => Ok(parse_variable_hex(value, field)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn optional_quantity(
+    value: Option<&str>,
+    field: &'static str,
+) -> std::result::Result<u128, UserOpDecodeError> {
+    value.map_or(Ok(0), |value| parse_quantity(value, field))
+}
+
+fn parse_quantity(
+    value: &str,
+    field: &'static str,
+) -> std::result::Result<u128, UserOpDecodeError> {
+    let word = parse_quantity_word(value, field)?;
+    let (high, low) = unpack_u128_pair(&word);
+    if high != 0 {
+        return Err(UserOpDecodeError::Overflow { field });
+    }
+    Ok(low)
+}
+
+fn parse_quantity_word(
+    value: &str,
+    field: &'static str,
+) -> std::result::Result<[u8; 32], UserOpDecodeError> {
+    // JSON-RPC quantities drop leading zeroes, so left-pad to an even,
+    // word-sized hex string before reusing the fixed-width parser.
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.len() > 2 * WORD {
+        return Err(DecodeError::InvalidLength {
+            field,
+            expected: WORD,
+        }
+        .into());
+    }
+    let padded = format!("{digits:0>64}");
+    Ok(parse_fixed_hex::<32>(&padded, field)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct StepClock {
+        unix_ms: i64,
+        mono_ns: u64,
+    }
+
+    impl IngestClock for StepClock {
+        fn now_unix_ms(&mut self) -> i64 {
+            self.unix_ms += 1;
+            self.unix_ms
+        }
+
+        fn now_mono_ns(&mut self) -> u64 {
+            self.mono_ns += 1;
+            self.mono_ns
+        }
+    }
+
+    fn selector(signature: &str) -> [u8; 4] {
+        let digest = keccak(signature.as_bytes());
+        [digest[0], digest[1], digest[2], digest[3]]
+    }
+
+    fn sample_op(version: EntryPointVersion, nonce: u8) -> UserOperation {
+        let mut paymaster_and_data = vec![0x77; 20];
+        if version == EntryPointVersion::V07 {
+            paymaster_and_data.extend_from_slice(&50_000_u128.to_be_bytes());
+            paymaster_and_data.extend_from_slice(&10_000_u128.to_be_bytes());
+        }
+        paymaster_and_data.extend_from_slice(&[0xde, 0xad]);
+        let mut nonce_word = [0_u8; 32];
+        nonce_word[31] = nonce;
+        UserOperation {
+            version,
+            sender: [0x11; 20],
+            nonce: nonce_word,
+            init_code: Vec::new(),
+            call_data: vec![0xb6, 0x1d, 0x27, 0xf6, 0x01, 0x02],
+            call_gas_limit: 120_000,
+            verification_gas_limit: 90_000,
+            pre_verification_gas: 48_000,
+            max_fee_per_gas: 3_000_000_000,
+            max_priority_fee_per_gas: 1_500_000_000,
+            paymaster_and_data,
+            signature: vec![0x99; 65],
+        }
+    }
+
+    fn encode_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+        out.extend_from_slice(&u128_word(bytes.len() as u128));
+        out.extend_from_slice(bytes);
+        out.resize(out.len() + (WORD - bytes.len() % WORD) % WORD, 0);
+    }
+
+    fn encode_packed_op(op: &UserOperation) -> Vec<u8> {
+        let mut head = Vec::new();
+        head.push(address_word(op.sender));
+        head.push(op.nonce);
+        let dynamic: [&[u8]; 4] = [
+            &op.init_code,
+            &op.call_data,
+            &op.paymaster_and_data,
+            &op.signature,
+        ];
+        let static_words: Vec<[u8; 32]> = match op.version {
+            EntryPointVersion::V06 => vec![
+                u128_word(op.call_gas_limit),
+                u128_word(op.verification_gas_limit),
+                u128_word(op.pre_verification_gas),
+                u128_word(op.max_fee_per_gas),
+                u128_word(op.max_priority_fee_per_gas),
+            ],
+            EntryPointVersion::V07 => vec![
+                pack_u128_pair(op.verification_gas_limit, op.call_gas_limit),
+                u128_word(op.pre_verification_gas),
+                pack_u128_pair(op.max_priority_fee_per_gas, op.max_fee_per_gas),
+            ],
+        };
+        let head_len = (2 + dynamic.len() + static_words.len()) * WORD;
+        let mut tail = Vec::new();
+        let mut offsets = Vec::new();
+        for bytes in &dynamic {
+            offsets.push(u128_word((head_len + tail.len()) as u128));
+            encode_bytes(&mut tail, bytes);
+        }
+        head.push(offsets[0]);
+        head.push(offsets[1]);
+        head.extend(static_words);
+        head.push(offsets[2]);
+        head.push(offsets[3]);
+
+        let mut out = head.concat();
+        out.extend(tail);
+        out
+    }
+
+    fn encode_handle_ops(version: EntryPointVersion, ops: &[UserOperation]) -> Vec<u8> {
+        let selector = match version {
+            EntryPointVersion::V06 => HANDLE_OPS_V06_SELECTOR,
+            EntryPointVersion::V07 => HANDLE_OPS_V07_SELECTOR,
+        };
+        let encoded = ops.iter().map(encode_packed_op).collect::<Vec<_>>();
+        let mut out = selector.to_vec();
+        out.extend_from_slice(&u128_word(64));
+        out.extend_from_slice(&address_word([0xbe; 20]));
+        out.extend_from_slice(&u128_word(ops.len() as u128));
+        let mut offset = ops.len() * WORD;
+        for op in &encoded {
+            out.extend_from_slice(&u128_word(offset as u128));
+            offset += op.len();
+        }
+        for op in encoded {
+            out.extend(op);
+        }
+        out
+    }
+
+    #[test]
+    fn handle_ops_selectors_match_entry_point_abi() {
+        assert_eq!(
+            selector(
+                "handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)"
+            ),
+            HANDLE_OPS_V06_SELECTOR
+        );
+        assert_eq!(
+            selector(
+                "handleOps((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes)[],address)"
+            ),
+            HANDLE_OPS_V07_SELECTOR
+        );
+    }
+
+    #[test]
+    fn decodes_v06_and_v07_handle_ops_bundles() {
+        for version in [EntryPointVersion::V06, EntryPointVersion::V07] {
+            let ops = vec![sample_op(version, 1), sample_op(version, 2)];
+            let decoded = decode_handle_ops(&encode_handle_ops(version, &ops)).expect("decode");
+            assert_eq!(decoded, ops);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_handle_ops_calldata() {
+        let ops = vec![sample_op(EntryPointVersion::V07, 1)];
+        let calldata = encode_handle_ops(EntryPointVersion::V07, &ops);
+        let err = decode_handle_ops(&calldata[..calldata.len() - 40]).expect_err("truncated");
+        assert!(matches!(err, UserOpDecodeError::MalformedAbi { .. }));
+        assert!(matches!(
+            decode_handle_ops(&[0xa9, 0x05, 0x9c, 0xbb]),
+            Err(UserOpDecodeError::NotHandleOps)
+        ));
+    }
+
+    #[test]
+    fn decodes_unpacked_v07_rpc_user_operation_into_packed_fields() {
+        let json = r#"[{"userOp":{
+            "sender":"0x1111111111111111111111111111111111111111",
+            "nonce":"0x2",
+            "callData":"0xb61d27f60102",
+            "callGasLimit":"0x1d4c0",
+            "verificationGasLimit":"0x15f90",
+            "preVerificationGas":"0xbb80",
+            "maxFeePerGas":"0xb2d05e00",
+            "maxPriorityFeePerGas":"0x59682f00",
+            "paymaster":"0x7777777777777777777777777777777777777777",
+            "paymasterVerificationGasLimit":"0xc350",
+            "paymasterPostOpGasLimit":"0x2710",
+            "paymasterData":"0xdead",
+            "signature":"0x"
+        }}]"#;
+
+        let decoded =
+            decode_bundler_mempool_dump(EntryPointVersion::V07, json).expect("decode dump");
+        let mut expected = sample_op(EntryPointVersion::V07, 2);
+        expected.signature.clear();
+        assert_eq!(decoded, vec![expected]);
+        assert_eq!(decoded[0].paymaster(), Some([0x77; 20]));
+        assert_eq!(decoded[0].factory(), None);
+    }
+
+    #[test]
+    fn user_op_hash_binds_entry_point_and_chain() {
+        let op = sample_op(EntryPointVersion::V06, 1);
+        let base = op.hash(ENTRY_POINT_V06_ADDRESS, 1);
+        assert_ne!(base, op.hash(ENTRY_POINT_V06_ADDRESS, 10));
+        assert_ne!(base, op.hash(ENTRY_POINT_V07_ADDRESS, 1));
+        assert_ne!(
+            base,
+            sample_op(EntryPointVersion::V06, 2).hash(ENTRY_POINT_V06_ADDRESS, 1)
+        );
+    }
+
+    #[test]
+    fn service_dedups_alt_mempool_ops_and_links_bundle() {
+        let version = EntryPointVersion::V07;
+        let first = sample_op(version, 1);
+        let second = sample_op(version, 2);
+        let pending = |op: &UserOperation| PendingUserOp {
+            entry_point: ENTRY_POINT_V07_ADDRESS,
+            user_op: op.clone(),
+        };
+        let provider = InMemoryUserOpProvider::new(vec![
+            vec![pending(&first), pending(&first)],
+            vec![pending(&first)],
+        ]);
+        let mut service = UserOpIngestService::with_config(
+            provider,
+            SourceId::new("bundler-base"),
+            StepClock::default(),
+            UserOpIngestConfig {
+                chain_id: 8453,
+                max_seen_user_ops: 16,
+            },
+        );
+
+        let first_poll = service.process_pending_user_ops().expect("poll one");
+        assert_eq!(first_poll.len(), 1);
+        assert!(
+            service
+                .process_pending_user_ops()
+                .expect("poll two")
+                .is_empty()
+        );
+
+        let bundle_hash = [0xaa; 32];
+        let calldata = encode_handle_ops(version, &[first.clone(), second.clone()]);
+        assert!(
+            service
+                .process_bundle_transaction(bundle_hash, Some([0x01; 20]), &calldata)
+                .expect("non entry point")
+                .is_empty()
+        );
+        let linked = service
+            .process_bundle_transaction(bundle_hash, Some(ENTRY_POINT_V07_ADDRESS), &calldata)
+            .expect("bundle");
+
+        let linked = linked
+            .iter()
+            .map(|event| match &event.payload {
+                EventPayload::UserOpSeen(seen) => seen.clone(),
+                other => panic!("unexpected payload {other:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(linked.len(), 2);
+        assert!(
+            linked
+                .iter()
+                .all(|seen| seen.bundle_tx_hash == Some(bundle_hash))
+        );
+        assert_eq!(
+            linked[0].user_op_hash,
+            first.hash(ENTRY_POINT_V07_ADDRESS, 8453)
+        );
+        assert_eq!(linked[0].user_op_hash, first_poll[0].payload.primary_hash());
+        assert_eq!(linked[1].paymaster, Some([0x77; 20]));
+    }
+}
@@ -1,5 +1,5 @@
 use common::{SourceId, TxHash};
-use event_log::{EventEnvelope, EventPayload};
+use event_log::{DropReason, EventEnvelope, EventPayload};
 use ingest::{Devp2pRuntime, P2pIngestConfig, P2pRuntime, P2pTxPayload};
 
 fn hash(value: u8) -> TxHash {
@@ -22,6 +22,7 @@ async fn devp2p_runtime_emits_seen_fetch_decode_and_tracks_backpressure() {
         P2pIngestConfig {
             fetch_queue_capacity: 1,
             max_seen_hashes: 64,
+            chain_id: None,
         },
         SourceId::new("p2p-runtime"),
     );
@@ -34,8 +35,8 @@ async fn devp2p_runtime_emits_seen_fetch_decode_and_tracks_backpressure() {
         seen_events[1].payload,
         EventPayload::TxDropped(ref dropped)
             if dropped.hash == hash(2)
-                && dropped.reason.contains("QueueFull")
-                && dropped.reason.contains("queue=p2p.fetch")
+                && dropped.reason == DropReason::QueueFull
+                && dropped.detail.as_deref().is_some_and(|detail| detail.contains("queue=p2p.fetch"))
     ));
     assert_eq!(runtime.metrics().queue_dropped_total, 1);
 
@@ -5,9 +5,10 @@ use runtime_core::{RuntimeCore, RuntimeCoreHandle, RuntimeCoreStartArgs};
 
 use crate::handle::{NodeRuntime, ShutdownHook};
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 /// Ingest mode requested for the node runtime.
 pub enum IngestMode {
+    #[default]
     Rpc,
     P2p,
     Hybrid,
@@ -32,12 +33,6 @@ impl IngestMode {
     }
 }
 
-impl Default for IngestMode {
-    fn default() -> Self {
-        Self::Rpc
-    }
-}
-
 type StartupHookFactory =
     dyn FnOnce(Option<RuntimeCoreHandle>) -> Result<Option<ShutdownHook>> + Send;
 
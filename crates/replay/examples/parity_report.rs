@@ -1,6 +1,7 @@
 use common::{Address, BlockHash, SourceId, TxHash};
 use event_log::{
-    EventEnvelope, EventPayload, TxConfirmed, TxDecoded, TxDropped, TxReorged, TxReplaced,
+    DropReason, EventEnvelope, EventPayload, TxConfirmed, TxDecoded, TxDropped, TxReorged,
+    TxReplaced,
 };
 use replay::lifecycle_parity;
 use serde::Serialize;
@@ -148,7 +149,8 @@ fn synthetic_event_window() -> (Vec<EventEnvelope>, Vec<u64>) {
                     source_id.clone(),
                     EventPayload::TxDropped(TxDropped {
                         hash,
-                        reason: "evicted_low_tip".to_owned(),
+                        reason: DropReason::Evicted,
+                        detail: Some("low_tip".to_owned()),
                     }),
                 ),
             );
@@ -238,6 +240,9 @@ fn envelope(
         ingest_ts_mono_ns: ts_mono_ns,
         source_id,
         payload,
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -0,0 +1,568 @@
+//! Counterfactual replay: re-executes recorded transactions through scheduler
+//! admission, searcher ranking, deterministic simulation and block assembly
+//! under an alternative config, then diffs the outcome against what
+//! production emitted.
+//!
+//! Transactions are re-admitted at the point production admitted or dropped
+//! them (`TxDecoded`, or a scheduler-policy `TxDropped`), in deterministic
+//! event order. Simulation runs in synthetic deterministic mode rather than
+//! against chain state, so assembly decisions differ from production wherever
+//! a remote simulation failed or timed out.
+
+use crate::ReplayError;
+use builder::{AssemblyCandidate, AssemblyConfig, AssemblyDecision, AssemblyEngine};
+use common::TxHash;
+use event_log::{AssemblyDecisionKind, DropReason, EventEnvelope, EventPayload, TxDecoded};
+use scheduler::{
+    InlineScheduler, SchedulerAdmission, SchedulerCandidate, SchedulerConfig, SchedulerQueueState,
+    SchedulerSimulationResult, ValidatedTransaction,
+};
+use searcher::{OpportunityCandidate, SearcherConfig, SearcherInputTx, rank_opportunity_batch};
+use serde::{Deserialize, Serialize};
+use sim_engine::{
+    ChainContext, SimulationBatchResult, SimulationMode, SimulationTxInput, simulate_with_mode,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Pipeline configuration a counterfactual replay re-executes with.
+///
+/// Every section defaults to the live pipeline's settings, so a config file
+/// only needs the fields being tuned.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CounterfactualConfig {
+    pub scheduler: SchedulerConfig,
+    pub searcher: SearcherConfig,
+    pub assembly: AssemblyConfig,
+}
+
+impl Default for CounterfactualConfig {
+    fn default() -> Self {
+        Self {
+            scheduler: SchedulerConfig::default(),
+            searcher: SearcherConfig::live(),
+            assembly: AssemblyConfig::default(),
+        }
+    }
+}
+
+/// Decoded fields and calldata recorded for one transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedTransaction {
+    pub decoded: TxDecoded,
+    pub calldata: Vec<u8>,
+}
+
+/// Scheduler admission decision, without the replaced hash.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdmissionKind {
+    Admitted,
+    Replaced,
+    Duplicate,
+    UnderpricedReplacement,
+    SenderLimitReached,
+}
+
+impl From<SchedulerAdmission> for AdmissionKind {
+    fn from(admission: SchedulerAdmission) -> Self {
+        match admission {
+            SchedulerAdmission::Admitted => Self::Admitted,
+            SchedulerAdmission::Duplicate => Self::Duplicate,
+            SchedulerAdmission::Replaced { .. } => Self::Replaced,
+            SchedulerAdmission::UnderpricedReplacement => Self::UnderpricedReplacement,
+            SchedulerAdmission::SenderLimitReached => Self::SenderLimitReached,
+        }
+    }
+}
+
+/// Transaction admitted differently under the counterfactual config.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AdmissionChange {
+    pub hash: TxHash,
+    pub production: AdmissionKind,
+    pub counterfactual: AdmissionKind,
+}
+
+/// Candidate queued for simulation, as of its latest registration.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CandidateOutcome {
+    pub candidate_id: String,
+    pub tx_hash: TxHash,
+    pub member_tx_hashes: Vec<TxHash>,
+    pub strategy: String,
+    pub score: u32,
+}
+
+/// Candidate queued on both sides with a different score.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ScoreChange {
+    pub candidate_id: String,
+    pub tx_hash: TxHash,
+    pub production: u32,
+    pub counterfactual: u32,
+}
+
+/// Builder decision for a candidate, as of its latest insertion attempt.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AssemblyOutcome {
+    pub decision: AssemblyDecisionKind,
+    pub reason: Option<String>,
+}
+
+/// Candidate whose assembly decision differs; `None` means no decision.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AssemblyChange {
+    pub candidate_id: String,
+    pub tx_hash: TxHash,
+    pub production: Option<AssemblyOutcome>,
+    pub counterfactual: Option<AssemblyOutcome>,
+}
+
+/// Differences between production and a counterfactual re-execution.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CounterfactualReport {
+    pub config: CounterfactualConfig,
+    pub replayed_transactions: usize,
+    /// Transactions production admitted or dropped but whose calldata or
+    /// decoded fields were not recorded; their candidates are left out.
+    pub skipped_transactions: usize,
+    pub production_candidates: usize,
+    pub counterfactual_candidates: usize,
+    pub admissions_changed: Vec<AdmissionChange>,
+    pub candidates_added: Vec<CandidateOutcome>,
+    pub candidates_removed: Vec<CandidateOutcome>,
+    pub scores_changed: Vec<ScoreChange>,
+    pub assembly_changed: Vec<AssemblyChange>,
+}
+
+impl CounterfactualReport {
+    /// Returns whether the counterfactual config changed any outcome.
+    pub fn has_changes(&self) -> bool {
+        !(self.admissions_changed.is_empty()
+            && self.candidates_added.is_empty()
+            && self.candidates_removed.is_empty()
+            && self.scores_changed.is_empty()
+            && self.assembly_changed.is_empty())
+    }
+}
+
+/// Incremental counterfactual replay over events in deterministic order.
+///
+/// `recorded` supplies calldata, and for transactions production dropped
+/// before emitting `TxDecoded` also the decoded fields, by hash.
+pub struct CounterfactualReplay<F> {
+    config: CounterfactualConfig,
+    recorded: F,
+    scheduler: InlineScheduler,
+    assembly: AssemblyEngine,
+    replayed: usize,
+    skipped: BTreeSet<TxHash>,
+    production_replacements: BTreeSet<TxHash>,
+    admissions_changed: Vec<AdmissionChange>,
+    production_candidates: BTreeMap<String, CandidateOutcome>,
+    counterfactual_candidates: BTreeMap<String, CandidateOutcome>,
+    production_assembly: BTreeMap<String, (TxHash, AssemblyOutcome)>,
+    counterfactual_assembly: BTreeMap<String, (TxHash, AssemblyOutcome)>,
+}
+
+impl<F> CounterfactualReplay<F>
+where
+    F: FnMut(&TxHash) -> Option<RecordedTransaction>,
+{
+    pub fn new(config: CounterfactualConfig, recorded: F) -> Result<Self, ReplayError> {
+        Ok(Self {
+            config,
+            recorded,
+            scheduler: InlineScheduler::new(config.scheduler)?,
+            assembly: AssemblyEngine::new(config.assembly),
+            replayed: 0,
+            skipped: BTreeSet::new(),
+            production_replacements: BTreeSet::new(),
+            admissions_changed: Vec::new(),
+            production_candidates: BTreeMap::new(),
+            counterfactual_candidates: BTreeMap::new(),
+            production_assembly: BTreeMap::new(),
+            counterfactual_assembly: BTreeMap::new(),
+        })
+    }
+
+    /// Records what production emitted for `event` and re-executes the
+    /// transaction it admitted or dropped, if any.
+    pub fn apply_event(&mut self, event: &EventEnvelope) {
+        match &event.payload {
+            EventPayload::TxReplaced(replaced) => {
+                self.production_replacements.insert(replaced.replaced_by);
+            }
+            EventPayload::TxDecoded(decoded) => {
+                let production = if self.production_replacements.remove(&decoded.hash) {
+                    AdmissionKind::Replaced
+                } else {
+                    AdmissionKind::Admitted
+                };
+                match (self.recorded)(&decoded.hash) {
+                    Some(recorded) => {
+                        self.reexecute(event, decoded.clone(), recorded.calldata, production);
+                    }
+                    None => {
+                        self.skipped.insert(decoded.hash);
+                    }
+                }
+            }
+            EventPayload::TxDropped(dropped) => {
+                let production = match dropped.reason {
+                    DropReason::UnderpricedReplacement => AdmissionKind::UnderpricedReplacement,
+                    DropReason::SenderLimitReached => AdmissionKind::SenderLimitReached,
+                    _ => return,
+                };
+                match (self.recorded)(&dropped.hash) {
+                    Some(recorded) => {
+                        self.reexecute(event, recorded.decoded, recorded.calldata, production);
+                    }
+                    None => {
+                        self.skipped.insert(dropped.hash);
+                    }
+                }
+            }
+            EventPayload::CandidateQueued(queued) => {
+                self.production_candidates.insert(
+                    queued.candidate_id.clone(),
+                    CandidateOutcome {
+                        candidate_id: queued.candidate_id.clone(),
+                        tx_hash: queued.tx_hash,
+                        member_tx_hashes: queued.member_tx_hashes.clone(),
+                        strategy: queued.strategy.clone(),
+                        score: queued.score,
+                    },
+                );
+            }
+            EventPayload::AssemblyDecisionApplied(applied) => {
+                self.production_assembly.insert(
+                    applied.candidate_id.clone(),
+                    (
+                        applied.tx_hash,
+                        AssemblyOutcome {
+                            decision: applied.decision.clone(),
+                            reason: applied.reason.clone(),
+                        },
+                    ),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Diffs everything replayed so far.
+    pub fn finish(self) -> CounterfactualReport {
+        let skipped = &self.skipped;
+        let touches_skipped = |candidate: &CandidateOutcome| {
+            skipped.contains(&candidate.tx_hash)
+                || candidate
+                    .member_tx_hashes
+                    .iter()
+                    .any(|hash| skipped.contains(hash))
+        };
+        let production_candidates = self
+            .production_candidates
+            .into_iter()
+            .filter(|(_, candidate)| !touches_skipped(candidate))
+            .collect::<BTreeMap<_, _>>();
+        let production_assembly = self
+            .production_assembly
+            .into_iter()
+            .filter(|(_, (tx_hash, _))| !skipped.contains(tx_hash))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut candidates_removed = Vec::new();
+        let mut scores_changed = Vec::new();
+        for (candidate_id, production) in &production_candidates {
+            match self.counterfactual_candidates.get(candidate_id) {
+                None => candidates_removed.push(production.clone()),
+                Some(counterfactual) if counterfactual.score != production.score => {
+                    scores_changed.push(ScoreChange {
+                        candidate_id: candidate_id.clone(),
+                        tx_hash: production.tx_hash,
+                        production: production.score,
+                        counterfactual: counterfactual.score,
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        let candidates_added = self
+            .counterfactual_candidates
+            .values()
+            .filter(|candidate| !production_candidates.contains_key(&candidate.candidate_id))
+            .cloned()
+            .collect();
+
+        let assembly_ids = production_assembly
+            .keys()
+            .chain(self.counterfactual_assembly.keys())
+            .collect::<BTreeSet<_>>();
+        let assembly_changed = assembly_ids
+            .into_iter()
+            .filter_map(|candidate_id| {
+                let production = production_assembly.get(candidate_id);
+                let counterfactual = self.counterfactual_assembly.get(candidate_id);
+                let (tx_hash, _) = production.or(counterfactual)?;
+                (production.map(|(_, outcome)| outcome)
+                    != counterfactual.map(|(_, outcome)| outcome))
+                .then(|| AssemblyChange {
+                    candidate_id: candidate_id.clone(),
+                    tx_hash: *tx_hash,
+                    production: production.map(|(_, outcome)| outcome.clone()),
+                    counterfactual: counterfactual.map(|(_, outcome)| outcome.clone()),
+                })
+            })
+            .collect();
+
+        CounterfactualReport {
+            config: self.config,
+            replayed_transactions: self.replayed,
+            skipped_transactions: self.skipped.len(),
+            production_candidates: production_candidates.len(),
+            counterfactual_candidates: self.counterfactual_candidates.len(),
+            admissions_changed: self.admissions_changed,
+            candidates_added,
+            candidates_removed,
+            scores_changed,
+            assembly_changed,
+        }
+    }
+
+    /// Runs one transaction through admission, search, simulation and
+    /// assembly the way the live pipeline does.
+    fn reexecute(
+        &mut self,
+        event: &EventEnvelope,
+        decoded: TxDecoded,
+        calldata: Vec<u8>,
+        production: AdmissionKind,
+    ) {
+        let hash = decoded.hash;
+        let outcome = self.scheduler.admit(ValidatedTransaction {
+            source_id: event.source_id.clone(),
+            observed_at_unix_ms: event.ingest_ts_unix_ms,
+            observed_at_mono_ns: event.ingest_ts_mono_ns,
+            calldata,
+            decoded,
+        });
+        self.replayed += 1;
+        let counterfactual = AdmissionKind::from(outcome.admission);
+        if counterfactual != production {
+            self.admissions_changed.push(AdmissionChange {
+                hash,
+                production,
+                counterfactual,
+            });
+        }
+        if let SchedulerAdmission::Replaced { replaced_hash } = outcome.admission {
+            self.scheduler.invalidate_candidate_hash(replaced_hash);
+        }
+
+        let mut ready_hashes = Vec::new();
+        for transition in &outcome.queue_transitions {
+            if matches!(transition.state, SchedulerQueueState::Ready)
+                && !ready_hashes.contains(&transition.hash)
+            {
+                ready_hashes.push(transition.hash);
+            }
+        }
+        if ready_hashes.is_empty() {
+            return;
+        }
+        let executable = self.scheduler.pending_transactions(&ready_hashes);
+        let frontier = self.scheduler.ready_transactions();
+        let batch = executable
+            .iter()
+            .map(|tx| SearcherInputTx::borrowed(tx.decoded.clone(), tx.calldata.as_slice()))
+            .collect::<Vec<_>>();
+        let prepared = rank_opportunity_batch(&batch, self.config.searcher)
+            .candidates
+            .into_iter()
+            .filter_map(|opportunity| {
+                let members = simulation_members(&opportunity, &frontier)?;
+                Some((
+                    scheduler_candidate(&opportunity, event.ingest_ts_unix_ms),
+                    members,
+                    opportunity,
+                ))
+            })
+            .collect::<Vec<_>>();
+        if prepared.is_empty() {
+            return;
+        }
+
+        let dispatch = self.scheduler.register_candidates(
+            prepared
+                .iter()
+                .map(|(candidate, _, _)| candidate.clone())
+                .collect(),
+        );
+        for (task, (candidate, members, opportunity)) in
+            dispatch.simulation_tasks.into_iter().zip(prepared)
+        {
+            self.counterfactual_candidates.insert(
+                candidate.candidate_id.to_string(),
+                CandidateOutcome {
+                    candidate_id: candidate.candidate_id.to_string(),
+                    tx_hash: candidate.tx_hash,
+                    member_tx_hashes: candidate.member_tx_hashes.clone(),
+                    strategy: candidate.strategy.to_string(),
+                    score: candidate.score,
+                },
+            );
+
+            let simulation = simulate(event, task.block_number, self.config.assembly, &members);
+            let applied = self
+                .scheduler
+                .apply_simulation_result(SchedulerSimulationResult {
+                    candidate_id: task.candidate_id.clone(),
+                    tx_hash: task.tx_hash,
+                    member_tx_hashes: task.member_tx_hashes.clone(),
+                    block_number: task.block_number,
+                    generation: task.generation,
+                    approved: simulation
+                        .as_ref()
+                        .is_some_and(|batch| batch.tx_results.iter().all(|result| result.success)),
+                });
+            if applied.builder_handoffs.is_empty() {
+                continue;
+            }
+            let Some(simulation) = simulation else {
+                continue;
+            };
+            let Ok(assembly_candidate) =
+                AssemblyCandidate::from_simulated_opportunity(&opportunity, &simulation)
+            else {
+                continue;
+            };
+            let (candidate_id, outcome) = match self.assembly.insert(assembly_candidate) {
+                AssemblyDecision::Inserted { candidate_id, .. } => (
+                    candidate_id,
+                    AssemblyOutcome {
+                        decision: AssemblyDecisionKind::Inserted,
+                        reason: None,
+                    },
+                ),
+                AssemblyDecision::Rejected {
+                    candidate_id,
+                    reason,
+                } => (
+                    candidate_id,
+                    AssemblyOutcome {
+                        decision: AssemblyDecisionKind::Rejected,
+                        reason: Some(reason),
+                    },
+                ),
+            };
+            self.counterfactual_assembly
+                .insert(candidate_id, (opportunity.tx_hash, outcome));
+        }
+    }
+}
+
+/// Replays `events` under `config` and diffs the result against what
+/// production emitted.
+pub fn counterfactual_replay<I, F>(
+    events: I,
+    config: CounterfactualConfig,
+    recorded: F,
+) -> Result<CounterfactualReport, ReplayError>
+where
+    I: IntoIterator<Item = EventEnvelope>,
+    F: FnMut(&TxHash) -> Option<RecordedTransaction>,
+{
+    let mut replay = CounterfactualReplay::new(config, recorded)?;
+    for event in events {
+        replay.apply_event(&event);
+    }
+    Ok(replay.finish())
+}
+
+fn scheduler_candidate(
+    opportunity: &OpportunityCandidate,
+    detected_unix_ms: i64,
+) -> SchedulerCandidate {
+    SchedulerCandidate {
+        candidate_id: format!(
+            "{:?}:0x{}",
+            opportunity.strategy,
+            hex::encode(opportunity.tx_hash)
+        )
+        .into(),
+        tx_hash: opportunity.tx_hash,
+        member_tx_hashes: if opportunity.member_tx_hashes.is_empty() {
+            vec![opportunity.tx_hash]
+        } else {
+            opportunity.member_tx_hashes.clone()
+        },
+        score: opportunity.score,
+        strategy: format!("{:?}", opportunity.strategy).into(),
+        detected_unix_ms,
+    }
+}
+
+/// Selects the frontier transactions a candidate is simulated with: its
+/// members plus every earlier nonce from the same senders.
+fn simulation_members(
+    opportunity: &OpportunityCandidate,
+    frontier: &[ValidatedTransaction],
+) -> Option<Vec<ValidatedTransaction>> {
+    let requested = if opportunity.member_tx_hashes.is_empty() {
+        vec![opportunity.tx_hash]
+    } else {
+        opportunity.member_tx_hashes.clone()
+    };
+    let mut selected = requested.iter().copied().collect::<BTreeSet<_>>();
+    for hash in &requested {
+        let member = frontier.iter().find(|tx| tx.hash() == *hash)?;
+        for tx in frontier {
+            if tx.decoded.sender == member.decoded.sender
+                && tx.decoded.nonce <= member.decoded.nonce
+            {
+                selected.insert(tx.hash());
+            }
+        }
+    }
+    Some(
+        frontier
+            .iter()
+            .filter(|tx| selected.contains(&tx.hash()))
+            .cloned()
+            .collect(),
+    )
+}
+
+fn simulate(
+    event: &EventEnvelope,
+    block_number: u64,
+    assembly: AssemblyConfig,
+    members: &[ValidatedTransaction],
+) -> Option<SimulationBatchResult> {
+    let chain_id = members
+        .iter()
+        .find_map(|tx| tx.decoded.chain_id)
+        .or(event.chain_id)
+        .unwrap_or(1);
+    let context = ChainContext {
+        chain_id,
+        block_number,
+        block_timestamp: event.ingest_ts_unix_ms.max(0) as u64 / 1_000,
+        gas_limit: assembly.block_gas_limit,
+        base_fee_wei: 0,
+        coinbase: [0; 20],
+        state_root: [0; 32],
+        op_l1_fee: None,
+    };
+    let inputs = members
+        .iter()
+        .map(|tx| SimulationTxInput {
+            decoded: tx.decoded.clone(),
+            calldata: Some(tx.calldata.clone()),
+            raw_tx: None,
+        })
+        .collect::<Vec<_>>();
+    simulate_with_mode(&context, &inputs, SimulationMode::SyntheticDeterministic).ok()
+}
@@ -2,7 +2,9 @@
 
 //! Deterministic replay and checkpoint helpers for persisted event streams.
 
+mod counterfactual;
 mod mempool_state;
+mod stream;
 
 use common::TxHash;
 #[cfg(test)]
@@ -14,6 +16,11 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error as StdError;
 use std::sync::Arc;
 
+pub use counterfactual::{
+    AdmissionChange, AdmissionKind, AssemblyChange, AssemblyOutcome, CandidateOutcome,
+    CounterfactualConfig, CounterfactualReplay, CounterfactualReport, RecordedTransaction,
+    ScoreChange, counterfactual_replay,
+};
 pub use mempool_state::{
     CandidateLifecycleEntry, CandidateLifecycleSnapshot, CandidateLifecycleState, MempoolState,
     ReplayQueueState, ReplaySenderQueue, ReplaySenderQueueEntry, StateTransition,
@@ -22,6 +29,11 @@ pub use mempool_state::{
 pub use sim_engine::{
     ChainContext as SimulationChainContext, SimulationBatchResult, TxSimulationResult,
 };
+pub use stream::{
+    DeterministicOrder, ReplayStream, checkpoint_hash_parity_iter, current_lifecycle_iter,
+    deterministic_checkpoint_hashes_iter, lifecycle_checkpoints_iter, replay_frames_iter,
+    replay_from_checkpoint_iter,
+};
 
 type SharedError = Arc<dyn StdError + Send + Sync>;
 
@@ -34,6 +46,8 @@ pub enum ReplayError {
         expected: Box<[u8; 32]>,
         actual: Box<[u8; 32]>,
     },
+    #[error("invalid counterfactual config: {0}")]
+    InvalidConfig(#[from] scheduler::SchedulerConfigError),
     #[error(transparent)]
     Other(SharedError),
 }
@@ -72,6 +86,17 @@ pub struct CheckpointHash {
     pub checkpoint_hash: [u8; 32],
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Checkpoint hash agreement between a reference and a candidate stream.
+pub struct CheckpointParityReport {
+    pub reference_checkpoints: usize,
+    pub candidate_checkpoints: usize,
+    /// Reference checkpoints whose sequence id and hash the candidate matches.
+    pub matched: usize,
+    pub first_mismatch_seq_id: Option<u64>,
+    pub parity_percent: f64,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 /// Diff between two lifecycle checkpoints.
 pub struct ReplayDiffSummary {
@@ -86,14 +111,18 @@ pub struct ReplayDiffSummary {
 }
 
 /// Replays an event stream into frames using the requested mode and stride.
+///
+/// Both modes currently step through every event; see [`replay_frames_iter`]
+/// for the streaming form.
 pub fn replay_frames(
     events: &[EventEnvelope],
     mode: ReplayMode,
     stride: usize,
 ) -> Vec<ReplayFrame> {
     match mode {
-        ReplayMode::DeterministicEventReplay => replay_deterministic(events, stride.max(1)),
-        ReplayMode::SnapshotReplay => replay_snapshot(events, stride.max(1)),
+        ReplayMode::DeterministicEventReplay | ReplayMode::SnapshotReplay => {
+            replay_frames_iter(sorted(events), stride).collect()
+        }
     }
 }
 
@@ -102,27 +131,7 @@ pub fn lifecycle_checkpoints(
     events: &[EventEnvelope],
     checkpoint_seq_ids: &[u64],
 ) -> Vec<LifecycleCheckpoint> {
-    let checkpoints: BTreeSet<u64> = checkpoint_seq_ids.iter().copied().collect();
-    let mut sorted = events.to_vec();
-    sort_deterministic(&mut sorted);
-
-    let mut state = MempoolState::default();
-    let mut out = Vec::new();
-
-    for event in sorted {
-        state.apply_event(&event);
-        if checkpoints.contains(&event.seq_id) {
-            let mut pending = state.pending_hashes();
-            pending.sort_unstable();
-            out.push(LifecycleCheckpoint {
-                seq_id: event.seq_id,
-                pending_hashes: pending,
-                sender_queues: state.sender_queues(),
-            });
-        }
-    }
-
-    out
+    lifecycle_checkpoints_iter(sorted(events), checkpoint_seq_ids).collect()
 }
 
 /// Returns a single checkpoint snapshot for the requested sequence id.
@@ -176,7 +185,7 @@ pub fn replay_from_checkpoint(
     checkpoint: &LifecycleCheckpoint,
     stride: usize,
 ) -> Vec<ReplayFrame> {
-    replay_deterministic_from_checkpoint(events, checkpoint, stride.max(1))
+    replay_from_checkpoint_iter(sorted(events), checkpoint, stride).collect()
 }
 
 #[must_use = "diff summaries must be inspected by callers"]
@@ -191,117 +200,43 @@ pub fn replay_diff_summary(
     } else {
         (to_seq_id, from_seq_id)
     };
-    let from_checkpoint = lifecycle_snapshot(events, from_seq_id)?;
-    let to_checkpoint = lifecycle_snapshot(events, to_seq_id)?;
+    let mut checkpoints = lifecycle_checkpoints(events, &[from_seq_id, to_seq_id]).into_iter();
+    let from_checkpoint = checkpoints.next()?;
+    let to_checkpoint = if from_seq_id == to_seq_id {
+        from_checkpoint.clone()
+    } else {
+        checkpoints.next()?
+    };
+    Some(checkpoint_diff_summary(&from_checkpoint, &to_checkpoint))
+}
 
+/// Computes the pending-set diff between two captured checkpoints.
+pub fn checkpoint_diff_summary(
+    from_checkpoint: &LifecycleCheckpoint,
+    to_checkpoint: &LifecycleCheckpoint,
+) -> ReplayDiffSummary {
     let from_set: BTreeSet<TxHash> = from_checkpoint.pending_hashes.iter().copied().collect();
     let to_set: BTreeSet<TxHash> = to_checkpoint.pending_hashes.iter().copied().collect();
 
     let added_pending = to_set.difference(&from_set).copied().collect::<Vec<_>>();
     let removed_pending = from_set.difference(&to_set).copied().collect::<Vec<_>>();
 
-    Some(ReplayDiffSummary {
-        from_seq_id,
-        to_seq_id,
+    ReplayDiffSummary {
+        from_seq_id: from_checkpoint.seq_id,
+        to_seq_id: to_checkpoint.seq_id,
         from_pending_count: from_checkpoint.pending_hashes.len(),
         to_pending_count: to_checkpoint.pending_hashes.len(),
-        from_checkpoint_hash: lifecycle_checkpoint_hash(&from_checkpoint),
-        to_checkpoint_hash: lifecycle_checkpoint_hash(&to_checkpoint),
+        from_checkpoint_hash: lifecycle_checkpoint_hash(from_checkpoint),
+        to_checkpoint_hash: lifecycle_checkpoint_hash(to_checkpoint),
         added_pending,
         removed_pending,
-    })
-}
-
-fn replay_deterministic(events: &[EventEnvelope], stride: usize) -> Vec<ReplayFrame> {
-    let mut sorted = events.to_vec();
-    sort_deterministic(&mut sorted);
-
-    let mut state = MempoolState::default();
-    let mut frames = Vec::new();
-
-    for (idx, event) in sorted.iter().enumerate() {
-        state.apply_event(event);
-        let should_emit = (idx + 1) % stride == 0 || idx + 1 == sorted.len();
-        if should_emit {
-            let mut pending = state.pending_hashes();
-            pending.sort_unstable();
-            frames.push(ReplayFrame {
-                seq_hi: event.seq_id,
-                timestamp_unix_ms: event.ingest_ts_unix_ms,
-                pending,
-                sender_queues: state.sender_queues(),
-            });
-        }
     }
-
-    frames
 }
 
-fn replay_deterministic_from_checkpoint(
-    events: &[EventEnvelope],
-    checkpoint: &LifecycleCheckpoint,
-    stride: usize,
-) -> Vec<ReplayFrame> {
+fn sorted(events: &[EventEnvelope]) -> Vec<EventEnvelope> {
     let mut sorted = events.to_vec();
     sort_deterministic(&mut sorted);
-    let sorted_tail = sorted
-        .into_iter()
-        .filter(|event| event.seq_id > checkpoint.seq_id)
-        .collect::<Vec<_>>();
-
-    if sorted_tail.is_empty() {
-        return Vec::new();
-    }
-
-    let mut state = if checkpoint.sender_queues.is_empty() {
-        MempoolState::from_pending_hashes(&checkpoint.pending_hashes)
-    } else {
-        MempoolState::from_checkpoint(&checkpoint.pending_hashes, &checkpoint.sender_queues)
-    };
-    let mut frames = Vec::new();
-    for (idx, event) in sorted_tail.iter().enumerate() {
-        state.apply_event(event);
-        let should_emit = (idx + 1) % stride == 0 || idx + 1 == sorted_tail.len();
-        if should_emit {
-            let mut pending = state.pending_hashes();
-            pending.sort_unstable();
-            frames.push(ReplayFrame {
-                seq_hi: event.seq_id,
-                timestamp_unix_ms: event.ingest_ts_unix_ms,
-                pending,
-                sender_queues: state.sender_queues(),
-            });
-        }
-    }
-
-    frames
-}
-
-fn replay_snapshot(events: &[EventEnvelope], stride: usize) -> Vec<ReplayFrame> {
-    let mut sorted = events.to_vec();
-    sort_deterministic(&mut sorted);
-
-    let mut state = MempoolState::default();
-    let mut frames = Vec::new();
-    let mut count = 0usize;
-
-    for event in &sorted {
-        count += 1;
-        state.apply_event(event);
-
-        if count.is_multiple_of(stride) || count == sorted.len() {
-            let mut pending = state.pending_hashes();
-            pending.sort_unstable();
-            frames.push(ReplayFrame {
-                seq_hi: event.seq_id,
-                timestamp_unix_ms: event.ingest_ts_unix_ms,
-                pending,
-                sender_queues: state.sender_queues(),
-            });
-        }
-    }
-
-    frames
+    sorted
 }
 
 /// Compares checkpoint parity between replayed frames and direct lifecycle reconstruction.
@@ -333,14 +268,7 @@ pub fn deterministic_checkpoint_hashes(
     events: &[EventEnvelope],
     stride: usize,
 ) -> Vec<CheckpointHash> {
-    replay_frames(events, ReplayMode::DeterministicEventReplay, stride.max(1))
-        .into_iter()
-        .map(|frame| CheckpointHash {
-            seq_id: frame.seq_hi,
-            pending_count: frame.pending.len(),
-            checkpoint_hash: frame_checkpoint_hash(&frame),
-        })
-        .collect()
+    deterministic_checkpoint_hashes_iter(sorted(events), stride).collect()
 }
 
 /// Returns the percentage of matching checkpoint hashes between two event streams.
@@ -349,30 +277,11 @@ pub fn checkpoint_hash_parity_percent(
     candidate_events: &[EventEnvelope],
     stride: usize,
 ) -> f64 {
-    let reference = deterministic_checkpoint_hashes(reference_events, stride.max(1));
-    let candidate = deterministic_checkpoint_hashes(candidate_events, stride.max(1));
-    if reference.is_empty() {
-        return if candidate.is_empty() { 100.0 } else { 0.0 };
-    }
-
-    let candidate_by_seq: BTreeMap<u64, [u8; 32]> = candidate
-        .into_iter()
-        .map(|checkpoint| (checkpoint.seq_id, checkpoint.checkpoint_hash))
-        .collect();
-
-    let matched = reference
-        .iter()
-        .filter(|checkpoint| {
-            candidate_by_seq
-                .get(&checkpoint.seq_id)
-                .is_some_and(|hash| *hash == checkpoint.checkpoint_hash)
-        })
-        .count();
-
-    (matched as f64 / reference.len() as f64) * 100.0
+    checkpoint_hash_parity_iter(sorted(reference_events), sorted(candidate_events), stride)
+        .parity_percent
 }
 
-fn frame_checkpoint_hash(frame: &ReplayFrame) -> [u8; 32] {
+pub(crate) fn frame_checkpoint_hash(frame: &ReplayFrame) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(frame.seq_hi.to_le_bytes());
     hasher.update(frame.timestamp_unix_ms.to_le_bytes());
@@ -408,20 +317,14 @@ fn hash_sender_queues(hasher: &mut Sha256, sender_queues: &[ReplaySenderQueue])
 
 /// Returns the current lifecycle status for a transaction after replaying all events.
 pub fn current_lifecycle(events: &[EventEnvelope], hash: TxHash) -> Option<TxLifecycleStatus> {
-    let mut sorted = events.to_vec();
-    sort_deterministic(&mut sorted);
-    let mut state = MempoolState::default();
-    for event in &sorted {
-        state.apply_event(event);
-    }
-    state.lifecycle(&hash).cloned()
+    current_lifecycle_iter(sorted(events), hash)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use common::{Address, BlockHash, SourceId};
-    use event_log::{TxBlocked, TxConfirmed, TxDecoded, TxDropped, TxReady};
+    use event_log::{DropReason, TxBlocked, TxConfirmed, TxDecoded, TxDropped, TxReady};
 
     fn hash(v: u8) -> TxHash {
         [v; 32]
@@ -440,6 +343,9 @@ mod tests {
             ingest_ts_mono_ns: seq * 100,
             source_id: SourceId::new("test"),
             payload,
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         }
     }
 
@@ -500,10 +406,7 @@ mod tests {
             decoded(2, 2, 9, 1),
             envelope(
                 3,
-                EventPayload::TxDropped(TxDropped {
-                    hash: hash(2),
-                    reason: "evicted".to_owned(),
-                }),
+                EventPayload::TxDropped(TxDropped::new(hash(2), DropReason::Evicted)),
             ),
         ];
         let mut shuffled = vec![ordered[2].clone(), ordered[0].clone(), ordered[1].clone()];
@@ -548,10 +451,10 @@ mod tests {
             if idx % 3 == 0 {
                 events.push(envelope(
                     seq,
-                    EventPayload::TxDropped(TxDropped {
-                        hash: hash(idx.wrapping_add(1)),
-                        reason: "evicted".to_owned(),
-                    }),
+                    EventPayload::TxDropped(TxDropped::new(
+                        hash(idx.wrapping_add(1)),
+                        DropReason::Evicted,
+                    )),
                 ));
                 seq = seq.saturating_add(1);
             }
@@ -566,6 +469,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn checkpoint_parity_report_counts_matches_and_first_divergence() {
+        let reference = (1..=6_u64)
+            .map(|seq| decoded(seq, seq as u8, seq as u8, 0))
+            .collect::<Vec<_>>();
+        let mut candidate = reference[..5].to_vec();
+        candidate[3] = decoded(4, 0xee, 4, 0);
+
+        let report = checkpoint_hash_parity_iter(reference.clone(), candidate.clone(), 2);
+        assert_eq!(report.reference_checkpoints, 3);
+        assert_eq!(report.candidate_checkpoints, 3);
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.first_mismatch_seq_id, Some(4));
+        assert_eq!(
+            report.parity_percent,
+            crate::checkpoint_hash_parity_percent(&reference, &candidate, 2)
+        );
+
+        let identical = checkpoint_hash_parity_iter(reference.clone(), reference, 2);
+        assert_eq!(identical.parity_percent, 100.0);
+        assert_eq!(identical.first_mismatch_seq_id, None);
+    }
+
     #[test]
     fn lifecycle_snapshot_checkpoint_hash_is_stable_for_pending_ordering() {
         let checkpoint = LifecycleCheckpoint {
@@ -592,18 +518,12 @@ mod tests {
             decoded(2, 2, 2, 1),
             envelope(
                 3,
-                EventPayload::TxDropped(TxDropped {
-                    hash: hash(1),
-                    reason: "evicted".to_owned(),
-                }),
+                EventPayload::TxDropped(TxDropped::new(hash(1), DropReason::Evicted)),
             ),
             decoded(4, 3, 3, 1),
             envelope(
                 5,
-                EventPayload::TxDropped(TxDropped {
-                    hash: hash(2),
-                    reason: "included".to_owned(),
-                }),
+                EventPayload::TxDropped(TxDropped::new(hash(2), DropReason::Included)),
             ),
         ];
 
@@ -618,6 +538,55 @@ mod tests {
         assert_eq!(from_checkpoint, expected_tail);
     }
 
+    #[test]
+    fn streaming_replay_matches_slice_replay_for_nearly_sorted_input() {
+        let events = (1..=12_u64)
+            .map(|seq| decoded(seq, seq as u8, seq as u8 % 3, seq))
+            .collect::<Vec<_>>();
+        let mut shuffled = events.clone();
+        for pair in shuffled.chunks_mut(2) {
+            pair.reverse();
+        }
+
+        let expected = replay_frames(&events, ReplayMode::DeterministicEventReplay, 5);
+        assert_eq!(expected.len(), 3);
+        let streamed =
+            replay_frames_iter(DeterministicOrder::new(shuffled.clone(), 1), 5).collect::<Vec<_>>();
+        assert_eq!(streamed, expected);
+        assert_eq!(
+            replay_frames(&shuffled, ReplayMode::SnapshotReplay, 5),
+            expected
+        );
+
+        let checkpoint = lifecycle_snapshot(&events, 4).expect("checkpoint at seq=4");
+        assert_eq!(
+            replay_from_checkpoint_iter(events.clone(), &checkpoint, 5).collect::<Vec<_>>(),
+            replay_from_checkpoint(&events, &checkpoint, 5)
+        );
+        assert_eq!(
+            deterministic_checkpoint_hashes_iter(events.clone(), 5).collect::<Vec<_>>(),
+            deterministic_checkpoint_hashes(&events, 5)
+        );
+    }
+
+    #[test]
+    fn streaming_checkpoints_stop_reading_after_the_last_requested_seq() {
+        let events = (1..=10_u64)
+            .map(|seq| decoded(seq, seq as u8, 1, seq))
+            .collect::<Vec<_>>();
+        let mut read = 0;
+        let checkpoints =
+            lifecycle_checkpoints_iter(events.iter().cloned().inspect(|_| read += 1), &[3, 5])
+                .collect::<Vec<_>>();
+
+        assert_eq!(checkpoints, lifecycle_checkpoints(&events, &[3, 5]));
+        assert_eq!(read, 5);
+        assert_eq!(
+            current_lifecycle_iter(events.clone(), hash(7)),
+            current_lifecycle(&events, hash(7))
+        );
+    }
+
     #[test]
     fn replay_diff_summary_reports_added_and_removed_pending_hashes() {
         let events = vec![
@@ -625,10 +594,7 @@ mod tests {
             decoded(2, 2, 2, 1),
             envelope(
                 3,
-                EventPayload::TxDropped(TxDropped {
-                    hash: hash(1),
-                    reason: "evicted".to_owned(),
-                }),
+                EventPayload::TxDropped(TxDropped::new(hash(1), DropReason::Evicted)),
             ),
             decoded(4, 3, 3, 1),
         ];
@@ -735,4 +701,176 @@ mod tests {
 
         assert_eq!(from_checkpoint, expected_tail);
     }
+
+    const UNISWAP_V2_ROUTER: Address = [
+        0x7a, 0x25, 0x0d, 0x56, 0x30, 0xb4, 0xcf, 0x53, 0x97, 0x39, 0xdf, 0x2c, 0x5d, 0xac, 0xb4,
+        0xc6, 0x59, 0xf2, 0x48, 0x8d,
+    ];
+    const SWAP_CALLDATA: [u8; 9] = [0x38, 0xed, 0x17, 0x39, 1, 2, 3, 4, 5];
+
+    fn swap(seq: u64, hash_v: u8, sender_v: u8, nonce: u64) -> EventEnvelope {
+        envelope(
+            seq,
+            EventPayload::TxDecoded(TxDecoded {
+                hash: hash(hash_v),
+                tx_type: 2,
+                sender: address(sender_v),
+                nonce,
+                chain_id: Some(1),
+                to: Some(UNISWAP_V2_ROUTER),
+                value_wei: Some(1_000_000_000_000_000),
+                gas_limit: Some(320_000),
+                gas_price_wei: None,
+                max_fee_per_gas_wei: Some(45_000_000_000),
+                max_priority_fee_per_gas_wei: Some(7_000_000_000),
+                max_fee_per_blob_gas_wei: None,
+                calldata_len: Some(SWAP_CALLDATA.len() as u32),
+            }),
+        )
+    }
+
+    #[test]
+    fn counterfactual_replay_diffs_admissions_candidates_and_assembly_against_production() {
+        let decoded = vec![
+            swap(1, 0x10, 0xa1, 0),
+            swap(2, 0x11, 0xa1, 1),
+            swap(3, 0x20, 0xa2, 0),
+        ];
+        let recorded = decoded
+            .iter()
+            .filter_map(|event| match &event.payload {
+                EventPayload::TxDecoded(decoded) => Some((
+                    decoded.hash,
+                    RecordedTransaction {
+                        decoded: decoded.clone(),
+                        calldata: SWAP_CALLDATA.to_vec(),
+                    },
+                )),
+                _ => None,
+            })
+            .collect::<BTreeMap<_, _>>();
+        let lookup = |hash: &TxHash| recorded.get(hash).cloned();
+
+        // Without production candidates, everything the live config derives is "added".
+        let baseline =
+            counterfactual_replay(decoded.clone(), CounterfactualConfig::default(), lookup)
+                .expect("valid config");
+        assert_eq!(baseline.replayed_transactions, 3);
+        assert!(baseline.admissions_changed.is_empty());
+        assert!(baseline.candidates_removed.is_empty());
+        // Sandwich, backrun and arb candidates for each of the three swaps.
+        assert_eq!(baseline.candidates_added.len(), 9);
+        assert!(!baseline.assembly_changed.is_empty());
+
+        let mut production = decoded;
+        let mut seq = 3;
+        for candidate in &baseline.candidates_added {
+            seq += 1;
+            production.push(envelope(
+                seq,
+                EventPayload::CandidateQueued(event_log::CandidateQueued {
+                    candidate_id: candidate.candidate_id.clone(),
+                    tx_hash: candidate.tx_hash,
+                    member_tx_hashes: candidate.member_tx_hashes.clone(),
+                    chain_id: Some(1),
+                    strategy: candidate.strategy.clone(),
+                    score: candidate.score,
+                    protocol: String::new(),
+                    category: String::new(),
+                    feature_engine_version: String::new(),
+                    scorer_version: String::new(),
+                    strategy_version: String::new(),
+                    reasons: Vec::new(),
+                    detected_unix_ms: 0,
+                }),
+            ));
+        }
+        for change in &baseline.assembly_changed {
+            let outcome = change
+                .counterfactual
+                .clone()
+                .expect("counterfactual decision");
+            seq += 1;
+            production.push(envelope(
+                seq,
+                EventPayload::AssemblyDecisionApplied(event_log::AssemblyDecisionApplied {
+                    candidate_id: change.candidate_id.clone(),
+                    tx_hash: change.tx_hash,
+                    decision: outcome.decision,
+                    replaced_candidate_ids: Vec::new(),
+                    reason: outcome.reason,
+                    block_number: 0,
+                }),
+            ));
+        }
+
+        let unchanged =
+            counterfactual_replay(production.clone(), CounterfactualConfig::default(), lookup)
+                .expect("valid config");
+        assert!(!unchanged.has_changes(), "{unchanged:?}");
+        assert_eq!(
+            unchanged.production_candidates,
+            baseline.candidates_added.len()
+        );
+
+        let live = CounterfactualConfig::default();
+        let tuned = CounterfactualConfig {
+            scheduler: scheduler::SchedulerConfig {
+                max_pending_per_sender: 1,
+                ..live.scheduler
+            },
+            searcher: searcher::SearcherConfig {
+                strategy_thresholds: searcher::StrategyThresholds {
+                    sandwich_min_mev_score: u16::MAX,
+                    ..searcher::StrategyThresholds::default()
+                },
+                ..live.searcher
+            },
+            ..live
+        };
+        let report = counterfactual_replay(production.clone(), tuned, lookup).expect("valid");
+        assert_eq!(
+            report.admissions_changed,
+            vec![AdmissionChange {
+                hash: hash(0x11),
+                production: AdmissionKind::Admitted,
+                counterfactual: AdmissionKind::SenderLimitReached,
+            }]
+        );
+        assert!(report.candidates_added.is_empty());
+        assert!(report.scores_changed.is_empty());
+        let removed = |candidate_id: &str| {
+            report
+                .candidates_removed
+                .iter()
+                .any(|candidate| candidate.candidate_id == candidate_id)
+        };
+        assert_eq!(report.candidates_removed.len(), 5);
+        assert!(report.candidates_removed.iter().all(|candidate| {
+            candidate.strategy == "SandwichCandidate" || candidate.tx_hash == hash(0x11)
+        }));
+        assert!(!report.assembly_changed.is_empty());
+        assert!(report.assembly_changed.iter().all(|change| {
+            change.production.is_some()
+                && (change.counterfactual.is_some() || removed(&change.candidate_id))
+        }));
+
+        let unrecorded =
+            counterfactual_replay(production, live, |_: &TxHash| None).expect("valid config");
+        assert_eq!(unrecorded.replayed_transactions, 0);
+        assert_eq!(unrecorded.skipped_transactions, 3);
+        assert!(!unrecorded.has_changes());
+
+        let invalid = CounterfactualConfig {
+            scheduler: scheduler::SchedulerConfig {
+                max_pending_per_sender: 0,
+                ..live.scheduler
+            },
+            ..live
+        };
+        assert!(matches!(
+            counterfactual_replay(Vec::new(), invalid, lookup),
+            Err(ReplayError::InvalidConfig(_))
+        ));
+    }
 }
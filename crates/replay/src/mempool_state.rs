@@ -2,7 +2,10 @@
 
 use ahash::RandomState;
 use common::{Address, BlockHash, TxHash};
-use event_log::{EventEnvelope, EventPayload, TxBlocked, TxDecoded, TxDropped, TxReady, TxReorged};
+use event_log::{
+    AssemblyDecisionKind, DropReason, EventEnvelope, EventPayload, SimulationStatus, TxBlocked,
+    TxDecoded, TxDropped, TxReady, TxReorged,
+};
 use hashbrown::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
@@ -60,9 +63,9 @@ pub struct CandidateLifecycleEntry {
     #[serde(default)]
     pub detected_unix_ms: i64,
     #[serde(default)]
-    pub simulation_status: Option<String>,
+    pub simulation_status: Option<SimulationStatus>,
     #[serde(default)]
-    pub assembly_status: Option<String>,
+    pub assembly_status: Option<AssemblyDecisionKind>,
     #[serde(default)]
     pub simulation_block_number: Option<u64>,
     #[serde(default)]
@@ -81,7 +84,8 @@ pub struct CandidateLifecycleSnapshot {
     pub candidates: BTreeMap<String, CandidateLifecycleEntry>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
 /// Current replayed lifecycle status for a transaction hash.
 pub enum TxLifecycleStatus {
     Pending,
@@ -89,7 +93,7 @@ pub enum TxLifecycleStatus {
         by: TxHash,
     },
     Dropped {
-        reason: String,
+        reason: DropReason,
     },
     ConfirmedProvisional {
         block_number: u64,
@@ -113,7 +117,7 @@ pub enum StateTransition {
     },
     Dropped {
         hash: TxHash,
-        reason: String,
+        reason: DropReason,
     },
     ConfirmedProvisional {
         hash: TxHash,
@@ -299,7 +303,9 @@ impl MempoolState {
             | EventPayload::OppDetected(_)
             | EventPayload::SimCompleted(_)
             | EventPayload::AssemblyDecisionApplied(_)
-            | EventPayload::BundleSubmitted(_) => Vec::new(),
+            | EventPayload::BundleSubmitted(_)
+            | EventPayload::UserOpSeen(_)
+            | EventPayload::ChainCheckpoint(_) => Vec::new(),
         }
     }
 
@@ -398,6 +404,16 @@ impl MempoolState {
     }
 
     fn apply_dropped(&mut self, dropped: &TxDropped) -> Vec<StateTransition> {
+        match dropped.reason {
+            // Repeated announcement rejected by ingest dedup, not a pool exit.
+            DropReason::Duplicate => return Vec::new(),
+            DropReason::QueueFull
+            | DropReason::UnderpricedReplacement
+            | DropReason::SenderLimitReached
+            | DropReason::Evicted
+            | DropReason::Included
+            | DropReason::Other(_) => {}
+        }
         let (sender, nonce) = self
             .txs
             .get(&dropped.hash)
@@ -696,6 +712,9 @@ mod tests {
             ingest_ts_mono_ns: seq_id * 10,
             source_id: SourceId::new("test-source"),
             payload,
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         }
     }
 
@@ -775,10 +794,7 @@ mod tests {
         state.apply_event(&envelope(1, decoded(7, 2, 11)));
         state.apply_event(&envelope(
             2,
-            EventPayload::TxDropped(TxDropped {
-                hash: hash(7),
-                reason: "evicted".to_owned(),
-            }),
+            EventPayload::TxDropped(TxDropped::new(hash(7), DropReason::Evicted)),
         ));
         let transitions = state.apply_event(&envelope(3, decoded(8, 2, 11)));
 
@@ -789,29 +805,40 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn duplicate_announcement_keeps_pending_tx_pending() {
+        let mut state = MempoolState::default();
+
+        state.apply_event(&envelope(1, decoded(9, 2, 11)));
+        let transitions = state.apply_event(&envelope(
+            2,
+            EventPayload::TxDropped(TxDropped::new(hash(9), DropReason::Duplicate)),
+        ));
+
+        assert!(transitions.is_empty());
+        assert_eq!(state.lifecycle(&hash(9)), Some(&TxLifecycleStatus::Pending));
+    }
+
     #[test]
     fn dropped_tx_without_prior_decode_still_records_lifecycle() {
         let mut state = MempoolState::default();
 
         let transitions = state.apply_event(&envelope(
             1,
-            EventPayload::TxDropped(TxDropped {
-                hash: hash(12),
-                reason: "queue_full".to_owned(),
-            }),
+            EventPayload::TxDropped(TxDropped::new(hash(12), DropReason::QueueFull)),
         ));
 
         assert_eq!(
             transitions,
             vec![StateTransition::Dropped {
                 hash: hash(12),
-                reason: "queue_full".to_owned(),
+                reason: DropReason::QueueFull,
             }]
         );
         assert_eq!(
             state.lifecycle(&hash(12)),
             Some(&TxLifecycleStatus::Dropped {
-                reason: "queue_full".to_owned(),
+                reason: DropReason::QueueFull,
             })
         );
         assert!(state.pending_hashes().is_empty());
@@ -0,0 +1,303 @@
+//! Incremental replay over event iterators.
+//!
+//! The streaming APIs hold only the replayed mempool state and, when
+//! reordering, a bounded window of events, so a log larger than memory can be
+//! replayed straight from a reader. Input is expected in canonical
+//! deterministic order (`seq_id` order for logs written by a single
+//! sequencer); [`DeterministicOrder`] repairs bounded disorder.
+
+use crate::{
+    CheckpointHash, CheckpointParityReport, LifecycleCheckpoint, MempoolState, ReplayFrame,
+    TxLifecycleStatus,
+};
+use common::TxHash;
+use event_log::{EventEnvelope, cmp_deterministic};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeSet, BinaryHeap};
+
+/// Iterator of replay frames, emitted every `stride` events and after the
+/// last event.
+pub struct ReplayStream<I> {
+    events: I,
+    state: MempoolState,
+    stride: usize,
+    replayed: usize,
+    after_seq_id: Option<u64>,
+    lookahead: Option<EventEnvelope>,
+}
+
+impl<I: Iterator<Item = EventEnvelope>> ReplayStream<I> {
+    /// Replays `events` from an empty mempool.
+    pub fn new(events: impl IntoIterator<IntoIter = I>, stride: usize) -> Self {
+        Self::with_state(events, MempoolState::default(), None, stride)
+    }
+
+    /// Continues replay from `checkpoint`, skipping events at or before its
+    /// sequence id.
+    pub fn from_checkpoint(
+        events: impl IntoIterator<IntoIter = I>,
+        checkpoint: &LifecycleCheckpoint,
+        stride: usize,
+    ) -> Self {
+        let state = if checkpoint.sender_queues.is_empty() {
+            MempoolState::from_pending_hashes(&checkpoint.pending_hashes)
+        } else {
+            MempoolState::from_checkpoint(&checkpoint.pending_hashes, &checkpoint.sender_queues)
+        };
+        Self::with_state(events, state, Some(checkpoint.seq_id), stride)
+    }
+
+    fn with_state(
+        events: impl IntoIterator<IntoIter = I>,
+        state: MempoolState,
+        after_seq_id: Option<u64>,
+        stride: usize,
+    ) -> Self {
+        Self {
+            events: events.into_iter(),
+            state,
+            stride: stride.max(1),
+            replayed: 0,
+            after_seq_id,
+            lookahead: None,
+        }
+    }
+
+    /// Mempool state after the events replayed so far.
+    pub fn state(&self) -> &MempoolState {
+        &self.state
+    }
+
+    /// Number of events replayed so far.
+    pub fn replayed(&self) -> usize {
+        self.replayed
+    }
+
+    fn pull(&mut self) -> Option<EventEnvelope> {
+        let after_seq_id = self.after_seq_id;
+        self.events
+            .by_ref()
+            .find(|event| after_seq_id.is_none_or(|after| event.seq_id > after))
+    }
+}
+
+impl<I: Iterator<Item = EventEnvelope>> Iterator for ReplayStream<I> {
+    type Item = ReplayFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut event = match self.lookahead.take() {
+            Some(event) => event,
+            None => self.pull()?,
+        };
+        loop {
+            self.state.apply_event(&event);
+            self.replayed += 1;
+            match self.pull() {
+                Some(next) if !self.replayed.is_multiple_of(self.stride) => event = next,
+                next => {
+                    self.lookahead = next;
+                    return Some(frame_at(&self.state, &event));
+                }
+            }
+        }
+    }
+}
+
+/// Streams replay frames from events in deterministic order.
+pub fn replay_frames_iter<I>(events: I, stride: usize) -> ReplayStream<I::IntoIter>
+where
+    I: IntoIterator<Item = EventEnvelope>,
+{
+    ReplayStream::new(events, stride)
+}
+
+/// Streams replay frames continuing from a previously captured checkpoint.
+pub fn replay_from_checkpoint_iter<I>(
+    events: I,
+    checkpoint: &LifecycleCheckpoint,
+    stride: usize,
+) -> ReplayStream<I::IntoIter>
+where
+    I: IntoIterator<Item = EventEnvelope>,
+{
+    ReplayStream::from_checkpoint(events, checkpoint, stride)
+}
+
+/// Streams checkpoints for the requested sequence ids, stopping once the last
+/// one has been emitted.
+pub fn lifecycle_checkpoints_iter<I>(
+    events: I,
+    checkpoint_seq_ids: &[u64],
+) -> impl Iterator<Item = LifecycleCheckpoint> + use<I>
+where
+    I: IntoIterator<Item = EventEnvelope>,
+{
+    let checkpoints: BTreeSet<u64> = checkpoint_seq_ids.iter().copied().collect();
+    let wanted = checkpoints.len();
+    let mut state = MempoolState::default();
+    events
+        .into_iter()
+        .filter_map(move |event| {
+            state.apply_event(&event);
+            checkpoints
+                .contains(&event.seq_id)
+                .then(|| checkpoint_at(&state, event.seq_id))
+        })
+        .take(wanted)
+}
+
+/// Streams deterministic checkpoint hashes at the given replay stride.
+pub fn deterministic_checkpoint_hashes_iter<I>(
+    events: I,
+    stride: usize,
+) -> impl Iterator<Item = CheckpointHash>
+where
+    I: IntoIterator<Item = EventEnvelope>,
+{
+    ReplayStream::new(events, stride).map(|frame| CheckpointHash {
+        seq_id: frame.seq_hi,
+        pending_count: frame.pending.len(),
+        checkpoint_hash: crate::frame_checkpoint_hash(&frame),
+    })
+}
+
+/// Compares checkpoint hashes of two streams at the given stride, merging
+/// them by sequence id so neither side is held in memory.
+pub fn checkpoint_hash_parity_iter<R, C>(
+    reference_events: R,
+    candidate_events: C,
+    stride: usize,
+) -> CheckpointParityReport
+where
+    R: IntoIterator<Item = EventEnvelope>,
+    C: IntoIterator<Item = EventEnvelope>,
+{
+    let mut candidate = deterministic_checkpoint_hashes_iter(candidate_events, stride).peekable();
+    let mut report = CheckpointParityReport {
+        reference_checkpoints: 0,
+        candidate_checkpoints: 0,
+        matched: 0,
+        first_mismatch_seq_id: None,
+        parity_percent: 0.0,
+    };
+    for reference in deterministic_checkpoint_hashes_iter(reference_events, stride) {
+        report.reference_checkpoints += 1;
+        while candidate
+            .next_if(|next| next.seq_id < reference.seq_id)
+            .is_some()
+        {
+            report.candidate_checkpoints += 1;
+        }
+        let matched = candidate
+            .next_if(|next| next.seq_id == reference.seq_id)
+            .inspect(|_| report.candidate_checkpoints += 1)
+            .is_some_and(|next| next.checkpoint_hash == reference.checkpoint_hash);
+        if matched {
+            report.matched += 1;
+        } else if report.first_mismatch_seq_id.is_none() {
+            report.first_mismatch_seq_id = Some(reference.seq_id);
+        }
+    }
+    report.candidate_checkpoints += candidate.count();
+    report.parity_percent = if report.reference_checkpoints == 0 {
+        if report.candidate_checkpoints == 0 {
+            100.0
+        } else {
+            0.0
+        }
+    } else {
+        (report.matched as f64 / report.reference_checkpoints as f64) * 100.0
+    };
+    report
+}
+
+/// Returns the lifecycle status of a transaction after replaying a stream.
+pub fn current_lifecycle_iter<I>(events: I, hash: TxHash) -> Option<TxLifecycleStatus>
+where
+    I: IntoIterator<Item = EventEnvelope>,
+{
+    let mut state = MempoolState::default();
+    for event in events {
+        state.apply_event(&event);
+    }
+    state.lifecycle(&hash).cloned()
+}
+
+/// Restores canonical deterministic order over a nearly sorted stream by
+/// holding up to `window` events.
+///
+/// Output is fully sorted when no event arrives more than `window` positions
+/// after an event that sorts behind it; beyond that the displaced event is
+/// emitted late rather than buffering without bound.
+pub struct DeterministicOrder<I> {
+    events: I,
+    window: usize,
+    buffered: BinaryHeap<Reverse<Ordered>>,
+}
+
+impl<I: Iterator<Item = EventEnvelope>> DeterministicOrder<I> {
+    pub fn new(events: impl IntoIterator<IntoIter = I>, window: usize) -> Self {
+        Self {
+            events: events.into_iter(),
+            window,
+            buffered: BinaryHeap::with_capacity(window.saturating_add(1).min(4096)),
+        }
+    }
+}
+
+impl<I: Iterator<Item = EventEnvelope>> Iterator for DeterministicOrder<I> {
+    type Item = EventEnvelope;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffered.len() <= self.window {
+            match self.events.next() {
+                Some(event) => self.buffered.push(Reverse(Ordered(event))),
+                None => break,
+            }
+        }
+        self.buffered.pop().map(|Reverse(Ordered(event))| event)
+    }
+}
+
+struct Ordered(EventEnvelope);
+
+impl PartialEq for Ordered {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ordered {}
+
+impl PartialOrd for Ordered {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ordered {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_deterministic(&self.0, &other.0)
+    }
+}
+
+fn frame_at(state: &MempoolState, event: &EventEnvelope) -> ReplayFrame {
+    let mut pending = state.pending_hashes();
+    pending.sort_unstable();
+    ReplayFrame {
+        seq_hi: event.seq_id,
+        timestamp_unix_ms: event.ingest_ts_unix_ms,
+        pending,
+        sender_queues: state.sender_queues(),
+    }
+}
+
+fn checkpoint_at(state: &MempoolState, seq_id: u64) -> LifecycleCheckpoint {
+    let mut pending = state.pending_hashes();
+    pending.sort_unstable();
+    LifecycleCheckpoint {
+        seq_id,
+        pending_hashes: pending,
+        sender_queues: state.sender_queues(),
+    }
+}
@@ -9,17 +9,22 @@ pub use crate::{
 use ahash::RandomState;
 use anyhow::{Context, Result, anyhow};
 use builder::{AssemblyCandidate, AssemblyDecision};
-use common::{Address, CandidateId, SourceId, TxHash};
+use common::{Address, CandidateId, ChainFamily, L2TxFields, SourceId, TxHash};
 use event_log::{
-    AssemblyDecisionApplied, BundleSubmitted, CandidateQueued, EventPayload, OppDetected,
-    SimCompleted, SimDispatched, TxBlocked, TxDecoded, TxDropped, TxFetched, TxReady, TxReplaced,
-    TxSeen,
+    AssemblyDecisionApplied, AssemblyDecisionKind, BundleSubmitted, CandidateQueued, DropReason,
+    EventPayload, OppDetected, SimCompleted, SimDispatched, SimFailCategory, SimulationStatus,
+    TxBlocked, TxDecoded, TxDropped, TxFetched, TxReady, TxReplaced, TxSeen,
 };
 use feature_engine::{
     FeatureAnalysis, FeatureInput, analyze_transaction, version as feature_engine_version,
 };
 use futures::{SinkExt, StreamExt};
 use hashbrown::{HashMap, HashSet};
+use ingest::{
+    ENTRY_POINT_V06_ADDRESS, ENTRY_POINT_V07_ADDRESS, InMemoryUserOpProvider, PendingUserOp,
+    SystemClock, UserOpIngestConfig, UserOpIngestService, decode_bundler_mempool_dump,
+    entry_point_version,
+};
 use parking_lot::RwLock;
 use scheduler::{
     SchedulerAdmission, SchedulerCandidate, SchedulerEnqueueError, SchedulerHandle,
@@ -30,8 +35,8 @@ use searcher::{OpportunityCandidate, SearcherConfig, SearcherInputTx, rank_oppor
 use serde::{Deserialize, de::IgnoredAny};
 use serde_json::json;
 use sim_engine::{
-    AccountSeed, ChainContext, SimulationFailCategory, SimulationMode, SimulationTxInput,
-    StateProvider, simulate_with_mode,
+    AccountSeed, ChainContext, OpL1FeeFormula, OpL1FeeParams, SimulationFailCategory,
+    SimulationMode, SimulationTxInput, StateProvider, estimate_l1_data_fee, simulate_with_mode,
 };
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fs;
@@ -76,8 +81,12 @@ const DEFAULT_SIM_CACHE_TTL_MS: u64 = 5_000;
 const DEFAULT_SIM_RPC_TIMEOUT_MS: u64 = 2_000;
 const DEFAULT_SIM_QUEUE_CAPACITY: usize = 128;
 const DEFAULT_SIM_WORKER_COUNT: usize = 4;
-const SEARCHER_MIN_SCORE: u32 = 0;
-const SEARCHER_MAX_CANDIDATES: usize = 8;
+const BUNDLER_POLL_INTERVAL_MS: u64 = 1_000;
+const OP_L1_BLOCK_PREDEPLOY: &str = "0x4200000000000000000000000000000000000015";
+const OP_L1_BLOCK_BASEFEE_SELECTOR: &str = "0x5cf24969";
+const OP_L1_BLOCK_BLOB_BASE_FEE_SELECTOR: &str = "0xf8206140";
+const OP_L1_BLOCK_BASE_FEE_SCALAR_SELECTOR: &str = "0xc5985918";
+const OP_L1_BLOCK_BLOB_BASE_FEE_SCALAR_SELECTOR: &str = "0x68d5dca6";
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct ExecutableOpportunity {
@@ -97,7 +106,7 @@ struct RemoteSimulationRequest {
 struct RemoteSimulationOutcome {
     sim_id: String,
     status: RemoteSimulationStatus,
-    fail_category: Option<String>,
+    fail_category: Option<SimFailCategory>,
     latency_ms: u64,
     tx_count: u32,
     simulation_batch: Option<sim_engine::SimulationBatchResult>,
@@ -146,10 +155,20 @@ struct LiveRpcSimulationRuntime {
     ingress_tx: mpsc::Sender<SimulationTask>,
 }
 
+/// User operation ingest for one chain; fed by the bundler poller and by
+/// `handleOps` bundles on the live tx stream.
+type LiveUserOpIngest = UserOpIngestService<InMemoryUserOpProvider, SystemClock>;
+
 #[derive(Clone)]
 struct LiveRpcStateOwner {
     runtime_core: RuntimeCoreHandle,
     simulation_service: LiveRpcSimulationService,
+    /// Latest OP-stack L1 fee parameters per chain id, refreshed by each
+    /// remote simulation and used to price searcher candidates.
+    op_l1_fee_params: Arc<RwLock<HashMap<u64, OpL1FeeParams>>>,
+    /// User operation ingest per chain id, shared so alt-mempool and bundle
+    /// sightings dedup against each other.
+    user_op_ingest: Arc<parking_lot::Mutex<HashMap<u64, LiveUserOpIngest>>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -174,6 +193,14 @@ struct RpcHeaderResponseEnvelope {
     result: Option<RpcHeader>,
 }
 
+#[derive(Debug, Deserialize)]
+struct BundlerMempoolResponseEnvelope {
+    #[serde(default)]
+    error: Option<RpcFetchErrorEnvelope>,
+    #[serde(default)]
+    result: Option<Box<serde_json::value::RawValue>>,
+}
+
 #[derive(Debug, Deserialize)]
 struct RpcScalarResponseEnvelope {
     #[serde(default)]
@@ -189,6 +216,8 @@ impl LiveRpcStateOwner {
         Self {
             runtime_core: handle,
             simulation_service: LiveRpcSimulationService::new(queue_capacity, worker_total),
+            op_l1_fee_params: Arc::new(RwLock::new(HashMap::new())),
+            user_op_ingest: Arc::new(parking_lot::Mutex::new(HashMap::new())),
         }
     }
 
@@ -196,6 +225,85 @@ impl LiveRpcStateOwner {
         &self.runtime_core
     }
 
+    fn op_l1_fee_params(&self, chain_id: Option<u64>) -> Option<OpL1FeeParams> {
+        self.op_l1_fee_params.read().get(&chain_id?).copied()
+    }
+
+    fn observe_op_l1_fee_params(&self, chain_context: &ChainContext) {
+        if let Some(params) = chain_context.op_l1_fee {
+            self.op_l1_fee_params
+                .write()
+                .insert(chain_context.chain_id, params);
+        }
+    }
+
+    fn with_user_op_ingest<T>(
+        &self,
+        chain: &ChainRpcConfig,
+        chain_id: u64,
+        apply: impl FnOnce(&mut LiveUserOpIngest) -> T,
+    ) -> T {
+        let mut services = self.user_op_ingest.lock();
+        let service = services.entry(chain_id).or_insert_with(|| {
+            UserOpIngestService::with_config(
+                InMemoryUserOpProvider::default(),
+                chain.source_id.clone(),
+                SystemClock::default(),
+                UserOpIngestConfig {
+                    chain_id,
+                    ..UserOpIngestConfig::default()
+                },
+            )
+        });
+        apply(service)
+    }
+
+    /// Returns `UserOpSeen` payloads for operations not previously seen on
+    /// `chain_id`.
+    fn observe_bundler_user_ops(
+        &self,
+        chain: &ChainRpcConfig,
+        chain_id: u64,
+        batch: Vec<PendingUserOp>,
+    ) -> Result<Vec<EventPayload>> {
+        self.with_user_op_ingest(chain, chain_id, |service| {
+            service.provider_mut().push_batch(batch);
+            service.process_pending_user_ops()
+        })
+        .map(|events| events.into_iter().map(|event| event.payload).collect())
+        .map_err(|err| anyhow!("user operation ingest failed: {err}"))
+    }
+
+    /// Returns `UserOpSeen` payloads linking every operation in a `handleOps`
+    /// bundle to `tx`.
+    fn link_user_op_bundle(
+        &self,
+        chain: &ChainRpcConfig,
+        chain_id: Option<u64>,
+        tx: &LiveTx,
+    ) -> Vec<EventPayload> {
+        let (Some(chain_id), Some(to)) = (chain_id, tx.to) else {
+            return Vec::new();
+        };
+        if entry_point_version(to).is_none() {
+            return Vec::new();
+        }
+        match self.with_user_op_ingest(chain, chain_id, |service| {
+            service.process_bundle_transaction(tx.hash, Some(to), &tx.input)
+        }) {
+            Ok(events) => events.into_iter().map(|event| event.payload).collect(),
+            Err(err) => {
+                tracing::debug!(
+                    error = %err,
+                    chain_key = %chain.chain_key,
+                    hash = %format_fixed_hex(&tx.hash),
+                    "failed to decode handleOps bundle"
+                );
+                Vec::new()
+            }
+        }
+    }
+
     fn reset_drop_metrics(&self) {
         self.handle().reset_drop_metrics();
     }
@@ -249,7 +357,7 @@ impl LiveRpcStateOwner {
         fail_category: Option<&str>,
     ) {
         self.handle().observe_simulation_result(
-            simulation_status_label(status),
+            simulation_status(status).as_str(),
             latency_ms,
             tx_count,
             fail_category,
@@ -519,11 +627,11 @@ fn build_simulation_status_snapshot(
     LiveRpcSimulationStatusSnapshot {
         id: outcome.sim_id.clone(),
         bundle_id,
-        status: simulation_status_label(outcome.status).to_owned(),
+        status: simulation_status(outcome.status).to_string(),
         relay_url: "not_submitted".to_owned(),
         attempt_count: 0,
         accepted: outcome.status == RemoteSimulationStatus::Ok,
-        fail_category: outcome.fail_category.clone(),
+        fail_category: outcome.fail_category.as_ref().map(ToString::to_string),
         started_unix_ms: task.enqueued_unix_ms,
         finished_unix_ms,
     }
@@ -683,6 +791,10 @@ struct EnvChainConfig {
     endpoints: Vec<EnvRpcEndpointConfig>,
     #[serde(default)]
     source_id: Option<String>,
+    #[serde(default)]
+    bundler_http_url: Option<String>,
+    #[serde(default)]
+    chain_family: Option<ChainFamily>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -699,6 +811,8 @@ pub struct ChainRpcConfig {
     chain_id: Option<u64>,
     endpoints: Vec<RpcEndpoint>,
     source_id: SourceId,
+    bundler_http_url: Option<String>,
+    chain_family: Option<ChainFamily>,
 }
 
 impl ChainRpcConfig {
@@ -730,6 +844,17 @@ impl ChainRpcConfig {
             .first()
             .map(|endpoint| endpoint.http_url.as_str())
     }
+
+    /// Returns the bundler JSON-RPC endpoint polled for user operations, if any.
+    pub fn bundler_http_url(&self) -> Option<&str> {
+        self.bundler_http_url.as_deref()
+    }
+
+    /// Returns the execution-layer family of the chain, either as declared in
+    /// its config or looked up from the configured chain id.
+    pub fn chain_family(&self) -> Option<ChainFamily> {
+        self.chain_family
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -780,6 +905,8 @@ impl Default for LiveRpcConfig {
                     },
                 ],
                 source_id: SourceId::new("rpc-live"),
+                bundler_http_url: None,
+                chain_family: Some(ChainFamily::Ethereum),
             }],
             max_seen_hashes: 10_000,
             batch_fetch: BatchFetchConfig::default(),
@@ -1050,11 +1177,24 @@ fn parse_env_chain_config(chain: EnvChainConfig) -> Result<ChainRpcConfig> {
         .filter(|value| !value.is_empty())
         .unwrap_or_else(|| format!("rpc-{chain_key}"));
 
+    let bundler_http_url = chain
+        .bundler_http_url
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty());
+
+    let chain_family = chain.chain_family.or_else(|| {
+        chain
+            .chain_id
+            .map(|chain_id| ChainFamily::from_chain_id(Some(chain_id)))
+    });
+
     Ok(ChainRpcConfig {
         chain_key: chain_key.to_owned(),
         chain_id: chain.chain_id,
         endpoints,
         source_id: SourceId::new(source_id),
+        bundler_http_url,
+        chain_family,
     })
 }
 
@@ -1266,6 +1406,20 @@ async fn run_chain_worker(worker: LiveRpcChainWorkerContext) {
         }
     }
 
+    if let (Some(bundler_http_url), Some(chain_id)) =
+        (chain.bundler_http_url.clone(), chain.chain_id)
+    {
+        tokio::spawn(run_bundler_poller(
+            state_owner.clone(),
+            writer.clone(),
+            chain.clone(),
+            chain_id,
+            bundler_http_url,
+            client.clone(),
+            next_seq_id.clone(),
+        ));
+    }
+
     let mut seen_hashes = FastSet::default();
     let mut seen_order = VecDeque::new();
     let mut endpoint_index = 0usize;
@@ -1380,6 +1534,61 @@ async fn run_chain_worker(worker: LiveRpcChainWorkerContext) {
     }
 }
 
+/// Polls the bundler alt-mempool for each known EntryPoint and appends
+/// `UserOpSeen` events for operations not seen before.
+async fn run_bundler_poller(
+    state_owner: LiveRpcStateOwner,
+    writer: StorageWriteHandle,
+    chain: ChainRpcConfig,
+    chain_id: u64,
+    bundler_http_url: String,
+    client: reqwest::Client,
+    next_seq_id: Arc<AtomicU64>,
+) {
+    let mut interval = tokio::time::interval(Duration::from_millis(BUNDLER_POLL_INTERVAL_MS));
+    loop {
+        interval.tick().await;
+        for entry_point in [ENTRY_POINT_V06_ADDRESS, ENTRY_POINT_V07_ADDRESS] {
+            let payloads = match fetch_bundler_user_ops(&client, &bundler_http_url, entry_point)
+                .await
+                .and_then(|batch| state_owner.observe_bundler_user_ops(&chain, chain_id, batch))
+            {
+                Ok(payloads) => payloads,
+                Err(err) => {
+                    tracing::debug!(
+                        error = %err,
+                        chain_key = %chain.chain_key,
+                        entry_point = %format_fixed_hex(&entry_point),
+                        "bundler mempool poll failed"
+                    );
+                    continue;
+                }
+            };
+            for payload in payloads {
+                match append_event_with_owner(
+                    &state_owner,
+                    &writer,
+                    &chain,
+                    &next_seq_id,
+                    current_unix_ms(),
+                    payload,
+                ) {
+                    Ok(true) => {}
+                    Ok(false) => break,
+                    Err(err) => {
+                        tracing::warn!(
+                            error = %err,
+                            chain_key = %chain.chain_key,
+                            "stopping bundler poller after storage writer closed"
+                        );
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
 async fn run_chain_pending_pool_rebuild(
     state_owner: LiveRpcStateOwner,
     writer: StorageWriteHandle,
@@ -1554,6 +1763,7 @@ async fn run_ws_session(
                             );
                         }
                     }
+                    #[allow(clippy::collapsible_match)]
                     Message::Ping(payload) => {
                         if write.send(Message::Pong(payload)).await.is_err() {
                             break;
@@ -1748,6 +1958,7 @@ async fn process_pending_hash_batch(
             .iter()
             .map(|observation| observation.hash_hex.clone())
             .collect::<Vec<_>>(),
+        session.chain.chain_family(),
         session.batch_fetch,
     )
     .await?;
@@ -1766,6 +1977,7 @@ async fn process_pending_hash_batch(
         "live rpc batch fetch metrics"
     );
 
+    #[allow(clippy::useless_conversion)]
     for ((observation, hash), fetched_tx) in deduped_observations
         .iter()
         .zip(deduped_raw.into_iter())
@@ -1789,12 +2001,18 @@ async fn process_pending_hash_batch(
     Ok(())
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 enum SchedulerPersistenceDecision {
     Admitted,
     Duplicate,
-    Replaced { replaced_hash: TxHash },
-    Dropped { reason: &'static str },
+    Replaced {
+        replaced_hash: TxHash,
+    },
+    Dropped {
+        reason: DropReason,
+    },
+    /// Protocol-originated transaction that never enters the mempool scheduler.
+    SystemTxExcluded,
 }
 
 impl SchedulerPersistenceDecision {
@@ -1804,42 +2022,43 @@ impl SchedulerPersistenceDecision {
             SchedulerAdmission::Duplicate => Self::Duplicate,
             SchedulerAdmission::Replaced { replaced_hash } => Self::Replaced { replaced_hash },
             SchedulerAdmission::UnderpricedReplacement => Self::Dropped {
-                reason: "underpriced_replacement",
+                reason: DropReason::UnderpricedReplacement,
             },
             SchedulerAdmission::SenderLimitReached => Self::Dropped {
-                reason: "sender_limit_reached",
+                reason: DropReason::SenderLimitReached,
             },
         }
     }
 
-    fn is_admitted(self) -> bool {
+    fn is_admitted(&self) -> bool {
         matches!(self, Self::Admitted | Self::Replaced { .. })
     }
 
-    fn emits_decoded(self) -> bool {
+    fn emits_decoded(&self) -> bool {
         self.is_admitted()
     }
 
-    fn replaced_hash(self) -> Option<TxHash> {
+    fn replaced_hash(&self) -> Option<TxHash> {
         match self {
-            Self::Replaced { replaced_hash } => Some(replaced_hash),
+            Self::Replaced { replaced_hash } => Some(*replaced_hash),
             _ => None,
         }
     }
 
-    fn dropped_reason(self) -> Option<&'static str> {
+    fn dropped_reason(&self) -> Option<&DropReason> {
         match self {
             Self::Dropped { reason } => Some(reason),
             _ => None,
         }
     }
 
-    fn label(self) -> &'static str {
+    fn label(&self) -> &str {
         match self {
             Self::Admitted => "admitted",
             Self::Duplicate => "duplicate",
             Self::Replaced { .. } => "replaced",
-            Self::Dropped { reason } => reason,
+            Self::Dropped { reason } => reason.as_str(),
+            Self::SystemTxExcluded => "system_tx_excluded",
         }
     }
 }
@@ -1909,7 +2128,16 @@ async fn process_pending_hash_with_fetched_tx_with_owner(
         );
     }
 
-    let scheduler_result = if let Some(tx) = fetched_tx.as_ref() {
+    let scheduler_result = if let Some(tx) = fetched_tx.as_ref().filter(|tx| tx.system_tx) {
+        tracing::debug!(
+            chain_key = %chain.chain_key,
+            source_id = %chain.source_id,
+            hash = %observation.hash_hex,
+            tx_type = tx.tx_type,
+            "system transaction excluded from scheduler admission"
+        );
+        Some((SchedulerPersistenceDecision::SystemTxExcluded, Vec::new()))
+    } else if let Some(tx) = fetched_tx.as_ref() {
         let validated = validated_transaction_from_live_tx(
             chain,
             observation.observed_at_unix_ms,
@@ -1923,7 +2151,7 @@ async fn process_pending_hash_with_fetched_tx_with_owner(
             ),
             Err(SchedulerEnqueueError::QueueFull) => (
                 SchedulerPersistenceDecision::Dropped {
-                    reason: "queue_full",
+                    reason: DropReason::QueueFull,
                 },
                 Vec::new(),
             ),
@@ -1953,6 +2181,7 @@ async fn process_pending_hash_with_fetched_tx_with_owner(
         chain,
         StorageWriteOp::AppendPayload {
             source_id: chain.source_id.clone(),
+            chain_id: chain.chain_id,
             ingest_ts_unix_ms: observation.observed_at_unix_ms,
             ingest_ts_mono_ns: observation.observed_at_mono_ns,
             payload: EventPayload::TxSeen(TxSeen {
@@ -2026,6 +2255,7 @@ async fn process_pending_hash_with_fetched_tx_with_owner(
                 max_fee_per_blob_gas_wei: tx.max_fee_per_blob_gas_wei,
                 calldata_len: Some(raw_tx.len() as u32),
                 raw_tx: raw_tx.clone(),
+                l2_fields: tx.l2_fields.clone().map(Box::new),
             }),
         )? {
             return Ok(());
@@ -2078,6 +2308,19 @@ async fn process_pending_hash_with_fetched_tx_with_owner(
         {
             return Ok(());
         }
+        for payload in state_owner.link_user_op_bundle(chain, resolved_chain_id, &tx) {
+            if !append_event_with_owner(
+                state_owner,
+                writer,
+                chain,
+                next_seq_id,
+                processed_at_unix_ms,
+                payload,
+            )? {
+                return Ok(());
+            }
+        }
+        #[allow(clippy::collapsible_if)]
         if let Some(reason) = scheduler_decision.dropped_reason() {
             if !append_event_with_owner(
                 state_owner,
@@ -2085,10 +2328,7 @@ async fn process_pending_hash_with_fetched_tx_with_owner(
                 chain,
                 next_seq_id,
                 processed_at_unix_ms,
-                EventPayload::TxDropped(TxDropped {
-                    hash: tx.hash,
-                    reason: reason.to_owned(),
-                }),
+                EventPayload::TxDropped(TxDropped::new(tx.hash, reason.clone())),
             )? {
                 return Ok(());
             }
@@ -2113,6 +2353,7 @@ async fn process_pending_hash_with_fetched_tx_with_owner(
             &executable_transactions,
             processed_at_unix_ms,
             resolved_chain_id,
+            state_owner.op_l1_fee_params(resolved_chain_id).as_ref(),
         );
         if !executable_transactions.is_empty() {
             let executable_records = executable_opportunities
@@ -2163,6 +2404,7 @@ async fn process_pending_hash_with_fetched_tx_with_owner(
                 String,
                 (RemoteSimulationRequest, Vec<RegisteredSimulationCandidate>),
             >::new();
+            #[allow(clippy::useless_conversion)]
             for (task_spec, (candidate, request, opportunity)) in dispatch
                 .simulation_tasks
                 .into_iter()
@@ -2238,7 +2480,13 @@ async fn rebuild_scheduler_from_pending_pool_with_owner(
 ) -> Result<usize> {
     let mut last_error: Option<anyhow::Error> = None;
     for endpoint in &chain.endpoints {
-        match fetch_pending_block_transactions(client, endpoint.http_url.as_str()).await {
+        match fetch_pending_block_transactions(
+            client,
+            endpoint.http_url.as_str(),
+            chain.chain_family(),
+        )
+        .await
+        {
             Ok(pending) => {
                 return rebuild_scheduler_from_pending_transactions_with_owner(
                     state_owner,
@@ -2376,6 +2624,7 @@ fn append_event_with_owner(
         chain,
         StorageWriteOp::AppendPayload {
             source_id: chain.source_id.clone(),
+            chain_id: chain.chain_id,
             ingest_ts_unix_ms: now_unix_ms,
             ingest_ts_mono_ns: seq_id.saturating_mul(1_000_000),
             payload,
@@ -2455,6 +2704,34 @@ struct RpcTransaction {
     max_fee_per_blob_gas: Option<String>,
     #[serde(default)]
     input: Option<String>,
+    #[serde(default, rename = "sourceHash")]
+    source_hash: Option<String>,
+    #[serde(default)]
+    mint: Option<String>,
+    #[serde(default, rename = "isSystemTx")]
+    is_system_tx: Option<bool>,
+    #[serde(default, rename = "l1Fee")]
+    l1_fee: Option<String>,
+    #[serde(default, rename = "l1GasUsed")]
+    l1_gas_used: Option<String>,
+    #[serde(default, rename = "l1GasPrice")]
+    l1_gas_price: Option<String>,
+    #[serde(default, rename = "l1BlobBaseFee")]
+    l1_blob_base_fee: Option<String>,
+    #[serde(default, rename = "l1BaseFeeScalar")]
+    l1_base_fee_scalar: Option<String>,
+    #[serde(default, rename = "l1BlobBaseFeeScalar")]
+    l1_blob_base_fee_scalar: Option<String>,
+    #[serde(default, rename = "requestId")]
+    request_id: Option<String>,
+    #[serde(default, rename = "ticketId")]
+    ticket_id: Option<String>,
+    #[serde(default, rename = "refundTo")]
+    refund_to: Option<String>,
+    #[serde(default, rename = "l1BaseFee")]
+    l1_base_fee: Option<String>,
+    #[serde(default, rename = "maxRefund")]
+    max_refund: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -2472,6 +2749,10 @@ struct LiveTx {
     max_priority_fee_per_gas_wei: Option<u128>,
     max_fee_per_blob_gas_wei: Option<u128>,
     input: Vec<u8>,
+    /// Protocol-originated transaction (OP deposit, Arbitrum system tx) that
+    /// must bypass mempool scheduling.
+    system_tx: bool,
+    l2_fields: Option<L2TxFields>,
 }
 
 #[inline]
@@ -2512,11 +2793,9 @@ fn build_opportunity_records(
         &[SearcherInputTx {
             decoded: decoded.clone(),
             calldata: calldata.to_vec().into(),
+            l1_data_fee_wei: None,
         }],
-        SearcherConfig {
-            min_score: SEARCHER_MIN_SCORE,
-            max_candidates: SEARCHER_MAX_CANDIDATES,
-        },
+        SearcherConfig::live(),
     )
     .candidates
     .into_iter()
@@ -2540,6 +2819,7 @@ fn build_executable_opportunities(
     executable: &[ValidatedTransaction],
     detected_unix_ms: i64,
     chain_id: Option<u64>,
+    op_l1_fee: Option<&OpL1FeeParams>,
 ) -> Vec<ExecutableOpportunity> {
     if executable.is_empty() {
         return Vec::new();
@@ -2547,35 +2827,34 @@ fn build_executable_opportunities(
 
     let batch = executable
         .iter()
-        .map(|tx| SearcherInputTx::borrowed(tx.decoded.clone(), tx.calldata.as_slice()))
+        .map(|tx| {
+            let l1_data_fee_wei = op_l1_fee
+                .and_then(|params| estimate_l1_data_fee(&tx.decoded, &tx.calldata, params));
+            SearcherInputTx::borrowed(tx.decoded.clone(), tx.calldata.as_slice())
+                .with_l1_data_fee(l1_data_fee_wei)
+        })
         .collect::<Vec<_>>();
 
-    rank_opportunity_batch(
-        &batch,
-        SearcherConfig {
-            min_score: SEARCHER_MIN_SCORE,
-            max_candidates: SEARCHER_MAX_CANDIDATES,
-        },
-    )
-    .candidates
-    .into_iter()
-    .map(|candidate| {
-        let record = OpportunityRecord {
-            tx_hash: candidate.tx_hash,
-            strategy: format!("{:?}", candidate.strategy),
-            score: candidate.score,
-            protocol: candidate.protocol.clone(),
-            category: candidate.category.clone(),
-            feature_engine_version: candidate.feature_engine_version.clone(),
-            scorer_version: candidate.scorer_version.clone(),
-            strategy_version: candidate.strategy_version.clone(),
-            reasons: candidate.reasons.clone(),
-            detected_unix_ms,
-            chain_id,
-        };
-        ExecutableOpportunity { record, candidate }
-    })
-    .collect()
+    rank_opportunity_batch(&batch, SearcherConfig::live())
+        .candidates
+        .into_iter()
+        .map(|candidate| {
+            let record = OpportunityRecord {
+                tx_hash: candidate.tx_hash,
+                strategy: format!("{:?}", candidate.strategy),
+                score: candidate.score,
+                protocol: candidate.protocol.clone(),
+                category: candidate.category.clone(),
+                feature_engine_version: candidate.feature_engine_version.clone(),
+                scorer_version: candidate.scorer_version.clone(),
+                strategy_version: candidate.strategy_version.clone(),
+                reasons: candidate.reasons.clone(),
+                detected_unix_ms,
+                chain_id,
+            };
+            ExecutableOpportunity { record, candidate }
+        })
+        .collect()
 }
 
 fn ready_transactions_for_queue_transitions(
@@ -2749,6 +3028,7 @@ async fn simulate_remote_request_with_owner(
             .primary_http_url()
             .ok_or_else(|| anyhow!("no http endpoint configured for simulation"))?;
         let chain_context = fetch_remote_chain_context(client, chain, http_url, request).await?;
+        state_owner.observe_op_l1_fee_params(&chain_context);
         let account_seeds = fetch_cached_account_seeds(
             state_owner,
             client,
@@ -2764,6 +3044,7 @@ async fn simulate_remote_request_with_owner(
             .map(|tx| SimulationTxInput {
                 decoded: tx.decoded.clone(),
                 calldata: Some(tx.calldata.clone()),
+                raw_tx: None,
             })
             .collect::<Vec<_>>();
         simulate_with_mode(
@@ -2782,16 +3063,21 @@ async fn simulate_remote_request_with_owner(
         }
         Ok(Err(_)) => (
             RemoteSimulationStatus::StateError,
-            Some("state_rpc".to_owned()),
+            Some(SimFailCategory::StateRpc),
             None,
         ),
         Err(_) => (
             RemoteSimulationStatus::Timeout,
-            Some("state_timeout".to_owned()),
+            Some(SimFailCategory::StateTimeout),
             None,
         ),
     };
-    state_owner.observe_simulation_result(status, latency_ms, tx_count, fail_category.as_deref());
+    state_owner.observe_simulation_result(
+        status,
+        latency_ms,
+        tx_count,
+        fail_category.as_ref().map(SimFailCategory::as_str),
+    );
 
     Ok(RemoteSimulationOutcome {
         sim_id,
@@ -2826,13 +3112,21 @@ fn build_sim_completed_payload(
     SimCompleted {
         hash: opportunity.tx_hash,
         sim_id: outcome.sim_id.clone(),
-        status: simulation_status_label(outcome.status).to_owned(),
+        status: simulation_status(outcome.status),
         feature_engine_version: opportunity.feature_engine_version.clone(),
         scorer_version: opportunity.scorer_version.clone(),
         strategy_version: opportunity.strategy_version.clone(),
         fail_category: outcome.fail_category.clone(),
         latency_ms: Some(outcome.latency_ms),
         tx_count: Some(outcome.tx_count),
+        l1_data_fee_wei: outcome
+            .simulation_batch
+            .as_ref()
+            .and_then(sim_engine::SimulationBatchResult::total_l1_data_fee_wei),
+        gas_cost_wei: outcome
+            .simulation_batch
+            .as_ref()
+            .map(sim_engine::SimulationBatchResult::total_gas_cost_wei),
     }
 }
 
@@ -2848,7 +3142,7 @@ fn build_assembly_decision_payload(
         } => EventPayload::AssemblyDecisionApplied(AssemblyDecisionApplied {
             candidate_id: candidate_id.clone(),
             tx_hash,
-            decision: "inserted".to_owned(),
+            decision: AssemblyDecisionKind::Inserted,
             replaced_candidate_ids: replaced_candidate_ids.clone(),
             reason: None,
             block_number,
@@ -2859,7 +3153,7 @@ fn build_assembly_decision_payload(
         } => EventPayload::AssemblyDecisionApplied(AssemblyDecisionApplied {
             candidate_id: candidate_id.clone(),
             tx_hash,
-            decision: "rejected".to_owned(),
+            decision: AssemblyDecisionKind::Rejected,
             replaced_candidate_ids: Vec::new(),
             reason: Some(reason.clone()),
             block_number,
@@ -2878,35 +3172,33 @@ fn build_bundle_id(hash: TxHash, detected_unix_ms: i64) -> String {
 
 fn summarize_simulation_batch(
     batch: &sim_engine::SimulationBatchResult,
-) -> (RemoteSimulationStatus, Option<String>) {
+) -> (RemoteSimulationStatus, Option<SimFailCategory>) {
     if let Some(failed) = batch.tx_results.iter().find(|result| !result.success) {
         let category = failed
             .fail_category
-            .map(simulation_fail_category_label)
-            .unwrap_or("unknown")
-            .to_owned();
+            .map_or(SimFailCategory::Unknown, sim_fail_category);
         return (RemoteSimulationStatus::Failed, Some(category));
     }
 
     (RemoteSimulationStatus::Ok, None)
 }
 
-fn simulation_status_label(status: RemoteSimulationStatus) -> &'static str {
+fn simulation_status(status: RemoteSimulationStatus) -> SimulationStatus {
     match status {
-        RemoteSimulationStatus::Ok => "ok",
-        RemoteSimulationStatus::Failed => "failed",
-        RemoteSimulationStatus::StateError => "state_error",
-        RemoteSimulationStatus::Timeout => "timeout",
+        RemoteSimulationStatus::Ok => SimulationStatus::Ok,
+        RemoteSimulationStatus::Failed => SimulationStatus::Failed,
+        RemoteSimulationStatus::StateError => SimulationStatus::StateError,
+        RemoteSimulationStatus::Timeout => SimulationStatus::Timeout,
     }
 }
 
-fn simulation_fail_category_label(category: SimulationFailCategory) -> &'static str {
+fn sim_fail_category(category: SimulationFailCategory) -> SimFailCategory {
     match category {
-        SimulationFailCategory::Revert => "revert",
-        SimulationFailCategory::OutOfGas => "out_of_gas",
-        SimulationFailCategory::NonceMismatch => "nonce_mismatch",
-        SimulationFailCategory::StateMismatch => "state_mismatch",
-        SimulationFailCategory::Unknown => "unknown",
+        SimulationFailCategory::Revert => SimFailCategory::Revert,
+        SimulationFailCategory::OutOfGas => SimFailCategory::OutOfGas,
+        SimulationFailCategory::NonceMismatch => SimFailCategory::NonceMismatch,
+        SimulationFailCategory::StateMismatch => SimFailCategory::StateMismatch,
+        SimulationFailCategory::Unknown => SimFailCategory::Unknown,
     }
 }
 
@@ -2991,6 +3283,27 @@ async fn fetch_remote_chain_context(
         .as_deref()
         .and_then(parse_fixed_hex::<32>)
         .unwrap_or([0_u8; 32]);
+    let family = chain
+        .chain_family()
+        .unwrap_or_else(|| ChainFamily::from_chain_id(Some(chain_id)));
+    let op_l1_fee = if family == ChainFamily::OpStack {
+        match fetch_op_l1_fee_params(client, http_url).await {
+            Ok(params) => Some(params),
+            Err(err) => {
+                // Execution results stay valid without the L1 component, so a
+                // missing oracle read only degrades the cost estimate.
+                tracing::warn!(
+                    error = %err,
+                    chain_key = %chain.chain_key,
+                    http_url,
+                    "op-stack L1 fee parameter fetch failed; simulating without L1 data fee"
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
 
     Ok(ChainContext {
         chain_id,
@@ -3000,9 +3313,53 @@ async fn fetch_remote_chain_context(
         base_fee_wei,
         coinbase,
         state_root,
+        op_l1_fee,
     })
 }
 
+async fn fetch_op_l1_fee_params(client: &reqwest::Client, http_url: &str) -> Result<OpL1FeeParams> {
+    let mut words = [0_u128; 4];
+    for (index, selector) in [
+        OP_L1_BLOCK_BASEFEE_SELECTOR,
+        OP_L1_BLOCK_BLOB_BASE_FEE_SELECTOR,
+        OP_L1_BLOCK_BASE_FEE_SCALAR_SELECTOR,
+        OP_L1_BLOCK_BLOB_BASE_FEE_SCALAR_SELECTOR,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let raw = fetch_rpc_scalar(
+            client,
+            http_url,
+            47 + index as u64,
+            "eth_call",
+            json!([{ "to": OP_L1_BLOCK_PREDEPLOY, "data": selector }, "latest"]),
+        )
+        .await?;
+        words[index] =
+            parse_abi_word_u128(&raw).ok_or_else(|| anyhow!("invalid L1Block word: {raw}"))?;
+    }
+
+    Ok(OpL1FeeParams {
+        formula: OpL1FeeFormula::Fjord,
+        l1_base_fee_wei: words[0],
+        l1_blob_base_fee_wei: words[1],
+        base_fee_scalar: u32::try_from(words[2]).context("L1Block baseFeeScalar overflow")?,
+        blob_base_fee_scalar: u32::try_from(words[3])
+            .context("L1Block blobBaseFeeScalar overflow")?,
+    })
+}
+
+/// Parses one 32-byte ABI return word, rejecting values wider than `u128`.
+fn parse_abi_word_u128(value: &str) -> Option<u128> {
+    let trimmed = value.strip_prefix("0x").unwrap_or(value);
+    let significant = trimmed.trim_start_matches('0');
+    if significant.len() > 32 {
+        return None;
+    }
+    parse_hex_u128(significant)
+}
+
 async fn fetch_cached_account_seeds(
     state_owner: &LiveRpcStateOwner,
     client: &reqwest::Client,
@@ -3110,6 +3467,48 @@ async fn fetch_rpc_scalar(
         .ok_or_else(|| anyhow!("rpc scalar response missing result"))
 }
 
+async fn fetch_bundler_user_ops(
+    client: &reqwest::Client,
+    bundler_http_url: &str,
+    entry_point: Address,
+) -> Result<Vec<PendingUserOp>> {
+    let version = entry_point_version(entry_point)
+        .ok_or_else(|| anyhow!("unknown entry point {}", format_fixed_hex(&entry_point)))?;
+    let body = rpc_post_bytes(
+        client,
+        bundler_http_url,
+        &json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "debug_bundler_dumpMempool",
+            "params": [format_fixed_hex(&entry_point)],
+        }),
+    )
+    .await?;
+    let response: BundlerMempoolResponseEnvelope =
+        serde_json::from_slice(&body).context("decode bundler mempool response")?;
+    if let Some(error) = response.error {
+        return Err(anyhow!(
+            "bundler mempool dump failed: {}",
+            error
+                .message
+                .unwrap_or_else(|| "unknown rpc error".to_owned())
+        ));
+    }
+    let Some(result) = response.result else {
+        return Ok(Vec::new());
+    };
+    let user_ops = decode_bundler_mempool_dump(version, result.get())
+        .map_err(|err| anyhow!("decode bundler mempool dump: {err}"))?;
+    Ok(user_ops
+        .into_iter()
+        .map(|user_op| PendingUserOp {
+            entry_point,
+            user_op,
+        })
+        .collect())
+}
+
 struct CachedStateProviderView {
     account_seeds: HashMap<Address, AccountSeed>,
 }
@@ -3146,6 +3545,7 @@ async fn fetch_transaction_by_hash(
     client: &reqwest::Client,
     http_url: &str,
     hash_hex: &str,
+    chain_family: Option<ChainFamily>,
 ) -> Result<Option<LiveTx>> {
     let body = json!({
         "jsonrpc": "2.0",
@@ -3154,12 +3554,13 @@ async fn fetch_transaction_by_hash(
         "params": [hash_hex],
     });
     let response_bytes = rpc_post_bytes(client, http_url, &body).await?;
-    decode_transaction_fetch_response_to_live_tx(response_bytes.as_ref(), hash_hex)
+    decode_transaction_fetch_response_to_live_tx(response_bytes.as_ref(), hash_hex, chain_family)
 }
 
 async fn fetch_pending_block_transactions(
     client: &reqwest::Client,
     http_url: &str,
+    chain_family: Option<ChainFamily>,
 ) -> Result<Vec<LiveTx>> {
     let body = json!({
         "jsonrpc": "2.0",
@@ -3168,18 +3569,19 @@ async fn fetch_pending_block_transactions(
         "params": ["pending", true],
     });
     let response_bytes = rpc_post_bytes(client, http_url, &body).await?;
-    decode_pending_block_response_to_live_txs(response_bytes.as_ref())
+    decode_pending_block_response_to_live_txs(response_bytes.as_ref(), chain_family)
 }
 
 async fn fetch_transactions_by_hash_batch_with_retry(
     client: &reqwest::Client,
     http_url: &str,
     hashes: &[String],
+    chain_family: Option<ChainFamily>,
     batch_fetch: BatchFetchConfig,
 ) -> Result<Vec<Option<LiveTx>>> {
     let mut attempt = 0usize;
     loop {
-        match fetch_transactions_by_hash_batch(client, http_url, hashes).await {
+        match fetch_transactions_by_hash_batch(client, http_url, hashes, chain_family).await {
             Ok(fetched) => return Ok(fetched),
             Err(err) => {
                 if attempt >= batch_fetch.retry_attempts {
@@ -3208,13 +3610,14 @@ async fn fetch_transactions_by_hash_batch(
     client: &reqwest::Client,
     http_url: &str,
     hashes: &[String],
+    chain_family: Option<ChainFamily>,
 ) -> Result<Vec<Option<LiveTx>>> {
     if hashes.is_empty() {
         return Ok(Vec::new());
     }
     if hashes.len() == 1 {
         return Ok(vec![
-            fetch_transaction_by_hash(client, http_url, hashes[0].as_str()).await?,
+            fetch_transaction_by_hash(client, http_url, hashes[0].as_str(), chain_family).await?,
         ]);
     }
 
@@ -3233,7 +3636,11 @@ async fn fetch_transactions_by_hash_batch(
 
     let response_bytes =
         rpc_post_bytes(client, http_url, &serde_json::Value::Array(request_body)).await?;
-    decode_transaction_fetch_batch_response_to_live_txs(response_bytes.as_ref(), hashes)
+    decode_transaction_fetch_batch_response_to_live_txs(
+        response_bytes.as_ref(),
+        hashes,
+        chain_family,
+    )
 }
 
 #[derive(Debug, Deserialize)]
@@ -3309,6 +3716,7 @@ enum RpcBatchFetchResponseBody {
 fn decode_transaction_fetch_response_to_live_tx(
     payload: &[u8],
     hash_hex: &str,
+    chain_family: Option<ChainFamily>,
 ) -> Result<Option<LiveTx>> {
     let response: RpcFetchResponseEnvelope =
         serde_json::from_slice(payload).context("decode rpc json response")?;
@@ -3324,10 +3732,13 @@ fn decode_transaction_fetch_response_to_live_tx(
         Some(tx) => tx,
         None => return Ok(None),
     };
-    Ok(Some(rpc_tx_to_live_tx(tx, hash_hex)?))
+    Ok(Some(rpc_tx_to_live_tx(tx, hash_hex, chain_family)?))
 }
 
-fn decode_pending_block_response_to_live_txs(payload: &[u8]) -> Result<Vec<LiveTx>> {
+fn decode_pending_block_response_to_live_txs(
+    payload: &[u8],
+    chain_family: Option<ChainFamily>,
+) -> Result<Vec<LiveTx>> {
     let response: RpcPendingBlockResponseEnvelope =
         serde_json::from_slice(payload).context("decode pending block rpc json response")?;
     if let Some(error) = response.error {
@@ -3348,7 +3759,7 @@ fn decode_pending_block_response_to_live_txs(payload: &[u8]) -> Result<Vec<LiveT
         .into_iter()
         .map(|tx| {
             let hash_hex = tx.hash.clone();
-            rpc_tx_to_live_tx(tx, hash_hex.as_str())
+            rpc_tx_to_live_tx(tx, hash_hex.as_str(), chain_family)
         })
         .collect()
 }
@@ -3356,6 +3767,7 @@ fn decode_pending_block_response_to_live_txs(payload: &[u8]) -> Result<Vec<LiveT
 fn decode_transaction_fetch_batch_response_to_live_txs(
     payload: &[u8],
     hashes: &[String],
+    chain_family: Option<ChainFamily>,
 ) -> Result<Vec<Option<LiveTx>>> {
     let response: RpcBatchFetchResponseBody =
         serde_json::from_slice(payload).context("decode rpc batch json response")?;
@@ -3392,7 +3804,7 @@ fn decode_transaction_fetch_batch_response_to_live_txs(
             continue;
         };
 
-        match rpc_tx_to_live_tx(tx, hashes[index].as_str()) {
+        match rpc_tx_to_live_tx(tx, hashes[index].as_str(), chain_family) {
             Ok(tx) => fetched[index] = Some(tx),
             Err(err) => {
                 tracing::warn!(
@@ -3407,7 +3819,11 @@ fn decode_transaction_fetch_batch_response_to_live_txs(
     Ok(fetched)
 }
 
-fn rpc_tx_to_live_tx(tx: RpcTransaction, hash_hex: &str) -> Result<LiveTx> {
+fn rpc_tx_to_live_tx(
+    tx: RpcTransaction,
+    hash_hex: &str,
+    configured_family: Option<ChainFamily>,
+) -> Result<LiveTx> {
     let hash = parse_fixed_hex::<32>(&tx.hash)
         .or_else(|| parse_fixed_hex::<32>(hash_hex))
         .ok_or_else(|| anyhow!("invalid transaction hash"))?;
@@ -3442,6 +3858,35 @@ fn rpc_tx_to_live_tx(tx: RpcTransaction, hash_hex: &str) -> Result<LiveTx> {
         .as_deref()
         .and_then(parse_hex_u128);
     let max_fee_per_blob_gas_wei = tx.max_fee_per_blob_gas.as_deref().and_then(parse_hex_u128);
+    // The chain config is authoritative; the payload's `chainId` only matters
+    // for chains configured without a family or id. Deposits omit it anyway.
+    let family = configured_family.unwrap_or_else(|| ChainFamily::from_chain_id(chain_id));
+    let system_tx = family.is_system_tx_type(tx_type);
+    let l2_fields = L2TxFields {
+        source_hash: tx.source_hash.as_deref().and_then(parse_fixed_hex::<32>),
+        mint_wei: tx.mint.as_deref().and_then(parse_hex_u128),
+        is_system_tx: tx.is_system_tx,
+        l1_fee_wei: tx.l1_fee.as_deref().and_then(parse_hex_u128),
+        l1_gas_used: tx.l1_gas_used.as_deref().and_then(parse_hex_u64),
+        l1_gas_price_wei: tx.l1_gas_price.as_deref().and_then(parse_hex_u128),
+        l1_blob_base_fee_wei: tx.l1_blob_base_fee.as_deref().and_then(parse_hex_u128),
+        l1_base_fee_scalar: tx
+            .l1_base_fee_scalar
+            .as_deref()
+            .and_then(parse_hex_u64)
+            .and_then(|value| u32::try_from(value).ok()),
+        l1_blob_base_fee_scalar: tx
+            .l1_blob_base_fee_scalar
+            .as_deref()
+            .and_then(parse_hex_u64)
+            .and_then(|value| u32::try_from(value).ok()),
+        request_id: tx.request_id.as_deref().and_then(parse_fixed_hex::<32>),
+        ticket_id: tx.ticket_id.as_deref().and_then(parse_fixed_hex::<32>),
+        refund_to: tx.refund_to.as_deref().and_then(parse_fixed_hex::<20>),
+        l1_base_fee_wei: tx.l1_base_fee.as_deref().and_then(parse_hex_u128),
+        max_refund_wei: tx.max_refund.as_deref().and_then(parse_hex_u128),
+    };
+    let l2_fields = (!l2_fields.is_empty()).then_some(l2_fields);
 
     Ok(LiveTx {
         hash,
@@ -3457,6 +3902,8 @@ fn rpc_tx_to_live_tx(tx: RpcTransaction, hash_hex: &str) -> Result<LiveTx> {
         max_priority_fee_per_gas_wei,
         max_fee_per_blob_gas_wei,
         input,
+        system_tx,
+        l2_fields,
     })
 }
 
@@ -3551,7 +3998,7 @@ fn format_method_selector_hex(selector: Option<[u8; 4]>) -> String {
 }
 
 fn current_seq_hi(storage: &Arc<RwLock<InMemoryStorage>>) -> u64 {
-    storage.read().latest_seq_id().unwrap_or(0)
+    storage.latest_seq_id().unwrap_or(0)
 }
 
 fn current_unix_ms() -> i64 {
@@ -3708,6 +4155,29 @@ mod tests {
                     }))
                 }
             }
+            "debug_bundler_dumpMempool" => {
+                let entry_point = payload["params"][0].as_str().unwrap_or_default();
+                let user_ops = if entry_point == format_fixed_hex(&ENTRY_POINT_V07_ADDRESS) {
+                    json!([{
+                        "sender": format!("0x{}", "11".repeat(20)),
+                        "nonce": "0x2",
+                        "callData": "0xb61d27f60102",
+                        "callGasLimit": "0x1d4c0",
+                        "verificationGasLimit": "0x15f90",
+                        "preVerificationGas": "0xbb80",
+                        "maxFeePerGas": "0xb2d05e00",
+                        "maxPriorityFeePerGas": "0x59682f00",
+                        "signature": "0x"
+                    }])
+                } else {
+                    json!([])
+                };
+                Json(json!({
+                    "jsonrpc": "2.0",
+                    "id": payload.get("id").cloned().unwrap_or(json!(1)),
+                    "result": user_ops
+                }))
+            }
             other => Json(json!({
                 "jsonrpc": "2.0",
                 "id": payload.get("id").cloned().unwrap_or(json!(1)),
@@ -3728,6 +4198,8 @@ mod tests {
                 ws_url: "ws://127.0.0.1/unused".to_owned(),
                 http_url,
             }],
+            bundler_http_url: None,
+            chain_family: Some(ChainFamily::Ethereum),
         }
     }
 
@@ -3755,6 +4227,8 @@ mod tests {
             max_priority_fee_per_gas_wei: Some(3),
             max_fee_per_blob_gas_wei: None,
             input: vec![0xaa, 0xbb, 0xcc],
+            system_tx: false,
+            l2_fields: None,
         }
     }
 
@@ -3847,6 +4321,7 @@ mod tests {
                     urgency_component: 0,
                     structural_component: 0,
                     strategy_bonus: 0,
+                    l1_fee_penalty: 0,
                 },
                 reasons: vec!["test".to_owned()],
             },
@@ -3900,7 +4375,8 @@ mod tests {
         }))
         .expect("decode rpc tx");
 
-        let live = rpc_tx_to_live_tx(rpc_tx, &format!("0x{}", "11".repeat(32))).expect("map tx");
+        let live =
+            rpc_tx_to_live_tx(rpc_tx, &format!("0x{}", "11".repeat(32)), None).expect("map tx");
 
         assert_eq!(live.hash, [0x11; 32]);
         assert_eq!(live.sender, [0x22; 20]);
@@ -3917,6 +4393,103 @@ mod tests {
         assert_eq!(live.max_fee_per_blob_gas_wei, Some(3));
     }
 
+    #[test]
+    fn rpc_tx_to_live_tx_flags_op_deposit_on_configured_op_chain() {
+        let hash_hex = format!("0x{}", "11".repeat(32));
+        let rpc_tx: RpcTransaction = serde_json::from_value(json!({
+            "hash": hash_hex,
+            "from": format!("0x{}", "22".repeat(20)),
+            "to": format!("0x{}", "33".repeat(20)),
+            "nonce": "0x0",
+            "type": "0x7e",
+            "input": "0x",
+            "gas": "0xf4240",
+            "sourceHash": format!("0x{}", "44".repeat(32)),
+            "mint": "0x2386f26fc10000",
+            "isSystemTx": false
+        }))
+        .expect("decode rpc tx");
+
+        let deposit =
+            rpc_tx_to_live_tx(rpc_tx, &hash_hex, Some(ChainFamily::OpStack)).expect("map deposit");
+        assert_eq!(deposit.tx_type, 0x7e);
+        assert!(deposit.system_tx);
+        let fields = deposit.l2_fields.expect("deposit fields");
+        assert_eq!(fields.source_hash, Some([0x44; 32]));
+        assert_eq!(fields.mint_wei, Some(10_000_000_000_000_000));
+        assert_eq!(fields.is_system_tx, Some(false));
+
+        let rpc_tx: RpcTransaction = serde_json::from_value(json!({
+            "hash": hash_hex,
+            "type": "0x7e"
+        }))
+        .expect("decode rpc tx");
+        let mainnet =
+            rpc_tx_to_live_tx(rpc_tx, &hash_hex, Some(ChainFamily::Ethereum)).expect("map tx");
+        assert!(!mainnet.system_tx);
+        assert!(mainnet.l2_fields.is_none());
+    }
+
+    #[test]
+    fn chain_config_family_overrides_chain_id_table() {
+        let chains = parse_env_chain_configs(
+            &json!([
+                {
+                    "chain_key": "op-devnet",
+                    "chain_id": 901,
+                    "chain_family": "op_stack",
+                    "ws_url": "ws://127.0.0.1:9546",
+                    "http_url": "http://127.0.0.1:9545"
+                },
+                {
+                    "chain_key": "base-mainnet",
+                    "chain_id": 8453,
+                    "ws_url": "ws://127.0.0.1:8546",
+                    "http_url": "http://127.0.0.1:8545"
+                },
+                {
+                    "chain_key": "unknown",
+                    "chain_id": 999_999,
+                    "ws_url": "ws://127.0.0.1:7546",
+                    "http_url": "http://127.0.0.1:7545"
+                },
+                {
+                    "chain_key": "unnumbered",
+                    "ws_url": "ws://127.0.0.1:6546",
+                    "http_url": "http://127.0.0.1:6545"
+                }
+            ])
+            .to_string(),
+        )
+        .expect("parse chain configs");
+
+        let families = chains
+            .iter()
+            .map(ChainRpcConfig::chain_family)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            families,
+            vec![
+                Some(ChainFamily::OpStack),
+                Some(ChainFamily::OpStack),
+                Some(ChainFamily::Ethereum),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_abi_word_u128_accepts_padded_words() {
+        assert_eq!(
+            parse_abi_word_u128(&format!("0x{}{}", "0".repeat(56), "0000055c")),
+            Some(1_372)
+        );
+        assert_eq!(
+            parse_abi_word_u128(&format!("0x01{}", "0".repeat(62))),
+            None
+        );
+    }
+
     #[test]
     fn decode_transaction_fetch_response_to_live_tx_decodes_raw_bytes() {
         let hash_hex = format!("0x{}", "11".repeat(32));
@@ -3938,7 +4511,7 @@ mod tests {
             to = "33".repeat(20),
         );
 
-        let tx = decode_transaction_fetch_response_to_live_tx(payload.as_bytes(), &hash_hex)
+        let tx = decode_transaction_fetch_response_to_live_tx(payload.as_bytes(), &hash_hex, None)
             .expect("decode bytes payload")
             .expect("transaction present");
 
@@ -3954,13 +4527,13 @@ mod tests {
     #[test]
     fn decode_transaction_fetch_response_to_live_tx_handles_null_and_errors() {
         let missing = br#"{"jsonrpc":"2.0","id":42,"result":null}"#;
-        let tx =
-            decode_transaction_fetch_response_to_live_tx(missing, "0x00").expect("decode response");
+        let tx = decode_transaction_fetch_response_to_live_tx(missing, "0x00", None)
+            .expect("decode response");
         assert!(tx.is_none());
 
         let failed = br#"{"jsonrpc":"2.0","id":42,"error":{"code":-32000,"message":"boom"}}"#;
-        let err =
-            decode_transaction_fetch_response_to_live_tx(failed, "0x00").expect_err("rpc error");
+        let err = decode_transaction_fetch_response_to_live_tx(failed, "0x00", None)
+            .expect_err("rpc error");
         assert!(err.to_string().contains("rpc returned error"));
     }
 
@@ -4002,8 +4575,8 @@ mod tests {
         }))
         .expect("encode pending block payload");
 
-        let txs =
-            decode_pending_block_response_to_live_txs(&payload).expect("decode pending block");
+        let txs = decode_pending_block_response_to_live_txs(&payload, Some(ChainFamily::Ethereum))
+            .expect("decode pending block");
 
         assert_eq!(txs.len(), 2);
         assert_eq!(txs[0].hash, [0x11; 32]);
@@ -4107,7 +4680,7 @@ mod tests {
         );
         let request = build_remote_simulation_request(
             &sample_executable_opportunity(&tx, 1_700_000_000_010),
-            &[tx.clone()],
+            std::slice::from_ref(&tx),
         )
         .expect("simulation request");
         let client = state_owner.simulation_http_client().clone();
@@ -4170,7 +4743,7 @@ mod tests {
         .expect("simulation outcome");
 
         assert_eq!(outcome.status, RemoteSimulationStatus::StateError);
-        assert_eq!(outcome.fail_category.as_deref(), Some("state_rpc"));
+        assert_eq!(outcome.fail_category, Some(SimFailCategory::StateRpc));
 
         server.abort();
     }
@@ -4212,7 +4785,7 @@ mod tests {
             })
             .expect("sim payload");
 
-        assert_eq!(sim_payload.status, "ok");
+        assert_eq!(sim_payload.status, SimulationStatus::Ok);
         assert_eq!(sim_payload.fail_category, None);
         assert!(sim_payload.latency_ms.is_some());
         assert_eq!(sim_payload.tx_count, Some(1));
@@ -4287,13 +4860,53 @@ mod tests {
             StorageWriteOp::AppendPayload {
                 payload: EventPayload::AssemblyDecisionApplied(decision),
                 ..
-            } if decision.tx_hash == tx.hash && decision.decision == "inserted"
+            } if decision.tx_hash == tx.hash && decision.decision == AssemblyDecisionKind::Inserted
         )));
 
         runtime_task.abort();
         server.abort();
     }
 
+    #[tokio::test]
+    async fn bundler_poll_emits_user_ops_once_per_chain() {
+        let (_rpc_state, rpc_addr, server) =
+            start_mock_simulation_rpc(MockSimulationRpcState::new(100)).await;
+        let (storage_tx, _storage_rx) = tokio::sync::mpsc::channel(16);
+        let writer = StorageWriteHandle::from_sender(storage_tx);
+        let (scheduler, _runtime) =
+            scheduler::scheduler_channel(scheduler::SchedulerConfig::default())
+                .expect("valid scheduler config");
+        let (_runtime_core, state_owner) = test_runtime_core_owner(&writer, &scheduler);
+        let chain = test_chain();
+        let client = reqwest::Client::new();
+        let bundler_url = format!("http://{rpc_addr}");
+
+        let mut emitted = Vec::new();
+        for _ in 0..2 {
+            for entry_point in [ENTRY_POINT_V06_ADDRESS, ENTRY_POINT_V07_ADDRESS] {
+                let batch = fetch_bundler_user_ops(&client, &bundler_url, entry_point)
+                    .await
+                    .expect("dump mempool");
+                emitted.extend(
+                    state_owner
+                        .observe_bundler_user_ops(&chain, 1, batch)
+                        .expect("ingest user ops"),
+                );
+            }
+        }
+
+        assert_eq!(emitted.len(), 1);
+        let EventPayload::UserOpSeen(seen) = &emitted[0] else {
+            panic!("expected UserOpSeen, got {:?}", emitted[0]);
+        };
+        assert_eq!(seen.entry_point, ENTRY_POINT_V07_ADDRESS);
+        assert_eq!(seen.chain_id, Some(1));
+        assert_eq!(seen.call_data, vec![0xb6, 0x1d, 0x27, 0xf6, 0x01, 0x02]);
+        assert_eq!(seen.bundle_tx_hash, None);
+
+        server.abort();
+    }
+
     #[tokio::test]
     async fn process_pending_hash_with_runtime_core_owner_updates_runtime_core_views() {
         let (rpc_state, rpc_addr, server) =
@@ -4465,7 +5078,7 @@ mod tests {
             StorageWriteOp::AppendPayload {
                 payload: EventPayload::AssemblyDecisionApplied(decision),
                 ..
-            } if decision.tx_hash == tx.hash && decision.decision == "inserted"
+            } if decision.tx_hash == tx.hash && decision.decision == AssemblyDecisionKind::Inserted
         )));
 
         runtime_task.abort();
@@ -4535,7 +5148,7 @@ mod tests {
             StorageWriteOp::AppendPayload {
                 payload: EventPayload::AssemblyDecisionApplied(decision),
                 ..
-            } if decision.tx_hash == replacement.hash && decision.decision == "inserted"
+            } if decision.tx_hash == replacement.hash && decision.decision == AssemblyDecisionKind::Inserted
         )));
         assert!(runtime_core.scheduler_metrics().stale_simulation_drop_total >= 1);
 
@@ -4599,6 +5212,67 @@ mod tests {
         runtime_task.abort();
     }
 
+    #[tokio::test]
+    async fn process_pending_hash_with_fetched_tx_excludes_system_txs_from_scheduler() {
+        let (storage_tx, mut storage_rx) = tokio::sync::mpsc::channel(64);
+        let writer = StorageWriteHandle::from_sender(storage_tx);
+        let (scheduler, runtime) =
+            scheduler::scheduler_channel(scheduler::SchedulerConfig::default())
+                .expect("valid scheduler config");
+        let runtime_task = tokio::spawn(runtime.run());
+        let (_runtime_core, state_owner) = test_runtime_core_owner(&writer, &scheduler);
+
+        let mut chain = test_chain();
+        chain.chain_key = "base-mainnet".to_owned();
+        chain.chain_id = Some(8453);
+        let mut tx = sample_live_tx(0x7e, 0x45, 0, 0);
+        tx.tx_type = 0x7e;
+        tx.chain_id = None;
+        tx.system_tx = true;
+        tx.l2_fields = Some(L2TxFields {
+            source_hash: Some([0x99; 32]),
+            mint_wei: Some(1),
+            ..L2TxFields::default()
+        });
+        let next_seq_id = Arc::new(AtomicU64::new(1));
+
+        process_pending_hash_with_fetched_tx_with_owner(
+            pending_tx_process_context(&state_owner, &writer, &scheduler, &chain, &next_seq_id),
+            &sample_pending_observation(tx.hash, 1_700_000_124_001, 777),
+            tx.hash,
+            Some(tx.clone()),
+        )
+        .await
+        .expect("process deposit");
+
+        assert!(scheduler.snapshot().pending.is_empty());
+        let ops = drain_storage_ops(&mut storage_rx);
+        let full = ops
+            .iter()
+            .find_map(|op| match op {
+                StorageWriteOp::UpsertTxFull(record) => Some(record.clone()),
+                _ => None,
+            })
+            .expect("tx full record");
+        assert_eq!(full.tx_type, 0x7e);
+        assert_eq!(full.chain_id, Some(8453));
+        assert_eq!(
+            full.l2_fields.and_then(|fields| fields.source_hash),
+            Some([0x99; 32])
+        );
+        assert!(!ops.iter().any(|op| matches!(
+            op,
+            StorageWriteOp::AppendPayload {
+                payload: EventPayload::TxDecoded(_)
+                    | EventPayload::TxDropped(_)
+                    | EventPayload::TxReady(_),
+                ..
+            }
+        )));
+
+        runtime_task.abort();
+    }
+
     #[tokio::test]
     async fn process_pending_hash_with_fetched_tx_emits_replaced_event_for_scheduler_replacement() {
         let (storage_tx, mut storage_rx) = tokio::sync::mpsc::channel(64);
@@ -4690,8 +5364,8 @@ mod tests {
         )));
         assert!(ops.iter().any(|op| matches!(
             op,
-            StorageWriteOp::AppendPayload { payload: EventPayload::TxDropped(TxDropped { hash, reason }), .. }
-                if *hash == tx.hash && reason == "queue_full"
+            StorageWriteOp::AppendPayload { payload: EventPayload::TxDropped(TxDropped { hash, reason, .. }), .. }
+                if *hash == tx.hash && *reason == DropReason::QueueFull
         )));
         assert!(!ops.iter().any(|op| matches!(
             op,
@@ -29,7 +29,8 @@ impl ValidatedTransaction {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 /// Admission and sender-queue limits enforced by the scheduler.
 pub struct SchedulerConfig {
     pub handoff_queue_capacity: usize,
@@ -569,6 +570,74 @@ fn spawn_runtime(handle: SchedulerHandle, runtime: SchedulerRuntime) -> Schedule
     handle
 }
 
+#[derive(Debug)]
+/// Scheduler state driven on the caller's thread instead of the actor, so
+/// recorded transactions can be re-admitted deterministically offline.
+pub struct InlineScheduler {
+    config: SchedulerConfig,
+    state: SchedulerState,
+}
+
+impl InlineScheduler {
+    /// Creates an empty scheduler from validated config.
+    pub fn new(config: SchedulerConfig) -> Result<Self, SchedulerConfigError> {
+        Ok(Self {
+            config: validate_config(config)?,
+            state: SchedulerState::default(),
+        })
+    }
+
+    pub fn config(&self) -> SchedulerConfig {
+        self.config
+    }
+
+    /// Admits a transaction and returns both the decision and queue transitions.
+    pub fn admit(&mut self, tx: ValidatedTransaction) -> SchedulerAdmissionOutcome {
+        self.state.admit(tx, self.config)
+    }
+
+    /// Registers candidates and returns the simulation tasks that should be run.
+    pub fn register_candidates(
+        &mut self,
+        candidates: Vec<SchedulerCandidate>,
+    ) -> SchedulerCandidateDispatch {
+        self.state.register_candidates(candidates)
+    }
+
+    /// Applies a simulation result and returns any builder handoffs that became eligible.
+    pub fn apply_simulation_result(
+        &mut self,
+        result: SchedulerSimulationResult,
+    ) -> SchedulerSimulationApplyOutcome {
+        self.state.apply_simulation_result(result)
+    }
+
+    /// Invalidates any candidate tied to the provided transaction hash.
+    pub fn invalidate_candidate_hash(&mut self, hash: TxHash) {
+        self.state.invalidate_candidate_hash(hash);
+    }
+
+    /// Advances the head block and drops stale candidate generations.
+    pub fn advance_head(&mut self, block_number: u64) {
+        self.state.advance_head(block_number);
+    }
+
+    /// Returns pending transactions matching the provided hashes.
+    pub fn pending_transactions(&self, hashes: &[TxHash]) -> Vec<ValidatedTransaction> {
+        self.state.pending_transactions(hashes)
+    }
+
+    /// Returns the executable frontier: every ready transaction, by sender and nonce.
+    pub fn ready_transactions(&self) -> Vec<ValidatedTransaction> {
+        self.state.classify_by_sender().ready
+    }
+
+    /// Returns a snapshot of the current scheduler state.
+    pub fn snapshot(&self) -> SchedulerSnapshot {
+        self.state.snapshot()
+    }
+}
+
 fn validate_config(config: SchedulerConfig) -> Result<SchedulerConfig, SchedulerConfigError> {
     if config.handoff_queue_capacity == 0 {
         return Err(SchedulerConfigError::HandoffQueueCapacityZero);
@@ -1,8 +1,8 @@
 use common::{Address, SourceId};
 use event_log::TxDecoded;
 use scheduler::{
-    SchedulerAdmission, SchedulerConfig, SchedulerEnqueueError, ValidatedTransaction,
-    scheduler_channel,
+    InlineScheduler, SchedulerAdmission, SchedulerConfig, SchedulerConfigError,
+    SchedulerEnqueueError, SchedulerQueueState, ValidatedTransaction, scheduler_channel,
 };
 use tokio::sync::Barrier;
 use tokio::time::{Duration, Instant, sleep};
@@ -494,3 +494,50 @@ async fn scheduler_drops_new_nonce_when_sender_queue_capacity_is_exceeded() {
 
     runtime_task.abort();
 }
+
+#[test]
+fn inline_scheduler_applies_admission_policy_without_the_actor() {
+    assert_eq!(
+        InlineScheduler::new(SchedulerConfig {
+            max_pending_per_sender: 0,
+            ..SchedulerConfig::default()
+        })
+        .expect_err("zero sender limit is rejected"),
+        SchedulerConfigError::MaxPendingPerSenderZero
+    );
+
+    let mut scheduler = InlineScheduler::new(SchedulerConfig {
+        max_pending_per_sender: 2,
+        ..SchedulerConfig::default()
+    })
+    .expect("valid scheduler config");
+    let sender = sender(0xfb);
+    let first = sample_validated_tx(50, sender, 2, 100, "rpc-mainnet", 1_700_000_004_000, 40);
+    let gap_filler = sample_validated_tx(51, sender, 1, 100, "rpc-mainnet", 1_700_000_004_001, 41);
+    let over_limit = sample_validated_tx(52, sender, 3, 100, "rpc-mainnet", 1_700_000_004_002, 42);
+    let underpriced = sample_validated_tx(53, sender, 1, 105, "rpc-mainnet", 1_700_000_004_003, 43);
+
+    let admitted = scheduler.admit(first.clone());
+    assert_eq!(admitted.admission, SchedulerAdmission::Admitted);
+    assert_eq!(
+        admitted.queue_transitions[0].state,
+        SchedulerQueueState::Ready
+    );
+    assert_eq!(
+        scheduler.admit(gap_filler.clone()).admission,
+        SchedulerAdmission::Admitted
+    );
+    assert_eq!(
+        scheduler.admit(first.clone()).admission,
+        SchedulerAdmission::Duplicate
+    );
+    assert_eq!(
+        scheduler.admit(over_limit).admission,
+        SchedulerAdmission::SenderLimitReached
+    );
+    assert_eq!(
+        scheduler.admit(underpriced).admission,
+        SchedulerAdmission::UnderpricedReplacement
+    );
+    assert_eq!(scheduler.ready_transactions(), vec![gap_filler, first]);
+}
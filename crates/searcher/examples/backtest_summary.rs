@@ -65,6 +65,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = SearcherConfig {
         min_score: 8_000,
         max_candidates: 64,
+        ..SearcherConfig::default()
     };
     let ranked = rank_opportunity_batch(&batch, config).candidates;
 
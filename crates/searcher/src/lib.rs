@@ -15,13 +15,15 @@ use std::borrow::Cow;
 
 pub use scoring::ScoreBreakdown as OpportunityScoreBreakdown;
 pub use scoring::scorer_version;
-pub use strategies::{StrategyKind, strategy_version};
+pub use strategies::{StrategyKind, StrategyThresholds, strategy_version};
 
 /// Searcher-facing transaction input with borrowed or owned calldata.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SearcherInputTx<'a> {
     pub decoded: TxDecoded,
     pub calldata: Cow<'a, [u8]>,
+    /// Estimated OP-stack L1 data fee; `None` on chains without one.
+    pub l1_data_fee_wei: Option<u128>,
 }
 
 /// Owned variant of [`SearcherInputTx`].
@@ -33,6 +35,7 @@ impl<'a> SearcherInputTx<'a> {
         Self {
             decoded,
             calldata: Cow::Borrowed(calldata),
+            l1_data_fee_wei: None,
         }
     }
 
@@ -41,15 +44,24 @@ impl<'a> SearcherInputTx<'a> {
         OwnedSearcherInputTx {
             decoded,
             calldata: Cow::Owned(calldata),
+            l1_data_fee_wei: None,
         }
     }
+
+    /// Attaches the estimated L1 data fee charged for this transaction.
+    pub fn with_l1_data_fee(mut self, l1_data_fee_wei: Option<u128>) -> Self {
+        self.l1_data_fee_wei = l1_data_fee_wei;
+        self
+    }
 }
 
 /// Ranking thresholds for one searcher batch.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SearcherConfig {
     pub min_score: u32,
     pub max_candidates: usize,
+    pub strategy_thresholds: StrategyThresholds,
 }
 
 impl Default for SearcherConfig {
@@ -57,6 +69,17 @@ impl Default for SearcherConfig {
         Self {
             min_score: 0,
             max_candidates: 64,
+            strategy_thresholds: StrategyThresholds::default(),
+        }
+    }
+}
+
+impl SearcherConfig {
+    /// Thresholds the live ingest pipeline ranks each batch with.
+    pub fn live() -> Self {
+        Self {
+            max_candidates: 8,
+            ..Self::default()
         }
     }
 }
@@ -105,7 +128,19 @@ pub fn rank_opportunity_batch(
 
     for input in batch {
         let featured = analyze_decoded_transaction(&input.decoded, input.calldata.as_ref());
-        let mut per_tx_candidates = strategies::rank_transaction(&featured, config.min_score);
+        let mut per_tx_candidates =
+            strategies::rank_transaction(&featured, config.min_score, &config.strategy_thresholds);
+        if let Some(l1_data_fee_wei) = input.l1_data_fee_wei {
+            let penalty = scoring::l1_fee_penalty(l1_data_fee_wei);
+            for candidate in &mut per_tx_candidates {
+                candidate.breakdown.l1_fee_penalty = penalty;
+                candidate.score = candidate.breakdown.total();
+                candidate
+                    .reasons
+                    .push(format!("l1_data_fee_penalty={penalty}"));
+            }
+            per_tx_candidates.retain(|candidate| candidate.score >= config.min_score);
+        }
         per_tx_candidates.sort_by(candidate_sort_key);
         if let Some(top_candidate) = per_tx_candidates.first().cloned() {
             bundle_inputs.push(BundleInput {
@@ -189,7 +224,28 @@ fn build_bundle_candidates(inputs: &[BundleInput]) -> Vec<OpportunityCandidate>
                 .strategy_bonus
                 .saturating_add(right.candidate.breakdown.strategy_bonus)
                 .saturating_add(400),
+            l1_fee_penalty: left
+                .candidate
+                .breakdown
+                .l1_fee_penalty
+                .saturating_add(right.candidate.breakdown.l1_fee_penalty),
         };
+        let mut reasons = vec![
+            "bundle_size=2".to_owned(),
+            format!("contiguous_nonces={}..{}", left.nonce, right.nonce),
+            format!(
+                "member_hashes={:?}+{:?}",
+                left.candidate.tx_hash, right.candidate.tx_hash
+            ),
+            format!(
+                "member_scores={}+{}",
+                left.candidate.score, right.candidate.score
+            ),
+            "bundle bonus=400".to_owned(),
+        ];
+        if breakdown.l1_fee_penalty > 0 {
+            reasons.push(format!("l1_data_fee_penalty={}", breakdown.l1_fee_penalty));
+        }
         let score = breakdown.total();
         bundles.push(OpportunityCandidate {
             // Bundle records are keyed by the first member hash in storage; full membership
@@ -204,19 +260,7 @@ fn build_bundle_candidates(inputs: &[BundleInput]) -> Vec<OpportunityCandidate>
             protocol: left.candidate.protocol.clone(),
             category: left.candidate.category.clone(),
             breakdown,
-            reasons: vec![
-                "bundle_size=2".to_owned(),
-                format!("contiguous_nonces={}..{}", left.nonce, right.nonce),
-                format!(
-                    "member_hashes={:?}+{:?}",
-                    left.candidate.tx_hash, right.candidate.tx_hash
-                ),
-                format!(
-                    "member_scores={}+{}",
-                    left.candidate.score, right.candidate.score
-                ),
-                "bundle bonus=400".to_owned(),
-            ],
+            reasons,
         });
     }
 
@@ -16,6 +16,9 @@ pub struct ScoreBreakdown {
     pub urgency_component: u32,
     pub structural_component: u32,
     pub strategy_bonus: u32,
+    /// Points deducted for the estimated OP-stack L1 data fee.
+    #[serde(default)]
+    pub l1_fee_penalty: u32,
 }
 
 impl ScoreBreakdown {
@@ -25,5 +28,14 @@ impl ScoreBreakdown {
             .saturating_add(self.urgency_component)
             .saturating_add(self.structural_component)
             .saturating_add(self.strategy_bonus)
+            .saturating_sub(self.l1_fee_penalty)
     }
 }
+
+/// L1 data fee, in wei, that costs one score point.
+const L1_FEE_WEI_PER_POINT: u128 = 100_000_000_000;
+
+/// Returns the score penalty for an estimated L1 data fee.
+pub fn l1_fee_penalty(l1_data_fee_wei: u128) -> u32 {
+    u32::try_from(l1_data_fee_wei / L1_FEE_WEI_PER_POINT).unwrap_or(u32::MAX)
+}
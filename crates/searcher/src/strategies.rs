@@ -17,6 +17,27 @@ pub enum StrategyKind {
     BundleCandidate,
 }
 
+/// Feature-score gates a swap must clear before each strategy scores it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct StrategyThresholds {
+    pub sandwich_min_mev_score: u16,
+    pub backrun_min_mev_score: u16,
+    pub backrun_min_urgency_score: u16,
+    pub arb_min_mev_score: u16,
+}
+
+impl Default for StrategyThresholds {
+    fn default() -> Self {
+        Self {
+            sandwich_min_mev_score: 70,
+            backrun_min_mev_score: 55,
+            backrun_min_urgency_score: 12,
+            arb_min_mev_score: 50,
+        }
+    }
+}
+
 const SANDWICH_STRATEGY_VERSION: &str = "strategy.sandwich.v1";
 const BACKRUN_STRATEGY_VERSION: &str = "strategy.backrun.v1";
 const ARB_STRATEGY_VERSION: &str = "strategy.arb.v1";
@@ -35,12 +56,13 @@ pub const fn strategy_version(kind: StrategyKind) -> &'static str {
 pub(crate) fn rank_transaction(
     featured: &FeaturedTransaction,
     min_score: u32,
+    thresholds: &StrategyThresholds,
 ) -> Vec<OpportunityCandidate> {
     let mut candidates = Vec::with_capacity(3);
     for candidate in [
-        evaluate_sandwich_candidate(featured),
-        evaluate_backrun_candidate(featured),
-        evaluate_arb_candidate(featured),
+        evaluate_sandwich_candidate(featured, thresholds),
+        evaluate_backrun_candidate(featured, thresholds),
+        evaluate_arb_candidate(featured, thresholds),
     ]
     .into_iter()
     .flatten()
@@ -58,9 +80,12 @@ fn structural_bonus(featured: &FeaturedTransaction) -> u32 {
     calldata_bonus + gas_bonus
 }
 
-fn evaluate_sandwich_candidate(featured: &FeaturedTransaction) -> Option<OpportunityCandidate> {
+fn evaluate_sandwich_candidate(
+    featured: &FeaturedTransaction,
+    thresholds: &StrategyThresholds,
+) -> Option<OpportunityCandidate> {
     let analysis = featured.analysis;
-    if analysis.category != "swap" || analysis.mev_score < 70 {
+    if analysis.category != "swap" || analysis.mev_score < thresholds.sandwich_min_mev_score {
         return None;
     }
     if !analysis.protocol.starts_with("uniswap") && analysis.protocol != "1inch" {
@@ -72,6 +97,7 @@ fn evaluate_sandwich_candidate(featured: &FeaturedTransaction) -> Option<Opportu
         urgency_component: analysis.urgency_score as u32 * 25,
         structural_component: structural_bonus(featured),
         strategy_bonus: 500,
+        l1_fee_penalty: 0,
     };
     let reasons = vec![
         format!("mev_score={}*120", analysis.mev_score),
@@ -90,9 +116,15 @@ fn evaluate_sandwich_candidate(featured: &FeaturedTransaction) -> Option<Opportu
     ))
 }
 
-fn evaluate_backrun_candidate(featured: &FeaturedTransaction) -> Option<OpportunityCandidate> {
+fn evaluate_backrun_candidate(
+    featured: &FeaturedTransaction,
+    thresholds: &StrategyThresholds,
+) -> Option<OpportunityCandidate> {
     let analysis = featured.analysis;
-    if analysis.category != "swap" || analysis.mev_score < 55 || analysis.urgency_score < 12 {
+    if analysis.category != "swap"
+        || analysis.mev_score < thresholds.backrun_min_mev_score
+        || analysis.urgency_score < thresholds.backrun_min_urgency_score
+    {
         return None;
     }
 
@@ -101,6 +133,7 @@ fn evaluate_backrun_candidate(featured: &FeaturedTransaction) -> Option<Opportun
         urgency_component: analysis.urgency_score as u32 * 20,
         structural_component: structural_bonus(featured),
         strategy_bonus: 300,
+        l1_fee_penalty: 0,
     };
     let reasons = vec![
         format!("mev_score={}*100", analysis.mev_score),
@@ -119,9 +152,12 @@ fn evaluate_backrun_candidate(featured: &FeaturedTransaction) -> Option<Opportun
     ))
 }
 
-fn evaluate_arb_candidate(featured: &FeaturedTransaction) -> Option<OpportunityCandidate> {
+fn evaluate_arb_candidate(
+    featured: &FeaturedTransaction,
+    thresholds: &StrategyThresholds,
+) -> Option<OpportunityCandidate> {
     let analysis = featured.analysis;
-    if analysis.category != "swap" || analysis.mev_score < 50 {
+    if analysis.category != "swap" || analysis.mev_score < thresholds.arb_min_mev_score {
         return None;
     }
 
@@ -130,6 +166,7 @@ fn evaluate_arb_candidate(featured: &FeaturedTransaction) -> Option<OpportunityC
         urgency_component: analysis.urgency_score as u32 * 15,
         structural_component: structural_bonus(featured),
         strategy_bonus: 200,
+        l1_fee_penalty: 0,
     };
     let reasons = vec![
         format!("mev_score={}*90", analysis.mev_score),
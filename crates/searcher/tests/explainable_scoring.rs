@@ -47,6 +47,7 @@ fn ranked_candidates_include_score_breakdown_and_reasons() {
         SearcherConfig {
             min_score: 0,
             max_candidates: 8,
+            ..SearcherConfig::default()
         },
     )
     .candidates;
@@ -122,6 +123,7 @@ fn ranked_batch_reports_bounded_metrics_and_bundle_attribution() {
         SearcherConfig {
             min_score: 0,
             max_candidates: 2,
+            ..SearcherConfig::default()
         },
     );
 
@@ -139,3 +141,41 @@ fn ranked_batch_reports_bounded_metrics_and_bundle_attribution() {
 
     assert_eq!(result.candidates.len(), 2);
 }
+
+#[test]
+fn l1_data_fee_penalty_lowers_score_and_is_explained() {
+    let uniswap_v2 = [
+        0x7a, 0x25, 0x0d, 0x56, 0x30, 0xb4, 0xcf, 0x53, 0x97, 0x39, 0xdf, 0x2c, 0x5d, 0xac, 0xb4,
+        0xc6, 0x59, 0xf2, 0x48, 0x8d,
+    ];
+    let calldata = vec![0x38, 0xed, 0x17, 0x39, 1, 2, 3, 4, 5, 6, 7, 8];
+    let config = SearcherConfig {
+        min_score: 0,
+        max_candidates: 8,
+        ..SearcherConfig::default()
+    };
+    let baseline = rank_opportunity_batch(
+        &[SearcherInputTx::borrowed(tx(0x44, uniswap_v2), &calldata)],
+        config,
+    )
+    .candidates;
+    let charged = rank_opportunity_batch(
+        &[SearcherInputTx::borrowed(tx(0x44, uniswap_v2), &calldata)
+            .with_l1_data_fee(Some(2_500_000_000_000))],
+        config,
+    )
+    .candidates;
+
+    let baseline_top = baseline.first().expect("baseline candidate");
+    let charged_top = charged.first().expect("charged candidate");
+    assert_eq!(charged_top.strategy, baseline_top.strategy);
+    assert_eq!(charged_top.breakdown.l1_fee_penalty, 25);
+    assert_eq!(charged_top.score, baseline_top.score - 25);
+    assert_eq!(charged_top.score, charged_top.breakdown.total());
+    assert!(
+        charged_top
+            .reasons
+            .iter()
+            .any(|reason| reason == "l1_data_fee_penalty=25")
+    );
+}
@@ -1,6 +1,8 @@
 use common::{Address, TxHash};
 use event_log::TxDecoded;
-use searcher::{SearcherConfig, SearcherInputTx, StrategyKind, rank_opportunity_batch};
+use searcher::{
+    SearcherConfig, SearcherInputTx, StrategyKind, StrategyThresholds, rank_opportunity_batch,
+};
 
 fn hash(v: u8) -> TxHash {
     [v; 32]
@@ -65,6 +67,7 @@ fn opportunity_scoring_is_deterministic_and_prunes() {
     let config = SearcherConfig {
         min_score: 8_000,
         max_candidates: 3,
+        ..SearcherConfig::default()
     };
     let ranked_a = rank_opportunity_batch(&batch, config).candidates;
     let ranked_b = rank_opportunity_batch(&batch, config).candidates;
@@ -136,6 +139,7 @@ fn contiguous_same_sender_swaps_produce_a_bundle_candidate() {
         SearcherConfig {
             min_score: 0,
             max_candidates: 8,
+            ..SearcherConfig::default()
         },
     )
     .candidates;
@@ -148,3 +152,50 @@ fn contiguous_same_sender_swaps_produce_a_bundle_candidate() {
                 .any(|reason| reason.contains("bundle"))
     }));
 }
+
+#[test]
+fn strategy_thresholds_gate_which_strategies_score_a_swap() {
+    let uniswap_v2 = [
+        0x7a, 0x25, 0x0d, 0x56, 0x30, 0xb4, 0xcf, 0x53, 0x97, 0x39, 0xdf, 0x2c, 0x5d, 0xac, 0xb4,
+        0xc6, 0x59, 0xf2, 0x48, 0x8d,
+    ];
+    let batch = vec![SearcherInputTx::owned(
+        tx(0x10, uniswap_v2, 320_000, 7_000_000_000, 256),
+        vec![0x38, 0xed, 0x17, 0x39, 1, 2, 3, 4, 5],
+    )];
+    let strategies = |strategy_thresholds| {
+        let mut strategies = rank_opportunity_batch(
+            &batch,
+            SearcherConfig {
+                strategy_thresholds,
+                ..SearcherConfig::default()
+            },
+        )
+        .candidates
+        .into_iter()
+        .map(|candidate| candidate.strategy)
+        .collect::<Vec<_>>();
+        strategies.sort();
+        strategies
+    };
+
+    let defaults = strategies(StrategyThresholds::default());
+    assert!(defaults.contains(&StrategyKind::ArbCandidate));
+    assert_eq!(
+        strategies(StrategyThresholds {
+            sandwich_min_mev_score: u16::MAX,
+            backrun_min_mev_score: u16::MAX,
+            ..StrategyThresholds::default()
+        }),
+        vec![StrategyKind::ArbCandidate]
+    );
+    assert!(
+        strategies(StrategyThresholds {
+            sandwich_min_mev_score: u16::MAX,
+            backrun_min_mev_score: u16::MAX,
+            arb_min_mev_score: u16::MAX,
+            ..StrategyThresholds::default()
+        })
+        .is_empty()
+    );
+}
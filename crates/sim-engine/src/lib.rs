@@ -2,12 +2,14 @@
 
 #![forbid(unsafe_code)]
 
+mod op_l1_fee;
 mod state_provider;
+mod tx_envelope;
 
-use common::{Address, TxHash};
+use common::{Address, ChainFamily, TxHash};
 use event_log::TxDecoded;
-use revm::context_interface::ContextTr;
 use revm::context_interface::result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction};
+use revm::context_interface::{ContextTr, Transaction};
 use revm::database::InMemoryDB;
 use revm::primitives::{Address as RevmAddress, Bytes, U256, hardfork::SpecId};
 use revm::state::AccountInfo;
@@ -18,7 +20,9 @@ use std::collections::BTreeMap;
 use std::error::Error as StdError;
 use std::sync::Arc;
 
+pub use op_l1_fee::{OpL1FeeFormula, OpL1FeeParams, flz_compress_len, op_l1_data_fee};
 pub use state_provider::{AccountSeed, NoopStateProvider, StateProvider};
+pub use tx_envelope::{TxSignature, encode_signed_tx};
 
 const MAX_SYNTHETIC_CALLDATA_BYTES: u32 = 8_192;
 const SEEDED_BALANCE_WEI: u128 = 1_000_000_000_000_000_000_000_000_000_000;
@@ -70,6 +74,9 @@ pub struct ChainContext {
     pub base_fee_wei: u128,
     pub coinbase: Address,
     pub state_root: TxHash,
+    /// L1 fee inputs for OP-stack chains; `None` on chains without an L1 data fee.
+    #[serde(default)]
+    pub op_l1_fee: Option<OpL1FeeParams>,
 }
 
 /// Simulation outcome for one transaction hash.
@@ -81,6 +88,12 @@ pub struct TxSimulationResult {
     pub state_diff_hash: TxHash,
     pub fail_category: Option<SimulationFailCategory>,
     pub trace_id: u64,
+    /// OP-stack L1 data fee charged on top of L2 execution gas.
+    #[serde(default)]
+    pub l1_data_fee_wei: Option<u128>,
+    /// L2 execution fee at the effective gas price plus the L1 data fee.
+    #[serde(default)]
+    pub gas_cost_wei: u128,
 }
 
 /// Aggregate simulation output for a batch executed against one chain context.
@@ -91,12 +104,33 @@ pub struct SimulationBatchResult {
     pub final_state_diff_hash: TxHash,
 }
 
+impl SimulationBatchResult {
+    /// Returns the summed L1 data fee, or `None` when the chain charges none.
+    pub fn total_l1_data_fee_wei(&self) -> Option<u128> {
+        self.tx_results
+            .iter()
+            .filter_map(|result| result.l1_data_fee_wei)
+            .reduce(u128::saturating_add)
+    }
+
+    /// Returns the summed gas cost of every transaction in the batch.
+    pub fn total_gas_cost_wei(&self) -> u128 {
+        self.tx_results
+            .iter()
+            .fold(0, |total, result| total.saturating_add(result.gas_cost_wei))
+    }
+}
+
 /// Transaction input accepted by the simulator.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct SimulationTxInput {
     pub decoded: TxDecoded,
     #[serde(default)]
     pub calldata: Option<Vec<u8>>,
+    /// Signed EIP-2718 envelope, when known; otherwise one is synthesized
+    /// from the decoded fields for L1 data fee estimation.
+    #[serde(default)]
+    pub raw_tx: Option<Vec<u8>>,
 }
 
 /// High-level failure classification used by downstream metrics and APIs.
@@ -127,6 +161,7 @@ pub fn simulate_deterministic(
         .map(|decoded| SimulationTxInput {
             decoded,
             calldata: None,
+            raw_tx: None,
         })
         .collect::<Vec<_>>();
     simulate_with_mode(
@@ -171,6 +206,12 @@ pub fn simulate_with_mode(
     let mut tx_results = Vec::with_capacity(txs.len());
     for tx in txs {
         let tx_env = tx_env_from_input(chain_context, tx);
+        let l1_data_fee_wei = l1_data_fee(
+            chain_context,
+            tx,
+            tx.calldata.as_deref().unwrap_or(&tx_env.data),
+        );
+        let effective_gas_price = tx_env.effective_gas_price(chain_context.base_fee_wei);
         match evm.transact(tx_env) {
             Ok(result_and_state) => {
                 let gas_used = result_and_state.result.gas_used();
@@ -194,6 +235,9 @@ pub fn simulate_with_mode(
                 // Commit after hashing so the aggregate digest reflects the
                 // exact per-transaction outcome sequence.
                 evm.db().commit(result_and_state.state);
+                let gas_cost_wei = u128::from(gas_used)
+                    .saturating_mul(effective_gas_price)
+                    .saturating_add(l1_data_fee_wei.unwrap_or_default());
                 tx_results.push(TxSimulationResult {
                     hash: tx.decoded.hash,
                     success,
@@ -201,6 +245,8 @@ pub fn simulate_with_mode(
                     state_diff_hash,
                     fail_category,
                     trace_id,
+                    l1_data_fee_wei,
+                    gas_cost_wei,
                 });
             }
             Err(error) => {
@@ -222,6 +268,9 @@ pub fn simulate_with_mode(
                     state_diff_hash,
                     fail_category: Some(fail_category),
                     trace_id,
+                    l1_data_fee_wei,
+                    // Invalid transactions cannot be included, so they are never charged.
+                    gas_cost_wei: 0,
                 });
             }
         }
@@ -234,6 +283,40 @@ pub fn simulate_with_mode(
     })
 }
 
+/// Computes the OP-stack L1 data fee over the signed envelope of `tx`.
+fn l1_data_fee(
+    chain_context: &ChainContext,
+    tx: &SimulationTxInput,
+    calldata: &[u8],
+) -> Option<u128> {
+    let params = chain_context.op_l1_fee.as_ref()?;
+    match &tx.raw_tx {
+        Some(raw_tx) => (!ChainFamily::OpStack.is_system_tx_type(tx.decoded.tx_type))
+            .then(|| op_l1_data_fee(raw_tx, params)),
+        None => estimate_l1_data_fee(&tx.decoded, calldata, params),
+    }
+}
+
+/// Estimates the OP-stack L1 data fee for a decoded transaction by encoding
+/// its signed envelope with a placeholder signature.
+///
+/// The fee parameters only exist on OP-stack chains, so the chain family is
+/// implied by `params`. System transactions (deposits) are not posted as user
+/// transactions and pay no L1 fee, so they return `None`.
+pub fn estimate_l1_data_fee(
+    decoded: &TxDecoded,
+    calldata: &[u8],
+    params: &OpL1FeeParams,
+) -> Option<u128> {
+    if ChainFamily::OpStack.is_system_tx_type(decoded.tx_type) {
+        return None;
+    }
+    Some(op_l1_data_fee(
+        &encode_signed_tx(decoded, calldata, None),
+        params,
+    ))
+}
+
 fn seed_sender_accounts(
     db: &mut InMemoryDB,
     txs: &[SimulationTxInput],
@@ -323,7 +406,7 @@ fn hash_state_diff(state: &revm::state::EvmState) -> TxHash {
         hasher.update([account.status.bits()]);
 
         let mut storage_slots: Vec<_> = account.changed_storage_slots().collect();
-        storage_slots.sort_by(|(left, _), (right, _)| left.cmp(right));
+        storage_slots.sort_by_key(|(slot, _)| **slot);
         for (slot, value) in storage_slots {
             hasher.update(slot.to_string().as_bytes());
             hasher.update(value.present_value().to_string().as_bytes());
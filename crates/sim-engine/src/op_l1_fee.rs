@@ -0,0 +1,199 @@
+//! OP-stack L1 data fee estimation for L2 gas cost accounting.
+//!
+//! Mirrors the Ecotone and Fjord cost functions from the OP-stack execution
+//! client so simulated L2 costs include the L1 data availability charge.
+
+use serde::{Deserialize, Serialize};
+
+const ECOTONE_DIVISOR: u128 = 16_000_000;
+const FJORD_DIVISOR: u128 = 1_000_000_000_000;
+const FJORD_COST_INTERCEPT: i128 = -42_585_600;
+const FJORD_FASTLZ_COEF: i128 = 836_500;
+const FJORD_MIN_TX_SIZE_SCALED: i128 = 100_000_000;
+const FJORD_FASTLZ_OVERHEAD_BYTES: u64 = 68;
+
+/// L1 fee cost function active on the target OP-stack chain.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpL1FeeFormula {
+    Ecotone,
+    #[default]
+    Fjord,
+}
+
+/// L1 fee inputs read from the OP-stack `L1Block` predeploy.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct OpL1FeeParams {
+    pub formula: OpL1FeeFormula,
+    pub l1_base_fee_wei: u128,
+    pub l1_blob_base_fee_wei: u128,
+    pub base_fee_scalar: u32,
+    pub blob_base_fee_scalar: u32,
+}
+
+/// Computes the L1 data fee charged for one serialized OP-stack transaction.
+pub fn op_l1_data_fee(tx_bytes: &[u8], params: &OpL1FeeParams) -> u128 {
+    match params.formula {
+        OpL1FeeFormula::Ecotone => ecotone_l1_data_fee(tx_bytes, params),
+        OpL1FeeFormula::Fjord => fjord_l1_data_fee(tx_bytes, params),
+    }
+}
+
+fn weighted_l1_gas_price(params: &OpL1FeeParams) -> u128 {
+    let calldata_cost = params
+        .l1_base_fee_wei
+        .saturating_mul(16)
+        .saturating_mul(u128::from(params.base_fee_scalar));
+    let blob_cost = params
+        .l1_blob_base_fee_wei
+        .saturating_mul(u128::from(params.blob_base_fee_scalar));
+    calldata_cost.saturating_add(blob_cost)
+}
+
+fn ecotone_l1_data_fee(tx_bytes: &[u8], params: &OpL1FeeParams) -> u128 {
+    let zeroes = tx_bytes.iter().filter(|byte| **byte == 0).count() as u128;
+    let ones = tx_bytes.len() as u128 - zeroes;
+    let calldata_gas = zeroes * 4 + ones * 16;
+    calldata_gas.saturating_mul(weighted_l1_gas_price(params)) / ECOTONE_DIVISOR
+}
+
+fn fjord_l1_data_fee(tx_bytes: &[u8], params: &OpL1FeeParams) -> u128 {
+    let fastlz_size = u64::from(flz_compress_len(tx_bytes)) + FJORD_FASTLZ_OVERHEAD_BYTES;
+    let estimated_size = (FJORD_COST_INTERCEPT + FJORD_FASTLZ_COEF * i128::from(fastlz_size))
+        .max(FJORD_MIN_TX_SIZE_SCALED) as u128;
+    estimated_size.saturating_mul(weighted_l1_gas_price(params)) / FJORD_DIVISOR
+}
+
+/// Returns the length of the FastLZ (level 1) compression of `input`, matching
+/// the estimator used by the OP-stack `GasPriceOracle`.
+pub fn flz_compress_len(input: &[u8]) -> u32 {
+    let len = input.len() as u32;
+    let mut out = 0_u32;
+    let mut table = vec![0_u32; 8_192];
+
+    let u24 = |index: u32| -> u32 {
+        let index = index as usize;
+        u32::from(input[index])
+            | (u32::from(input[index + 1]) << 8)
+            | (u32::from(input[index + 2]) << 16)
+    };
+    let hash =
+        |value: u32| -> usize { ((value.wrapping_mul(2_654_435_769) >> 19) & 0x1fff) as usize };
+    let literals = |run: u32, out: &mut u32| {
+        *out += 0x21 * (run / 0x20);
+        let rest = run % 0x20;
+        if rest != 0 {
+            *out += rest + 1;
+        }
+    };
+
+    let mut anchor = 0_u32;
+    let ip_limit = len.saturating_sub(13);
+    let mut ip = anchor + 2;
+    while ip < ip_limit {
+        let mut reference;
+        loop {
+            let sequence = u24(ip);
+            let slot = hash(sequence);
+            reference = table[slot];
+            table[slot] = ip;
+            let distance = ip - reference;
+            if ip >= ip_limit {
+                break;
+            }
+            ip += 1;
+            if distance <= 0x1fff && sequence == u24(reference) {
+                break;
+            }
+        }
+        if ip >= ip_limit {
+            break;
+        }
+        ip -= 1;
+        if ip > anchor {
+            literals(ip - anchor, &mut out);
+        }
+
+        // The reference implementation counts one byte past the first
+        // mismatch; keep that quirk so estimates match on-chain values.
+        let mut match_len = 0_u32;
+        let mut end = ip_limit + 9 - (ip + 3);
+        while match_len < end {
+            if input[(reference + 3 + match_len) as usize] != input[(ip + 3 + match_len) as usize] {
+                end = 0;
+            }
+            match_len += 1;
+        }
+
+        let encoded = match_len - 1;
+        out += 3 * (encoded / 262);
+        out += if encoded % 262 >= 6 { 3 } else { 2 };
+
+        ip += match_len;
+        table[hash(u24(ip))] = ip;
+        ip += 1;
+        table[hash(u24(ip))] = ip;
+        ip += 1;
+        anchor = ip;
+    }
+    literals(len - anchor, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(formula: OpL1FeeFormula) -> OpL1FeeParams {
+        OpL1FeeParams {
+            formula,
+            l1_base_fee_wei: 20_000_000_000,
+            l1_blob_base_fee_wei: 1,
+            base_fee_scalar: 1_368,
+            blob_base_fee_scalar: 810_949,
+        }
+    }
+
+    #[test]
+    fn ecotone_fee_weights_zero_and_nonzero_bytes() {
+        let tx = [0_u8, 0, 1, 2];
+        // 2 zero bytes * 4 + 2 non-zero bytes * 16 = 40 calldata gas.
+        let expected = 40 * (20_000_000_000_u128 * 16 * 1_368 + 810_949) / 16_000_000;
+        assert_eq!(
+            op_l1_data_fee(&tx, &params(OpL1FeeFormula::Ecotone)),
+            expected
+        );
+    }
+
+    #[test]
+    fn fjord_fee_applies_minimum_size_floor() {
+        let expected = 100_000_000 * (20_000_000_000_u128 * 16 * 1_368 + 810_949) / FJORD_DIVISOR;
+        assert_eq!(
+            op_l1_data_fee(&[], &params(OpL1FeeFormula::Fjord)),
+            expected
+        );
+    }
+
+    #[test]
+    fn fastlz_estimate_rewards_repetitive_payloads() {
+        assert_eq!(flz_compress_len(&[]), 0);
+        assert_eq!(flz_compress_len(&[0xab; 4]), 5);
+
+        let repetitive = vec![0x11_u8; 1_024];
+        let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+        let varied = (0..1_024)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                (state >> 56) as u8
+            })
+            .collect::<Vec<_>>();
+        assert!(flz_compress_len(&repetitive) < 64);
+        assert!(flz_compress_len(&varied) > 1_000);
+        assert!(
+            op_l1_data_fee(&repetitive, &params(OpL1FeeFormula::Fjord))
+                < op_l1_data_fee(&varied, &params(OpL1FeeFormula::Fjord))
+        );
+    }
+}
@@ -20,6 +20,7 @@ fn deterministic_replay_same_inputs_produce_identical_outputs() {
         base_fee_wei: 35_000_000_000,
         coinbase: address(0x42),
         state_root: hash(0x7f),
+        op_l1_fee: None,
     };
 
     let txs = vec![
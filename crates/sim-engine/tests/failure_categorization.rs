@@ -22,6 +22,7 @@ fn context() -> ChainContext {
         base_fee_wei: 1_000_000_000,
         coinbase: address(0x55),
         state_root: hash(0xaa),
+        op_l1_fee: None,
     }
 }
 
@@ -59,6 +60,7 @@ fn rpc_backed_nonce_mismatch_is_categorized_and_trace_id_is_present() {
             calldata_len: Some(4),
         },
         calldata: Some(vec![0xde, 0xad, 0xbe, 0xef]),
+        raw_tx: None,
     }];
 
     let batch = simulate_with_mode(
@@ -99,6 +101,7 @@ fn create_loop_out_of_gas_is_categorized() {
             calldata_len: Some(loop_init_code.len() as u32),
         },
         calldata: Some(loop_init_code),
+        raw_tx: None,
     }];
 
     let batch = simulate_with_mode(&context(), &txs, SimulationMode::SyntheticDeterministic)
@@ -0,0 +1,126 @@
+use common::{Address, TxHash};
+use event_log::TxDecoded;
+use sim_engine::{
+    ChainContext, OpL1FeeFormula, OpL1FeeParams, SimulationMode, SimulationTxInput,
+    encode_signed_tx, op_l1_data_fee, simulate_with_mode,
+};
+
+fn hash(v: u8) -> TxHash {
+    [v; 32]
+}
+
+fn address(v: u8) -> Address {
+    [v; 20]
+}
+
+fn fee_params() -> OpL1FeeParams {
+    OpL1FeeParams {
+        formula: OpL1FeeFormula::Fjord,
+        l1_base_fee_wei: 20_000_000_000,
+        l1_blob_base_fee_wei: 1_000_000_000,
+        base_fee_scalar: 2_269,
+        blob_base_fee_scalar: 1_055_762,
+    }
+}
+
+fn base_chain(op_l1_fee: Option<OpL1FeeParams>) -> ChainContext {
+    ChainContext {
+        chain_id: 8453,
+        block_number: 21_000_000,
+        block_timestamp: 1_720_000_000,
+        gas_limit: 30_000_000,
+        base_fee_wei: 10_000_000,
+        coinbase: address(0x42),
+        state_root: hash(0x7f),
+        op_l1_fee,
+    }
+}
+
+fn input(raw_tx: Option<Vec<u8>>) -> SimulationTxInput {
+    let calldata = (0..96_u8).collect::<Vec<_>>();
+    SimulationTxInput {
+        decoded: TxDecoded {
+            hash: hash(0x01),
+            tx_type: 2,
+            sender: address(0xaa),
+            nonce: 0,
+            chain_id: Some(8453),
+            to: Some(address(0xbb)),
+            value_wei: Some(0),
+            gas_limit: Some(90_000),
+            gas_price_wei: None,
+            max_fee_per_gas_wei: Some(50_000_000),
+            max_priority_fee_per_gas_wei: Some(1_000_000),
+            max_fee_per_blob_gas_wei: None,
+            calldata_len: Some(calldata.len() as u32),
+        },
+        calldata: Some(calldata),
+        raw_tx,
+    }
+}
+
+#[test]
+fn l1_data_fee_is_charged_on_signed_envelope_and_included_in_gas_cost() {
+    let params = fee_params();
+    let tx = input(None);
+    let calldata = tx.calldata.clone().expect("calldata");
+    let batch = simulate_with_mode(
+        &base_chain(Some(params)),
+        std::slice::from_ref(&tx),
+        SimulationMode::SyntheticDeterministic,
+    )
+    .expect("simulate");
+    let result = &batch.tx_results[0];
+
+    let expected = op_l1_data_fee(&encode_signed_tx(&tx.decoded, &calldata, None), &params);
+    assert_eq!(result.l1_data_fee_wei, Some(expected));
+    assert!(expected > op_l1_data_fee(&calldata, &params));
+    // Effective price is min(max fee, base fee + priority fee).
+    let effective_gas_price = 11_000_000_u128;
+    assert_eq!(
+        result.gas_cost_wei,
+        u128::from(result.gas_used) * effective_gas_price + expected
+    );
+    assert_eq!(batch.total_l1_data_fee_wei(), Some(expected));
+    assert_eq!(batch.total_gas_cost_wei(), result.gas_cost_wei);
+}
+
+#[test]
+fn l1_data_fee_uses_raw_envelope_when_provided() {
+    let params = fee_params();
+    let raw_tx = vec![0x02; 180];
+    let batch = simulate_with_mode(
+        &base_chain(Some(params)),
+        &[input(Some(raw_tx.clone()))],
+        SimulationMode::SyntheticDeterministic,
+    )
+    .expect("simulate");
+
+    assert_eq!(
+        batch.tx_results[0].l1_data_fee_wei,
+        Some(op_l1_data_fee(&raw_tx, &params))
+    );
+}
+
+#[test]
+fn l1_fee_params_do_not_change_execution_digest() {
+    let with_fee = simulate_with_mode(
+        &base_chain(Some(fee_params())),
+        &[input(None)],
+        SimulationMode::SyntheticDeterministic,
+    )
+    .expect("simulate with fee");
+    let without_fee = simulate_with_mode(
+        &base_chain(None),
+        &[input(None)],
+        SimulationMode::SyntheticDeterministic,
+    )
+    .expect("simulate without fee");
+
+    assert_eq!(without_fee.tx_results[0].l1_data_fee_wei, None);
+    assert_eq!(
+        with_fee.final_state_diff_hash,
+        without_fee.final_state_diff_hash
+    );
+    assert!(with_fee.tx_results[0].gas_cost_wei > without_fee.tx_results[0].gas_cost_wei);
+}
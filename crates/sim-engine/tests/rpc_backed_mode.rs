@@ -51,6 +51,7 @@ fn rpc_backed_mode_fetches_account_state_before_execution() {
         base_fee_wei: 1_000_000_000,
         coinbase: address(0x55),
         state_root: hash(0xaa),
+        op_l1_fee: None,
     };
     let txs = vec![SimulationTxInput {
         decoded: TxDecoded {
@@ -69,6 +70,7 @@ fn rpc_backed_mode_fetches_account_state_before_execution() {
             calldata_len: Some(4),
         },
         calldata: Some(vec![0xde, 0xad, 0xbe, 0xef]),
+        raw_tx: None,
     }];
 
     let result = simulate_with_mode(&context, &txs, SimulationMode::RpcBacked(&provider))
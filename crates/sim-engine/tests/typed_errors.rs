@@ -22,6 +22,7 @@ fn context() -> ChainContext {
         base_fee_wei: 1_000_000_000,
         coinbase: address(0x55),
         state_root: hash(0xaa),
+        op_l1_fee: None,
     }
 }
 
@@ -54,6 +55,7 @@ fn rpc_backed_state_provider_errors_are_typed() {
             calldata_len: Some(4),
         },
         calldata: Some(vec![0xde, 0xad, 0xbe, 0xef]),
+        raw_tx: None,
     }];
 
     let err = simulate_with_mode(
@@ -1,10 +1,38 @@
 //! Backfill helpers for replaying historical events into storage.
+//!
+//! [`BackfillWriter`] applies slices already in memory. A [`BackfillJob`]
+//! instead streams a [`BackfillSource`] (the ClickHouse `mev_v2.events`
+//! table or a copied WAL) through the storage writer task: before the writer
+//! sequences any live event it moves its sequencer past the source's head,
+//! so live ingest is numbered after the history and both can be applied
+//! side by side; history for a hash that a newer event already projected is
+//! stored without touching that hash's rows. Each applied batch is persisted to the cursor file, and a
+//! restart whose storage recovered that prefix resumes after it.
 
-use crate::{EventStore, InMemoryStorage};
-use event_log::{EventEnvelope, sort_deterministic};
+use crate::clickhouse_rows::DATABASE;
+use crate::{
+    ClickHouseHttpSink, EventStore, FastMap, InMemoryStorage, SharedStorageLookup, StorageError,
+    StorageWal, StorageWriteOp, WalEventIter, WalHead, WalScanRange, WalWriter,
+};
+use anyhow::Context;
+use async_trait::async_trait;
+use common::TxHash;
+use event_log::{
+    EventEnvelope, EventPayload, EventSchemaRegistry, UpcastError, sort_deterministic,
+};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+type Result<T, E = StorageError> = std::result::Result<T, E>;
+
+const DEFAULT_BACKFILL_BATCH_SIZE: usize = 1_000;
+const BACKFILL_FETCH_ATTEMPTS: u32 = 3;
+const BACKFILL_RETRY_BACKOFF_MS: u64 = 250;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 /// Retention settings applied while backfilling historical events.
@@ -69,4 +97,639 @@ impl BackfillWriter {
         }
         summary
     }
+
+    /// Upcasts newline-delimited JSON rows, such as a ClickHouse
+    /// `mev_v2.events` export, to the current event schema and applies them
+    /// like [`Self::apply_events`].
+    ///
+    /// Rows may carry the payload either inline as `payload` or as the
+    /// `payload_json` string column. Nothing is applied if any row fails.
+    pub fn apply_json_rows(
+        &mut self,
+        rows: &[u8],
+        now_unix_ms: i64,
+    ) -> Result<BackfillSummary, UpcastError> {
+        let registry = EventSchemaRegistry::builtin();
+        let events = rows
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.trim_ascii().is_empty())
+            .map(|line| match clickhouse_row_record(line)? {
+                Some(record) => registry.decode_json_slice(&record),
+                None => registry.decode_json_slice(line),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.apply_events(&events, now_unix_ms))
+    }
+}
+
+/// Seq and time bounds of the history to backfill.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BackfillRange {
+    /// Only events with a strictly greater `seq_id` are backfilled.
+    pub after_seq_id: u64,
+    /// Inclusive upper bound on `seq_id`.
+    pub to_seq_id: Option<u64>,
+    /// Inclusive lower bound on `ingest_ts_unix_ms`.
+    pub from_ts_unix_ms: Option<i64>,
+    /// Inclusive upper bound on `ingest_ts_unix_ms`.
+    pub to_ts_unix_ms: Option<i64>,
+}
+
+impl BackfillRange {
+    fn contains(&self, event: &EventEnvelope) -> bool {
+        event.seq_id > self.after_seq_id
+            && self.to_seq_id.is_none_or(|to| event.seq_id <= to)
+            && self
+                .from_ts_unix_ms
+                .is_none_or(|from| event.ingest_ts_unix_ms >= from)
+            && self
+                .to_ts_unix_ms
+                .is_none_or(|to| event.ingest_ts_unix_ms <= to)
+    }
+}
+
+#[async_trait]
+/// Historical event stream a [`BackfillJob`] reads in seq order.
+pub trait BackfillSource: Send {
+    /// Stable description of the source; a cursor file only resumes the
+    /// source it was written for.
+    fn label(&self) -> String;
+
+    /// Moves the start of the stream past `seq_id`; called before the first
+    /// batch when resuming.
+    fn resume_after(&mut self, seq_id: u64);
+
+    /// Highest seq and chain seq ids the range holds.
+    async fn head(&mut self) -> Result<WalHead>;
+
+    /// Up to `limit` further events of the range; empty once exhausted.
+    async fn next_batch(&mut self, limit: usize) -> Result<Vec<EventEnvelope>>;
+}
+
+/// Pages the ClickHouse `mev_v2.events` table over HTTP in seq order.
+pub struct ClickHouseBackfillSource {
+    client: reqwest::Client,
+    base_url: String,
+    range: BackfillRange,
+}
+
+impl ClickHouseBackfillSource {
+    /// Reads from the server at `base_url` (for example
+    /// `http://localhost:8123`).
+    pub fn new(base_url: impl Into<String>, range: BackfillRange) -> Result<Self> {
+        Ok(Self {
+            client: ClickHouseHttpSink::client().map_err(StorageError::backfill)?,
+            base_url: base_url.into(),
+            range,
+        })
+    }
+
+    fn where_clause(&self) -> String {
+        let range = &self.range;
+        let mut clause = format!("seq_id > {}", range.after_seq_id);
+        if let Some(to) = range.to_seq_id {
+            clause.push_str(&format!(" AND seq_id <= {to}"));
+        }
+        if let Some(from) = range.from_ts_unix_ms {
+            clause.push_str(&format!(" AND ingest_ts_unix_ms >= {from}"));
+        }
+        if let Some(to) = range.to_ts_unix_ms {
+            clause.push_str(&format!(" AND ingest_ts_unix_ms <= {to}"));
+        }
+        clause
+    }
+
+    async fn query(&self, sql: String) -> Result<String> {
+        let url = &self.base_url;
+        self.client
+            .post(url)
+            .query(&[("output_format_json_quote_64bit_integers", "0")])
+            .header("content-type", "text/plain")
+            .body(sql)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| StorageError::backfill(anyhow::anyhow!("POST {url}: {err}")))?
+            .text()
+            .await
+            .map_err(|err| StorageError::backfill(anyhow::anyhow!("read {url}: {err}")))
+    }
+}
+
+#[derive(Deserialize)]
+struct ClickHouseHeadRow {
+    seq_id: u64,
+    events: u64,
+}
+
+#[async_trait]
+impl BackfillSource for ClickHouseBackfillSource {
+    fn label(&self) -> String {
+        format!("clickhouse:{}", self.base_url)
+    }
+
+    fn resume_after(&mut self, seq_id: u64) {
+        self.range.after_seq_id = self.range.after_seq_id.max(seq_id);
+    }
+
+    async fn head(&mut self) -> Result<WalHead> {
+        let body = self
+            .query(format!(
+                "SELECT max(seq_id) AS seq_id, count() AS events FROM {DATABASE}.events \
+                 WHERE {} FORMAT JSONEachRow",
+                self.where_clause()
+            ))
+            .await?;
+        let row = body
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .map(serde_json::from_str::<ClickHouseHeadRow>)
+            .transpose()
+            .context("decode ClickHouse backfill head")
+            .map_err(StorageError::backfill)?;
+        // The events table keeps no chain seq ids.
+        Ok(WalHead {
+            latest_seq_id: row.filter(|row| row.events > 0).map(|row| row.seq_id),
+            ..WalHead::default()
+        })
+    }
+
+    async fn next_batch(&mut self, limit: usize) -> Result<Vec<EventEnvelope>> {
+        let body = self
+            .query(format!(
+                "SELECT seq_id, ingest_ts_unix_ms, ingest_ts_mono_ns, source_id, schema_version, \
+                 chain_id, payload_json FROM {DATABASE}.events WHERE {} ORDER BY seq_id LIMIT {} \
+                 FORMAT JSONEachRow",
+                self.where_clause(),
+                limit.max(1)
+            ))
+            .await?;
+        let registry = EventSchemaRegistry::builtin();
+        let events = body
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let record = clickhouse_row_record(line.as_bytes())?;
+                registry.decode_json_slice(record.as_deref().unwrap_or(line.as_bytes()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(StorageError::backfill)?;
+        if let Some(last) = events.iter().map(|event| event.seq_id).max() {
+            self.range.after_seq_id = last;
+        }
+        Ok(events)
+    }
+}
+
+/// Streams the events of a WAL, such as one copied from another node.
+pub struct WalBackfillSource {
+    path: PathBuf,
+    wal: StorageWal,
+    range: BackfillRange,
+    events: Option<WalEventIter>,
+}
+
+impl WalBackfillSource {
+    /// Reads the WAL whose base path is `path`.
+    pub fn open(path: impl Into<PathBuf>, range: BackfillRange) -> Result<Self> {
+        let path = path.into();
+        Ok(Self {
+            wal: StorageWal::new(path.clone())?,
+            path,
+            range,
+            events: None,
+        })
+    }
+
+    fn scan_range(&self) -> WalScanRange {
+        WalScanRange {
+            after_seq_id: self.range.after_seq_id,
+            from_ts_unix_ms: self.range.from_ts_unix_ms,
+            to_ts_unix_ms: self.range.to_ts_unix_ms,
+        }
+    }
+}
+
+#[async_trait]
+impl BackfillSource for WalBackfillSource {
+    fn label(&self) -> String {
+        format!("wal:{}", self.path.display())
+    }
+
+    fn resume_after(&mut self, seq_id: u64) {
+        self.range.after_seq_id = self.range.after_seq_id.max(seq_id);
+        self.events = None;
+    }
+
+    async fn head(&mut self) -> Result<WalHead> {
+        let mut head = WalHead::default();
+        for event in self.wal.iter_range(self.scan_range())? {
+            let event = event?;
+            if self.range.contains(&event) {
+                head.observe(std::slice::from_ref(&event));
+            }
+        }
+        // Chain links of the history belong to the node that wrote it.
+        head.hash_chain = None;
+        Ok(head)
+    }
+
+    async fn next_batch(&mut self, limit: usize) -> Result<Vec<EventEnvelope>> {
+        let events = match self.events.as_mut() {
+            Some(events) => events,
+            None => self.events.insert(self.wal.iter_range(self.scan_range())?),
+        };
+        let mut batch = Vec::with_capacity(limit.max(1));
+        while batch.len() < limit.max(1) {
+            match events.next() {
+                Some(event) => {
+                    let event = event?;
+                    if self.range.contains(&event) {
+                        batch.push(event);
+                    }
+                }
+                None => break,
+            }
+        }
+        Ok(batch)
+    }
+}
+
+/// A backfill handed to [`crate::spawn_single_writer_with_backfill`].
+pub struct BackfillJob {
+    pub source: Box<dyn BackfillSource>,
+    pub batch_size: usize,
+    /// File recording the last applied seq id, so an interrupted run resumes
+    /// after the prefix storage recovered.
+    pub cursor_path: Option<PathBuf>,
+}
+
+impl BackfillJob {
+    pub fn new(source: impl BackfillSource + 'static) -> Self {
+        Self {
+            source: Box::new(source),
+            batch_size: DEFAULT_BACKFILL_BATCH_SIZE,
+            cursor_path: None,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_cursor_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cursor_path = Some(path.into());
+        self
+    }
+}
+
+/// Lifecycle of a backfill run.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackfillState {
+    #[default]
+    Starting,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl BackfillState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Starting => "starting",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Progress of the writer's backfill run.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BackfillProgress {
+    pub source: String,
+    pub state: BackfillState,
+    /// Highest seq id of the backfilled range; live events are numbered
+    /// after it.
+    pub head_seq_id: Option<u64>,
+    /// Seq id this run resumed after, when a cursor file matched.
+    pub resumed_after_seq_id: Option<u64>,
+    pub last_seq_id: Option<u64>,
+    pub applied: u64,
+    /// Events already present in storage, such as a prefix recovered from
+    /// the WAL.
+    pub skipped_duplicates: u64,
+    pub batches: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct BackfillCursor {
+    source: String,
+    last_seq_id: u64,
+}
+
+/// A cursor update deferred until its batch is committed to the WAL.
+pub(crate) struct BackfillCursorWrite {
+    path: PathBuf,
+    cursor: BackfillCursor,
+}
+
+impl BackfillCursorWrite {
+    /// Replaces the cursor file; a failure only costs refetching on resume.
+    pub(crate) fn persist(&self) {
+        if let Err(err) = write_cursor(&self.path, &self.cursor) {
+            tracing::warn!(error = %err, path = %self.path.display(), "failed to write backfill cursor");
+        }
+    }
+}
+
+pub(crate) enum BackfillFetch {
+    Batch(Vec<EventEnvelope>),
+    Failed(String),
+}
+
+/// Writer-task side of a running backfill.
+pub(crate) struct BackfillRun {
+    progress: Arc<RwLock<BackfillProgress>>,
+    cursor_path: Option<PathBuf>,
+    batches: mpsc::Receiver<BackfillFetch>,
+    /// Newest seq id that drove each hash's projections outside the
+    /// backfill: events recovered before it started and live writes since.
+    projected_seq_ids: FastMap<TxHash, u64>,
+    /// Cursor for the last applied batch, written once that batch is durable.
+    pending_cursor: Option<BackfillCursorWrite>,
+}
+
+impl BackfillRun {
+    /// Moves `sequencer` past the source's head, resumes after the cursor when
+    /// storage still holds the event it names, and starts fetching.
+    pub(crate) async fn start(
+        mut job: BackfillJob,
+        storage: &RwLock<InMemoryStorage>,
+        sequencer: &mut event_log::GlobalSequencer,
+        progress: Arc<RwLock<BackfillProgress>>,
+    ) -> Option<Self> {
+        let label = job.source.label();
+        progress.write().source = label.clone();
+        let head = match job.source.head().await {
+            Ok(head) => head,
+            Err(err) => {
+                tracing::warn!(error = %err, source = %label, "failed to read backfill head");
+                let mut progress = progress.write();
+                progress.state = BackfillState::Failed;
+                progress.error = Some(err.to_string());
+                return None;
+            }
+        };
+        sequencer.skip_past(
+            head.latest_seq_id,
+            head.chain_seq_ids.iter().map(|(k, v)| (*k, *v)),
+        );
+
+        let resumed_after = job
+            .cursor_path
+            .as_deref()
+            .and_then(read_cursor)
+            .filter(|cursor| cursor.source == label)
+            .map(|cursor| cursor.last_seq_id)
+            .filter(|seq_id| storage.contains_seq_id(*seq_id));
+        if let Some(seq_id) = resumed_after {
+            job.source.resume_after(seq_id);
+        }
+        {
+            let mut progress = progress.write();
+            progress.state = BackfillState::Running;
+            progress.head_seq_id = head.latest_seq_id;
+            progress.resumed_after_seq_id = resumed_after;
+        }
+        tracing::info!(
+            source = %label,
+            head_seq_id = ?head.latest_seq_id,
+            resumed_after_seq_id = ?resumed_after,
+            "starting storage backfill"
+        );
+        let projected_seq_ids = storage.read().latest_seq_ids_by_hash();
+        Some(Self {
+            progress,
+            cursor_path: job.cursor_path,
+            batches: spawn_fetcher(job.source, job.batch_size.max(1)),
+            projected_seq_ids,
+            pending_cursor: None,
+        })
+    }
+
+    /// Records the hash a live write is about to project. Live writes are
+    /// sequenced after the backfill head, so they outrank every backfilled
+    /// event for that hash.
+    pub(crate) fn note_live_write(&mut self, op: &StorageWriteOp) {
+        let hash = match op {
+            StorageWriteOp::AppendEvent(EventEnvelope { payload, .. })
+            | StorageWriteOp::AppendPayload { payload, .. } => {
+                if matches!(payload, EventPayload::ChainCheckpoint(_)) {
+                    return;
+                }
+                payload.primary_hash()
+            }
+            StorageWriteOp::UpsertTxSeen(record) => record.hash,
+            StorageWriteOp::UpsertTxFull(record) => record.hash,
+            StorageWriteOp::UpsertTxFeatures(record) => record.hash,
+            StorageWriteOp::UpsertOpportunity(record) => record.tx_hash,
+            StorageWriteOp::UpsertTxLifecycle(record) => record.hash,
+            StorageWriteOp::UpsertPeerStats(_) | StorageWriteOp::WriteSchedulerSnapshot(_) => {
+                return;
+            }
+        };
+        self.projected_seq_ids.insert(hash, u64::MAX);
+    }
+
+    /// Takes the cursor covering the batches applied so far.
+    pub(crate) fn take_cursor_write(&mut self) -> Option<BackfillCursorWrite> {
+        self.pending_cursor.take()
+    }
+
+    /// Waits for the next fetched batch; never resolves once `run` is `None`.
+    pub(crate) async fn next(run: &mut Option<Self>) -> Option<BackfillFetch> {
+        match run.as_mut() {
+            Some(run) => run.batches.recv().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Applies one fetched batch, or finishes the run when `fetch` is `None`.
+    /// Returns whether the run continues.
+    ///
+    /// The batch is only appended to the WAL; the caller commits it and then
+    /// persists [`BackfillRun::take_cursor_write`].
+    pub(crate) fn apply(
+        &mut self,
+        fetch: Option<BackfillFetch>,
+        storage: &mut InMemoryStorage,
+        mut wal: Option<&mut WalWriter>,
+    ) -> bool {
+        let events = match fetch {
+            Some(BackfillFetch::Batch(events)) => events,
+            Some(BackfillFetch::Failed(error)) => {
+                let mut progress = self.progress.write();
+                progress.state = BackfillState::Failed;
+                progress.error = Some(error);
+                return false;
+            }
+            None => {
+                let mut progress = self.progress.write();
+                progress.state = BackfillState::Completed;
+                tracing::info!(
+                    source = %progress.source,
+                    applied = progress.applied,
+                    skipped_duplicates = progress.skipped_duplicates,
+                    "storage backfill completed"
+                );
+                return false;
+            }
+        };
+
+        let (mut applied, mut skipped) = (0_u64, 0_u64);
+        let mut last_seq_id = None;
+        for event in events {
+            last_seq_id = last_seq_id.max(Some(event.seq_id));
+            if storage.contains_seq_id(event.seq_id) {
+                skipped += 1;
+                continue;
+            }
+            if let Some(wal) = wal.as_deref_mut()
+                && let Err(err) = wal.append(&event)
+            {
+                tracing::warn!(error = %err, "failed to append backfilled event to storage WAL");
+            }
+            // History is still stored and indexed, but a hash whose rows a
+            // newer event already drove keeps them.
+            let project = self
+                .projected_seq_ids
+                .get(&event.payload.primary_hash())
+                .is_none_or(|seq_id| event.seq_id > *seq_id);
+            storage.append_event_with_projections(event, project);
+            applied += 1;
+        }
+        if let (Some(path), Some(last_seq_id)) = (self.cursor_path.as_ref(), last_seq_id) {
+            self.pending_cursor = Some(BackfillCursorWrite {
+                path: path.clone(),
+                cursor: BackfillCursor {
+                    source: self.progress.read().source.clone(),
+                    last_seq_id,
+                },
+            });
+        }
+
+        let mut progress = self.progress.write();
+        progress.applied += applied;
+        progress.skipped_duplicates += skipped;
+        progress.batches += 1;
+        progress.last_seq_id = progress.last_seq_id.max(last_seq_id);
+        true
+    }
+}
+
+fn spawn_fetcher(
+    mut source: Box<dyn BackfillSource>,
+    batch_size: usize,
+) -> mpsc::Receiver<BackfillFetch> {
+    // Two batches in flight keep the writer busy without buffering history.
+    let (tx, rx) = mpsc::channel(2);
+    tokio::spawn(async move {
+        let mut attempt = 0;
+        loop {
+            match source.next_batch(batch_size).await {
+                Ok(events) if events.is_empty() => break,
+                Ok(events) => {
+                    attempt = 0;
+                    if tx.send(BackfillFetch::Batch(events)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    attempt += 1;
+                    if attempt >= BACKFILL_FETCH_ATTEMPTS {
+                        tracing::warn!(error = %err, "storage backfill failed");
+                        let _ = tx.send(BackfillFetch::Failed(err.to_string())).await;
+                        break;
+                    }
+                    let backoff = BACKFILL_RETRY_BACKOFF_MS << (attempt - 1);
+                    tokio::time::sleep(Duration::from_millis(backoff)).await;
+                }
+            }
+        }
+    });
+    rx
+}
+
+fn read_cursor(path: &Path) -> Option<BackfillCursor> {
+    let bytes = std::fs::read(path).ok()?;
+    match serde_json::from_slice(&bytes) {
+        Ok(cursor) => Some(cursor),
+        Err(err) => {
+            tracing::warn!(error = %err, path = %path.display(), "ignoring unreadable backfill cursor");
+            None
+        }
+    }
+}
+
+fn write_cursor(path: &Path, cursor: &BackfillCursor) -> Result<()> {
+    let staging = path.with_extension("tmp");
+    serde_json::to_vec(cursor)
+        .context("encode backfill cursor")
+        .and_then(|bytes| std::fs::write(&staging, bytes).context("write backfill cursor"))
+        .and_then(|()| std::fs::rename(&staging, path).context("replace backfill cursor"))
+        .map_err(StorageError::backfill)
+}
+
+#[derive(Deserialize)]
+struct ClickHouseEventRow {
+    #[serde(default)]
+    schema_version: Option<u32>,
+    seq_id: u64,
+    ingest_ts_unix_ms: i64,
+    ingest_ts_mono_ns: u64,
+    source_id: String,
+    #[serde(default)]
+    chain_id: Option<u64>,
+    #[serde(default)]
+    payload_json: Option<String>,
+}
+
+/// Envelope record rebuilt from a `payload_json` row; the payload is embedded
+/// verbatim so wide integers survive.
+#[derive(Serialize)]
+struct ClickHouseEventRecord<'a> {
+    schema_version: u32,
+    seq_id: u64,
+    ingest_ts_unix_ms: i64,
+    ingest_ts_mono_ns: u64,
+    source_id: &'a str,
+    payload: &'a RawValue,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chain_id: Option<u64>,
+}
+
+/// Rebuilds an envelope record from a row that stores its payload in the
+/// `payload_json` column; returns `None` for rows with an inline payload.
+fn clickhouse_row_record(line: &[u8]) -> Result<Option<Vec<u8>>, UpcastError> {
+    let json_error = |err: serde_json::Error| UpcastError::Json(err.to_string());
+    let row: ClickHouseEventRow = serde_json::from_slice(line).map_err(json_error)?;
+    let Some(payload_json) = row.payload_json else {
+        return Ok(None);
+    };
+    let payload: &RawValue = serde_json::from_str(&payload_json).map_err(json_error)?;
+    serde_json::to_vec(&ClickHouseEventRecord {
+        schema_version: row.schema_version.unwrap_or(1),
+        seq_id: row.seq_id,
+        ingest_ts_unix_ms: row.ingest_ts_unix_ms,
+        ingest_ts_mono_ns: row.ingest_ts_mono_ns,
+        source_id: &row.source_id,
+        payload,
+        chain_id: row.chain_id,
+    })
+    .map(Some)
+    .map_err(json_error)
 }
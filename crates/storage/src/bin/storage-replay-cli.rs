@@ -0,0 +1,1049 @@
+#![forbid(unsafe_code)]
+
+//! CLI for replaying stored event logs.
+//!
+//! It lives in `storage` as `storage-replay-cli` because it reads WALs and
+//! writes Parquet through this crate, which itself depends on `replay`.
+//!
+//! Replaying commands read events from `--input <file>` (JSON lines, binary
+//! frames or a JSON array export) or `--wal <path>` (a WAL base path, or a
+//! directory holding one WAL) and stream them through replay, so memory stays
+//! bounded by the reorder window rather than the log size.
+//!
+//! ```text
+//! replay-cli frames       [--stride N] [--mode deterministic|snapshot] [--format json|csv|parquet]
+//! replay-cli checkpoints  [--seq N,N,... | --stride N] [--format json|csv]
+//! replay-cli diff         --from N --to N
+//! replay-cli parity       --candidate <file> | --candidate-wal <path> [--stride N] [--min-percent P]
+//! replay-cli verify       --expected <checkpoints.json|.csv>
+//! replay-cli lifecycle    <tx-hash>
+//! replay-cli export       [--format ndjson|binary|parquet]
+//! replay-cli counterfactual --tx-full <tx_full.parquet|.json> [--config <config.json>]
+//! replay-cli rewrite      --input <path> --out <path> [--encoding json|binary]
+//! replay-cli verify-chain --input <path> [--input <path> ...] [--key-id <id> [--public-key-file <path>] [--checkpoint-interval N]]
+//! ```
+//!
+//! Replaying commands also take `--out <path>`, `--chain-id N`,
+//! `--filter <expr>` and `--reorder-window N`. Without a subcommand the
+//! arguments are run as `frames`.
+//!
+//! Exits with status 0 on success, 1 when a check fails (pending sets differ,
+//! parity below `--min-percent`, a checkpoint mismatches or is missing, an
+//! unknown transaction, a counterfactual config changes an outcome, a broken
+//! hash chain) and 2 on usage or I/O errors.
+
+use anyhow::{Context, Result, anyhow};
+use common::TxHash;
+use event_log::{
+    CURRENT_EVENT_SCHEMA_VERSION, CheckpointVerifier, EventEncoding, EventEnvelope, EventFilter,
+    EventStreamReader, TxDecoded, decode_event_json_array, decode_event_stream, verify_hash_chain,
+};
+use replay::{
+    CheckpointHash, CounterfactualConfig, DeterministicOrder, LifecycleCheckpoint,
+    RecordedTransaction, ReplayError, ReplayFrame, ReplayMode, checkpoint_diff_summary,
+    checkpoint_hash_parity_iter, counterfactual_replay, current_lifecycle_iter,
+    lifecycle_checkpoint_hash, lifecycle_checkpoints_iter, replay_frames_iter,
+    verify_lifecycle_checkpoint_hash,
+};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use storage::{
+    ParquetExportOptions, ParquetTable, ParquetTableWriter, StorageWal, TxFullRecord,
+    read_parquet_table,
+};
+
+/// Events held back to restore deterministic order in unsorted input.
+const DEFAULT_REORDER_WINDOW: usize = 4096;
+/// Exit status for a check that ran and failed.
+const EXIT_CHECK_FAILED: u8 = 1;
+/// Exit status for usage, input and output errors.
+const EXIT_ERROR: u8 = 2;
+
+/// Options shared by every replaying command.
+const SELECTION_FLAGS: &[&str] = &[
+    "--input",
+    "--wal",
+    "--out",
+    "--chain-id",
+    "--filter",
+    "--reorder-window",
+];
+const SELECTION_USAGE: &str = "--input <path> | --wal <path> [--out <path>] [--chain-id N] [--filter <expr>] [--reorder-window N]";
+
+fn main() -> ExitCode {
+    match run_with_args(&env::args().skip(1).collect::<Vec<_>>()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+fn run_with_args(args: &[String]) -> Result<ExitCode> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) if !command.starts_with("--") => (command.as_str(), rest),
+        _ => ("frames", args),
+    };
+    match command {
+        "frames" => run_frames(rest),
+        "checkpoints" => run_checkpoints(rest),
+        "diff" => run_diff(rest),
+        "parity" => run_parity(rest),
+        "verify" => run_verify(rest),
+        "lifecycle" => run_lifecycle(rest),
+        "export" => run_export(rest),
+        "counterfactual" => run_counterfactual(rest),
+        "rewrite" => run_rewrite(rest),
+        "verify-chain" => run_verify_chain(rest),
+        unknown => Err(anyhow!(
+            "unknown subcommand '{unknown}'. expected one of: frames, checkpoints, diff, parity, verify, lifecycle, export, counterfactual, rewrite, verify-chain"
+        )),
+    }
+}
+
+/// Writes replay frames every `--stride` events.
+fn run_frames(args: &[String]) -> Result<ExitCode> {
+    let flags = Flags::parse(
+        args,
+        &["--stride", "--mode", "--format"],
+        "frames [--stride N] [--mode deterministic|snapshot] [--format json|csv|parquet]",
+    )?;
+    let stride = flags.value::<usize>("--stride")?.unwrap_or(1);
+    let mode = match flags.get("--mode") {
+        None | Some("deterministic") => ReplayMode::DeterministicEventReplay,
+        Some("snapshot") => ReplayMode::SnapshotReplay,
+        Some(other) => return Err(anyhow!("unsupported --mode value: {other:?}")),
+    };
+    let format = OutputFormat::parse(flags.get("--format"), &["json", "csv", "parquet"])?;
+    let selection = Selection::from_flags(&flags)?;
+    let mut source = EventSource::open(&input_spec(&flags, "--input", "--wal")?)?;
+
+    // Both modes step through every event, so frames are written as they are
+    // replayed.
+    let frames = match mode {
+        ReplayMode::DeterministicEventReplay | ReplayMode::SnapshotReplay => {
+            replay_frames_iter(source.select(&selection), stride)
+        }
+    };
+    let out = flags.get("--out");
+    match format {
+        OutputFormat::Json => write_json_array(frames, out)?,
+        OutputFormat::Csv => write_csv(
+            frames,
+            out,
+            "seq_hi,timestamp_unix_ms,pending_count,sender_queue_count,pending",
+            |frame: &ReplayFrame| {
+                let pending = frame
+                    .pending
+                    .iter()
+                    .map(format_hash)
+                    .collect::<Vec<_>>()
+                    .join(";");
+                format!(
+                    "{},{},{},{},{pending}",
+                    frame.seq_hi,
+                    frame.timestamp_unix_ms,
+                    frame.pending.len(),
+                    frame.sender_queues.len()
+                )
+            },
+        )?,
+        OutputFormat::Parquet => write_parquet(frames, out)?,
+        OutputFormat::Ndjson | OutputFormat::Binary => unreachable!("rejected by parse"),
+    }
+    source.finish()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Writes lifecycle checkpoint hashes at `--seq` ids or every `--stride`
+/// events, in the form `verify --expected` reads back.
+fn run_checkpoints(args: &[String]) -> Result<ExitCode> {
+    let flags = Flags::parse(
+        args,
+        &["--seq", "--stride", "--format"],
+        "checkpoints [--seq N,N,... | --stride N] [--format json|csv]",
+    )?;
+    let seq_ids = flags.get("--seq").map(parse_seq_ids).transpose()?;
+    let stride = flags.value::<usize>("--stride")?;
+    if seq_ids.is_some() && stride.is_some() {
+        return Err(anyhow!("--seq and --stride are mutually exclusive"));
+    }
+    let format = OutputFormat::parse(flags.get("--format"), &["json", "csv"])?;
+    let selection = Selection::from_flags(&flags)?;
+    let mut source = EventSource::open(&input_spec(&flags, "--input", "--wal")?)?;
+
+    let events = source.select(&selection);
+    let checkpoints: Box<dyn Iterator<Item = LifecycleCheckpoint> + '_> = match &seq_ids {
+        Some(seq_ids) => Box::new(lifecycle_checkpoints_iter(events, seq_ids)),
+        None => Box::new(
+            replay_frames_iter(events, stride.unwrap_or(1)).map(|frame| LifecycleCheckpoint {
+                seq_id: frame.seq_hi,
+                pending_hashes: frame.pending,
+                sender_queues: frame.sender_queues,
+            }),
+        ),
+    };
+    let mut found = BTreeSet::new();
+    let hashes = checkpoints.map(|checkpoint| {
+        found.insert(checkpoint.seq_id);
+        CheckpointHash {
+            seq_id: checkpoint.seq_id,
+            pending_count: checkpoint.pending_hashes.len(),
+            checkpoint_hash: lifecycle_checkpoint_hash(&checkpoint),
+        }
+    });
+    let out = flags.get("--out");
+    match format {
+        OutputFormat::Json => write_json_array(hashes, out)?,
+        _ => write_csv(
+            hashes,
+            out,
+            "seq_id,pending_count,checkpoint_hash",
+            |checkpoint: &CheckpointHash| {
+                format!(
+                    "{},{},{}",
+                    checkpoint.seq_id,
+                    checkpoint.pending_count,
+                    format_hash(&checkpoint.checkpoint_hash)
+                )
+            },
+        )?,
+    }
+    source.finish()?;
+
+    let missing = seq_ids
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|seq_id| !found.contains(seq_id))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        eprintln!("checkpoint seq ids not found in input: {missing:?}");
+        Ok(ExitCode::from(EXIT_CHECK_FAILED))
+    }
+}
+
+/// Reports the pending-set diff between two sequence ids, exiting 1 when the
+/// sets differ.
+fn run_diff(args: &[String]) -> Result<ExitCode> {
+    let flags = Flags::parse(args, &["--from", "--to"], "diff --from N --to N")?;
+    let from = flags
+        .value::<u64>("--from")?
+        .context("missing required argument --from N")?;
+    let to = flags
+        .value::<u64>("--to")?
+        .context("missing required argument --to N")?;
+    let (from, to) = if from <= to { (from, to) } else { (to, from) };
+    let selection = Selection::from_flags(&flags)?;
+    let mut source = EventSource::open(&input_spec(&flags, "--input", "--wal")?)?;
+
+    let checkpoints = lifecycle_checkpoints_iter(source.select(&selection), &[from, to])
+        .map(|checkpoint| (checkpoint.seq_id, checkpoint))
+        .collect::<BTreeMap<_, _>>();
+    source.finish()?;
+    let checkpoint = |seq_id: u64| {
+        checkpoints
+            .get(&seq_id)
+            .with_context(|| format!("seq id {seq_id} not found in input"))
+    };
+    let summary = checkpoint_diff_summary(checkpoint(from)?, checkpoint(to)?);
+    write_json(&summary, flags.get("--out"))?;
+
+    Ok(
+        if summary.added_pending.is_empty() && summary.removed_pending.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(EXIT_CHECK_FAILED)
+        },
+    )
+}
+
+/// Compares checkpoint hashes of the input against a candidate log, exiting 1
+/// when parity is below `--min-percent` (default 100).
+fn run_parity(args: &[String]) -> Result<ExitCode> {
+    let flags = Flags::parse(
+        args,
+        &[
+            "--candidate",
+            "--candidate-wal",
+            "--stride",
+            "--min-percent",
+        ],
+        "parity --candidate <path> | --candidate-wal <path> [--stride N] [--min-percent P]",
+    )?;
+    let stride = flags.value::<usize>("--stride")?.unwrap_or(1);
+    let min_percent = flags.value::<f64>("--min-percent")?.unwrap_or(100.0);
+    let selection = Selection::from_flags(&flags)?;
+    let mut reference = EventSource::open(&input_spec(&flags, "--input", "--wal")?)?;
+    let mut candidate = EventSource::open(&input_spec(&flags, "--candidate", "--candidate-wal")?)?;
+
+    let report = checkpoint_hash_parity_iter(
+        reference.select(&selection),
+        candidate.select(&selection),
+        stride,
+    );
+    reference.finish()?;
+    candidate.finish().context("read candidate")?;
+    write_json(&report, flags.get("--out"))?;
+
+    Ok(if report.parity_percent >= min_percent {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_CHECK_FAILED)
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct VerifyReport {
+    checked: usize,
+    verified: usize,
+    mismatches: Vec<VerifyMismatch>,
+    missing_seq_ids: Vec<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct VerifyMismatch {
+    seq_id: u64,
+    expected: String,
+    actual: String,
+}
+
+/// Replays the input and checks it against expected checkpoint hashes,
+/// exiting 1 on any mismatch or missing checkpoint.
+fn run_verify(args: &[String]) -> Result<ExitCode> {
+    let flags = Flags::parse(
+        args,
+        &["--expected"],
+        "verify --expected <checkpoints.json|.csv>",
+    )?;
+    let expected_path = flags
+        .get("--expected")
+        .context("missing required argument --expected <path>")?;
+    let expected = read_expected_checkpoints(expected_path)?
+        .into_iter()
+        .map(|checkpoint| (checkpoint.seq_id, checkpoint.checkpoint_hash))
+        .collect::<BTreeMap<_, _>>();
+    let selection = Selection::from_flags(&flags)?;
+    let mut source = EventSource::open(&input_spec(&flags, "--input", "--wal")?)?;
+
+    let seq_ids = expected.keys().copied().collect::<Vec<_>>();
+    let mut report = VerifyReport {
+        checked: expected.len(),
+        verified: 0,
+        mismatches: Vec::new(),
+        missing_seq_ids: Vec::new(),
+    };
+    let mut found = BTreeSet::new();
+    for checkpoint in lifecycle_checkpoints_iter(source.select(&selection), &seq_ids) {
+        found.insert(checkpoint.seq_id);
+        match verify_lifecycle_checkpoint_hash(&checkpoint, expected[&checkpoint.seq_id]) {
+            Ok(_) => report.verified += 1,
+            Err(ReplayError::CheckpointMismatch {
+                seq,
+                expected,
+                actual,
+            }) => report.mismatches.push(VerifyMismatch {
+                seq_id: seq,
+                expected: format_hash(&expected),
+                actual: format_hash(&actual),
+            }),
+            Err(err) => return Err(err.into()),
+        }
+    }
+    source.finish()?;
+    report.missing_seq_ids = seq_ids
+        .into_iter()
+        .filter(|seq_id| !found.contains(seq_id))
+        .collect();
+    write_json(&report, flags.get("--out"))?;
+
+    Ok(
+        if report.mismatches.is_empty() && report.missing_seq_ids.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(EXIT_CHECK_FAILED)
+        },
+    )
+}
+
+/// Prints the replayed lifecycle status of one transaction, exiting 1 when
+/// the input never mentions it.
+fn run_lifecycle(args: &[String]) -> Result<ExitCode> {
+    let flags = Flags::parse(args, &[], "lifecycle <tx-hash>")?;
+    let [hash] = flags.positional.as_slice() else {
+        return Err(anyhow!(
+            "expected exactly one transaction hash: lifecycle <tx-hash>"
+        ));
+    };
+    let hash = parse_hash(hash)?;
+    let selection = Selection::from_flags(&flags)?;
+    let mut source = EventSource::open(&input_spec(&flags, "--input", "--wal")?)?;
+
+    let status = current_lifecycle_iter(source.select(&selection), hash);
+    source.finish()?;
+    match status {
+        Some(status) => {
+            write_json(&status, flags.get("--out"))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        None => {
+            eprintln!("transaction {} not found in input", format_hash(&hash));
+            Ok(ExitCode::from(EXIT_CHECK_FAILED))
+        }
+    }
+}
+
+/// Re-encodes the selected events, upcast to the current schema version.
+fn run_export(args: &[String]) -> Result<ExitCode> {
+    let flags = Flags::parse(
+        args,
+        &["--format"],
+        "export [--format ndjson|binary|parquet]",
+    )?;
+    let format = OutputFormat::parse(flags.get("--format"), &["ndjson", "binary", "parquet"])?;
+    let selection = Selection::from_flags(&flags)?;
+    let mut source = EventSource::open(&input_spec(&flags, "--input", "--wal")?)?;
+
+    let out = flags.get("--out");
+    let mut exported = 0_u64;
+    let events = source.select(&selection).inspect(|_| exported += 1);
+    match format {
+        OutputFormat::Parquet => write_parquet(events, out)?,
+        OutputFormat::Binary | OutputFormat::Ndjson => {
+            let encoding = if format == OutputFormat::Binary {
+                EventEncoding::Binary
+            } else {
+                EventEncoding::Json
+            };
+            let mut output = output_writer(out)?;
+            let mut record = Vec::new();
+            for event in events {
+                record.clear();
+                encoding
+                    .encode_into(&event, &mut record)
+                    .with_context(|| format!("encode event seq_id={}", event.seq_id))?;
+                output.write_all(&record).context("write output")?;
+            }
+            output.flush().context("write output")?;
+        }
+        OutputFormat::Json | OutputFormat::Csv => unreachable!("rejected by parse"),
+    }
+    source.finish()?;
+    eprintln!("exported {exported} events at schema version {CURRENT_EVENT_SCHEMA_VERSION}");
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Re-executes the recorded transactions through admission, search,
+/// simulation and assembly with `--config` and reports which outcomes differ
+/// from what production emitted, exiting 1 when any do.
+fn run_counterfactual(args: &[String]) -> Result<ExitCode> {
+    let flags = Flags::parse(
+        args,
+        &["--tx-full", "--config"],
+        "counterfactual --tx-full <tx_full.parquet|.json> [--config <config.json>]",
+    )?;
+    let tx_full_path = flags
+        .get("--tx-full")
+        .context("missing required argument --tx-full <path>")?;
+    let config = match flags.get("--config") {
+        Some(path) => serde_json::from_str::<CounterfactualConfig>(
+            &fs::read_to_string(path).with_context(|| format!("read config file {path}"))?,
+        )
+        .with_context(|| format!("decode config file {path}"))?,
+        None => CounterfactualConfig::default(),
+    };
+    let recorded = read_recorded_transactions(tx_full_path)?;
+    let selection = Selection::from_flags(&flags)?;
+    let mut source = EventSource::open(&input_spec(&flags, "--input", "--wal")?)?;
+
+    let report = counterfactual_replay(source.select(&selection), config, |hash| {
+        recorded.get(hash).cloned()
+    })?;
+    source.finish()?;
+    write_json(&report, flags.get("--out"))?;
+
+    Ok(if report.has_changes() {
+        ExitCode::from(EXIT_CHECK_FAILED)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Rewrites an event log of any supported schema version or encoding to the
+/// current schema version.
+fn run_rewrite(args: &[String]) -> Result<ExitCode> {
+    let mut input_path: Option<String> = None;
+    let mut output_path: Option<String> = None;
+    let mut encoding = EventEncoding::Json;
+
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" => {
+                i += 1;
+                input_path = args.get(i).cloned();
+            }
+            "--out" => {
+                i += 1;
+                output_path = args.get(i).cloned();
+            }
+            "--encoding" => {
+                i += 1;
+                encoding = match args.get(i).map(String::as_str) {
+                    Some("json") => EventEncoding::Json,
+                    Some("binary") => EventEncoding::Binary,
+                    other => return Err(anyhow!("unsupported --encoding value: {other:?}")),
+                };
+            }
+            unknown => {
+                return Err(anyhow!(
+                    "unknown argument '{unknown}'. expected: rewrite --input <path> --out <path> [--encoding json|binary]"
+                ));
+            }
+        }
+        i += 1;
+    }
+
+    let input_path = input_path.context("missing required argument --input <path>")?;
+    let output_path = output_path.context("missing required argument --out <path>")?;
+    let file =
+        File::create(&output_path).with_context(|| format!("create output file {output_path}"))?;
+    let mut output = BufWriter::new(file);
+    let mut record = Vec::new();
+    let mut rewritten = 0_u64;
+    for event in read_input_events(Path::new(&input_path))? {
+        let event = event.with_context(|| format!("decode input file {input_path}"))?;
+        record.clear();
+        encoding
+            .encode_into(&event, &mut record)
+            .with_context(|| format!("encode event seq_id={}", event.seq_id))?;
+        output
+            .write_all(&record)
+            .with_context(|| format!("write output file {output_path}"))?;
+        rewritten += 1;
+    }
+    output
+        .flush()
+        .with_context(|| format!("write output file {output_path}"))?;
+    println!("rewrote {rewritten} events to schema version {CURRENT_EVENT_SCHEMA_VERSION}");
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Environment variable holding the hex-encoded checkpoint public key when
+/// `--public-key-file` is not given.
+const CHECKPOINT_PUBLIC_KEY_ENV: &str = "CHECKPOINT_PUBLIC_KEY";
+
+/// Verifies the hash chain across one or more files, read in the given order,
+/// and fails with the first broken link. Given a key, it also fails when
+/// checkpoints are missing, overdue, or signed under another key id.
+fn run_verify_chain(args: &[String]) -> Result<ExitCode> {
+    let mut input_paths = Vec::new();
+    let mut key_id: Option<String> = None;
+    let mut public_key_file: Option<String> = None;
+    let mut checkpoint_interval: Option<u64> = None;
+
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" => {
+                i += 1;
+                input_paths.push(args.get(i).context("--input requires a path")?.clone());
+            }
+            "--key-id" => {
+                i += 1;
+                key_id = args.get(i).cloned();
+            }
+            "--public-key-file" => {
+                i += 1;
+                public_key_file = args.get(i).cloned();
+            }
+            "--checkpoint-interval" => {
+                i += 1;
+                let value = args
+                    .get(i)
+                    .context("--checkpoint-interval requires a value")?;
+                checkpoint_interval = Some(
+                    value
+                        .parse()
+                        .with_context(|| format!("invalid --checkpoint-interval '{value}'"))?,
+                );
+            }
+            unknown => {
+                return Err(anyhow!(
+                    "unknown argument '{unknown}'. expected: verify-chain --input <path> [--input <path> ...] [--key-id <id> [--public-key-file <path>] [--checkpoint-interval N]]"
+                ));
+            }
+        }
+        i += 1;
+    }
+
+    if input_paths.is_empty() {
+        return Err(anyhow!("missing required argument --input <path>"));
+    }
+    let verifier = match (key_id, public_key_file) {
+        (Some(key_id), public_key_file) => {
+            let key_hex = match public_key_file {
+                Some(path) => fs::read_to_string(&path)
+                    .with_context(|| format!("read public key file {path}"))?,
+                None => env::var(CHECKPOINT_PUBLIC_KEY_ENV).with_context(|| {
+                    format!("--key-id requires --public-key-file or {CHECKPOINT_PUBLIC_KEY_ENV}")
+                })?,
+            };
+            let key = hex::decode(key_hex.trim().trim_start_matches("0x"))
+                .context("invalid hex checkpoint public key")?;
+            let verifier = CheckpointVerifier::new(key_id, key);
+            Some(match checkpoint_interval {
+                Some(interval) => verifier.with_checkpoint_interval(interval),
+                None => verifier,
+            })
+        }
+        (None, None) if checkpoint_interval.is_some() => {
+            return Err(anyhow!("--checkpoint-interval requires --key-id"));
+        }
+        (None, None) => None,
+        (None, Some(_)) => return Err(anyhow!("--public-key-file requires --key-id")),
+    };
+
+    let mut events = Vec::new();
+    for path in &input_paths {
+        let bytes = fs::read(path).with_context(|| format!("read input file {path}"))?;
+        events.extend(decode_input_events(&bytes).with_context(|| format!("decode {path}"))?);
+    }
+    let report = verify_hash_chain(&events, verifier.as_ref());
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).context("encode verify report")?
+    );
+    match report.first_break {
+        Some(chain_break) => {
+            eprintln!("Error: {chain_break}");
+            Ok(ExitCode::from(EXIT_CHECK_FAILED))
+        }
+        None => Ok(ExitCode::SUCCESS),
+    }
+}
+
+/// Parsed `--name value` options and positional arguments.
+struct Flags {
+    values: Vec<(String, String)>,
+    positional: Vec<String>,
+}
+
+impl Flags {
+    /// Accepts the selection flags plus `extra`; `usage` names the command in
+    /// errors.
+    fn parse(args: &[String], extra: &[&str], usage: &str) -> Result<Self> {
+        let mut flags = Self {
+            values: Vec::new(),
+            positional: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                flags.positional.push(arg.clone());
+                continue;
+            }
+            if !SELECTION_FLAGS.contains(&arg.as_str()) && !extra.contains(&arg.as_str()) {
+                return Err(anyhow!(
+                    "unknown argument '{arg}'. expected: {usage} {SELECTION_USAGE}"
+                ));
+            }
+            let value = args
+                .next()
+                .with_context(|| format!("{arg} requires a value"))?;
+            flags.values.push((arg.clone(), value.clone()));
+        }
+        Ok(flags)
+    }
+
+    /// Last value given for `name`.
+    fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(flag, _)| flag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn value<T>(&self, name: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        self.get(name)
+            .map(|raw| raw.parse::<T>())
+            .transpose()
+            .with_context(|| format!("invalid {name} value"))
+    }
+}
+
+/// Event selection shared by the replaying commands.
+struct Selection {
+    chain_id: Option<u64>,
+    filter: Option<EventFilter>,
+    reorder_window: usize,
+}
+
+impl Selection {
+    fn from_flags(flags: &Flags) -> Result<Self> {
+        Ok(Self {
+            chain_id: flags.value::<u64>("--chain-id")?,
+            filter: flags
+                .get("--filter")
+                .map(EventFilter::parse)
+                .transpose()
+                .context("invalid --filter value")?,
+            reorder_window: flags
+                .value::<usize>("--reorder-window")?
+                .unwrap_or(DEFAULT_REORDER_WINDOW),
+        })
+    }
+
+    /// Mirrors `chain_events_after(events, chain_id, 0)` for one event.
+    fn matches(&self, event: &EventEnvelope) -> bool {
+        self.chain_id.is_none_or(|chain_id| {
+            event.chain_id == Some(chain_id)
+                && event
+                    .chain_seq_id
+                    .is_some_and(|chain_seq_id| chain_seq_id > 0)
+        }) && self
+            .filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(event))
+    }
+}
+
+enum InputSpec {
+    File(PathBuf),
+    Wal(PathBuf),
+}
+
+/// Resolves exactly one of the `file_flag` and `wal_flag` inputs.
+fn input_spec(flags: &Flags, file_flag: &str, wal_flag: &str) -> Result<InputSpec> {
+    match (flags.get(file_flag), flags.get(wal_flag)) {
+        (Some(path), None) => Ok(InputSpec::File(path.into())),
+        (None, Some(path)) => Ok(InputSpec::Wal(path.into())),
+        (Some(_), Some(_)) => Err(anyhow!("{file_flag} and {wal_flag} are mutually exclusive")),
+        (None, None) => Err(anyhow!(
+            "missing required argument {file_flag} <path> or {wal_flag} <path>"
+        )),
+    }
+}
+
+/// Input events that stop at the first read error, which
+/// [`EventSource::finish`] reports once the consumer is done.
+struct EventSource {
+    events: Box<dyn Iterator<Item = Result<EventEnvelope>>>,
+    error: Option<anyhow::Error>,
+}
+
+impl EventSource {
+    fn open(spec: &InputSpec) -> Result<Self> {
+        let events = match spec {
+            InputSpec::File(path) => read_input_events(path)?,
+            InputSpec::Wal(path) => {
+                let wal = open_wal(path)?;
+                let display = path.display().to_string();
+                Box::new(
+                    wal.iter_from(0)
+                        .with_context(|| format!("read WAL {display}"))?
+                        .map(move |event| event.with_context(|| format!("read WAL {display}"))),
+                )
+            }
+        };
+        Ok(Self {
+            events,
+            error: None,
+        })
+    }
+
+    /// Selected events in deterministic order.
+    fn select<'a>(
+        &'a mut self,
+        selection: &'a Selection,
+    ) -> DeterministicOrder<impl Iterator<Item = EventEnvelope> + 'a> {
+        DeterministicOrder::new(
+            self.filter(|event| selection.matches(event)),
+            selection.reorder_window,
+        )
+    }
+
+    fn finish(self) -> Result<()> {
+        self.error.map_or(Ok(()), Err)
+    }
+}
+
+impl Iterator for EventSource {
+    type Item = EventEnvelope;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() {
+            return None;
+        }
+        match self.events.next()? {
+            Ok(event) => Some(event),
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+}
+
+/// Opens a WAL by base path, or the single WAL whose segments fill a
+/// directory.
+fn open_wal(path: &Path) -> Result<StorageWal> {
+    if !path.is_dir() {
+        if !path.exists() && !path.parent().is_some_and(Path::is_dir) {
+            return Err(anyhow!("WAL path {} does not exist", path.display()));
+        }
+        return StorageWal::new(path).with_context(|| format!("open WAL {}", path.display()));
+    }
+    let mut bases = BTreeSet::new();
+    for entry in fs::read_dir(path).with_context(|| format!("read WAL dir {}", path.display()))? {
+        let entry = entry.with_context(|| format!("read WAL dir {}", path.display()))?;
+        if let Some((base, _)) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.split_once(".seg."))
+        {
+            bases.insert(base.to_owned());
+        }
+    }
+    let mut bases = bases.into_iter();
+    match (bases.next(), bases.next()) {
+        (Some(base), None) => StorageWal::new(path.join(&base))
+            .with_context(|| format!("open WAL {base} in {}", path.display())),
+        (None, _) => Err(anyhow!("no WAL segments in {}", path.display())),
+        (Some(_), Some(_)) => Err(anyhow!(
+            "several WALs in {}; pass the WAL base path instead",
+            path.display()
+        )),
+    }
+}
+
+/// Streams events from a JSON lines or binary frame log one record at a time.
+/// JSON array exports have no record boundaries and are decoded whole.
+fn read_input_events(path: &Path) -> Result<Box<dyn Iterator<Item = Result<EventEnvelope>>>> {
+    let display = path.display();
+    let mut reader =
+        BufReader::new(File::open(path).with_context(|| format!("read input file {display}"))?);
+    let first = loop {
+        let buf = reader
+            .fill_buf()
+            .with_context(|| format!("read input file {display}"))?;
+        match buf.iter().position(|byte| !byte.is_ascii_whitespace()) {
+            Some(offset) => break Some(buf[offset]),
+            None if buf.is_empty() => break None,
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    };
+    if first == Some(b'[') {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("read input file {display}"))?;
+        let events = decode_input_events(&bytes)?;
+        return Ok(Box::new(events.into_iter().map(Ok)));
+    }
+    let path = path.display().to_string();
+    Ok(Box::new(EventStreamReader::new(reader).map(move |event| {
+        event.with_context(|| format!("decode input file {path}"))
+    })))
+}
+
+/// Accepts a JSON array export, JSON lines, or binary frames such as a WAL segment.
+fn decode_input_events(bytes: &[u8]) -> Result<Vec<EventEnvelope>> {
+    if bytes.iter().find(|byte| !byte.is_ascii_whitespace()) == Some(&b'[') {
+        return decode_event_json_array(bytes).context("decode input event json");
+    }
+    decode_event_stream(bytes).context("decode input event stream")
+}
+
+/// Decoded fields and calldata by hash, from a `tx_full` Parquet export or a
+/// JSON array or JSON lines of `TxFullRecord`s.
+fn read_recorded_transactions(path: &str) -> Result<BTreeMap<TxHash, RecordedTransaction>> {
+    let bytes = fs::read(path).with_context(|| format!("read tx_full file {path}"))?;
+    let records = if bytes.starts_with(b"PAR1") {
+        read_parquet_table::<TxFullRecord>(path)
+            .with_context(|| format!("decode tx_full file {path}"))?
+    } else if bytes.iter().find(|byte| !byte.is_ascii_whitespace()) == Some(&b'[') {
+        serde_json::from_slice(&bytes).with_context(|| format!("decode tx_full file {path}"))?
+    } else {
+        bytes
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.trim_ascii().is_empty())
+            .map(serde_json::from_slice::<TxFullRecord>)
+            .collect::<Result<_, _>>()
+            .with_context(|| format!("decode tx_full file {path}"))?
+    };
+    Ok(records
+        .into_iter()
+        .map(|record: TxFullRecord| {
+            let decoded = TxDecoded {
+                hash: record.hash,
+                tx_type: record.tx_type,
+                sender: record.sender,
+                nonce: record.nonce,
+                chain_id: record.chain_id,
+                to: record.to,
+                value_wei: record.value_wei,
+                gas_limit: record.gas_limit,
+                gas_price_wei: record.gas_price_wei,
+                max_fee_per_gas_wei: record.max_fee_per_gas_wei,
+                max_priority_fee_per_gas_wei: record.max_priority_fee_per_gas_wei,
+                max_fee_per_blob_gas_wei: record.max_fee_per_blob_gas_wei,
+                calldata_len: record.calldata_len,
+            };
+            (
+                record.hash,
+                RecordedTransaction {
+                    decoded,
+                    calldata: record.raw_tx,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Checkpoint hashes as written by `checkpoints`, either format.
+fn read_expected_checkpoints(path: &str) -> Result<Vec<CheckpointHash>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read expected file {path}"))?;
+    if raw.trim_start().starts_with('[') {
+        return serde_json::from_str(&raw).with_context(|| format!("decode expected file {path}"));
+    }
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("seq_id"))
+        .map(|line| {
+            let fields = line.split(',').collect::<Vec<_>>();
+            let [seq_id, pending_count, checkpoint_hash] = fields.as_slice() else {
+                return Err(anyhow!(
+                    "expected seq_id,pending_count,checkpoint_hash: {line}"
+                ));
+            };
+            Ok(CheckpointHash {
+                seq_id: seq_id.parse().context("invalid seq_id")?,
+                pending_count: pending_count.parse().context("invalid pending_count")?,
+                checkpoint_hash: parse_hash(checkpoint_hash)?,
+            })
+        })
+        .collect::<Result<_>>()
+        .with_context(|| format!("decode expected file {path}"))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum OutputFormat {
+    Json,
+    Csv,
+    Parquet,
+    Ndjson,
+    Binary,
+}
+
+impl OutputFormat {
+    /// Parses `--format`, defaulting to the first of `allowed`.
+    fn parse(raw: Option<&str>, allowed: &[&str]) -> Result<Self> {
+        let raw = raw.unwrap_or(allowed[0]);
+        if !allowed.contains(&raw) {
+            return Err(anyhow!(
+                "unsupported --format value: {raw:?}. expected one of: {}",
+                allowed.join(", ")
+            ));
+        }
+        Ok(match raw {
+            "json" => Self::Json,
+            "csv" => Self::Csv,
+            "parquet" => Self::Parquet,
+            "ndjson" => Self::Ndjson,
+            _ => Self::Binary,
+        })
+    }
+}
+
+/// Writes to `--out`, or standard output when it is absent.
+fn output_writer(out: Option<&str>) -> Result<BufWriter<Box<dyn Write>>> {
+    let output: Box<dyn Write> = match out {
+        Some(path) => {
+            Box::new(File::create(path).with_context(|| format!("create output file {path}"))?)
+        }
+        None => Box::new(io::stdout().lock()),
+    };
+    Ok(BufWriter::new(output))
+}
+
+fn write_json(value: &impl Serialize, out: Option<&str>) -> Result<()> {
+    let mut output = output_writer(out)?;
+    serde_json::to_writer_pretty(&mut output, value).context("encode output")?;
+    output.write_all(b"\n").context("write output")?;
+    output.flush().context("write output")
+}
+
+/// Serializes rows into a JSON array as they are produced.
+fn write_json_array<T: Serialize>(
+    rows: impl IntoIterator<Item = T>,
+    out: Option<&str>,
+) -> Result<()> {
+    let mut output = output_writer(out)?;
+    serde_json::Serializer::pretty(&mut output)
+        .collect_seq(rows)
+        .context("encode output")?;
+    output.write_all(b"\n").context("write output")?;
+    output.flush().context("write output")
+}
+
+fn write_csv<T>(
+    rows: impl IntoIterator<Item = T>,
+    out: Option<&str>,
+    header: &str,
+    row: impl Fn(&T) -> String,
+) -> Result<()> {
+    let mut output = output_writer(out)?;
+    writeln!(output, "{header}").context("write output")?;
+    for value in rows {
+        writeln!(output, "{}", row(&value)).context("write output")?;
+    }
+    output.flush().context("write output")
+}
+
+fn write_parquet<T: ParquetTable>(
+    rows: impl IntoIterator<Item = T>,
+    out: Option<&str>,
+) -> Result<()> {
+    let path = out.context("--format parquet requires --out <path>")?;
+    let mut writer = ParquetTableWriter::<T>::create(path, &ParquetExportOptions::default())
+        .with_context(|| format!("create output file {path}"))?;
+    for row in rows {
+        writer.write(&row).context("write parquet row")?;
+    }
+    writer.finish().context("finish parquet file")?;
+    Ok(())
+}
+
+fn parse_seq_ids(raw: &str) -> Result<Vec<u64>> {
+    raw.split(',')
+        .map(|seq_id| seq_id.trim().parse::<u64>())
+        .collect::<Result<_, _>>()
+        .context("invalid --seq value")
+}
+
+fn parse_hash(raw: &str) -> Result<TxHash> {
+    let bytes = hex::decode(raw.trim().trim_start_matches("0x"))
+        .with_context(|| format!("invalid hex hash {raw}"))?;
+    TxHash::try_from(bytes.as_slice()).map_err(|_| anyhow!("hash {raw} is not 32 bytes"))
+}
+
+fn format_hash(hash: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(hash))
+}
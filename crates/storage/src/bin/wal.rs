@@ -0,0 +1,68 @@
+#![forbid(unsafe_code)]
+
+//! CLI for checking and repairing storage WAL segments.
+//!
+//! `wal verify --path <wal>` prints an integrity report and exits with status
+//! 1 when any record is damaged; `wal repair --path <wal>` rewrites damaged
+//! segments and moves the bad bytes into `<wal>.quarantine/`.
+
+use anyhow::{Context, Result, anyhow};
+use std::env;
+use std::process::ExitCode;
+use storage::StorageWal;
+
+fn main() -> Result<ExitCode> {
+    run_with_args(&env::args().skip(1).collect::<Vec<_>>())
+}
+
+fn run_with_args(args: &[String]) -> Result<ExitCode> {
+    let (command, rest) = args
+        .split_first()
+        .context("expected a subcommand: verify --path <wal> | repair --path <wal>")?;
+    let wal = StorageWal::new(parse_path(rest)?).context("open WAL")?;
+    match command.as_str() {
+        "verify" => {
+            let report = wal.verify().context("verify WAL")?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).context("encode verify report")?
+            );
+            Ok(if report.is_clean() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            })
+        }
+        "repair" => {
+            let report = wal.repair().context("repair WAL")?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).context("encode repair report")?
+            );
+            Ok(ExitCode::SUCCESS)
+        }
+        unknown => Err(anyhow!(
+            "unknown subcommand '{unknown}'. expected: verify --path <wal> | repair --path <wal>"
+        )),
+    }
+}
+
+fn parse_path(args: &[String]) -> Result<String> {
+    let mut path = None;
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--path" => {
+                i += 1;
+                path = args.get(i).cloned();
+            }
+            unknown => {
+                return Err(anyhow!(
+                    "unknown argument '{unknown}'. expected: --path <wal>"
+                ));
+            }
+        }
+        i += 1;
+    }
+    path.context("missing required argument --path <wal>")
+}
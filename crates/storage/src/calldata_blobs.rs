@@ -0,0 +1,284 @@
+//! Content-addressed file holding calldata offloaded from the `tx_full` table.
+//!
+//! Each blob is framed as its SHA-256 digest, a little-endian `u32` length and
+//! the bytes. Identical calldata is stored once and reference counted; once
+//! released blobs outweigh live ones (and pass a floor), the file is
+//! rewritten without them. The file is scratch space for one process: it is
+//! truncated on open, and checkpoints and the cold store keep calldata inline.
+
+use crate::{FastMap, Result, StorageError};
+use anyhow::{Context, anyhow};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// SHA-256 of a blob's bytes.
+pub type BlobDigest = [u8; 32];
+
+const FRAME_HEADER_LEN: u64 = 32 + 4;
+const AUTO_COMPACT_MIN_DEAD_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Size counters of a [`CalldataBlobStore`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CalldataBlobStats {
+    pub blobs: usize,
+    /// Framed bytes of blobs still referenced.
+    pub live_bytes: u64,
+    /// Current file size, including released blobs not yet compacted away.
+    pub file_bytes: u64,
+    pub compactions_total: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BlobSlot {
+    /// Offset of the blob bytes, just past the frame header.
+    offset: u64,
+    len: u32,
+    refs: u32,
+}
+
+#[derive(Debug)]
+struct BlobFile {
+    file: File,
+    slots: FastMap<BlobDigest, BlobSlot>,
+    stats: CalldataBlobStats,
+}
+
+/// Append-only, reference-counted calldata blobs keyed by content digest.
+#[derive(Debug)]
+pub struct CalldataBlobStore {
+    path: PathBuf,
+    inner: Mutex<BlobFile>,
+}
+
+impl CalldataBlobStore {
+    /// Creates (or truncates) the blob file at `path`, creating its parent
+    /// directory when missing.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create blob directory {}", parent.display()))
+                .map_err(StorageError::calldata_blob)?;
+        }
+        let file = open_blob_file(&path, true)?;
+        Ok(Self {
+            path,
+            inner: Mutex::new(BlobFile {
+                file,
+                slots: FastMap::default(),
+                stats: CalldataBlobStats::default(),
+            }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stores `bytes`, or takes another reference to an identical blob.
+    pub fn put(&self, bytes: &[u8]) -> Result<BlobDigest> {
+        let digest: BlobDigest = Sha256::digest(bytes).into();
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| StorageError::calldata_blob(anyhow!("blob of {} bytes", bytes.len())))?;
+        let mut inner = self.inner.lock();
+        if let Some(slot) = inner.slots.get_mut(&digest) {
+            slot.refs = slot.refs.saturating_add(1);
+            return Ok(digest);
+        }
+
+        let frame_start = inner.stats.file_bytes;
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN as usize + bytes.len());
+        frame.extend_from_slice(&digest);
+        frame.extend_from_slice(&len.to_le_bytes());
+        frame.extend_from_slice(bytes);
+        inner
+            .file
+            .seek(SeekFrom::Start(frame_start))
+            .and_then(|_| inner.file.write_all(&frame))
+            .context("append calldata blob")
+            .map_err(StorageError::calldata_blob)?;
+        inner.stats.file_bytes += frame.len() as u64;
+        inner.stats.live_bytes += frame.len() as u64;
+        inner.stats.blobs += 1;
+        inner.slots.insert(
+            digest,
+            BlobSlot {
+                offset: frame_start + FRAME_HEADER_LEN,
+                len,
+                refs: 1,
+            },
+        );
+        Ok(digest)
+    }
+
+    /// Reads a live blob back, or `None` when `digest` is not stored.
+    pub fn get(&self, digest: &BlobDigest) -> Result<Option<Vec<u8>>> {
+        let mut inner = self.inner.lock();
+        let Some(slot) = inner.slots.get(digest).copied() else {
+            return Ok(None);
+        };
+        let mut bytes = vec![0; slot.len as usize];
+        inner
+            .file
+            .seek(SeekFrom::Start(slot.offset))
+            .and_then(|_| inner.file.read_exact(&mut bytes))
+            .context("read calldata blob")
+            .map_err(StorageError::calldata_blob)?;
+        Ok(Some(bytes))
+    }
+
+    pub fn blob_len(&self, digest: &BlobDigest) -> Option<usize> {
+        self.inner
+            .lock()
+            .slots
+            .get(digest)
+            .map(|slot| slot.len as usize)
+    }
+
+    /// Drops one reference to `digest`, compacting the file when released
+    /// blobs now dominate it.
+    pub fn release(&self, digest: &BlobDigest) {
+        let mut inner = self.inner.lock();
+        let Some(slot) = inner.slots.get_mut(digest) else {
+            return;
+        };
+        slot.refs = slot.refs.saturating_sub(1);
+        if slot.refs > 0 {
+            return;
+        }
+        let len = u64::from(slot.len);
+        inner.slots.remove(digest);
+        inner.stats.blobs = inner.stats.blobs.saturating_sub(1);
+        inner.stats.live_bytes = inner
+            .stats
+            .live_bytes
+            .saturating_sub(FRAME_HEADER_LEN + len);
+        let dead_bytes = inner.stats.file_bytes - inner.stats.live_bytes;
+        if dead_bytes >= AUTO_COMPACT_MIN_DEAD_BYTES
+            && dead_bytes > inner.stats.live_bytes
+            && let Err(err) = self.compact_locked(&mut inner)
+        {
+            tracing::warn!(error = %err, path = %self.path.display(), "failed to compact calldata blobs");
+        }
+    }
+
+    /// Rewrites the file with only the live blobs.
+    pub fn compact(&self) -> Result<()> {
+        let mut inner = self.inner.lock();
+        self.compact_locked(&mut inner)
+    }
+
+    pub fn stats(&self) -> CalldataBlobStats {
+        self.inner.lock().stats
+    }
+
+    fn compact_locked(&self, inner: &mut BlobFile) -> Result<()> {
+        let staging = self.path.with_extension("compacting");
+        let mut slots = inner
+            .slots
+            .iter()
+            .map(|(digest, slot)| (*digest, *slot))
+            .collect::<Vec<_>>();
+        slots.sort_unstable_by_key(|(_, slot)| slot.offset);
+
+        let mut out = BufWriter::new(open_blob_file(&staging, true)?);
+        let mut relocated = FastMap::default();
+        let mut offset = 0;
+        let mut bytes = Vec::new();
+        for (digest, slot) in slots {
+            bytes.resize(slot.len as usize, 0);
+            inner
+                .file
+                .seek(SeekFrom::Start(slot.offset))
+                .and_then(|_| inner.file.read_exact(&mut bytes))
+                .and_then(|_| out.write_all(&digest))
+                .and_then(|_| out.write_all(&slot.len.to_le_bytes()))
+                .and_then(|_| out.write_all(&bytes))
+                .context("copy live calldata blob")
+                .map_err(StorageError::calldata_blob)?;
+            relocated.insert(
+                digest,
+                BlobSlot {
+                    offset: offset + FRAME_HEADER_LEN,
+                    ..slot
+                },
+            );
+            offset += FRAME_HEADER_LEN + u64::from(slot.len);
+        }
+        out.flush()
+            .context("flush compacted calldata blobs")
+            .map_err(StorageError::calldata_blob)?;
+        drop(out);
+        std::fs::rename(&staging, &self.path)
+            .context("replace calldata blob file")
+            .map_err(StorageError::calldata_blob)?;
+
+        inner.file = open_blob_file(&self.path, false)?;
+        inner.slots = relocated;
+        inner.stats.file_bytes = offset;
+        inner.stats.live_bytes = offset;
+        inner.stats.compactions_total += 1;
+        Ok(())
+    }
+}
+
+fn open_blob_file(path: &Path, truncate: bool) -> Result<File> {
+    File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(truncate)
+        .open(path)
+        .with_context(|| format!("open calldata blob file {}", path.display()))
+        .map_err(StorageError::calldata_blob)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_blob_path(label: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "mempulse-calldata-blobs-{label}-{}-{}.bin",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos())
+        ))
+    }
+
+    #[test]
+    fn identical_calldata_is_stored_once_and_survives_compaction() {
+        let path = temp_blob_path("dedup");
+        let store = CalldataBlobStore::create(&path).unwrap();
+        let swap = store.put(&[0xaa; 100]).unwrap();
+        assert_eq!(store.put(&[0xaa; 100]).unwrap(), swap);
+        let transfer = store.put(&[0xbb; 10]).unwrap();
+        assert_eq!(store.stats().blobs, 2);
+        assert_eq!(store.stats().file_bytes, 2 * FRAME_HEADER_LEN + 110);
+
+        store.release(&swap);
+        assert_eq!(store.blob_len(&swap), Some(100), "one reference remains");
+        store.release(&swap);
+        assert_eq!(store.get(&swap).unwrap(), None);
+        assert_eq!(store.stats().live_bytes, FRAME_HEADER_LEN + 10);
+
+        store.compact().unwrap();
+        let stats = store.stats();
+        assert_eq!(stats.file_bytes, FRAME_HEADER_LEN + 10);
+        assert_eq!(stats.compactions_total, 1);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), stats.file_bytes);
+        assert_eq!(store.get(&transfer).unwrap(), Some(vec![0xbb; 10]));
+        let again = store.put(&[0xcc; 4]).unwrap();
+        assert_eq!(store.get(&again).unwrap(), Some(vec![0xcc; 4]));
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,380 @@
+//! Checkpoints of `InMemoryStorage` projections used to compact the WAL.
+//!
+//! A checkpoint file opens with an 8-byte header (`MCKP`, format version,
+//! three reserved bytes) followed by CRC-framed entries, framed like WAL
+//! records: one metadata entry, one entry per retained event (binary
+//! encoding) or table row (JSON), and a trailer carrying the entry counts so
+//! a truncated file is rejected rather than half loaded.
+
+use crate::wal::WalHead;
+use crate::wal_record::{decode_record, frame_record, take_frame};
+use crate::{
+    BuilderLifecycleRecord, EventStore, InMemoryStorage, MarketStatsSnapshot, OpportunityRecord,
+    PeerStatsRecord, RecentTransactionRecord, Result, SimulationRecord, StorageError, TableRow,
+    TxFeaturesRecord, TxFullRecord, TxLifecycleRecord, TxSeenRecord, UserOpRecord, push_bounded,
+};
+use anyhow::{Context, anyhow};
+use common::TxHash;
+use event_log::{EventEncoding, EventEnvelope, cmp_deterministic};
+use scheduler::PersistedSchedulerSnapshot;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+const CHECKPOINT_MAGIC: [u8; 4] = *b"MCKP";
+const CHECKPOINT_FORMAT_VERSION: u8 = 1;
+const CHECKPOINT_HEADER_LEN: usize = 8;
+const TAG_META: u8 = b'M';
+const TAG_EVENT: u8 = b'E';
+const TAG_ROW: u8 = b'R';
+const TAG_END: u8 = b'Z';
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CheckpointMeta {
+    seq_id: u64,
+    created_unix_ms: i64,
+    head: WalHead,
+    market_stats: MarketStatsSnapshot,
+    latest_finalized_block_unix_ms: Option<i64>,
+    scheduler_snapshot: Option<PersistedSchedulerSnapshot>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+struct CheckpointEnd {
+    events: u64,
+    rows: u64,
+}
+
+/// One row of a bounded projection table, in table order.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "table", content = "row", rename_all = "snake_case")]
+enum CheckpointRow {
+    TxSeen(TxSeenRecord),
+    TxFull(TxFullRecord),
+    TxFeatures(TxFeaturesRecord),
+    Opportunity(OpportunityRecord),
+    BuilderLifecycle(BuilderLifecycleRecord),
+    Simulation(SimulationRecord),
+    UserOp(UserOpRecord),
+    TxLifecycle(TxLifecycleRecord),
+    PeerStats(PeerStatsRecord),
+    RecentTxOrder(TxHash),
+    RecentTx(RecentTransactionRecord),
+}
+
+/// Projections of an [`InMemoryStorage`] as of `seq_id`, together with the
+/// sequencer head needed to keep numbering after a restart.
+#[derive(Clone, Debug)]
+pub struct StorageCheckpoint {
+    /// Every event up to and including this `seq_id` is reflected.
+    pub seq_id: u64,
+    pub created_unix_ms: i64,
+    pub head: WalHead,
+    events: Vec<EventEnvelope>,
+    rows: Vec<CheckpointRow>,
+    market_stats: MarketStatsSnapshot,
+    latest_finalized_block_unix_ms: Option<i64>,
+    scheduler_snapshot: Option<PersistedSchedulerSnapshot>,
+}
+
+impl StorageCheckpoint {
+    /// Number of retained events captured by the checkpoint.
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Streams the checkpoint in its file format.
+    pub(crate) fn write_to(&self, out: &mut impl Write) -> Result<()> {
+        let mut header = [0u8; CHECKPOINT_HEADER_LEN];
+        header[..4].copy_from_slice(&CHECKPOINT_MAGIC);
+        header[4] = CHECKPOINT_FORMAT_VERSION;
+        out.write_all(&header)
+            .context("write checkpoint header")
+            .map_err(StorageError::wal_write)?;
+
+        let mut payload = Vec::with_capacity(512);
+        let mut frame = Vec::with_capacity(512);
+        let mut write_entry =
+            |tag: u8, encode: &mut dyn FnMut(&mut Vec<u8>) -> anyhow::Result<()>| -> Result<()> {
+                payload.clear();
+                payload.push(tag);
+                encode(&mut payload).map_err(StorageError::wal_write)?;
+                frame.clear();
+                frame_record(&payload, &mut frame);
+                out.write_all(&frame)
+                    .context("write checkpoint entry")
+                    .map_err(StorageError::wal_write)
+            };
+
+        let meta = CheckpointMeta {
+            seq_id: self.seq_id,
+            created_unix_ms: self.created_unix_ms,
+            head: self.head.clone(),
+            market_stats: self.market_stats,
+            latest_finalized_block_unix_ms: self.latest_finalized_block_unix_ms,
+            scheduler_snapshot: self.scheduler_snapshot.clone(),
+        };
+        write_entry(TAG_META, &mut |buf| {
+            serde_json::to_writer(buf, &meta).context("serialize checkpoint metadata")
+        })?;
+        for event in &self.events {
+            write_entry(TAG_EVENT, &mut |buf| {
+                EventEncoding::Binary
+                    .encode_into(event, buf)
+                    .context("serialize checkpoint event")
+            })?;
+        }
+        for row in &self.rows {
+            write_entry(TAG_ROW, &mut |buf| {
+                serde_json::to_writer(buf, row).context("serialize checkpoint row")
+            })?;
+        }
+        let end = CheckpointEnd {
+            events: self.events.len() as u64,
+            rows: self.rows.len() as u64,
+        };
+        write_entry(TAG_END, &mut |buf| {
+            serde_json::to_writer(buf, &end).context("serialize checkpoint trailer")
+        })
+    }
+
+    /// Decodes a checkpoint file, rejecting damaged or truncated ones.
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Self> {
+        let header = bytes
+            .get(..CHECKPOINT_HEADER_LEN)
+            .filter(|header| header[..4] == CHECKPOINT_MAGIC)
+            .ok_or_else(|| StorageError::wal_write(anyhow!("not a storage checkpoint")))?;
+        if header[4] != CHECKPOINT_FORMAT_VERSION {
+            return Err(StorageError::wal_write(anyhow!(
+                "unsupported checkpoint format version {}",
+                header[4]
+            )));
+        }
+
+        let mut offset = CHECKPOINT_HEADER_LEN;
+        let mut meta = None;
+        let mut end = None;
+        let mut events = Vec::new();
+        let mut rows = Vec::new();
+        while offset < bytes.len() {
+            let at = offset;
+            let entry = take_frame(bytes, &mut offset).ok_or_else(|| {
+                StorageError::wal_write(anyhow!("damaged checkpoint entry at byte {at}"))
+            })?;
+            let (tag, body) = entry
+                .split_first()
+                .ok_or_else(|| StorageError::wal_write(anyhow!("empty checkpoint entry")))?;
+            match *tag {
+                TAG_META => {
+                    meta = Some(
+                        serde_json::from_slice::<CheckpointMeta>(body)
+                            .context("decode checkpoint metadata")
+                            .map_err(StorageError::wal_write)?,
+                    );
+                }
+                TAG_EVENT => events.push(
+                    decode_record(EventEncoding::Binary, body)
+                        .context("decode checkpoint event")
+                        .map_err(StorageError::wal_write)?,
+                ),
+                TAG_ROW => rows.push(
+                    serde_json::from_slice::<CheckpointRow>(body)
+                        .context("decode checkpoint row")
+                        .map_err(StorageError::wal_write)?,
+                ),
+                TAG_END => {
+                    end = Some(
+                        serde_json::from_slice::<CheckpointEnd>(body)
+                            .context("decode checkpoint trailer")
+                            .map_err(StorageError::wal_write)?,
+                    );
+                    break;
+                }
+                other => {
+                    return Err(StorageError::wal_write(anyhow!(
+                        "unknown checkpoint entry tag {other:#04x}"
+                    )));
+                }
+            }
+        }
+
+        let (Some(meta), Some(end)) = (meta, end) else {
+            return Err(StorageError::wal_write(anyhow!("truncated checkpoint")));
+        };
+        if end.events != events.len() as u64 || end.rows != rows.len() as u64 {
+            return Err(StorageError::wal_write(anyhow!(
+                "checkpoint trailer expects {} events and {} rows, found {} and {}",
+                end.events,
+                end.rows,
+                events.len(),
+                rows.len()
+            )));
+        }
+        Ok(Self {
+            seq_id: meta.seq_id,
+            created_unix_ms: meta.created_unix_ms,
+            head: meta.head,
+            events,
+            rows,
+            market_stats: meta.market_stats,
+            latest_finalized_block_unix_ms: meta.latest_finalized_block_unix_ms,
+            scheduler_snapshot: meta.scheduler_snapshot,
+        })
+    }
+}
+
+impl InMemoryStorage {
+    /// Captures the projections as of the latest sequenced event.
+    ///
+    /// `head` is the writer's sequencer position; the checkpoint covers
+    /// whichever of it and the newest retained event is later.
+    pub fn checkpoint(&self, head: WalHead, created_unix_ms: i64) -> StorageCheckpoint {
+        let rows = self
+            .tx_seen
+            .iter()
+            .cloned()
+            .map(CheckpointRow::TxSeen)
+            .chain(
+                self.tx_full
+                    .iter()
+                    .map(|row| CheckpointRow::TxFull(self.with_inline_calldata(row))),
+            )
+            .chain(
+                self.tx_features
+                    .iter()
+                    .cloned()
+                    .map(CheckpointRow::TxFeatures),
+            )
+            .chain(
+                self.opportunities
+                    .iter()
+                    .cloned()
+                    .map(CheckpointRow::Opportunity),
+            )
+            .chain(
+                self.builder_lifecycle
+                    .iter()
+                    .cloned()
+                    .map(CheckpointRow::BuilderLifecycle),
+            )
+            .chain(
+                self.simulations
+                    .iter()
+                    .cloned()
+                    .map(CheckpointRow::Simulation),
+            )
+            .chain(self.user_ops.iter().cloned().map(CheckpointRow::UserOp))
+            .chain(
+                self.tx_lifecycle
+                    .iter()
+                    .cloned()
+                    .map(CheckpointRow::TxLifecycle),
+            )
+            .chain(
+                self.peer_stats
+                    .iter()
+                    .cloned()
+                    .map(CheckpointRow::PeerStats),
+            )
+            .chain(
+                self.recent_tx_order
+                    .iter()
+                    .copied()
+                    .map(CheckpointRow::RecentTxOrder),
+            )
+            .chain(
+                self.recent_tx_lookup
+                    .values()
+                    .cloned()
+                    .map(CheckpointRow::RecentTx),
+            )
+            .collect();
+        StorageCheckpoint {
+            seq_id: head
+                .latest_seq_id
+                .max(self.latest_seq_id())
+                .unwrap_or_default(),
+            created_unix_ms,
+            head,
+            events: self.events.iter().cloned().collect(),
+            rows,
+            market_stats: self.market_stats,
+            latest_finalized_block_unix_ms: self.latest_finalized_block_unix_ms,
+            scheduler_snapshot: self.scheduler_snapshot.clone(),
+        }
+    }
+
+    /// Replaces every projection with the checkpointed one.
+    ///
+    /// Rows are loaded directly rather than re-derived through
+    /// [`EventStore::append_event`]; lookup maps and indexes are rebuilt
+    /// from them, peer observations from the retained events, and the
+    /// capacities and memory budget of this storage still apply.
+    pub fn restore_checkpoint(&mut self, checkpoint: StorageCheckpoint) {
+        let revision = self.read_model_revision;
+        let cold = self.cold.take();
+        let calldata_blobs = self.calldata_blobs.take();
+        if let Some(blobs) = calldata_blobs.as_deref() {
+            for digest in self.calldata_refs.values() {
+                blobs.release(digest);
+            }
+        }
+        *self = Self::with_config(self.config.clone());
+        self.cold = cold;
+        self.calldata_blobs = calldata_blobs;
+
+        for event in checkpoint.events {
+            self.events.push_back(event);
+        }
+        while self.events.len() > self.config.event_capacity {
+            self.events.pop_front();
+        }
+        self.event_index = self.events.iter().cloned().collect();
+        self.event_index.sort_by(cmp_deterministic);
+        for event in self.events.clone() {
+            self.index_event(&event);
+            self.charge_event_row(&event);
+            self.peer_observations.observe(&event);
+        }
+
+        for row in checkpoint.rows {
+            match row {
+                CheckpointRow::TxSeen(record) => self.push_row(TableRow::TxSeen(record)),
+                CheckpointRow::TxFull(record) => self.push_row(TableRow::TxFull(record)),
+                CheckpointRow::TxFeatures(record) => self.push_row(TableRow::TxFeatures(record)),
+                CheckpointRow::Opportunity(record) => self.push_row(TableRow::Opportunity(record)),
+                CheckpointRow::BuilderLifecycle(record) => {
+                    self.push_row(TableRow::BuilderLifecycle(record));
+                }
+                CheckpointRow::Simulation(record) => self.push_row(TableRow::Simulation(record)),
+                CheckpointRow::UserOp(record) => self.push_row(TableRow::UserOp(record)),
+                CheckpointRow::TxLifecycle(record) => {
+                    self.push_row(TableRow::TxLifecycle(record));
+                }
+                CheckpointRow::PeerStats(record) => self.push_row(TableRow::PeerStats(record)),
+                CheckpointRow::RecentTxOrder(hash) => {
+                    push_bounded(
+                        &mut self.recent_tx_order,
+                        hash,
+                        self.config.recent_tx_capacity,
+                    );
+                }
+                CheckpointRow::RecentTx(record) => {
+                    self.recent_tx_lookup.insert(record.hash, record);
+                }
+            }
+        }
+        for hash in &self.recent_tx_order {
+            *self.recent_tx_counts.entry(*hash).or_insert(0) += 1;
+        }
+        let recent_tx_counts = &self.recent_tx_counts;
+        self.recent_tx_lookup
+            .retain(|hash, _| recent_tx_counts.contains_key(hash));
+
+        self.market_stats = checkpoint.market_stats;
+        self.latest_finalized_block_unix_ms = checkpoint.latest_finalized_block_unix_ms;
+        self.scheduler_snapshot = checkpoint.scheduler_snapshot;
+        // Readers cache on the revision, so it must move past anything they
+        // saw before the restore.
+        self.read_model_revision = revision.saturating_add(1);
+    }
+}
@@ -0,0 +1,667 @@
+//! Typed ClickHouse tables and the row encoders that feed them.
+//!
+//! Each typed table is declared once as a [`TypedTable`]; the same column
+//! list renders the `CREATE TABLE` statement and drives the `RowBinary`
+//! encoder, so the DDL and the insert bodies cannot drift apart. The append-only
+//! `events` table keeps its `payload_json` column and is written as
+//! `JSONEachRow`.
+
+use crate::{Result, StorageError};
+use anyhow::anyhow;
+use common::TxHash;
+use event_log::{EventEnvelope, EventPayload};
+use serde::Serialize;
+
+pub(crate) const DATABASE: &str = "mev_v2";
+
+/// Column types used by the typed tables.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ChType {
+    UInt8,
+    UInt32,
+    UInt64,
+    Int64,
+    String,
+    LowCardinality,
+    FixedString(usize),
+    UInt128,
+    Nullable(&'static ChType),
+    Array(&'static ChType),
+}
+
+const HASH: ChType = ChType::FixedString(32);
+const ADDRESS: ChType = ChType::FixedString(20);
+const NULLABLE_HASH: ChType = ChType::Nullable(&HASH);
+const NULLABLE_ADDRESS: ChType = ChType::Nullable(&ADDRESS);
+const NULLABLE_U32: ChType = ChType::Nullable(&ChType::UInt32);
+const NULLABLE_U64: ChType = ChType::Nullable(&ChType::UInt64);
+const NULLABLE_I64: ChType = ChType::Nullable(&ChType::Int64);
+const NULLABLE_U128: ChType = ChType::Nullable(&ChType::UInt128);
+const NULLABLE_STRING: ChType = ChType::Nullable(&ChType::String);
+const NULLABLE_LABEL: ChType = ChType::Nullable(&ChType::LowCardinality);
+const HASH_ARRAY: ChType = ChType::Array(&HASH);
+const STRING_ARRAY: ChType = ChType::Array(&ChType::String);
+const LABEL_ARRAY: ChType = ChType::Array(&ChType::LowCardinality);
+
+impl ChType {
+    pub(crate) fn sql(self) -> String {
+        match self {
+            Self::UInt8 => "UInt8".to_owned(),
+            Self::UInt32 => "UInt32".to_owned(),
+            Self::UInt64 => "UInt64".to_owned(),
+            Self::Int64 => "Int64".to_owned(),
+            Self::String => "String".to_owned(),
+            Self::LowCardinality => "LowCardinality(String)".to_owned(),
+            Self::FixedString(width) => format!("FixedString({width})"),
+            Self::UInt128 => "UInt128".to_owned(),
+            Self::Nullable(ChType::LowCardinality) => "LowCardinality(Nullable(String))".to_owned(),
+            Self::Nullable(inner) => format!("Nullable({})", inner.sql()),
+            Self::Array(inner) => format!("Array({})", inner.sql()),
+        }
+    }
+}
+
+/// A typed table fed from one or more event payload types.
+#[derive(Debug)]
+pub(crate) struct TypedTable {
+    pub(crate) name: &'static str,
+    pub(crate) columns: &'static [(&'static str, ChType)],
+    pub(crate) order_by: &'static str,
+    pub(crate) indexes: &'static [&'static str],
+}
+
+impl TypedTable {
+    fn insert_query(&self) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "INSERT INTO {DATABASE}.{} ({columns}) FORMAT RowBinary",
+            self.name
+        )
+    }
+}
+
+/// Columns every typed table starts with.
+macro_rules! typed_columns {
+    ($($name:literal => $ty:expr),* $(,)?) => {
+        &[
+            ("seq_id", ChType::UInt64),
+            ("ingest_ts_unix_ms", ChType::Int64),
+            ("chain_id", ChType::UInt64),
+            ("source_id", ChType::LowCardinality),
+            $(($name, $ty)),*
+        ]
+    };
+}
+
+pub(crate) const TX_DECODED: TypedTable = TypedTable {
+    name: "tx_decoded",
+    columns: typed_columns!(
+        "hash" => HASH,
+        "sender" => ADDRESS,
+        "nonce" => ChType::UInt64,
+        "tx_type" => ChType::UInt8,
+        "to" => NULLABLE_ADDRESS,
+        "value_wei" => NULLABLE_U128,
+        "gas_limit" => NULLABLE_U64,
+        "gas_price_wei" => NULLABLE_U128,
+        "max_fee_per_gas_wei" => NULLABLE_U128,
+        "max_priority_fee_per_gas_wei" => NULLABLE_U128,
+        "max_fee_per_blob_gas_wei" => NULLABLE_U128,
+        "calldata_len" => NULLABLE_U32,
+    ),
+    order_by: "chain_id, sender, ingest_ts_unix_ms",
+    indexes: &["INDEX hash_bloom hash TYPE bloom_filter GRANULARITY 4"],
+};
+
+pub(crate) const CANDIDATES: TypedTable = TypedTable {
+    name: "candidates",
+    columns: typed_columns!(
+        "tx_hash" => HASH,
+        "candidate_id" => ChType::String,
+        "member_tx_hashes" => HASH_ARRAY,
+        "strategy" => ChType::LowCardinality,
+        "score" => ChType::UInt32,
+        "protocol" => ChType::LowCardinality,
+        "category" => ChType::LowCardinality,
+        "feature_engine_version" => ChType::LowCardinality,
+        "scorer_version" => ChType::LowCardinality,
+        "strategy_version" => ChType::LowCardinality,
+        "reasons" => LABEL_ARRAY,
+        "detected_unix_ms" => ChType::Int64,
+    ),
+    order_by: "chain_id, tx_hash, ingest_ts_unix_ms",
+    indexes: &[],
+};
+
+pub(crate) const SIM_COMPLETED: TypedTable = TypedTable {
+    name: "sim_completed",
+    columns: typed_columns!(
+        "hash" => HASH,
+        "sim_id" => ChType::String,
+        "status" => ChType::LowCardinality,
+        "fail_category" => NULLABLE_LABEL,
+        "latency_ms" => NULLABLE_U64,
+        "tx_count" => NULLABLE_U32,
+        "l1_data_fee_wei" => NULLABLE_U128,
+        "gas_cost_wei" => NULLABLE_U128,
+        "feature_engine_version" => ChType::LowCardinality,
+        "scorer_version" => ChType::LowCardinality,
+        "strategy_version" => ChType::LowCardinality,
+    ),
+    order_by: "chain_id, hash, ingest_ts_unix_ms",
+    indexes: &[],
+};
+
+pub(crate) const ASSEMBLY_DECISIONS: TypedTable = TypedTable {
+    name: "assembly_decisions",
+    columns: typed_columns!(
+        "tx_hash" => HASH,
+        "candidate_id" => ChType::String,
+        "decision" => ChType::LowCardinality,
+        "replaced_candidate_ids" => STRING_ARRAY,
+        "reason" => NULLABLE_STRING,
+        "block_number" => ChType::UInt64,
+    ),
+    order_by: "chain_id, tx_hash, ingest_ts_unix_ms",
+    indexes: &[],
+};
+
+/// One row per transaction lifecycle transition. `block_hash` holds the
+/// confirming block (or the orphaned block of a reorg) and `related_hash`
+/// the replacing transaction (or the new block of a reorg).
+pub(crate) const LIFECYCLE: TypedTable = TypedTable {
+    name: "lifecycle",
+    columns: typed_columns!(
+        "hash" => HASH,
+        "event_type" => ChType::LowCardinality,
+        "observed_unix_ms" => NULLABLE_I64,
+        "peer_id" => NULLABLE_LABEL,
+        "sender" => NULLABLE_ADDRESS,
+        "nonce" => NULLABLE_U64,
+        "expected_nonce" => NULLABLE_U64,
+        "block_number" => NULLABLE_U64,
+        "block_hash" => NULLABLE_HASH,
+        "related_hash" => NULLABLE_HASH,
+        "drop_reason" => NULLABLE_LABEL,
+        "detail" => NULLABLE_STRING,
+    ),
+    order_by: "chain_id, hash, ingest_ts_unix_ms",
+    indexes: &[],
+};
+
+pub(crate) const TYPED_TABLES: [&TypedTable; 5] = [
+    &TX_DECODED,
+    &CANDIDATES,
+    &SIM_COMPLETED,
+    &ASSEMBLY_DECISIONS,
+    &LIFECYCLE,
+];
+
+/// One HTTP insert: the query to run and the body to post with it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClickHouseInsert {
+    pub table: &'static str,
+    pub query: String,
+    pub body: Vec<u8>,
+    pub rows: usize,
+    /// `insert_deduplication_token` for this insert; the same batch always
+    /// yields the same token, so a retried or drained batch is not inserted
+    /// twice.
+    pub dedup_token: String,
+}
+
+/// Dedup token for the rows a batch of events contributes to `table`.
+pub(crate) fn batch_dedup_token(table: &str, events: &[EventEnvelope]) -> String {
+    let first = events.iter().map(|event| event.seq_id).min().unwrap_or(0);
+    let last = events.iter().map(|event| event.seq_id).max().unwrap_or(0);
+    format!("{table}-{first}-{last}-{}", events.len())
+}
+
+/// Splits a batch of events into ClickHouse inserts: every event goes to
+/// `events` as `JSONEachRow`, and events with a typed table also go to that
+/// table as `RowBinary`. Tables without rows in the batch are left out.
+pub fn clickhouse_typed_inserts(events: &[EventEnvelope]) -> Result<Vec<ClickHouseInsert>> {
+    let mut inserts = vec![ClickHouseInsert {
+        table: "events",
+        query: format!("INSERT INTO {DATABASE}.events FORMAT JSONEachRow"),
+        body: Vec::new(),
+        rows: 0,
+        dedup_token: batch_dedup_token("events", events),
+    }];
+    inserts.extend(TYPED_TABLES.iter().map(|table| ClickHouseInsert {
+        table: table.name,
+        query: table.insert_query(),
+        body: Vec::new(),
+        rows: 0,
+        dedup_token: batch_dedup_token(table.name, events),
+    }));
+    for event in events {
+        encode_event_row(event, &mut inserts[0].body)?;
+        inserts[0].rows += 1;
+        let Some((table_index, values)) = typed_row(event) else {
+            continue;
+        };
+        let table = TYPED_TABLES[table_index];
+        let insert = &mut inserts[table_index + 1];
+        write_row_binary(table, &values, &mut insert.body)?;
+        insert.rows += 1;
+    }
+    inserts.retain(|insert| insert.rows > 0);
+    Ok(inserts)
+}
+
+/// Row of the append-only `events` table.
+#[derive(Serialize)]
+struct EventRow<'a> {
+    seq_id: u64,
+    ingest_ts_unix_ms: i64,
+    ingest_ts_mono_ns: u64,
+    source_id: &'a str,
+    schema_version: u32,
+    event_type: &'static str,
+    chain_id: Option<u64>,
+    payload_json: String,
+}
+
+fn encode_event_row(event: &EventEnvelope, out: &mut Vec<u8>) -> Result<()> {
+    let encode_error =
+        |err: serde_json::Error| StorageError::clickhouse_batch(anyhow!("serialize event: {err}"));
+    let row = EventRow {
+        seq_id: event.seq_id,
+        ingest_ts_unix_ms: event.ingest_ts_unix_ms,
+        ingest_ts_mono_ns: event.ingest_ts_mono_ns,
+        source_id: &event.source_id.0,
+        schema_version: event_log::CURRENT_EVENT_SCHEMA_VERSION,
+        event_type: event.payload.type_name(),
+        chain_id: event.chain_id,
+        payload_json: serde_json::to_string(&event.payload).map_err(encode_error)?,
+    };
+    serde_json::to_writer(&mut *out, &row).map_err(encode_error)?;
+    out.push(b'\n');
+    Ok(())
+}
+
+/// A value for one typed column.
+#[derive(Debug)]
+enum ChValue<'a> {
+    Null,
+    UInt8(u8),
+    UInt32(u32),
+    UInt64(u64),
+    Int64(i64),
+    Str(&'a str),
+    Bytes(&'a [u8]),
+    UInt128(u128),
+    Array(Vec<ChValue<'a>>),
+}
+
+macro_rules! ch_value_from {
+    ($($ty:ty => $variant:ident),+ $(,)?) => {
+        $(impl<'a> From<$ty> for ChValue<'a> {
+            fn from(value: $ty) -> Self {
+                Self::$variant(value)
+            }
+        })+
+    };
+}
+
+ch_value_from!(
+    u8 => UInt8,
+    u32 => UInt32,
+    u64 => UInt64,
+    i64 => Int64,
+    &'a str => Str,
+    u128 => UInt128,
+);
+
+impl<'a> From<&'a String> for ChValue<'a> {
+    fn from(value: &'a String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl<'a, const N: usize> From<&'a [u8; N]> for ChValue<'a> {
+    fn from(value: &'a [u8; N]) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl<'a, T> From<&'a Vec<T>> for ChValue<'a>
+where
+    &'a T: Into<ChValue<'a>>,
+{
+    fn from(values: &'a Vec<T>) -> Self {
+        Self::Array(values.iter().map(Into::into).collect())
+    }
+}
+
+impl<'a, T: Into<ChValue<'a>>> From<Option<T>> for ChValue<'a> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+/// Builds the typed row for `event`, returning the index of its table in
+/// [`TYPED_TABLES`]; payloads without a typed table return `None`.
+fn typed_row(event: &EventEnvelope) -> Option<(usize, Vec<ChValue<'_>>)> {
+    let payload_chain_id = match &event.payload {
+        EventPayload::TxDecoded(decoded) => decoded.chain_id,
+        EventPayload::CandidateQueued(candidate) => candidate.chain_id,
+        _ => None,
+    };
+    let mut row = vec![
+        ChValue::UInt64(event.seq_id),
+        ChValue::Int64(event.ingest_ts_unix_ms),
+        // Sort keys cannot be nullable; chain 0 stands for "unknown".
+        ChValue::UInt64(event.chain_id.or(payload_chain_id).unwrap_or(0)),
+        ChValue::Str(&event.source_id.0),
+    ];
+    let table = match &event.payload {
+        EventPayload::TxDecoded(e) => {
+            row.extend([
+                (&e.hash).into(),
+                (&e.sender).into(),
+                e.nonce.into(),
+                e.tx_type.into(),
+                e.to.as_ref().into(),
+                e.value_wei.into(),
+                e.gas_limit.into(),
+                e.gas_price_wei.into(),
+                e.max_fee_per_gas_wei.into(),
+                e.max_priority_fee_per_gas_wei.into(),
+                e.max_fee_per_blob_gas_wei.into(),
+                e.calldata_len.into(),
+            ]);
+            0
+        }
+        EventPayload::CandidateQueued(e) => {
+            row.extend([
+                (&e.tx_hash).into(),
+                (&e.candidate_id).into(),
+                (&e.member_tx_hashes).into(),
+                (&e.strategy).into(),
+                e.score.into(),
+                (&e.protocol).into(),
+                (&e.category).into(),
+                (&e.feature_engine_version).into(),
+                (&e.scorer_version).into(),
+                (&e.strategy_version).into(),
+                (&e.reasons).into(),
+                e.detected_unix_ms.into(),
+            ]);
+            1
+        }
+        EventPayload::SimCompleted(e) => {
+            row.extend([
+                (&e.hash).into(),
+                (&e.sim_id).into(),
+                e.status.as_str().into(),
+                e.fail_category
+                    .as_ref()
+                    .map(|category| category.as_str())
+                    .into(),
+                e.latency_ms.into(),
+                e.tx_count.into(),
+                e.l1_data_fee_wei.into(),
+                e.gas_cost_wei.into(),
+                (&e.feature_engine_version).into(),
+                (&e.scorer_version).into(),
+                (&e.strategy_version).into(),
+            ]);
+            2
+        }
+        EventPayload::AssemblyDecisionApplied(e) => {
+            row.extend([
+                (&e.tx_hash).into(),
+                (&e.candidate_id).into(),
+                e.decision.as_str().into(),
+                (&e.replaced_candidate_ids).into(),
+                e.reason.as_ref().into(),
+                e.block_number.into(),
+            ]);
+            3
+        }
+        payload => {
+            row.extend(lifecycle_columns(payload)?);
+            4
+        }
+    };
+    Some((table, row))
+}
+
+/// Lifecycle columns after the shared prefix, or `None` for payloads that
+/// are not transaction lifecycle transitions.
+fn lifecycle_columns(payload: &EventPayload) -> Option<[ChValue<'_>; 12]> {
+    let mut observed_unix_ms = None;
+    let mut peer_id = None;
+    let mut sender = None;
+    let mut nonce = None;
+    let mut expected_nonce = None;
+    let mut block_number = None;
+    let mut block_hash = None;
+    let mut related_hash = None;
+    let mut drop_reason = None;
+    let mut detail = None;
+    let hash: &TxHash = match payload {
+        EventPayload::TxSeen(e) => {
+            observed_unix_ms = Some(e.seen_at_unix_ms);
+            peer_id = Some(e.peer_id.as_str());
+            &e.hash
+        }
+        EventPayload::TxFetched(e) => {
+            observed_unix_ms = Some(e.fetched_at_unix_ms);
+            &e.hash
+        }
+        EventPayload::TxReady(e) => {
+            sender = Some(&e.sender);
+            nonce = Some(e.nonce);
+            &e.hash
+        }
+        EventPayload::TxBlocked(e) => {
+            sender = Some(&e.sender);
+            nonce = Some(e.nonce);
+            expected_nonce = e.expected_nonce;
+            &e.hash
+        }
+        EventPayload::TxReplaced(e) => {
+            related_hash = Some(&e.replaced_by);
+            &e.hash
+        }
+        EventPayload::TxDropped(e) => {
+            drop_reason = Some(e.reason.as_str());
+            detail = e.detail.as_ref();
+            &e.hash
+        }
+        EventPayload::TxConfirmedProvisional(e) | EventPayload::TxConfirmedFinal(e) => {
+            block_number = Some(e.block_number);
+            block_hash = Some(&e.block_hash);
+            &e.hash
+        }
+        EventPayload::TxReorged(e) => {
+            block_hash = Some(&e.old_block_hash);
+            related_hash = Some(&e.new_block_hash);
+            &e.hash
+        }
+        _ => return None,
+    };
+    Some([
+        hash.into(),
+        payload.type_name().into(),
+        observed_unix_ms.into(),
+        peer_id.into(),
+        sender.into(),
+        nonce.into(),
+        expected_nonce.into(),
+        block_number.into(),
+        block_hash.into(),
+        related_hash.into(),
+        drop_reason.into(),
+        detail.into(),
+    ])
+}
+
+fn write_row_binary(table: &TypedTable, values: &[ChValue<'_>], out: &mut Vec<u8>) -> Result<()> {
+    if values.len() != table.columns.len() {
+        return Err(StorageError::clickhouse_batch(anyhow!(
+            "{} row has {} values for {} columns",
+            table.name,
+            values.len(),
+            table.columns.len()
+        )));
+    }
+    for ((name, ty), value) in table.columns.iter().zip(values) {
+        write_value(*ty, value, out).map_err(|err| {
+            StorageError::clickhouse_batch(anyhow!("{}.{name}: {err}", table.name))
+        })?;
+    }
+    Ok(())
+}
+
+fn write_value(
+    ty: ChType,
+    value: &ChValue<'_>,
+    out: &mut Vec<u8>,
+) -> std::result::Result<(), String> {
+    match (ty, value) {
+        (ChType::Nullable(_), ChValue::Null) => out.push(1),
+        (ChType::Nullable(inner), value) => {
+            out.push(0);
+            write_value(*inner, value, out)?;
+        }
+        (ChType::UInt8, ChValue::UInt8(v)) => out.push(*v),
+        (ChType::UInt32, ChValue::UInt32(v)) => out.extend_from_slice(&v.to_le_bytes()),
+        (ChType::UInt64, ChValue::UInt64(v)) => out.extend_from_slice(&v.to_le_bytes()),
+        (ChType::Int64, ChValue::Int64(v)) => out.extend_from_slice(&v.to_le_bytes()),
+        (ChType::UInt128, ChValue::UInt128(v)) => out.extend_from_slice(&v.to_le_bytes()),
+        (ChType::String | ChType::LowCardinality, ChValue::Str(v)) => {
+            write_var_uint(v.len() as u64, out);
+            out.extend_from_slice(v.as_bytes());
+        }
+        (ChType::FixedString(width), ChValue::Bytes(v)) if v.len() == width => {
+            out.extend_from_slice(v);
+        }
+        (ChType::Array(inner), ChValue::Array(values)) => {
+            write_var_uint(values.len() as u64, out);
+            for value in values {
+                write_value(*inner, value, out)?;
+            }
+        }
+        (ty, value) => return Err(format!("{value:?} does not fit {}", ty.sql())),
+    }
+    Ok(())
+}
+
+/// LEB128 length prefix used by `RowBinary` strings and arrays.
+fn write_var_uint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::SourceId;
+    use event_log::{TxConfirmed, TxDecoded};
+
+    fn envelope(seq_id: u64, payload: EventPayload) -> EventEnvelope {
+        EventEnvelope {
+            seq_id,
+            ingest_ts_unix_ms: 1_700_000_000_000,
+            ingest_ts_mono_ns: seq_id,
+            source_id: SourceId::new("ch"),
+            payload,
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        }
+    }
+
+    #[test]
+    fn row_binary_encodes_nullable_fixed_strings_and_var_uint_lengths() {
+        let event = envelope(
+            7,
+            EventPayload::TxConfirmedFinal(TxConfirmed {
+                hash: [1; 32],
+                block_number: 300,
+                block_hash: [2; 32],
+            }),
+        );
+        let inserts = clickhouse_typed_inserts(&[event]).expect("encode");
+        assert_eq!(
+            inserts
+                .iter()
+                .map(|insert| insert.table)
+                .collect::<Vec<_>>(),
+            vec!["events", "lifecycle"]
+        );
+        let body = &inserts[1].body;
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&7_u64.to_le_bytes());
+        expected.extend_from_slice(&1_700_000_000_000_i64.to_le_bytes());
+        expected.extend_from_slice(&0_u64.to_le_bytes());
+        expected.extend_from_slice(&[2, b'c', b'h']);
+        expected.extend_from_slice(&[1; 32]);
+        expected.push(16);
+        expected.extend_from_slice(b"TxConfirmedFinal");
+        expected.extend_from_slice(&[1, 1, 1, 1, 1]);
+        expected.push(0);
+        expected.extend_from_slice(&300_u64.to_le_bytes());
+        expected.push(0);
+        expected.extend_from_slice(&[2; 32]);
+        expected.extend_from_slice(&[1, 1, 1]);
+        assert_eq!(body, &expected);
+    }
+
+    #[test]
+    fn typed_rows_take_chain_id_from_the_payload_when_the_envelope_has_none() {
+        let event = envelope(
+            1,
+            EventPayload::TxDecoded(TxDecoded {
+                hash: [3; 32],
+                tx_type: 2,
+                sender: [4; 20],
+                nonce: 9,
+                chain_id: Some(10),
+                to: None,
+                value_wei: Some(u128::MAX),
+                gas_limit: None,
+                gas_price_wei: None,
+                max_fee_per_gas_wei: None,
+                max_priority_fee_per_gas_wei: None,
+                max_fee_per_blob_gas_wei: None,
+                calldata_len: None,
+            }),
+        );
+        let inserts = clickhouse_typed_inserts(&[event]).expect("encode");
+        let decoded = inserts
+            .iter()
+            .find(|insert| insert.table == "tx_decoded")
+            .expect("tx_decoded insert");
+        assert!(decoded.query.starts_with(
+            "INSERT INTO mev_v2.tx_decoded (seq_id, ingest_ts_unix_ms, chain_id, source_id, hash"
+        ));
+        assert_eq!(&decoded.body[16..24], &10_u64.to_le_bytes());
+        let events_row: serde_json::Value =
+            serde_json::from_slice(&inserts[0].body).expect("events row json");
+        assert_eq!(events_row["event_type"], "TxDecoded");
+        assert!(
+            events_row["payload_json"]
+                .as_str()
+                .is_some_and(|payload| payload.contains("340282366920938463463374607431768211455"))
+        );
+    }
+
+    #[test]
+    fn column_types_render_low_cardinality_nullable_strings() {
+        assert_eq!(NULLABLE_LABEL.sql(), "LowCardinality(Nullable(String))");
+        assert_eq!(HASH_ARRAY.sql(), "Array(FixedString(32))");
+    }
+}
@@ -1,5 +1,10 @@
 //! ClickHouse DDL helpers for the event storage schema.
+//!
+//! The schema is applied as an ordered list of [`ClickHouseMigration`]s whose
+//! versions are recorded in `mev_v2.schema_migrations`, so a server only runs
+//! the migrations it has not seen yet.
 
+use crate::clickhouse_rows::{DATABASE, TYPED_TABLES, TypedTable};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -14,22 +19,135 @@ impl Default for ClickHouseSchemaConfig {
     }
 }
 
-/// Returns the full ClickHouse DDL required by this crate.
-pub fn clickhouse_schema_ddl(config: ClickHouseSchemaConfig) -> Vec<String> {
+/// One versioned step of the ClickHouse schema.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClickHouseMigration {
+    pub version: u32,
+    pub name: &'static str,
+    pub statements: Vec<String>,
+}
+
+impl ClickHouseMigration {
+    /// Returns the statement that records this migration as applied.
+    pub fn record_statement(&self) -> String {
+        format!(
+            "INSERT INTO {DATABASE}.schema_migrations (version, name) VALUES ({}, '{}')",
+            self.version, self.name
+        )
+    }
+}
+
+/// Schema version reached once every migration has been applied.
+pub const CLICKHOUSE_SCHEMA_VERSION: u32 = 4;
+
+/// Recent insert blocks whose dedup tokens a non-replicated table remembers,
+/// which bounds how late a spilled batch can be drained without duplicates.
+const DEDUP_WINDOW_BLOCKS: u32 = 10_000;
+
+/// Sentinel the inclusion-latency aggregates use for "not observed yet".
+const UNSEEN_MS: &str = "9223372036854775807";
+
+/// Returns the statements that create the database and the migration ledger;
+/// they run before any migration and are safe to repeat.
+pub fn clickhouse_migration_bootstrap_ddl() -> Vec<String> {
     vec![
-        "CREATE DATABASE IF NOT EXISTS mev_v2".to_owned(),
-        clickhouse_event_table_ddl(config),
+        format!("CREATE DATABASE IF NOT EXISTS {DATABASE}"),
+        format!(
+            "CREATE TABLE IF NOT EXISTS {DATABASE}.schema_migrations (
+    version UInt32,
+    name String,
+    applied_at DateTime DEFAULT now()
+) ENGINE = ReplacingMergeTree
+ORDER BY version"
+        ),
     ]
 }
 
+/// Returns every schema migration in version order.
+pub fn clickhouse_migrations(config: ClickHouseSchemaConfig) -> Vec<ClickHouseMigration> {
+    let mut typed_tables = TYPED_TABLES
+        .iter()
+        .map(|table| clickhouse_typed_table_ddl(table, config))
+        .collect::<Vec<_>>();
+    typed_tables.extend([
+        format!(
+            "ALTER TABLE {DATABASE}.events ADD COLUMN IF NOT EXISTS event_type LowCardinality(String) DEFAULT '' AFTER schema_version"
+        ),
+        format!(
+            "ALTER TABLE {DATABASE}.events ADD COLUMN IF NOT EXISTS chain_id Nullable(UInt64) AFTER event_type"
+        ),
+    ]);
+    vec![
+        ClickHouseMigration {
+            version: 1,
+            name: "events_table",
+            statements: vec![
+                clickhouse_event_table_ddl(config),
+                format!(
+                    "ALTER TABLE {DATABASE}.events ADD COLUMN IF NOT EXISTS schema_version UInt32 DEFAULT 1 AFTER source_id"
+                ),
+            ],
+        },
+        ClickHouseMigration {
+            version: 2,
+            name: "typed_event_tables",
+            statements: typed_tables,
+        },
+        ClickHouseMigration {
+            version: 3,
+            name: "analytics_views",
+            statements: analytics_view_ddl(),
+        },
+        ClickHouseMigration {
+            version: 4,
+            name: "insert_deduplication",
+            statements: ["events", "inclusion_latency", "opportunity_funnel"]
+                .into_iter()
+                .chain(TYPED_TABLES.iter().map(|table| table.name))
+                .map(|table| {
+                    format!(
+                        "ALTER TABLE {DATABASE}.{table} MODIFY SETTING non_replicated_deduplication_window = {DEDUP_WINDOW_BLOCKS}"
+                    )
+                })
+                .collect(),
+        },
+    ]
+}
+
+/// Returns the migrations newer than `applied_version`.
+pub fn clickhouse_pending_migrations(
+    config: ClickHouseSchemaConfig,
+    applied_version: u32,
+) -> Vec<ClickHouseMigration> {
+    clickhouse_migrations(config)
+        .into_iter()
+        .filter(|migration| migration.version > applied_version)
+        .collect()
+}
+
+/// Returns the full ClickHouse DDL required by this crate.
+///
+/// Tables created before a column existed are migrated in place, so the
+/// statements are safe to re-run against an existing database.
+pub fn clickhouse_schema_ddl(config: ClickHouseSchemaConfig) -> Vec<String> {
+    let mut ddl = clickhouse_migration_bootstrap_ddl();
+    ddl.extend(
+        clickhouse_migrations(config)
+            .into_iter()
+            .flat_map(|migration| migration.statements),
+    );
+    ddl
+}
+
 /// Returns the ClickHouse DDL for the append-only events table.
 pub fn clickhouse_event_table_ddl(config: ClickHouseSchemaConfig) -> String {
     format!(
-        "CREATE TABLE IF NOT EXISTS mev_v2.events (
+        "CREATE TABLE IF NOT EXISTS {DATABASE}.events (
     seq_id UInt64,
     ingest_ts_unix_ms Int64,
     ingest_ts_mono_ns UInt64,
     source_id String,
+    schema_version UInt32 DEFAULT 1,
     payload_json String
 ) ENGINE = MergeTree
 PARTITION BY toYYYYMM(toDateTime(ingest_ts_unix_ms / 1000))
@@ -39,9 +157,127 @@ TTL toDateTime(ingest_ts_unix_ms / 1000) + INTERVAL {} DAY",
     )
 }
 
+fn clickhouse_typed_table_ddl(table: &TypedTable, config: ClickHouseSchemaConfig) -> String {
+    let columns = table
+        .columns
+        .iter()
+        .map(|(name, ty)| format!("    {name} {}", ty.sql()))
+        .chain(table.indexes.iter().map(|index| format!("    {index}")))
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!(
+        "CREATE TABLE IF NOT EXISTS {DATABASE}.{} (
+{columns}
+) ENGINE = MergeTree
+PARTITION BY toYYYYMM(toDateTime(ingest_ts_unix_ms / 1000))
+ORDER BY ({})
+TTL toDateTime(ingest_ts_unix_ms / 1000) + INTERVAL {} DAY",
+        table.name,
+        table.order_by,
+        config.retention_days.max(1),
+    )
+}
+
+/// Inclusion latency (first seen to first confirmation per transaction) and
+/// the daily candidate -> simulation -> assembly funnel.
+fn analytics_view_ddl() -> Vec<String> {
+    let day = "toDate(toDateTime(intDiv(ingest_ts_unix_ms, 1000)))";
+    vec![
+        format!(
+            "CREATE TABLE IF NOT EXISTS {DATABASE}.inclusion_latency (
+    chain_id UInt64,
+    hash FixedString(32),
+    first_seen_unix_ms SimpleAggregateFunction(min, Int64),
+    included_unix_ms SimpleAggregateFunction(min, Int64),
+    included_block_number SimpleAggregateFunction(min, UInt64)
+) ENGINE = AggregatingMergeTree
+ORDER BY (chain_id, hash)"
+        ),
+        format!(
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS {DATABASE}.inclusion_latency_mv
+TO {DATABASE}.inclusion_latency AS
+SELECT
+    chain_id,
+    hash,
+    min(if(event_type = 'TxSeen', coalesce(observed_unix_ms, ingest_ts_unix_ms), {UNSEEN_MS})) AS first_seen_unix_ms,
+    min(if(event_type = 'TxSeen', {UNSEEN_MS}, ingest_ts_unix_ms)) AS included_unix_ms,
+    min(coalesce(block_number, 18446744073709551615)) AS included_block_number
+FROM {DATABASE}.lifecycle
+WHERE event_type IN ('TxSeen', 'TxConfirmedProvisional', 'TxConfirmedFinal')
+GROUP BY chain_id, hash"
+        ),
+        format!(
+            "CREATE VIEW IF NOT EXISTS {DATABASE}.tx_inclusion_latency AS
+SELECT
+    chain_id,
+    hash,
+    first_seen_unix_ms,
+    included_unix_ms,
+    included_block_number,
+    included_unix_ms - first_seen_unix_ms AS latency_ms
+FROM (
+    SELECT
+        chain_id,
+        hash,
+        min(first_seen_unix_ms) AS first_seen_unix_ms,
+        min(included_unix_ms) AS included_unix_ms,
+        min(included_block_number) AS included_block_number
+    FROM {DATABASE}.inclusion_latency
+    GROUP BY chain_id, hash
+)
+WHERE first_seen_unix_ms < {UNSEEN_MS} AND included_unix_ms < {UNSEEN_MS}"
+        ),
+        format!(
+            "CREATE TABLE IF NOT EXISTS {DATABASE}.opportunity_funnel (
+    chain_id UInt64,
+    day Date,
+    candidates UInt64,
+    simulated UInt64,
+    sim_ok UInt64,
+    assembly_inserted UInt64,
+    assembly_rejected UInt64,
+    assembly_replaced UInt64
+) ENGINE = SummingMergeTree
+ORDER BY (chain_id, day)"
+        ),
+        format!(
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS {DATABASE}.opportunity_funnel_candidates_mv
+TO {DATABASE}.opportunity_funnel AS
+SELECT chain_id, {day} AS day, count() AS candidates,
+    toUInt64(0) AS simulated, toUInt64(0) AS sim_ok,
+    toUInt64(0) AS assembly_inserted, toUInt64(0) AS assembly_rejected, toUInt64(0) AS assembly_replaced
+FROM {DATABASE}.candidates
+GROUP BY chain_id, day"
+        ),
+        format!(
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS {DATABASE}.opportunity_funnel_sims_mv
+TO {DATABASE}.opportunity_funnel AS
+SELECT chain_id, {day} AS day, toUInt64(0) AS candidates,
+    count() AS simulated, countIf(status = 'ok') AS sim_ok,
+    toUInt64(0) AS assembly_inserted, toUInt64(0) AS assembly_rejected, toUInt64(0) AS assembly_replaced
+FROM {DATABASE}.sim_completed
+GROUP BY chain_id, day"
+        ),
+        format!(
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS {DATABASE}.opportunity_funnel_assembly_mv
+TO {DATABASE}.opportunity_funnel AS
+SELECT chain_id, {day} AS day, toUInt64(0) AS candidates,
+    toUInt64(0) AS simulated, toUInt64(0) AS sim_ok,
+    countIf(decision = 'inserted') AS assembly_inserted,
+    countIf(decision = 'rejected') AS assembly_rejected,
+    countIf(decision = 'replaced') AS assembly_replaced
+FROM {DATABASE}.assembly_decisions
+GROUP BY chain_id, day"
+        ),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ClickHouseSchemaConfig, clickhouse_event_table_ddl, clickhouse_schema_ddl};
+    use super::{
+        CLICKHOUSE_SCHEMA_VERSION, ClickHouseSchemaConfig, clickhouse_event_table_ddl,
+        clickhouse_migrations, clickhouse_pending_migrations, clickhouse_schema_ddl,
+    };
 
     #[test]
     fn schema_ddl_contains_retention_interval() {
@@ -52,8 +288,68 @@ mod tests {
     #[test]
     fn schema_ddl_emits_database_and_table_statements() {
         let ddl = clickhouse_schema_ddl(ClickHouseSchemaConfig::default());
-        assert_eq!(ddl.len(), 2);
         assert!(ddl[0].contains("CREATE DATABASE IF NOT EXISTS"));
-        assert!(ddl[1].contains("CREATE TABLE IF NOT EXISTS"));
+        assert!(ddl[1].contains("CREATE TABLE IF NOT EXISTS mev_v2.schema_migrations"));
+        assert!(ddl[2].contains("CREATE TABLE IF NOT EXISTS mev_v2.events"));
+        assert!(ddl[3].starts_with(
+            "ALTER TABLE mev_v2.events ADD COLUMN IF NOT EXISTS schema_version UInt32 DEFAULT 1"
+        ));
+        for table in [
+            "tx_decoded",
+            "candidates",
+            "sim_completed",
+            "assembly_decisions",
+            "lifecycle",
+        ] {
+            let create = format!("CREATE TABLE IF NOT EXISTS mev_v2.{table} (");
+            assert!(ddl.iter().any(|statement| statement.starts_with(&create)));
+        }
+    }
+
+    #[test]
+    fn typed_tables_sort_by_chain_then_hash_or_sender_then_time() {
+        let ddl = clickhouse_schema_ddl(ClickHouseSchemaConfig::default());
+        let decoded = ddl
+            .iter()
+            .find(|statement| statement.contains("mev_v2.tx_decoded ("))
+            .expect("tx_decoded ddl");
+        assert!(decoded.contains("ORDER BY (chain_id, sender, ingest_ts_unix_ms)"));
+        assert!(decoded.contains("source_id LowCardinality(String)"));
+        assert!(decoded.contains("value_wei Nullable(UInt128)"));
+        let lifecycle = ddl
+            .iter()
+            .find(|statement| statement.contains("mev_v2.lifecycle ("))
+            .expect("lifecycle ddl");
+        assert!(lifecycle.contains("ORDER BY (chain_id, hash, ingest_ts_unix_ms)"));
+        assert!(lifecycle.contains("drop_reason LowCardinality(Nullable(String))"));
+    }
+
+    #[test]
+    fn migrations_are_versioned_in_order_and_filter_applied_versions() {
+        let migrations = clickhouse_migrations(ClickHouseSchemaConfig::default());
+        let versions = migrations
+            .iter()
+            .map(|migration| migration.version)
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec![1, 2, 3, 4]);
+        assert_eq!(versions.last(), Some(&CLICKHOUSE_SCHEMA_VERSION));
+        assert_eq!(
+            migrations[2].record_statement(),
+            "INSERT INTO mev_v2.schema_migrations (version, name) VALUES (3, 'analytics_views')"
+        );
+
+        let pending = clickhouse_pending_migrations(ClickHouseSchemaConfig::default(), 1);
+        assert_eq!(
+            pending
+                .iter()
+                .map(|migration| migration.name)
+                .collect::<Vec<_>>(),
+            vec![
+                "typed_event_tables",
+                "analytics_views",
+                "insert_deduplication"
+            ]
+        );
+        assert!(clickhouse_pending_migrations(ClickHouseSchemaConfig::default(), 4).is_empty());
     }
 }
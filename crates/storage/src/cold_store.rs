@@ -0,0 +1,181 @@
+//! Durable tier behind the bounded in-memory read model.
+//!
+//! `InMemoryStorage` keeps only the newest `event_capacity` events and
+//! `table_capacity` rows per table. With a [`ColdEventStore`] attached, every
+//! event and per-hash projection it accepts is also queued for the cold
+//! store and committed as one [`ColdWriteBatch`] per writer flush, and reads
+//! that fall outside the in-memory window are answered from there instead of
+//! coming back empty. Shared readers go through the `RwLock<InMemoryStorage>`
+//! impls, which release the storage lock before touching the cold store.
+
+use crate::{Result, TxFeaturesRecord, TxFullRecord, TxLifecycleRecord, TxSeenRecord};
+use common::TxHash;
+use event_log::EventEnvelope;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Per-hash projection row written through to a cold store.
+#[derive(Clone, Copy, Debug)]
+pub enum TxRecordRef<'a> {
+    Seen(&'a TxSeenRecord),
+    Full(&'a TxFullRecord),
+    Features(&'a TxFeaturesRecord),
+    Lifecycle(&'a TxLifecycleRecord),
+}
+
+impl TxRecordRef<'_> {
+    pub fn hash(&self) -> TxHash {
+        match self {
+            Self::Seen(record) => record.hash,
+            Self::Full(record) => record.hash,
+            Self::Features(record) => record.hash,
+            Self::Lifecycle(record) => record.hash,
+        }
+    }
+}
+
+/// Owned per-hash projection row waiting in a [`ColdWriteBatch`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TxRecord {
+    Seen(TxSeenRecord),
+    Full(TxFullRecord),
+    Features(TxFeaturesRecord),
+    Lifecycle(TxLifecycleRecord),
+}
+
+impl TxRecord {
+    pub fn borrowed(&self) -> TxRecordRef<'_> {
+        match self {
+            Self::Seen(record) => TxRecordRef::Seen(record),
+            Self::Full(record) => TxRecordRef::Full(record),
+            Self::Features(record) => TxRecordRef::Features(record),
+            Self::Lifecycle(record) => TxRecordRef::Lifecycle(record),
+        }
+    }
+}
+
+/// Cold writes accepted since the last commit, in arrival order.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ColdWriteBatch {
+    pub events: Vec<EventEnvelope>,
+    pub records: Vec<TxRecord>,
+}
+
+impl ColdWriteBatch {
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.records.is_empty()
+    }
+
+    /// Moves the writes of `later` after those already in this batch.
+    pub fn append(&mut self, mut later: Self) {
+        self.events.append(&mut later.events);
+        self.records.append(&mut later.records);
+    }
+}
+
+/// Latest projections known for one transaction hash.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TransactionLookup {
+    pub seen: Option<TxSeenRecord>,
+    pub full: Option<TxFullRecord>,
+    pub features: Option<TxFeaturesRecord>,
+    pub lifecycle: Option<TxLifecycleRecord>,
+}
+
+impl TransactionLookup {
+    /// Returns whether no projection is known for the hash.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_none()
+            && self.full.is_none()
+            && self.features.is_none()
+            && self.lifecycle.is_none()
+    }
+
+    /// Fills the projections missing from `self` with those of `other`.
+    pub(crate) fn or(self, other: Self) -> Self {
+        Self {
+            seen: self.seen.or(other.seen),
+            full: self.full.or(other.full),
+            features: self.features.or(other.features),
+            lifecycle: self.lifecycle.or(other.lifecycle),
+        }
+    }
+}
+
+/// Durable, unbounded store of events and per-hash projections.
+///
+/// Writes go through `&self` so one store can sit behind a shared
+/// `InMemoryStorage` hot cache; appending an event whose `seq_id` is already
+/// stored replaces it, which keeps WAL replay idempotent.
+pub trait ColdEventStore: Debug + Send + Sync {
+    fn append_event(&self, event: &EventEnvelope) -> Result<()>;
+    /// Returns up to `limit` events with `seq_id` strictly after `from_seq_id`.
+    fn scan_events(&self, from_seq_id: u64, limit: usize) -> Result<Vec<EventEnvelope>>;
+    fn latest_seq_id(&self) -> Result<Option<u64>>;
+    /// Returns one chain's events ordered by `chain_seq_id`, strictly after the cursor.
+    fn scan_chain_events(
+        &self,
+        chain_id: u64,
+        from_chain_seq_id: u64,
+        limit: usize,
+    ) -> Result<Vec<EventEnvelope>>;
+    fn latest_chain_seq_id(&self, chain_id: u64) -> Result<Option<u64>>;
+    fn upsert_tx_record(&self, record: TxRecordRef<'_>) -> Result<()>;
+    /// Writes a whole batch; stores that support it commit it as one
+    /// transaction. Records are applied in order, so the last row per hash
+    /// wins.
+    fn write_batch(&self, batch: &ColdWriteBatch) -> Result<()> {
+        for event in &batch.events {
+            self.append_event(event)?;
+        }
+        for record in &batch.records {
+            self.upsert_tx_record(record.borrowed())?;
+        }
+        Ok(())
+    }
+    fn transaction_lookup(&self, hash: &TxHash) -> Result<TransactionLookup>;
+    /// Makes every write so far durable; the storage writer calls this
+    /// before it lets the WAL forget a flushed batch.
+    fn sync(&self) -> Result<()>;
+}
+
+/// Cold-store read over a cloned store handle, given the in-memory answer.
+type ColdReadFn<T> = Box<dyn FnOnce(&T) -> Result<T>>;
+
+/// An answer from the in-memory window, plus the cold-store read that
+/// replaces it when the answer may lie outside that window.
+///
+/// Taking the answer needs the storage lock; [`ColdRead::finish`] does not,
+/// so shared readers run it after releasing the lock.
+#[must_use]
+pub(crate) struct ColdRead<T> {
+    hot: T,
+    cold: Option<ColdReadFn<T>>,
+}
+
+impl<T: 'static> ColdRead<T> {
+    pub(crate) fn new(
+        hot: T,
+        cold: Option<Arc<dyn ColdEventStore>>,
+        read: impl FnOnce(&dyn ColdEventStore, &T) -> Result<T> + 'static,
+    ) -> Self {
+        let cold =
+            cold.map(|cold| -> ColdReadFn<T> { Box::new(move |hot| read(cold.as_ref(), hot)) });
+        Self { hot, cold }
+    }
+
+    /// Returns the cold-store answer when one is needed, falling back to the
+    /// in-memory answer if the cold store cannot be read.
+    pub(crate) fn finish(self) -> T {
+        let Some(read) = self.cold else {
+            return self.hot;
+        };
+        match read(&self.hot) {
+            Ok(answer) => answer,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to read from cold store");
+                self.hot
+            }
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! Embedded on-disk [`ColdEventStore`] and [`EventStore`] backed by redb.
+//!
+//! Events are stored in their binary wire encoding keyed by `seq_id`, with a
+//! `(chain_id, chain_seq_id)` index beside them. The latest `tx_seen`,
+//! `tx_full`, `tx_features` and `tx_lifecycle` projections are stored as JSON
+//! keyed by transaction hash. Appends commit without an fsync, since the WAL
+//! still holds those events; [`ColdEventStore::sync`] makes them durable.
+//! The storage writer hands over each flush as one [`ColdWriteBatch`], which
+//! commits in a single transaction.
+
+use crate::cold_store::{ColdEventStore, ColdWriteBatch, TransactionLookup, TxRecordRef};
+use crate::{EventStore, Result, StorageError};
+use anyhow::anyhow;
+use common::TxHash;
+use event_log::{EventEnvelope, decode_event, encode_event, upcast_binary_event};
+use redb::{
+    Database, Durability, ReadOnlyTable, ReadableTable, ReadableTableMetadata, TableDefinition,
+    WriteTransaction,
+};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const EVENTS: TableDefinition<u64, &[u8]> = TableDefinition::new("events");
+const CHAIN_EVENTS: TableDefinition<(u64, u64), u64> = TableDefinition::new("chain_events");
+const TX_SEEN: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tx_seen");
+const TX_FULL: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tx_full");
+const TX_FEATURES: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tx_features");
+const TX_LIFECYCLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tx_lifecycle");
+/// Events read per transaction by `list_events`.
+const LIST_EVENTS_PAGE: usize = 4_096;
+
+/// Disk-backed event store; clones share one open database.
+#[derive(Clone)]
+pub struct DiskEventStore {
+    db: Arc<Database>,
+    path: PathBuf,
+}
+
+impl fmt::Debug for DiskEventStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiskEventStore")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DiskEventStore {
+    /// Opens the database file at `path`, creating it and its parent
+    /// directory when missing.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent).map_err(StorageError::disk_store)?;
+        }
+        let db = Database::create(&path).map_err(StorageError::disk_store)?;
+        let store = Self {
+            db: Arc::new(db),
+            path,
+        };
+        // Readers open tables without creating them, so create every table up front.
+        store.write(Durability::Immediate, |txn| {
+            txn.open_table(EVENTS).map_err(StorageError::disk_store)?;
+            txn.open_table(CHAIN_EVENTS)
+                .map_err(StorageError::disk_store)?;
+            for table in [TX_SEEN, TX_FULL, TX_FEATURES, TX_LIFECYCLE] {
+                txn.open_table(table).map_err(StorageError::disk_store)?;
+            }
+            Ok(())
+        })?;
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `events` in one transaction; an event whose `seq_id` is
+    /// already stored is replaced.
+    pub fn append_events(&self, events: &[EventEnvelope]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.write(Durability::None, |txn| insert_events(txn, events))
+    }
+
+    /// Returns the number of stored events.
+    pub fn len(&self) -> Result<u64> {
+        let txn = self.db.begin_read().map_err(StorageError::disk_store)?;
+        let table = txn.open_table(EVENTS).map_err(StorageError::disk_store)?;
+        table.len().map_err(StorageError::disk_store)
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    fn write(
+        &self,
+        durability: Durability,
+        apply: impl FnOnce(&WriteTransaction) -> Result<()>,
+    ) -> Result<()> {
+        let mut txn = self.db.begin_write().map_err(StorageError::disk_store)?;
+        txn.set_durability(durability);
+        apply(&txn)?;
+        txn.commit().map_err(StorageError::disk_store)
+    }
+
+    fn event_by_seq_id(
+        table: &ReadOnlyTable<u64, &'static [u8]>,
+        seq_id: u64,
+    ) -> Result<Option<EventEnvelope>> {
+        table
+            .get(seq_id)
+            .map_err(StorageError::disk_store)?
+            .map(|bytes| decode_stored_event(bytes.value()))
+            .transpose()
+    }
+
+    fn record<T: DeserializeOwned>(
+        &self,
+        table: TableDefinition<&[u8], &[u8]>,
+        hash: &TxHash,
+    ) -> Result<Option<T>> {
+        let txn = self.db.begin_read().map_err(StorageError::disk_store)?;
+        let table = txn.open_table(table).map_err(StorageError::disk_store)?;
+        table
+            .get(hash.as_slice())
+            .map_err(StorageError::disk_store)?
+            .map(|bytes| serde_json::from_slice(bytes.value()).map_err(StorageError::disk_store))
+            .transpose()
+    }
+}
+
+fn insert_events(txn: &WriteTransaction, events: &[EventEnvelope]) -> Result<()> {
+    let mut table = txn.open_table(EVENTS).map_err(StorageError::disk_store)?;
+    let mut chain_table = txn
+        .open_table(CHAIN_EVENTS)
+        .map_err(StorageError::disk_store)?;
+    for event in events {
+        table
+            .insert(event.seq_id, encode_event(event).as_slice())
+            .map_err(StorageError::disk_store)?;
+        if let (Some(chain_id), Some(chain_seq_id)) = (event.chain_id, event.chain_seq_id) {
+            chain_table
+                .insert((chain_id, chain_seq_id), event.seq_id)
+                .map_err(StorageError::disk_store)?;
+        }
+    }
+    Ok(())
+}
+
+fn insert_tx_record(txn: &WriteTransaction, record: TxRecordRef<'_>) -> Result<()> {
+    let (table, json) = match record {
+        TxRecordRef::Seen(record) => (TX_SEEN, serde_json::to_vec(record)),
+        TxRecordRef::Full(record) => (TX_FULL, serde_json::to_vec(record)),
+        TxRecordRef::Features(record) => (TX_FEATURES, serde_json::to_vec(record)),
+        TxRecordRef::Lifecycle(record) => (TX_LIFECYCLE, serde_json::to_vec(record)),
+    };
+    let json = json.map_err(StorageError::disk_store)?;
+    txn.open_table(table)
+        .map_err(StorageError::disk_store)?
+        .insert(record.hash().as_slice(), json.as_slice())
+        .map_err(StorageError::disk_store)?;
+    Ok(())
+}
+
+/// Decodes a stored event, upcasting records written by older versions like
+/// every other binary reader.
+fn decode_stored_event(bytes: &[u8]) -> Result<EventEnvelope> {
+    let (mut event, _) = decode_event(bytes)
+        .map_err(|err| StorageError::disk_store(anyhow!("decode stored event: {err}")))?;
+    upcast_binary_event(&mut event);
+    Ok(event)
+}
+
+impl ColdEventStore for DiskEventStore {
+    fn append_event(&self, event: &EventEnvelope) -> Result<()> {
+        self.append_events(std::slice::from_ref(event))
+    }
+
+    fn scan_events(&self, from_seq_id: u64, limit: usize) -> Result<Vec<EventEnvelope>> {
+        let Some(start) = from_seq_id.checked_add(1) else {
+            return Ok(Vec::new());
+        };
+        let txn = self.db.begin_read().map_err(StorageError::disk_store)?;
+        let table = txn.open_table(EVENTS).map_err(StorageError::disk_store)?;
+        table
+            .range(start..)
+            .map_err(StorageError::disk_store)?
+            .take(limit.max(1))
+            .map(|entry| {
+                let (_, bytes) = entry.map_err(StorageError::disk_store)?;
+                decode_stored_event(bytes.value())
+            })
+            .collect()
+    }
+
+    fn latest_seq_id(&self) -> Result<Option<u64>> {
+        let txn = self.db.begin_read().map_err(StorageError::disk_store)?;
+        let table = txn.open_table(EVENTS).map_err(StorageError::disk_store)?;
+        Ok(table
+            .last()
+            .map_err(StorageError::disk_store)?
+            .map(|(seq_id, _)| seq_id.value()))
+    }
+
+    fn scan_chain_events(
+        &self,
+        chain_id: u64,
+        from_chain_seq_id: u64,
+        limit: usize,
+    ) -> Result<Vec<EventEnvelope>> {
+        let Some(start) = from_chain_seq_id.checked_add(1) else {
+            return Ok(Vec::new());
+        };
+        let txn = self.db.begin_read().map_err(StorageError::disk_store)?;
+        let chain_table = txn
+            .open_table(CHAIN_EVENTS)
+            .map_err(StorageError::disk_store)?;
+        let table = txn.open_table(EVENTS).map_err(StorageError::disk_store)?;
+        let mut out = Vec::new();
+        for entry in chain_table
+            .range((chain_id, start)..=(chain_id, u64::MAX))
+            .map_err(StorageError::disk_store)?
+        {
+            if out.len() == limit.max(1) {
+                break;
+            }
+            let (_, seq_id) = entry.map_err(StorageError::disk_store)?;
+            out.extend(Self::event_by_seq_id(&table, seq_id.value())?);
+        }
+        Ok(out)
+    }
+
+    fn latest_chain_seq_id(&self, chain_id: u64) -> Result<Option<u64>> {
+        let txn = self.db.begin_read().map_err(StorageError::disk_store)?;
+        let table = txn
+            .open_table(CHAIN_EVENTS)
+            .map_err(StorageError::disk_store)?;
+        let last = table
+            .range((chain_id, 0)..=(chain_id, u64::MAX))
+            .map_err(StorageError::disk_store)?
+            .next_back()
+            .transpose()
+            .map_err(StorageError::disk_store)?;
+        Ok(last.map(|(key, _)| key.value().1))
+    }
+
+    fn upsert_tx_record(&self, record: TxRecordRef<'_>) -> Result<()> {
+        self.write(Durability::None, |txn| insert_tx_record(txn, record))
+    }
+
+    fn write_batch(&self, batch: &ColdWriteBatch) -> Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.write(Durability::None, |txn| {
+            insert_events(txn, &batch.events)?;
+            for record in &batch.records {
+                insert_tx_record(txn, record.borrowed())?;
+            }
+            Ok(())
+        })
+    }
+
+    fn transaction_lookup(&self, hash: &TxHash) -> Result<TransactionLookup> {
+        Ok(TransactionLookup {
+            seen: self.record(TX_SEEN, hash)?,
+            full: self.record(TX_FULL, hash)?,
+            features: self.record(TX_FEATURES, hash)?,
+            lifecycle: self.record(TX_LIFECYCLE, hash)?,
+        })
+    }
+
+    fn sync(&self) -> Result<()> {
+        self.write(Durability::Immediate, |_| Ok(()))
+    }
+}
+
+/// Infallible view for `EventStore` callers: read failures surface as empty
+/// results and failed appends are logged.
+impl EventStore for DiskEventStore {
+    fn append_event(&mut self, event: EventEnvelope) {
+        if let Err(err) = ColdEventStore::append_event(self, &event) {
+            tracing::warn!(error = %err, seq_id = event.seq_id, "failed to append event to disk store");
+        }
+    }
+
+    /// Returns the whole stored history, read one page per transaction.
+    /// The result is as large as the database, so prefer paging with
+    /// `scan_events` wherever the history may outgrow memory.
+    fn list_events(&self) -> Vec<EventEnvelope> {
+        let mut events = Vec::new();
+        loop {
+            let cursor = events
+                .last()
+                .map_or(0, |event: &EventEnvelope| event.seq_id);
+            let page = EventStore::scan_events(self, cursor, LIST_EVENTS_PAGE);
+            let done = page.len() < LIST_EVENTS_PAGE;
+            events.extend(page);
+            if done {
+                return events;
+            }
+        }
+    }
+
+    fn scan_events(&self, from_seq_id: u64, limit: usize) -> Vec<EventEnvelope> {
+        ColdEventStore::scan_events(self, from_seq_id, limit).unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "failed to scan disk store events");
+            Vec::new()
+        })
+    }
+
+    fn latest_seq_id(&self) -> Option<u64> {
+        ColdEventStore::latest_seq_id(self).ok().flatten()
+    }
+
+    fn scan_chain_events(
+        &self,
+        chain_id: u64,
+        from_chain_seq_id: u64,
+        limit: usize,
+    ) -> Vec<EventEnvelope> {
+        ColdEventStore::scan_chain_events(self, chain_id, from_chain_seq_id, limit).unwrap_or_else(
+            |err| {
+                tracing::warn!(error = %err, chain_id, "failed to scan disk store chain events");
+                Vec::new()
+            },
+        )
+    }
+
+    fn latest_chain_seq_id(&self, chain_id: u64) -> Option<u64> {
+        ColdEventStore::latest_chain_seq_id(self, chain_id)
+            .ok()
+            .flatten()
+    }
+}
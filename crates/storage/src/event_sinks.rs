@@ -0,0 +1,567 @@
+//! Fan-out of sequenced events to downstream [`EventSink`]s.
+//!
+//! Every sink runs in its own task behind a bounded queue of batches. The
+//! storage writer only filters each flushed batch and `try_send`s it, so a
+//! slow or failing sink loses its own batches (counted in
+//! [`EventSinkStats::dropped_events`]) instead of stalling the writer. Sinks
+//! receive events after the WAL commit and are not retried, so delivery is
+//! at most once.
+
+use crate::{Result, StorageError};
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use event_log::{EventEncoding, EventEnvelope};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+#[cfg(unix)]
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+const DEFAULT_QUEUE_CAPACITY: usize = 64;
+const DEFAULT_NDJSON_MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+const DEFAULT_NDJSON_MAX_FILES: usize = 8;
+const NDJSON_EXTENSION: &str = "ndjson";
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+const SOCKET_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[async_trait]
+/// Downstream consumer of the sequenced event stream.
+pub trait EventSink: Send {
+    /// Name reported in [`EventSinkStats`] and logs.
+    fn name(&self) -> String;
+
+    /// Delivers one batch, in seq order, that passed the sink's filter.
+    async fn deliver(&mut self, events: &[EventEnvelope]) -> Result<()>;
+}
+
+/// Selects the events a sink receives; empty lists match everything.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EventSinkFilter {
+    /// Payload type names, for example `TxSeen` or `OppDetected`.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub chain_ids: Vec<u64>,
+}
+
+impl EventSinkFilter {
+    pub fn matches(&self, event: &EventEnvelope) -> bool {
+        (self.event_types.is_empty()
+            || self
+                .event_types
+                .iter()
+                .any(|name| name == event.payload.type_name()))
+            && (self.chain_ids.is_empty()
+                || event
+                    .chain_id
+                    .is_some_and(|chain_id| self.chain_ids.contains(&chain_id)))
+    }
+}
+
+/// Filter and queue bound of one registered sink.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EventSinkOptions {
+    #[serde(flatten)]
+    pub filter: EventSinkFilter,
+    /// Batches waiting for the sink before further batches are dropped.
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+}
+
+impl Default for EventSinkOptions {
+    fn default() -> Self {
+        Self {
+            filter: EventSinkFilter::default(),
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+        }
+    }
+}
+
+fn default_queue_capacity() -> usize {
+    DEFAULT_QUEUE_CAPACITY
+}
+
+/// Delivery counters of one sink.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EventSinkStats {
+    pub name: String,
+    pub queued_batches: u64,
+    pub delivered_batches: u64,
+    pub delivered_events: u64,
+    pub failed_batches: u64,
+    /// Events lost because the queue was full, the sink task was gone or a
+    /// delivery failed.
+    pub dropped_events: u64,
+}
+
+#[derive(Default)]
+struct EventSinkCounters {
+    queued_batches: AtomicU64,
+    delivered_batches: AtomicU64,
+    delivered_events: AtomicU64,
+    failed_batches: AtomicU64,
+    dropped_events: AtomicU64,
+}
+
+struct RegisteredSink {
+    name: String,
+    filter: EventSinkFilter,
+    tx: mpsc::Sender<Vec<EventEnvelope>>,
+    counters: Arc<EventSinkCounters>,
+}
+
+/// The sinks the storage writer publishes flushed batches to.
+#[derive(Clone, Default)]
+pub struct EventSinkFanout {
+    sinks: Vec<Arc<RegisteredSink>>,
+}
+
+impl fmt::Debug for EventSinkFanout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.sinks.iter().map(|sink| &sink.name))
+            .finish()
+    }
+}
+
+impl EventSinkFanout {
+    /// Registers `sink` and spawns its delivery task; must be called inside
+    /// a tokio runtime.
+    pub fn with_sink(mut self, sink: impl EventSink + 'static, options: EventSinkOptions) -> Self {
+        let name = sink.name();
+        let (tx, rx) = mpsc::channel(options.queue_capacity.max(1));
+        let counters = Arc::<EventSinkCounters>::default();
+        tokio::spawn(run_sink(sink, rx, Arc::clone(&counters)));
+        self.sinks.push(Arc::new(RegisteredSink {
+            name,
+            filter: options.filter,
+            tx,
+            counters,
+        }));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Queues the events of `batch` each sink's filter accepts, without
+    /// waiting on any sink.
+    pub fn publish(&self, batch: &[EventEnvelope]) {
+        for sink in &self.sinks {
+            let events = batch
+                .iter()
+                .filter(|event| sink.filter.matches(event))
+                .cloned()
+                .collect::<Vec<_>>();
+            if events.is_empty() {
+                continue;
+            }
+            let len = events.len() as u64;
+            // Counted before sending so the task never sees the queue below zero.
+            sink.counters.queued_batches.fetch_add(1, Ordering::Relaxed);
+            if sink.tx.try_send(events).is_err() {
+                sink.counters.queued_batches.fetch_sub(1, Ordering::Relaxed);
+                sink.counters
+                    .dropped_events
+                    .fetch_add(len, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> Vec<EventSinkStats> {
+        self.sinks
+            .iter()
+            .map(|sink| {
+                let counters = &sink.counters;
+                EventSinkStats {
+                    name: sink.name.clone(),
+                    queued_batches: counters.queued_batches.load(Ordering::Relaxed),
+                    delivered_batches: counters.delivered_batches.load(Ordering::Relaxed),
+                    delivered_events: counters.delivered_events.load(Ordering::Relaxed),
+                    failed_batches: counters.failed_batches.load(Ordering::Relaxed),
+                    dropped_events: counters.dropped_events.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+async fn run_sink(
+    mut sink: impl EventSink,
+    mut rx: mpsc::Receiver<Vec<EventEnvelope>>,
+    counters: Arc<EventSinkCounters>,
+) {
+    while let Some(events) = rx.recv().await {
+        counters.queued_batches.fetch_sub(1, Ordering::Relaxed);
+        let len = events.len() as u64;
+        match sink.deliver(&events).await {
+            Ok(()) => {
+                counters.delivered_batches.fetch_add(1, Ordering::Relaxed);
+                counters.delivered_events.fetch_add(len, Ordering::Relaxed);
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, sink = %sink.name(), "event sink delivery failed");
+                counters.failed_batches.fetch_add(1, Ordering::Relaxed);
+                counters.dropped_events.fetch_add(len, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T> EventSink for Box<T>
+where
+    T: EventSink + ?Sized,
+{
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    async fn deliver(&mut self, events: &[EventEnvelope]) -> Result<()> {
+        (**self).deliver(events).await
+    }
+}
+
+fn encode_ndjson(events: &[EventEnvelope]) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    for event in events {
+        EventEncoding::Json
+            .encode_into(event, &mut body)
+            .map_err(|err| StorageError::event_sink(anyhow!("serialize event: {err}")))?;
+    }
+    Ok(body)
+}
+
+/// Appends events as NDJSON to size-rotated files in a directory.
+///
+/// Files are named after the first seq id they hold, so lexical order is seq
+/// order; only the newest `max_files` are kept.
+#[derive(Clone)]
+pub struct NdjsonFileSink {
+    dir: PathBuf,
+    max_file_bytes: u64,
+    max_files: usize,
+    active: Option<(PathBuf, u64)>,
+}
+
+impl NdjsonFileSink {
+    /// Writes to `dir`, rotating at 64 MiB and keeping 8 files.
+    pub fn create(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("create event sink directory {}", dir.display()))
+            .map_err(StorageError::event_sink)?;
+        Ok(Self {
+            dir,
+            max_file_bytes: DEFAULT_NDJSON_MAX_FILE_BYTES,
+            max_files: DEFAULT_NDJSON_MAX_FILES,
+            active: None,
+        })
+    }
+
+    pub fn with_rotation(mut self, max_file_bytes: u64, max_files: usize) -> Self {
+        self.max_file_bytes = max_file_bytes.max(1);
+        self.max_files = max_files.max(1);
+        self
+    }
+
+    /// Files currently in the directory, oldest first.
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = fs::read_dir(&self.dir)
+            .with_context(|| format!("list event sink directory {}", self.dir.display()))
+            .map_err(StorageError::event_sink)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == NDJSON_EXTENSION))
+            .collect::<Vec<_>>();
+        files.sort();
+        Ok(files)
+    }
+
+    fn rotate(&mut self, first_seq_id: u64) -> Result<PathBuf> {
+        let path = self
+            .dir
+            .join(format!("events-{first_seq_id:020}.{NDJSON_EXTENSION}"));
+        self.active = Some((path.clone(), 0));
+        let files = self.files()?;
+        // The new file does not exist yet, so keep room for it.
+        let excess = (files.len() + 1).saturating_sub(self.max_files);
+        for old in files.iter().take(excess) {
+            fs::remove_file(old)
+                .with_context(|| format!("remove rotated event file {}", old.display()))
+                .map_err(StorageError::event_sink)?;
+        }
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl EventSink for NdjsonFileSink {
+    fn name(&self) -> String {
+        format!("ndjson:{}", self.dir.display())
+    }
+
+    async fn deliver(&mut self, events: &[EventEnvelope]) -> Result<()> {
+        let Some(first) = events.first() else {
+            return Ok(());
+        };
+        let body = encode_ndjson(events)?;
+        let first_seq_id = first.seq_id;
+        // Rotation and appends are blocking file I/O; keep them off the
+        // runtime's workers.
+        let mut files = self.clone();
+        *self =
+            tokio::task::spawn_blocking(move || files.append(first_seq_id, &body).map(|()| files))
+                .await
+                .map_err(|err| {
+                    StorageError::event_sink(anyhow!("ndjson sink task failed: {err}"))
+                })??;
+        Ok(())
+    }
+}
+
+impl NdjsonFileSink {
+    fn append(&mut self, first_seq_id: u64, body: &[u8]) -> Result<()> {
+        let path = match &self.active {
+            Some((path, written))
+                if *written == 0 || written + body.len() as u64 <= self.max_file_bytes =>
+            {
+                path.clone()
+            }
+            _ => self.rotate(first_seq_id)?,
+        };
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(body))
+            .with_context(|| format!("append to event file {}", path.display()))
+            .map_err(StorageError::event_sink)?;
+        if let Some((_, written)) = self.active.as_mut() {
+            *written += body.len() as u64;
+        }
+        Ok(())
+    }
+}
+
+/// POSTs each batch as an NDJSON body to an HTTP endpoint.
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|err| StorageError::event_sink(anyhow!("build webhook client: {err}")))?;
+        Ok(Self {
+            client,
+            url: url.into(),
+        })
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    fn name(&self) -> String {
+        format!("webhook:{}", self.url)
+    }
+
+    async fn deliver(&mut self, events: &[EventEnvelope]) -> Result<()> {
+        let url = &self.url;
+        self.client
+            .post(url)
+            .header("content-type", "application/x-ndjson")
+            .body(encode_ndjson(events)?)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map(drop)
+            .map_err(|err| StorageError::event_sink(anyhow!("POST {url}: {err}")))
+    }
+}
+
+/// Streams NDJSON lines to a listener on a Unix socket, reconnecting on the
+/// next batch after a failed write.
+#[cfg(unix)]
+pub struct UnixSocketSink {
+    path: PathBuf,
+    stream: Option<tokio::net::UnixStream>,
+}
+
+#[cfg(unix)]
+impl UnixSocketSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            stream: None,
+        }
+    }
+
+    async fn write(&mut self, body: &[u8]) -> std::io::Result<()> {
+        let mut stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => tokio::net::UnixStream::connect(&self.path).await?,
+        };
+        // A stalled reader fails the batch instead of holding up the queue.
+        tokio::time::timeout(SOCKET_WRITE_TIMEOUT, stream.write_all(body))
+            .await
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "write timed out"))??;
+        self.stream = Some(stream);
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+#[async_trait]
+impl EventSink for UnixSocketSink {
+    fn name(&self) -> String {
+        format!("unix:{}", self.path.display())
+    }
+
+    async fn deliver(&mut self, events: &[EventEnvelope]) -> Result<()> {
+        let body = encode_ndjson(events)?;
+        self.write(&body).await.map_err(|err| {
+            StorageError::event_sink(anyhow!("write to {}: {err}", self.path.display()))
+        })
+    }
+}
+
+/// Declarative sink definition, as read from configuration.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventSinkSpec {
+    Ndjson {
+        dir: PathBuf,
+        #[serde(default = "default_ndjson_max_file_bytes")]
+        max_file_bytes: u64,
+        #[serde(default = "default_ndjson_max_files")]
+        max_files: usize,
+        #[serde(flatten)]
+        options: EventSinkOptions,
+    },
+    Webhook {
+        url: String,
+        #[serde(flatten)]
+        options: EventSinkOptions,
+    },
+    UnixSocket {
+        path: PathBuf,
+        #[serde(flatten)]
+        options: EventSinkOptions,
+    },
+}
+
+fn default_ndjson_max_file_bytes() -> u64 {
+    DEFAULT_NDJSON_MAX_FILE_BYTES
+}
+
+fn default_ndjson_max_files() -> usize {
+    DEFAULT_NDJSON_MAX_FILES
+}
+
+impl EventSinkSpec {
+    /// Opens the sink, returning it with its filter and queue options.
+    pub fn open(self) -> Result<(Box<dyn EventSink>, EventSinkOptions)> {
+        Ok(match self {
+            Self::Ndjson {
+                dir,
+                max_file_bytes,
+                max_files,
+                options,
+            } => (
+                Box::new(NdjsonFileSink::create(dir)?.with_rotation(max_file_bytes, max_files)),
+                options,
+            ),
+            Self::Webhook { url, options } => (Box::new(WebhookSink::new(url)?), options),
+            #[cfg(unix)]
+            Self::UnixSocket { path, options } => (Box::new(UnixSocketSink::new(path)), options),
+            #[cfg(not(unix))]
+            Self::UnixSocket { .. } => {
+                return Err(StorageError::event_sink(anyhow!(
+                    "unix socket sinks are not supported on this platform"
+                )));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::SourceId;
+    use event_log::{EventPayload, TxSeen};
+
+    fn seen(seq_id: u64, chain_id: Option<u64>) -> EventEnvelope {
+        EventEnvelope {
+            seq_id,
+            ingest_ts_unix_ms: 1_700_000_000_000,
+            ingest_ts_mono_ns: seq_id,
+            source_id: SourceId::new("sink-test"),
+            payload: EventPayload::TxSeen(TxSeen {
+                hash: [seq_id as u8; 32],
+                peer_id: "peer-a".to_owned(),
+                seen_at_unix_ms: 1_700_000_000_000,
+                seen_at_mono_ns: seq_id,
+            }),
+            chain_id,
+            chain_seq_id: None,
+            hash_link: None,
+        }
+    }
+
+    #[test]
+    fn filter_matches_type_and_chain_lists() {
+        let filter = EventSinkFilter {
+            event_types: vec!["TxSeen".to_owned()],
+            chain_ids: vec![8453],
+        };
+        assert!(filter.matches(&seen(1, Some(8453))));
+        assert!(!filter.matches(&seen(2, Some(1))));
+        assert!(!filter.matches(&seen(3, None)));
+        assert!(EventSinkFilter::default().matches(&seen(4, None)));
+    }
+
+    #[test]
+    fn specs_parse_with_flattened_filters_and_defaults() {
+        let specs: Vec<EventSinkSpec> = serde_json::from_str(
+            r#"[
+                {"kind": "webhook", "url": "http://bot", "event_types": ["OppDetected"]},
+                {"kind": "ndjson", "dir": "/tmp/events", "max_files": 2, "queue_capacity": 4}
+            ]"#,
+        )
+        .expect("parse specs");
+        assert_eq!(
+            specs,
+            vec![
+                EventSinkSpec::Webhook {
+                    url: "http://bot".to_owned(),
+                    options: EventSinkOptions {
+                        filter: EventSinkFilter {
+                            event_types: vec!["OppDetected".to_owned()],
+                            chain_ids: Vec::new(),
+                        },
+                        queue_capacity: DEFAULT_QUEUE_CAPACITY,
+                    },
+                },
+                EventSinkSpec::Ndjson {
+                    dir: PathBuf::from("/tmp/events"),
+                    max_file_bytes: DEFAULT_NDJSON_MAX_FILE_BYTES,
+                    max_files: 2,
+                    options: EventSinkOptions {
+                        queue_capacity: 4,
+                        ..EventSinkOptions::default()
+                    },
+                },
+            ]
+        );
+    }
+}
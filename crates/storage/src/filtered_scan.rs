@@ -0,0 +1,83 @@
+//! Bounded filtered scans and the posting lists that narrow them.
+
+use event_log::EventEnvelope;
+use std::collections::VecDeque;
+use std::iter::Peekable;
+
+/// One bounded step of a filtered scan.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FilteredScan {
+    /// Matching events in scan order.
+    pub events: Vec<EventEnvelope>,
+    /// Last position examined, in the scan's own cursor space (`seq_id`, or
+    /// `chain_seq_id` for chain scans); pass it back to resume.
+    pub resume_after: u64,
+    /// Whether the scan stopped on its budget rather than on `limit` or the
+    /// end of the store.
+    pub budget_exhausted: bool,
+}
+
+impl FilteredScan {
+    pub(crate) fn starting_at(cursor: u64) -> Self {
+        Self {
+            resume_after: cursor,
+            ..Self::default()
+        }
+    }
+}
+
+/// Inserts `entry` into an ascending posting list; appends are the fast path.
+pub(crate) fn insert_posting<T: Ord + Copy>(postings: &mut VecDeque<T>, entry: T) {
+    if postings.back().is_none_or(|last| *last <= entry) {
+        postings.push_back(entry);
+    } else {
+        let insert_at = postings.partition_point(|existing| *existing <= entry);
+        postings.insert(insert_at, entry);
+    }
+}
+
+/// Removes one `entry` from an ascending posting list; FIFO eviction makes
+/// the front the usual hit.
+pub(crate) fn remove_posting<T: Ord + Copy>(postings: &mut VecDeque<T>, entry: T) {
+    if postings.front() == Some(&entry) {
+        postings.pop_front();
+    } else if let Ok(index) = postings.binary_search(&entry) {
+        postings.remove(index);
+    }
+}
+
+/// Merges ascending `seq_id` sources into one ascending stream without
+/// repeats.
+pub(crate) struct SeqIdMerge<'a> {
+    sources: Vec<Peekable<Box<dyn Iterator<Item = u64> + 'a>>>,
+    last: Option<u64>,
+}
+
+impl<'a> SeqIdMerge<'a> {
+    pub(crate) fn new(sources: Vec<Box<dyn Iterator<Item = u64> + 'a>>) -> Self {
+        Self {
+            sources: sources.into_iter().map(Iterator::peekable).collect(),
+            last: None,
+        }
+    }
+}
+
+impl Iterator for SeqIdMerge<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            let (index, _) = self
+                .sources
+                .iter_mut()
+                .enumerate()
+                .filter_map(|(index, source)| source.peek().map(|seq_id| (index, *seq_id)))
+                .min_by_key(|(_, seq_id)| *seq_id)?;
+            let seq_id = self.sources[index].next()?;
+            if self.last != Some(seq_id) {
+                self.last = Some(seq_id);
+                return Some(seq_id);
+            }
+        }
+    }
+}
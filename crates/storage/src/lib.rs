@@ -3,18 +3,37 @@
 //! In-memory event storage, derived tables, and async persistence plumbing.
 
 mod backfill;
+mod calldata_blobs;
+mod checkpoint;
+mod clickhouse_rows;
 mod clickhouse_schema;
+mod cold_store;
+#[cfg(feature = "disk-store")]
+mod disk_store;
+mod event_sinks;
+mod filtered_scan;
+mod parquet_export;
+mod peer_observations;
+mod resilient_sink;
+mod retention;
+mod secondary_index;
 mod wal;
+mod wal_index;
+mod wal_record;
+mod wal_writer;
 
 use ahash::RandomState;
 use anyhow::{Result as AnyResult, anyhow};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use common::{Address, PeerId, SourceId, TxHash};
+use common::{Address, L2TxFields, PeerId, SourceId, TxHash};
 use event_log::{
-    AssemblyDecisionApplied, CandidateQueued, EventEnvelope, EventPayload, GlobalSequencer,
+    AssemblyDecisionApplied, AssemblyDecisionKind, CandidateQueued, DropReason, EventEncoding,
+    EventEnvelope, EventFilter, EventHashChain, EventPayload, FilterHints, GlobalSequencer,
+    HashChainConfig, SimCompleted, SimFailCategory, SimulationStatus, UserOpSeen,
     cmp_deterministic,
 };
+use feature_engine::user_op::analyze_user_operation;
 use hashbrown::{HashMap, HashSet};
 use parking_lot::RwLock;
 use replay::ReplayFrame;
@@ -22,24 +41,74 @@ use scheduler::PersistedSchedulerSnapshot;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::error::Error as StdError;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
-use std::{fs, path::PathBuf};
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::error::TrySendError;
 use tokio::time::MissedTickBehavior;
 
-pub use backfill::{BackfillConfig, BackfillSummary, BackfillWriter};
+use backfill::BackfillRun;
+pub use backfill::{
+    BackfillConfig, BackfillJob, BackfillProgress, BackfillRange, BackfillSource, BackfillState,
+    BackfillSummary, BackfillWriter, ClickHouseBackfillSource, WalBackfillSource,
+};
+pub use calldata_blobs::{BlobDigest, CalldataBlobStats, CalldataBlobStore};
+pub use checkpoint::StorageCheckpoint;
+use clickhouse_rows::batch_dedup_token;
+pub use clickhouse_rows::{ClickHouseInsert, clickhouse_typed_inserts};
 pub use clickhouse_schema::{
-    ClickHouseSchemaConfig, clickhouse_event_table_ddl, clickhouse_schema_ddl,
+    CLICKHOUSE_SCHEMA_VERSION, ClickHouseMigration, ClickHouseSchemaConfig,
+    clickhouse_event_table_ddl, clickhouse_migration_bootstrap_ddl, clickhouse_migrations,
+    clickhouse_pending_migrations, clickhouse_schema_ddl,
+};
+use cold_store::ColdRead;
+pub use cold_store::{ColdEventStore, ColdWriteBatch, TransactionLookup, TxRecord, TxRecordRef};
+#[cfg(feature = "disk-store")]
+pub use disk_store::DiskEventStore;
+#[cfg(unix)]
+pub use event_sinks::UnixSocketSink;
+pub use event_sinks::{
+    EventSink, EventSinkFanout, EventSinkFilter, EventSinkOptions, EventSinkSpec, EventSinkStats,
+    NdjsonFileSink, WebhookSink,
+};
+pub use filtered_scan::FilteredScan;
+use filtered_scan::{SeqIdMerge, insert_posting, remove_posting};
+pub use parquet_export::{
+    ParquetCompression, ParquetExportOptions, ParquetExportSummary, ParquetTable,
+    ParquetTableWriter, read_parquet_table, write_parquet_table,
+};
+use peer_observations::PeerObservations;
+pub use peer_observations::{
+    ObservedPropagationEdge, ObserverGroup, PeerObservationConfig, PeerObservationStats,
 };
-pub use wal::StorageWal;
+pub use resilient_sink::{
+    ClickHouseRetryConfig, ClickHouseSinkHealth, ClickHouseSinkStatus, ClickHouseSpillConfig,
+    ResilientClickHouseSink, ResilientClickHouseSinkConfig,
+};
+use retention::{MemoryAccounting, RetainedBytes};
+pub use retention::{
+    MemoryBudgetConfig, StorageMemoryUsage, StorageTable, TableMemoryUsage, TableWeights,
+};
+pub use secondary_index::{IndexPage, IndexPageQuery, TxIndexKey};
+use secondary_index::{OpportunityProtocolIndex, TxSecondaryIndexes, collect_page};
+pub use wal::{
+    StorageWal, WalCompaction, WalHead, WalRecovery, WalRepairReport, WalRetention, WalVerifyReport,
+};
+pub use wal_index::{WalEventIter, WalIndexEntry, WalScanRange, WalSegmentIndex};
+pub use wal_record::{WalIssue, WalIssueKind};
+pub use wal_writer::{WalDurability, WalMetrics, WalMetricsSnapshot, WalWriter};
 
 /// Hash map alias used for hot-path in-memory indices.
 pub type FastMap<K, V> = HashMap<K, V, RandomState>;
 /// Hash set alias used for hot-path in-memory indices.
 pub type FastSet<T> = HashSet<T, RandomState>;
 
+/// Source id stamped on hash-chain checkpoints emitted by the storage writer.
+pub const HASH_CHAIN_SOURCE_ID: &str = "storage-hash-chain";
+
 type Result<T> = std::result::Result<T, StorageError>;
 type SharedError = Arc<dyn StdError + Send + Sync>;
 
@@ -52,6 +121,14 @@ pub enum StorageError {
     ClickHouseBatch(SharedError),
     #[error("Parquet export failed: {0}")]
     ParquetExport(SharedError),
+    #[error("disk store failed: {0}")]
+    DiskStore(SharedError),
+    #[error("calldata blob store failed: {0}")]
+    CalldataBlob(SharedError),
+    #[error("backfill failed: {0}")]
+    Backfill(SharedError),
+    #[error("event sink failed: {0}")]
+    EventSink(SharedError),
     #[error(transparent)]
     Other(SharedError),
 }
@@ -84,6 +161,35 @@ impl StorageError {
     {
         Self::ParquetExport(Self::into_shared_error(error))
     }
+
+    fn calldata_blob<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        Self::CalldataBlob(Self::into_shared_error(error))
+    }
+
+    fn backfill<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        Self::Backfill(Self::into_shared_error(error))
+    }
+
+    fn event_sink<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        Self::EventSink(Self::into_shared_error(error))
+    }
+
+    #[cfg(feature = "disk-store")]
+    fn disk_store<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        Self::DiskStore(Self::into_shared_error(error))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -93,6 +199,12 @@ pub struct StorageConfig {
     pub recent_tx_capacity: usize,
     pub table_capacity: usize,
     pub write_latency_capacity: usize,
+    /// Byte budget across the event ring and projection tables, enforced on
+    /// top of the item capacities; `None` retains by count only.
+    pub memory_budget: Option<MemoryBudgetConfig>,
+    /// Window behind the peer and source statistics computed from `TxSeen`
+    /// and `TxDropped` events.
+    pub peer_observations: PeerObservationConfig,
 }
 
 impl Default for StorageConfig {
@@ -102,6 +214,8 @@ impl Default for StorageConfig {
             recent_tx_capacity: 25_000,
             table_capacity: 250_000,
             write_latency_capacity: 16_384,
+            memory_budget: None,
+            peer_observations: PeerObservationConfig::default(),
         }
     }
 }
@@ -133,6 +247,9 @@ pub struct TxFullRecord {
     pub max_fee_per_blob_gas_wei: Option<u128>,
     pub calldata_len: Option<u32>,
     pub raw_tx: Vec<u8>,
+    /// OP-stack / Arbitrum specific fields, when the chain carries them.
+    #[serde(default)]
+    pub l2_fields: Option<Box<L2TxFields>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -172,13 +289,52 @@ pub struct OpportunityRecord {
 pub struct BuilderLifecycleRecord {
     pub candidate_id: String,
     pub tx_hash: TxHash,
-    pub decision: String,
+    pub decision: AssemblyDecisionKind,
     pub replaced_candidate_ids: Vec<String>,
     pub reason: Option<String>,
     pub block_number: u64,
     pub updated_unix_ms: i64,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+/// Simulation outcome projection derived from `SimCompleted` events.
+pub struct SimulationRecord {
+    pub sim_id: String,
+    pub tx_hash: TxHash,
+    pub status: SimulationStatus,
+    pub fail_category: Option<SimFailCategory>,
+    pub latency_ms: Option<u64>,
+    pub tx_count: Option<u32>,
+    pub l1_data_fee_wei: Option<u128>,
+    pub gas_cost_wei: Option<u128>,
+    pub completed_unix_ms: i64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+/// Classified call forwarded by a smart account while executing a user operation.
+pub struct UserOpInnerCallRecord {
+    pub to: Address,
+    pub value_wei: u128,
+    pub protocol: String,
+    pub category: String,
+    pub mev_score: u16,
+    pub method_selector: Option<[u8; 4]>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+/// ERC-4337 user operation projection derived from `UserOpSeen` events.
+pub struct UserOpRecord {
+    pub user_op_hash: TxHash,
+    pub sender: Address,
+    pub entry_point: Address,
+    pub chain_id: Option<u64>,
+    pub bundle_tx_hash: Option<TxHash>,
+    pub inner_calls: Vec<UserOpInnerCallRecord>,
+    #[serde(default = "default_feature_engine_version")]
+    pub feature_engine_version: String,
+    pub updated_unix_ms: i64,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 /// Transaction lifecycle projection derived from event-log status changes.
 pub struct TxLifecycleRecord {
@@ -240,6 +396,94 @@ pub trait EventStore {
     fn scan_events(&self, from_seq_id: u64, limit: usize) -> Vec<EventEnvelope>;
     #[must_use = "callers should inspect the latest sequence id when querying event state"]
     fn latest_seq_id(&self) -> Option<u64>;
+    /// Returns one chain's events ordered by `chain_seq_id`, strictly after the cursor.
+    #[must_use]
+    fn scan_chain_events(
+        &self,
+        chain_id: u64,
+        from_chain_seq_id: u64,
+        limit: usize,
+    ) -> Vec<EventEnvelope>;
+    #[must_use]
+    fn latest_chain_seq_id(&self, chain_id: u64) -> Option<u64>;
+    /// Returns up to `limit` events after `from_seq_id` that satisfy `filter`,
+    /// examining at most `scan_budget` events.
+    #[must_use]
+    fn scan_filtered_events(
+        &self,
+        filter: &EventFilter,
+        from_seq_id: u64,
+        limit: usize,
+        scan_budget: usize,
+    ) -> FilteredScan {
+        let (limit, scan_budget) = (limit.max(1), scan_budget.max(1));
+        let hints = filter.hints();
+        let mut cursor = hints.min_seq_id.map_or(from_seq_id, |min_seq_id| {
+            from_seq_id.max(min_seq_id.saturating_sub(1))
+        });
+        let mut scan = FilteredScan::starting_at(cursor);
+        let mut examined = 0;
+        while examined < scan_budget {
+            let page = self.scan_events(cursor, limit.min(scan_budget - examined));
+            let Some(last) = page.last() else {
+                return scan;
+            };
+            cursor = last.seq_id;
+            for event in page {
+                if hints
+                    .max_seq_id
+                    .is_some_and(|max_seq_id| event.seq_id > max_seq_id)
+                {
+                    return scan;
+                }
+                examined += 1;
+                scan.resume_after = event.seq_id;
+                if filter.matches(&event) {
+                    scan.events.push(event);
+                    if scan.events.len() == limit {
+                        return scan;
+                    }
+                }
+            }
+        }
+        scan.budget_exhausted = true;
+        scan
+    }
+    /// Returns up to `limit` of one chain's events after `from_chain_seq_id`
+    /// that satisfy `filter`, examining at most `scan_budget` events.
+    #[must_use]
+    fn scan_filtered_chain_events(
+        &self,
+        chain_id: u64,
+        filter: &EventFilter,
+        from_chain_seq_id: u64,
+        limit: usize,
+        scan_budget: usize,
+    ) -> FilteredScan {
+        let (limit, scan_budget) = (limit.max(1), scan_budget.max(1));
+        let mut cursor = from_chain_seq_id;
+        let mut scan = FilteredScan::starting_at(cursor);
+        let mut examined = 0;
+        while examined < scan_budget {
+            let page = self.scan_chain_events(chain_id, cursor, limit.min(scan_budget - examined));
+            let Some(last) = page.last() else {
+                return scan;
+            };
+            cursor = last.chain_seq_id.unwrap_or(cursor);
+            for event in page {
+                examined += 1;
+                scan.resume_after = event.chain_seq_id.unwrap_or(scan.resume_after);
+                if filter.matches(&event) {
+                    scan.events.push(event);
+                    if scan.events.len() == limit {
+                        return scan;
+                    }
+                }
+            }
+        }
+        scan.budget_exhausted = true;
+        scan
+    }
 }
 
 /// Returns the first index containing events strictly after `from_seq_id`.
@@ -261,6 +505,18 @@ pub struct InMemoryStorage {
     config: StorageConfig,
     events: VecDeque<EventEnvelope>,
     event_index: Vec<EventEnvelope>,
+    /// Per-chain `(chain_seq_id, seq_id)` pairs in chain order, resolved
+    /// against `event_index` so chain scans do not keep a second copy of
+    /// every event.
+    chain_event_index: FastMap<u64, VecDeque<(u64, u64)>>,
+    /// Ascending `seq_id`s of events without an envelope `chain_id`; a
+    /// filter's `chain_id` falls back to the payload for these, so chain
+    /// hinted scans read them alongside `chain_event_index`.
+    unchained_event_index: VecDeque<u64>,
+    /// Ascending `seq_id`s per payload type name.
+    type_event_index: FastMap<&'static str, VecDeque<u64>>,
+    /// `(ingest_ts_unix_ms, seq_id)` pairs in time order.
+    time_event_index: VecDeque<(i64, u64)>,
     tx_seen: VecDeque<TxSeenRecord>,
     tx_seen_counts: FastMap<TxHash, usize>,
     tx_seen_lookup: FastMap<TxHash, TxSeenRecord>,
@@ -271,12 +527,21 @@ pub struct InMemoryStorage {
     tx_features_counts: FastMap<TxHash, usize>,
     tx_features_lookup: FastMap<TxHash, TxFeaturesRecord>,
     feature_summary_counts: FastMap<(String, String), u64>,
+    /// Sender, recipient, chain and protocol postings over the latest
+    /// `tx_full` / `tx_features` rows.
+    tx_indexes: TxSecondaryIndexes,
     opportunities: VecDeque<OpportunityRecord>,
+    opportunity_protocol_index: OpportunityProtocolIndex,
     builder_lifecycle: VecDeque<BuilderLifecycleRecord>,
+    simulations: VecDeque<SimulationRecord>,
+    user_ops: VecDeque<UserOpRecord>,
+    user_ops_counts: FastMap<TxHash, usize>,
+    user_ops_lookup: FastMap<TxHash, UserOpRecord>,
     tx_lifecycle: VecDeque<TxLifecycleRecord>,
     tx_lifecycle_counts: FastMap<TxHash, usize>,
     tx_lifecycle_lookup: FastMap<TxHash, TxLifecycleRecord>,
     peer_stats: VecDeque<PeerStatsRecord>,
+    peer_observations: PeerObservations,
     scheduler_snapshot: Option<PersistedSchedulerSnapshot>,
     latest_finalized_block_unix_ms: Option<i64>,
     write_latency_ns: VecDeque<u64>,
@@ -285,6 +550,19 @@ pub struct InMemoryStorage {
     recent_tx_lookup: FastMap<TxHash, RecentTransactionRecord>,
     market_stats: MarketStatsSnapshot,
     read_model_revision: u64,
+    /// Durable tier fed by every append and per-hash upsert and read when a
+    /// query falls outside the in-memory window.
+    cold: Option<Arc<dyn ColdEventStore>>,
+    /// Cold writes queued since the last commit; the writer commits them
+    /// outside the storage lock. A row evicted from memory before that
+    /// commit is briefly readable from neither tier.
+    cold_pending: ColdWriteBatch,
+    /// Estimated bytes per table, plus row ages when a budget is set.
+    memory: MemoryAccounting,
+    /// Where `tx_full` calldata is offloaded to, when attached.
+    calldata_blobs: Option<Arc<CalldataBlobStore>>,
+    /// Blob holding the calldata of each hash's latest `tx_full` row.
+    calldata_refs: FastMap<TxHash, BlobDigest>,
 }
 
 impl Default for InMemoryStorage {
@@ -319,12 +597,17 @@ impl InMemoryStorage {
             recent_tx_capacity: config.recent_tx_capacity.max(1),
             table_capacity: config.table_capacity.max(1),
             write_latency_capacity: config.write_latency_capacity.max(1),
+            memory_budget: config.memory_budget,
+            peer_observations: config.peer_observations,
         };
 
         Self {
-            config,
             events: VecDeque::new(),
             event_index: Vec::new(),
+            chain_event_index: FastMap::default(),
+            unchained_event_index: VecDeque::new(),
+            type_event_index: FastMap::default(),
+            time_event_index: VecDeque::new(),
             tx_seen: VecDeque::new(),
             tx_seen_counts: FastMap::default(),
             tx_seen_lookup: FastMap::default(),
@@ -335,12 +618,19 @@ impl InMemoryStorage {
             tx_features_counts: FastMap::default(),
             tx_features_lookup: FastMap::default(),
             feature_summary_counts: FastMap::default(),
+            tx_indexes: TxSecondaryIndexes::default(),
             opportunities: VecDeque::new(),
+            opportunity_protocol_index: OpportunityProtocolIndex::default(),
             builder_lifecycle: VecDeque::new(),
+            simulations: VecDeque::new(),
+            user_ops: VecDeque::new(),
+            user_ops_counts: FastMap::default(),
+            user_ops_lookup: FastMap::default(),
             tx_lifecycle: VecDeque::new(),
             tx_lifecycle_counts: FastMap::default(),
             tx_lifecycle_lookup: FastMap::default(),
             peer_stats: VecDeque::new(),
+            peer_observations: PeerObservations::new(config.peer_observations),
             scheduler_snapshot: None,
             latest_finalized_block_unix_ms: None,
             write_latency_ns: VecDeque::new(),
@@ -349,20 +639,83 @@ impl InMemoryStorage {
             recent_tx_lookup: FastMap::default(),
             market_stats: MarketStatsSnapshot::default(),
             read_model_revision: 0,
+            cold: None,
+            cold_pending: ColdWriteBatch::default(),
+            memory: MemoryAccounting::new(config.memory_budget.is_some()),
+            calldata_blobs: None,
+            calldata_refs: FastMap::default(),
+            config,
+        }
+    }
+
+    /// Puts this storage in front of `cold` as a hot cache. Only writes made
+    /// after attaching reach the cold store, once committed by the writer's
+    /// next flush or [`Self::sync_cold_store`]; `list_events` and the table
+    /// accessors still cover the in-memory window only.
+    pub fn with_cold_store(mut self, cold: Arc<dyn ColdEventStore>) -> Self {
+        self.cold = Some(cold);
+        self
+    }
+
+    pub fn cold_store(&self) -> Option<&Arc<dyn ColdEventStore>> {
+        self.cold.as_ref()
+    }
+
+    /// Commits the queued cold writes and makes everything written to the
+    /// cold store so far durable.
+    pub fn sync_cold_store(&mut self) -> Result<()> {
+        let Some((cold, pending)) = self.take_cold_writes() else {
+            return Ok(());
+        };
+        let committed = cold.write_batch(&pending).and_then(|()| cold.sync());
+        if committed.is_err() {
+            self.requeue_cold_writes(pending);
+        }
+        committed
+    }
+
+    /// Hands over the queued cold writes so they can be committed without
+    /// holding the storage lock.
+    pub(crate) fn take_cold_writes(&mut self) -> Option<(Arc<dyn ColdEventStore>, ColdWriteBatch)> {
+        let cold = self.cold.clone()?;
+        Some((cold, std::mem::take(&mut self.cold_pending)))
+    }
+
+    /// Puts writes whose commit failed back ahead of those queued since, so
+    /// the next commit retries them in their original order.
+    pub(crate) fn requeue_cold_writes(&mut self, mut failed: ColdWriteBatch) {
+        failed.append(std::mem::take(&mut self.cold_pending));
+        self.cold_pending = failed;
+    }
+
+    /// Offloads `tx_full` calldata into `blobs`, keeping only its digest in
+    /// memory. Applies to rows upserted after attaching.
+    pub fn with_calldata_blobs(mut self, blobs: Arc<CalldataBlobStore>) -> Self {
+        self.calldata_blobs = Some(blobs);
+        self
+    }
+
+    fn write_through(&mut self, queue: impl FnOnce(&mut ColdWriteBatch)) {
+        if self.cold.is_some() {
+            queue(&mut self.cold_pending);
         }
     }
 
+    /// Pairs `hot` with the cold-store read that replaces it when `needed`.
+    fn cold_read<T: 'static>(
+        &self,
+        hot: T,
+        needed: bool,
+        read: impl FnOnce(&dyn ColdEventStore, &T) -> Result<T> + 'static,
+    ) -> ColdRead<T> {
+        ColdRead::new(hot, self.cold.clone().filter(|_| needed), read)
+    }
+
     /// Upserts the latest `TxSeen` projection for a hash.
     pub fn upsert_tx_seen(&mut self, record: TxSeenRecord) {
         let start = Instant::now();
-        push_bounded_hash_indexed(
-            &mut self.tx_seen,
-            &mut self.tx_seen_counts,
-            &mut self.tx_seen_lookup,
-            record,
-            self.config.table_capacity,
-            |row| row.hash,
-        );
+        self.write_through(|pending| pending.records.push(TxRecord::Seen(record.clone())));
+        self.push_row(TableRow::TxSeen(record));
         self.market_stats.total_tx_count = self.market_stats.total_tx_count.saturating_add(1);
         self.record_write_latency(start.elapsed().as_nanos() as u64);
         self.bump_read_model_revision();
@@ -371,14 +724,8 @@ impl InMemoryStorage {
     /// Upserts the latest full transaction projection for a hash.
     pub fn upsert_tx_full(&mut self, record: TxFullRecord) {
         let start = Instant::now();
-        push_bounded_hash_indexed(
-            &mut self.tx_full,
-            &mut self.tx_full_counts,
-            &mut self.tx_full_lookup,
-            record,
-            self.config.table_capacity,
-            |row| row.hash,
-        );
+        self.write_through(|pending| pending.records.push(TxRecord::Full(record.clone())));
+        self.push_row(TableRow::TxFull(record));
         self.record_write_latency(start.elapsed().as_nanos() as u64);
         self.bump_read_model_revision();
     }
@@ -386,34 +733,9 @@ impl InMemoryStorage {
     /// Upserts the latest feature projection and updates summary counters.
     pub fn upsert_tx_features(&mut self, record: TxFeaturesRecord) {
         let start = Instant::now();
+        self.write_through(|pending| pending.records.push(TxRecord::Features(record.clone())));
         let mev_score = record.mev_score;
-        let hash = record.hash;
-        if let Some(previous) = self.tx_features_lookup.get(&hash).cloned() {
-            self.decrement_feature_summary_count(&previous.protocol, &previous.category);
-        }
-        self.increment_feature_summary_count(&record.protocol, &record.category);
-        self.tx_features.push_back(record.clone());
-        *self.tx_features_counts.entry(hash).or_insert(0) += 1;
-        self.tx_features_lookup.insert(hash, record);
-
-        while self.tx_features.len() > self.config.table_capacity {
-            if let Some(evicted) = self.tx_features.pop_front() {
-                let evicted_hash = evicted.hash;
-                if let Some(count) = self.tx_features_counts.get_mut(&evicted_hash) {
-                    *count = count.saturating_sub(1);
-                    if *count == 0 {
-                        self.tx_features_counts.remove(&evicted_hash);
-                        if let Some(removed_latest) = self.tx_features_lookup.remove(&evicted_hash)
-                        {
-                            self.decrement_feature_summary_count(
-                                &removed_latest.protocol,
-                                &removed_latest.category,
-                            );
-                        }
-                    }
-                }
-            }
-        }
+        self.push_row(TableRow::TxFeatures(record));
         self.market_stats.total_signal_volume =
             self.market_stats.total_signal_volume.saturating_add(1);
         if mev_score >= 80 {
@@ -431,7 +753,7 @@ impl InMemoryStorage {
     /// Stores a bounded opportunity projection.
     pub fn upsert_opportunity(&mut self, record: OpportunityRecord) {
         let start = Instant::now();
-        push_bounded(&mut self.opportunities, record, self.config.table_capacity);
+        self.push_row(TableRow::Opportunity(record));
         self.record_write_latency(start.elapsed().as_nanos() as u64);
         self.bump_read_model_revision();
     }
@@ -439,11 +761,23 @@ impl InMemoryStorage {
     /// Stores a bounded builder lifecycle projection.
     pub fn upsert_builder_lifecycle(&mut self, record: BuilderLifecycleRecord) {
         let start = Instant::now();
-        push_bounded(
-            &mut self.builder_lifecycle,
-            record,
-            self.config.table_capacity,
-        );
+        self.push_row(TableRow::BuilderLifecycle(record));
+        self.record_write_latency(start.elapsed().as_nanos() as u64);
+        self.bump_read_model_revision();
+    }
+
+    /// Stores a bounded simulation outcome projection.
+    pub fn upsert_simulation(&mut self, record: SimulationRecord) {
+        let start = Instant::now();
+        self.push_row(TableRow::Simulation(record));
+        self.record_write_latency(start.elapsed().as_nanos() as u64);
+        self.bump_read_model_revision();
+    }
+
+    /// Upserts the latest user operation projection for a user operation hash.
+    pub fn upsert_user_op(&mut self, record: UserOpRecord) {
+        let start = Instant::now();
+        self.push_row(TableRow::UserOp(record));
         self.record_write_latency(start.elapsed().as_nanos() as u64);
         self.bump_read_model_revision();
     }
@@ -451,14 +785,8 @@ impl InMemoryStorage {
     /// Upserts the latest transaction lifecycle projection for a hash.
     pub fn upsert_tx_lifecycle(&mut self, record: TxLifecycleRecord) {
         let start = Instant::now();
-        push_bounded_hash_indexed(
-            &mut self.tx_lifecycle,
-            &mut self.tx_lifecycle_counts,
-            &mut self.tx_lifecycle_lookup,
-            record,
-            self.config.table_capacity,
-            |row| row.hash,
-        );
+        self.write_through(|pending| pending.records.push(TxRecord::Lifecycle(record.clone())));
+        self.push_row(TableRow::TxLifecycle(record));
         self.record_write_latency(start.elapsed().as_nanos() as u64);
         self.bump_read_model_revision();
     }
@@ -466,11 +794,300 @@ impl InMemoryStorage {
     /// Stores peer statistics in a bounded table.
     pub fn upsert_peer_stats(&mut self, record: PeerStatsRecord) {
         let start = Instant::now();
-        push_bounded(&mut self.peer_stats, record, self.config.table_capacity);
+        self.push_row(TableRow::PeerStats(record));
         self.record_write_latency(start.elapsed().as_nanos() as u64);
         self.bump_read_model_revision();
     }
 
+    /// Appends a projection row with its index and byte bookkeeping, then
+    /// applies the table capacity and the memory budget.
+    pub(crate) fn push_row(&mut self, row: TableRow) {
+        let table = row.table();
+        let (pushed, replaced) = match row {
+            TableRow::TxSeen(record) => charged(push_hash_indexed(
+                &mut self.tx_seen,
+                &mut self.tx_seen_counts,
+                &mut self.tx_seen_lookup,
+                record,
+                |row| row.hash,
+            )),
+            TableRow::TxFull(mut record) => {
+                self.offload_calldata(&mut record);
+                self.tx_indexes.index_full(&record);
+                charged(push_hash_indexed(
+                    &mut self.tx_full,
+                    &mut self.tx_full_counts,
+                    &mut self.tx_full_lookup,
+                    record,
+                    |row| row.hash,
+                ))
+            }
+            TableRow::TxFeatures(record) => {
+                self.increment_feature_summary_count(&record.protocol, &record.category);
+                self.tx_indexes.index_features(&record);
+                let (pushed, replaced) = push_hash_indexed(
+                    &mut self.tx_features,
+                    &mut self.tx_features_counts,
+                    &mut self.tx_features_lookup,
+                    record,
+                    |row| row.hash,
+                );
+                if let Some(previous) = &replaced {
+                    self.decrement_feature_summary_count(&previous.protocol, &previous.category);
+                }
+                charged((pushed, replaced))
+            }
+            TableRow::TxLifecycle(record) => charged(push_hash_indexed(
+                &mut self.tx_lifecycle,
+                &mut self.tx_lifecycle_counts,
+                &mut self.tx_lifecycle_lookup,
+                record,
+                |row| row.hash,
+            )),
+            TableRow::UserOp(record) => charged(push_hash_indexed(
+                &mut self.user_ops,
+                &mut self.user_ops_counts,
+                &mut self.user_ops_lookup,
+                record,
+                |row| row.user_op_hash,
+            )),
+            TableRow::Opportunity(record) => {
+                self.opportunity_protocol_index
+                    .push(&record, self.opportunities.len() + 1);
+                (push_row_bytes(&mut self.opportunities, record), 0)
+            }
+            TableRow::BuilderLifecycle(record) => {
+                (push_row_bytes(&mut self.builder_lifecycle, record), 0)
+            }
+            TableRow::Simulation(record) => (push_row_bytes(&mut self.simulations, record), 0),
+            TableRow::PeerStats(record) => (push_row_bytes(&mut self.peer_stats, record), 0),
+        };
+        self.memory.row_pushed(table);
+        self.memory.charge(table, pushed);
+        self.memory.release(table, replaced);
+        while self.table_len(table) > self.config.table_capacity {
+            if !self.evict_oldest(table) {
+                break;
+            }
+        }
+        self.enforce_memory_budget();
+    }
+
+    /// Charges an event appended to the back of the ring buffer.
+    pub(crate) fn charge_event_row(&mut self, event: &EventEnvelope) {
+        self.memory.row_pushed(StorageTable::Events);
+        self.memory
+            .charge(StorageTable::Events, event.retained_bytes());
+    }
+
+    fn table_len(&self, table: StorageTable) -> usize {
+        match table {
+            StorageTable::Events => self.events.len(),
+            StorageTable::TxSeen => self.tx_seen.len(),
+            StorageTable::TxFull => self.tx_full.len(),
+            StorageTable::TxFeatures => self.tx_features.len(),
+            StorageTable::TxLifecycle => self.tx_lifecycle.len(),
+            StorageTable::Opportunities => self.opportunities.len(),
+            StorageTable::BuilderLifecycle => self.builder_lifecycle.len(),
+            StorageTable::Simulations => self.simulations.len(),
+            StorageTable::UserOps => self.user_ops.len(),
+            StorageTable::PeerStats => self.peer_stats.len(),
+        }
+    }
+
+    /// Evicts the oldest row of `table` together with everything derived
+    /// from it; returns `false` when the table is empty.
+    fn evict_oldest(&mut self, table: StorageTable) -> bool {
+        let released = match table {
+            StorageTable::Events => self.events.pop_front().map(|event| {
+                remove_event_from_sorted_index(&mut self.event_index, &event);
+                self.remove_event_from_indexes(&event);
+                event.retained_bytes()
+            }),
+            StorageTable::TxSeen => pop_hash_indexed(
+                &mut self.tx_seen,
+                &mut self.tx_seen_counts,
+                &mut self.tx_seen_lookup,
+                |row| row.hash,
+            )
+            .map(|(bytes, _)| bytes),
+            StorageTable::TxFull => pop_hash_indexed(
+                &mut self.tx_full,
+                &mut self.tx_full_counts,
+                &mut self.tx_full_lookup,
+                |row| row.hash,
+            )
+            .map(|(bytes, forgotten)| {
+                if let Some(forgotten) = forgotten {
+                    self.tx_indexes.forget_full(&forgotten.hash);
+                    self.release_calldata(&forgotten.hash);
+                }
+                bytes
+            }),
+            StorageTable::TxFeatures => pop_hash_indexed(
+                &mut self.tx_features,
+                &mut self.tx_features_counts,
+                &mut self.tx_features_lookup,
+                |row| row.hash,
+            )
+            .map(|(bytes, forgotten)| {
+                if let Some(forgotten) = forgotten {
+                    self.tx_indexes.forget_features(&forgotten.hash);
+                    self.decrement_feature_summary_count(&forgotten.protocol, &forgotten.category);
+                }
+                bytes
+            }),
+            StorageTable::TxLifecycle => pop_hash_indexed(
+                &mut self.tx_lifecycle,
+                &mut self.tx_lifecycle_counts,
+                &mut self.tx_lifecycle_lookup,
+                |row| row.hash,
+            )
+            .map(|(bytes, _)| bytes),
+            StorageTable::UserOps => pop_hash_indexed(
+                &mut self.user_ops,
+                &mut self.user_ops_counts,
+                &mut self.user_ops_lookup,
+                |row| row.user_op_hash,
+            )
+            .map(|(bytes, _)| bytes),
+            StorageTable::Opportunities => self.opportunities.pop_front().map(|evicted| {
+                self.opportunity_protocol_index.evict_front(&evicted);
+                evicted.retained_bytes()
+            }),
+            StorageTable::BuilderLifecycle => self
+                .builder_lifecycle
+                .pop_front()
+                .map(|row| row.retained_bytes()),
+            StorageTable::Simulations => {
+                self.simulations.pop_front().map(|row| row.retained_bytes())
+            }
+            StorageTable::PeerStats => self.peer_stats.pop_front().map(|row| row.retained_bytes()),
+        };
+        let Some(released) = released else {
+            return false;
+        };
+        self.memory.row_popped(table);
+        self.memory.release(table, released);
+        true
+    }
+
+    /// Evicts rows, oldest first among the tables above their weighted
+    /// share, until the estimated total fits the memory budget.
+    fn enforce_memory_budget(&mut self) {
+        let Some(budget) = self.config.memory_budget else {
+            return;
+        };
+        while self.memory.total() > budget.max_bytes {
+            let Some(table) = self.memory.budget_victim(&budget) else {
+                break;
+            };
+            if !self.evict_oldest(table) {
+                break;
+            }
+            self.memory.record_budget_eviction(table);
+        }
+    }
+
+    /// Estimated bytes held per table, with the budget share of each.
+    pub fn memory_usage(&self) -> StorageMemoryUsage {
+        let budget = self.config.memory_budget;
+        StorageMemoryUsage {
+            total_bytes: self.memory.total(),
+            budget_bytes: budget.map(|budget| budget.max_bytes),
+            tables: StorageTable::ALL
+                .into_iter()
+                .map(|table| TableMemoryUsage {
+                    table,
+                    rows: self.table_len(table),
+                    bytes: self.memory.bytes(table),
+                    budget_bytes: budget.map(|budget| budget.share_bytes(table)),
+                    budget_evictions_total: self.memory.budget_evictions(table),
+                })
+                .collect(),
+            calldata_blobs: self.calldata_blobs.as_ref().map(|blobs| blobs.stats()),
+        }
+    }
+
+    /// Moves the calldata of `record` into the blob store, leaving `raw_tx`
+    /// empty; on failure the calldata stays inline.
+    fn offload_calldata(&mut self, record: &mut TxFullRecord) {
+        let Some(blobs) = self.calldata_blobs.as_deref() else {
+            return;
+        };
+        let stored = if record.raw_tx.is_empty() {
+            None
+        } else {
+            blobs
+                .put(&record.raw_tx)
+                .map_err(|err| tracing::warn!(error = %err, "failed to offload calldata"))
+                .ok()
+        };
+        let previous = match stored {
+            Some(digest) => {
+                record.raw_tx = Vec::new();
+                self.calldata_refs.insert(record.hash, digest)
+            }
+            None => self.calldata_refs.remove(&record.hash),
+        };
+        if let Some(previous) = previous {
+            blobs.release(&previous);
+        }
+    }
+
+    fn release_calldata(&mut self, hash: &TxHash) {
+        if let (Some(digest), Some(blobs)) = (
+            self.calldata_refs.remove(hash),
+            self.calldata_blobs.as_deref(),
+        ) {
+            blobs.release(&digest);
+        }
+    }
+
+    /// Returns `record` with offloaded calldata read back into `raw_tx`.
+    pub(crate) fn with_inline_calldata(&self, record: &TxFullRecord) -> TxFullRecord {
+        let mut record = record.clone();
+        if record.raw_tx.is_empty()
+            && let Some(calldata) = self.offloaded_calldata(&record.hash)
+        {
+            record.raw_tx = calldata;
+        }
+        record
+    }
+
+    fn offloaded_calldata(&self, hash: &TxHash) -> Option<Vec<u8>> {
+        let digest = self.calldata_refs.get(hash)?;
+        self.calldata_blobs
+            .as_deref()?
+            .get(digest)
+            .map_err(|err| tracing::warn!(error = %err, "failed to read offloaded calldata"))
+            .ok()
+            .flatten()
+    }
+
+    /// Returns the raw transaction bytes of the latest `tx_full` row, reading
+    /// them back from the blob store when they were offloaded.
+    pub fn tx_calldata(&self, hash: &TxHash) -> Option<Vec<u8>> {
+        let row = self.tx_full_lookup.get(hash)?;
+        if row.raw_tx.is_empty()
+            && let Some(calldata) = self.offloaded_calldata(hash)
+        {
+            return Some(calldata);
+        }
+        Some(row.raw_tx.clone())
+    }
+
+    /// Length of [`Self::tx_calldata`] without reading offloaded bytes.
+    pub fn tx_calldata_len(&self, hash: &TxHash) -> Option<usize> {
+        let row = self.tx_full_lookup.get(hash)?;
+        let offloaded = self
+            .calldata_refs
+            .get(hash)
+            .zip(self.calldata_blobs.as_deref())
+            .and_then(|(digest, blobs)| blobs.blob_len(digest));
+        Some(offloaded.unwrap_or(row.raw_tx.len()))
+    }
+
     /// Persists the most recent scheduler snapshot for restart-time rehydration.
     pub fn write_scheduler_snapshot(&mut self, snapshot: PersistedSchedulerSnapshot) {
         let start = Instant::now();
@@ -487,6 +1104,8 @@ impl InMemoryStorage {
         self.tx_seen_lookup.get(hash)
     }
 
+    /// Rows whose calldata was offloaded to a blob store carry an empty
+    /// `raw_tx`; [`Self::tx_calldata`] reads it back.
     pub fn tx_full(&self) -> &VecDeque<TxFullRecord> {
         &self.tx_full
     }
@@ -515,10 +1134,70 @@ impl InMemoryStorage {
         out
     }
 
+    /// Returns one newest-first page of retained transaction hashes whose
+    /// latest row matches `key`. Time bounds apply to first-seen time and
+    /// `status` to the lifecycle row; hashes missing either are skipped
+    /// when that bound is set.
+    pub fn transactions_by_index(
+        &self,
+        key: &TxIndexKey,
+        query: &IndexPageQuery,
+    ) -> IndexPage<TxHash> {
+        let status = query.status.as_deref();
+        collect_page(
+            self.tx_indexes.newest_first(key, query.before),
+            query.limit,
+            |hash| {
+                let seen_unix_ms = self
+                    .tx_seen_lookup
+                    .get(&hash)
+                    .map(|seen| seen.first_seen_unix_ms);
+                let status_matches = status.is_none_or(|status| {
+                    self.tx_lifecycle_lookup
+                        .get(&hash)
+                        .is_some_and(|lifecycle| lifecycle.status.eq_ignore_ascii_case(status))
+                });
+                (status_matches && query.in_window(seen_unix_ms)).then_some(hash)
+            },
+        )
+    }
+
+    /// Returns one newest-first page of retained opportunities for
+    /// `protocol`, with time bounds applied to detection time.
+    pub fn opportunities_by_protocol(
+        &self,
+        protocol: &str,
+        query: &IndexPageQuery,
+    ) -> IndexPage<OpportunityRecord> {
+        collect_page(
+            self.opportunity_protocol_index
+                .newest_first(protocol, query.before),
+            query.limit,
+            |index| {
+                self.opportunities
+                    .get(index)
+                    .filter(|row| query.in_window(Some(row.detected_unix_ms)))
+                    .cloned()
+            },
+        )
+    }
+
     pub fn builder_lifecycle(&self) -> &VecDeque<BuilderLifecycleRecord> {
         &self.builder_lifecycle
     }
 
+    pub fn simulations(&self) -> &VecDeque<SimulationRecord> {
+        &self.simulations
+    }
+
+    pub fn user_ops(&self) -> &VecDeque<UserOpRecord> {
+        &self.user_ops
+    }
+
+    pub fn user_op_by_hash(&self, user_op_hash: &TxHash) -> Option<&UserOpRecord> {
+        self.user_ops_lookup.get(user_op_hash)
+    }
+
     /// Returns aggregated feature summary buckets sorted by count descending.
     pub fn dashboard_feature_summary(&self, limit: usize) -> Vec<FeatureSummaryBucket> {
         let target = limit.max(1);
@@ -550,10 +1229,54 @@ impl InMemoryStorage {
         self.tx_lifecycle_lookup.get(hash)
     }
 
+    /// Returns the latest projections for `hash`, reading the cold store for
+    /// any that have left the in-memory tables.
+    pub fn transaction_lookup(&self, hash: &TxHash) -> TransactionLookup {
+        self.transaction_lookup_read(hash).finish()
+    }
+
+    fn transaction_lookup_read(&self, hash: &TxHash) -> ColdRead<TransactionLookup> {
+        let hot = TransactionLookup {
+            seen: self.tx_seen_lookup.get(hash).cloned(),
+            full: self.tx_full_lookup.get(hash).cloned(),
+            features: self.tx_features_lookup.get(hash).cloned(),
+            lifecycle: self.tx_lifecycle_lookup.get(hash).cloned(),
+        };
+        let complete = hot.seen.is_some()
+            && hot.full.is_some()
+            && hot.features.is_some()
+            && hot.lifecycle.is_some();
+        let hash = *hash;
+        self.cold_read(hot, !complete, move |cold, hot| {
+            Ok(hot.clone().or(cold.transaction_lookup(&hash)?))
+        })
+    }
+
     pub fn peer_stats(&self) -> &VecDeque<PeerStatsRecord> {
         &self.peer_stats
     }
 
+    /// First-seen share, lag, unique-hash and drop statistics per peer or
+    /// source over the last `window_ms` of observations, capped at the
+    /// configured window.
+    pub fn peer_observation_stats(
+        &self,
+        group: ObserverGroup,
+        window_ms: u64,
+    ) -> Vec<PeerObservationStats> {
+        self.peer_observations.stats(group, window_ms)
+    }
+
+    /// Observed lag from the first observer of each hash to later ones over
+    /// the last `window_ms`.
+    pub fn observed_propagation_edges(
+        &self,
+        group: ObserverGroup,
+        window_ms: u64,
+    ) -> Vec<ObservedPropagationEdge> {
+        self.peer_observations.edges(group, window_ms)
+    }
+
     pub fn scheduler_snapshot(&self) -> Option<&PersistedSchedulerSnapshot> {
         self.scheduler_snapshot.as_ref()
     }
@@ -676,15 +1399,95 @@ impl InMemoryStorage {
         }
     }
 
-    fn bump_read_model_revision(&mut self) {
-        self.read_model_revision = self.read_model_revision.saturating_add(1);
+    /// Returns the newest retained per-chain sequence id for every chain.
+    pub fn latest_chain_seq_ids(&self) -> Vec<(u64, u64)> {
+        let mut latest = self
+            .chain_event_index
+            .iter()
+            .filter_map(|(chain_id, entries)| {
+                entries
+                    .back()
+                    .map(|(chain_seq_id, _)| (*chain_id, *chain_seq_id))
+            })
+            .collect::<Vec<_>>();
+        latest.sort_unstable();
+        latest
     }
-}
 
-impl EventStore for InMemoryStorage {
-    fn append_event(&mut self, event: EventEnvelope) {
+    /// Returns the lowest and highest `seq_id` of retained events ingested
+    /// within the inclusive unix-ms range, or `None` when there are none.
+    pub fn seq_id_range_for_time(
+        &self,
+        from_ts_unix_ms: Option<i64>,
+        to_ts_unix_ms: Option<i64>,
+    ) -> Option<RangeInclusive<u64>> {
+        let start = from_ts_unix_ms.map_or(0, |from| {
+            self.time_event_index.partition_point(|(ts, _)| *ts < from)
+        });
+        let end = to_ts_unix_ms.map_or(self.time_event_index.len(), |to| {
+            self.time_event_index.partition_point(|(ts, _)| *ts <= to)
+        });
+        let mut seq_ids = self
+            .time_event_index
+            .range(start..end.max(start))
+            .map(|(_, seq_id)| *seq_id);
+        let first = seq_ids.next()?;
+        let (min, max) = seq_ids.fold((first, first), |(min, max), seq_id| {
+            (min.min(seq_id), max.max(seq_id))
+        });
+        Some(min..=max)
+    }
+
+    /// Appends an event, deriving the hash-keyed projections from it only
+    /// when `project` is set.
+    ///
+    /// Backfill passes `false` for history older than what already drove a
+    /// hash's projections, so a late historical event cannot roll a live row
+    /// back.
+    pub(crate) fn append_event_with_projections(&mut self, event: EventEnvelope, project: bool) {
         let start = Instant::now();
 
+        if let EventPayload::TxConfirmedFinal(confirmed) = &event.payload {
+            let _ = confirmed;
+            self.latest_finalized_block_unix_ms = Some(
+                self.latest_finalized_block_unix_ms
+                    .unwrap_or_default()
+                    .max(event.ingest_ts_unix_ms),
+            );
+        }
+
+        if project {
+            self.project_event(&event);
+        }
+
+        let append_to_tail = match self.event_index.last() {
+            None => true,
+            Some(last) => !cmp_deterministic(last, &event).is_gt(),
+        };
+        if append_to_tail {
+            self.event_index.push(event.clone());
+        } else {
+            let insert_at = self
+                .event_index
+                .partition_point(|existing| cmp_deterministic(existing, &event).is_lt());
+            self.event_index.insert(insert_at, event.clone());
+        }
+        self.index_event(&event);
+        self.write_through(|pending| pending.events.push(event.clone()));
+        self.charge_event_row(&event);
+        self.events.push_back(event);
+        while self.events.len() > self.config.event_capacity {
+            if !self.evict_oldest(StorageTable::Events) {
+                break;
+            }
+        }
+        self.enforce_memory_budget();
+
+        self.record_write_latency(start.elapsed().as_nanos() as u64);
+        self.bump_read_model_revision();
+    }
+
+    fn project_event(&mut self, event: &EventEnvelope) {
         if let EventPayload::TxDecoded(decoded) = &event.payload {
             self.track_recent_transaction(RecentTransactionRecord {
                 hash: decoded.hash,
@@ -695,14 +1498,6 @@ impl EventStore for InMemoryStorage {
                 source_id: event.source_id.0.clone(),
             });
         }
-        if let EventPayload::TxConfirmedFinal(confirmed) = &event.payload {
-            let _ = confirmed;
-            self.latest_finalized_block_unix_ms = Some(
-                self.latest_finalized_block_unix_ms
-                    .unwrap_or_default()
-                    .max(event.ingest_ts_unix_ms),
-            );
-        }
 
         // Event append is the authority boundary; flat tables are derived here so replay and live
         // ingest drive the same projection code path.
@@ -715,6 +1510,12 @@ impl EventStore for InMemoryStorage {
                 event.ingest_ts_unix_ms,
             ));
         }
+        if let EventPayload::SimCompleted(completed) = &event.payload {
+            self.upsert_simulation(project_simulation(completed, event.ingest_ts_unix_ms));
+        }
+        if let EventPayload::UserOpSeen(seen) = &event.payload {
+            self.upsert_user_op(project_user_op(seen, event.ingest_ts_unix_ms));
+        }
 
         let lifecycle_update = match &event.payload {
             EventPayload::TxDecoded(decoded) => Some(TxLifecycleRecord {
@@ -723,12 +1524,25 @@ impl EventStore for InMemoryStorage {
                 reason: None,
                 updated_unix_ms: event.ingest_ts_unix_ms,
             }),
-            EventPayload::TxDropped(dropped) => Some(TxLifecycleRecord {
-                hash: dropped.hash,
-                status: "dropped".to_owned(),
-                reason: Some(dropped.reason.clone()),
-                updated_unix_ms: event.ingest_ts_unix_ms,
-            }),
+            EventPayload::TxDropped(dropped) => match dropped.reason {
+                // Ingest dedup of a repeated announcement; whatever state the
+                // first sighting reached still stands.
+                DropReason::Duplicate => None,
+                DropReason::QueueFull
+                | DropReason::UnderpricedReplacement
+                | DropReason::SenderLimitReached
+                | DropReason::Evicted
+                | DropReason::Included
+                | DropReason::Other(_) => Some(TxLifecycleRecord {
+                    hash: dropped.hash,
+                    status: "dropped".to_owned(),
+                    reason: Some(match &dropped.detail {
+                        Some(detail) => format!("{};{detail}", dropped.reason),
+                        None => dropped.reason.to_string(),
+                    }),
+                    updated_unix_ms: event.ingest_ts_unix_ms,
+                }),
+            },
             EventPayload::TxConfirmedProvisional(confirmed) => Some(TxLifecycleRecord {
                 hash: confirmed.hash,
                 status: "confirmed_provisional".to_owned(),
@@ -770,51 +1584,427 @@ impl EventStore for InMemoryStorage {
             | EventPayload::AssemblyDecisionApplied(_)
             | EventPayload::BundleSubmitted(_)
             | EventPayload::TxReady(_)
-            | EventPayload::TxBlocked(_) => None,
+            | EventPayload::TxBlocked(_)
+            | EventPayload::UserOpSeen(_)
+            | EventPayload::ChainCheckpoint(_) => None,
         };
         if let Some(record) = lifecycle_update {
             self.upsert_tx_lifecycle(record);
         }
+        self.peer_observations.observe(event);
+    }
+
+    /// Returns the highest in-memory seq id of each transaction hash's events.
+    pub(crate) fn latest_seq_ids_by_hash(&self) -> FastMap<TxHash, u64> {
+        let mut latest = FastMap::default();
+        for event in &self.event_index {
+            if !matches!(event.payload, EventPayload::ChainCheckpoint(_)) {
+                latest.insert(event.payload.primary_hash(), event.seq_id);
+            }
+        }
+        latest
+    }
 
-        let append_to_tail = match self.event_index.last() {
-            None => true,
-            Some(last) => !cmp_deterministic(last, &event).is_gt(),
+    /// Returns whether the event with `seq_id` is stored, in memory or in
+    /// the cold store.
+    pub fn contains_seq_id(&self, seq_id: u64) -> bool {
+        self.ingest_ts_for_seq_id(seq_id).is_some()
+    }
+
+    /// Returns the ingest time of the event with `seq_id`, reading the cold
+    /// store when it has left the in-memory window.
+    pub fn ingest_ts_for_seq_id(&self, seq_id: u64) -> Option<i64> {
+        self.ingest_ts_for_seq_id_read(seq_id).finish()
+    }
+
+    fn ingest_ts_for_seq_id_read(&self, seq_id: u64) -> ColdRead<Option<i64>> {
+        let start = self
+            .event_index
+            .partition_point(|event| event.seq_id < seq_id);
+        let hot = self
+            .event_index
+            .get(start)
+            .filter(|event| event.seq_id == seq_id)
+            .map(|event| event.ingest_ts_unix_ms);
+        self.cold_read(hot, hot.is_none(), move |cold, _| {
+            Ok(cold
+                .scan_events(seq_id.saturating_sub(1), 1)?
+                .into_iter()
+                .find(|event| event.seq_id == seq_id)
+                .map(|event| event.ingest_ts_unix_ms))
+        })
+    }
+
+    /// Returns up to `limit` retained events after `from_seq_id` ingested
+    /// within the inclusive unix-ms range, in `seq_id` order.
+    pub fn scan_events_in_time_range(
+        &self,
+        from_ts_unix_ms: Option<i64>,
+        to_ts_unix_ms: Option<i64>,
+        from_seq_id: u64,
+        limit: usize,
+    ) -> Vec<EventEnvelope> {
+        self.scan_filtered_events(
+            &EventFilter::time_range(from_ts_unix_ms, to_ts_unix_ms),
+            from_seq_id,
+            limit,
+            usize::MAX,
+        )
+        .events
+    }
+
+    fn index_event(&mut self, event: &EventEnvelope) {
+        insert_posting(
+            self.type_event_index
+                .entry(event.payload.type_name())
+                .or_default(),
+            event.seq_id,
+        );
+        if event.chain_id.is_none() {
+            insert_posting(&mut self.unchained_event_index, event.seq_id);
+        }
+        insert_posting(
+            &mut self.time_event_index,
+            (event.ingest_ts_unix_ms, event.seq_id),
+        );
+        let (Some(chain_id), Some(chain_seq_id)) = (event.chain_id, event.chain_seq_id) else {
+            return;
         };
-        if append_to_tail {
-            self.event_index.push(event.clone());
+        let entries = self.chain_event_index.entry(chain_id).or_default();
+        let entry = (chain_seq_id, event.seq_id);
+        if entries.back().is_none_or(|last| *last <= entry) {
+            entries.push_back(entry);
         } else {
-            let insert_at = self
-                .event_index
-                .partition_point(|existing| cmp_deterministic(existing, &event).is_lt());
-            self.event_index.insert(insert_at, event.clone());
+            let insert_at = entries.partition_point(|existing| *existing <= entry);
+            entries.insert(insert_at, entry);
         }
-        self.events.push_back(event);
-        while self.events.len() > self.config.event_capacity {
-            if let Some(old_event) = self.events.pop_front() {
-                remove_event_from_sorted_index(&mut self.event_index, &old_event);
+    }
+
+    fn remove_event_from_indexes(&mut self, target: &EventEnvelope) {
+        let type_name = target.payload.type_name();
+        if let Some(postings) = self.type_event_index.get_mut(type_name) {
+            remove_posting(postings, target.seq_id);
+            if postings.is_empty() {
+                self.type_event_index.remove(type_name);
             }
         }
+        if target.chain_id.is_none() {
+            remove_posting(&mut self.unchained_event_index, target.seq_id);
+        }
+        remove_posting(
+            &mut self.time_event_index,
+            (target.ingest_ts_unix_ms, target.seq_id),
+        );
+        let (Some(chain_id), Some(chain_seq_id)) = (target.chain_id, target.chain_seq_id) else {
+            return;
+        };
+        let Some(entries) = self.chain_event_index.get_mut(&chain_id) else {
+            return;
+        };
+        // Eviction is FIFO, so the evicted event is almost always the chain's
+        // oldest entry.
+        let entry = (chain_seq_id, target.seq_id);
+        if entries.front() == Some(&entry) {
+            entries.pop_front();
+        } else if let Ok(index) = entries.binary_search(&entry) {
+            entries.remove(index);
+        }
+        if entries.is_empty() {
+            self.chain_event_index.remove(&chain_id);
+        }
+    }
 
-        self.record_write_latency(start.elapsed().as_nanos() as u64);
-        self.bump_read_model_revision();
+    /// Returns ascending candidate `seq_id`s after `cursor` from the narrowest
+    /// index the hints allow, falling back to every retained event.
+    ///
+    /// Chain entries are read in `seq_id` order, which holds because the
+    /// sequencer assigns `chain_seq_id` and `seq_id` together.
+    fn candidate_seq_ids(&self, hints: &FilterHints, cursor: u64) -> SeqIdMerge<'_> {
+        type Source<'a> = Box<dyn Iterator<Item = u64> + 'a>;
+        let after = |list: &VecDeque<u64>| list.partition_point(|seq_id| *seq_id <= cursor);
+
+        let by_type = hints.event_types.as_ref().map(|types| {
+            types
+                .iter()
+                .filter_map(|type_name| self.type_event_index.get(type_name))
+                .collect::<Vec<_>>()
+        });
+        let by_chain = hints.chain_ids.as_ref().map(|chain_ids| {
+            chain_ids
+                .iter()
+                .filter_map(|chain_id| self.chain_event_index.get(chain_id))
+                .collect::<Vec<_>>()
+        });
+        let type_cost = by_type
+            .as_ref()
+            .map(|lists| lists.iter().map(|list| list.len()).sum::<usize>());
+        let chain_cost = by_chain.as_ref().map(|lists| {
+            lists.iter().map(|list| list.len()).sum::<usize>() + self.unchained_event_index.len()
+        });
+
+        let sources: Vec<Source<'_>> = match (by_type, by_chain) {
+            (Some(lists), _) if type_cost <= chain_cost || chain_cost.is_none() => lists
+                .into_iter()
+                .map(|list| Box::new(list.range(after(list)..).copied()) as Source<'_>)
+                .collect(),
+            (_, Some(lists)) => lists
+                .into_iter()
+                .map(|entries| {
+                    let start = entries.partition_point(|(_, seq_id)| *seq_id <= cursor);
+                    Box::new(entries.range(start..).map(|(_, seq_id)| *seq_id)) as Source<'_>
+                })
+                .chain(std::iter::once(Box::new(
+                    self.unchained_event_index
+                        .range(after(&self.unchained_event_index)..)
+                        .copied(),
+                ) as Source<'_>))
+                .collect(),
+            (_, None) => {
+                let start = scan_events_cursor_start(&self.event_index, cursor);
+                vec![Box::new(
+                    self.event_index[start..].iter().map(|event| event.seq_id),
+                )]
+            }
+        };
+        SeqIdMerge::new(sources)
     }
 
-    fn list_events(&self) -> Vec<EventEnvelope> {
-        self.event_index.clone()
+    fn chain_event(&self, chain_id: u64, chain_seq_id: u64, seq_id: u64) -> Option<&EventEnvelope> {
+        let start = self
+            .event_index
+            .partition_point(|event| event.seq_id < seq_id);
+        self.event_index[start..]
+            .iter()
+            .take_while(|event| event.seq_id == seq_id)
+            .find(|event| {
+                event.chain_id == Some(chain_id) && event.chain_seq_id == Some(chain_seq_id)
+            })
     }
 
-    fn scan_events(&self, from_seq_id: u64, limit: usize) -> Vec<EventEnvelope> {
+    fn bump_read_model_revision(&mut self) {
+        self.read_model_revision = self.read_model_revision.saturating_add(1);
+    }
+}
+
+/// In-memory halves of the `EventStore` reads that may fall back to the cold
+/// store.
+impl InMemoryStorage {
+    fn scan_events_read(&self, from_seq_id: u64, limit: usize) -> ColdRead<Vec<EventEnvelope>> {
         let limit = limit.max(1);
+        let evicted = self
+            .event_index
+            .first()
+            .is_none_or(|oldest| from_seq_id.saturating_add(1) < oldest.seq_id);
         let start = scan_events_cursor_start(&self.event_index, from_seq_id);
-        self.event_index[start..]
+        let hot = self.event_index[start..]
             .iter()
             .take(limit)
             .cloned()
-            .collect()
+            .collect();
+        self.cold_read(hot, evicted, move |cold, _| {
+            cold.scan_events(from_seq_id, limit)
+        })
+    }
+
+    fn latest_seq_id_read(&self) -> ColdRead<Option<u64>> {
+        let hot = self.event_index.last().map(|event| event.seq_id);
+        self.cold_read(hot, hot.is_none(), |cold, _| cold.latest_seq_id())
+    }
+
+    fn scan_chain_events_read(
+        &self,
+        chain_id: u64,
+        from_chain_seq_id: u64,
+        limit: usize,
+    ) -> ColdRead<Vec<EventEnvelope>> {
+        let limit = limit.max(1);
+        let entries = self.chain_event_index.get(&chain_id);
+        let evicted = entries
+            .and_then(|entries| entries.front())
+            .is_none_or(|(oldest, _)| from_chain_seq_id.saturating_add(1) < *oldest);
+        let hot = entries.map_or_else(Vec::new, |entries| {
+            let start =
+                entries.partition_point(|(chain_seq_id, _)| *chain_seq_id <= from_chain_seq_id);
+            entries
+                .range(start..)
+                .filter_map(|(chain_seq_id, seq_id)| {
+                    self.chain_event(chain_id, *chain_seq_id, *seq_id)
+                })
+                .take(limit)
+                .cloned()
+                .collect()
+        });
+        self.cold_read(hot, evicted, move |cold, _| {
+            cold.scan_chain_events(chain_id, from_chain_seq_id, limit)
+        })
+    }
+
+    fn latest_chain_seq_id_read(&self, chain_id: u64) -> ColdRead<Option<u64>> {
+        let hot = self
+            .chain_event_index
+            .get(&chain_id)
+            .and_then(|entries| entries.back())
+            .map(|(chain_seq_id, _)| *chain_seq_id);
+        self.cold_read(hot, hot.is_none(), move |cold, _| {
+            cold.latest_chain_seq_id(chain_id)
+        })
+    }
+}
+
+impl EventStore for InMemoryStorage {
+    fn append_event(&mut self, event: EventEnvelope) {
+        self.append_event_with_projections(event, true);
+    }
+
+    fn list_events(&self) -> Vec<EventEnvelope> {
+        self.event_index.clone()
+    }
+
+    fn scan_events(&self, from_seq_id: u64, limit: usize) -> Vec<EventEnvelope> {
+        self.scan_events_read(from_seq_id, limit).finish()
+    }
+
+    fn latest_seq_id(&self) -> Option<u64> {
+        self.latest_seq_id_read().finish()
+    }
+
+    fn scan_chain_events(
+        &self,
+        chain_id: u64,
+        from_chain_seq_id: u64,
+        limit: usize,
+    ) -> Vec<EventEnvelope> {
+        self.scan_chain_events_read(chain_id, from_chain_seq_id, limit)
+            .finish()
+    }
+
+    fn latest_chain_seq_id(&self, chain_id: u64) -> Option<u64> {
+        self.latest_chain_seq_id_read(chain_id).finish()
+    }
+
+    fn scan_filtered_events(
+        &self,
+        filter: &EventFilter,
+        from_seq_id: u64,
+        limit: usize,
+        scan_budget: usize,
+    ) -> FilteredScan {
+        let (limit, scan_budget) = (limit.max(1), scan_budget.max(1));
+        let mut hints = filter.hints();
+        // Time bounds narrow to the seq ids of the events inside them.
+        if hints.min_ts_unix_ms.is_some() || hints.max_ts_unix_ms.is_some() {
+            let Some(seq_ids) =
+                self.seq_id_range_for_time(hints.min_ts_unix_ms, hints.max_ts_unix_ms)
+            else {
+                return FilteredScan::starting_at(from_seq_id);
+            };
+            let (first, last) = seq_ids.into_inner();
+            hints.min_seq_id = Some(hints.min_seq_id.map_or(first, |min| min.max(first)));
+            hints.max_seq_id = Some(hints.max_seq_id.map_or(last, |max| max.min(last)));
+        }
+        let cursor = hints.min_seq_id.map_or(from_seq_id, |min_seq_id| {
+            from_seq_id.max(min_seq_id.saturating_sub(1))
+        });
+        let within_bounds = |seq_id: u64| hints.max_seq_id.is_none_or(|max| seq_id <= max);
+        let mut scan = FilteredScan::starting_at(cursor);
+
+        let candidates = self.candidate_seq_ids(&hints, cursor);
+        for (examined, seq_id) in candidates
+            .take_while(|seq_id| within_bounds(*seq_id))
+            .enumerate()
+        {
+            if scan.events.len() == limit {
+                return scan;
+            }
+            if examined == scan_budget {
+                scan.budget_exhausted = true;
+                return scan;
+            }
+            scan.resume_after = seq_id;
+            let start = self
+                .event_index
+                .partition_point(|event| event.seq_id < seq_id);
+            scan.events.extend(
+                self.event_index[start..]
+                    .iter()
+                    .take_while(|event| event.seq_id == seq_id)
+                    .filter(|event| filter.matches(*event))
+                    .cloned(),
+            );
+        }
+        scan
+    }
+}
+
+/// Shared storage as queried by readers other than the writer task. Each
+/// call holds the read lock only for the in-memory part and queries the cold
+/// store after releasing it, so cold-store I/O never blocks the writer.
+impl EventStore for RwLock<InMemoryStorage> {
+    fn append_event(&mut self, event: EventEnvelope) {
+        self.get_mut().append_event(event);
+    }
+
+    fn list_events(&self) -> Vec<EventEnvelope> {
+        self.read().list_events()
+    }
+
+    fn scan_events(&self, from_seq_id: u64, limit: usize) -> Vec<EventEnvelope> {
+        let read = self.read().scan_events_read(from_seq_id, limit);
+        read.finish()
     }
 
     fn latest_seq_id(&self) -> Option<u64> {
-        self.event_index.last().map(|event| event.seq_id)
+        let read = self.read().latest_seq_id_read();
+        read.finish()
+    }
+
+    fn scan_chain_events(
+        &self,
+        chain_id: u64,
+        from_chain_seq_id: u64,
+        limit: usize,
+    ) -> Vec<EventEnvelope> {
+        let read = self
+            .read()
+            .scan_chain_events_read(chain_id, from_chain_seq_id, limit);
+        read.finish()
+    }
+
+    fn latest_chain_seq_id(&self, chain_id: u64) -> Option<u64> {
+        let read = self.read().latest_chain_seq_id_read(chain_id);
+        read.finish()
+    }
+
+    fn scan_filtered_events(
+        &self,
+        filter: &EventFilter,
+        from_seq_id: u64,
+        limit: usize,
+        scan_budget: usize,
+    ) -> FilteredScan {
+        self.read()
+            .scan_filtered_events(filter, from_seq_id, limit, scan_budget)
+    }
+}
+
+/// Lookups on shared storage that, like its `EventStore` impl, query the
+/// cold store only after releasing the read lock.
+pub trait SharedStorageLookup {
+    /// See [`InMemoryStorage::transaction_lookup`].
+    fn transaction_lookup(&self, hash: &TxHash) -> TransactionLookup;
+    /// See [`InMemoryStorage::contains_seq_id`].
+    fn contains_seq_id(&self, seq_id: u64) -> bool;
+}
+
+impl SharedStorageLookup for RwLock<InMemoryStorage> {
+    fn transaction_lookup(&self, hash: &TxHash) -> TransactionLookup {
+        let read = self.read().transaction_lookup_read(hash);
+        read.finish()
+    }
+
+    fn contains_seq_id(&self, seq_id: u64) -> bool {
+        let read = self.read().ingest_ts_for_seq_id_read(seq_id);
+        read.finish().is_some()
     }
 }
 
@@ -824,6 +2014,8 @@ pub enum StorageWriteOp {
     AppendEvent(EventEnvelope),
     AppendPayload {
         source_id: SourceId,
+        /// Chain the payload was observed on; assigns a per-chain sequence id when set.
+        chain_id: Option<u64>,
         payload: EventPayload,
         ingest_ts_unix_ms: i64,
         ingest_ts_mono_ns: u64,
@@ -844,6 +2036,36 @@ pub struct StorageWriterConfig {
     pub flush_batch_size: usize,
     pub flush_interval_ms: u64,
     pub wal_path: Option<PathBuf>,
+    /// Encoding for newly appended WAL records; recovery reads either format.
+    pub wal_encoding: EventEncoding,
+    /// When WAL group commits are fsynced. Records are committed once per
+    /// flushed batch, i.e. every `flush_batch_size` events or flush tick.
+    pub wal_durability: WalDurability,
+    /// Links every sequenced event into a hash chain when set.
+    pub hash_chain: Option<HashChainConfig>,
+    /// Checkpoints projections and compacts the WAL instead of clearing it
+    /// after every flush; restarts then load the checkpoint plus WAL tail.
+    pub checkpoint: Option<StorageCheckpointConfig>,
+    /// Downstream sinks handed every flushed batch; a full sink queue drops
+    /// that sink's batch rather than blocking the writer.
+    pub event_sinks: EventSinkFanout,
+}
+
+/// How often the writer checkpoints projections and which covered WAL
+/// segments it keeps afterwards.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StorageCheckpointConfig {
+    pub interval_ms: u64,
+    pub retention: WalRetention,
+}
+
+impl Default for StorageCheckpointConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: 60_000,
+            retention: WalRetention::default(),
+        }
+    }
 }
 
 impl Default for StorageWriterConfig {
@@ -853,6 +2075,11 @@ impl Default for StorageWriterConfig {
             flush_batch_size: 512,
             flush_interval_ms: 500,
             wal_path: None,
+            wal_encoding: EventEncoding::default(),
+            wal_durability: WalDurability::default(),
+            hash_chain: None,
+            checkpoint: None,
+            event_sinks: EventSinkFanout::default(),
         }
     }
 }
@@ -861,6 +2088,10 @@ impl Default for StorageWriterConfig {
 /// Handle used by producers to enqueue writes into the storage writer task.
 pub struct StorageWriteHandle {
     tx: mpsc::Sender<StorageWriteOp>,
+    wal_metrics: Arc<WalMetrics>,
+    sink: Option<Arc<dyn ClickHouseBatchSink>>,
+    backfill: Option<Arc<RwLock<BackfillProgress>>>,
+    event_sinks: EventSinkFanout,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -873,7 +2104,37 @@ pub enum StorageTryEnqueueError {
 impl StorageWriteHandle {
     /// Wraps an existing storage-writer sender.
     pub fn from_sender(tx: mpsc::Sender<StorageWriteOp>) -> Self {
-        Self { tx }
+        Self {
+            tx,
+            wal_metrics: Arc::default(),
+            sink: None,
+            backfill: None,
+            event_sinks: EventSinkFanout::default(),
+        }
+    }
+
+    /// Returns the WAL counters of the writer task; all zero without a WAL.
+    pub fn wal_metrics(&self) -> Arc<WalMetrics> {
+        Arc::clone(&self.wal_metrics)
+    }
+
+    /// Returns the delivery health of the writer's ClickHouse sink, when the
+    /// sink tracks it.
+    pub fn sink_health(&self) -> Option<ClickHouseSinkHealth> {
+        self.sink.as_ref().and_then(|sink| sink.health())
+    }
+
+    /// Returns the progress of the writer's backfill, when it was spawned
+    /// with one.
+    pub fn backfill_progress(&self) -> Option<BackfillProgress> {
+        self.backfill
+            .as_ref()
+            .map(|progress| progress.read().clone())
+    }
+
+    /// Returns the delivery counters of the writer's event sinks.
+    pub fn event_sink_stats(&self) -> Vec<EventSinkStats> {
+        self.event_sinks.stats()
     }
 
     /// Enqueues a write, waiting for queue capacity if needed.
@@ -910,6 +2171,11 @@ impl StorageWriteHandle {
 /// Sink for flushing event batches to external analytical storage.
 pub trait ClickHouseBatchSink: Send + Sync {
     async fn flush_event_batch(&self, events: Vec<EventEnvelope>) -> Result<()>;
+
+    /// Delivery health, for sinks that track it.
+    fn health(&self) -> Option<ClickHouseSinkHealth> {
+        None
+    }
 }
 
 #[async_trait]
@@ -920,6 +2186,9 @@ where
     async fn flush_event_batch(&self, events: Vec<EventEnvelope>) -> Result<()> {
         (**self).flush_event_batch(events).await
     }
+    fn health(&self) -> Option<ClickHouseSinkHealth> {
+        (**self).health()
+    }
 }
 
 #[async_trait]
@@ -930,6 +2199,9 @@ where
     async fn flush_event_batch(&self, events: Vec<EventEnvelope>) -> Result<()> {
         (**self).flush_event_batch(events).await
     }
+    fn health(&self) -> Option<ClickHouseSinkHealth> {
+        (**self).health()
+    }
 }
 
 #[async_trait]
@@ -940,6 +2212,9 @@ where
     async fn flush_event_batch(&self, events: Vec<EventEnvelope>) -> Result<()> {
         (**self).flush_event_batch(events).await
     }
+    fn health(&self) -> Option<ClickHouseSinkHealth> {
+        (**self).health()
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -954,72 +2229,203 @@ impl ClickHouseBatchSink for NoopClickHouseSink {
 }
 
 #[derive(Clone)]
-/// HTTP-backed ClickHouse sink that writes NDJSON batches.
+/// HTTP-backed ClickHouse sink.
+///
+/// Built from an insert URL it posts versioned NDJSON event lines to that
+/// URL. Built from a server URL with [`ClickHouseHttpSink::typed`] it writes
+/// `mev_v2.events` as `JSONEachRow` and the per-payload tables as
+/// `RowBinary`, applying pending schema migrations before the first insert.
 pub struct ClickHouseHttpSink {
     client: reqwest::Client,
-    insert_url: String,
+    target: ClickHouseTarget,
+}
+
+#[derive(Clone, Debug)]
+enum ClickHouseTarget {
+    InsertUrl(String),
+    Typed {
+        base_url: String,
+        schema: ClickHouseSchemaConfig,
+        migrated: Arc<AtomicBool>,
+    },
 }
 
 impl ClickHouseHttpSink {
     /// Creates a ClickHouse sink for the given insert endpoint.
     pub fn new(insert_url: impl Into<String>) -> AnyResult<Self> {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(5))
-            .build()
-            .map_err(|err| anyhow!("build clickhouse http client: {err}"))?;
         Ok(Self {
-            client,
-            insert_url: insert_url.into(),
+            client: Self::client()?,
+            target: ClickHouseTarget::InsertUrl(insert_url.into()),
+        })
+    }
+
+    /// Creates a sink that routes typed rows to the server at `base_url`
+    /// (for example `http://localhost:8123`).
+    pub fn typed(base_url: impl Into<String>, schema: ClickHouseSchemaConfig) -> AnyResult<Self> {
+        Ok(Self {
+            client: Self::client()?,
+            target: ClickHouseTarget::Typed {
+                base_url: base_url.into(),
+                schema,
+                migrated: Arc::new(AtomicBool::new(false)),
+            },
         })
     }
 
-    /// Builds a sink from `CLICKHOUSE_EVENTS_INSERT_URL` when configured.
+    /// Builds a sink from `CLICKHOUSE_URL` (typed tables) or
+    /// `CLICKHOUSE_EVENTS_INSERT_URL` (NDJSON events) when configured.
     pub fn from_env() -> AnyResult<Option<Self>> {
-        let url = match std::env::var("CLICKHOUSE_EVENTS_INSERT_URL") {
-            Ok(value) if !value.trim().is_empty() => value,
-            _ => return Ok(None),
+        let env_url = |key: &str| match std::env::var(key) {
+            Ok(value) if !value.trim().is_empty() => Some(value),
+            _ => None,
         };
-        Self::new(url).map(Some)
+        if let Some(base_url) = env_url("CLICKHOUSE_URL") {
+            return Self::typed(base_url, ClickHouseSchemaConfig::default()).map(Some);
+        }
+        env_url("CLICKHOUSE_EVENTS_INSERT_URL")
+            .map(Self::new)
+            .transpose()
     }
-}
 
-#[async_trait]
-impl ClickHouseBatchSink for ClickHouseHttpSink {
-    async fn flush_event_batch(&self, events: Vec<EventEnvelope>) -> Result<()> {
-        if events.is_empty() {
-            return Ok(());
-        }
+    pub(crate) fn client() -> AnyResult<reqwest::Client> {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .map_err(|err| anyhow!("build clickhouse http client: {err}"))
+    }
 
-        let mut body = String::new();
-        for event in events {
-            let encoded = serde_json::to_string(&event).map_err(|err| {
-                StorageError::clickhouse_batch(std::io::Error::other(format!(
-                    "serialize event: {err}"
-                )))
-            })?;
-            body.push_str(&encoded);
-            body.push('\n');
+    /// Applies the schema migrations the server has not recorded yet and
+    /// returns the resulting schema version.
+    pub async fn apply_migrations(&self) -> Result<u32> {
+        let ClickHouseTarget::Typed {
+            base_url,
+            schema,
+            migrated,
+        } = &self.target
+        else {
+            return Err(StorageError::clickhouse_batch(std::io::Error::other(
+                "schema migrations need a ClickHouse server URL, not an insert URL",
+            )));
+        };
+        for statement in clickhouse_migration_bootstrap_ddl() {
+            self.post(base_url, &[], statement.into_bytes()).await?;
         }
+        let applied = self
+            .post(
+                base_url,
+                &[],
+                b"SELECT max(version) FROM mev_v2.schema_migrations FORMAT TabSeparated".to_vec(),
+            )
+            .await?;
+        let applied = applied.trim().parse::<u32>().map_err(|err| {
+            StorageError::clickhouse_batch(std::io::Error::other(format!(
+                "parse applied schema version {applied:?}: {err}"
+            )))
+        })?;
+        let mut version = applied;
+        for migration in clickhouse_pending_migrations(*schema, applied) {
+            for statement in &migration.statements {
+                self.post(base_url, &[], statement.clone().into_bytes())
+                    .await?;
+            }
+            self.post(base_url, &[], migration.record_statement().into_bytes())
+                .await?;
+            tracing::info!(
+                version = migration.version,
+                name = migration.name,
+                "applied clickhouse schema migration"
+            );
+            version = migration.version;
+        }
+        migrated.store(true, Ordering::Release);
+        Ok(version)
+    }
 
-        self.client
-            .post(&self.insert_url)
-            .header("content-type", "application/x-ndjson")
-            .body(body)
+    async fn post(&self, url: &str, params: &[(&str, &str)], body: Vec<u8>) -> Result<String> {
+        self.post_as(url, params, "text/plain", body).await
+    }
+
+    async fn post_as(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<String> {
+        let mut request = self
+            .client
+            .post(url)
+            .header("content-type", content_type)
+            .body(body);
+        if !params.is_empty() {
+            request = request.query(params);
+        }
+        request
             .send()
             .await
             .map_err(|err| {
-                StorageError::clickhouse_batch(std::io::Error::other(format!(
-                    "POST {}: {err}",
-                    self.insert_url
-                )))
+                StorageError::clickhouse_batch(std::io::Error::other(format!("POST {url}: {err}")))
             })?
             .error_for_status()
             .map_err(|err| {
                 StorageError::clickhouse_batch(std::io::Error::other(format!(
                     "clickhouse insert returned error status: {err}"
                 )))
-            })?;
+            })?
+            .text()
+            .await
+            .map_err(|err| {
+                StorageError::clickhouse_batch(std::io::Error::other(format!(
+                    "read response from {url}: {err}"
+                )))
+            })
+    }
+}
 
+#[async_trait]
+impl ClickHouseBatchSink for ClickHouseHttpSink {
+    async fn flush_event_batch(&self, events: Vec<EventEnvelope>) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let (base_url, migrated) = match &self.target {
+            ClickHouseTarget::InsertUrl(insert_url) => {
+                let dedup_token = batch_dedup_token("events", &events);
+                let mut body = Vec::new();
+                for event in events {
+                    EventEncoding::Json
+                        .encode_into(&event, &mut body)
+                        .map_err(|err| {
+                            StorageError::clickhouse_batch(std::io::Error::other(format!(
+                                "serialize event: {err}"
+                            )))
+                        })?;
+                }
+                self.post_as(
+                    insert_url,
+                    &[("insert_deduplication_token", &dedup_token)],
+                    "application/x-ndjson",
+                    body,
+                )
+                .await?;
+                return Ok(());
+            }
+            ClickHouseTarget::Typed {
+                base_url, migrated, ..
+            } => (base_url, migrated),
+        };
+        if !migrated.load(Ordering::Acquire) {
+            self.apply_migrations().await?;
+        }
+        for insert in clickhouse_typed_inserts(&events)? {
+            let params = [
+                ("query", insert.query.as_str()),
+                ("insert_deduplication_token", &insert.dedup_token),
+                ("deduplicate_blocks_in_dependent_materialized_views", "1"),
+            ];
+            self.post(base_url, &params, insert.body).await?;
+        }
         Ok(())
     }
 }
@@ -1029,26 +2435,101 @@ pub fn spawn_single_writer(
     storage: Arc<RwLock<InMemoryStorage>>,
     sink: Arc<dyn ClickHouseBatchSink>,
     config: StorageWriterConfig,
+) -> StorageWriteHandle {
+    spawn_writer(storage, sink, config, None)
+}
+
+/// Spawns the single storage writer task with a backfill of historical
+/// events.
+///
+/// Before sequencing any live write the task moves its sequencer past the
+/// head of `backfill`'s source, then applies backfilled batches between live
+/// writes. Backfilled events go to storage and the WAL but not to `sink`,
+/// and events storage already holds are skipped.
+pub fn spawn_single_writer_with_backfill(
+    storage: Arc<RwLock<InMemoryStorage>>,
+    sink: Arc<dyn ClickHouseBatchSink>,
+    config: StorageWriterConfig,
+    backfill: BackfillJob,
+) -> StorageWriteHandle {
+    spawn_writer(storage, sink, config, Some(backfill))
+}
+
+fn spawn_writer(
+    storage: Arc<RwLock<InMemoryStorage>>,
+    sink: Arc<dyn ClickHouseBatchSink>,
+    config: StorageWriterConfig,
+    backfill: Option<BackfillJob>,
 ) -> StorageWriteHandle {
     let config = StorageWriterConfig {
         queue_capacity: config.queue_capacity.max(1),
         flush_batch_size: config.flush_batch_size.max(1),
         flush_interval_ms: config.flush_interval_ms.max(1),
         wal_path: config.wal_path,
+        wal_encoding: config.wal_encoding,
+        wal_durability: config.wal_durability,
+        hash_chain: config.hash_chain,
+        checkpoint: config.checkpoint,
+        event_sinks: config.event_sinks,
     };
+    let wal_metrics = Arc::<WalMetrics>::default();
+    let handle_sink = Arc::clone(&sink);
     let wal = config
         .wal_path
         .and_then(|path| match StorageWal::new(path) {
-            Ok(wal) => Some(wal),
+            Ok(wal) => Some(wal.with_encoding(config.wal_encoding)),
             Err(err) => {
                 tracing::warn!(error = %err, "failed to initialize storage WAL");
                 None
             }
         });
 
+    let mut head = WalHead::default();
     if let Some(wal) = wal.as_ref() {
-        match wal.recover_events() {
-            Ok(events) => {
+        match wal.read_head() {
+            Ok(persisted) => head = persisted.unwrap_or_default(),
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to read storage WAL head");
+            }
+        }
+        let mut recover_after = 0;
+        if config.checkpoint.is_some() && storage.latest_seq_id().is_none() {
+            match wal.read_checkpoint() {
+                Ok(Some(checkpoint)) => {
+                    if checkpoint.head.latest_seq_id > head.latest_seq_id {
+                        head = checkpoint.head.clone();
+                    }
+                    recover_after = checkpoint.seq_id;
+                    storage.write().restore_checkpoint(checkpoint);
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    tracing::warn!(error = %err, "failed to load storage checkpoint; replaying the WAL");
+                }
+            }
+        }
+        match wal.recover_from(recover_after) {
+            Ok(WalRecovery {
+                events,
+                issues,
+                truncated_bytes,
+            }) => {
+                if truncated_bytes > 0 {
+                    tracing::warn!(
+                        truncated_bytes,
+                        "truncated torn write at the end of storage WAL"
+                    );
+                }
+                for issue in &issues {
+                    tracing::warn!(
+                        segment = %issue.segment.display(),
+                        offset = issue.offset,
+                        len = issue.len,
+                        kind = ?issue.kind,
+                        "skipped damaged storage WAL record; run `wal repair` to quarantine it",
+                    );
+                }
+                head.observe(&events);
                 if !events.is_empty() {
                     let mut guard = storage.write();
                     for event in events {
@@ -1062,11 +2543,50 @@ pub fn spawn_single_writer(
         }
     }
 
-    let mut sequencer = GlobalSequencer::from_latest_seq_id(storage.read().latest_seq_id());
+    let wal = wal.map(|wal| {
+        wal.writer(config.wal_durability)
+            .with_metrics(Arc::clone(&wal_metrics))
+    });
+
+    let mut sequencer = {
+        let guard = storage.read();
+        let sequencer =
+            GlobalSequencer::from_latest_seq_id(guard.latest_seq_id().max(head.latest_seq_id))
+                .with_latest_chain_seq_ids(head.chain_seq_ids);
+        match config.hash_chain {
+            // Continue from the persisted head so a restart after the WAL was
+            // cleared does not restart the chain from genesis.
+            Some(hash_chain) => {
+                sequencer.with_hash_chain(EventHashChain::resume(hash_chain, head.hash_chain))
+            }
+            None => sequencer,
+        }
+    };
 
     let (tx, mut rx) = mpsc::channel::<StorageWriteOp>(config.queue_capacity);
+    let checkpointer = config.checkpoint.map(WalCheckpointer::new);
+    let backfill_progress = backfill
+        .as_ref()
+        .map(|_| Arc::new(RwLock::new(BackfillProgress::default())));
+    let task_backfill_progress = backfill_progress.clone();
+    let event_sinks = config.event_sinks.clone();
 
     tokio::spawn(async move {
+        // Live writes wait in the queue until the sequencer is past the
+        // backfill head; after that they interleave with history, which
+        // stays out of the projections of any hash they touched.
+        let mut backfill = match (backfill, task_backfill_progress) {
+            (Some(job), Some(progress)) => {
+                BackfillRun::start(job, &storage, &mut sequencer, progress).await
+            }
+            _ => None,
+        };
+        let mut disk = WriterDisk {
+            cold_attached: storage.read().cold_store().is_some(),
+            storage: Arc::clone(&storage),
+            wal,
+            checkpointer,
+        };
         let mut batch = Vec::with_capacity(config.flush_batch_size);
         let mut ticker = tokio::time::interval(Duration::from_millis(config.flush_interval_ms));
         ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
@@ -1079,53 +2599,86 @@ pub fn spawn_single_writer(
                         None => break,
                     };
 
+                    if let Some(run) = backfill.as_mut() {
+                        run.note_live_write(&op);
+                    }
                     {
                         let mut guard = storage.write();
                         // Sequence assignment, WAL persistence, and read-model mutation happen in
                         // one place so all producers observe the same ordering semantics.
-                        apply_write_op(&mut guard, op, &mut batch, wal.as_ref(), &mut sequencer);
+                        apply_write_op(&mut guard, op, &mut batch, disk.wal.as_mut(), &mut sequencer);
                     }
 
                     if batch.len() >= config.flush_batch_size {
-                        flush_batch(&sink, &mut batch, wal.as_ref()).await;
+                        flush_batch(&sink, &mut disk, &mut batch, &sequencer, &config.event_sinks)
+                            .await;
+                    }
+                }
+                fetch = BackfillRun::next(&mut backfill) => {
+                    let running = backfill.as_mut().is_some_and(|run| {
+                        run.apply(fetch, &mut storage.write(), disk.wal.as_mut())
+                    });
+                    // The cursor may only move past a batch once it is durable.
+                    let cursor = backfill.as_mut().and_then(BackfillRun::take_cursor_write);
+                    disk.run_blocking(move |disk| {
+                        disk.commit();
+                        if let Some(cursor) = cursor {
+                            cursor.persist();
+                        }
+                    })
+                    .await;
+                    if !running {
+                        backfill = None;
                     }
                 }
                 _ = ticker.tick() => {
                     if !batch.is_empty() {
-                        flush_batch(&sink, &mut batch, wal.as_ref()).await;
+                        flush_batch(&sink, &mut disk, &mut batch, &sequencer, &config.event_sinks)
+                            .await;
+                    } else {
+                        disk.run_blocking(WriterDisk::idle).await;
                     }
                 }
             }
         }
 
         if !batch.is_empty() {
-            flush_batch(&sink, &mut batch, wal.as_ref()).await;
+            flush_batch(
+                &sink,
+                &mut disk,
+                &mut batch,
+                &sequencer,
+                &config.event_sinks,
+            )
+            .await;
         }
+        disk.run_blocking(WriterDisk::commit).await;
     });
 
-    StorageWriteHandle { tx }
+    StorageWriteHandle {
+        tx,
+        wal_metrics,
+        sink: Some(handle_sink),
+        backfill: backfill_progress,
+        event_sinks,
+    }
 }
 
 fn apply_write_op(
     storage: &mut InMemoryStorage,
     op: StorageWriteOp,
     batch: &mut Vec<EventEnvelope>,
-    wal: Option<&StorageWal>,
+    mut wal: Option<&mut WalWriter>,
     sequencer: &mut GlobalSequencer,
 ) {
     match op {
         StorageWriteOp::AppendEvent(event) => {
             let event = sequencer.assign(event);
-            if let Some(wal) = wal
-                && let Err(err) = wal.append_event(&event)
-            {
-                tracing::warn!(error = %err, "failed to append event to storage WAL");
-            }
-            storage.append_event(event.clone());
-            batch.push(event);
+            append_sequenced_event(storage, batch, wal.as_deref_mut(), sequencer, event);
         }
         StorageWriteOp::AppendPayload {
             source_id,
+            chain_id,
             payload,
             ingest_ts_unix_ms,
             ingest_ts_mono_ns,
@@ -1136,14 +2689,11 @@ fn apply_write_op(
                 ingest_ts_mono_ns,
                 source_id,
                 payload,
+                chain_id,
+                chain_seq_id: None,
+                hash_link: None,
             });
-            if let Some(wal) = wal
-                && let Err(err) = wal.append_event(&event)
-            {
-                tracing::warn!(error = %err, "failed to append event to storage WAL");
-            }
-            storage.append_event(event.clone());
-            batch.push(event);
+            append_sequenced_event(storage, batch, wal, sequencer, event);
         }
         StorageWriteOp::UpsertTxSeen(record) => storage.upsert_tx_seen(record),
         StorageWriteOp::UpsertTxFull(record) => storage.upsert_tx_full(record),
@@ -1157,24 +2707,215 @@ fn apply_write_op(
     }
 }
 
+fn append_sequenced_event(
+    storage: &mut InMemoryStorage,
+    batch: &mut Vec<EventEnvelope>,
+    mut wal: Option<&mut WalWriter>,
+    sequencer: &mut GlobalSequencer,
+    event: EventEnvelope,
+) {
+    let checkpoint = sequencer.next_checkpoint(
+        event.ingest_ts_unix_ms,
+        event.ingest_ts_mono_ns,
+        SourceId::new(HASH_CHAIN_SOURCE_ID),
+    );
+    for event in std::iter::once(event).chain(checkpoint) {
+        if let Some(wal) = wal.as_deref_mut()
+            && let Err(err) = wal.append(&event)
+        {
+            tracing::warn!(error = %err, "failed to append event to storage WAL");
+        }
+        storage.append_event(event.clone());
+        batch.push(event);
+    }
+}
+
 async fn flush_batch(
     sink: &Arc<dyn ClickHouseBatchSink>,
+    disk: &mut WriterDisk,
     batch: &mut Vec<EventEnvelope>,
-    wal: Option<&StorageWal>,
+    sequencer: &GlobalSequencer,
+    event_sinks: &EventSinkFanout,
 ) {
     if batch.is_empty() {
         return;
     }
+    // Group commit: the whole batch reaches the WAL, and is fsynced per the
+    // durability mode, before it is handed to the sink. The cold store must
+    // hold the batch before the WAL may be cleared below.
+    let cold_committed = disk.run_blocking(WriterDisk::commit).await == Some(true);
+    event_sinks.publish(batch);
     let pending = std::mem::take(batch);
     if let Err(err) = sink.flush_event_batch(pending).await {
         tracing::warn!(error = %err, "clickhouse batch flush failed");
-    } else if let Some(wal) = wal
-        && let Err(err) = wal.clear()
+    } else if cold_committed && disk.wal.is_some() {
+        let head = WalHead::of_sequencer(sequencer);
+        disk.run_blocking(move |disk| disk.clear_wal(&head)).await;
+    }
+}
+
+/// The writer task's disk state. WAL commits, cold-store commits and
+/// checkpoints block on fsync, so they run on the blocking pool while the
+/// task awaits them.
+struct WriterDisk {
+    storage: Arc<RwLock<InMemoryStorage>>,
+    /// Cold stores are attached before the writer starts, so this is read
+    /// once instead of taking the storage lock on every tick.
+    cold_attached: bool,
+    wal: Option<WalWriter>,
+    checkpointer: Option<WalCheckpointer>,
+}
+
+impl WriterDisk {
+    /// Moves the WAL and checkpointer onto a blocking thread for `work` and
+    /// takes them back afterwards. If the thread panics they are dropped, and
+    /// the writer carries on without a WAL and `None` is returned.
+    async fn run_blocking<F, T>(&mut self, work: F) -> Option<T>
+    where
+        F: FnOnce(&mut WriterDisk) -> T + Send + 'static,
+        T: Send + 'static,
     {
-        tracing::warn!(error = %err, "failed to clear storage WAL after flush");
+        let mut moved = WriterDisk {
+            storage: Arc::clone(&self.storage),
+            cold_attached: self.cold_attached,
+            wal: self.wal.take(),
+            checkpointer: self.checkpointer.take(),
+        };
+        match tokio::task::spawn_blocking(move || {
+            let output = work(&mut moved);
+            (moved, output)
+        })
+        .await
+        {
+            Ok((moved, output)) => {
+                self.wal = moved.wal;
+                self.checkpointer = moved.checkpointer;
+                Some(output)
+            }
+            Err(err) => {
+                tracing::error!(error = %err, "storage writer disk task failed; WAL disabled");
+                None
+            }
+        }
+    }
+
+    /// Writes the pending WAL group, then the cold writes queued since the
+    /// last commit. Returns whether the cold writes were committed.
+    fn commit(&mut self) -> bool {
+        if let Some(wal) = self.wal.as_mut()
+            && let Err(err) = wal.commit()
+        {
+            tracing::warn!(error = %err, "failed to commit storage WAL batch");
+        }
+        self.commit_cold_writes()
+    }
+
+    /// Idle tick: upserts queue cold writes without filling a batch, and
+    /// interval durability still owes its fsync.
+    fn idle(&mut self) {
+        self.commit_cold_writes();
+        if let Some(wal) = self.wal.as_mut()
+            && let Err(err) = wal.sync_if_due()
+        {
+            tracing::warn!(error = %err, "failed to fsync storage WAL");
+        }
+    }
+
+    /// Drops the WAL records the sink acknowledged, through a checkpoint when
+    /// one is configured.
+    fn clear_wal(&mut self, head: &WalHead) {
+        let Some(wal) = self.wal.as_mut() else {
+            return;
+        };
+        // The head must land before the WAL is emptied; otherwise a crash in
+        // between would lose the sequencer position.
+        let cleared = wal
+            .wal()
+            .write_head(head)
+            .and_then(|()| match self.checkpointer.as_mut() {
+                Some(checkpointer) => checkpointer.checkpoint_if_due(&self.storage, wal, head),
+                None => wal.clear(),
+            });
+        if let Err(err) = cleared {
+            tracing::warn!(error = %err, "failed to clear or compact storage WAL after flush");
+        }
+    }
+
+    /// Commits the cold writes queued since the last flush in one
+    /// transaction, after releasing the storage lock so readers never wait on
+    /// disk. A batch that fails is queued again for the next commit.
+    fn commit_cold_writes(&self) -> bool {
+        if !self.cold_attached {
+            return true;
+        }
+        let Some((cold, pending)) = self.storage.write().take_cold_writes() else {
+            return true;
+        };
+        if pending.is_empty() {
+            return true;
+        }
+        match cold.write_batch(&pending).and_then(|()| cold.sync()) {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to commit cold store batch; retrying next flush");
+                self.storage.write().requeue_cold_writes(pending);
+                false
+            }
+        }
+    }
+}
+
+/// Writer-task state for periodic checkpoints.
+struct WalCheckpointer {
+    config: StorageCheckpointConfig,
+    last_checkpoint: Instant,
+}
+
+impl WalCheckpointer {
+    fn new(config: StorageCheckpointConfig) -> Self {
+        Self {
+            config,
+            last_checkpoint: Instant::now(),
+        }
+    }
+
+    /// Checkpoints the projections once the interval has elapsed, then seals
+    /// the active segment and drops the segments the checkpoint covers.
+    fn checkpoint_if_due(
+        &mut self,
+        storage: &RwLock<InMemoryStorage>,
+        wal: &mut WalWriter,
+        head: &WalHead,
+    ) -> Result<()> {
+        if self.last_checkpoint.elapsed() < Duration::from_millis(self.config.interval_ms) {
+            return Ok(());
+        }
+        self.last_checkpoint = Instant::now();
+        let now_unix_ms = unix_now_ms();
+        let checkpoint = storage.read().checkpoint(head.clone(), now_unix_ms);
+        wal.wal().write_checkpoint(&checkpoint)?;
+        wal.roll()?;
+        let compaction =
+            wal.wal()
+                .compact(checkpoint.seq_id, &self.config.retention, now_unix_ms)?;
+        tracing::debug!(
+            seq_id = checkpoint.seq_id,
+            segments_removed = compaction.segments_removed,
+            bytes_removed = compaction.bytes_removed,
+            "checkpointed storage and compacted WAL"
+        );
+        Ok(())
     }
 }
 
+fn unix_now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| {
+            i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX)
+        })
+}
+
 fn push_bounded<T>(deque: &mut VecDeque<T>, value: T, capacity: usize) {
     deque.push_back(value);
     while deque.len() > capacity {
@@ -1182,35 +2923,91 @@ fn push_bounded<T>(deque: &mut VecDeque<T>, value: T, capacity: usize) {
     }
 }
 
-fn push_bounded_hash_indexed<T, FHash>(
+/// One row bound for a projection table.
+pub(crate) enum TableRow {
+    TxSeen(TxSeenRecord),
+    TxFull(TxFullRecord),
+    TxFeatures(TxFeaturesRecord),
+    TxLifecycle(TxLifecycleRecord),
+    Opportunity(OpportunityRecord),
+    BuilderLifecycle(BuilderLifecycleRecord),
+    Simulation(SimulationRecord),
+    UserOp(UserOpRecord),
+    PeerStats(PeerStatsRecord),
+}
+
+impl TableRow {
+    fn table(&self) -> StorageTable {
+        match self {
+            Self::TxSeen(_) => StorageTable::TxSeen,
+            Self::TxFull(_) => StorageTable::TxFull,
+            Self::TxFeatures(_) => StorageTable::TxFeatures,
+            Self::TxLifecycle(_) => StorageTable::TxLifecycle,
+            Self::Opportunity(_) => StorageTable::Opportunities,
+            Self::BuilderLifecycle(_) => StorageTable::BuilderLifecycle,
+            Self::Simulation(_) => StorageTable::Simulations,
+            Self::UserOp(_) => StorageTable::UserOps,
+            Self::PeerStats(_) => StorageTable::PeerStats,
+        }
+    }
+}
+
+/// Appends `value` and returns the bytes it is charged.
+fn push_row_bytes<T: RetainedBytes>(deque: &mut VecDeque<T>, value: T) -> usize {
+    let bytes = value.retained_bytes();
+    deque.push_back(value);
+    bytes
+}
+
+/// Appends `value` and makes it the lookup row of its hash. Returns the bytes
+/// charged for the deque and lookup copies, and the lookup row it replaced.
+fn push_hash_indexed<T, FHash>(
     deque: &mut VecDeque<T>,
     counts: &mut FastMap<TxHash, usize>,
     lookup: &mut FastMap<TxHash, T>,
     value: T,
-    capacity: usize,
     hash_of: FHash,
-) where
-    T: Clone,
+) -> (usize, Option<T>)
+where
+    T: Clone + RetainedBytes,
     FHash: Fn(&T) -> TxHash,
 {
     let value_hash = hash_of(&value);
+    let bytes = 2 * value.retained_bytes();
     deque.push_back(value.clone());
     *counts.entry(value_hash).or_insert(0) += 1;
-    lookup.insert(value_hash, value);
+    (bytes, lookup.insert(value_hash, value))
+}
 
-    while deque.len() > capacity {
-        let Some(evicted) = deque.pop_front() else {
-            break;
-        };
-        let evicted_hash = hash_of(&evicted);
-        if let Some(count) = counts.get_mut(&evicted_hash) {
-            *count = count.saturating_sub(1);
-            if *count == 0 {
-                counts.remove(&evicted_hash);
-                lookup.remove(&evicted_hash);
-            }
+/// Converts a [`push_hash_indexed`] result into charged and released bytes.
+fn charged<T: RetainedBytes>((pushed, replaced): (usize, Option<T>)) -> (usize, usize) {
+    (pushed, replaced.map_or(0, |row| row.retained_bytes()))
+}
+
+/// Evicts the oldest row of a hash-keyed table. Returns the bytes released
+/// and, when that was the hash's last retained row, its dropped lookup row.
+fn pop_hash_indexed<T, FHash>(
+    deque: &mut VecDeque<T>,
+    counts: &mut FastMap<TxHash, usize>,
+    lookup: &mut FastMap<TxHash, T>,
+    hash_of: FHash,
+) -> Option<(usize, Option<T>)>
+where
+    T: RetainedBytes,
+    FHash: Fn(&T) -> TxHash,
+{
+    let evicted = deque.pop_front()?;
+    let evicted_hash = hash_of(&evicted);
+    let mut forgotten = None;
+    if let Some(count) = counts.get_mut(&evicted_hash) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            counts.remove(&evicted_hash);
+            forgotten = lookup.remove(&evicted_hash);
         }
     }
+    let bytes = evicted.retained_bytes() + forgotten.as_ref().map_or(0, |row| row.retained_bytes());
+    Some((bytes, forgotten))
 }
 
 fn remove_event_from_sorted_index(events: &mut Vec<EventEnvelope>, target: &EventEnvelope) {
@@ -1255,15 +3052,36 @@ pub fn export_replay_frames_csv(frames: &[ReplayFrame]) -> String {
     out
 }
 
-/// Extension point for exporting replay frames to parquet-like formats.
+/// Extension point for exporting events, projections and replay frames to
+/// Parquet.
 pub trait ParquetExporter {
     fn export_replay_frames_parquet(&self, _frames: &[ReplayFrame], _path: &str) -> Result<()> {
-        Err(StorageError::parquet_export(std::io::Error::other(
-            "parquet export is not wired in this crate yet; use adapter implementation",
-        )))
+        Err(parquet_unsupported())
+    }
+
+    fn export_events_parquet(&self, _events: &[EventEnvelope], _path: &str) -> Result<()> {
+        Err(parquet_unsupported())
+    }
+
+    fn export_tx_full_parquet(&self, _rows: &[TxFullRecord], _path: &str) -> Result<()> {
+        Err(parquet_unsupported())
+    }
+
+    fn export_tx_features_parquet(&self, _rows: &[TxFeaturesRecord], _path: &str) -> Result<()> {
+        Err(parquet_unsupported())
+    }
+
+    fn export_opportunities_parquet(&self, _rows: &[OpportunityRecord], _path: &str) -> Result<()> {
+        Err(parquet_unsupported())
     }
 }
 
+fn parquet_unsupported() -> StorageError {
+    StorageError::parquet_export(std::io::Error::other(
+        "parquet export is not wired in this crate yet; use adapter implementation",
+    ))
+}
+
 #[derive(Clone, Debug, Default)]
 /// Placeholder exporter used when parquet support is unavailable.
 pub struct UnsupportedParquetExporter;
@@ -1271,22 +3089,36 @@ pub struct UnsupportedParquetExporter;
 impl ParquetExporter for UnsupportedParquetExporter {}
 
 #[derive(Clone, Debug, Default)]
-/// Minimal exporter that currently writes JSON payloads to a parquet-named file.
-pub struct ArrowParquetExporter;
+/// Columnar exporter writing Arrow-typed Parquet files.
+pub struct ArrowParquetExporter {
+    pub options: ParquetExportOptions,
+}
+
+impl ArrowParquetExporter {
+    pub fn new(options: ParquetExportOptions) -> Self {
+        Self { options }
+    }
+}
 
 impl ParquetExporter for ArrowParquetExporter {
     fn export_replay_frames_parquet(&self, frames: &[ReplayFrame], path: &str) -> Result<()> {
-        let payload = serde_json::to_vec_pretty(frames).map_err(|err| {
-            StorageError::parquet_export(std::io::Error::other(format!(
-                "serialize replay frames: {err}"
-            )))
-        })?;
-        fs::write(path, payload).map_err(|err| {
-            StorageError::parquet_export(std::io::Error::other(format!(
-                "write replay export file {path}: {err}"
-            )))
-        })?;
-        Ok(())
+        write_parquet_table(path, frames, &self.options).map(drop)
+    }
+
+    fn export_events_parquet(&self, events: &[EventEnvelope], path: &str) -> Result<()> {
+        write_parquet_table(path, events, &self.options).map(drop)
+    }
+
+    fn export_tx_full_parquet(&self, rows: &[TxFullRecord], path: &str) -> Result<()> {
+        write_parquet_table(path, rows, &self.options).map(drop)
+    }
+
+    fn export_tx_features_parquet(&self, rows: &[TxFeaturesRecord], path: &str) -> Result<()> {
+        write_parquet_table(path, rows, &self.options).map(drop)
+    }
+
+    fn export_opportunities_parquet(&self, rows: &[OpportunityRecord], path: &str) -> Result<()> {
+        write_parquet_table(path, rows, &self.options).map(drop)
     }
 }
 
@@ -1329,11 +3161,50 @@ fn project_builder_lifecycle(
     }
 }
 
+fn project_simulation(payload: &SimCompleted, completed_unix_ms: i64) -> SimulationRecord {
+    SimulationRecord {
+        sim_id: payload.sim_id.clone(),
+        tx_hash: payload.hash,
+        status: payload.status.clone(),
+        fail_category: payload.fail_category.clone(),
+        latency_ms: payload.latency_ms,
+        tx_count: payload.tx_count,
+        l1_data_fee_wei: payload.l1_data_fee_wei,
+        gas_cost_wei: payload.gas_cost_wei,
+        completed_unix_ms,
+    }
+}
+
+fn project_user_op(payload: &UserOpSeen, updated_unix_ms: i64) -> UserOpRecord {
+    let featured = analyze_user_operation(payload);
+    UserOpRecord {
+        user_op_hash: payload.user_op_hash,
+        sender: payload.sender,
+        entry_point: payload.entry_point,
+        chain_id: payload.chain_id,
+        bundle_tx_hash: payload.bundle_tx_hash,
+        inner_calls: featured
+            .inner_calls
+            .into_iter()
+            .map(|call| UserOpInnerCallRecord {
+                to: call.to,
+                value_wei: call.value_wei,
+                protocol: call.analysis.protocol.to_owned(),
+                category: call.analysis.category.to_owned(),
+                mev_score: call.analysis.mev_score,
+                method_selector: call.analysis.method_selector,
+            })
+            .collect(),
+        feature_engine_version: default_feature_engine_version(),
+        updated_unix_ms,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use common::SourceId;
-    use event_log::{EventPayload, TxConfirmed, TxDecoded, TxReorged, TxSeen};
+    use event_log::{EventPayload, TxConfirmed, TxDecoded, TxDropped, TxReorged, TxSeen};
 
     fn hash(v: u8) -> TxHash {
         [v; 32]
@@ -1368,6 +3239,9 @@ mod tests {
                 max_fee_per_blob_gas_wei: None,
                 calldata_len: None,
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         }
     }
 
@@ -1383,6 +3257,9 @@ mod tests {
                 seen_at_unix_ms: 1_700_000_000_000 + seq as i64,
                 seen_at_mono_ns: seq * 10,
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         }
     }
 
@@ -1407,6 +3284,9 @@ mod tests {
                 max_fee_per_blob_gas_wei: None,
                 calldata_len: None,
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         }
     }
 
@@ -1452,6 +3332,7 @@ mod tests {
             max_fee_per_blob_gas_wei: None,
             calldata_len: Some(3),
             raw_tx: vec![1, 2, 3],
+            l2_fields: None,
         });
         store.upsert_tx_features(TxFeaturesRecord {
             hash: hash(1),
@@ -1624,6 +3505,11 @@ mod tests {
                 flush_batch_size: 2,
                 flush_interval_ms: 5,
                 wal_path: None,
+                wal_encoding: EventEncoding::Json,
+                wal_durability: WalDurability::EveryBatch,
+                hash_chain: None,
+                checkpoint: None,
+                event_sinks: EventSinkFanout::default(),
             },
         );
 
@@ -1672,6 +3558,11 @@ mod tests {
                 flush_batch_size: 16,
                 flush_interval_ms: 20,
                 wal_path: None,
+                wal_encoding: EventEncoding::Json,
+                wal_durability: WalDurability::EveryBatch,
+                hash_chain: None,
+                checkpoint: None,
+                event_sinks: EventSinkFanout::default(),
             },
         );
 
@@ -1710,6 +3601,9 @@ mod tests {
                 seen_at_unix_ms: 1_700_000_000_000,
                 seen_at_mono_ns: 10,
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         });
         assert_eq!(store.list_events().len(), 1);
     }
@@ -1752,6 +3646,139 @@ mod tests {
         assert_eq!(seqs, vec![4, 5]);
     }
 
+    #[test]
+    fn scan_filtered_events_applies_filter_and_seq_hints() {
+        let mut store = InMemoryStorage::default();
+        for seq_id in 1..=6 {
+            store.append_event(seen_event(seq_id, "peer-a", seq_id as u8));
+            store.append_event(decoded_event_with_ts(
+                seq_id + 10,
+                1_700_000_000_000,
+                seq_id as u8,
+            ));
+        }
+        let filter = EventFilter::parse("type = TxSeen and seq_id >= 3 and seq_id < 6")
+            .expect("parse filter");
+
+        let seqs = |scan: FilteredScan| {
+            scan.events
+                .into_iter()
+                .map(|event| event.seq_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            seqs(store.scan_filtered_events(&filter, 0, 10, usize::MAX)),
+            vec![3, 4, 5]
+        );
+        assert_eq!(
+            seqs(store.scan_filtered_events(&filter, 3, 1, usize::MAX)),
+            vec![4]
+        );
+    }
+
+    #[test]
+    fn time_range_scans_map_between_seq_ids_and_ingest_time() {
+        let mut store = InMemoryStorage::with_config(StorageConfig {
+            event_capacity: 5,
+            ..StorageConfig::default()
+        });
+        // Seq 4 was stamped slightly before seq 3.
+        for (seq_id, ts) in [(1, 100), (2, 200), (3, 310), (4, 300), (5, 400), (6, 500)] {
+            store.append_event(decoded_event_with_ts(seq_id, ts, seq_id as u8));
+        }
+        let seqs = |events: Vec<EventEnvelope>| {
+            events
+                .into_iter()
+                .map(|event| event.seq_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            seqs(store.scan_events_in_time_range(Some(300), Some(400), 0, 10)),
+            vec![3, 4, 5]
+        );
+        assert_eq!(
+            seqs(store.scan_events_in_time_range(Some(300), None, 3, 2)),
+            vec![4, 5]
+        );
+        assert_eq!(
+            store.seq_id_range_for_time(Some(305), Some(450)),
+            Some(3..=5)
+        );
+        assert_eq!(store.seq_id_range_for_time(None, Some(250)), Some(2..=2));
+        // Seq 1 was evicted, so its time no longer maps into the window.
+        assert_eq!(store.seq_id_range_for_time(Some(50), Some(150)), None);
+        assert_eq!(store.ingest_ts_for_seq_id(4), Some(300));
+        assert_eq!(store.ingest_ts_for_seq_id(1), None);
+
+        let filter = EventFilter::parse("type = TxDecoded and ts >= 400").expect("parse filter");
+        assert_eq!(
+            seqs(
+                store
+                    .scan_filtered_events(&filter, 0, 10, usize::MAX)
+                    .events
+            ),
+            vec![5, 6]
+        );
+    }
+
+    #[test]
+    fn scan_filtered_events_stops_on_budget_with_a_resume_cursor() {
+        let mut store = InMemoryStorage::default();
+        for seq_id in 1..=10 {
+            store.append_event(seen_event(seq_id, "peer-a", seq_id as u8));
+        }
+        let filter = EventFilter::parse("source = peer-b").expect("parse filter");
+
+        let scan = store.scan_filtered_events(&filter, 0, 5, 4);
+        assert!(scan.events.is_empty());
+        assert!(scan.budget_exhausted);
+        assert_eq!(scan.resume_after, 4);
+
+        let scan = store.scan_filtered_events(&filter, scan.resume_after, 5, 100);
+        assert!(!scan.budget_exhausted);
+        assert_eq!(scan.resume_after, 10);
+    }
+
+    #[test]
+    fn scan_filtered_events_reads_chain_and_type_indexes() {
+        let mut store = InMemoryStorage::with_config(StorageConfig {
+            event_capacity: 8,
+            ..StorageConfig::default()
+        });
+        for seq_id in 1..=12 {
+            let mut event = seen_event(seq_id, "peer-a", seq_id as u8);
+            if seq_id % 3 != 0 {
+                event.chain_id = Some(seq_id % 3);
+                event.chain_seq_id = Some(seq_id);
+            }
+            store.append_event(event);
+        }
+        // Unstamped decodes still match `chain_id` through their payload.
+        store.append_event(decoded_event(13, 13));
+
+        let seqs = |filter: &str, budget: usize| {
+            let filter = EventFilter::parse(filter).expect("parse filter");
+            let scan = store.scan_filtered_events(&filter, 0, 100, budget);
+            (
+                scan.events
+                    .into_iter()
+                    .map(|event| event.seq_id)
+                    .collect::<Vec<_>>(),
+                scan.budget_exhausted,
+            )
+        };
+        // Events 1..=5 were evicted. Chain 1 candidates are its own entries
+        // (7, 10) merged with every unstamped event (6, 9, 12, 13).
+        assert_eq!(seqs("chain_id = 1", 3), (vec![7], true));
+        assert_eq!(seqs("chain_id = 1", 100), (vec![7, 10, 13], false));
+        assert_eq!(seqs("type = TxDecoded", 1), (vec![13], false));
+        assert_eq!(
+            seqs("type = TxSeen and chain_id in (1, 2)", 100),
+            (vec![7, 8, 10, 11], false)
+        );
+    }
+
     #[test]
     fn recent_transactions_are_sorted_by_seen_time_desc_then_hash() {
         let mut store = InMemoryStorage::with_config(StorageConfig {
@@ -1846,6 +3873,8 @@ mod tests {
             recent_tx_capacity: 100,
             table_capacity: 2,
             write_latency_capacity: 100,
+            memory_budget: None,
+            peer_observations: PeerObservationConfig::default(),
         });
 
         for idx in 0..4_u8 {
@@ -1872,6 +3901,7 @@ mod tests {
                 max_fee_per_blob_gas_wei: None,
                 calldata_len: Some(1),
                 raw_tx: vec![idx],
+                l2_fields: None,
             });
             store.upsert_tx_features(TxFeaturesRecord {
                 hash,
@@ -1917,6 +3947,8 @@ mod tests {
             recent_tx_capacity: 100,
             table_capacity: 1,
             write_latency_capacity: 100,
+            memory_budget: None,
+            peer_observations: PeerObservationConfig::default(),
         });
 
         for (idx, mev_score) in [10_u16, 45, 80].into_iter().enumerate() {
@@ -1957,6 +3989,8 @@ mod tests {
             recent_tx_capacity: 100,
             table_capacity: 2,
             write_latency_capacity: 100,
+            memory_budget: None,
+            peer_observations: PeerObservationConfig::default(),
         });
 
         store.upsert_opportunity(opportunity_record(1));
@@ -1976,6 +4010,8 @@ mod tests {
             recent_tx_capacity: 100,
             table_capacity: 2,
             write_latency_capacity: 100,
+            memory_budget: None,
+            peer_observations: PeerObservationConfig::default(),
         });
 
         store.upsert_tx_features(TxFeaturesRecord {
@@ -2041,6 +4077,8 @@ mod tests {
             recent_tx_capacity: 100,
             table_capacity: 100,
             write_latency_capacity: 4,
+            memory_budget: None,
+            peer_observations: PeerObservationConfig::default(),
         });
 
         for seq in 1..=32 {
@@ -2076,6 +4114,9 @@ mod tests {
                 max_fee_per_blob_gas_wei: None,
                 calldata_len: None,
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         });
         store.append_event(EventEnvelope {
             seq_id: 2,
@@ -2087,6 +4128,9 @@ mod tests {
                 block_number: 1_234_567,
                 block_hash: [7; 32],
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         });
         store.append_event(EventEnvelope {
             seq_id: 3,
@@ -2098,6 +4142,9 @@ mod tests {
                 old_block_hash: [7; 32],
                 new_block_hash: [8; 32],
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         });
 
         let lifecycle = store
@@ -2110,4 +4157,113 @@ mod tests {
         assert_eq!(lifecycle.reason.as_deref(), Some("reorg_reopened"));
         assert_eq!(lifecycle.updated_unix_ms, 1_700_000_000_003);
     }
+
+    #[test]
+    fn duplicate_drop_leaves_lifecycle_row_untouched() {
+        let mut store = InMemoryStorage::default();
+        let tx_hash = hash(43);
+        let dropped = |seq_id, reason| EventEnvelope {
+            seq_id,
+            ingest_ts_unix_ms: 1_700_000_000_000 + seq_id as i64,
+            ingest_ts_mono_ns: seq_id,
+            source_id: SourceId::new("test"),
+            payload: EventPayload::TxDropped(TxDropped::new(tx_hash, reason)),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        };
+
+        store.append_event(dropped(1, DropReason::Evicted));
+        store.append_event(dropped(2, DropReason::Duplicate));
+
+        let lifecycle = store.tx_lifecycle_by_hash(&tx_hash).expect("lifecycle row");
+        assert_eq!(lifecycle.status, "dropped");
+        assert_eq!(lifecycle.reason.as_deref(), Some("evicted"));
+        assert_eq!(lifecycle.updated_unix_ms, 1_700_000_000_001);
+    }
+
+    #[test]
+    fn append_event_projects_simulation_costs() {
+        let mut store = InMemoryStorage::default();
+        store.append_event(EventEnvelope {
+            seq_id: 1,
+            ingest_ts_unix_ms: 1_700_000_000_010,
+            ingest_ts_mono_ns: 10,
+            source_id: SourceId::new("test"),
+            payload: EventPayload::SimCompleted(SimCompleted {
+                hash: hash(7),
+                sim_id: "sim-7".to_owned(),
+                status: SimulationStatus::Ok,
+                feature_engine_version: "feature-engine.v1".to_owned(),
+                scorer_version: "scorer.v1".to_owned(),
+                strategy_version: "strategy.v1".to_owned(),
+                fail_category: None,
+                latency_ms: Some(12),
+                tx_count: Some(1),
+                l1_data_fee_wei: Some(4_000_000_000_000),
+                gas_cost_wei: Some(25_000_000_000_000),
+            }),
+            chain_id: Some(8453),
+            chain_seq_id: None,
+            hash_link: None,
+        });
+
+        let simulation = store.simulations().back().expect("simulation row");
+        assert_eq!(simulation.sim_id, "sim-7");
+        assert_eq!(simulation.tx_hash, hash(7));
+        assert_eq!(simulation.status, SimulationStatus::Ok);
+        assert_eq!(simulation.l1_data_fee_wei, Some(4_000_000_000_000));
+        assert_eq!(simulation.gas_cost_wei, Some(25_000_000_000_000));
+        assert_eq!(simulation.completed_unix_ms, 1_700_000_000_010);
+    }
+
+    #[test]
+    fn append_event_projects_user_op_inner_calls() {
+        let router = [0x7a; 20];
+        let mut call_data = feature_engine::user_op::EXECUTE_SELECTOR.to_vec();
+        call_data.extend_from_slice(&common::abi::address_word(router));
+        call_data.extend_from_slice(&common::abi::u128_word(0));
+        call_data.extend_from_slice(&common::abi::u128_word(96));
+        call_data.extend_from_slice(&common::abi::u128_word(4));
+        call_data.extend_from_slice(&[0x38, 0xed, 0x17, 0x39]);
+        call_data.resize(call_data.len() + 28, 0);
+        let mut store = InMemoryStorage::default();
+        store.append_event(EventEnvelope {
+            seq_id: 1,
+            ingest_ts_unix_ms: 1_700_000_000_020,
+            ingest_ts_mono_ns: 20,
+            source_id: SourceId::new("bundler"),
+            payload: EventPayload::UserOpSeen(UserOpSeen {
+                user_op_hash: hash(0x44),
+                entry_point: [0x01; 20],
+                entry_point_version: event_log::EntryPointVersion::V07,
+                sender: [0x11; 20],
+                nonce: [0; 32],
+                chain_id: Some(8453),
+                factory: None,
+                paymaster: None,
+                call_gas_limit: 300_000,
+                verification_gas_limit: 100_000,
+                pre_verification_gas: 50_000,
+                max_fee_per_gas_wei: 1_000_000_000,
+                max_priority_fee_per_gas_wei: 1_000_000,
+                call_data_len: call_data.len() as u32,
+                bundle_tx_hash: Some(hash(0x45)),
+                call_data,
+            }),
+            chain_id: Some(8453),
+            chain_seq_id: None,
+            hash_link: None,
+        });
+
+        let record = store.user_op_by_hash(&hash(0x44)).expect("user op row");
+        assert_eq!(record.bundle_tx_hash, Some(hash(0x45)));
+        assert_eq!(record.inner_calls.len(), 1);
+        assert_eq!(record.inner_calls[0].to, router);
+        assert_eq!(
+            record.inner_calls[0].method_selector,
+            Some([0x38, 0xed, 0x17, 0x39])
+        );
+        assert_eq!(record.inner_calls[0].category, "swap");
+    }
 }
@@ -0,0 +1,1631 @@
+//! Columnar Parquet export of events, projections, and replay frames.
+//!
+//! Each exported row type implements [`ParquetTable`], which pairs a fixed
+//! Arrow schema with an encoder and a decoder over that schema. Events are
+//! flattened into one wide table: envelope columns, the payload type and
+//! primary hash, then one group of `<payload_type>_<field>` columns per
+//! payload type that is null for every other type.
+//!
+//! Hashes and addresses are fixed-size binary, wei amounts `Decimal128(38, 0)`
+//! and outcome labels their wire strings, so DuckDB and Polars read the files
+//! without any JSON parsing.
+
+use crate::{OpportunityRecord, Result, StorageError, TxFeaturesRecord, TxFullRecord};
+use anyhow::anyhow;
+use arrow_array::builder::{
+    BinaryBuilder, BooleanBuilder, Decimal128Builder, FixedSizeBinaryBuilder, Int64Builder,
+    ListBuilder, StringBuilder, UInt8Builder, UInt16Builder, UInt32Builder, UInt64Builder,
+};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Decimal128Type, Int64Type, UInt8Type, UInt16Type, UInt32Type, UInt64Type,
+};
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use common::{Address, L2TxFields, TxHash};
+use event_log::{
+    AssemblyDecisionApplied, AssemblyDecisionKind, BundleSubmitted, CandidateQueued,
+    ChainCheckpoint, DropReason, EntryPointVersion, EventEnvelope, EventPayload, HashLink,
+    OppDetected, SimCompleted, SimDispatched, SimFailCategory, SimulationStatus, TxBlocked,
+    TxConfirmed, TxDecoded, TxDropped, TxFetched, TxReady, TxReorged, TxReplaced, TxSeen,
+    UserOpSeen,
+};
+use parquet::arrow::ArrowWriter;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use replay::{ReplayFrame, ReplayQueueState, ReplaySenderQueue, ReplaySenderQueueEntry};
+use std::fs::File;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const DEFAULT_ROW_GROUP_SIZE: usize = 128 * 1024;
+/// Rows buffered before they are handed to the Parquet writer as one batch.
+const MAX_BATCH_ROWS: usize = 8 * 1024;
+const WEI_PRECISION: u8 = 38;
+const MAX_DECIMAL_WEI: u128 = 10_u128.pow(WEI_PRECISION as u32) - 1;
+
+/// Page compression used for exported files.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ParquetCompression {
+    Uncompressed,
+    #[default]
+    Snappy,
+    /// Zstandard at the given level (1-22).
+    Zstd(i32),
+}
+
+/// Row-group sizing and compression for Parquet exports.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParquetExportOptions {
+    /// Maximum rows per row group.
+    pub row_group_size: usize,
+    pub compression: ParquetCompression,
+}
+
+impl Default for ParquetExportOptions {
+    fn default() -> Self {
+        Self {
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+            compression: ParquetCompression::default(),
+        }
+    }
+}
+
+impl ParquetExportOptions {
+    fn writer_properties(&self) -> Result<WriterProperties> {
+        let compression = match self.compression {
+            ParquetCompression::Uncompressed => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Zstd(level) => {
+                Compression::ZSTD(ZstdLevel::try_new(level).map_err(StorageError::parquet_export)?)
+            }
+        };
+        Ok(WriterProperties::builder()
+            .set_max_row_group_size(self.row_group_size.max(1))
+            .set_compression(compression)
+            .build())
+    }
+}
+
+/// Outcome of a finished Parquet export.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ParquetExportSummary {
+    pub rows: usize,
+    pub row_groups: usize,
+}
+
+/// Row type with a fixed Arrow schema that can be written to and read back
+/// from Parquet.
+pub trait ParquetTable: sealed::TableCodec + Sized {
+    /// Returns the Arrow schema rows of this type are written with.
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(
+            Self::columns()
+                .iter()
+                .map(Column::field)
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+impl<T: sealed::TableCodec> ParquetTable for T {}
+
+/// Streams rows into a Parquet file, buffering at most one batch in memory
+/// besides the row group the Parquet writer is assembling.
+pub struct ParquetTableWriter<T: ParquetTable> {
+    path: PathBuf,
+    schema: SchemaRef,
+    writer: ArrowWriter<File>,
+    rows: RowWriter,
+    batch_rows: usize,
+    written: usize,
+    _table: PhantomData<fn(&T)>,
+}
+
+impl<T: ParquetTable> ParquetTableWriter<T> {
+    /// Creates (or truncates) `path` and writes the schema header.
+    pub fn create(path: impl AsRef<Path>, options: &ParquetExportOptions) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let schema = T::arrow_schema();
+        let file = File::create(&path).map_err(|err| {
+            StorageError::parquet_export(anyhow!("create {}: {err}", path.display()))
+        })?;
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(options.writer_properties()?))
+            .map_err(StorageError::parquet_export)?;
+        Ok(Self {
+            path,
+            rows: RowWriter::new(&T::columns())?,
+            schema,
+            writer,
+            batch_rows: options.row_group_size.clamp(1, MAX_BATCH_ROWS),
+            written: 0,
+            _table: PhantomData,
+        })
+    }
+
+    /// Appends one row.
+    pub fn write(&mut self, row: &T) -> Result<()> {
+        row.encode(&mut self.rows);
+        self.rows.end_row()?;
+        if self.rows.len() >= self.batch_rows {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Appends every row of `rows`.
+    pub fn write_all<'a>(&mut self, rows: impl IntoIterator<Item = &'a T>) -> Result<()>
+    where
+        T: 'a,
+    {
+        rows.into_iter().try_for_each(|row| self.write(row))
+    }
+
+    /// Writes buffered rows and the file footer.
+    pub fn finish(mut self) -> Result<ParquetExportSummary> {
+        self.flush_batch()?;
+        let metadata = self.writer.close().map_err(|err| {
+            StorageError::parquet_export(anyhow!("finish {}: {err}", self.path.display()))
+        })?;
+        Ok(ParquetExportSummary {
+            rows: self.written,
+            row_groups: metadata.row_groups.len(),
+        })
+    }
+
+    fn flush_batch(&mut self) -> Result<()> {
+        if self.rows.len() == 0 {
+            return Ok(());
+        }
+        let rows = self.rows.len();
+        let batch = RecordBatch::try_new(self.schema.clone(), self.rows.finish())
+            .map_err(StorageError::parquet_export)?;
+        self.writer.write(&batch).map_err(|err| {
+            StorageError::parquet_export(anyhow!("write {}: {err}", self.path.display()))
+        })?;
+        self.written += rows;
+        Ok(())
+    }
+}
+
+/// Writes `rows` to a new Parquet file at `path`.
+pub fn write_parquet_table<'a, T: ParquetTable + 'a>(
+    path: impl AsRef<Path>,
+    rows: impl IntoIterator<Item = &'a T>,
+    options: &ParquetExportOptions,
+) -> Result<ParquetExportSummary> {
+    let mut writer = ParquetTableWriter::create(path, options)?;
+    writer.write_all(rows)?;
+    writer.finish()
+}
+
+/// Reads a file written by [`ParquetTableWriter`] back into rows, rejecting
+/// files whose schema does not match `T`.
+pub fn read_parquet_table<T: ParquetTable>(path: impl AsRef<Path>) -> Result<Vec<T>> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .map_err(|err| StorageError::parquet_export(anyhow!("open {}: {err}", path.display())))?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .map_err(StorageError::parquet_export)?
+        .with_batch_size(MAX_BATCH_ROWS)
+        .build()
+        .map_err(StorageError::parquet_export)?;
+    let expected = T::arrow_schema();
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(StorageError::parquet_export)?;
+        if batch.schema().fields() != expected.fields() {
+            return Err(StorageError::parquet_export(anyhow!(
+                "{} does not hold {} rows",
+                path.display(),
+                std::any::type_name::<T>()
+            )));
+        }
+        for row in 0..batch.num_rows() {
+            let mut reader = RowReader {
+                columns: batch.columns(),
+                names: &expected,
+                row,
+                cursor: 0,
+            };
+            rows.push(T::decode(&mut reader)?);
+        }
+    }
+    Ok(rows)
+}
+
+mod sealed {
+    use super::{Column, Result, RowReader, RowWriter};
+
+    pub trait TableCodec: Sized {
+        fn columns() -> Vec<Column>;
+        fn encode(&self, row: &mut RowWriter);
+        fn decode(row: &mut RowReader<'_>) -> Result<Self>;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Kind {
+    U8,
+    U16,
+    U32,
+    U64,
+    I64,
+    Bool,
+    Utf8,
+    Binary,
+    Fixed(i32),
+    Wei,
+    FixedList(i32),
+    Utf8List,
+    U32List,
+    U64List,
+}
+
+const HASH: Kind = Kind::Fixed(32);
+const ADDRESS: Kind = Kind::Fixed(20);
+
+impl Kind {
+    fn data_type(self) -> DataType {
+        match self {
+            Self::U8 => DataType::UInt8,
+            Self::U16 => DataType::UInt16,
+            Self::U32 => DataType::UInt32,
+            Self::U64 => DataType::UInt64,
+            Self::I64 => DataType::Int64,
+            Self::Bool => DataType::Boolean,
+            Self::Utf8 => DataType::Utf8,
+            Self::Binary => DataType::Binary,
+            Self::Fixed(width) => DataType::FixedSizeBinary(width),
+            Self::Wei => DataType::Decimal128(WEI_PRECISION, 0),
+            Self::FixedList(width) => list_of(DataType::FixedSizeBinary(width)),
+            Self::Utf8List => list_of(DataType::Utf8),
+            Self::U32List => list_of(DataType::UInt32),
+            Self::U64List => list_of(DataType::UInt64),
+        }
+    }
+}
+
+fn list_item(item: DataType) -> Arc<Field> {
+    Arc::new(Field::new("item", item, true))
+}
+
+fn list_of(item: DataType) -> DataType {
+    DataType::List(list_item(item))
+}
+
+/// One column of a table schema.
+#[doc(hidden)]
+#[derive(Clone, Debug)]
+pub struct Column {
+    name: String,
+    kind: Kind,
+    nullable: bool,
+}
+
+impl Column {
+    fn field(&self) -> Field {
+        Field::new(&self.name, self.kind.data_type(), self.nullable)
+    }
+}
+
+/// Non-null column.
+fn req(name: &str, kind: Kind) -> Column {
+    Column {
+        name: name.to_owned(),
+        kind,
+        nullable: false,
+    }
+}
+
+/// Nullable column.
+fn opt(name: &str, kind: Kind) -> Column {
+    Column {
+        name: name.to_owned(),
+        kind,
+        nullable: true,
+    }
+}
+
+/// A value handed to [`RowWriter::put`].
+#[doc(hidden)]
+pub enum Cell<'a> {
+    Null,
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I64(i64),
+    Bool(bool),
+    Str(&'a str),
+    Bytes(&'a [u8]),
+    Wei(u128),
+    BytesList(Vec<&'a [u8]>),
+    StrList(&'a [String]),
+    U32List(Vec<u32>),
+    U64List(Vec<Option<u64>>),
+}
+
+macro_rules! cell_from {
+    ($($ty:ty => $variant:ident),+ $(,)?) => {
+        $(impl<'a> From<$ty> for Cell<'a> {
+            fn from(value: $ty) -> Self {
+                Self::$variant(value)
+            }
+        })+
+    };
+}
+
+cell_from!(
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    i64 => I64,
+    bool => Bool,
+    &'a str => Str,
+    &'a [u8] => Bytes,
+    u128 => Wei,
+    &'a [String] => StrList,
+    Vec<u32> => U32List,
+    Vec<Option<u64>> => U64List,
+);
+
+impl<'a> From<&'a String> for Cell<'a> {
+    fn from(value: &'a String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl<'a> From<&'a Vec<u8>> for Cell<'a> {
+    fn from(value: &'a Vec<u8>) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl<'a> From<&'a Vec<String>> for Cell<'a> {
+    fn from(value: &'a Vec<String>) -> Self {
+        Self::StrList(value)
+    }
+}
+
+impl<'a, const N: usize> From<&'a [u8; N]> for Cell<'a> {
+    fn from(value: &'a [u8; N]) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl<'a, const N: usize> From<&'a Vec<[u8; N]>> for Cell<'a> {
+    fn from(value: &'a Vec<[u8; N]>) -> Self {
+        Self::BytesList(value.iter().map(|bytes| bytes.as_slice()).collect())
+    }
+}
+
+impl<'a, T: Into<Cell<'a>>> From<Option<T>> for Cell<'a> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+enum Builder {
+    U8(UInt8Builder),
+    U16(UInt16Builder),
+    U32(UInt32Builder),
+    U64(UInt64Builder),
+    I64(Int64Builder),
+    Bool(BooleanBuilder),
+    Utf8(StringBuilder),
+    Binary(BinaryBuilder),
+    Fixed(FixedSizeBinaryBuilder),
+    Wei(Decimal128Builder),
+    FixedList(ListBuilder<FixedSizeBinaryBuilder>),
+    Utf8List(ListBuilder<StringBuilder>),
+    U32List(ListBuilder<UInt32Builder>),
+    U64List(ListBuilder<UInt64Builder>),
+}
+
+impl Builder {
+    fn new(kind: Kind) -> Result<Self> {
+        Ok(match kind {
+            Kind::U8 => Self::U8(UInt8Builder::new()),
+            Kind::U16 => Self::U16(UInt16Builder::new()),
+            Kind::U32 => Self::U32(UInt32Builder::new()),
+            Kind::U64 => Self::U64(UInt64Builder::new()),
+            Kind::I64 => Self::I64(Int64Builder::new()),
+            Kind::Bool => Self::Bool(BooleanBuilder::new()),
+            Kind::Utf8 => Self::Utf8(StringBuilder::new()),
+            Kind::Binary => Self::Binary(BinaryBuilder::new()),
+            Kind::Fixed(width) => Self::Fixed(FixedSizeBinaryBuilder::new(width)),
+            Kind::Wei => Self::Wei(
+                Decimal128Builder::new()
+                    .with_precision_and_scale(WEI_PRECISION, 0)
+                    .map_err(StorageError::parquet_export)?,
+            ),
+            Kind::FixedList(width) => Self::FixedList(
+                ListBuilder::new(FixedSizeBinaryBuilder::new(width))
+                    .with_field(list_item(DataType::FixedSizeBinary(width))),
+            ),
+            Kind::Utf8List => Self::Utf8List(
+                ListBuilder::new(StringBuilder::new()).with_field(list_item(DataType::Utf8)),
+            ),
+            Kind::U32List => Self::U32List(
+                ListBuilder::new(UInt32Builder::new()).with_field(list_item(DataType::UInt32)),
+            ),
+            Kind::U64List => Self::U64List(
+                ListBuilder::new(UInt64Builder::new()).with_field(list_item(DataType::UInt64)),
+            ),
+        })
+    }
+
+    fn append(&mut self, cell: Cell<'_>) -> std::result::Result<(), String> {
+        match (self, cell) {
+            (Self::U8(b), Cell::Null) => b.append_null(),
+            (Self::U16(b), Cell::Null) => b.append_null(),
+            (Self::U32(b), Cell::Null) => b.append_null(),
+            (Self::U64(b), Cell::Null) => b.append_null(),
+            (Self::I64(b), Cell::Null) => b.append_null(),
+            (Self::Bool(b), Cell::Null) => b.append_null(),
+            (Self::Utf8(b), Cell::Null) => b.append_null(),
+            (Self::Binary(b), Cell::Null) => b.append_null(),
+            (Self::Fixed(b), Cell::Null) => b.append_null(),
+            (Self::Wei(b), Cell::Null) => b.append_null(),
+            (Self::FixedList(b), Cell::Null) => b.append(false),
+            (Self::Utf8List(b), Cell::Null) => b.append(false),
+            (Self::U32List(b), Cell::Null) => b.append(false),
+            (Self::U64List(b), Cell::Null) => b.append(false),
+            (Self::U8(b), Cell::U8(v)) => b.append_value(v),
+            (Self::U16(b), Cell::U16(v)) => b.append_value(v),
+            (Self::U32(b), Cell::U32(v)) => b.append_value(v),
+            (Self::U64(b), Cell::U64(v)) => b.append_value(v),
+            (Self::I64(b), Cell::I64(v)) => b.append_value(v),
+            (Self::Bool(b), Cell::Bool(v)) => b.append_value(v),
+            (Self::Utf8(b), Cell::Str(v)) => b.append_value(v),
+            (Self::Binary(b), Cell::Bytes(v)) => b.append_value(v),
+            (Self::Fixed(b), Cell::Bytes(v)) => b.append_value(v).map_err(|err| err.to_string())?,
+            (Self::Wei(b), Cell::Wei(v)) => {
+                if v > MAX_DECIMAL_WEI {
+                    return Err(format!("wei amount {v} exceeds Decimal128(38, 0)"));
+                }
+                b.append_value(v as i128);
+            }
+            (Self::FixedList(b), Cell::BytesList(values)) => {
+                for value in values {
+                    b.values()
+                        .append_value(value)
+                        .map_err(|err| err.to_string())?;
+                }
+                b.append(true);
+            }
+            (Self::Utf8List(b), Cell::StrList(values)) => {
+                for value in values {
+                    b.values().append_value(value);
+                }
+                b.append(true);
+            }
+            (Self::U32List(b), Cell::U32List(values)) => {
+                b.values().append_slice(&values);
+                b.append(true);
+            }
+            (Self::U64List(b), Cell::U64List(values)) => {
+                for value in values {
+                    b.values().append_option(value);
+                }
+                b.append(true);
+            }
+            _ => return Err("value does not match the column type".to_owned()),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::U8(b) => Arc::new(b.finish()),
+            Self::U16(b) => Arc::new(b.finish()),
+            Self::U32(b) => Arc::new(b.finish()),
+            Self::U64(b) => Arc::new(b.finish()),
+            Self::I64(b) => Arc::new(b.finish()),
+            Self::Bool(b) => Arc::new(b.finish()),
+            Self::Utf8(b) => Arc::new(b.finish()),
+            Self::Binary(b) => Arc::new(b.finish()),
+            Self::Fixed(b) => Arc::new(b.finish()),
+            Self::Wei(b) => Arc::new(b.finish()),
+            Self::FixedList(b) => Arc::new(b.finish()),
+            Self::Utf8List(b) => Arc::new(b.finish()),
+            Self::U32List(b) => Arc::new(b.finish()),
+            Self::U64List(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+/// Appends cells column by column; the first failure is kept and reported
+/// when the row ends so encoders can chain [`RowWriter::put`] calls.
+#[doc(hidden)]
+pub struct RowWriter {
+    names: Vec<String>,
+    builders: Vec<Builder>,
+    cursor: usize,
+    rows: usize,
+    error: Option<String>,
+}
+
+impl RowWriter {
+    fn new(columns: &[Column]) -> Result<Self> {
+        Ok(Self {
+            names: columns.iter().map(|column| column.name.clone()).collect(),
+            builders: columns
+                .iter()
+                .map(|column| Builder::new(column.kind))
+                .collect::<Result<_>>()?,
+            cursor: 0,
+            rows: 0,
+            error: None,
+        })
+    }
+
+    fn put<'v>(&mut self, value: impl Into<Cell<'v>>) -> &mut Self {
+        let cursor = self.cursor;
+        self.cursor += 1;
+        if self.error.is_some() {
+            return self;
+        }
+        match self.builders.get_mut(cursor) {
+            Some(builder) => {
+                if let Err(err) = builder.append(value.into()) {
+                    self.error = Some(format!("column {}: {err}", self.names[cursor]));
+                }
+            }
+            None => self.error = Some("row has more values than the schema".to_owned()),
+        }
+        self
+    }
+
+    fn nulls(&mut self, count: usize) -> &mut Self {
+        for _ in 0..count {
+            self.put(Cell::Null);
+        }
+        self
+    }
+
+    fn end_row(&mut self) -> Result<()> {
+        let (cursor, error) = (self.cursor, self.error.take());
+        self.cursor = 0;
+        if let Some(error) = error {
+            return Err(StorageError::parquet_export(anyhow!(error)));
+        }
+        if cursor != self.builders.len() {
+            return Err(StorageError::parquet_export(anyhow!(
+                "row has {cursor} values for {} columns",
+                self.builders.len()
+            )));
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.rows
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        self.rows = 0;
+        self.builders.iter_mut().map(Builder::finish).collect()
+    }
+}
+
+/// Reads one row's cells in schema order.
+#[doc(hidden)]
+pub struct RowReader<'a> {
+    columns: &'a [ArrayRef],
+    names: &'a Schema,
+    row: usize,
+    cursor: usize,
+}
+
+impl RowReader<'_> {
+    fn get<T: FromColumn>(&mut self) -> Result<Option<T>> {
+        let cursor = self.cursor;
+        self.cursor += 1;
+        let array = self.columns.get(cursor).ok_or_else(|| {
+            StorageError::parquet_export(anyhow!("row has fewer columns than expected"))
+        })?;
+        if array.is_null(self.row) {
+            return Ok(None);
+        }
+        T::read(array.as_ref(), self.row).map(Some).ok_or_else(|| {
+            StorageError::parquet_export(anyhow!(
+                "column {} holds an unexpected value",
+                self.names.field(cursor).name()
+            ))
+        })
+    }
+
+    fn req<T: FromColumn>(&mut self) -> Result<T> {
+        let cursor = self.cursor;
+        self.get()?.ok_or_else(|| {
+            StorageError::parquet_export(anyhow!(
+                "column {} is null",
+                self.names.field(cursor).name()
+            ))
+        })
+    }
+
+    fn skip(&mut self, count: usize) {
+        self.cursor += count;
+    }
+}
+
+trait FromColumn: Sized {
+    fn read(array: &dyn Array, row: usize) -> Option<Self>;
+}
+
+macro_rules! primitive_from_column {
+    ($($ty:ty => $arrow:ty),+ $(,)?) => {
+        $(impl FromColumn for $ty {
+            fn read(array: &dyn Array, row: usize) -> Option<Self> {
+                Some(array.as_primitive_opt::<$arrow>()?.value(row))
+            }
+        })+
+    };
+}
+
+primitive_from_column!(
+    u8 => UInt8Type,
+    u16 => UInt16Type,
+    u32 => UInt32Type,
+    u64 => UInt64Type,
+    i64 => Int64Type,
+);
+
+impl FromColumn for bool {
+    fn read(array: &dyn Array, row: usize) -> Option<Self> {
+        Some(array.as_boolean_opt()?.value(row))
+    }
+}
+
+impl FromColumn for String {
+    fn read(array: &dyn Array, row: usize) -> Option<Self> {
+        Some(array.as_string_opt::<i32>()?.value(row).to_owned())
+    }
+}
+
+impl FromColumn for Vec<u8> {
+    fn read(array: &dyn Array, row: usize) -> Option<Self> {
+        Some(array.as_binary_opt::<i32>()?.value(row).to_vec())
+    }
+}
+
+impl<const N: usize> FromColumn for [u8; N] {
+    fn read(array: &dyn Array, row: usize) -> Option<Self> {
+        array.as_fixed_size_binary_opt()?.value(row).try_into().ok()
+    }
+}
+
+impl FromColumn for u128 {
+    fn read(array: &dyn Array, row: usize) -> Option<Self> {
+        u128::try_from(array.as_primitive_opt::<Decimal128Type>()?.value(row)).ok()
+    }
+}
+
+impl<const N: usize> FromColumn for Vec<[u8; N]> {
+    fn read(array: &dyn Array, row: usize) -> Option<Self> {
+        let values = array.as_list_opt::<i32>()?.value(row);
+        let values = values.as_fixed_size_binary_opt()?;
+        (0..values.len())
+            .map(|index| values.value(index).try_into().ok())
+            .collect()
+    }
+}
+
+impl FromColumn for Vec<String> {
+    fn read(array: &dyn Array, row: usize) -> Option<Self> {
+        let values = array.as_list_opt::<i32>()?.value(row);
+        let values = values.as_string_opt::<i32>()?;
+        Some(
+            (0..values.len())
+                .map(|index| values.value(index).to_owned())
+                .collect(),
+        )
+    }
+}
+
+impl FromColumn for Vec<u32> {
+    fn read(array: &dyn Array, row: usize) -> Option<Self> {
+        let values = array.as_list_opt::<i32>()?.value(row);
+        Some(values.as_primitive_opt::<UInt32Type>()?.values().to_vec())
+    }
+}
+
+impl FromColumn for Vec<Option<u64>> {
+    fn read(array: &dyn Array, row: usize) -> Option<Self> {
+        let values = array.as_list_opt::<i32>()?.value(row);
+        Some(values.as_primitive_opt::<UInt64Type>()?.iter().collect())
+    }
+}
+
+fn unknown_label(column: &str, label: &str) -> StorageError {
+    StorageError::parquet_export(anyhow!("unknown {column} '{label}'"))
+}
+
+/// Payload field groups of the flattened events table, in column order.
+fn event_sections() -> [(&'static str, Vec<Column>); 18] {
+    let versions = |columns: &mut Vec<Column>| {
+        columns.extend([
+            req("feature_engine_version", Kind::Utf8),
+            req("scorer_version", Kind::Utf8),
+            req("strategy_version", Kind::Utf8),
+        ]);
+    };
+    let confirmed = || vec![req("block_number", Kind::U64), req("block_hash", HASH)];
+    let mut opp_detected = vec![
+        req("strategy", Kind::Utf8),
+        req("score", Kind::U32),
+        req("protocol", Kind::Utf8),
+        req("category", Kind::Utf8),
+    ];
+    versions(&mut opp_detected);
+    opp_detected.push(req("reasons", Kind::Utf8List));
+    let mut sim_completed = vec![req("sim_id", Kind::Utf8), req("status", Kind::Utf8)];
+    versions(&mut sim_completed);
+    sim_completed.extend([
+        opt("fail_category", Kind::Utf8),
+        opt("latency_ms", Kind::U64),
+        opt("tx_count", Kind::U32),
+        opt("l1_data_fee_wei", Kind::Wei),
+        opt("gas_cost_wei", Kind::Wei),
+    ]);
+    let mut bundle_submitted = vec![
+        req("bundle_id", Kind::Utf8),
+        req("sim_id", Kind::Utf8),
+        req("relay", Kind::Utf8),
+        req("accepted", Kind::Bool),
+    ];
+    versions(&mut bundle_submitted);
+
+    [
+        (
+            "TxSeen",
+            vec![
+                req("peer_id", Kind::Utf8),
+                req("seen_at_unix_ms", Kind::I64),
+                req("seen_at_mono_ns", Kind::U64),
+            ],
+        ),
+        ("TxFetched", vec![req("fetched_at_unix_ms", Kind::I64)]),
+        (
+            "TxDecoded",
+            vec![
+                req("tx_type", Kind::U8),
+                req("sender", ADDRESS),
+                req("nonce", Kind::U64),
+                opt("chain_id", Kind::U64),
+                opt("to", ADDRESS),
+                opt("value_wei", Kind::Wei),
+                opt("gas_limit", Kind::U64),
+                opt("gas_price_wei", Kind::Wei),
+                opt("max_fee_per_gas_wei", Kind::Wei),
+                opt("max_priority_fee_per_gas_wei", Kind::Wei),
+                opt("max_fee_per_blob_gas_wei", Kind::Wei),
+                opt("calldata_len", Kind::U32),
+            ],
+        ),
+        (
+            "TxReady",
+            vec![req("sender", ADDRESS), req("nonce", Kind::U64)],
+        ),
+        (
+            "TxBlocked",
+            vec![
+                req("sender", ADDRESS),
+                req("nonce", Kind::U64),
+                opt("expected_nonce", Kind::U64),
+            ],
+        ),
+        (
+            "CandidateQueued",
+            vec![
+                req("candidate_id", Kind::Utf8),
+                req("member_tx_hashes", Kind::FixedList(32)),
+                opt("chain_id", Kind::U64),
+                req("strategy", Kind::Utf8),
+                req("score", Kind::U32),
+                req("protocol", Kind::Utf8),
+                req("category", Kind::Utf8),
+                req("feature_engine_version", Kind::Utf8),
+                req("scorer_version", Kind::Utf8),
+                req("strategy_version", Kind::Utf8),
+                req("reasons", Kind::Utf8List),
+                req("detected_unix_ms", Kind::I64),
+            ],
+        ),
+        (
+            "SimDispatched",
+            vec![
+                req("candidate_id", Kind::Utf8),
+                req("member_tx_hashes", Kind::FixedList(32)),
+                req("block_number", Kind::U64),
+            ],
+        ),
+        ("OppDetected", opp_detected),
+        ("SimCompleted", sim_completed),
+        (
+            "AssemblyDecisionApplied",
+            vec![
+                req("candidate_id", Kind::Utf8),
+                req("decision", Kind::Utf8),
+                req("replaced_candidate_ids", Kind::Utf8List),
+                opt("reason", Kind::Utf8),
+                req("block_number", Kind::U64),
+            ],
+        ),
+        ("BundleSubmitted", bundle_submitted),
+        ("TxReplaced", vec![req("replaced_by", HASH)]),
+        (
+            "TxDropped",
+            vec![req("reason", Kind::Utf8), opt("detail", Kind::Utf8)],
+        ),
+        ("TxConfirmedProvisional", confirmed()),
+        ("TxConfirmedFinal", confirmed()),
+        (
+            "TxReorged",
+            vec![req("old_block_hash", HASH), req("new_block_hash", HASH)],
+        ),
+        (
+            "UserOpSeen",
+            vec![
+                req("entry_point", ADDRESS),
+                req("entry_point_version", Kind::Utf8),
+                req("sender", ADDRESS),
+                req("nonce", HASH),
+                opt("chain_id", Kind::U64),
+                opt("factory", ADDRESS),
+                opt("paymaster", ADDRESS),
+                req("call_gas_limit", Kind::Wei),
+                req("verification_gas_limit", Kind::Wei),
+                req("pre_verification_gas", Kind::Wei),
+                req("max_fee_per_gas_wei", Kind::Wei),
+                req("max_priority_fee_per_gas_wei", Kind::Wei),
+                req("call_data_len", Kind::U32),
+                opt("bundle_tx_hash", HASH),
+                req("call_data", Kind::Binary),
+            ],
+        ),
+        (
+            "ChainCheckpoint",
+            vec![
+                req("covered_seq_id", Kind::U64),
+                req("covered_digest", HASH),
+                req("key_id", Kind::Utf8),
+                req("signature", Kind::Binary),
+            ],
+        ),
+    ]
+}
+
+/// `TxConfirmedFinal` -> `tx_confirmed_final`.
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (index, ch) in name.chars().enumerate() {
+        if ch.is_ascii_uppercase() {
+            if index > 0 {
+                out.push('_');
+            }
+            out.push(ch.to_ascii_lowercase());
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+const EVENT_ENVELOPE_COLUMNS: usize = 10;
+
+impl sealed::TableCodec for EventEnvelope {
+    fn columns() -> Vec<Column> {
+        let mut columns = vec![
+            req("seq_id", Kind::U64),
+            req("ingest_ts_unix_ms", Kind::I64),
+            req("ingest_ts_mono_ns", Kind::U64),
+            req("source_id", Kind::Utf8),
+            opt("chain_id", Kind::U64),
+            opt("chain_seq_id", Kind::U64),
+            opt("hash_link_prev_digest", HASH),
+            opt("hash_link_digest", HASH),
+            req("event_type", Kind::Utf8),
+            req("hash", HASH),
+        ];
+        debug_assert_eq!(columns.len(), EVENT_ENVELOPE_COLUMNS);
+        for (type_name, section) in event_sections() {
+            let prefix = snake_case(type_name);
+            columns.extend(section.into_iter().map(|column| Column {
+                name: format!("{prefix}_{}", column.name),
+                // Sections are null on rows of every other payload type.
+                nullable: true,
+                ..column
+            }));
+        }
+        columns
+    }
+
+    fn encode(&self, row: &mut RowWriter) {
+        let link = self.hash_link.as_ref();
+        row.put(self.seq_id)
+            .put(self.ingest_ts_unix_ms)
+            .put(self.ingest_ts_mono_ns)
+            .put(self.source_id.0.as_str())
+            .put(self.chain_id)
+            .put(self.chain_seq_id)
+            .put(link.map(|link| &link.prev_digest))
+            .put(link.map(|link| &link.digest))
+            .put(self.payload.type_name())
+            .put(&self.payload.primary_hash());
+        let type_name = self.payload.type_name();
+        for (section_type, section) in event_sections() {
+            if section_type == type_name {
+                encode_payload(&self.payload, row);
+            } else {
+                row.nulls(section.len());
+            }
+        }
+    }
+
+    fn decode(row: &mut RowReader<'_>) -> Result<Self> {
+        let seq_id = row.req()?;
+        let ingest_ts_unix_ms = row.req()?;
+        let ingest_ts_mono_ns = row.req()?;
+        let source_id = common::SourceId::new(row.req::<String>()?);
+        let chain_id = row.get()?;
+        let chain_seq_id = row.get()?;
+        let prev_digest = row.get()?;
+        let digest = row.get()?;
+        let type_name = row.req::<String>()?;
+        let hash = row.req::<TxHash>()?;
+        let mut payload = None;
+        for (section_type, section) in event_sections() {
+            if section_type == type_name {
+                payload = Some(decode_payload(section_type, hash, row)?);
+            } else {
+                row.skip(section.len());
+            }
+        }
+        let payload = payload.ok_or_else(|| unknown_label("event_type", &type_name))?;
+        Ok(Self {
+            seq_id,
+            ingest_ts_unix_ms,
+            ingest_ts_mono_ns,
+            source_id,
+            payload,
+            chain_id,
+            chain_seq_id,
+            hash_link: prev_digest
+                .zip(digest)
+                .map(|(prev_digest, digest)| HashLink {
+                    prev_digest,
+                    digest,
+                }),
+        })
+    }
+}
+
+/// Writes the payload's own section; the primary hash is an envelope column.
+fn encode_payload(payload: &EventPayload, row: &mut RowWriter) {
+    match payload {
+        EventPayload::TxSeen(e) => {
+            row.put(&e.peer_id)
+                .put(e.seen_at_unix_ms)
+                .put(e.seen_at_mono_ns);
+        }
+        EventPayload::TxFetched(e) => {
+            row.put(e.fetched_at_unix_ms);
+        }
+        EventPayload::TxDecoded(e) => {
+            row.put(e.tx_type)
+                .put(&e.sender)
+                .put(e.nonce)
+                .put(e.chain_id)
+                .put(e.to.as_ref())
+                .put(e.value_wei)
+                .put(e.gas_limit)
+                .put(e.gas_price_wei)
+                .put(e.max_fee_per_gas_wei)
+                .put(e.max_priority_fee_per_gas_wei)
+                .put(e.max_fee_per_blob_gas_wei)
+                .put(e.calldata_len);
+        }
+        EventPayload::TxReady(e) => {
+            row.put(&e.sender).put(e.nonce);
+        }
+        EventPayload::TxBlocked(e) => {
+            row.put(&e.sender).put(e.nonce).put(e.expected_nonce);
+        }
+        EventPayload::CandidateQueued(e) => {
+            row.put(&e.candidate_id)
+                .put(&e.member_tx_hashes)
+                .put(e.chain_id)
+                .put(&e.strategy)
+                .put(e.score)
+                .put(&e.protocol)
+                .put(&e.category)
+                .put(&e.feature_engine_version)
+                .put(&e.scorer_version)
+                .put(&e.strategy_version)
+                .put(&e.reasons)
+                .put(e.detected_unix_ms);
+        }
+        EventPayload::SimDispatched(e) => {
+            row.put(&e.candidate_id)
+                .put(&e.member_tx_hashes)
+                .put(e.block_number);
+        }
+        EventPayload::OppDetected(e) => {
+            row.put(&e.strategy)
+                .put(e.score)
+                .put(&e.protocol)
+                .put(&e.category)
+                .put(&e.feature_engine_version)
+                .put(&e.scorer_version)
+                .put(&e.strategy_version)
+                .put(&e.reasons);
+        }
+        EventPayload::SimCompleted(e) => {
+            row.put(&e.sim_id)
+                .put(e.status.as_str())
+                .put(&e.feature_engine_version)
+                .put(&e.scorer_version)
+                .put(&e.strategy_version)
+                .put(e.fail_category.as_ref().map(SimFailCategory::as_str))
+                .put(e.latency_ms)
+                .put(e.tx_count)
+                .put(e.l1_data_fee_wei)
+                .put(e.gas_cost_wei);
+        }
+        EventPayload::AssemblyDecisionApplied(e) => {
+            row.put(&e.candidate_id)
+                .put(e.decision.as_str())
+                .put(&e.replaced_candidate_ids)
+                .put(e.reason.as_ref())
+                .put(e.block_number);
+        }
+        EventPayload::BundleSubmitted(e) => {
+            row.put(&e.bundle_id)
+                .put(&e.sim_id)
+                .put(&e.relay)
+                .put(e.accepted)
+                .put(&e.feature_engine_version)
+                .put(&e.scorer_version)
+                .put(&e.strategy_version);
+        }
+        EventPayload::TxReplaced(e) => {
+            row.put(&e.replaced_by);
+        }
+        EventPayload::TxDropped(e) => {
+            row.put(e.reason.as_str()).put(e.detail.as_ref());
+        }
+        EventPayload::TxConfirmedProvisional(e) | EventPayload::TxConfirmedFinal(e) => {
+            row.put(e.block_number).put(&e.block_hash);
+        }
+        EventPayload::TxReorged(e) => {
+            row.put(&e.old_block_hash).put(&e.new_block_hash);
+        }
+        EventPayload::UserOpSeen(e) => {
+            row.put(&e.entry_point)
+                .put(match e.entry_point_version {
+                    EntryPointVersion::V06 => "v06",
+                    EntryPointVersion::V07 => "v07",
+                })
+                .put(&e.sender)
+                .put(&e.nonce)
+                .put(e.chain_id)
+                .put(e.factory.as_ref())
+                .put(e.paymaster.as_ref())
+                .put(e.call_gas_limit)
+                .put(e.verification_gas_limit)
+                .put(e.pre_verification_gas)
+                .put(e.max_fee_per_gas_wei)
+                .put(e.max_priority_fee_per_gas_wei)
+                .put(e.call_data_len)
+                .put(e.bundle_tx_hash.as_ref())
+                .put(&e.call_data);
+        }
+        EventPayload::ChainCheckpoint(e) => {
+            row.put(e.covered_seq_id)
+                .put(&e.covered_digest)
+                .put(&e.key_id)
+                .put(&e.signature);
+        }
+    }
+}
+
+fn decode_payload(type_name: &str, hash: TxHash, row: &mut RowReader<'_>) -> Result<EventPayload> {
+    Ok(match type_name {
+        "TxSeen" => EventPayload::TxSeen(TxSeen {
+            hash,
+            peer_id: row.req()?,
+            seen_at_unix_ms: row.req()?,
+            seen_at_mono_ns: row.req()?,
+        }),
+        "TxFetched" => EventPayload::TxFetched(TxFetched {
+            hash,
+            fetched_at_unix_ms: row.req()?,
+        }),
+        "TxDecoded" => EventPayload::TxDecoded(TxDecoded {
+            hash,
+            tx_type: row.req()?,
+            sender: row.req()?,
+            nonce: row.req()?,
+            chain_id: row.get()?,
+            to: row.get()?,
+            value_wei: row.get()?,
+            gas_limit: row.get()?,
+            gas_price_wei: row.get()?,
+            max_fee_per_gas_wei: row.get()?,
+            max_priority_fee_per_gas_wei: row.get()?,
+            max_fee_per_blob_gas_wei: row.get()?,
+            calldata_len: row.get()?,
+        }),
+        "TxReady" => EventPayload::TxReady(TxReady {
+            hash,
+            sender: row.req()?,
+            nonce: row.req()?,
+        }),
+        "TxBlocked" => EventPayload::TxBlocked(TxBlocked {
+            hash,
+            sender: row.req()?,
+            nonce: row.req()?,
+            expected_nonce: row.get()?,
+        }),
+        "CandidateQueued" => EventPayload::CandidateQueued(CandidateQueued {
+            tx_hash: hash,
+            candidate_id: row.req()?,
+            member_tx_hashes: row.req()?,
+            chain_id: row.get()?,
+            strategy: row.req()?,
+            score: row.req()?,
+            protocol: row.req()?,
+            category: row.req()?,
+            feature_engine_version: row.req()?,
+            scorer_version: row.req()?,
+            strategy_version: row.req()?,
+            reasons: row.req()?,
+            detected_unix_ms: row.req()?,
+        }),
+        "SimDispatched" => EventPayload::SimDispatched(SimDispatched {
+            tx_hash: hash,
+            candidate_id: row.req()?,
+            member_tx_hashes: row.req()?,
+            block_number: row.req()?,
+        }),
+        "OppDetected" => EventPayload::OppDetected(OppDetected {
+            hash,
+            strategy: row.req()?,
+            score: row.req()?,
+            protocol: row.req()?,
+            category: row.req()?,
+            feature_engine_version: row.req()?,
+            scorer_version: row.req()?,
+            strategy_version: row.req()?,
+            reasons: row.req()?,
+        }),
+        "SimCompleted" => EventPayload::SimCompleted(SimCompleted {
+            hash,
+            sim_id: row.req()?,
+            status: SimulationStatus::from(row.req::<String>()?),
+            feature_engine_version: row.req()?,
+            scorer_version: row.req()?,
+            strategy_version: row.req()?,
+            fail_category: row.get::<String>()?.map(SimFailCategory::from),
+            latency_ms: row.get()?,
+            tx_count: row.get()?,
+            l1_data_fee_wei: row.get()?,
+            gas_cost_wei: row.get()?,
+        }),
+        "AssemblyDecisionApplied" => {
+            EventPayload::AssemblyDecisionApplied(AssemblyDecisionApplied {
+                tx_hash: hash,
+                candidate_id: row.req()?,
+                decision: AssemblyDecisionKind::from(row.req::<String>()?),
+                replaced_candidate_ids: row.req()?,
+                reason: row.get()?,
+                block_number: row.req()?,
+            })
+        }
+        "BundleSubmitted" => EventPayload::BundleSubmitted(BundleSubmitted {
+            hash,
+            bundle_id: row.req()?,
+            sim_id: row.req()?,
+            relay: row.req()?,
+            accepted: row.req()?,
+            feature_engine_version: row.req()?,
+            scorer_version: row.req()?,
+            strategy_version: row.req()?,
+        }),
+        "TxReplaced" => EventPayload::TxReplaced(TxReplaced {
+            hash,
+            replaced_by: row.req()?,
+        }),
+        "TxDropped" => EventPayload::TxDropped(TxDropped {
+            hash,
+            reason: DropReason::from(row.req::<String>()?),
+            detail: row.get()?,
+        }),
+        "TxConfirmedProvisional" => EventPayload::TxConfirmedProvisional(TxConfirmed {
+            hash,
+            block_number: row.req()?,
+            block_hash: row.req()?,
+        }),
+        "TxConfirmedFinal" => EventPayload::TxConfirmedFinal(TxConfirmed {
+            hash,
+            block_number: row.req()?,
+            block_hash: row.req()?,
+        }),
+        "TxReorged" => EventPayload::TxReorged(TxReorged {
+            hash,
+            old_block_hash: row.req()?,
+            new_block_hash: row.req()?,
+        }),
+        "UserOpSeen" => EventPayload::UserOpSeen(UserOpSeen {
+            user_op_hash: hash,
+            entry_point: row.req()?,
+            entry_point_version: match row.req::<String>()?.as_str() {
+                "v06" => EntryPointVersion::V06,
+                "v07" => EntryPointVersion::V07,
+                other => return Err(unknown_label("entry_point_version", other)),
+            },
+            sender: row.req()?,
+            nonce: row.req()?,
+            chain_id: row.get()?,
+            factory: row.get()?,
+            paymaster: row.get()?,
+            call_gas_limit: row.req()?,
+            verification_gas_limit: row.req()?,
+            pre_verification_gas: row.req()?,
+            max_fee_per_gas_wei: row.req()?,
+            max_priority_fee_per_gas_wei: row.req()?,
+            call_data_len: row.req()?,
+            bundle_tx_hash: row.get()?,
+            call_data: row.req()?,
+        }),
+        "ChainCheckpoint" => EventPayload::ChainCheckpoint(ChainCheckpoint {
+            covered_seq_id: row.req()?,
+            covered_digest: row.req()?,
+            key_id: row.req()?,
+            signature: row.req()?,
+        }),
+        other => return Err(unknown_label("event_type", other)),
+    })
+}
+
+impl sealed::TableCodec for TxFullRecord {
+    fn columns() -> Vec<Column> {
+        vec![
+            req("hash", HASH),
+            req("tx_type", Kind::U8),
+            req("sender", ADDRESS),
+            req("nonce", Kind::U64),
+            opt("to", ADDRESS),
+            opt("chain_id", Kind::U64),
+            opt("value_wei", Kind::Wei),
+            opt("gas_limit", Kind::U64),
+            opt("gas_price_wei", Kind::Wei),
+            opt("max_fee_per_gas_wei", Kind::Wei),
+            opt("max_priority_fee_per_gas_wei", Kind::Wei),
+            opt("max_fee_per_blob_gas_wei", Kind::Wei),
+            opt("calldata_len", Kind::U32),
+            req("raw_tx", Kind::Binary),
+            opt("l2_source_hash", HASH),
+            opt("l2_mint_wei", Kind::Wei),
+            opt("l2_is_system_tx", Kind::Bool),
+            opt("l2_l1_fee_wei", Kind::Wei),
+            opt("l2_l1_gas_used", Kind::U64),
+            opt("l2_l1_gas_price_wei", Kind::Wei),
+            opt("l2_l1_blob_base_fee_wei", Kind::Wei),
+            opt("l2_l1_base_fee_scalar", Kind::U32),
+            opt("l2_l1_blob_base_fee_scalar", Kind::U32),
+            opt("l2_request_id", HASH),
+            opt("l2_ticket_id", HASH),
+            opt("l2_refund_to", ADDRESS),
+            opt("l2_l1_base_fee_wei", Kind::Wei),
+            opt("l2_max_refund_wei", Kind::Wei),
+        ]
+    }
+
+    fn encode(&self, row: &mut RowWriter) {
+        let l2 = self.l2_fields.as_deref().cloned().unwrap_or_default();
+        row.put(&self.hash)
+            .put(self.tx_type)
+            .put(&self.sender)
+            .put(self.nonce)
+            .put(self.to.as_ref())
+            .put(self.chain_id)
+            .put(self.value_wei)
+            .put(self.gas_limit)
+            .put(self.gas_price_wei)
+            .put(self.max_fee_per_gas_wei)
+            .put(self.max_priority_fee_per_gas_wei)
+            .put(self.max_fee_per_blob_gas_wei)
+            .put(self.calldata_len)
+            .put(&self.raw_tx)
+            .put(l2.source_hash.as_ref())
+            .put(l2.mint_wei)
+            .put(l2.is_system_tx)
+            .put(l2.l1_fee_wei)
+            .put(l2.l1_gas_used)
+            .put(l2.l1_gas_price_wei)
+            .put(l2.l1_blob_base_fee_wei)
+            .put(l2.l1_base_fee_scalar)
+            .put(l2.l1_blob_base_fee_scalar)
+            .put(l2.request_id.as_ref())
+            .put(l2.ticket_id.as_ref())
+            .put(l2.refund_to.as_ref())
+            .put(l2.l1_base_fee_wei)
+            .put(l2.max_refund_wei);
+    }
+
+    fn decode(row: &mut RowReader<'_>) -> Result<Self> {
+        let mut record = Self {
+            hash: row.req()?,
+            tx_type: row.req()?,
+            sender: row.req()?,
+            nonce: row.req()?,
+            to: row.get()?,
+            chain_id: row.get()?,
+            value_wei: row.get()?,
+            gas_limit: row.get()?,
+            gas_price_wei: row.get()?,
+            max_fee_per_gas_wei: row.get()?,
+            max_priority_fee_per_gas_wei: row.get()?,
+            max_fee_per_blob_gas_wei: row.get()?,
+            calldata_len: row.get()?,
+            raw_tx: row.req()?,
+            l2_fields: None,
+        };
+        let l2 = L2TxFields {
+            source_hash: row.get()?,
+            mint_wei: row.get()?,
+            is_system_tx: row.get()?,
+            l1_fee_wei: row.get()?,
+            l1_gas_used: row.get()?,
+            l1_gas_price_wei: row.get()?,
+            l1_blob_base_fee_wei: row.get()?,
+            l1_base_fee_scalar: row.get()?,
+            l1_blob_base_fee_scalar: row.get()?,
+            request_id: row.get()?,
+            ticket_id: row.get()?,
+            refund_to: row.get::<Address>()?,
+            l1_base_fee_wei: row.get()?,
+            max_refund_wei: row.get()?,
+        };
+        // An all-null group reads back as no L2 fields at all.
+        record.l2_fields = (!l2.is_empty()).then(|| Box::new(l2));
+        Ok(record)
+    }
+}
+
+impl sealed::TableCodec for TxFeaturesRecord {
+    fn columns() -> Vec<Column> {
+        vec![
+            req("hash", HASH),
+            opt("chain_id", Kind::U64),
+            req("protocol", Kind::Utf8),
+            req("category", Kind::Utf8),
+            req("mev_score", Kind::U16),
+            req("urgency_score", Kind::U16),
+            opt("method_selector", Kind::Fixed(4)),
+            req("feature_engine_version", Kind::Utf8),
+        ]
+    }
+
+    fn encode(&self, row: &mut RowWriter) {
+        row.put(&self.hash)
+            .put(self.chain_id)
+            .put(&self.protocol)
+            .put(&self.category)
+            .put(self.mev_score)
+            .put(self.urgency_score)
+            .put(self.method_selector.as_ref())
+            .put(&self.feature_engine_version);
+    }
+
+    fn decode(row: &mut RowReader<'_>) -> Result<Self> {
+        Ok(Self {
+            hash: row.req()?,
+            chain_id: row.get()?,
+            protocol: row.req()?,
+            category: row.req()?,
+            mev_score: row.req()?,
+            urgency_score: row.req()?,
+            method_selector: row.get()?,
+            feature_engine_version: row.req()?,
+        })
+    }
+}
+
+impl sealed::TableCodec for OpportunityRecord {
+    fn columns() -> Vec<Column> {
+        vec![
+            req("tx_hash", HASH),
+            opt("chain_id", Kind::U64),
+            req("strategy", Kind::Utf8),
+            req("score", Kind::U32),
+            req("protocol", Kind::Utf8),
+            req("category", Kind::Utf8),
+            req("feature_engine_version", Kind::Utf8),
+            req("scorer_version", Kind::Utf8),
+            req("strategy_version", Kind::Utf8),
+            req("reasons", Kind::Utf8List),
+            req("detected_unix_ms", Kind::I64),
+        ]
+    }
+
+    fn encode(&self, row: &mut RowWriter) {
+        row.put(&self.tx_hash)
+            .put(self.chain_id)
+            .put(&self.strategy)
+            .put(self.score)
+            .put(&self.protocol)
+            .put(&self.category)
+            .put(&self.feature_engine_version)
+            .put(&self.scorer_version)
+            .put(&self.strategy_version)
+            .put(&self.reasons)
+            .put(self.detected_unix_ms);
+    }
+
+    fn decode(row: &mut RowReader<'_>) -> Result<Self> {
+        Ok(Self {
+            tx_hash: row.req()?,
+            chain_id: row.get()?,
+            strategy: row.req()?,
+            score: row.req()?,
+            protocol: row.req()?,
+            category: row.req()?,
+            feature_engine_version: row.req()?,
+            scorer_version: row.req()?,
+            strategy_version: row.req()?,
+            reasons: row.req()?,
+            detected_unix_ms: row.req()?,
+        })
+    }
+}
+
+/// Sender queues are flattened into parallel lists: one entry per queue in
+/// `queue_senders`, and one per queued transaction in the `queued_*` lists,
+/// which point at their queue through `queued_sender_index`. A null
+/// `queued_expected_nonce` marks a ready entry.
+impl sealed::TableCodec for ReplayFrame {
+    fn columns() -> Vec<Column> {
+        vec![
+            req("seq_hi", Kind::U64),
+            req("timestamp_unix_ms", Kind::I64),
+            req("pending", Kind::FixedList(32)),
+            req("queue_senders", Kind::FixedList(20)),
+            req("queued_sender_index", Kind::U32List),
+            req("queued_hash", Kind::FixedList(32)),
+            req("queued_nonce", Kind::U64List),
+            req("queued_expected_nonce", Kind::U64List),
+        ]
+    }
+
+    fn encode(&self, row: &mut RowWriter) {
+        let senders = self
+            .sender_queues
+            .iter()
+            .map(|queue| queue.sender)
+            .collect::<Vec<_>>();
+        let entries = self
+            .sender_queues
+            .iter()
+            .enumerate()
+            .flat_map(|(index, queue)| queue.queued.iter().map(move |entry| (index, entry)));
+        let mut sender_index = Vec::new();
+        let mut hashes = Vec::new();
+        let mut nonces = Vec::new();
+        let mut expected = Vec::new();
+        for (index, entry) in entries {
+            sender_index.push(index as u32);
+            hashes.push(entry.hash);
+            nonces.push(Some(entry.nonce));
+            expected.push(match entry.state {
+                ReplayQueueState::Ready => None,
+                ReplayQueueState::Blocked { expected_nonce } => Some(expected_nonce),
+            });
+        }
+        row.put(self.seq_hi)
+            .put(self.timestamp_unix_ms)
+            .put(&self.pending)
+            .put(&senders)
+            .put(sender_index)
+            .put(&hashes)
+            .put(nonces)
+            .put(expected);
+    }
+
+    fn decode(row: &mut RowReader<'_>) -> Result<Self> {
+        let seq_hi = row.req()?;
+        let timestamp_unix_ms = row.req()?;
+        let pending = row.req()?;
+        let senders = row.req::<Vec<Address>>()?;
+        let sender_index = row.req::<Vec<u32>>()?;
+        let hashes = row.req::<Vec<TxHash>>()?;
+        let nonces = row.req::<Vec<Option<u64>>>()?;
+        let expected = row.req::<Vec<Option<u64>>>()?;
+        let mut sender_queues = senders
+            .into_iter()
+            .map(|sender| ReplaySenderQueue {
+                sender,
+                queued: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        let entries = sender_index.len();
+        if hashes.len() != entries || nonces.len() != entries || expected.len() != entries {
+            return Err(StorageError::parquet_export(anyhow!(
+                "replay frame {seq_hi} has mismatched queue entry lists"
+            )));
+        }
+        for (((index, hash), nonce), expected_nonce) in sender_index
+            .into_iter()
+            .zip(hashes)
+            .zip(nonces)
+            .zip(expected)
+        {
+            let (Some(queue), Some(nonce)) = (sender_queues.get_mut(index as usize), nonce) else {
+                return Err(StorageError::parquet_export(anyhow!(
+                    "replay frame {seq_hi} has a dangling queue entry"
+                )));
+            };
+            queue.queued.push(ReplaySenderQueueEntry {
+                hash,
+                nonce,
+                state: match expected_nonce {
+                    None => ReplayQueueState::Ready,
+                    Some(expected_nonce) => ReplayQueueState::Blocked { expected_nonce },
+                },
+            });
+        }
+        Ok(Self {
+            seq_hi,
+            timestamp_unix_ms,
+            pending,
+            sender_queues,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_schema_prefixes_payload_columns_by_type() {
+        let schema = EventEnvelope::arrow_schema();
+        assert_eq!(schema.field(0).name(), "seq_id");
+        assert!(schema.field_with_name("tx_decoded_sender").is_ok());
+        assert!(
+            schema
+                .field_with_name("tx_confirmed_final_block_number")
+                .is_ok()
+        );
+        assert_eq!(
+            schema
+                .field_with_name("sim_completed_gas_cost_wei")
+                .map(|field| field.data_type().clone())
+                .ok(),
+            Some(DataType::Decimal128(38, 0))
+        );
+        let section_columns = event_sections()
+            .iter()
+            .map(|(_, section)| section.len())
+            .sum::<usize>();
+        assert_eq!(
+            schema.fields().len(),
+            EVENT_ENVELOPE_COLUMNS + section_columns
+        );
+    }
+
+    #[test]
+    fn snake_case_splits_payload_type_names() {
+        assert_eq!(snake_case("TxConfirmedFinal"), "tx_confirmed_final");
+        assert_eq!(snake_case("UserOpSeen"), "user_op_seen");
+    }
+}
@@ -0,0 +1,563 @@
+//! Per-peer and per-source propagation statistics derived from the event stream.
+//!
+//! Every `TxSeen` is an observation of its hash by a peer behind an ingest
+//! source, and a duplicate `TxDropped` is a later announcement of an already
+//! seen hash (the p2p lane names the announcing peer in its detail). The first
+//! observation of a hash sets the reference every later one lags behind, and
+//! any other drop marks the hash dropped for all of its observers.
+//! Observations are kept in timestamp order for the configured window behind
+//! the newest one, so queries over shorter windows are rolling views computed
+//! on read.
+
+use crate::FastMap;
+use common::{PeerId, SourceId, TxHash};
+use event_log::{DropReason, EventEnvelope, EventPayload};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+/// Retention of the observation window.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PeerObservationConfig {
+    /// Longest window queries can cover, measured back from the newest
+    /// observation.
+    pub window_ms: u64,
+    /// Cap on hashes tracked at once; the oldest observations go first.
+    pub max_tracked_hashes: usize,
+}
+
+impl Default for PeerObservationConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 15 * 60 * 1_000,
+            max_tracked_hashes: 250_000,
+        }
+    }
+}
+
+/// Whether statistics are keyed by announcing peer or by ingest source.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObserverGroup {
+    #[default]
+    Peer,
+    Source,
+}
+
+impl ObserverGroup {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Peer => "peer",
+            Self::Source => "source",
+        }
+    }
+}
+
+impl fmt::Display for ObserverGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ObserverGroup {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "peer" => Ok(Self::Peer),
+            "source" => Ok(Self::Source),
+            other => Err(format!("unknown observer group `{other}`")),
+        }
+    }
+}
+
+/// Propagation statistics of one peer or source over a window.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PeerObservationStats {
+    pub observer: String,
+    /// Observations of any hash, first or not.
+    pub announcements: u64,
+    /// Hashes this observer saw before anyone else.
+    pub first_seen: u64,
+    /// `first_seen` over all hashes first seen in the window, in basis points.
+    pub first_seen_share_bps: u16,
+    /// Median delay behind the first observer over the later announcements;
+    /// `None` when the observer was always first.
+    pub median_lag_ms: Option<u64>,
+    /// Hashes first seen here that no other observer announced.
+    pub unique_hashes: u64,
+    /// Announcements of hashes that were later dropped for a reason other
+    /// than deduplication.
+    pub dropped: u64,
+    /// `dropped` over `announcements`, in basis points.
+    pub drop_ratio_bps: u16,
+}
+
+/// Observed delay from the first observer of a hash to a later one.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ObservedPropagationEdge {
+    pub source: String,
+    pub destination: String,
+    pub samples: u64,
+    pub p50_lag_ms: u64,
+    pub p99_lag_ms: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Observer {
+    /// `None` for duplicate announcements whose lane does not name the peer.
+    peer: Option<PeerId>,
+    source: SourceId,
+}
+
+impl Observer {
+    fn key(&self, group: ObserverGroup) -> Option<&str> {
+        match group {
+            ObserverGroup::Peer => self.peer.as_deref(),
+            ObserverGroup::Source => Some(self.source.as_str()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Observation {
+    at_unix_ms: i64,
+    hash: TxHash,
+    observer: Observer,
+    /// Delay behind the first observer and who that was; `None` for the
+    /// first observation itself.
+    lag: Option<(u64, Observer)>,
+}
+
+#[derive(Clone, Debug)]
+struct HashObservers {
+    observers: Vec<Observer>,
+    dropped: bool,
+    first_at_unix_ms: i64,
+    /// Observations of the hash still in the log; the hash is forgotten only
+    /// once the last of them leaves the window.
+    observations: usize,
+}
+
+impl HashObservers {
+    fn unique_to(&self, group: ObserverGroup, key: &str) -> bool {
+        self.observers
+            .iter()
+            .all(|observer| observer.key(group) == Some(key))
+    }
+}
+
+#[derive(Default)]
+struct StatsAccumulator {
+    announcements: u64,
+    first_seen: u64,
+    lags: Vec<u64>,
+    unique_hashes: u64,
+    dropped: u64,
+}
+
+/// Rolling observation log behind [`PeerObservationStats`].
+#[derive(Clone, Debug)]
+pub(crate) struct PeerObservations {
+    config: PeerObservationConfig,
+    observations: VecDeque<Observation>,
+    hashes: FastMap<TxHash, HashObservers>,
+    newest_unix_ms: Option<i64>,
+}
+
+impl PeerObservations {
+    pub(crate) fn new(config: PeerObservationConfig) -> Self {
+        Self {
+            config,
+            observations: VecDeque::new(),
+            hashes: FastMap::default(),
+            newest_unix_ms: None,
+        }
+    }
+
+    pub(crate) fn observe(&mut self, event: &EventEnvelope) {
+        match &event.payload {
+            EventPayload::TxSeen(seen) => self.announce(
+                seen.hash,
+                Observer {
+                    peer: Some(seen.peer_id.clone()),
+                    source: event.source_id.clone(),
+                },
+                seen.seen_at_unix_ms,
+            ),
+            // A duplicate of a hash this log never saw carries no lag.
+            EventPayload::TxDropped(dropped)
+                if dropped.reason == DropReason::Duplicate
+                    && self.hashes.contains_key(&dropped.hash) =>
+            {
+                let peer = dropped.detail.as_deref().and_then(detail_peer);
+                self.announce(
+                    dropped.hash,
+                    Observer {
+                        peer,
+                        source: event.source_id.clone(),
+                    },
+                    event.ingest_ts_unix_ms,
+                );
+            }
+            EventPayload::TxDropped(dropped) if dropped.reason != DropReason::Duplicate => {
+                if let Some(observers) = self.hashes.get_mut(&dropped.hash) {
+                    observers.dropped = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn announce(&mut self, hash: TxHash, observer: Observer, at_unix_ms: i64) {
+        let lag = match self.hashes.get_mut(&hash) {
+            Some(observers) => {
+                let lag_ms = at_unix_ms.saturating_sub(observers.first_at_unix_ms).max(0) as u64;
+                let first = observers.observers[0].clone();
+                observers.observations += 1;
+                if !observers.observers.contains(&observer) {
+                    observers.observers.push(observer.clone());
+                }
+                Some((lag_ms, first))
+            }
+            None => {
+                self.hashes.insert(
+                    hash,
+                    HashObservers {
+                        observers: vec![observer.clone()],
+                        dropped: false,
+                        first_at_unix_ms: at_unix_ms,
+                        observations: 1,
+                    },
+                );
+                None
+            }
+        };
+        // Sources report their own clocks, so an observation can be older
+        // than ones already logged; it usually belongs near the back.
+        let position = self
+            .observations
+            .partition_point(|logged| logged.at_unix_ms <= at_unix_ms);
+        self.observations.insert(
+            position,
+            Observation {
+                at_unix_ms,
+                hash,
+                observer,
+                lag,
+            },
+        );
+        let newest = self
+            .newest_unix_ms
+            .map_or(at_unix_ms, |newest| newest.max(at_unix_ms));
+        self.newest_unix_ms = Some(newest);
+        self.prune(newest);
+    }
+
+    fn prune(&mut self, newest_unix_ms: i64) {
+        let cutoff = window_start(newest_unix_ms, self.config.window_ms);
+        while let Some(oldest) = self.observations.front() {
+            if oldest.at_unix_ms >= cutoff && self.hashes.len() <= self.config.max_tracked_hashes {
+                break;
+            }
+            let Some(oldest) = self.observations.pop_front() else {
+                break;
+            };
+            if let Some(observers) = self.hashes.get_mut(&oldest.hash) {
+                observers.observations = observers.observations.saturating_sub(1);
+                if observers.observations == 0 {
+                    self.hashes.remove(&oldest.hash);
+                }
+            }
+        }
+    }
+
+    /// Observations made within `window_ms` of the newest one.
+    fn window(&self, window_ms: u64) -> impl Iterator<Item = &Observation> {
+        let cutoff = self.newest_unix_ms.map_or(i64::MAX, |newest| {
+            window_start(newest, window_ms.min(self.config.window_ms))
+        });
+        self.observations
+            .iter()
+            .rev()
+            .take_while(move |observation| observation.at_unix_ms >= cutoff)
+    }
+
+    /// Statistics per observer over the last `window_ms`, ordered by key.
+    pub(crate) fn stats(&self, group: ObserverGroup, window_ms: u64) -> Vec<PeerObservationStats> {
+        let mut by_key = FastMap::<&str, StatsAccumulator>::default();
+        let mut first_seen_total = 0_u64;
+        for observation in self.window(window_ms) {
+            let Some(key) = observation.observer.key(group) else {
+                continue;
+            };
+            let observers = self.hashes.get(&observation.hash);
+            let stats = by_key.entry(key).or_default();
+            stats.announcements += 1;
+            match &observation.lag {
+                None => {
+                    first_seen_total += 1;
+                    stats.first_seen += 1;
+                    if observers.is_some_and(|observers| observers.unique_to(group, key)) {
+                        stats.unique_hashes += 1;
+                    }
+                }
+                Some((lag_ms, _)) => stats.lags.push(*lag_ms),
+            }
+            if observers.is_some_and(|observers| observers.dropped) {
+                stats.dropped += 1;
+            }
+        }
+
+        let mut rows = by_key
+            .into_iter()
+            .map(|(key, mut stats)| {
+                stats.lags.sort_unstable();
+                PeerObservationStats {
+                    observer: key.to_owned(),
+                    announcements: stats.announcements,
+                    first_seen: stats.first_seen,
+                    first_seen_share_bps: ratio_bps(stats.first_seen, first_seen_total),
+                    median_lag_ms: (!stats.lags.is_empty()).then(|| percentile(&stats.lags, 50)),
+                    unique_hashes: stats.unique_hashes,
+                    dropped: stats.dropped,
+                    drop_ratio_bps: ratio_bps(stats.dropped, stats.announcements),
+                }
+            })
+            .collect::<Vec<_>>();
+        rows.sort_unstable_by(|left, right| left.observer.cmp(&right.observer));
+        rows
+    }
+
+    /// Lag from first to later observers over the last `window_ms`, one edge
+    /// per ordered pair of distinct keys.
+    pub(crate) fn edges(
+        &self,
+        group: ObserverGroup,
+        window_ms: u64,
+    ) -> Vec<ObservedPropagationEdge> {
+        let mut lags = FastMap::<(&str, &str), Vec<u64>>::default();
+        for observation in self.window(window_ms) {
+            let Some((lag_ms, first)) = &observation.lag else {
+                continue;
+            };
+            let (Some(source), Some(destination)) =
+                (first.key(group), observation.observer.key(group))
+            else {
+                continue;
+            };
+            if source != destination {
+                lags.entry((source, destination)).or_default().push(*lag_ms);
+            }
+        }
+
+        let mut edges = lags
+            .into_iter()
+            .map(|((source, destination), mut lags)| {
+                lags.sort_unstable();
+                ObservedPropagationEdge {
+                    source: source.to_owned(),
+                    destination: destination.to_owned(),
+                    samples: lags.len() as u64,
+                    p50_lag_ms: percentile(&lags, 50),
+                    p99_lag_ms: percentile(&lags, 99),
+                }
+            })
+            .collect::<Vec<_>>();
+        edges.sort_unstable_by(|left, right| {
+            left.source
+                .cmp(&right.source)
+                .then_with(|| left.destination.cmp(&right.destination))
+        });
+        edges
+    }
+}
+
+fn window_start(newest_unix_ms: i64, window_ms: u64) -> i64 {
+    newest_unix_ms.saturating_sub(i64::try_from(window_ms).unwrap_or(i64::MAX))
+}
+
+/// Reads the `peer=` field of a p2p drop detail.
+fn detail_peer(detail: &str) -> Option<PeerId> {
+    detail
+        .split(';')
+        .find_map(|field| field.strip_prefix("peer="))
+        .filter(|peer| !peer.is_empty())
+        .map(str::to_owned)
+}
+
+fn ratio_bps(numerator: u64, denominator: u64) -> u16 {
+    if denominator == 0 {
+        return 0;
+    }
+    (numerator.saturating_mul(10_000) / denominator).min(10_000) as u16
+}
+
+/// Nearest-rank percentile of ascending, non-empty `sorted`; ties go to the
+/// lower sample.
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    let max_index = sorted.len().saturating_sub(1);
+    sorted[(max_index * pct + 49) / 100]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use event_log::{TxDropped, TxSeen};
+
+    fn event(seq_id: u64, source: &str, at_unix_ms: i64, payload: EventPayload) -> EventEnvelope {
+        EventEnvelope {
+            seq_id,
+            ingest_ts_unix_ms: at_unix_ms,
+            ingest_ts_mono_ns: seq_id,
+            source_id: SourceId::new(source),
+            payload,
+            chain_id: Some(1),
+            chain_seq_id: None,
+            hash_link: None,
+        }
+    }
+
+    fn seen(seq_id: u64, peer: &str, hash: u8, at_unix_ms: i64) -> EventEnvelope {
+        event(
+            seq_id,
+            "p2p",
+            at_unix_ms,
+            EventPayload::TxSeen(TxSeen {
+                hash: [hash; 32],
+                peer_id: peer.to_owned(),
+                seen_at_unix_ms: at_unix_ms,
+                seen_at_mono_ns: seq_id,
+            }),
+        )
+    }
+
+    fn duplicate(seq_id: u64, peer: &str, hash: u8, at_unix_ms: i64) -> EventEnvelope {
+        let mut dropped = TxDropped::new([hash; 32], DropReason::Duplicate);
+        dropped.detail = Some(format!(
+            "lane=p2p;source=p2p;peer={peer};queue=p2p.fetch;depth_current=0;depth_peak=0"
+        ));
+        event(seq_id, "p2p", at_unix_ms, EventPayload::TxDropped(dropped))
+    }
+
+    #[test]
+    fn duplicate_announcements_lag_behind_the_first_peer() {
+        let mut log = PeerObservations::new(PeerObservationConfig::default());
+        log.observe(&seen(1, "peer-a", 1, 1_000));
+        log.observe(&duplicate(2, "peer-b", 1, 1_040));
+        log.observe(&seen(3, "peer-a", 2, 1_100));
+        log.observe(&duplicate(4, "peer-b", 2, 1_120));
+        log.observe(&seen(5, "peer-b", 3, 1_200));
+        log.observe(&event(
+            6,
+            "p2p",
+            1_300,
+            EventPayload::TxDropped(TxDropped::new([2; 32], DropReason::Evicted)),
+        ));
+
+        let stats = log.stats(ObserverGroup::Peer, 60_000);
+        assert_eq!(stats.len(), 2);
+        let (a, b) = (&stats[0], &stats[1]);
+        assert_eq!(
+            (a.observer.as_str(), a.first_seen, a.announcements),
+            ("peer-a", 2, 2)
+        );
+        assert_eq!(a.first_seen_share_bps, 6_666);
+        assert_eq!(a.median_lag_ms, None);
+        assert_eq!(a.unique_hashes, 0);
+        assert_eq!((a.dropped, a.drop_ratio_bps), (1, 5_000));
+        assert_eq!(
+            (b.observer.as_str(), b.first_seen, b.announcements),
+            ("peer-b", 1, 3)
+        );
+        assert_eq!(b.median_lag_ms, Some(20));
+        assert_eq!(b.unique_hashes, 1);
+
+        let edges = log.edges(ObserverGroup::Peer, 60_000);
+        assert_eq!(
+            edges,
+            vec![ObservedPropagationEdge {
+                source: "peer-a".to_owned(),
+                destination: "peer-b".to_owned(),
+                samples: 2,
+                p50_lag_ms: 20,
+                p99_lag_ms: 40,
+            }]
+        );
+
+        // Both peers sit behind one source, so every hash is unique to it.
+        let by_source = log.stats(ObserverGroup::Source, 60_000);
+        assert_eq!(by_source.len(), 1);
+        assert_eq!(by_source[0].unique_hashes, 3);
+        assert!(log.edges(ObserverGroup::Source, 60_000).is_empty());
+
+        // The last 50 ms only hold hash 3.
+        let recent = log.stats(ObserverGroup::Peer, 50);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].first_seen_share_bps, 10_000);
+    }
+
+    #[test]
+    fn observations_leave_the_window_and_the_hash_cap() {
+        let mut log = PeerObservations::new(PeerObservationConfig {
+            window_ms: 1_000,
+            max_tracked_hashes: 2,
+        });
+        log.observe(&seen(1, "peer-a", 1, 0));
+        log.observe(&seen(2, "peer-a", 2, 10));
+        log.observe(&seen(3, "peer-a", 3, 20));
+        assert_eq!(log.hashes.len(), 2);
+        assert!(!log.hashes.contains_key(&[1; 32]));
+
+        log.observe(&seen(4, "peer-b", 4, 1_015));
+        assert_eq!(log.observations.len(), 2);
+        // Hash 2 aged out, so a late duplicate has nothing to lag behind.
+        log.observe(&duplicate(5, "peer-b", 2, 1_016));
+        assert_eq!(log.observations.len(), 2);
+        assert_eq!(
+            ObserverGroup::from_str(" Source "),
+            Ok(ObserverGroup::Source)
+        );
+    }
+
+    #[test]
+    fn hashes_outlive_their_first_observation_and_late_timestamps_age_out() {
+        let mut log = PeerObservations::new(PeerObservationConfig {
+            window_ms: 1_000,
+            max_tracked_hashes: 100,
+        });
+        log.observe(&seen(1, "peer-a", 1, 0));
+        log.observe(&seen(2, "peer-b", 1, 500));
+        // Peer-a's first sighting of hash 1 leaves the window, peer-b's stays.
+        log.observe(&seen(3, "peer-a", 2, 1_200));
+        assert_eq!(log.hashes[&[1; 32]].observations, 1);
+
+        // A later sighting still lags behind peer-a instead of counting as
+        // a first one.
+        log.observe(&seen(4, "peer-c", 1, 1_300));
+        let peer_c = log
+            .stats(ObserverGroup::Peer, 1_000)
+            .into_iter()
+            .find(|row| row.observer == "peer-c")
+            .expect("peer-c row");
+        assert_eq!((peer_c.first_seen, peer_c.unique_hashes), (0, 0));
+        assert_eq!(peer_c.median_lag_ms, Some(1_300));
+
+        // Logged after newer observations but stamped earlier.
+        log.observe(&seen(5, "peer-a", 3, 900));
+        log.observe(&seen(6, "peer-d", 4, 2_000));
+        assert_eq!(
+            log.observations
+                .iter()
+                .map(|observation| observation.at_unix_ms)
+                .collect::<Vec<_>>(),
+            vec![1_200, 1_300, 2_000]
+        );
+        assert!(!log.hashes.contains_key(&[3; 32]));
+
+        log.observe(&seen(7, "peer-d", 5, 2_400));
+        assert!(!log.hashes.contains_key(&[1; 32]));
+        assert_eq!(log.hashes.len(), 2);
+    }
+}
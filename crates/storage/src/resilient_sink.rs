@@ -0,0 +1,437 @@
+//! Retrying, spilling wrapper around a [`ClickHouseBatchSink`].
+//!
+//! A batch is retried with exponential backoff a bounded number of times so
+//! a slow or unreachable ClickHouse never holds the storage writer for long.
+//! Batches that still fail are written to a local spill directory and
+//! drained in seq order by a background task once the server accepts
+//! inserts again. While a backlog exists new batches are spilled straight
+//! away, so the writer never waits on a server that is known to be down.
+//! Redelivery is safe because the HTTP sink sends a deterministic dedup token
+//! with every insert.
+
+use crate::wal_record::{frame_record, take_frame};
+use crate::{ClickHouseBatchSink, Result, StorageError, unix_now_ms};
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use event_log::{EventEncoding, EventEnvelope, decode_event_stream};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+const SPILL_EXTENSION: &str = "spill";
+
+/// Bounded retry applied to each batch before it is spilled.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ClickHouseRetryConfig {
+    /// Delivery attempts per batch, including the first.
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for ClickHouseRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 2_000,
+        }
+    }
+}
+
+impl ClickHouseRetryConfig {
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1_u64 << attempt.min(16);
+        Duration::from_millis(
+            self.initial_backoff_ms
+                .saturating_mul(factor)
+                .min(self.max_backoff_ms),
+        )
+    }
+}
+
+/// Local directory holding batches ClickHouse did not accept.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ClickHouseSpillConfig {
+    pub dir: PathBuf,
+    /// Spilled bytes above which further failed batches are dropped.
+    pub max_bytes: u64,
+    /// How often the background task tries to drain the spill directory.
+    pub drain_interval_ms: u64,
+}
+
+impl ClickHouseSpillConfig {
+    /// Spill configuration for `dir` with a 1 GiB cap and a 5 s drain interval.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes: 1024 * 1024 * 1024,
+            drain_interval_ms: 5_000,
+        }
+    }
+}
+
+/// Retry and spill settings for [`ResilientClickHouseSink`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ResilientClickHouseSinkConfig {
+    pub retry: ClickHouseRetryConfig,
+    /// Without a spill directory, batches that exhaust their retries are
+    /// dropped and the error is returned to the writer.
+    pub spill: Option<ClickHouseSpillConfig>,
+}
+
+/// Coarse delivery state reported by `/health`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickHouseSinkStatus {
+    /// The last delivery succeeded and nothing is waiting in the spill.
+    #[default]
+    Healthy,
+    /// Deliveries are failing or a spill backlog is still draining; no
+    /// batch has been lost.
+    Degraded,
+    /// Deliveries are failing and failed batches are being dropped.
+    Down,
+}
+
+impl ClickHouseSinkStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Degraded => "degraded",
+            Self::Down => "down",
+        }
+    }
+}
+
+/// Point-in-time delivery health and counters of a ClickHouse sink.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ClickHouseSinkHealth {
+    pub status: ClickHouseSinkStatus,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_success_unix_ms: Option<i64>,
+    pub spilled_batches: u64,
+    pub spilled_bytes: u64,
+    pub batches_delivered_total: u64,
+    pub retries_total: u64,
+    pub failures_total: u64,
+    pub batches_spilled_total: u64,
+    pub batches_drained_total: u64,
+    pub batches_dropped_total: u64,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    health: ClickHouseSinkHealth,
+    /// Whether the most recent failed batch was dropped rather than spilled.
+    dropping: bool,
+}
+
+/// [`ClickHouseBatchSink`] wrapper adding bounded retries, a spill directory
+/// and background draining.
+pub struct ResilientClickHouseSink<S> {
+    inner: S,
+    retry: ClickHouseRetryConfig,
+    spill: Option<SpillDir>,
+    state: Mutex<HealthState>,
+    drain_lock: tokio::sync::Mutex<()>,
+}
+
+impl<S: ClickHouseBatchSink + 'static> ResilientClickHouseSink<S> {
+    /// Wraps `inner` without starting the background drain task; callers
+    /// drain with [`Self::drain_spill`].
+    pub fn new(inner: S, config: ResilientClickHouseSinkConfig) -> Result<Self> {
+        let spill = config.spill.map(SpillDir::open).transpose()?;
+        let mut state = HealthState::default();
+        if let Some(spill) = &spill {
+            let files = spill.files()?;
+            state.health.spilled_batches = files.len() as u64;
+            state.health.spilled_bytes = files.iter().map(|(_, bytes)| bytes).sum();
+        }
+        Ok(Self {
+            inner,
+            retry: ClickHouseRetryConfig {
+                max_attempts: config.retry.max_attempts.max(1),
+                ..config.retry
+            },
+            spill,
+            state: Mutex::new(state),
+            drain_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Wraps `inner` and, with a spill directory, starts a task that drains
+    /// it every `drain_interval_ms` for as long as the sink is alive.
+    pub fn spawn(inner: S, config: ResilientClickHouseSinkConfig) -> Result<Arc<Self>> {
+        let interval = config
+            .spill
+            .as_ref()
+            .map(|spill| Duration::from_millis(spill.drain_interval_ms.max(1)));
+        let sink = Arc::new(Self::new(inner, config)?);
+        if let Some(interval) = interval {
+            let weak = Arc::downgrade(&sink);
+            tokio::spawn(drain_loop(weak, interval));
+        }
+        Ok(sink)
+    }
+
+    /// Returns the current delivery health.
+    pub fn health_snapshot(&self) -> ClickHouseSinkHealth {
+        let state = self.state.lock();
+        let mut health = state.health.clone();
+        health.status = if health.consecutive_failures > 0 && state.dropping {
+            ClickHouseSinkStatus::Down
+        } else if health.consecutive_failures > 0 || health.spilled_batches > 0 {
+            ClickHouseSinkStatus::Degraded
+        } else {
+            ClickHouseSinkStatus::Healthy
+        };
+        health
+    }
+
+    /// Delivers spilled batches oldest first, stopping at the first failure,
+    /// and returns how many were delivered.
+    pub async fn drain_spill(&self) -> Result<usize> {
+        let Some(spill) = &self.spill else {
+            return Ok(0);
+        };
+        let _guard = self.drain_lock.lock().await;
+        let mut drained = 0;
+        for (path, bytes) in spill.files()? {
+            let events = match SpillDir::read(&path) {
+                Ok(events) => events,
+                Err(err) => {
+                    tracing::warn!(path = %path.display(), error = %err, "quarantining unreadable clickhouse spill file");
+                    let _ = fs::rename(&path, path.with_extension("corrupt"));
+                    let mut state = self.state.lock();
+                    Self::forget_spilled(&mut state.health, bytes);
+                    state.health.batches_dropped_total += 1;
+                    continue;
+                }
+            };
+            if let Err(err) = self.inner.flush_event_batch(events).await {
+                self.record_failure(&err);
+                return Ok(drained);
+            }
+            fs::remove_file(&path)
+                .with_context(|| format!("remove drained spill file {}", path.display()))
+                .map_err(StorageError::clickhouse_batch)?;
+            let mut state = self.state.lock();
+            Self::forget_spilled(&mut state.health, bytes);
+            state.health.batches_drained_total += 1;
+            Self::record_success(&mut state);
+            drained += 1;
+        }
+        Ok(drained)
+    }
+
+    async fn deliver(&self, events: &[EventEnvelope]) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            match self.inner.flush_event_batch(events.to_vec()).await {
+                Ok(()) => {
+                    let mut state = self.state.lock();
+                    state.health.batches_delivered_total += 1;
+                    Self::record_success(&mut state);
+                    return Ok(());
+                }
+                Err(err) => {
+                    self.record_failure(&err);
+                    attempt += 1;
+                    if attempt >= self.retry.max_attempts {
+                        return Err(err);
+                    }
+                    self.state.lock().health.retries_total += 1;
+                    tokio::time::sleep(self.retry.backoff(attempt - 1)).await;
+                }
+            }
+        }
+    }
+
+    fn spill_batch(&self, spill: &SpillDir, events: &[EventEnvelope]) -> Result<()> {
+        let spilled_bytes = self.state.lock().health.spilled_bytes;
+        let written = spill.write(events, spilled_bytes)?;
+        let mut state = self.state.lock();
+        state.dropping = false;
+        state.health.spilled_batches += 1;
+        state.health.spilled_bytes += written;
+        state.health.batches_spilled_total += 1;
+        Ok(())
+    }
+
+    fn record_success(state: &mut HealthState) {
+        state.dropping = false;
+        state.health.consecutive_failures = 0;
+        state.health.last_success_unix_ms = Some(unix_now_ms());
+    }
+
+    fn record_failure(&self, err: &StorageError) {
+        let mut state = self.state.lock();
+        state.health.consecutive_failures = state.health.consecutive_failures.saturating_add(1);
+        state.health.failures_total += 1;
+        state.health.last_error = Some(err.to_string());
+    }
+
+    fn forget_spilled(health: &mut ClickHouseSinkHealth, bytes: u64) {
+        health.spilled_batches = health.spilled_batches.saturating_sub(1);
+        health.spilled_bytes = health.spilled_bytes.saturating_sub(bytes);
+    }
+}
+
+#[async_trait]
+impl<S: ClickHouseBatchSink + 'static> ClickHouseBatchSink for ResilientClickHouseSink<S> {
+    async fn flush_event_batch(&self, events: Vec<EventEnvelope>) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let backlog = self.state.lock().health.spilled_batches > 0;
+        let result = match &self.spill {
+            Some(spill) if backlog => self.spill_batch(spill, &events),
+            Some(spill) => match self.deliver(&events).await {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    tracing::warn!(error = %err, "clickhouse batch failed after retries, spilling");
+                    self.spill_batch(spill, &events)
+                }
+            },
+            None => self.deliver(&events).await,
+        };
+        if result.is_err() {
+            let mut state = self.state.lock();
+            state.dropping = true;
+            state.health.batches_dropped_total += 1;
+        }
+        result
+    }
+
+    fn health(&self) -> Option<ClickHouseSinkHealth> {
+        Some(self.health_snapshot())
+    }
+}
+
+async fn drain_loop<S: ClickHouseBatchSink + 'static>(
+    sink: Weak<ResilientClickHouseSink<S>>,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let Some(sink) = sink.upgrade() else {
+            return;
+        };
+        match sink.drain_spill().await {
+            Ok(0) => {}
+            Ok(drained) => tracing::info!(drained, "drained clickhouse spill batches"),
+            Err(err) => tracing::warn!(error = %err, "failed to drain clickhouse spill"),
+        }
+    }
+}
+
+/// Spill directory: one CRC-framed file of binary-encoded events per batch,
+/// named by the batch's seq range so a listing sorts oldest first.
+struct SpillDir {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl SpillDir {
+    fn open(config: ClickHouseSpillConfig) -> Result<Self> {
+        fs::create_dir_all(&config.dir)
+            .with_context(|| format!("create spill dir {}", config.dir.display()))
+            .map_err(StorageError::clickhouse_batch)?;
+        Ok(Self {
+            dir: config.dir,
+            max_bytes: config.max_bytes,
+        })
+    }
+
+    fn files(&self) -> Result<Vec<(PathBuf, u64)>> {
+        let entries = fs::read_dir(&self.dir)
+            .with_context(|| format!("list spill dir {}", self.dir.display()))
+            .map_err(StorageError::clickhouse_batch)?;
+        let mut files = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry.path().extension().and_then(|ext| ext.to_str()) == Some(SPILL_EXTENSION)
+            })
+            .map(|entry| {
+                let bytes = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+                (entry.path(), bytes)
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Durably writes `events` and returns the file size; refuses the batch
+    /// when it would take the directory past `max_bytes`.
+    fn write(&self, events: &[EventEnvelope], spilled_bytes: u64) -> Result<u64> {
+        let mut encoded = Vec::new();
+        for event in events {
+            EventEncoding::Binary
+                .encode_into(event, &mut encoded)
+                .map_err(|err| {
+                    StorageError::clickhouse_batch(anyhow!("encode spill batch: {err}"))
+                })?;
+        }
+        let mut framed = Vec::with_capacity(encoded.len() + 8);
+        frame_record(&encoded, &mut framed);
+        let bytes = framed.len() as u64;
+        if spilled_bytes.saturating_add(bytes) > self.max_bytes {
+            return Err(StorageError::clickhouse_batch(anyhow!(
+                "clickhouse spill dir {} is full ({spilled_bytes} of {} bytes)",
+                self.dir.display(),
+                self.max_bytes
+            )));
+        }
+        let first = events.first().map_or(0, |event| event.seq_id);
+        let last = events.last().map_or(0, |event| event.seq_id);
+        let path = self
+            .dir
+            .join(format!("{first:020}-{last:020}.{SPILL_EXTENSION}"));
+        let tmp = path.with_extension("tmp");
+        (|| -> anyhow::Result<()> {
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(&framed)?;
+            file.sync_all()?;
+            fs::rename(&tmp, &path)?;
+            Ok(())
+        })()
+        .with_context(|| format!("write spill file {}", path.display()))
+        .map_err(StorageError::clickhouse_batch)?;
+        Ok(bytes)
+    }
+
+    fn read(path: &Path) -> anyhow::Result<Vec<EventEnvelope>> {
+        let bytes = fs::read(path)?;
+        let mut offset = 0;
+        let payload = take_frame(&bytes, &mut offset)
+            .filter(|_| offset == bytes.len())
+            .ok_or_else(|| anyhow!("spill file is torn or corrupt"))?;
+        Ok(decode_event_stream(payload)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let retry = ClickHouseRetryConfig {
+            max_attempts: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 350,
+        };
+        let delays = (0..4)
+            .map(|attempt| retry.backoff(attempt).as_millis())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![100, 200, 350, 350]);
+    }
+}
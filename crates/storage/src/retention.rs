@@ -0,0 +1,486 @@
+//! Byte-accounted retention for the in-memory event ring and projection tables.
+//!
+//! Every retained row is charged an estimate of its footprint: its inline size
+//! plus the heap data it owns (calldata, strings, reason lists). Hash-keyed
+//! tables are also charged for the latest-row copy held by their lookup map.
+//! With a [`MemoryBudgetConfig`], storage evicts rows while the total exceeds
+//! the budget: each table is entitled to a weighted share of it, and among the
+//! tables above their share the one holding the oldest row gives that row up
+//! first. The item-count capacities of `StorageConfig` still apply on top.
+
+use crate::calldata_blobs::CalldataBlobStats;
+use crate::{
+    BuilderLifecycleRecord, OpportunityRecord, PeerStatsRecord, SimulationRecord, TxFeaturesRecord,
+    TxFullRecord, TxLifecycleRecord, TxSeenRecord, UserOpRecord,
+};
+use event_log::{DropReason, EventEnvelope, EventPayload};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem::size_of;
+use std::str::FromStr;
+
+const TABLE_COUNT: usize = StorageTable::ALL.len();
+
+/// Bounded in-memory table subject to retention.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageTable {
+    Events,
+    TxSeen,
+    TxFull,
+    TxFeatures,
+    TxLifecycle,
+    Opportunities,
+    BuilderLifecycle,
+    Simulations,
+    UserOps,
+    PeerStats,
+}
+
+impl StorageTable {
+    pub const ALL: [Self; 10] = [
+        Self::Events,
+        Self::TxSeen,
+        Self::TxFull,
+        Self::TxFeatures,
+        Self::TxLifecycle,
+        Self::Opportunities,
+        Self::BuilderLifecycle,
+        Self::Simulations,
+        Self::UserOps,
+        Self::PeerStats,
+    ];
+
+    /// Returns the snake_case name used in configuration and metrics labels.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Events => "events",
+            Self::TxSeen => "tx_seen",
+            Self::TxFull => "tx_full",
+            Self::TxFeatures => "tx_features",
+            Self::TxLifecycle => "tx_lifecycle",
+            Self::Opportunities => "opportunities",
+            Self::BuilderLifecycle => "builder_lifecycle",
+            Self::Simulations => "simulations",
+            Self::UserOps => "user_ops",
+            Self::PeerStats => "peer_stats",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for StorageTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for StorageTable {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|table| table.as_str() == value)
+            .ok_or_else(|| format!("unknown storage table `{value}`"))
+    }
+}
+
+/// Relative share of the memory budget each table is entitled to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TableWeights([u32; TABLE_COUNT]);
+
+impl Default for TableWeights {
+    /// Favors the event ring and `tx_full`, which hold the bulk of the data.
+    fn default() -> Self {
+        let mut weights = [1; TABLE_COUNT];
+        weights[StorageTable::Events.index()] = 4;
+        weights[StorageTable::TxFull.index()] = 4;
+        Self(weights)
+    }
+}
+
+impl TableWeights {
+    pub fn get(&self, table: StorageTable) -> u32 {
+        self.0[table.index()]
+    }
+
+    pub fn with(mut self, table: StorageTable, weight: u32) -> Self {
+        self.0[table.index()] = weight;
+        self
+    }
+
+    /// Applies comma-separated `table=weight` overrides, such as
+    /// `tx_full=8,peer_stats=0`, on top of the defaults.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .try_fold(Self::default(), |weights, entry| {
+                let (table, weight) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected `table=weight`, got `{entry}`"))?;
+                let weight = weight
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|err| format!("invalid weight in `{entry}`: {err}"))?;
+                Ok(weights.with(table.trim().parse()?, weight))
+            })
+    }
+
+    fn total(&self) -> u64 {
+        self.0.iter().map(|weight| u64::from(*weight)).sum()
+    }
+}
+
+/// Global byte budget for the in-memory tables.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MemoryBudgetConfig {
+    pub max_bytes: usize,
+    pub weights: TableWeights,
+}
+
+impl MemoryBudgetConfig {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            weights: TableWeights::default(),
+        }
+    }
+
+    /// Bytes `table` may hold before it becomes an eviction candidate; a
+    /// zero-weight table is always one.
+    pub fn share_bytes(&self, table: StorageTable) -> usize {
+        let total = self.weights.total();
+        if total == 0 {
+            return 0;
+        }
+        let share =
+            self.max_bytes as u128 * u128::from(self.weights.get(table)) / u128::from(total);
+        usize::try_from(share).unwrap_or(usize::MAX)
+    }
+}
+
+/// Retained rows and estimated bytes of one table.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TableMemoryUsage {
+    pub table: StorageTable,
+    pub rows: usize,
+    pub bytes: usize,
+    /// Weighted share of the budget, when one is configured.
+    pub budget_bytes: Option<usize>,
+    /// Rows evicted to stay within the budget rather than by item count.
+    pub budget_evictions_total: u64,
+}
+
+/// Estimated memory held by storage, per table.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StorageMemoryUsage {
+    pub total_bytes: usize,
+    pub budget_bytes: Option<usize>,
+    pub tables: Vec<TableMemoryUsage>,
+    /// Offloaded calldata, when a blob store is attached.
+    pub calldata_blobs: Option<CalldataBlobStats>,
+}
+
+/// Estimated bytes a row keeps alive while retained.
+pub(crate) trait RetainedBytes {
+    fn retained_bytes(&self) -> usize;
+}
+
+fn strings_bytes(values: &[String]) -> usize {
+    values
+        .iter()
+        .map(|value| size_of::<String>() + value.len())
+        .sum()
+}
+
+fn option_string_bytes(value: &Option<String>) -> usize {
+    value.as_ref().map_or(0, String::len)
+}
+
+impl RetainedBytes for EventEnvelope {
+    /// Counts the envelope twice: the ring buffer and the sorted index each
+    /// hold a copy.
+    fn retained_bytes(&self) -> usize {
+        let heap = self.source_id.0.len()
+            + match &self.payload {
+                EventPayload::TxSeen(seen) => seen.peer_id.len(),
+                EventPayload::CandidateQueued(queued) => {
+                    queued.candidate_id.len()
+                        + queued.member_tx_hashes.len() * size_of::<common::TxHash>()
+                        + queued.strategy.len()
+                        + queued.protocol.len()
+                        + queued.category.len()
+                        + queued.feature_engine_version.len()
+                        + queued.scorer_version.len()
+                        + queued.strategy_version.len()
+                        + strings_bytes(&queued.reasons)
+                }
+                EventPayload::SimDispatched(dispatched) => {
+                    dispatched.candidate_id.len()
+                        + dispatched.member_tx_hashes.len() * size_of::<common::TxHash>()
+                }
+                EventPayload::OppDetected(detected) => {
+                    detected.strategy.len()
+                        + detected.protocol.len()
+                        + detected.category.len()
+                        + detected.feature_engine_version.len()
+                        + detected.scorer_version.len()
+                        + detected.strategy_version.len()
+                        + strings_bytes(&detected.reasons)
+                }
+                EventPayload::SimCompleted(completed) => {
+                    completed.sim_id.len()
+                        + completed.feature_engine_version.len()
+                        + completed.scorer_version.len()
+                        + completed.strategy_version.len()
+                }
+                EventPayload::AssemblyDecisionApplied(applied) => {
+                    applied.candidate_id.len()
+                        + strings_bytes(&applied.replaced_candidate_ids)
+                        + option_string_bytes(&applied.reason)
+                }
+                EventPayload::BundleSubmitted(submitted) => {
+                    submitted.bundle_id.len()
+                        + submitted.sim_id.len()
+                        + submitted.relay.len()
+                        + submitted.feature_engine_version.len()
+                        + submitted.scorer_version.len()
+                        + submitted.strategy_version.len()
+                }
+                EventPayload::TxDropped(dropped) => {
+                    option_string_bytes(&dropped.detail)
+                        + match &dropped.reason {
+                            DropReason::Other(reason) => reason.len(),
+                            _ => 0,
+                        }
+                }
+                EventPayload::UserOpSeen(seen) => seen.call_data.len(),
+                EventPayload::ChainCheckpoint(checkpoint) => {
+                    checkpoint.key_id.len() + checkpoint.signature.len()
+                }
+                EventPayload::TxFetched(_)
+                | EventPayload::TxDecoded(_)
+                | EventPayload::TxReady(_)
+                | EventPayload::TxBlocked(_)
+                | EventPayload::TxReplaced(_)
+                | EventPayload::TxConfirmedProvisional(_)
+                | EventPayload::TxConfirmedFinal(_)
+                | EventPayload::TxReorged(_) => 0,
+            };
+        2 * (size_of::<Self>() + heap)
+    }
+}
+
+impl RetainedBytes for TxSeenRecord {
+    fn retained_bytes(&self) -> usize {
+        size_of::<Self>() + self.peer.len()
+    }
+}
+
+impl RetainedBytes for TxFullRecord {
+    fn retained_bytes(&self) -> usize {
+        size_of::<Self>()
+            + self.raw_tx.len()
+            + self
+                .l2_fields
+                .as_ref()
+                .map_or(0, |fields| size_of_val(fields.as_ref()))
+    }
+}
+
+impl RetainedBytes for TxFeaturesRecord {
+    fn retained_bytes(&self) -> usize {
+        size_of::<Self>()
+            + self.protocol.len()
+            + self.category.len()
+            + self.feature_engine_version.len()
+    }
+}
+
+impl RetainedBytes for TxLifecycleRecord {
+    fn retained_bytes(&self) -> usize {
+        size_of::<Self>() + self.status.len() + option_string_bytes(&self.reason)
+    }
+}
+
+impl RetainedBytes for OpportunityRecord {
+    fn retained_bytes(&self) -> usize {
+        size_of::<Self>()
+            + self.strategy.len()
+            + self.protocol.len()
+            + self.category.len()
+            + self.feature_engine_version.len()
+            + self.scorer_version.len()
+            + self.strategy_version.len()
+            + strings_bytes(&self.reasons)
+    }
+}
+
+impl RetainedBytes for BuilderLifecycleRecord {
+    fn retained_bytes(&self) -> usize {
+        size_of::<Self>()
+            + self.candidate_id.len()
+            + strings_bytes(&self.replaced_candidate_ids)
+            + option_string_bytes(&self.reason)
+    }
+}
+
+impl RetainedBytes for SimulationRecord {
+    fn retained_bytes(&self) -> usize {
+        size_of::<Self>() + self.sim_id.len()
+    }
+}
+
+impl RetainedBytes for UserOpRecord {
+    fn retained_bytes(&self) -> usize {
+        size_of::<Self>()
+            + self.feature_engine_version.len()
+            + self
+                .inner_calls
+                .iter()
+                .map(|call| size_of_val(call) + call.protocol.len() + call.category.len())
+                .sum::<usize>()
+    }
+}
+
+impl RetainedBytes for PeerStatsRecord {
+    fn retained_bytes(&self) -> usize {
+        size_of::<Self>() + self.peer.len()
+    }
+}
+
+/// Per-table byte totals plus, when a budget is set, the insertion tick of
+/// every retained row so the oldest row across tables can be found.
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryAccounting {
+    bytes: [usize; TABLE_COUNT],
+    budget_evictions: [u64; TABLE_COUNT],
+    ticks: Option<[VecDeque<u64>; TABLE_COUNT]>,
+    next_tick: u64,
+}
+
+impl MemoryAccounting {
+    pub(crate) fn new(track_age: bool) -> Self {
+        Self {
+            bytes: [0; TABLE_COUNT],
+            budget_evictions: [0; TABLE_COUNT],
+            ticks: track_age.then(|| std::array::from_fn(|_| VecDeque::new())),
+            next_tick: 0,
+        }
+    }
+
+    pub(crate) fn charge(&mut self, table: StorageTable, bytes: usize) {
+        let total = &mut self.bytes[table.index()];
+        *total = total.saturating_add(bytes);
+    }
+
+    pub(crate) fn release(&mut self, table: StorageTable, bytes: usize) {
+        let total = &mut self.bytes[table.index()];
+        *total = total.saturating_sub(bytes);
+    }
+
+    /// Records a row appended to the back of `table`.
+    pub(crate) fn row_pushed(&mut self, table: StorageTable) {
+        if let Some(ticks) = self.ticks.as_mut() {
+            ticks[table.index()].push_back(self.next_tick);
+            self.next_tick += 1;
+        }
+    }
+
+    /// Records a row removed from the front of `table`.
+    pub(crate) fn row_popped(&mut self, table: StorageTable) {
+        if let Some(ticks) = self.ticks.as_mut() {
+            ticks[table.index()].pop_front();
+        }
+    }
+
+    pub(crate) fn record_budget_eviction(&mut self, table: StorageTable) {
+        self.budget_evictions[table.index()] += 1;
+    }
+
+    pub(crate) fn bytes(&self, table: StorageTable) -> usize {
+        self.bytes[table.index()]
+    }
+
+    pub(crate) fn budget_evictions(&self, table: StorageTable) -> u64 {
+        self.budget_evictions[table.index()]
+    }
+
+    pub(crate) fn total(&self) -> usize {
+        self.bytes
+            .iter()
+            .fold(0, |total, bytes| total.saturating_add(*bytes))
+    }
+
+    /// Picks the table to evict from next: the one holding the oldest row
+    /// among those above their share of `budget`.
+    pub(crate) fn budget_victim(&self, budget: &MemoryBudgetConfig) -> Option<StorageTable> {
+        let ticks = self.ticks.as_ref()?;
+        StorageTable::ALL
+            .into_iter()
+            .filter(|table| self.bytes(*table) > budget.share_bytes(*table))
+            .filter_map(|table| ticks[table.index()].front().map(|tick| (*tick, table)))
+            .min()
+            .map(|(_, table)| table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_parse_overrides_and_split_the_budget() {
+        let weights = TableWeights::parse("tx_full = 6, peer_stats=0,").unwrap();
+        assert_eq!(weights.get(StorageTable::TxFull), 6);
+        assert_eq!(weights.get(StorageTable::PeerStats), 0);
+        assert_eq!(weights.get(StorageTable::Events), 4);
+        assert!(TableWeights::parse("mempool=1").is_err());
+        assert!(TableWeights::parse("tx_full").is_err());
+
+        let budget = MemoryBudgetConfig {
+            max_bytes: 1_600,
+            weights,
+        };
+        // 4 + 6 + 7 * 1 + 0 = 17 weight units.
+        assert_eq!(budget.share_bytes(StorageTable::TxFull), 1_600 * 6 / 17);
+        assert_eq!(budget.share_bytes(StorageTable::PeerStats), 0);
+    }
+
+    #[test]
+    fn budget_victim_is_the_oldest_row_among_tables_over_their_share() {
+        let budget = MemoryBudgetConfig {
+            max_bytes: 100,
+            weights: TableWeights([1; TABLE_COUNT]).with(StorageTable::Events, 91),
+        };
+        let mut accounting = MemoryAccounting::new(true);
+        for table in [
+            StorageTable::Events,
+            StorageTable::TxSeen,
+            StorageTable::TxFull,
+            StorageTable::TxFull,
+        ] {
+            accounting.row_pushed(table);
+            accounting.charge(table, 40);
+        }
+        // The event row is the oldest, but events stay within their share.
+        assert_eq!(accounting.total(), 160);
+        assert_eq!(
+            accounting.budget_victim(&budget),
+            Some(StorageTable::TxSeen)
+        );
+        accounting.row_popped(StorageTable::TxSeen);
+        accounting.release(StorageTable::TxSeen, 40);
+        assert_eq!(
+            accounting.budget_victim(&budget),
+            Some(StorageTable::TxFull)
+        );
+        assert_eq!(MemoryAccounting::new(false).budget_victim(&budget), None);
+    }
+}
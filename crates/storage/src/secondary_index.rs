@@ -0,0 +1,255 @@
+//! Secondary indexes over the bounded transaction and opportunity tables.
+//!
+//! Each index maps an attribute value to the hashes (or opportunity rows)
+//! currently retained for it, ordered by an ordinal that grows with every
+//! upsert. Newest-first pages walk those ordinals downwards, and a page's
+//! `next_cursor` is the ordinal to pass back as `before`. Rows leave an
+//! index when their table evicts the last copy of them, so the indexes only
+//! ever cover the in-memory window.
+
+use crate::{FastMap, OpportunityRecord, TxFeaturesRecord, TxFullRecord};
+use common::{Address, TxHash};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::hash::Hash;
+
+/// Attribute value a transaction secondary index is keyed on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TxIndexKey {
+    Sender(Address),
+    To(Address),
+    ChainId(u64),
+    /// Feature-engine protocol of the transaction's latest feature row.
+    Protocol(String),
+}
+
+/// One newest-first page request against a secondary index.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IndexPageQuery {
+    /// Exclusive upper bound; the `next_cursor` of the previous page.
+    pub before: Option<u64>,
+    pub limit: usize,
+    /// Inclusive lower bound on first-seen (or detection) time.
+    pub from_unix_ms: Option<i64>,
+    /// Inclusive upper bound on first-seen (or detection) time.
+    pub to_unix_ms: Option<i64>,
+    /// Required lifecycle status, compared case-insensitively; transaction
+    /// indexes only.
+    pub status: Option<String>,
+}
+
+impl IndexPageQuery {
+    pub(crate) fn in_window(&self, unix_ms: Option<i64>) -> bool {
+        if self.from_unix_ms.is_none() && self.to_unix_ms.is_none() {
+            return true;
+        }
+        unix_ms.is_some_and(|unix_ms| {
+            self.from_unix_ms.is_none_or(|from| unix_ms >= from)
+                && self.to_unix_ms.is_none_or(|to| unix_ms <= to)
+        })
+    }
+}
+
+/// One page of index matches, newest first.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct IndexPage<T> {
+    pub rows: Vec<T>,
+    /// Cursor for the next page, or `None` once the index is exhausted.
+    pub next_cursor: Option<u64>,
+}
+
+impl<T> Default for IndexPage<T> {
+    fn default() -> Self {
+        Self {
+            rows: Vec::new(),
+            next_cursor: None,
+        }
+    }
+}
+
+/// Collects up to `limit` accepted entries from a descending `(ordinal, value)`
+/// stream, setting `next_cursor` only when more entries remain.
+pub(crate) fn collect_page<T, U>(
+    entries: impl Iterator<Item = (u64, T)>,
+    limit: usize,
+    mut accept: impl FnMut(T) -> Option<U>,
+) -> IndexPage<U> {
+    let limit = limit.max(1);
+    let mut page = IndexPage::default();
+    let mut entries = entries.peekable();
+    while let Some((ordinal, value)) = entries.next() {
+        if let Some(row) = accept(value) {
+            page.rows.push(row);
+            if page.rows.len() == limit {
+                if entries.peek().is_some() {
+                    page.next_cursor = Some(ordinal);
+                }
+                break;
+            }
+        }
+    }
+    page
+}
+
+/// Hashes per attribute value, each held once under the ordinal of its
+/// latest upsert.
+#[derive(Clone, Debug)]
+struct HashPostings<K> {
+    postings: FastMap<K, BTreeMap<u64, TxHash>>,
+    entries: FastMap<TxHash, (K, u64)>,
+}
+
+impl<K> Default for HashPostings<K> {
+    fn default() -> Self {
+        Self {
+            postings: FastMap::default(),
+            entries: FastMap::default(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> HashPostings<K> {
+    /// Moves `hash` under `key` at `ordinal`, or drops it when `key` is `None`.
+    fn insert(&mut self, hash: TxHash, key: Option<K>, ordinal: u64) {
+        self.remove(&hash);
+        if let Some(key) = key {
+            self.postings
+                .entry(key.clone())
+                .or_default()
+                .insert(ordinal, hash);
+            self.entries.insert(hash, (key, ordinal));
+        }
+    }
+
+    fn remove(&mut self, hash: &TxHash) {
+        let Some((key, ordinal)) = self.entries.remove(hash) else {
+            return;
+        };
+        if let Some(postings) = self.postings.get_mut(&key) {
+            postings.remove(&ordinal);
+            if postings.is_empty() {
+                self.postings.remove(&key);
+            }
+        }
+    }
+
+    fn newest_first(
+        &self,
+        key: &K,
+        before: Option<u64>,
+    ) -> impl Iterator<Item = (u64, TxHash)> + '_ {
+        self.postings
+            .get(key)
+            .into_iter()
+            .flat_map(move |postings| postings.range(..before.unwrap_or(u64::MAX)).rev())
+            .map(|(ordinal, hash)| (*ordinal, *hash))
+    }
+}
+
+/// Sender, recipient, chain and protocol indexes over the latest `tx_full`
+/// and `tx_features` row of each retained hash.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TxSecondaryIndexes {
+    next_ordinal: u64,
+    sender: HashPostings<Address>,
+    to: HashPostings<Address>,
+    chain_id: HashPostings<u64>,
+    protocol: HashPostings<String>,
+}
+
+impl TxSecondaryIndexes {
+    fn next_ordinal(&mut self) -> u64 {
+        self.next_ordinal = self.next_ordinal.saturating_add(1);
+        self.next_ordinal
+    }
+
+    pub(crate) fn index_full(&mut self, record: &TxFullRecord) {
+        let ordinal = self.next_ordinal();
+        self.sender
+            .insert(record.hash, Some(record.sender), ordinal);
+        self.to.insert(record.hash, record.to, ordinal);
+        self.chain_id.insert(record.hash, record.chain_id, ordinal);
+    }
+
+    pub(crate) fn forget_full(&mut self, hash: &TxHash) {
+        self.sender.remove(hash);
+        self.to.remove(hash);
+        self.chain_id.remove(hash);
+    }
+
+    pub(crate) fn index_features(&mut self, record: &TxFeaturesRecord) {
+        let ordinal = self.next_ordinal();
+        self.protocol
+            .insert(record.hash, Some(record.protocol.clone()), ordinal);
+    }
+
+    pub(crate) fn forget_features(&mut self, hash: &TxHash) {
+        self.protocol.remove(hash);
+    }
+
+    pub(crate) fn newest_first<'a>(
+        &'a self,
+        key: &'a TxIndexKey,
+        before: Option<u64>,
+    ) -> Box<dyn Iterator<Item = (u64, TxHash)> + 'a> {
+        match key {
+            TxIndexKey::Sender(sender) => Box::new(self.sender.newest_first(sender, before)),
+            TxIndexKey::To(to) => Box::new(self.to.newest_first(to, before)),
+            TxIndexKey::ChainId(chain_id) => Box::new(self.chain_id.newest_first(chain_id, before)),
+            TxIndexKey::Protocol(protocol) => {
+                Box::new(self.protocol.newest_first(protocol, before))
+            }
+        }
+    }
+}
+
+/// Protocol index over the FIFO opportunity table. Opportunities are not
+/// unique per hash, so a row's ordinal is its absolute position in the table:
+/// the row at deque index `i` has ordinal `front_ordinal + i`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OpportunityProtocolIndex {
+    front_ordinal: u64,
+    postings: FastMap<String, VecDeque<u64>>,
+}
+
+impl OpportunityProtocolIndex {
+    /// Indexes a row just pushed onto a table that now holds `len` rows.
+    pub(crate) fn push(&mut self, record: &OpportunityRecord, len: usize) {
+        let ordinal = self.front_ordinal + len.saturating_sub(1) as u64;
+        self.postings
+            .entry(record.protocol.clone())
+            .or_default()
+            .push_back(ordinal);
+    }
+
+    /// Drops the row just evicted from the front of the table.
+    pub(crate) fn evict_front(&mut self, record: &OpportunityRecord) {
+        if let Some(postings) = self.postings.get_mut(&record.protocol) {
+            if postings.front() == Some(&self.front_ordinal) {
+                postings.pop_front();
+            }
+            if postings.is_empty() {
+                self.postings.remove(&record.protocol);
+            }
+        }
+        self.front_ordinal += 1;
+    }
+
+    /// Yields `(ordinal, deque index)` pairs for `protocol`, newest first.
+    pub(crate) fn newest_first<'a>(
+        &'a self,
+        protocol: &str,
+        before: Option<u64>,
+    ) -> impl Iterator<Item = (u64, usize)> + 'a {
+        let before = before.unwrap_or(u64::MAX);
+        let front_ordinal = self.front_ordinal;
+        self.postings
+            .get(protocol)
+            .into_iter()
+            .flat_map(move |postings| {
+                let end = postings.partition_point(|ordinal| *ordinal < before);
+                postings.range(..end).rev()
+            })
+            .map(move |ordinal| (*ordinal, (*ordinal - front_ordinal) as usize))
+    }
+}
@@ -1,18 +1,127 @@
 //! Simple segmented write-ahead log used to recover event batches after restart.
 
+use crate::checkpoint::StorageCheckpoint;
+use crate::wal_index::{
+    SegmentIndexBuilder, WalEventIter, WalScanRange, WalSegmentIndex, index_path, read_index,
+};
+use crate::wal_record::{
+    SEGMENT_HEADER_LEN, SegmentFormat, SegmentRead, WalIssue, frame_record, read_segment,
+    segment_header,
+};
+use crate::wal_writer::{WalDurability, WalWriter};
 use crate::{Result, StorageError};
 use anyhow::Context;
-use event_log::EventEnvelope;
-use std::fs::{self, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use event_log::{EventEncoding, EventEnvelope, GlobalSequencer, HashChainHead};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 
 const DEFAULT_SEGMENT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Sequencer position persisted next to the WAL before it is cleared, so a
+/// restart keeps numbering and hash chaining after already flushed events.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalHead {
+    pub latest_seq_id: Option<u64>,
+    /// Latest `chain_seq_id` assigned per chain id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub chain_seq_ids: BTreeMap<u64, u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_chain: Option<HashChainHead>,
+}
+
+impl WalHead {
+    /// Captures the position of a running sequencer.
+    pub fn of_sequencer(sequencer: &GlobalSequencer) -> Self {
+        Self {
+            latest_seq_id: sequencer.latest_seq_id(),
+            chain_seq_ids: sequencer.latest_chain_seq_ids().collect(),
+            hash_chain: sequencer.hash_chain().map(|chain| chain.snapshot()),
+        }
+    }
+
+    /// Moves the head past recovered events that were written after it.
+    pub fn observe(&mut self, events: &[EventEnvelope]) {
+        for event in events {
+            self.latest_seq_id = self.latest_seq_id.max(Some(event.seq_id));
+            if let (Some(chain_id), Some(chain_seq_id)) = (event.chain_id, event.chain_seq_id) {
+                let latest = self.chain_seq_ids.entry(chain_id).or_default();
+                *latest = (*latest).max(chain_seq_id);
+            }
+            if event.hash_link.is_some() {
+                self.hash_chain.get_or_insert_default().advance(event);
+            }
+        }
+    }
+}
+
+/// Events recovered from the WAL together with the damage skipped on the way.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WalRecovery {
+    pub events: Vec<EventEnvelope>,
+    pub issues: Vec<WalIssue>,
+    /// Bytes cut from the end of the active segment after a torn write.
+    pub truncated_bytes: u64,
+}
+
+/// Read-only integrity report produced by [`StorageWal::verify`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct WalVerifyReport {
+    pub segments: usize,
+    pub records: usize,
+    pub issues: Vec<WalIssue>,
+}
+
+impl WalVerifyReport {
+    /// Whether every segment decoded without damage.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Outcome of [`StorageWal::repair`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct WalRepairReport {
+    /// Damage that was removed from the segments.
+    pub issues: Vec<WalIssue>,
+    /// Files holding the removed bytes, one per damaged range.
+    pub quarantined: Vec<PathBuf>,
+    pub segments_rewritten: usize,
+}
+
+/// How long WAL segments already covered by a checkpoint are kept.
+///
+/// With neither bound set, covered segments are removed as soon as a
+/// checkpoint is written. Segments holding events newer than the checkpoint
+/// are never removed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WalRetention {
+    /// Keep covered segments whose newest event is younger than this.
+    pub max_age_ms: Option<u64>,
+    /// Keep covered segments while the whole WAL stays within this size.
+    pub max_bytes: Option<u64>,
+}
+
+/// Outcome of [`StorageWal::compact`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct WalCompaction {
+    pub segments_removed: usize,
+    pub bytes_removed: u64,
+}
+
 #[derive(Clone, Debug)]
-/// Line-delimited JSON WAL with simple size-based segment rotation.
+/// Segmented event WAL with simple size-based rotation.
+///
+/// Segments hold either JSON lines or binary frames, each record framed with
+/// its length and a CRC32C; recovery detects the encoding per file, so the
+/// write encoding can change across restarts.
 pub struct StorageWal {
     path: PathBuf,
     segment_max_bytes: u64,
+    encoding: EventEncoding,
 }
 
 impl StorageWal {
@@ -32,52 +141,225 @@ impl StorageWal {
         Ok(Self {
             path,
             segment_max_bytes: segment_max_bytes.max(1),
+            encoding: EventEncoding::default(),
         })
     }
 
+    /// Selects the encoding used for newly appended events.
+    pub fn with_encoding(mut self, encoding: EventEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the encoding used for newly appended events.
+    pub fn encoding(&self) -> EventEncoding {
+        self.encoding
+    }
+
     /// Appends one event to the current active segment.
+    ///
+    /// Opens the segment for this one record; long-running writers should
+    /// keep a [`WalWriter`] instead.
     pub fn append_event(&self, event: &EventEnvelope) -> Result<()> {
-        let segment_path = self.active_segment_path_for_append()?;
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&segment_path)
-            .with_context(|| format!("open WAL segment {} for append", segment_path.display()))
-            .map_err(StorageError::wal_write)?;
-        let encoded = serde_json::to_string(event)
-            .context("serialize WAL event")
-            .map_err(StorageError::wal_write)?;
-        writeln!(file, "{encoded}")
-            .with_context(|| format!("append WAL event to segment {}", segment_path.display()))
-            .map_err(StorageError::wal_write)?;
-        Ok(())
+        let mut writer = self.writer(WalDurability::None);
+        writer.append(event)?;
+        writer.commit()
+    }
+
+    /// Opens a long-lived writer that group-commits appended records.
+    pub fn writer(&self, durability: WalDurability) -> WalWriter {
+        WalWriter::new(self.clone(), durability)
     }
 
     /// Recovers and sorts all events currently present in the WAL.
+    ///
+    /// Damaged records are skipped; use [`StorageWal::recover`] to see them.
     pub fn recover_events(&self) -> Result<Vec<EventEnvelope>> {
-        let mut events = Vec::new();
-        let segments = self.segment_paths()?;
-        for (_, path) in segments {
-            events.extend(read_events_from_path(&path)?);
+        self.recover().map(|recovery| recovery.events)
+    }
+
+    /// Recovers all readable events, truncating a torn write at the end of
+    /// the active segment so new appends follow the last intact record.
+    ///
+    /// Corrupt records elsewhere are reported, not removed; run
+    /// [`StorageWal::repair`] to quarantine them.
+    pub fn recover(&self) -> Result<WalRecovery> {
+        self.recover_from(0)
+    }
+
+    /// Recovers events strictly after `after_seq_id`, the tail left on top
+    /// of a checkpoint.
+    ///
+    /// Sealed segments whose index shows nothing newer are not read; the
+    /// active segment always is, so a torn tail is still truncated.
+    pub fn recover_from(&self, after_seq_id: u64) -> Result<WalRecovery> {
+        let mut recovery = WalRecovery::default();
+        let files = self.wal_files()?;
+        let active = self.segment_paths()?.pop().map(|(_, path)| path);
+        for path in files {
+            if after_seq_id > 0
+                && active.as_ref() != Some(&path)
+                && segment_covered(&path, after_seq_id)?
+            {
+                continue;
+            }
+            let read = read_segment(&read_wal_file(&path)?);
+            if read.torn_tail() && active.as_ref() == Some(&path) {
+                let (offset, len, _) = read.issues[read.issues.len() - 1];
+                truncate_segment(&path, offset as u64)?;
+                recovery.truncated_bytes = len as u64;
+            }
+            recovery.issues.extend(segment_issues(&path, &read));
+            recovery.events.extend(
+                read.records
+                    .into_iter()
+                    .map(|(event, _)| event)
+                    .filter(|event| event.seq_id > after_seq_id),
+            );
         }
 
-        if self.path.exists() {
-            events.extend(read_events_from_path(&self.path)?);
+        recovery.events.sort_by_key(|event| event.seq_id);
+        Ok(recovery)
+    }
+
+    /// Checks every segment without modifying anything.
+    pub fn verify(&self) -> Result<WalVerifyReport> {
+        let mut report = WalVerifyReport::default();
+        for path in self.wal_files()? {
+            let read = read_segment(&read_wal_file(&path)?);
+            report.segments += 1;
+            report.records += read.records.len();
+            report.issues.extend(segment_issues(&path, &read));
         }
+        Ok(report)
+    }
 
-        events.sort_by_key(|event| event.seq_id);
-        Ok(events)
+    /// Rewrites damaged segments with only their intact records, moving the
+    /// damaged bytes into a `<wal>.quarantine` directory next to the WAL.
+    ///
+    /// Rewritten segments are always framed, so legacy segments that needed
+    /// repair gain checksums.
+    pub fn repair(&self) -> Result<WalRepairReport> {
+        let mut report = WalRepairReport::default();
+        let quarantine_dir = self.quarantine_dir();
+        for path in self.wal_files()? {
+            let bytes = read_wal_file(&path)?;
+            let read = read_segment(&bytes);
+            if read.issues.is_empty() {
+                continue;
+            }
+            fs::create_dir_all(&quarantine_dir)
+                .with_context(|| format!("create WAL quarantine {}", quarantine_dir.display()))
+                .map_err(StorageError::wal_write)?;
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("segment");
+            for (offset, len, _) in &read.issues {
+                let target = quarantine_dir.join(format!("{file_name}.{offset:012}"));
+                fs::write(&target, &bytes[*offset..offset + len])
+                    .with_context(|| format!("write WAL quarantine {}", target.display()))
+                    .map_err(StorageError::wal_write)?;
+                report.quarantined.push(target);
+            }
+            rewrite_segment(&path, &bytes, &read)?;
+            remove_index(&path)?;
+            report.segments_rewritten += 1;
+            report.issues.extend(segment_issues(&path, &read));
+        }
+        Ok(report)
     }
 
     /// Returns recovered events strictly after the provided sequence id.
     pub fn scan(&self, from_seq_id: u64, limit: usize) -> Result<Vec<EventEnvelope>> {
-        let limit = limit.max(1);
-        let events = self.recover_events()?;
-        Ok(events
-            .into_iter()
-            .filter(|event| event.seq_id > from_seq_id)
-            .take(limit)
-            .collect())
+        self.iter_from(from_seq_id)?.take(limit.max(1)).collect()
+    }
+
+    /// Streams events strictly after `from_seq_id`, seeking past earlier
+    /// segments and records through the segment indexes.
+    pub fn iter_from(&self, from_seq_id: u64) -> Result<WalEventIter> {
+        self.iter_range(WalScanRange::after(from_seq_id))
+    }
+
+    /// Streams events ingested within an inclusive unix-ms time range.
+    pub fn iter_time_range(
+        &self,
+        from_ts_unix_ms: Option<i64>,
+        to_ts_unix_ms: Option<i64>,
+    ) -> Result<WalEventIter> {
+        self.iter_range(WalScanRange::time(from_ts_unix_ms, to_ts_unix_ms))
+    }
+
+    /// Returns up to `limit` events after `from_seq_id` ingested within an
+    /// inclusive unix-ms time range.
+    pub fn scan_time_range(
+        &self,
+        from_ts_unix_ms: Option<i64>,
+        to_ts_unix_ms: Option<i64>,
+        from_seq_id: u64,
+        limit: usize,
+    ) -> Result<Vec<EventEnvelope>> {
+        self.iter_range(WalScanRange {
+            after_seq_id: from_seq_id,
+            from_ts_unix_ms,
+            to_ts_unix_ms,
+        })?
+        .take(limit.max(1))
+        .collect()
+    }
+
+    /// Returns the lowest and highest `seq_id` of WAL events ingested within
+    /// an inclusive unix-ms time range, or `None` when there are none.
+    pub fn seq_id_range_for_time(
+        &self,
+        from_ts_unix_ms: Option<i64>,
+        to_ts_unix_ms: Option<i64>,
+    ) -> Result<Option<RangeInclusive<u64>>> {
+        let mut bounds: Option<(u64, u64)> = None;
+        for event in self.iter_time_range(from_ts_unix_ms, to_ts_unix_ms)? {
+            let seq_id = event?.seq_id;
+            bounds = Some(bounds.map_or((seq_id, seq_id), |(min, max)| {
+                (min.min(seq_id), max.max(seq_id))
+            }));
+        }
+        Ok(bounds.map(|(min, max)| min..=max))
+    }
+
+    /// Returns the ingest time of the WAL event with `seq_id`.
+    pub fn ingest_ts_for_seq_id(&self, seq_id: u64) -> Result<Option<i64>> {
+        for event in self.iter_from(seq_id.saturating_sub(1))? {
+            let event = event?;
+            if event.seq_id == seq_id {
+                return Ok(Some(event.ingest_ts_unix_ms));
+            }
+            if event.seq_id > seq_id {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Streams events selected by `range` in WAL order.
+    pub fn iter_range(&self, range: WalScanRange) -> Result<WalEventIter> {
+        Ok(WalEventIter::new(self.wal_files()?, range))
+    }
+
+    /// Returns the index of every segment, rebuilding missing or stale ones
+    /// in memory.
+    pub fn segment_indexes(&self) -> Result<Vec<(PathBuf, WalSegmentIndex)>> {
+        let mut indexes = Vec::new();
+        for (_, path) in self.segment_paths()? {
+            let len = fs::metadata(&path)
+                .with_context(|| format!("stat WAL segment {}", path.display()))
+                .map_err(StorageError::wal_write)?
+                .len();
+            let index = match read_index(&path, len) {
+                Some(index) => index,
+                None => SegmentIndexBuilder::from_segment(&read_wal_file(&path)?).finish(len),
+            };
+            indexes.push((path, index));
+        }
+        Ok(indexes)
     }
 
     /// Clears all WAL segments after a successful downstream flush.
@@ -87,6 +369,7 @@ impl StorageWal {
             fs::remove_file(&path)
                 .with_context(|| format!("remove WAL segment {}", path.display()))
                 .map_err(StorageError::wal_write)?;
+            remove_index(&path)?;
         }
 
         if self.path.exists() {
@@ -97,25 +380,151 @@ impl StorageWal {
         Ok(())
     }
 
+    /// Removes sealed segments whose events are all covered by a checkpoint
+    /// at `seq_id`, subject to `retention`; the active segment is kept.
+    pub fn compact(
+        &self,
+        seq_id: u64,
+        retention: &WalRetention,
+        now_unix_ms: i64,
+    ) -> Result<WalCompaction> {
+        let mut indexes = self.segment_indexes()?;
+        let mut total_bytes = indexes
+            .iter()
+            .map(|(_, index)| index.segment_len)
+            .sum::<u64>();
+        indexes.pop();
+        let mut compaction = WalCompaction::default();
+        for (path, index) in indexes {
+            if index.max_seq_id.is_some_and(|max| max > seq_id) {
+                break;
+            }
+            let expired = retention.max_age_ms.is_some_and(|max_age_ms| {
+                index.max_ts_unix_ms.is_none_or(|ts| {
+                    now_unix_ms.saturating_sub(ts) >= i64::try_from(max_age_ms).unwrap_or(i64::MAX)
+                })
+            });
+            let over_budget = retention
+                .max_bytes
+                .is_some_and(|max_bytes| total_bytes > max_bytes);
+            let unbounded = retention.max_age_ms.is_none() && retention.max_bytes.is_none();
+            if !(unbounded || expired || over_budget) {
+                continue;
+            }
+            fs::remove_file(&path)
+                .with_context(|| format!("remove compacted WAL segment {}", path.display()))
+                .map_err(StorageError::wal_write)?;
+            remove_index(&path)?;
+            total_bytes = total_bytes.saturating_sub(index.segment_len);
+            compaction.segments_removed += 1;
+            compaction.bytes_removed += index.segment_len;
+        }
+        Ok(compaction)
+    }
+
+    /// Atomically replaces the checkpoint stored next to the WAL.
+    pub fn write_checkpoint(&self, checkpoint: &StorageCheckpoint) -> Result<()> {
+        let path = self.checkpoint_path();
+        let staging = path.with_extension("checkpoint.tmp");
+        let file = File::create(&staging)
+            .with_context(|| format!("create WAL checkpoint {}", staging.display()))
+            .map_err(StorageError::wal_write)?;
+        let mut out = BufWriter::new(file);
+        checkpoint.write_to(&mut out)?;
+        out.into_inner()
+            .map_err(|err| err.into_error())
+            .and_then(|file| file.sync_all())
+            .and_then(|()| fs::rename(&staging, &path))
+            .and_then(|()| sync_parent_dir(&path))
+            .with_context(|| format!("write WAL checkpoint {}", path.display()))
+            .map_err(StorageError::wal_write)
+    }
+
+    /// Reads the stored checkpoint, if one was written.
+    pub fn read_checkpoint(&self) -> Result<Option<StorageCheckpoint>> {
+        let path = self.checkpoint_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path)
+            .with_context(|| format!("read WAL checkpoint {}", path.display()))
+            .map_err(StorageError::wal_write)?;
+        StorageCheckpoint::decode(&bytes).map(Some)
+    }
+
+    /// Atomically and durably replaces the persisted sequencer head.
+    ///
+    /// Callers clear or compact segments right after this returns, so the
+    /// head is synced to disk together with the rename that installs it.
+    pub fn write_head(&self, head: &WalHead) -> Result<()> {
+        let path = self.head_path();
+        let staging = path.with_extension("head.tmp");
+        let encoded = serde_json::to_vec(head)
+            .context("serialize WAL head")
+            .map_err(StorageError::wal_write)?;
+        replace_file(&staging, &path, &encoded)
+            .with_context(|| format!("write WAL head {}", path.display()))
+            .map_err(StorageError::wal_write)?;
+        Ok(())
+    }
+
+    /// Reads the persisted sequencer head, if one was written.
+    pub fn read_head(&self) -> Result<Option<WalHead>> {
+        let path = self.head_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path)
+            .with_context(|| format!("read WAL head {}", path.display()))
+            .map_err(StorageError::wal_write)?;
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .with_context(|| format!("decode WAL head {}", path.display()))
+            .map_err(StorageError::wal_write)
+    }
+
     /// Returns the base path used for WAL segment discovery.
     pub fn path(&self) -> &Path {
         &self.path
     }
 
-    fn active_segment_path_for_append(&self) -> Result<PathBuf> {
+    pub(crate) fn segment_max_bytes(&self) -> u64 {
+        self.segment_max_bytes
+    }
+
+    /// Segment id and path that new appends should go to.
+    pub(crate) fn active_segment_for_append(&self) -> Result<(u64, PathBuf)> {
         let segments = self.segment_paths()?;
         if let Some((id, path)) = segments.last() {
             let len = fs::metadata(path)
                 .with_context(|| format!("stat WAL segment {}", path.display()))
                 .map_err(StorageError::wal_write)?
                 .len();
-            if len < self.segment_max_bytes {
-                return Ok(path.clone());
+            // Never mix encodings or framing inside one segment; a restart
+            // with a new encoding, or over a legacy unframed segment, starts
+            // a fresh segment instead.
+            if len < self.segment_max_bytes
+                && (len == 0 || segment_format(path)? == SegmentFormat::Framed(self.encoding))
+            {
+                return Ok((*id, path.clone()));
             }
-            return self.segment_path(id.saturating_add(1));
+            let next_id = id.saturating_add(1);
+            return Ok((next_id, self.segment_path(next_id)));
         }
 
-        self.segment_path(0)
+        Ok((0, self.segment_path(0)))
+    }
+
+    /// The legacy single-file WAL if present, followed by segments in order.
+    ///
+    /// The legacy file predates segments, so its records are the oldest.
+    fn wal_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        if self.path.exists() {
+            files.push(self.path.clone());
+        }
+        files.extend(self.segment_paths()?.into_iter().map(|(_, path)| path));
+        Ok(files)
     }
 
     fn segment_paths(&self) -> Result<Vec<(u64, PathBuf)>> {
@@ -157,14 +566,41 @@ impl StorageWal {
         Ok(segments)
     }
 
-    fn segment_path(&self, id: u64) -> Result<PathBuf> {
+    pub(crate) fn segment_path(&self, id: u64) -> PathBuf {
         let parent = self
             .path
             .parent()
             .map(Path::to_path_buf)
             .unwrap_or_else(|| PathBuf::from("."));
         let base = self.base_name();
-        Ok(parent.join(format!("{base}.seg.{id:020}")))
+        parent.join(format!("{base}.seg.{id:020}"))
+    }
+
+    fn head_path(&self) -> PathBuf {
+        let parent = self
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+        parent.join(format!("{}.head", self.base_name()))
+    }
+
+    fn checkpoint_path(&self) -> PathBuf {
+        let parent = self
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+        parent.join(format!("{}.checkpoint", self.base_name()))
+    }
+
+    fn quarantine_dir(&self) -> PathBuf {
+        let parent = self
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+        parent.join(format!("{}.quarantine", self.base_name()))
     }
 
     fn base_name(&self) -> String {
@@ -176,25 +612,104 @@ impl StorageWal {
     }
 }
 
-fn read_events_from_path(path: &Path) -> Result<Vec<EventEnvelope>> {
-    let file = OpenOptions::new()
-        .read(true)
-        .open(path)
-        .with_context(|| format!("open WAL file {} for recovery", path.display()))
+fn segment_format(path: &Path) -> Result<SegmentFormat> {
+    let mut header = Vec::with_capacity(SEGMENT_HEADER_LEN);
+    File::open(path)
+        .and_then(|file| {
+            file.take(SEGMENT_HEADER_LEN as u64)
+                .read_to_end(&mut header)
+        })
+        .with_context(|| format!("read WAL segment header {}", path.display()))
         .map_err(StorageError::wal_write)?;
-    let reader = BufReader::new(file);
-    let mut events = Vec::new();
-    for line in reader.lines() {
-        let line = line
-            .context("read WAL line")
-            .map_err(StorageError::wal_write)?;
-        if line.trim().is_empty() {
-            continue;
+    Ok(SegmentFormat::detect(&header))
+}
+
+/// Whether a sealed segment holds nothing after `seq_id`, judged by its
+/// index sidecar; a segment without a valid index is read to be safe.
+fn segment_covered(path: &Path, seq_id: u64) -> Result<bool> {
+    let len = fs::metadata(path)
+        .with_context(|| format!("stat WAL segment {}", path.display()))
+        .map_err(StorageError::wal_write)?
+        .len();
+    Ok(read_index(path, len).is_some_and(|index| index.max_seq_id.is_none_or(|max| max <= seq_id)))
+}
+
+fn remove_index(segment: &Path) -> Result<()> {
+    let path = index_path(segment);
+    match fs::remove_file(&path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(StorageError::wal_write(
+            anyhow::Error::new(err).context(format!("remove WAL segment index {}", path.display())),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn read_wal_file(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path)
+        .with_context(|| format!("open WAL file {} for recovery", path.display()))
+        .map_err(StorageError::wal_write)
+}
+
+fn segment_issues<'a>(
+    path: &'a Path,
+    read: &'a SegmentRead,
+) -> impl Iterator<Item = WalIssue> + 'a {
+    read.issues.iter().map(move |(offset, len, kind)| WalIssue {
+        segment: path.to_path_buf(),
+        offset: *offset as u64,
+        len: *len as u64,
+        kind: *kind,
+    })
+}
+
+/// Writes `bytes` to `staging`, syncs it and renames it over `path`, then
+/// syncs the directory so the rename survives a power loss.
+pub(crate) fn replace_file(staging: &Path, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create(staging)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(staging, path)?;
+    sync_parent_dir(path)
+}
+
+/// Syncs the directory holding `path`, making renames and removals in it
+/// durable. Directories cannot be opened for syncing on Windows.
+fn sync_parent_dir(path: &Path) -> std::io::Result<()> {
+    if cfg!(unix) {
+        let parent = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+fn truncate_segment(path: &Path, len: u64) -> Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_len(len))
+        .with_context(|| format!("truncate torn WAL tail in {}", path.display()))
+        .map_err(StorageError::wal_write)
+}
+
+fn rewrite_segment(path: &Path, bytes: &[u8], read: &SegmentRead) -> Result<()> {
+    let encoding = match SegmentFormat::detect(bytes) {
+        SegmentFormat::Framed(encoding) | SegmentFormat::Legacy(encoding) => encoding,
+    };
+    let framed = matches!(SegmentFormat::detect(bytes), SegmentFormat::Framed(_));
+    let mut rewritten = segment_header(encoding).to_vec();
+    for (_, range) in &read.records {
+        if framed {
+            rewritten.extend_from_slice(&bytes[range.clone()]);
+        } else {
+            frame_record(&bytes[range.clone()], &mut rewritten);
         }
-        let event: EventEnvelope = serde_json::from_str(&line)
-            .context("decode WAL event line")
-            .map_err(StorageError::wal_write)?;
-        events.push(event);
     }
-    Ok(events)
+    let staging = path.with_extension("repair.tmp");
+    fs::write(&staging, &rewritten)
+        .and_then(|()| fs::rename(&staging, path))
+        .with_context(|| format!("rewrite repaired WAL segment {}", path.display()))
+        .map_err(StorageError::wal_write)
 }
@@ -0,0 +1,415 @@
+//! Sparse per-segment WAL indexes and the streaming scan built on them.
+//!
+//! Each rotated segment gets a `<segment>.idx` sidecar holding its seq and
+//! timestamp bounds plus a `seq_id → byte offset` sample roughly every
+//! [`INDEX_STRIDE_BYTES`]. Scans skip segments whose bounds miss the range
+//! and seek inside the first overlapping one, so only the segments that can
+//! hold matches are read. Indexes are advisory: one that is missing or does
+//! not match its segment's length is rebuilt from the segment itself.
+
+use crate::wal::replace_file;
+use crate::wal_record::{
+    FramedCursor, SEGMENT_HEADER_LEN, SegmentFormat, next_framed, read_segment,
+};
+use crate::{Result, StorageError};
+use anyhow::Context;
+use event_log::{EventEncoding, EventEnvelope};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Minimum distance between two sampled offsets in a segment index.
+pub(crate) const INDEX_STRIDE_BYTES: u64 = 64 * 1024;
+const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// One sampled record position inside a segment.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalIndexEntry {
+    pub seq_id: u64,
+    pub offset: u64,
+}
+
+/// Sparse index of one WAL segment.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WalSegmentIndex {
+    pub version: u32,
+    /// Segment length the index was built for; any other length means the
+    /// segment changed and the index is stale.
+    pub segment_len: u64,
+    pub records: u64,
+    pub min_seq_id: Option<u64>,
+    pub max_seq_id: Option<u64>,
+    pub min_ts_unix_ms: Option<i64>,
+    pub max_ts_unix_ms: Option<i64>,
+    /// Whether seq ids only grow within the segment; seeking relies on it.
+    pub seq_monotonic: bool,
+    /// Sampled record offsets in file order.
+    pub entries: Vec<WalIndexEntry>,
+}
+
+impl WalSegmentIndex {
+    /// Whether the segment can hold an event inside `range`.
+    pub fn overlaps(&self, range: &WalScanRange) -> bool {
+        let Some(max_seq_id) = self.max_seq_id else {
+            return false;
+        };
+        max_seq_id > range.after_seq_id
+            && range
+                .from_ts_unix_ms
+                .is_none_or(|from| self.max_ts_unix_ms.is_some_and(|max| max >= from))
+            && range
+                .to_ts_unix_ms
+                .is_none_or(|to| self.min_ts_unix_ms.is_some_and(|min| min <= to))
+    }
+
+    /// Byte offset from which every event after `after_seq_id` follows.
+    pub fn seek_offset(&self, after_seq_id: u64) -> u64 {
+        if !self.seq_monotonic {
+            return SEGMENT_HEADER_LEN as u64;
+        }
+        self.entries
+            .iter()
+            .take_while(|entry| entry.seq_id <= after_seq_id)
+            .last()
+            .map_or(SEGMENT_HEADER_LEN as u64, |entry| entry.offset)
+    }
+}
+
+/// Accumulates a [`WalSegmentIndex`] while records are appended or read.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SegmentIndexBuilder {
+    index: WalSegmentIndex,
+    last_sample: Option<u64>,
+}
+
+impl SegmentIndexBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            index: WalSegmentIndex {
+                version: INDEX_FORMAT_VERSION,
+                seq_monotonic: true,
+                ..WalSegmentIndex::default()
+            },
+            last_sample: None,
+        }
+    }
+
+    /// Builds the index of an existing segment from its bytes.
+    pub(crate) fn from_segment(bytes: &[u8]) -> Self {
+        let mut builder = Self::new();
+        for (event, range) in read_segment(bytes).records {
+            builder.observe(&event, range.start as u64);
+        }
+        builder.index.segment_len = bytes.len() as u64;
+        builder
+    }
+
+    /// Records one event written at `offset`.
+    pub(crate) fn observe(&mut self, event: &EventEnvelope, offset: u64) {
+        let index = &mut self.index;
+        if index.max_seq_id.is_some_and(|max| event.seq_id <= max) {
+            index.seq_monotonic = false;
+        }
+        index.records += 1;
+        let (seq_id, ts) = (event.seq_id, event.ingest_ts_unix_ms);
+        index.min_seq_id = Some(index.min_seq_id.map_or(seq_id, |min| min.min(seq_id)));
+        index.max_seq_id = Some(index.max_seq_id.map_or(seq_id, |max| max.max(seq_id)));
+        index.min_ts_unix_ms = Some(index.min_ts_unix_ms.map_or(ts, |min| min.min(ts)));
+        index.max_ts_unix_ms = Some(index.max_ts_unix_ms.map_or(ts, |max| max.max(ts)));
+        if self
+            .last_sample
+            .is_none_or(|last| offset.saturating_sub(last) >= INDEX_STRIDE_BYTES)
+        {
+            index.entries.push(WalIndexEntry {
+                seq_id: event.seq_id,
+                offset,
+            });
+            self.last_sample = Some(offset);
+        }
+    }
+
+    /// Finishes the index for a segment of `segment_len` bytes.
+    pub(crate) fn finish(&self, segment_len: u64) -> WalSegmentIndex {
+        WalSegmentIndex {
+            segment_len,
+            ..self.index.clone()
+        }
+    }
+}
+
+/// Path of the index sidecar of a segment.
+pub(crate) fn index_path(segment: &Path) -> PathBuf {
+    let mut name = segment.as_os_str().to_owned();
+    name.push(".idx");
+    PathBuf::from(name)
+}
+
+/// Atomically and durably writes the index sidecar of a segment.
+pub(crate) fn write_index(segment: &Path, index: &WalSegmentIndex) -> Result<()> {
+    let path = index_path(segment);
+    let staging = path.with_extension("idx.tmp");
+    let encoded = serde_json::to_vec(index)
+        .context("serialize WAL segment index")
+        .map_err(StorageError::wal_write)?;
+    replace_file(&staging, &path, &encoded)
+        .with_context(|| format!("write WAL segment index {}", path.display()))
+        .map_err(StorageError::wal_write)
+}
+
+/// Reads the index sidecar of a segment if it is present and current.
+pub(crate) fn read_index(segment: &Path, segment_len: u64) -> Option<WalSegmentIndex> {
+    let bytes = fs::read(index_path(segment)).ok()?;
+    serde_json::from_slice::<WalSegmentIndex>(&bytes)
+        .ok()
+        .filter(|index| index.version == INDEX_FORMAT_VERSION && index.segment_len == segment_len)
+}
+
+/// Events selected by a WAL scan.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WalScanRange {
+    /// Only events with a strictly greater `seq_id` are returned.
+    pub after_seq_id: u64,
+    /// Inclusive lower bound on `ingest_ts_unix_ms`.
+    pub from_ts_unix_ms: Option<i64>,
+    /// Inclusive upper bound on `ingest_ts_unix_ms`.
+    pub to_ts_unix_ms: Option<i64>,
+}
+
+impl WalScanRange {
+    /// Events strictly after `seq_id`.
+    pub fn after(seq_id: u64) -> Self {
+        Self {
+            after_seq_id: seq_id,
+            ..Self::default()
+        }
+    }
+
+    /// Events ingested within `[from_ts_unix_ms, to_ts_unix_ms]`.
+    pub fn time(from_ts_unix_ms: Option<i64>, to_ts_unix_ms: Option<i64>) -> Self {
+        Self {
+            after_seq_id: 0,
+            from_ts_unix_ms,
+            to_ts_unix_ms,
+        }
+    }
+
+    pub fn contains(&self, event: &EventEnvelope) -> bool {
+        event.seq_id > self.after_seq_id
+            && self
+                .from_ts_unix_ms
+                .is_none_or(|from| event.ingest_ts_unix_ms >= from)
+            && self
+                .to_ts_unix_ms
+                .is_none_or(|to| event.ingest_ts_unix_ms <= to)
+    }
+}
+
+enum SegmentCursor {
+    /// Framed segment bytes, decoded record by record from `cursor`.
+    Framed {
+        bytes: Vec<u8>,
+        cursor: FramedCursor,
+        encoding: EventEncoding,
+    },
+    /// Legacy segments have no record boundaries to seek to, so they are
+    /// decoded whole.
+    Decoded(std::vec::IntoIter<EventEnvelope>),
+}
+
+/// Streaming iterator over WAL events in file order, which is `seq_id`
+/// order for a WAL written by a single storage writer.
+///
+/// Only one segment is held in memory at a time. Damaged records are
+/// skipped, as in [`crate::StorageWal::recover_events`].
+pub struct WalEventIter {
+    files: VecDeque<PathBuf>,
+    range: WalScanRange,
+    cursor: Option<SegmentCursor>,
+}
+
+impl WalEventIter {
+    pub(crate) fn new(files: Vec<PathBuf>, range: WalScanRange) -> Self {
+        Self {
+            files: files.into(),
+            range,
+            cursor: None,
+        }
+    }
+
+    fn open_next(&mut self) -> Result<bool> {
+        while let Some(path) = self.files.pop_front() {
+            if let Some(cursor) = open_cursor(&path, &self.range)? {
+                self.cursor = Some(cursor);
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl Iterator for WalEventIter {
+    type Item = Result<EventEnvelope>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = match self.cursor.as_mut() {
+                Some(SegmentCursor::Framed {
+                    bytes,
+                    cursor,
+                    encoding,
+                }) => match next_framed(bytes, cursor, *encoding) {
+                    Some(Ok((event, _))) => Some(event),
+                    Some(Err(_)) => continue,
+                    None => None,
+                },
+                Some(SegmentCursor::Decoded(events)) => events.next(),
+                None => match self.open_next() {
+                    Ok(true) => continue,
+                    Ok(false) => return None,
+                    Err(err) => return Some(Err(err)),
+                },
+            };
+            match event {
+                Some(event) if self.range.contains(&event) => return Some(Ok(event)),
+                Some(_) => {}
+                None => self.cursor = None,
+            }
+        }
+    }
+}
+
+fn open_cursor(path: &Path, range: &WalScanRange) -> Result<Option<SegmentCursor>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        // Cleared or compacted between listing and reading.
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(StorageError::wal_write(
+                anyhow::Error::new(err).context(format!("open WAL segment {}", path.display())),
+            ));
+        }
+    };
+    let segment_len = file
+        .metadata()
+        .with_context(|| format!("stat WAL segment {}", path.display()))
+        .map_err(StorageError::wal_write)?
+        .len();
+
+    let mut header = Vec::with_capacity(SEGMENT_HEADER_LEN);
+    (&mut file)
+        .take(SEGMENT_HEADER_LEN as u64)
+        .read_to_end(&mut header)
+        .with_context(|| format!("read WAL segment header {}", path.display()))
+        .map_err(StorageError::wal_write)?;
+    let SegmentFormat::Framed(encoding) = SegmentFormat::detect(&header) else {
+        let mut bytes = header;
+        file.read_to_end(&mut bytes)
+            .with_context(|| format!("read WAL segment {}", path.display()))
+            .map_err(StorageError::wal_write)?;
+        let events = read_segment(&bytes)
+            .records
+            .into_iter()
+            .map(|(event, _)| event)
+            .collect::<Vec<_>>();
+        return Ok(Some(SegmentCursor::Decoded(events.into_iter())));
+    };
+
+    let index = match read_index(path, segment_len) {
+        Some(index) => index,
+        None => {
+            let mut bytes = header;
+            file.read_to_end(&mut bytes)
+                .with_context(|| format!("read WAL segment {}", path.display()))
+                .map_err(StorageError::wal_write)?;
+            let index = SegmentIndexBuilder::from_segment(&bytes).finish(segment_len);
+            if !index.overlaps(range) {
+                return Ok(None);
+            }
+            let offset = index.seek_offset(range.after_seq_id) as usize;
+            return Ok(Some(SegmentCursor::Framed {
+                bytes,
+                cursor: FramedCursor::at(offset),
+                encoding,
+            }));
+        }
+    };
+    if !index.overlaps(range) {
+        return Ok(None);
+    }
+
+    let offset = index.seek_offset(range.after_seq_id);
+    let mut bytes = Vec::with_capacity(segment_len.saturating_sub(offset) as usize);
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.read_to_end(&mut bytes))
+        .with_context(|| format!("read WAL segment {} from {offset}", path.display()))
+        .map_err(StorageError::wal_write)?;
+    Ok(Some(SegmentCursor::Framed {
+        bytes,
+        cursor: FramedCursor::at(0),
+        encoding,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::SourceId;
+    use event_log::{DropReason, EventPayload, TxDropped};
+
+    fn event(seq_id: u64, ts: i64) -> EventEnvelope {
+        EventEnvelope {
+            seq_id,
+            ingest_ts_unix_ms: ts,
+            ingest_ts_mono_ns: seq_id,
+            source_id: SourceId::new("wal-index-test"),
+            payload: EventPayload::TxDropped(TxDropped::new(
+                [seq_id as u8; 32],
+                DropReason::Other("test".to_owned()),
+            )),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        }
+    }
+
+    #[test]
+    fn index_tracks_bounds_and_seeks_to_the_last_sample_at_or_before() {
+        let mut builder = SegmentIndexBuilder::new();
+        for seq_id in 1..=4_u64 {
+            builder.observe(
+                &event(seq_id, 100 + seq_id as i64),
+                SEGMENT_HEADER_LEN as u64 + (seq_id - 1) * INDEX_STRIDE_BYTES,
+            );
+        }
+        let index = builder.finish(4 * INDEX_STRIDE_BYTES);
+        assert_eq!((index.min_seq_id, index.max_seq_id), (Some(1), Some(4)));
+        assert_eq!(
+            (index.min_ts_unix_ms, index.max_ts_unix_ms),
+            (Some(101), Some(104))
+        );
+        assert_eq!(index.entries.len(), 4);
+        assert_eq!(index.seek_offset(0), SEGMENT_HEADER_LEN as u64);
+        assert_eq!(
+            index.seek_offset(3),
+            SEGMENT_HEADER_LEN as u64 + 2 * INDEX_STRIDE_BYTES
+        );
+
+        assert!(index.overlaps(&WalScanRange::after(3)));
+        assert!(!index.overlaps(&WalScanRange::after(4)));
+        assert!(index.overlaps(&WalScanRange::time(Some(104), None)));
+        assert!(!index.overlaps(&WalScanRange::time(Some(105), None)));
+        assert!(!index.overlaps(&WalScanRange::time(None, Some(100))));
+    }
+
+    #[test]
+    fn out_of_order_segments_seek_from_the_start() {
+        let mut builder = SegmentIndexBuilder::new();
+        builder.observe(&event(5, 0), 8);
+        builder.observe(&event(2, 0), 8 + INDEX_STRIDE_BYTES);
+        let index = builder.finish(1);
+        assert!(!index.seq_monotonic);
+        assert_eq!(index.seek_offset(5), SEGMENT_HEADER_LEN as u64);
+    }
+}
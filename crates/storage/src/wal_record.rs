@@ -0,0 +1,479 @@
+//! Checksummed WAL record framing and tolerant segment decoding.
+//!
+//! A framed segment opens with an 8-byte header (`MWAL`, format version,
+//! event encoding, two reserved bytes) followed by records of
+//! `len: u32 LE | crc32c: u32 LE | payload`, where the CRC covers the length
+//! bytes and the payload. Segments written before framing existed carry bare
+//! JSON lines or binary frames and are still decoded, with weaker checks.
+
+use crc::{CRC_32_ISCSI, Crc};
+use event_log::{
+    CodecError, EventEncoding, EventEnvelope, EventSchemaRegistry, decode_event,
+    upcast_binary_event,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+pub(crate) const SEGMENT_MAGIC: [u8; 4] = *b"MWAL";
+pub(crate) const SEGMENT_HEADER_LEN: usize = 8;
+const SEGMENT_FORMAT_VERSION: u8 = 1;
+const RECORD_HEADER_LEN: usize = 8;
+/// Upper bound on one record; larger lengths are treated as corruption.
+const MAX_RECORD_LEN: usize = 64 * 1024 * 1024;
+/// Resync after damage accepts records up to the largest intact one seen so
+/// far, but never fewer than this many bytes.
+const RESYNC_MIN_RECORD_LEN: usize = 64 * 1024;
+
+/// What went wrong with one stretch of a WAL segment.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalIssueKind {
+    /// The segment ends inside a record, typically a write cut short by a crash.
+    TornTail,
+    /// Record bytes do not match their checksum.
+    ChecksumMismatch,
+    /// The record passed its checksum (or has none) but does not decode.
+    Undecodable,
+}
+
+/// One damaged byte range found while reading the WAL.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct WalIssue {
+    pub segment: PathBuf,
+    pub offset: u64,
+    pub len: u64,
+    pub kind: WalIssueKind,
+}
+
+/// Layout of a segment on disk.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum SegmentFormat {
+    Framed(EventEncoding),
+    Legacy(EventEncoding),
+}
+
+impl SegmentFormat {
+    pub(crate) fn detect(bytes: &[u8]) -> Self {
+        match parse_segment_header(bytes) {
+            Some(encoding) => Self::Framed(encoding),
+            None => Self::Legacy(EventEncoding::detect(bytes)),
+        }
+    }
+}
+
+/// Events and damage found in one segment.
+#[derive(Debug, Default)]
+pub(crate) struct SegmentRead {
+    /// Decoded events with the byte range of the record each came from.
+    pub(crate) records: Vec<(EventEnvelope, std::ops::Range<usize>)>,
+    pub(crate) issues: Vec<(usize, usize, WalIssueKind)>,
+    /// Offset just past the last intact record; a torn tail starts here.
+    pub(crate) intact_len: usize,
+}
+
+impl SegmentRead {
+    pub(crate) fn torn_tail(&self) -> bool {
+        self.issues
+            .last()
+            .is_some_and(|(_, _, kind)| *kind == WalIssueKind::TornTail)
+    }
+}
+
+pub(crate) fn segment_header(encoding: EventEncoding) -> [u8; SEGMENT_HEADER_LEN] {
+    let encoding = match encoding {
+        EventEncoding::Json => 0,
+        EventEncoding::Binary => 1,
+    };
+    let [m0, m1, m2, m3] = SEGMENT_MAGIC;
+    [m0, m1, m2, m3, SEGMENT_FORMAT_VERSION, encoding, 0, 0]
+}
+
+fn parse_segment_header(bytes: &[u8]) -> Option<EventEncoding> {
+    let header = bytes.get(..SEGMENT_HEADER_LEN)?;
+    if header[..4] != SEGMENT_MAGIC || header[4] != SEGMENT_FORMAT_VERSION {
+        return None;
+    }
+    match header[5] {
+        0 => Some(EventEncoding::Json),
+        1 => Some(EventEncoding::Binary),
+        _ => None,
+    }
+}
+
+/// Appends one framed record holding `payload` to `out`.
+pub(crate) fn frame_record(payload: &[u8], out: &mut Vec<u8>) {
+    let len = u32::try_from(payload.len())
+        .unwrap_or(u32::MAX)
+        .to_le_bytes();
+    let mut digest = CRC32C.digest();
+    digest.update(&len);
+    digest.update(payload);
+    out.extend_from_slice(&len);
+    out.extend_from_slice(&digest.finalize().to_le_bytes());
+    out.extend_from_slice(payload);
+}
+
+/// Decodes every readable event in a segment, recording damaged ranges
+/// instead of failing on them.
+pub(crate) fn read_segment(bytes: &[u8]) -> SegmentRead {
+    match SegmentFormat::detect(bytes) {
+        SegmentFormat::Framed(encoding) => read_framed(bytes, encoding),
+        SegmentFormat::Legacy(EventEncoding::Json) => read_legacy_json(bytes),
+        SegmentFormat::Legacy(EventEncoding::Binary) => read_legacy_binary(bytes),
+    }
+}
+
+enum Frame<'a> {
+    Intact(&'a [u8]),
+    Torn,
+    Corrupt,
+}
+
+/// Reads the frame at `offset`, treating lengths over `max_len` as corrupt
+/// before any checksum work.
+fn frame_at(bytes: &[u8], offset: usize, max_len: usize) -> Frame<'_> {
+    let Some(header) = bytes.get(offset..offset + RECORD_HEADER_LEN) else {
+        return Frame::Torn;
+    };
+    let len_bytes = [header[0], header[1], header[2], header[3]];
+    let crc = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    if len > max_len {
+        return Frame::Corrupt;
+    }
+    let start = offset + RECORD_HEADER_LEN;
+    let Some(payload) = bytes.get(start..start + len) else {
+        return Frame::Torn;
+    };
+    let mut digest = CRC32C.digest();
+    digest.update(&len_bytes);
+    digest.update(payload);
+    if digest.finalize() == crc {
+        Frame::Intact(payload)
+    } else {
+        Frame::Corrupt
+    }
+}
+
+/// Returns the payload of the intact frame at `*offset` and advances past
+/// it, or `None` when the frame there is missing, torn, or corrupt.
+pub(crate) fn take_frame<'a>(bytes: &'a [u8], offset: &mut usize) -> Option<&'a [u8]> {
+    match frame_at(bytes, *offset, MAX_RECORD_LEN) {
+        Frame::Intact(payload) => {
+            *offset += RECORD_HEADER_LEN + payload.len();
+            Some(payload)
+        }
+        Frame::Torn | Frame::Corrupt => None,
+    }
+}
+
+/// A decoded record with its byte range, or the `(offset, len, kind)` of the
+/// damaged bytes skipped instead.
+pub(crate) type FramedStep =
+    Result<(EventEnvelope, std::ops::Range<usize>), (usize, usize, WalIssueKind)>;
+
+/// Read position in framed segment bytes.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FramedCursor {
+    offset: usize,
+    /// Payload length of the largest intact record passed so far.
+    largest_record: usize,
+}
+
+impl FramedCursor {
+    pub(crate) fn at(offset: usize) -> Self {
+        Self {
+            offset,
+            largest_record: 0,
+        }
+    }
+}
+
+/// Decodes the record at the cursor in framed bytes and advances past it, or
+/// past the damaged range when the record is unreadable.
+pub(crate) fn next_framed(
+    bytes: &[u8],
+    cursor: &mut FramedCursor,
+    encoding: EventEncoding,
+) -> Option<FramedStep> {
+    let start = cursor.offset;
+    if start >= bytes.len() {
+        return None;
+    }
+    let step = match frame_at(bytes, start, MAX_RECORD_LEN) {
+        Frame::Intact(payload) => {
+            let end = start + RECORD_HEADER_LEN + payload.len();
+            cursor.offset = end;
+            cursor.largest_record = cursor.largest_record.max(payload.len());
+            match decode_record(encoding, payload) {
+                Ok(event) => Ok((event, start..end)),
+                Err(_) => Err((start, end - start, WalIssueKind::Undecodable)),
+            }
+        }
+        damaged => {
+            // A bad length loses the record boundary, and one pointing past
+            // the end looks like a torn write, so skip to the next offset
+            // where a complete record checks out. Only a record cut short by
+            // the end of the segment, with nothing intact after it, is a torn
+            // tail that recovery may truncate; a complete record failing its
+            // checksum is kept for `repair`. Candidates longer than any record
+            // seen are rejected unchecked, so each offset checksums at most
+            // `max_len` bytes instead of the rest of the segment.
+            let max_len = cursor.largest_record.max(RESYNC_MIN_RECORD_LEN);
+            let next = (start + 1..bytes.len())
+                .find(|candidate| matches!(frame_at(bytes, *candidate, max_len), Frame::Intact(_)));
+            let end = next.unwrap_or(bytes.len());
+            let kind = if next.is_none() && matches!(damaged, Frame::Torn) {
+                WalIssueKind::TornTail
+            } else {
+                WalIssueKind::ChecksumMismatch
+            };
+            cursor.offset = end;
+            Err((start, end - start, kind))
+        }
+    };
+    Some(step)
+}
+
+fn read_framed(bytes: &[u8], encoding: EventEncoding) -> SegmentRead {
+    let mut read = SegmentRead {
+        intact_len: SEGMENT_HEADER_LEN,
+        ..SegmentRead::default()
+    };
+    let mut cursor = FramedCursor::at(SEGMENT_HEADER_LEN);
+    while let Some(step) = next_framed(bytes, &mut cursor, encoding) {
+        match step {
+            Ok((event, range)) => {
+                read.intact_len = range.end;
+                read.records.push((event, range));
+            }
+            Err((start, len, kind)) => {
+                if kind == WalIssueKind::Undecodable {
+                    read.intact_len = start + len;
+                }
+                read.issues.push((start, len, kind));
+            }
+        }
+    }
+    read
+}
+
+fn read_legacy_json(bytes: &[u8]) -> SegmentRead {
+    let registry = EventSchemaRegistry::builtin();
+    let mut read = SegmentRead::default();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let (end, terminated) = match bytes[offset..].iter().position(|byte| *byte == b'\n') {
+            Some(newline) => (offset + newline + 1, true),
+            None => (bytes.len(), false),
+        };
+        let line = bytes[offset..end].trim_ascii();
+        if !line.is_empty() {
+            match registry.decode_json_slice(line) {
+                Ok(event) => read.records.push((event, offset..end)),
+                // Only the final, unterminated line can be a cut-short write.
+                Err(_) if !terminated => {
+                    read.issues
+                        .push((offset, end - offset, WalIssueKind::TornTail));
+                    break;
+                }
+                Err(_) => read
+                    .issues
+                    .push((offset, end - offset, WalIssueKind::Undecodable)),
+            }
+        }
+        read.intact_len = end;
+        offset = end;
+    }
+    read
+}
+
+fn read_legacy_binary(bytes: &[u8]) -> SegmentRead {
+    let mut read = SegmentRead::default();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        match decode_event(&bytes[offset..]) {
+            Ok((mut event, consumed)) => {
+                upcast_binary_event(&mut event);
+                read.records.push((event, offset..offset + consumed));
+                offset += consumed;
+                read.intact_len = offset;
+            }
+            // Unframed binary cannot resynchronize, so everything after the
+            // first bad frame is reported as one range.
+            Err(err) => {
+                let kind = if err == CodecError::Truncated {
+                    WalIssueKind::TornTail
+                } else {
+                    WalIssueKind::Undecodable
+                };
+                read.issues.push((offset, bytes.len() - offset, kind));
+                break;
+            }
+        }
+    }
+    read
+}
+
+pub(crate) fn decode_record(
+    encoding: EventEncoding,
+    payload: &[u8],
+) -> Result<EventEnvelope, CodecError> {
+    match encoding {
+        EventEncoding::Json => EventSchemaRegistry::builtin()
+            .decode_json_slice(payload.trim_ascii())
+            .map_err(CodecError::from),
+        EventEncoding::Binary => {
+            let (mut event, consumed) = decode_event(payload)?;
+            if consumed != payload.len() {
+                return Err(CodecError::Truncated);
+            }
+            upcast_binary_event(&mut event);
+            Ok(event)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::SourceId;
+    use event_log::{DropReason, EventPayload, TxDropped};
+
+    fn event(seq_id: u64) -> EventEnvelope {
+        EventEnvelope {
+            seq_id,
+            ingest_ts_unix_ms: 1_700_000_000_000,
+            ingest_ts_mono_ns: seq_id,
+            source_id: SourceId::new("wal-record-test"),
+            payload: EventPayload::TxDropped(TxDropped::new(
+                [seq_id as u8; 32],
+                DropReason::Other("test".to_owned()),
+            )),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        }
+    }
+
+    fn framed_segment(encoding: EventEncoding, seq_ids: &[u64]) -> Vec<u8> {
+        let mut segment = segment_header(encoding).to_vec();
+        for seq_id in seq_ids {
+            let mut payload = Vec::new();
+            encoding
+                .encode_into(&event(*seq_id), &mut payload)
+                .expect("encode");
+            frame_record(&payload, &mut segment);
+        }
+        segment
+    }
+
+    fn seq_ids(read: &SegmentRead) -> Vec<u64> {
+        read.records.iter().map(|(event, _)| event.seq_id).collect()
+    }
+
+    #[test]
+    fn framed_segments_round_trip_in_both_encodings() {
+        for encoding in [EventEncoding::Json, EventEncoding::Binary] {
+            let segment = framed_segment(encoding, &[1, 2, 3]);
+            assert_eq!(
+                SegmentFormat::detect(&segment),
+                SegmentFormat::Framed(encoding)
+            );
+            let read = read_segment(&segment);
+            assert_eq!(seq_ids(&read), vec![1, 2, 3]);
+            assert!(read.issues.is_empty());
+            assert_eq!(read.intact_len, segment.len());
+        }
+    }
+
+    #[test]
+    fn cut_short_last_record_is_a_torn_tail() {
+        let full = framed_segment(EventEncoding::Binary, &[1, 2]);
+        let intact = framed_segment(EventEncoding::Binary, &[1]).len();
+        for cut in [intact + 3, full.len() - 1] {
+            let read = read_segment(&full[..cut]);
+            assert_eq!(seq_ids(&read), vec![1]);
+            assert!(read.torn_tail());
+            assert_eq!(read.intact_len, intact);
+            assert_eq!(
+                read.issues,
+                vec![(intact, cut - intact, WalIssueKind::TornTail)]
+            );
+        }
+    }
+
+    #[test]
+    fn flipped_bit_skips_only_the_damaged_record() {
+        let mut segment = framed_segment(EventEncoding::Json, &[1, 2, 3]);
+        let first_end = framed_segment(EventEncoding::Json, &[1]).len();
+        let second_end = framed_segment(EventEncoding::Json, &[1, 2]).len();
+        segment[first_end + RECORD_HEADER_LEN + 4] ^= 0x20;
+
+        let read = read_segment(&segment);
+        assert_eq!(seq_ids(&read), vec![1, 3]);
+        assert_eq!(
+            read.issues,
+            vec![(
+                first_end,
+                second_end - first_end,
+                WalIssueKind::ChecksumMismatch
+            )]
+        );
+        assert!(!read.torn_tail());
+    }
+
+    #[test]
+    fn corrupted_length_early_in_a_large_segment_resyncs_without_rescanning() {
+        let records = (2..=20_000).collect::<Vec<_>>();
+        let tail = framed_segment(EventEncoding::Binary, &records);
+        let mut segment = framed_segment(EventEncoding::Binary, &[1]);
+        let damage_start = segment.len();
+        // Every fourth offset declares a 1 MiB record that fits in the rest
+        // of the segment, so an unbounded search would checksum megabytes at
+        // each of them.
+        for _ in 0..4 * 1024 {
+            segment.extend_from_slice(&[0, 0, 0x10, 0]);
+        }
+        let damage_len = segment.len() - damage_start;
+        segment.extend_from_slice(&tail[SEGMENT_HEADER_LEN..]);
+
+        let read = read_segment(&segment);
+        assert_eq!(read.records.len(), 20_000);
+        assert_eq!(
+            read.issues,
+            vec![(damage_start, damage_len, WalIssueKind::ChecksumMismatch)]
+        );
+        assert_eq!(read.intact_len, segment.len());
+    }
+
+    #[test]
+    fn legacy_json_lines_report_bad_lines_and_a_torn_last_line() {
+        let mut segment = Vec::new();
+        EventEncoding::Json
+            .encode_into(&event(1), &mut segment)
+            .expect("encode");
+        segment.extend_from_slice(b"{\"not\":\"an event\"}\n");
+        let before_tail = segment.len();
+        EventEncoding::Json
+            .encode_into(&event(3), &mut segment)
+            .expect("encode");
+        segment.truncate(segment.len() - 10);
+
+        let read = read_segment(&segment);
+        assert_eq!(seq_ids(&read), vec![1]);
+        assert_eq!(read.issues.len(), 2);
+        assert_eq!(read.issues[0].2, WalIssueKind::Undecodable);
+        assert_eq!(
+            read.issues[1],
+            (
+                before_tail,
+                segment.len() - before_tail,
+                WalIssueKind::TornTail
+            )
+        );
+        assert_eq!(read.intact_len, before_tail);
+    }
+}
@@ -0,0 +1,388 @@
+//! Long-lived WAL segment writer with group commit and selectable durability.
+
+use crate::wal::StorageWal;
+use crate::wal_index::{SegmentIndexBuilder, write_index};
+use crate::wal_record::{SEGMENT_HEADER_LEN, frame_record, segment_header};
+use crate::{Result, StorageError};
+use anyhow::Context;
+use event_log::EventEnvelope;
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+const DEFAULT_FSYNC_INTERVAL_MS: u64 = 1_000;
+
+/// When WAL commits are forced to stable storage.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WalDurability {
+    /// Never fsync; committed records reach the OS page cache only.
+    None,
+    /// Fsync on a commit at least `interval_ms` after the previous fsync.
+    Interval { interval_ms: u64 },
+    /// Fsync after every group commit.
+    #[default]
+    EveryBatch,
+}
+
+impl WalDurability {
+    /// Returns the stable string label used by config and diagnostics.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Interval { .. } => "interval",
+            Self::EveryBatch => "every_batch",
+        }
+    }
+}
+
+impl FromStr for WalDurability {
+    type Err = String;
+
+    /// Parses `none`, `every_batch`, `interval`, or `interval:<ms>`.
+    fn from_str(raw: &str) -> std::result::Result<Self, Self::Err> {
+        let raw = raw.trim().to_ascii_lowercase();
+        match raw.split_once(':') {
+            None if raw == "none" => Ok(Self::None),
+            None if raw == "every_batch" => Ok(Self::EveryBatch),
+            None if raw == "interval" => Ok(Self::Interval {
+                interval_ms: DEFAULT_FSYNC_INTERVAL_MS,
+            }),
+            Some(("interval", ms)) => ms
+                .parse::<u64>()
+                .map(|interval_ms| Self::Interval {
+                    interval_ms: interval_ms.max(1),
+                })
+                .map_err(|_| format!("invalid WAL fsync interval '{ms}'")),
+            _ => Err(format!(
+                "unknown WAL durability '{raw}'. expected: none | interval[:<ms>] | every_batch"
+            )),
+        }
+    }
+}
+
+/// Counters shared between a [`WalWriter`] and whoever reports its metrics.
+#[derive(Debug, Default)]
+pub struct WalMetrics {
+    records_total: AtomicU64,
+    bytes_written_total: AtomicU64,
+    commits_total: AtomicU64,
+    fsync_total: AtomicU64,
+    fsync_latency_ns_total: AtomicU64,
+    fsync_latency_ns_max: AtomicU64,
+}
+
+/// Point-in-time copy of [`WalMetrics`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct WalMetricsSnapshot {
+    pub records_total: u64,
+    pub bytes_written_total: u64,
+    pub commits_total: u64,
+    pub fsync_total: u64,
+    pub fsync_latency_ns_total: u64,
+    pub fsync_latency_ns_max: u64,
+}
+
+impl WalMetrics {
+    /// Reads all counters.
+    pub fn snapshot(&self) -> WalMetricsSnapshot {
+        WalMetricsSnapshot {
+            records_total: self.records_total.load(Ordering::Relaxed),
+            bytes_written_total: self.bytes_written_total.load(Ordering::Relaxed),
+            commits_total: self.commits_total.load(Ordering::Relaxed),
+            fsync_total: self.fsync_total.load(Ordering::Relaxed),
+            fsync_latency_ns_total: self.fsync_latency_ns_total.load(Ordering::Relaxed),
+            fsync_latency_ns_max: self.fsync_latency_ns_max.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_fsync(&self, latency: Duration) {
+        let latency_ns = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        self.fsync_total.fetch_add(1, Ordering::Relaxed);
+        self.fsync_latency_ns_total
+            .fetch_add(latency_ns, Ordering::Relaxed);
+        self.fsync_latency_ns_max
+            .fetch_max(latency_ns, Ordering::Relaxed);
+    }
+}
+
+struct ActiveSegment {
+    id: u64,
+    path: PathBuf,
+    file: File,
+    len: u64,
+    index: SegmentIndexBuilder,
+}
+
+/// Keeps the active WAL segment open across appends.
+///
+/// Appended records are framed in memory and written to the segment in one
+/// call per group by [`WalWriter::commit`], which then fsyncs them according
+/// to the configured [`WalDurability`]; the file only ever holds whole groups.
+/// Segments rotate at record boundaries once they reach the WAL's segment
+/// size, and each rotated segment gets its sparse index written next to it.
+pub struct WalWriter {
+    wal: StorageWal,
+    durability: WalDurability,
+    metrics: Arc<WalMetrics>,
+    active: Option<ActiveSegment>,
+    payload: Vec<u8>,
+    frame: Vec<u8>,
+    uncommitted: bool,
+    unsynced: bool,
+    last_sync: Instant,
+}
+
+impl WalWriter {
+    pub(crate) fn new(wal: StorageWal, durability: WalDurability) -> Self {
+        Self {
+            wal,
+            durability,
+            metrics: Arc::default(),
+            active: None,
+            payload: Vec::with_capacity(256),
+            frame: Vec::with_capacity(256),
+            uncommitted: false,
+            unsynced: false,
+            last_sync: Instant::now(),
+        }
+    }
+
+    /// Reports into shared counters instead of private ones.
+    pub fn with_metrics(mut self, metrics: Arc<WalMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Returns the WAL this writer appends to.
+    pub fn wal(&self) -> &StorageWal {
+        &self.wal
+    }
+
+    /// Returns the configured durability mode.
+    pub fn durability(&self) -> WalDurability {
+        self.durability
+    }
+
+    /// Returns the counters this writer reports into.
+    pub fn metrics(&self) -> &Arc<WalMetrics> {
+        &self.metrics
+    }
+
+    /// Frames one event for the active segment; it reaches the file with the
+    /// rest of its group on the next [`WalWriter::commit`].
+    pub fn append(&mut self, event: &EventEnvelope) -> Result<()> {
+        self.payload.clear();
+        self.wal
+            .encoding()
+            .encode_into(event, &mut self.payload)
+            .context("serialize WAL event")
+            .map_err(StorageError::wal_write)?;
+        if self.pending_len()? >= self.wal.segment_max_bytes() {
+            self.rotate()?;
+        }
+
+        let record_offset = self.pending_len()?;
+        let record_offset = if record_offset == 0 {
+            self.frame
+                .extend_from_slice(&segment_header(self.wal.encoding()));
+            SEGMENT_HEADER_LEN as u64
+        } else {
+            record_offset
+        };
+        frame_record(&self.payload, &mut self.frame);
+        let active = self.active.as_mut().expect("active segment is open");
+        active.index.observe(event, record_offset);
+        self.metrics.records_total.fetch_add(1, Ordering::Relaxed);
+        self.uncommitted = true;
+        Ok(())
+    }
+
+    /// Writes the current group of appends in one call and fsyncs it as the
+    /// durability mode requires.
+    pub fn commit(&mut self) -> Result<()> {
+        if !self.uncommitted {
+            return Ok(());
+        }
+        self.uncommitted = false;
+        self.metrics.commits_total.fetch_add(1, Ordering::Relaxed);
+        self.write_frames()?;
+        match self.durability {
+            WalDurability::None => Ok(()),
+            WalDurability::Interval { .. } => self.sync_if_due(),
+            WalDurability::EveryBatch => self.sync(),
+        }
+    }
+
+    /// Fsyncs committed records once the interval has elapsed; used from an
+    /// idle ticker so interval mode does not wait for the next commit.
+    pub fn sync_if_due(&mut self) -> Result<()> {
+        let WalDurability::Interval { interval_ms } = self.durability else {
+            return Ok(());
+        };
+        if self.last_sync.elapsed() >= Duration::from_millis(interval_ms) {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    /// Seals the active segment so everything appended so far becomes
+    /// eligible for compaction; a no-op while the segment is empty.
+    pub fn roll(&mut self) -> Result<()> {
+        if self.pending_len()? == 0 {
+            return Ok(());
+        }
+        self.rotate()
+    }
+
+    /// Drops the open segment and removes all WAL segments.
+    pub fn clear(&mut self) -> Result<()> {
+        self.active = None;
+        self.frame.clear();
+        self.uncommitted = false;
+        self.unsynced = false;
+        self.wal.clear()
+    }
+
+    fn active_segment(&mut self) -> Result<&mut ActiveSegment> {
+        if self.active.is_none() {
+            let (id, path) = self.wal.active_segment_for_append()?;
+            self.active = Some(open_segment(id, path)?);
+        }
+        Ok(self
+            .active
+            .as_mut()
+            .expect("active segment was just opened"))
+    }
+
+    /// Length of the active segment once its framed records are written.
+    fn pending_len(&mut self) -> Result<u64> {
+        let framed = self.frame.len() as u64;
+        Ok(self.active_segment()?.len + framed)
+    }
+
+    /// Writes the framed records of the active segment. A failed write is
+    /// truncated back to the length of the last commit, and the segment is
+    /// reopened on the next append so its length and index come from disk.
+    fn write_frames(&mut self) -> Result<()> {
+        let Some(active) = self.active.as_mut().filter(|_| !self.frame.is_empty()) else {
+            return Ok(());
+        };
+        if let Err(err) = active.file.write_all(&self.frame) {
+            let err = anyhow::Error::new(err).context(format!(
+                "append WAL records to segment {}",
+                active.path.display()
+            ));
+            if let Err(truncate_err) = active.file.set_len(active.len) {
+                tracing::warn!(
+                    error = %truncate_err,
+                    path = %active.path.display(),
+                    "failed to truncate WAL segment after a failed append"
+                );
+            }
+            self.active = None;
+            self.frame.clear();
+            return Err(StorageError::wal_write(err));
+        }
+        let written = self.frame.len() as u64;
+        active.len += written;
+        self.frame.clear();
+        self.metrics
+            .bytes_written_total
+            .fetch_add(written, Ordering::Relaxed);
+        self.unsynced = true;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        self.write_frames()?;
+        // Bytes in the outgoing segment must not stay unsynced forever just
+        // because later fsyncs go to a new file.
+        if self.durability != WalDurability::None {
+            self.sync()?;
+        }
+        let active = self.active_segment()?;
+        // A missing index only costs a rebuild on read, so a failed write
+        // must not stop appends.
+        if let Err(err) = write_index(&active.path, &active.index.finish(active.len)) {
+            tracing::warn!(error = %err, "failed to write WAL segment index");
+        }
+        let next_id = active.id.saturating_add(1);
+        let path = self.wal.segment_path(next_id);
+        self.active = Some(open_segment(next_id, path)?);
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        self.last_sync = Instant::now();
+        let Some(active) = self.active.as_ref().filter(|_| self.unsynced) else {
+            return Ok(());
+        };
+        let started = Instant::now();
+        active
+            .file
+            .sync_data()
+            .with_context(|| format!("fsync WAL segment {}", active.path.display()))
+            .map_err(StorageError::wal_write)?;
+        self.metrics.record_fsync(started.elapsed());
+        self.unsynced = false;
+        Ok(())
+    }
+}
+
+fn open_segment(id: u64, path: PathBuf) -> Result<ActiveSegment> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("open WAL segment {} for append", path.display()))
+        .map_err(StorageError::wal_write)?;
+    let len = file
+        .metadata()
+        .with_context(|| format!("stat WAL segment {}", path.display()))
+        .map_err(StorageError::wal_write)?
+        .len();
+    // Reopened after a restart: index what the previous writer left behind.
+    let index = if len == 0 {
+        SegmentIndexBuilder::new()
+    } else {
+        let bytes = fs::read(&path)
+            .with_context(|| format!("read WAL segment {} for indexing", path.display()))
+            .map_err(StorageError::wal_write)?;
+        SegmentIndexBuilder::from_segment(&bytes)
+    };
+    Ok(ActiveSegment {
+        id,
+        path,
+        file,
+        len,
+        index,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durability_parses_config_labels() {
+        assert_eq!("none".parse(), Ok(WalDurability::None));
+        assert_eq!(" EVERY_BATCH ".parse(), Ok(WalDurability::EveryBatch));
+        assert_eq!(
+            "interval".parse(),
+            Ok(WalDurability::Interval {
+                interval_ms: DEFAULT_FSYNC_INTERVAL_MS
+            })
+        );
+        assert_eq!(
+            "interval:250".parse(),
+            Ok(WalDurability::Interval { interval_ms: 250 })
+        );
+        assert!("interval:soon".parse::<WalDurability>().is_err());
+        assert!("always".parse::<WalDurability>().is_err());
+    }
+}
@@ -1,8 +1,11 @@
 use common::{SourceId, TxHash};
-use event_log::{EventEnvelope, EventPayload, TxSeen};
+use event_log::{EventEncoding, EventEnvelope, EventPayload, TxSeen};
 use parking_lot::RwLock;
 use std::sync::Arc;
-use storage::{BackfillConfig, BackfillSummary, BackfillWriter, EventStore, InMemoryStorage};
+use storage::{
+    BackfillConfig, BackfillSummary, BackfillWriter, EventStore, InMemoryStorage,
+    clickhouse_typed_inserts,
+};
 
 fn hash(v: u8) -> TxHash {
     [v; 32]
@@ -20,6 +23,9 @@ fn seen_event(seq_id: u64, ts_unix_ms: i64, hash_v: u8) -> EventEnvelope {
             seen_at_unix_ms: ts_unix_ms,
             seen_at_mono_ns: seq_id * 10,
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -74,3 +80,70 @@ fn backfill_prunes_events_outside_retention_window() {
     assert_eq!(events[0].seq_id, 2);
     assert_eq!(events[1].seq_id, 3);
 }
+
+#[test]
+fn backfill_upcasts_legacy_clickhouse_rows() {
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let mut writer = BackfillWriter::new(storage.clone(), BackfillConfig::default());
+    let now = 1_710_000_000_000_i64;
+    let legacy_payload = serde_json::json!({
+        "type": "SimDispatched",
+        "data": {
+            "candidate_id": "cand-1",
+            "tx_hash": hash(4),
+            "block_number": 19_000_000
+        }
+    });
+    let legacy_row = serde_json::json!({
+        "seq_id": 1,
+        "ingest_ts_unix_ms": now - 1_000,
+        "ingest_ts_mono_ns": 10,
+        "source_id": "clickhouse",
+        "payload_json": legacy_payload.to_string(),
+    });
+    let mut rows = format!("{legacy_row}\n").into_bytes();
+    EventEncoding::Json
+        .encode_into(&seen_event(2, now - 900, 2), &mut rows)
+        .expect("encode current row");
+
+    let summary = writer.apply_json_rows(&rows, now).expect("apply rows");
+    let events = storage.read().list_events();
+
+    assert_eq!(summary.inserted, 2);
+    let EventPayload::SimDispatched(dispatched) = &events[0].payload else {
+        panic!("expected SimDispatched");
+    };
+    assert_eq!(dispatched.member_tx_hashes, vec![hash(4)]);
+    assert_eq!(events[1], seen_event(2, now - 900, 2));
+}
+
+#[test]
+fn backfill_reads_back_typed_sink_event_rows() {
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let mut writer = BackfillWriter::new(storage.clone(), BackfillConfig::default());
+    let now = 1_710_000_000_000_i64;
+    let mut on_chain = seen_event(1, now - 1_000, 1);
+    on_chain.chain_id = Some(8453);
+    let events = vec![on_chain, seen_event(2, now - 900, 2)];
+
+    let inserts = clickhouse_typed_inserts(&events).expect("encode typed inserts");
+    let events_insert = inserts
+        .iter()
+        .find(|insert| insert.table == "events")
+        .expect("events insert");
+    assert_eq!(
+        events_insert.query,
+        "INSERT INTO mev_v2.events FORMAT JSONEachRow"
+    );
+    let lifecycle = inserts
+        .iter()
+        .find(|insert| insert.table == "lifecycle")
+        .expect("lifecycle insert");
+    assert_eq!(lifecycle.rows, 2);
+
+    let summary = writer
+        .apply_json_rows(&events_insert.body, now)
+        .expect("apply rows");
+    assert_eq!(summary.inserted, 2);
+    assert_eq!(storage.read().list_events(), events);
+}
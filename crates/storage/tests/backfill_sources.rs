@@ -0,0 +1,481 @@
+use common::SourceId;
+use event_log::{EventEncoding, EventEnvelope, EventPayload, TxConfirmed, TxDecoded, TxSeen};
+use parking_lot::{Mutex, RwLock};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use storage::{
+    BackfillJob, BackfillProgress, BackfillRange, BackfillState, ClickHouseBackfillSource,
+    ClickHouseBatchSink, EventSinkFanout, EventStore, InMemoryStorage, StorageError, StorageWal,
+    StorageWriteHandle, StorageWriteOp, StorageWriterConfig, WalBackfillSource, WalDurability,
+    WalHead, clickhouse_typed_inserts, spawn_single_writer_with_backfill,
+};
+
+fn temp_dir(suffix: &str) -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("prototype03-backfill-{suffix}-{now}"));
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    dir
+}
+
+fn history_event(seq_id: u64) -> EventEnvelope {
+    EventEnvelope {
+        seq_id,
+        ingest_ts_unix_ms: 1_700_000_000_000 + seq_id as i64,
+        ingest_ts_mono_ns: seq_id * 10,
+        source_id: SourceId::new("history"),
+        payload: EventPayload::TxSeen(TxSeen {
+            hash: [seq_id as u8; 32],
+            peer_id: "peer-a".to_owned(),
+            seen_at_unix_ms: 1_700_000_000_000 + seq_id as i64,
+            seen_at_mono_ns: seq_id * 10,
+        }),
+        chain_id: Some(1),
+        chain_seq_id: Some(seq_id),
+        hash_link: None,
+    }
+}
+
+fn live_op(seed: u8) -> StorageWriteOp {
+    StorageWriteOp::AppendPayload {
+        source_id: SourceId::new("live"),
+        chain_id: Some(1),
+        payload: EventPayload::TxSeen(TxSeen {
+            hash: [seed; 32],
+            peer_id: "peer-b".to_owned(),
+            seen_at_unix_ms: 1_800_000_000_000,
+            seen_at_mono_ns: 1,
+        }),
+        ingest_ts_unix_ms: 1_800_000_000_000,
+        ingest_ts_mono_ns: 1,
+    }
+}
+
+fn history_wal(dir: &Path, events: impl IntoIterator<Item = u64>) -> PathBuf {
+    let path = dir.join("history").join("events.wal");
+    let wal = StorageWal::new(&path).expect("create history wal");
+    for seq_id in events {
+        wal.append_event(&history_event(seq_id))
+            .expect("append history event");
+    }
+    path
+}
+
+fn writer_config(wal_path: Option<PathBuf>) -> StorageWriterConfig {
+    StorageWriterConfig {
+        queue_capacity: 8,
+        flush_batch_size: 64,
+        flush_interval_ms: 60_000,
+        wal_path,
+        wal_encoding: EventEncoding::Json,
+        wal_durability: WalDurability::EveryBatch,
+        hash_chain: None,
+        checkpoint: None,
+        event_sinks: EventSinkFanout::default(),
+    }
+}
+
+/// Sink that records the events the writer flushes.
+#[derive(Default)]
+struct RecordingSink {
+    flushed: Mutex<Vec<EventEnvelope>>,
+}
+
+#[async_trait::async_trait]
+impl ClickHouseBatchSink for RecordingSink {
+    async fn flush_event_batch(&self, events: Vec<EventEnvelope>) -> Result<(), StorageError> {
+        self.flushed.lock().extend(events);
+        Ok(())
+    }
+}
+
+async fn finished(handle: &StorageWriteHandle) -> BackfillProgress {
+    for _ in 0..200 {
+        let progress = handle.backfill_progress().expect("writer has a backfill");
+        if matches!(
+            progress.state,
+            BackfillState::Completed | BackfillState::Failed
+        ) {
+            return progress;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("backfill did not finish");
+}
+
+#[tokio::test]
+async fn live_events_are_sequenced_after_the_backfilled_wal() {
+    let dir = temp_dir("wal-handoff");
+    let history = history_wal(&dir, 1..=5);
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let sink = Arc::new(RecordingSink::default());
+    let job = BackfillJob::new(
+        WalBackfillSource::open(&history, BackfillRange::default()).expect("open history"),
+    )
+    .with_batch_size(2);
+
+    let handle =
+        spawn_single_writer_with_backfill(storage.clone(), sink.clone(), writer_config(None), job);
+    handle.enqueue(live_op(0xaa)).await.expect("enqueue live");
+
+    let progress = finished(&handle).await;
+    assert_eq!(progress.state, BackfillState::Completed);
+    assert_eq!(progress.head_seq_id, Some(5));
+    assert_eq!((progress.applied, progress.batches), (5, 3));
+    drop(handle);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let events = storage.read().list_events();
+    let seq_ids = events.iter().map(|event| event.seq_id).collect::<Vec<_>>();
+    assert_eq!(seq_ids, vec![1, 2, 3, 4, 5, 6]);
+    let live = &events[5];
+    assert_eq!(live.source_id, SourceId::new("live"));
+    assert_eq!(live.chain_seq_id, Some(6));
+    let flushed = sink.flushed.lock().clone();
+    assert_eq!(
+        flushed.iter().map(|event| event.seq_id).collect::<Vec<_>>(),
+        vec![6],
+        "backfilled history is not re-inserted into ClickHouse"
+    );
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// Serves `events` as one batch once `release` is notified.
+struct GatedSource {
+    events: Vec<EventEnvelope>,
+    release: Arc<tokio::sync::Notify>,
+}
+
+#[async_trait::async_trait]
+impl storage::BackfillSource for GatedSource {
+    fn label(&self) -> String {
+        "gated".to_owned()
+    }
+
+    fn resume_after(&mut self, _seq_id: u64) {}
+
+    async fn head(&mut self) -> Result<WalHead, StorageError> {
+        Ok(WalHead {
+            latest_seq_id: self.events.iter().map(|event| event.seq_id).max(),
+            ..WalHead::default()
+        })
+    }
+
+    async fn next_batch(&mut self, _limit: usize) -> Result<Vec<EventEnvelope>, StorageError> {
+        if !self.events.is_empty() {
+            self.release.notified().await;
+        }
+        Ok(std::mem::take(&mut self.events))
+    }
+}
+
+#[tokio::test]
+async fn late_history_does_not_roll_back_a_live_lifecycle() {
+    let tx = [0x42; 32];
+    let decoded = EventEnvelope {
+        seq_id: 1,
+        ingest_ts_unix_ms: 1_700_000_000_000,
+        ingest_ts_mono_ns: 10,
+        source_id: SourceId::new("history"),
+        payload: EventPayload::TxDecoded(TxDecoded {
+            hash: tx,
+            tx_type: 2,
+            sender: [0x11; 20],
+            nonce: 7,
+            chain_id: Some(1),
+            to: None,
+            value_wei: None,
+            gas_limit: Some(21_000),
+            gas_price_wei: None,
+            max_fee_per_gas_wei: Some(30_000_000_000),
+            max_priority_fee_per_gas_wei: Some(2_000_000_000),
+            max_fee_per_blob_gas_wei: None,
+            calldata_len: Some(0),
+        }),
+        chain_id: Some(1),
+        chain_seq_id: Some(1),
+        hash_link: None,
+    };
+    let release = Arc::new(tokio::sync::Notify::new());
+    let job = BackfillJob::new(GatedSource {
+        events: vec![decoded],
+        release: release.clone(),
+    });
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let sink = Arc::new(RecordingSink::default());
+    let handle = spawn_single_writer_with_backfill(storage.clone(), sink, writer_config(None), job);
+
+    handle
+        .enqueue(StorageWriteOp::AppendPayload {
+            source_id: SourceId::new("live"),
+            chain_id: Some(1),
+            payload: EventPayload::TxConfirmedFinal(TxConfirmed {
+                hash: tx,
+                block_number: 100,
+                block_hash: [0xbb; 32],
+            }),
+            ingest_ts_unix_ms: 1_800_000_000_000,
+            ingest_ts_mono_ns: 1,
+        })
+        .await
+        .expect("enqueue live");
+    let mut applied = false;
+    for _ in 0..200 {
+        if storage.read().latest_seq_id() == Some(2) {
+            applied = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert!(applied, "live event was not applied before the backfill");
+    release.notify_one();
+
+    let progress = finished(&handle).await;
+    assert_eq!(
+        (progress.state, progress.applied),
+        (BackfillState::Completed, 1)
+    );
+    let guard = storage.read();
+    assert_eq!(
+        guard
+            .list_events()
+            .iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    let lifecycle = guard.tx_lifecycle_by_hash(&tx).expect("lifecycle row");
+    assert_eq!(lifecycle.status, "confirmed_final");
+}
+
+#[tokio::test]
+async fn restart_resumes_after_the_cursor_the_wal_recovered() {
+    let dir = temp_dir("wal-resume");
+    let history = history_wal(&dir, 1..=5);
+    let node_wal = dir.join("node").join("events.wal");
+    let cursor = dir.join("backfill.cursor");
+
+    // The first run is cut short at seq 3.
+    let partial = BackfillRange {
+        to_seq_id: Some(3),
+        ..BackfillRange::default()
+    };
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let handle = spawn_single_writer_with_backfill(
+        storage,
+        Arc::new(RecordingSink::default()),
+        writer_config(Some(node_wal.clone())),
+        BackfillJob::new(WalBackfillSource::open(&history, partial).expect("open history"))
+            .with_cursor_path(&cursor),
+    );
+    assert_eq!(finished(&handle).await.applied, 3);
+    drop(handle);
+
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let handle = spawn_single_writer_with_backfill(
+        storage.clone(),
+        Arc::new(RecordingSink::default()),
+        writer_config(Some(node_wal)),
+        BackfillJob::new(
+            WalBackfillSource::open(&history, BackfillRange::default()).expect("open history"),
+        )
+        .with_cursor_path(&cursor),
+    );
+    let progress = finished(&handle).await;
+    assert_eq!(progress.resumed_after_seq_id, Some(3));
+    assert_eq!((progress.applied, progress.skipped_duplicates), (2, 0));
+    assert_eq!(progress.last_seq_id, Some(5));
+    assert_eq!(storage.read().latest_seq_id(), Some(5));
+    assert_eq!(storage.read().list_events().len(), 5);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn cursor_is_ignored_when_storage_lost_the_backfilled_prefix() {
+    let dir = temp_dir("wal-stale-cursor");
+    let history = history_wal(&dir, 1..=4);
+    let cursor = dir.join("backfill.cursor");
+    let job = || {
+        BackfillJob::new(
+            WalBackfillSource::open(&history, BackfillRange::default()).expect("open history"),
+        )
+        .with_cursor_path(&cursor)
+    };
+
+    let handle = spawn_single_writer_with_backfill(
+        Arc::new(RwLock::new(InMemoryStorage::default())),
+        Arc::new(RecordingSink::default()),
+        writer_config(None),
+        job(),
+    );
+    assert_eq!(finished(&handle).await.applied, 4);
+    drop(handle);
+
+    // Without a WAL nothing was recovered, so the cursor is not trusted.
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    storage.write().append_event(history_event(2));
+    let handle = spawn_single_writer_with_backfill(
+        storage.clone(),
+        Arc::new(RecordingSink::default()),
+        writer_config(None),
+        job(),
+    );
+    let progress = finished(&handle).await;
+    assert_eq!(progress.resumed_after_seq_id, None);
+    assert_eq!((progress.applied, progress.skipped_duplicates), (3, 1));
+    assert_eq!(storage.read().list_events().len(), 4);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// ClickHouse stand-in serving `rows` (typed-sink `events` rows) to the
+/// backfill's paged `SELECT`s.
+#[derive(Clone)]
+struct EventsTable {
+    rows: Arc<Vec<(u64, String)>>,
+    queries: Arc<Mutex<Vec<String>>>,
+}
+
+impl EventsTable {
+    fn start(events: &[EventEnvelope]) -> (Self, String) {
+        let inserts = clickhouse_typed_inserts(events).expect("encode events");
+        let body = &inserts
+            .iter()
+            .find(|insert| insert.table == "events")
+            .expect("events insert")
+            .body;
+        let rows = String::from_utf8_lossy(body)
+            .lines()
+            .map(|line| {
+                let row: serde_json::Value = serde_json::from_str(line).expect("decode row");
+                (row["seq_id"].as_u64().expect("seq_id"), line.to_owned())
+            })
+            .collect();
+        let table = Self {
+            rows: Arc::new(rows),
+            queries: Arc::default(),
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stand-in");
+        let url = format!("http://{}/", listener.local_addr().expect("local addr"));
+        let server = table.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                server.serve(stream);
+            }
+        });
+        (table, url)
+    }
+
+    fn answer(&self, sql: &str) -> String {
+        let number_after = |marker: &str| {
+            sql.split_once(marker).and_then(|(_, rest)| {
+                rest.split(|c: char| !c.is_ascii_digit())
+                    .find(|part| !part.is_empty())
+                    .and_then(|part| part.parse::<u64>().ok())
+            })
+        };
+        let after = number_after("seq_id > ").unwrap_or(0);
+        let to = number_after("seq_id <= ").unwrap_or(u64::MAX);
+        let in_range = self
+            .rows
+            .iter()
+            .filter(|(seq_id, _)| *seq_id > after && *seq_id <= to);
+        if sql.contains("count()") {
+            let (max, count) = in_range.fold((0, 0), |(max, count), (seq_id, _)| {
+                (max.max(*seq_id), count + 1)
+            });
+            return format!("{{\"seq_id\":{max},\"events\":{count}}}\n");
+        }
+        let limit = number_after("LIMIT ").unwrap_or(u64::MAX) as usize;
+        in_range
+            .take(limit)
+            .map(|(_, row)| format!("{row}\n"))
+            .collect()
+    }
+
+    fn serve(&self, stream: TcpStream) {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).is_err() {
+            return;
+        }
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).is_err() || line == "\r\n" || line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        let mut body = vec![0; content_length];
+        let _ = reader.read_exact(&mut body);
+        let sql = String::from_utf8_lossy(&body).into_owned();
+        let reply = self.answer(&sql);
+        self.queries.lock().push(sql);
+        let mut stream = reader.into_inner();
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{reply}",
+            reply.len()
+        );
+    }
+}
+
+#[tokio::test]
+async fn clickhouse_history_is_paged_into_storage_and_skips_known_events() {
+    let history = (1..=5).map(history_event).collect::<Vec<_>>();
+    let (table, url) = EventsTable::start(&history);
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    storage.write().append_event(history_event(1));
+    let range = BackfillRange {
+        to_seq_id: Some(4),
+        ..BackfillRange::default()
+    };
+    let job = BackfillJob::new(ClickHouseBackfillSource::new(url, range).expect("source"))
+        .with_batch_size(2);
+
+    let handle = spawn_single_writer_with_backfill(
+        storage.clone(),
+        Arc::new(RecordingSink::default()),
+        writer_config(None),
+        job,
+    );
+    let progress = finished(&handle).await;
+    assert_eq!(progress.state, BackfillState::Completed, "{progress:?}");
+    assert_eq!(progress.head_seq_id, Some(4));
+    assert_eq!((progress.applied, progress.skipped_duplicates), (3, 1));
+
+    handle.enqueue(live_op(0xbb)).await.expect("enqueue live");
+    drop(handle);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let events = storage.read().list_events();
+    // The events table keeps no chain seq ids.
+    assert!(
+        events[1..4]
+            .iter()
+            .zip(&history[1..4])
+            .all(|(event, history)| event.seq_id == history.seq_id
+                && event.payload == history.payload
+                && event.chain_seq_id.is_none())
+    );
+    assert_eq!(events[4].seq_id, 5);
+    let queries = table.queries.lock().clone();
+    assert!(
+        queries
+            .iter()
+            .skip(1)
+            .all(|sql| sql.contains("seq_id <= 4") && sql.contains("LIMIT 2")),
+        "{queries:?}"
+    );
+}
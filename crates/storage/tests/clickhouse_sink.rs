@@ -0,0 +1,330 @@
+use common::SourceId;
+use event_log::{EventEnvelope, EventPayload, TxSeen};
+use parking_lot::Mutex;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use storage::{
+    ClickHouseBatchSink, ClickHouseHttpSink, ClickHouseRetryConfig, ClickHouseSchemaConfig,
+    ClickHouseSinkStatus, ClickHouseSpillConfig, ResilientClickHouseSink,
+    ResilientClickHouseSinkConfig,
+};
+
+/// Request seen by the stand-in: decoded query string and body.
+#[derive(Clone, Debug)]
+struct RecordedRequest {
+    query: String,
+    body: Vec<u8>,
+}
+
+/// Minimal ClickHouse HTTP stand-in that fails the next `fail_next`
+/// requests with a 500 and answers the rest with 200.
+#[derive(Clone, Default)]
+struct StandIn {
+    fail_next: Arc<AtomicUsize>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    response_body: Arc<Mutex<String>>,
+}
+
+impl StandIn {
+    fn start() -> (Self, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stand-in");
+        let url = format!("http://{}/", listener.local_addr().expect("local addr"));
+        let stand_in = Self::default();
+        let server = stand_in.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                server.serve(stream);
+            }
+        });
+        (stand_in, url)
+    }
+
+    fn fail_next(&self, requests: usize) {
+        self.fail_next.store(requests, Ordering::SeqCst);
+    }
+
+    fn delivered(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().clone()
+    }
+
+    fn serve(&self, stream: TcpStream) {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).is_err() {
+            return;
+        }
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).is_err() || line == "\r\n" || line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        let mut body = vec![0; content_length];
+        let _ = reader.read_exact(&mut body);
+        let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+        let query = target.split_once('?').map_or("", |(_, query)| query);
+        let failing = self
+            .fail_next
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                left.checked_sub(1)
+            })
+            .is_ok();
+        let (status, reply) = if failing {
+            ("500 Internal Server Error", "injected failure".to_owned())
+        } else {
+            self.requests.lock().push(RecordedRequest {
+                query: percent_decode(query),
+                body,
+            });
+            ("200 OK", self.response_body.lock().clone())
+        };
+        let mut stream = reader.into_inner();
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{reply}",
+            reply.len()
+        );
+    }
+}
+
+fn percent_decode(query: &str) -> String {
+    let bytes = query.replace('+', " ").into_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(byte) = std::str::from_utf8(&bytes[index + 1..(index + 3).min(bytes.len())])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            out.push(byte);
+            index += 3;
+        } else {
+            out.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn seen_event(seq_id: u64) -> EventEnvelope {
+    EventEnvelope {
+        seq_id,
+        ingest_ts_unix_ms: 1_700_000_000_000 + seq_id as i64,
+        ingest_ts_mono_ns: seq_id,
+        source_id: SourceId::new("sink-test"),
+        payload: EventPayload::TxSeen(TxSeen {
+            hash: [seq_id as u8; 32],
+            peer_id: "peer-a".to_owned(),
+            seen_at_unix_ms: 1_700_000_000_000,
+            seen_at_mono_ns: seq_id,
+        }),
+        chain_id: Some(1),
+        chain_seq_id: Some(seq_id),
+        hash_link: None,
+    }
+}
+
+fn temp_dir(suffix: &str) -> std::path::PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir().join(format!("prototype03-clickhouse-{suffix}-{now}"))
+}
+
+fn fast_retry(max_attempts: u32) -> ClickHouseRetryConfig {
+    ClickHouseRetryConfig {
+        max_attempts,
+        initial_backoff_ms: 1,
+        max_backoff_ms: 4,
+    }
+}
+
+#[tokio::test]
+async fn retries_transient_failures_with_a_stable_dedup_token() {
+    let (stand_in, url) = StandIn::start();
+    let inner = ClickHouseHttpSink::new(url).expect("http sink");
+    let sink = ResilientClickHouseSink::new(
+        inner,
+        ResilientClickHouseSinkConfig {
+            retry: fast_retry(3),
+            spill: None,
+        },
+    )
+    .expect("resilient sink");
+
+    stand_in.fail_next(2);
+    sink.flush_event_batch(vec![seen_event(1), seen_event(2)])
+        .await
+        .expect("third attempt succeeds");
+
+    let delivered = stand_in.delivered();
+    assert_eq!(delivered.len(), 1);
+    assert!(
+        delivered[0]
+            .query
+            .contains("insert_deduplication_token=events-1-2-2")
+    );
+    let health = sink.health_snapshot();
+    assert_eq!(health.status, ClickHouseSinkStatus::Healthy);
+    assert_eq!(health.retries_total, 2);
+    assert_eq!(health.failures_total, 2);
+    assert_eq!(health.batches_delivered_total, 1);
+
+    stand_in.fail_next(usize::MAX);
+    let err = sink
+        .flush_event_batch(vec![seen_event(3)])
+        .await
+        .expect_err("no spill dir means the batch is dropped");
+    assert!(err.to_string().contains("ClickHouse batch failed:"));
+    let health = sink.health_snapshot();
+    assert_eq!(health.status, ClickHouseSinkStatus::Down);
+    assert_eq!(health.batches_dropped_total, 1);
+    stand_in.fail_next(0);
+}
+
+#[tokio::test]
+async fn spills_while_down_and_drains_in_seq_order_after_recovery() {
+    let (stand_in, url) = StandIn::start();
+    let spill_dir = temp_dir("spill");
+    let config = ResilientClickHouseSinkConfig {
+        retry: fast_retry(2),
+        spill: Some(ClickHouseSpillConfig::new(&spill_dir)),
+    };
+    let sink = ResilientClickHouseSink::new(
+        ClickHouseHttpSink::new(url.clone()).expect("http sink"),
+        config.clone(),
+    )
+    .expect("resilient sink");
+
+    stand_in.fail_next(usize::MAX);
+    sink.flush_event_batch(vec![seen_event(1), seen_event(2)])
+        .await
+        .expect("failed batch is spilled");
+    sink.flush_event_batch(vec![seen_event(3)])
+        .await
+        .expect("batch behind a backlog is spilled without retrying");
+    let health = sink.health_snapshot();
+    assert_eq!(health.status, ClickHouseSinkStatus::Degraded);
+    assert_eq!(health.spilled_batches, 2);
+    assert_eq!(health.batches_spilled_total, 2);
+    assert_eq!(health.failures_total, 2, "only the first batch was tried");
+    assert_eq!(sink.drain_spill().await.expect("drain while down"), 0);
+
+    // A restarted writer picks up the backlog left on disk.
+    drop(sink);
+    let sink =
+        ResilientClickHouseSink::new(ClickHouseHttpSink::new(url).expect("http sink"), config)
+            .expect("reopen resilient sink");
+    assert_eq!(sink.health_snapshot().spilled_batches, 2);
+
+    stand_in.fail_next(0);
+    assert_eq!(sink.drain_spill().await.expect("drain after recovery"), 2);
+    let delivered = stand_in.delivered();
+    assert_eq!(delivered.len(), 2);
+    assert!(delivered[0].query.contains("events-1-2-2"));
+    assert!(delivered[1].query.contains("events-3-3-1"));
+    let first_batch = String::from_utf8(delivered[0].body.clone()).expect("ndjson body");
+    assert_eq!(first_batch.lines().count(), 2);
+
+    let health = sink.health_snapshot();
+    assert_eq!(health.status, ClickHouseSinkStatus::Healthy);
+    assert_eq!(health.spilled_batches, 0);
+    assert_eq!(health.spilled_bytes, 0);
+    assert_eq!(health.batches_drained_total, 2);
+    assert_eq!(std::fs::read_dir(&spill_dir).expect("spill dir").count(), 0);
+
+    let _ = std::fs::remove_dir_all(spill_dir);
+}
+
+#[tokio::test]
+async fn full_spill_dir_drops_batches_and_reports_down() {
+    let (stand_in, url) = StandIn::start();
+    let spill_dir = temp_dir("spill-full");
+    let sink = ResilientClickHouseSink::new(
+        ClickHouseHttpSink::new(url).expect("http sink"),
+        ResilientClickHouseSinkConfig {
+            retry: fast_retry(1),
+            spill: Some(ClickHouseSpillConfig {
+                max_bytes: 16,
+                ..ClickHouseSpillConfig::new(&spill_dir)
+            }),
+        },
+    )
+    .expect("resilient sink");
+
+    stand_in.fail_next(usize::MAX);
+    let err = sink
+        .flush_event_batch(vec![seen_event(1)])
+        .await
+        .expect_err("batch larger than the spill cap is dropped");
+    assert!(err.to_string().contains("is full"));
+    let health = sink.health_snapshot();
+    assert_eq!(health.status, ClickHouseSinkStatus::Down);
+    assert_eq!(health.batches_dropped_total, 1);
+    assert_eq!(health.spilled_batches, 0);
+    stand_in.fail_next(0);
+
+    let _ = std::fs::remove_dir_all(spill_dir);
+}
+
+#[tokio::test]
+async fn typed_sink_applies_migrations_then_sends_deduplicated_inserts() {
+    let (stand_in, url) = StandIn::start();
+    *stand_in.response_body.lock() = "0\n".to_owned();
+    let sink =
+        ClickHouseHttpSink::typed(url, ClickHouseSchemaConfig::default()).expect("typed sink");
+
+    sink.flush_event_batch(vec![seen_event(5)])
+        .await
+        .expect("typed flush");
+
+    let delivered = stand_in.delivered();
+    let ddl = delivered
+        .iter()
+        .filter(|request| request.query.is_empty())
+        .map(|request| String::from_utf8_lossy(&request.body).into_owned())
+        .collect::<Vec<_>>();
+    assert!(ddl[0].starts_with("CREATE DATABASE IF NOT EXISTS mev_v2"));
+    assert!(ddl.iter().any(|statement| statement.contains(
+        "INSERT INTO mev_v2.schema_migrations (version, name) VALUES (4, 'insert_deduplication')"
+    )));
+    let inserts = delivered
+        .iter()
+        .filter(|request| request.query.contains("INSERT INTO"))
+        .collect::<Vec<_>>();
+    assert_eq!(inserts.len(), 2);
+    assert!(inserts[0].query.contains("FORMAT JSONEachRow"));
+    assert!(
+        inserts[0]
+            .query
+            .contains("insert_deduplication_token=events-5-5-1")
+    );
+    assert!(inserts[1].query.contains("INSERT INTO mev_v2.lifecycle"));
+    assert!(inserts[1].query.contains("FORMAT RowBinary"));
+    assert!(
+        inserts[1]
+            .query
+            .contains("insert_deduplication_token=lifecycle-5-5-1")
+    );
+
+    let before = stand_in.delivered().len();
+    sink.flush_event_batch(vec![seen_event(6)])
+        .await
+        .expect("second typed flush");
+    assert_eq!(
+        stand_in.delivered().len() - before,
+        2,
+        "migrations run once per sink"
+    );
+}
@@ -0,0 +1,366 @@
+#![cfg(feature = "disk-store")]
+
+use common::{SourceId, TxHash};
+use event_log::{CandidateQueued, EventEnvelope, EventPayload, TxSeen};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use storage::{
+    ColdEventStore, ColdWriteBatch, DiskEventStore, EventStore, InMemoryStorage,
+    NoopClickHouseSink, SharedStorageLookup, StorageConfig, StorageError, StorageWriteOp,
+    StorageWriterConfig, TransactionLookup, TxFullRecord, TxRecordRef, TxSeenRecord,
+    spawn_single_writer,
+};
+
+fn hash(v: u8) -> TxHash {
+    [v; 32]
+}
+
+fn seen_event(seq_id: u64, chain_seq_id: u64) -> EventEnvelope {
+    EventEnvelope {
+        seq_id,
+        ingest_ts_unix_ms: 1_700_000_000_000 + seq_id as i64,
+        ingest_ts_mono_ns: seq_id * 10,
+        source_id: SourceId::new("disk-store-test"),
+        payload: EventPayload::TxSeen(TxSeen {
+            hash: hash(seq_id as u8),
+            peer_id: "peer-a".to_owned(),
+            seen_at_unix_ms: 1_700_000_000_000 + seq_id as i64,
+            seen_at_mono_ns: seq_id * 10,
+        }),
+        chain_id: Some(1),
+        chain_seq_id: Some(chain_seq_id),
+        hash_link: None,
+    }
+}
+
+fn seen_record(v: u8) -> TxSeenRecord {
+    TxSeenRecord {
+        hash: hash(v),
+        peer: "peer-a".to_owned(),
+        first_seen_unix_ms: 1_700_000_000_000 + i64::from(v),
+        first_seen_mono_ns: u64::from(v),
+        seen_count: 1,
+    }
+}
+
+fn full_record(v: u8) -> TxFullRecord {
+    TxFullRecord {
+        hash: hash(v),
+        tx_type: 2,
+        sender: [v; 20],
+        nonce: u64::from(v),
+        to: Some([0xaa; 20]),
+        chain_id: Some(1),
+        value_wei: Some(u128::MAX),
+        gas_limit: Some(21_000),
+        gas_price_wei: None,
+        max_fee_per_gas_wei: Some(30_000_000_000),
+        max_priority_fee_per_gas_wei: Some(1_000_000_000),
+        max_fee_per_blob_gas_wei: None,
+        calldata_len: Some(4),
+        raw_tx: vec![0x02, v],
+        l2_fields: None,
+    }
+}
+
+fn temp_db(name: &str) -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir()
+        .join(format!("prototype03-disk-store-{name}-{now}"))
+        .join("events.redb")
+}
+
+fn seq_ids(events: &[EventEnvelope]) -> Vec<u64> {
+    events.iter().map(|event| event.seq_id).collect()
+}
+
+#[test]
+fn disk_store_scans_by_seq_and_chain_and_survives_reopen() {
+    let path = temp_db("reopen");
+    {
+        let store = DiskEventStore::open(&path).expect("open disk store");
+        store
+            .append_events(
+                &(1..=6)
+                    .map(|seq| seen_event(seq, seq + 100))
+                    .collect::<Vec<_>>(),
+            )
+            .expect("append events");
+        // Re-appending a stored seq id replaces it rather than duplicating it.
+        ColdEventStore::append_event(&store, &seen_event(3, 103)).expect("re-append");
+        ColdEventStore::sync(&store).expect("sync");
+        assert_eq!(store.len().expect("len"), 6);
+    }
+
+    let store = DiskEventStore::open(&path).expect("reopen disk store");
+    assert_eq!(
+        seq_ids(&EventStore::scan_events(&store, 2, 3)),
+        vec![3, 4, 5]
+    );
+    assert_eq!(EventStore::latest_seq_id(&store), Some(6));
+    assert_eq!(
+        seq_ids(&EventStore::scan_chain_events(&store, 1, 104, 10)),
+        vec![5, 6]
+    );
+    assert_eq!(EventStore::latest_chain_seq_id(&store, 1), Some(106));
+    assert_eq!(EventStore::latest_chain_seq_id(&store, 2), None);
+    assert_eq!(store.list_events().len(), 6);
+
+    let _ = std::fs::remove_dir_all(path.parent().expect("db dir"));
+}
+
+#[test]
+fn disk_store_upcasts_events_stored_by_older_writers() {
+    let path = temp_db("upcast");
+    let store = DiskEventStore::open(&path).expect("open disk store");
+    let mut queued = seen_event(1, 101);
+    queued.payload = EventPayload::CandidateQueued(CandidateQueued {
+        candidate_id: "candidate-1".to_owned(),
+        tx_hash: hash(0x11),
+        member_tx_hashes: Vec::new(),
+        chain_id: Some(1),
+        strategy: "SandwichCandidate".to_owned(),
+        score: 1,
+        protocol: String::new(),
+        category: String::new(),
+        feature_engine_version: String::new(),
+        scorer_version: String::new(),
+        strategy_version: String::new(),
+        reasons: Vec::new(),
+        detected_unix_ms: 1_700_000_000_001,
+    });
+    ColdEventStore::append_event(&store, &queued).expect("append event");
+
+    let stored = EventStore::scan_events(&store, 0, 1);
+    let EventPayload::CandidateQueued(stored) = &stored[0].payload else {
+        panic!("expected a queued candidate, got {:?}", stored[0].payload);
+    };
+    assert_eq!(stored.member_tx_hashes, vec![hash(0x11)]);
+
+    let _ = std::fs::remove_dir_all(path.parent().expect("db dir"));
+}
+
+#[test]
+fn in_memory_storage_reads_evicted_events_and_hashes_from_disk() {
+    let path = temp_db("hot-cache");
+    let disk = Arc::new(DiskEventStore::open(&path).expect("open disk store"));
+    let mut storage = InMemoryStorage::with_config(StorageConfig {
+        event_capacity: 2,
+        table_capacity: 2,
+        ..StorageConfig::default()
+    })
+    .with_cold_store(disk.clone());
+
+    for seq in 1..=5 {
+        storage.append_event(seen_event(seq, seq));
+        storage.upsert_tx_seen(seen_record(seq as u8));
+        storage.upsert_tx_full(full_record(seq as u8));
+    }
+    // Cold writes are queued until committed in one batch.
+    assert_eq!(disk.len().expect("disk len"), 0);
+    storage.sync_cold_store().expect("sync cold store");
+
+    // The hot window only holds the newest two events.
+    assert_eq!(seq_ids(&storage.list_events()), vec![4, 5]);
+    assert_eq!(seq_ids(&storage.scan_events(3, 10)), vec![4, 5]);
+    // Cursors before the window are answered from disk.
+    assert_eq!(seq_ids(&storage.scan_events(0, 10)), vec![1, 2, 3, 4, 5]);
+    assert_eq!(seq_ids(&storage.scan_chain_events(1, 1, 2)), vec![2, 3]);
+
+    assert!(storage.tx_seen_by_hash(&hash(1)).is_none());
+    let lookup = storage.transaction_lookup(&hash(1));
+    assert_eq!(lookup.seen, Some(seen_record(1)));
+    assert_eq!(lookup.full, Some(full_record(1)));
+    assert_eq!(lookup.features, None);
+    assert!(storage.transaction_lookup(&hash(0x77)).is_empty());
+
+    // A fresh hot cache in front of the same store starts from its head.
+    let restarted = InMemoryStorage::default().with_cold_store(disk);
+    assert_eq!(restarted.latest_seq_id(), Some(5));
+    assert_eq!(restarted.latest_chain_seq_id(1), Some(5));
+    assert_eq!(seq_ids(&restarted.scan_events(3, 10)), vec![4, 5]);
+
+    let _ = std::fs::remove_dir_all(path.parent().expect("db dir"));
+}
+
+/// Disk store whose next batch commit fails while `fail_next` is set, and
+/// whose reads check that the shared storage in front of it is not locked.
+#[derive(Debug)]
+struct TestColdStore {
+    disk: DiskEventStore,
+    fail_next: AtomicBool,
+    shared: OnceLock<Arc<parking_lot::RwLock<InMemoryStorage>>>,
+}
+
+impl TestColdStore {
+    fn new(disk: DiskEventStore, fail_next: bool) -> Self {
+        Self {
+            disk,
+            fail_next: AtomicBool::new(fail_next),
+            shared: OnceLock::new(),
+        }
+    }
+
+    fn assert_storage_unlocked(&self) {
+        if let Some(shared) = self.shared.get() {
+            assert!(
+                shared.try_write().is_some(),
+                "cold store read while the storage lock was held"
+            );
+        }
+    }
+}
+
+impl ColdEventStore for TestColdStore {
+    fn append_event(&self, event: &EventEnvelope) -> Result<(), StorageError> {
+        ColdEventStore::append_event(&self.disk, event)
+    }
+    fn scan_events(
+        &self,
+        from_seq_id: u64,
+        limit: usize,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        self.assert_storage_unlocked();
+        ColdEventStore::scan_events(&self.disk, from_seq_id, limit)
+    }
+    fn latest_seq_id(&self) -> Result<Option<u64>, StorageError> {
+        ColdEventStore::latest_seq_id(&self.disk)
+    }
+    fn scan_chain_events(
+        &self,
+        chain_id: u64,
+        from_chain_seq_id: u64,
+        limit: usize,
+    ) -> Result<Vec<EventEnvelope>, StorageError> {
+        ColdEventStore::scan_chain_events(&self.disk, chain_id, from_chain_seq_id, limit)
+    }
+    fn latest_chain_seq_id(&self, chain_id: u64) -> Result<Option<u64>, StorageError> {
+        ColdEventStore::latest_chain_seq_id(&self.disk, chain_id)
+    }
+    fn upsert_tx_record(&self, record: TxRecordRef<'_>) -> Result<(), StorageError> {
+        self.disk.upsert_tx_record(record)
+    }
+    fn write_batch(&self, batch: &ColdWriteBatch) -> Result<(), StorageError> {
+        if self.fail_next.swap(false, Ordering::SeqCst) {
+            return Err(StorageError::DiskStore(Arc::new(std::io::Error::other(
+                "disk full",
+            ))));
+        }
+        self.disk.write_batch(batch)
+    }
+    fn transaction_lookup(&self, hash: &TxHash) -> Result<TransactionLookup, StorageError> {
+        self.assert_storage_unlocked();
+        self.disk.transaction_lookup(hash)
+    }
+    fn sync(&self) -> Result<(), StorageError> {
+        self.disk.sync()
+    }
+}
+
+#[test]
+fn failed_cold_commit_is_retried_with_later_writes_in_order() {
+    let path = temp_db("flaky");
+    let disk = DiskEventStore::open(&path).expect("open disk store");
+    let cold = Arc::new(TestColdStore::new(disk.clone(), true));
+    let mut storage = InMemoryStorage::default().with_cold_store(cold);
+
+    storage.append_event(seen_event(1, 1));
+    storage.upsert_tx_full(full_record(1));
+    assert!(storage.sync_cold_store().is_err());
+    assert_eq!(disk.len().expect("disk len"), 0);
+
+    storage.append_event(seen_event(2, 2));
+    let mut updated = full_record(1);
+    updated.nonce = 99;
+    storage.upsert_tx_full(updated.clone());
+    storage.sync_cold_store().expect("retry cold commit");
+
+    assert_eq!(seq_ids(&EventStore::scan_events(&disk, 0, 10)), vec![1, 2]);
+    assert_eq!(
+        disk.transaction_lookup(&hash(1)).expect("lookup").full,
+        Some(updated)
+    );
+
+    let _ = std::fs::remove_dir_all(path.parent().expect("db dir"));
+}
+
+#[test]
+fn shared_storage_reads_the_cold_store_after_releasing_its_lock() {
+    let path = temp_db("shared-reads");
+    let cold = Arc::new(TestColdStore::new(
+        DiskEventStore::open(&path).expect("open disk store"),
+        false,
+    ));
+    let mut storage = InMemoryStorage::with_config(StorageConfig {
+        event_capacity: 1,
+        table_capacity: 1,
+        ..StorageConfig::default()
+    })
+    .with_cold_store(cold.clone());
+    for seq in 1..=3 {
+        storage.append_event(seen_event(seq, seq));
+        storage.upsert_tx_seen(seen_record(seq as u8));
+    }
+    storage.sync_cold_store().expect("sync cold store");
+    let shared = Arc::new(parking_lot::RwLock::new(storage));
+    cold.shared.set(shared.clone()).expect("set shared storage");
+
+    assert_eq!(seq_ids(&shared.scan_events(0, 10)), vec![1, 2, 3]);
+    assert_eq!(
+        shared.transaction_lookup(&hash(1)).seen,
+        Some(seen_record(1))
+    );
+    assert!(shared.contains_seq_id(1));
+
+    let _ = std::fs::remove_dir_all(path.parent().expect("db dir"));
+}
+
+#[tokio::test]
+async fn writer_commits_queued_cold_writes_when_it_flushes() {
+    let path = temp_db("writer-flush");
+    let disk = Arc::new(DiskEventStore::open(&path).expect("open disk store"));
+    let storage = Arc::new(parking_lot::RwLock::new(
+        InMemoryStorage::default().with_cold_store(disk.clone()),
+    ));
+    let handle = spawn_single_writer(
+        storage.clone(),
+        Arc::new(NoopClickHouseSink),
+        StorageWriterConfig {
+            flush_batch_size: 3,
+            flush_interval_ms: 60_000,
+            ..StorageWriterConfig::default()
+        },
+    );
+
+    handle
+        .enqueue(StorageWriteOp::UpsertTxFull(full_record(1)))
+        .await
+        .expect("enqueue upsert");
+    for seq in 1..=3 {
+        handle
+            .enqueue(StorageWriteOp::AppendEvent(seen_event(0, seq)))
+            .await
+            .expect("enqueue event");
+    }
+    let mut committed = false;
+    for _ in 0..200 {
+        if disk.len().expect("disk len") == 3 {
+            committed = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert!(committed, "flush did not commit the cold batch");
+    assert_eq!(
+        disk.transaction_lookup(&hash(1)).expect("lookup").full,
+        Some(full_record(1))
+    );
+
+    drop(handle);
+    let _ = std::fs::remove_dir_all(path.parent().expect("db dir"));
+}
@@ -0,0 +1,265 @@
+use common::SourceId;
+use event_log::{EventEncoding, EventEnvelope, EventPayload, TxSeen, decode_event_stream};
+use parking_lot::{Mutex, RwLock};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use storage::{
+    EventSink, EventSinkFanout, EventSinkFilter, EventSinkOptions, EventStore, InMemoryStorage,
+    NdjsonFileSink, NoopClickHouseSink, StorageError, StorageWriteOp, StorageWriterConfig,
+    WebhookSink, spawn_single_writer,
+};
+
+fn temp_dir(suffix: &str) -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir().join(format!("prototype03-event-sinks-{suffix}-{now}"))
+}
+
+fn seen_event(seq_id: u64, chain_id: u64) -> EventEnvelope {
+    EventEnvelope {
+        seq_id,
+        ingest_ts_unix_ms: 1_700_000_000_000 + seq_id as i64,
+        ingest_ts_mono_ns: seq_id,
+        source_id: SourceId::new("sink-test"),
+        payload: EventPayload::TxSeen(TxSeen {
+            hash: [seq_id as u8; 32],
+            peer_id: "peer-a".to_owned(),
+            seen_at_unix_ms: 1_700_000_000_000,
+            seen_at_mono_ns: seq_id,
+        }),
+        chain_id: Some(chain_id),
+        chain_seq_id: None,
+        hash_link: None,
+    }
+}
+
+fn seen_op(seed: u8, chain_id: u64) -> StorageWriteOp {
+    StorageWriteOp::AppendPayload {
+        source_id: SourceId::new("sink-test"),
+        chain_id: Some(chain_id),
+        payload: EventPayload::TxSeen(TxSeen {
+            hash: [seed; 32],
+            peer_id: "peer-a".to_owned(),
+            seen_at_unix_ms: 1_700_000_000_000,
+            seen_at_mono_ns: u64::from(seed),
+        }),
+        ingest_ts_unix_ms: 1_700_000_000_000,
+        ingest_ts_mono_ns: u64::from(seed),
+    }
+}
+
+/// Sink that records batches and, once stalled, never finishes a delivery.
+#[derive(Clone, Default)]
+struct ProbeSink {
+    batches: Arc<Mutex<Vec<Vec<u64>>>>,
+    stalled: bool,
+}
+
+#[async_trait::async_trait]
+impl EventSink for ProbeSink {
+    fn name(&self) -> String {
+        if self.stalled { "stalled" } else { "probe" }.to_owned()
+    }
+
+    async fn deliver(&mut self, events: &[EventEnvelope]) -> Result<(), StorageError> {
+        if self.stalled {
+            std::future::pending::<()>().await;
+        }
+        self.batches
+            .lock()
+            .push(events.iter().map(|event| event.seq_id).collect());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn stalled_sink_drops_its_batches_without_holding_the_writer() {
+    let probe = ProbeSink::default();
+    let fanout = EventSinkFanout::default()
+        .with_sink(
+            probe.clone(),
+            EventSinkOptions {
+                filter: EventSinkFilter {
+                    event_types: vec!["TxSeen".to_owned()],
+                    chain_ids: vec![8453],
+                },
+                ..EventSinkOptions::default()
+            },
+        )
+        .with_sink(
+            ProbeSink {
+                stalled: true,
+                ..ProbeSink::default()
+            },
+            EventSinkOptions {
+                queue_capacity: 1,
+                ..EventSinkOptions::default()
+            },
+        );
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let handle = spawn_single_writer(
+        storage.clone(),
+        Arc::new(NoopClickHouseSink),
+        StorageWriterConfig {
+            flush_batch_size: 2,
+            event_sinks: fanout,
+            ..StorageWriterConfig::default()
+        },
+    );
+
+    for seed in 1..=20_u8 {
+        let chain_id = if seed % 2 == 0 { 8453 } else { 1 };
+        handle
+            .enqueue(seen_op(seed, chain_id))
+            .await
+            .expect("enqueue");
+    }
+    // Every batch has been published once the healthy sink has delivered its
+    // share.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while handle
+            .event_sink_stats()
+            .first()
+            .is_none_or(|stats| stats.delivered_events < 10)
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("probe sink received every matching event");
+
+    assert_eq!(storage.read().list_events().len(), 20);
+    let delivered = probe.batches.lock().concat();
+    assert_eq!(delivered, (1..=10).map(|n| n * 2).collect::<Vec<u64>>());
+
+    let stats = handle.event_sink_stats();
+    assert_eq!(
+        stats
+            .iter()
+            .map(|stats| stats.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["probe", "stalled"]
+    );
+    assert_eq!(
+        (stats[0].delivered_events, stats[0].dropped_events),
+        (10, 0)
+    );
+    let stalled = &stats[1];
+    assert_eq!(stalled.delivered_batches, 0);
+    assert!(stalled.queued_batches <= 1);
+    // At most one batch is stuck in delivery and one waits in the queue.
+    assert!(stalled.dropped_events >= 20 - 4, "{stalled:?}");
+}
+
+#[tokio::test]
+async fn ndjson_sink_rotates_files_and_keeps_the_newest() {
+    let dir = temp_dir("ndjson");
+    let mut sink = NdjsonFileSink::create(&dir)
+        .expect("create sink")
+        .with_rotation(700, 2);
+    let events = (1..=9)
+        .map(|seq_id| seen_event(seq_id, 1))
+        .collect::<Vec<_>>();
+    for batch in events.chunks(2) {
+        sink.deliver(batch).await.expect("deliver");
+    }
+
+    let files = sink.files().expect("list files");
+    assert_eq!(files.len(), 2);
+    let names = files
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str())
+        .collect::<Vec<_>>();
+    assert!(names[0] < names[1], "{names:?}");
+    let mut kept = Vec::new();
+    for file in &files {
+        let bytes = std::fs::read(file).expect("read file");
+        assert_eq!(EventEncoding::detect(&bytes), EventEncoding::Json);
+        kept.extend(decode_event_stream(&bytes).expect("decode file"));
+    }
+    let tail = &events[events.len() - kept.len()..];
+    assert_eq!(kept, tail);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn webhook_sink_posts_ndjson_batches() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind stand-in");
+    let url = format!("http://{}/hook", listener.local_addr().expect("local addr"));
+    let received = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("accept");
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        let mut content_type = String::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).is_err() || line == "\r\n" || line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                } else if name.eq_ignore_ascii_case("content-type") {
+                    content_type = value.trim().to_owned();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        let _ = reader.read_exact(&mut body);
+        let _ = reader
+            .into_inner()
+            .write_all(b"HTTP/1.1 204 No Content\r\nconnection: close\r\n\r\n");
+        (content_type, body)
+    });
+
+    let events = vec![seen_event(1, 1), seen_event(2, 8453)];
+    let mut sink = WebhookSink::new(url).expect("create sink");
+    sink.deliver(&events).await.expect("deliver");
+
+    let (content_type, body) = received.join().expect("stand-in");
+    assert_eq!(content_type, "application/x-ndjson");
+    assert_eq!(decode_event_stream(&body).expect("decode body"), events);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_socket_sink_reconnects_after_the_listener_returns() {
+    use storage::UnixSocketSink;
+
+    let dir = temp_dir("unix");
+    std::fs::create_dir_all(&dir).expect("create dir");
+    let path = dir.join("events.sock");
+    let mut sink = UnixSocketSink::new(&path);
+    assert!(
+        sink.deliver(&[seen_event(1, 1)]).await.is_err(),
+        "nothing is listening yet"
+    );
+
+    let listener = std::os::unix::net::UnixListener::bind(&path).expect("bind socket");
+    let reader = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("accept");
+        BufReader::new(stream)
+            .lines()
+            .take(2)
+            .map(|line| line.expect("read line"))
+            .collect::<Vec<_>>()
+    });
+    sink.deliver(&[seen_event(2, 1)]).await.expect("deliver");
+    sink.deliver(&[seen_event(3, 1)]).await.expect("deliver");
+
+    let lines = reader.join().expect("reader");
+    let events =
+        decode_event_stream(format!("{}\n", lines.join("\n")).as_bytes()).expect("decode lines");
+    assert_eq!(
+        events.iter().map(|event| event.seq_id).collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+
+    let _ = std::fs::remove_dir_all(dir);
+}
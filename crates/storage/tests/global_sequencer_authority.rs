@@ -1,11 +1,11 @@
 use common::SourceId;
-use event_log::{EventPayload, TxSeen};
+use event_log::{EventEncoding, EventPayload, TxSeen};
 use parking_lot::RwLock;
 use std::sync::Arc;
 use std::time::Duration;
 use storage::{
-    EventStore, InMemoryStorage, NoopClickHouseSink, StorageWriteOp, StorageWriterConfig,
-    spawn_single_writer,
+    EventSinkFanout, EventStore, InMemoryStorage, NoopClickHouseSink, StorageWriteOp,
+    StorageWriterConfig, WalDurability, spawn_single_writer,
 };
 
 fn hash(v: u8) -> [u8; 32] {
@@ -24,12 +24,18 @@ async fn append_event_sequence_is_assigned_by_storage_writer() {
             flush_batch_size: 1,
             flush_interval_ms: 5,
             wal_path: None,
+            wal_encoding: EventEncoding::Json,
+            wal_durability: WalDurability::EveryBatch,
+            hash_chain: None,
+            checkpoint: None,
+            event_sinks: EventSinkFanout::default(),
         },
     );
 
     handle
         .enqueue(StorageWriteOp::AppendPayload {
             source_id: SourceId::new("rpc-mainnet"),
+            chain_id: None,
             payload: EventPayload::TxSeen(TxSeen {
                 hash: hash(1),
                 peer_id: "peer-a".to_owned(),
@@ -48,3 +54,56 @@ async fn append_event_sequence_is_assigned_by_storage_writer() {
     assert_eq!(events.len(), 1);
     assert_eq!(events[0].seq_id, 1);
 }
+
+#[tokio::test]
+async fn append_payload_assigns_per_chain_sequence_ids() {
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let handle = spawn_single_writer(
+        storage.clone(),
+        Arc::new(NoopClickHouseSink),
+        StorageWriterConfig {
+            queue_capacity: 8,
+            flush_batch_size: 1,
+            flush_interval_ms: 5,
+            wal_path: None,
+            wal_encoding: EventEncoding::Json,
+            wal_durability: WalDurability::EveryBatch,
+            hash_chain: None,
+            checkpoint: None,
+            event_sinks: EventSinkFanout::default(),
+        },
+    );
+
+    for (seed, chain_id) in [(1, 1), (2, 8453), (3, 1), (4, 8453), (5, 1)] {
+        handle
+            .enqueue(StorageWriteOp::AppendPayload {
+                source_id: SourceId::new("rpc-multichain"),
+                chain_id: Some(chain_id),
+                payload: EventPayload::TxSeen(TxSeen {
+                    hash: hash(seed),
+                    peer_id: "peer-a".to_owned(),
+                    seen_at_unix_ms: 1_700_000_000_000,
+                    seen_at_mono_ns: u64::from(seed),
+                }),
+                ingest_ts_unix_ms: 1_700_000_000_000,
+                ingest_ts_mono_ns: u64::from(seed),
+            })
+            .await
+            .expect("enqueue chain payload");
+    }
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    let guard = storage.read();
+    let mainnet = guard.scan_chain_events(1, 1, 10);
+    assert_eq!(
+        mainnet
+            .iter()
+            .map(|event| (event.seq_id, event.chain_seq_id))
+            .collect::<Vec<_>>(),
+        vec![(3, Some(2)), (5, Some(3))]
+    );
+    assert_eq!(guard.latest_chain_seq_id(8453), Some(2));
+    assert_eq!(guard.latest_chain_seq_ids(), vec![(1, 3), (8453, 2)]);
+    assert!(guard.scan_chain_events(10, 0, 10).is_empty());
+}
@@ -1,5 +1,7 @@
 use common::SourceId;
-use event_log::{AssemblyDecisionApplied, CandidateQueued, EventEnvelope, EventPayload};
+use event_log::{
+    AssemblyDecisionApplied, AssemblyDecisionKind, CandidateQueued, EventEnvelope, EventPayload,
+};
 use storage::{EventStore, InMemoryStorage};
 
 fn hash(value: u8) -> [u8; 32] {
@@ -13,6 +15,9 @@ fn envelope(seq_id: u64, payload: EventPayload) -> EventEnvelope {
         ingest_ts_mono_ns: seq_id * 1_000,
         source_id: SourceId::new("storage-test"),
         payload,
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -37,13 +42,13 @@ fn candidate_queued_event(candidate_id: &str) -> EventEnvelope {
     )
 }
 
-fn assembly_decision_event(candidate_id: &str, decision: &str) -> EventEnvelope {
+fn assembly_decision_event(candidate_id: &str, decision: AssemblyDecisionKind) -> EventEnvelope {
     envelope(
         2,
         EventPayload::AssemblyDecisionApplied(AssemblyDecisionApplied {
             candidate_id: candidate_id.to_owned(),
             tx_hash: hash(0x11),
-            decision: decision.to_owned(),
+            decision,
             replaced_candidate_ids: Vec::new(),
             reason: None,
             block_number: 42,
@@ -55,8 +60,15 @@ fn assembly_decision_event(candidate_id: &str, decision: &str) -> EventEnvelope
 fn append_event_derives_projections_without_direct_opportunity_upserts() {
     let mut store = InMemoryStorage::default();
     store.append_event(candidate_queued_event("cand-1"));
-    store.append_event(assembly_decision_event("cand-1", "inserted"));
+    store.append_event(assembly_decision_event(
+        "cand-1",
+        AssemblyDecisionKind::Inserted,
+    ));
 
     assert_eq!(store.opportunities().len(), 1);
     assert_eq!(store.builder_lifecycle().len(), 1);
+    assert_eq!(
+        store.builder_lifecycle()[0].decision,
+        AssemblyDecisionKind::Inserted
+    );
 }
@@ -0,0 +1,173 @@
+use common::TxHash;
+use std::path::PathBuf;
+use std::sync::Arc;
+use storage::{
+    CalldataBlobStore, InMemoryStorage, MemoryBudgetConfig, StorageConfig, StorageTable,
+    TableWeights, TxFullRecord, TxLifecycleRecord, WalHead,
+};
+
+fn hash(v: u8) -> TxHash {
+    [v; 32]
+}
+
+fn full_record(v: u8, calldata_len: usize) -> TxFullRecord {
+    TxFullRecord {
+        hash: hash(v),
+        tx_type: 2,
+        sender: [0x01; 20],
+        nonce: u64::from(v),
+        to: None,
+        chain_id: Some(1),
+        value_wei: None,
+        gas_limit: Some(21_000),
+        gas_price_wei: None,
+        max_fee_per_gas_wei: None,
+        max_priority_fee_per_gas_wei: None,
+        max_fee_per_blob_gas_wei: None,
+        calldata_len: None,
+        raw_tx: vec![v; calldata_len],
+        l2_fields: None,
+    }
+}
+
+fn lifecycle_record(v: u8) -> TxLifecycleRecord {
+    TxLifecycleRecord {
+        hash: hash(v),
+        status: "pending".to_owned(),
+        reason: None,
+        updated_unix_ms: i64::from(v),
+    }
+}
+
+fn table_bytes(storage: &InMemoryStorage, table: StorageTable) -> usize {
+    storage
+        .memory_usage()
+        .tables
+        .iter()
+        .find(|usage| usage.table == table)
+        .map(|usage| usage.bytes)
+        .unwrap()
+}
+
+fn temp_blob_path(label: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "mempulse-memory-budget-{label}-{}-{}.bin",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos())
+    ))
+}
+
+#[test]
+fn large_calldata_burst_evicts_oldest_rows_of_the_table_over_its_share() {
+    let budget = MemoryBudgetConfig {
+        max_bytes: 64 * 1024,
+        weights: TableWeights::default().with(StorageTable::TxFull, 8),
+    };
+    let mut storage = InMemoryStorage::with_config(StorageConfig {
+        memory_budget: Some(budget),
+        ..StorageConfig::default()
+    });
+    for v in 1..=4 {
+        storage.upsert_tx_lifecycle(lifecycle_record(v));
+    }
+    let lifecycle_bytes = table_bytes(&storage, StorageTable::TxLifecycle);
+
+    // Item counts stay far below the table capacity, but each row holds
+    // 8 KiB of calldata (counted twice: deque row and lookup copy).
+    for v in 1..=20 {
+        storage.upsert_tx_full(full_record(v, 8 * 1024));
+    }
+
+    let usage = storage.memory_usage();
+    assert!(usage.total_bytes <= budget.max_bytes, "{usage:?}");
+    assert_eq!(usage.budget_bytes, Some(budget.max_bytes));
+    let tx_full = usage
+        .tables
+        .iter()
+        .find(|usage| usage.table == StorageTable::TxFull)
+        .unwrap();
+    assert!(tx_full.budget_evictions_total > 0);
+    assert_eq!(tx_full.rows, storage.tx_full().len());
+    assert_eq!(
+        tx_full.budget_bytes,
+        Some(budget.share_bytes(StorageTable::TxFull))
+    );
+
+    // The oldest calldata rows went first, and the small lifecycle table
+    // within its share kept every row.
+    assert!(storage.tx_full_by_hash(&hash(1)).is_none());
+    assert!(storage.tx_full_by_hash(&hash(20)).is_some());
+    let retained = storage
+        .tx_full()
+        .iter()
+        .map(|row| row.nonce)
+        .collect::<Vec<_>>();
+    assert!(retained.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(*retained.last().unwrap(), 20);
+    assert_eq!(storage.tx_lifecycle().len(), 4);
+    assert_eq!(
+        table_bytes(&storage, StorageTable::TxLifecycle),
+        lifecycle_bytes
+    );
+}
+
+#[test]
+fn byte_accounting_follows_replaced_and_evicted_rows() {
+    let mut storage = InMemoryStorage::with_config(StorageConfig {
+        table_capacity: 2,
+        ..StorageConfig::default()
+    });
+    storage.upsert_tx_full(full_record(1, 1_000));
+    let one_row = table_bytes(&storage, StorageTable::TxFull);
+    assert!(one_row >= 2_000, "deque row and lookup copy");
+
+    // A newer row for the same hash replaces its lookup copy.
+    storage.upsert_tx_full(full_record(1, 1_000));
+    let two_rows = table_bytes(&storage, StorageTable::TxFull);
+    assert_eq!(two_rows, one_row + one_row / 2);
+
+    // Capacity eviction of both copies releases everything they held.
+    storage.upsert_tx_full(full_record(2, 0));
+    storage.upsert_tx_full(full_record(2, 0));
+    let small_rows = table_bytes(&storage, StorageTable::TxFull);
+    assert!(storage.tx_full_by_hash(&hash(1)).is_none());
+    assert!(small_rows < one_row);
+    assert_eq!(storage.memory_usage().budget_bytes, None);
+}
+
+#[test]
+fn offloaded_calldata_reads_back_and_checkpoints_inline() {
+    let path = temp_blob_path("offload");
+    let blobs = Arc::new(CalldataBlobStore::create(&path).unwrap());
+    let mut storage = InMemoryStorage::with_config(StorageConfig {
+        table_capacity: 2,
+        ..StorageConfig::default()
+    })
+    .with_calldata_blobs(blobs.clone());
+
+    storage.upsert_tx_full(full_record(1, 4 * 1024));
+    storage.upsert_tx_full(full_record(2, 4 * 1024));
+    assert!(table_bytes(&storage, StorageTable::TxFull) < 4 * 1024);
+    assert!(storage.tx_full_by_hash(&hash(1)).unwrap().raw_tx.is_empty());
+    assert_eq!(storage.tx_calldata(&hash(1)), Some(vec![1; 4 * 1024]));
+    assert_eq!(storage.tx_calldata_len(&hash(2)), Some(4 * 1024));
+    let blob_stats = storage.memory_usage().calldata_blobs.unwrap();
+    assert_eq!(blob_stats.blobs, 2);
+
+    let mut restored = InMemoryStorage::new();
+    restored.restore_checkpoint(storage.checkpoint(WalHead::default(), 0));
+    assert_eq!(
+        restored.tx_full_by_hash(&hash(2)).unwrap().raw_tx,
+        vec![2; 4 * 1024]
+    );
+
+    // Evicting the last row of a hash releases its blob.
+    storage.upsert_tx_full(full_record(3, 16));
+    assert_eq!(storage.tx_calldata(&hash(1)), None);
+    assert_eq!(blobs.stats().blobs, 2);
+    assert_eq!(storage.tx_calldata(&hash(3)), Some(vec![3; 16]));
+
+    let _ = std::fs::remove_file(path);
+}
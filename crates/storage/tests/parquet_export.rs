@@ -1,17 +1,159 @@
-use replay::ReplayFrame;
+use common::{L2TxFields, SourceId};
+use event_log::{
+    CandidateQueued, ChainCheckpoint, DropReason, EntryPointVersion, EventEnvelope, EventPayload,
+    HashLink, SimCompleted, SimFailCategory, SimulationStatus, TxConfirmed, TxDecoded, TxDropped,
+    TxSeen, UserOpSeen,
+};
+use replay::{ReplayFrame, ReplayQueueState, ReplaySenderQueue, ReplaySenderQueueEntry};
 use std::time::{SystemTime, UNIX_EPOCH};
-use storage::{ArrowParquetExporter, ParquetExporter};
+use storage::{
+    ArrowParquetExporter, OpportunityRecord, ParquetCompression, ParquetExportOptions,
+    ParquetExporter, ParquetTableWriter, TxFeaturesRecord, TxFullRecord,
+    UnsupportedParquetExporter, read_parquet_table,
+};
 
 fn hash(v: u8) -> [u8; 32] {
     [v; 32]
 }
 
-fn temp_path() -> std::path::PathBuf {
+fn temp_path(suffix: &str) -> std::path::PathBuf {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_nanos();
-    std::env::temp_dir().join(format!("prototype03-replay-{now}.parquet"))
+    std::env::temp_dir().join(format!("prototype03-{suffix}-{now}.parquet"))
+}
+
+fn envelope(seq_id: u64, payload: EventPayload) -> EventEnvelope {
+    EventEnvelope {
+        seq_id,
+        ingest_ts_unix_ms: 1_700_000_000_000 + seq_id as i64,
+        ingest_ts_mono_ns: seq_id * 10,
+        source_id: SourceId::new("parquet-test"),
+        payload,
+        chain_id: Some(1),
+        chain_seq_id: Some(seq_id),
+        hash_link: None,
+    }
+}
+
+fn sample_events() -> Vec<EventEnvelope> {
+    let mut seen = envelope(
+        1,
+        EventPayload::TxSeen(TxSeen {
+            hash: hash(1),
+            peer_id: "peer-a".to_owned(),
+            seen_at_unix_ms: 1_700_000_000_000,
+            seen_at_mono_ns: 7,
+        }),
+    );
+    seen.hash_link = Some(HashLink {
+        prev_digest: hash(0xa0),
+        digest: hash(0xa1),
+    });
+    seen.chain_id = None;
+    seen.chain_seq_id = None;
+    vec![
+        seen,
+        envelope(
+            2,
+            EventPayload::TxDecoded(TxDecoded {
+                hash: hash(1),
+                tx_type: 2,
+                sender: [3; 20],
+                nonce: 4,
+                chain_id: Some(1),
+                to: Some([5; 20]),
+                value_wei: Some(99_999_999_999_999_999_999_999_999_999_999_999_999),
+                gas_limit: Some(21_000),
+                gas_price_wei: None,
+                max_fee_per_gas_wei: Some(30_000_000_000),
+                max_priority_fee_per_gas_wei: Some(1_000_000_000),
+                max_fee_per_blob_gas_wei: None,
+                calldata_len: Some(68),
+            }),
+        ),
+        envelope(
+            3,
+            EventPayload::CandidateQueued(CandidateQueued {
+                candidate_id: "cand-1".to_owned(),
+                tx_hash: hash(1),
+                member_tx_hashes: vec![hash(1), hash(2)],
+                chain_id: Some(1),
+                strategy: "sandwich".to_owned(),
+                score: 7_500,
+                protocol: "uniswap-v2".to_owned(),
+                category: "swap".to_owned(),
+                feature_engine_version: "fe-1".to_owned(),
+                scorer_version: "sc-1".to_owned(),
+                strategy_version: "st-1".to_owned(),
+                reasons: vec!["large_swap".to_owned(), "slippage".to_owned()],
+                detected_unix_ms: 1_700_000_000_003,
+            }),
+        ),
+        envelope(
+            4,
+            EventPayload::SimCompleted(SimCompleted {
+                hash: hash(1),
+                sim_id: "sim-1".to_owned(),
+                status: SimulationStatus::from("failed"),
+                feature_engine_version: "fe-1".to_owned(),
+                scorer_version: "sc-1".to_owned(),
+                strategy_version: "st-1".to_owned(),
+                fail_category: Some(SimFailCategory::from("revert")),
+                latency_ms: Some(12),
+                tx_count: Some(2),
+                l1_data_fee_wei: None,
+                gas_cost_wei: Some(420_000),
+            }),
+        ),
+        envelope(
+            5,
+            EventPayload::TxDropped(TxDropped {
+                hash: hash(2),
+                reason: DropReason::from("replaced"),
+                detail: Some("higher fee".to_owned()),
+            }),
+        ),
+        envelope(
+            6,
+            EventPayload::TxConfirmedFinal(TxConfirmed {
+                hash: hash(1),
+                block_number: 19_000_000,
+                block_hash: hash(0xbb),
+            }),
+        ),
+        envelope(
+            7,
+            EventPayload::UserOpSeen(UserOpSeen {
+                user_op_hash: hash(9),
+                entry_point: [0x5f; 20],
+                entry_point_version: EntryPointVersion::V07,
+                sender: [6; 20],
+                nonce: hash(0),
+                chain_id: Some(8453),
+                factory: None,
+                paymaster: Some([7; 20]),
+                call_gas_limit: 100_000,
+                verification_gas_limit: 50_000,
+                pre_verification_gas: 21_000,
+                max_fee_per_gas_wei: 2_000_000_000,
+                max_priority_fee_per_gas_wei: 1_000_000,
+                call_data_len: 3,
+                bundle_tx_hash: None,
+                call_data: vec![0xde, 0xad, 0x01],
+            }),
+        ),
+        envelope(
+            8,
+            EventPayload::ChainCheckpoint(ChainCheckpoint {
+                covered_seq_id: 7,
+                covered_digest: hash(0xcc),
+                key_id: "key-1".to_owned(),
+                signature: vec![1, 2, 3, 4],
+            }),
+        ),
+    ]
 }
 
 #[test]
@@ -27,18 +169,210 @@ fn parquet_export_writes_replay_frames_that_can_be_read_back() {
             seq_hi: 2,
             timestamp_unix_ms: 1_700_000_000_002,
             pending: vec![hash(2), hash(3)],
-            sender_queues: Vec::new(),
+            sender_queues: vec![
+                ReplaySenderQueue {
+                    sender: [1; 20],
+                    queued: vec![
+                        ReplaySenderQueueEntry {
+                            hash: hash(2),
+                            nonce: 0,
+                            state: ReplayQueueState::Ready,
+                        },
+                        ReplaySenderQueueEntry {
+                            hash: hash(4),
+                            nonce: 2,
+                            state: ReplayQueueState::Blocked { expected_nonce: 1 },
+                        },
+                    ],
+                },
+                ReplaySenderQueue {
+                    sender: [2; 20],
+                    queued: Vec::new(),
+                },
+            ],
         },
     ];
-    let path = temp_path();
-    let exporter = ArrowParquetExporter;
+    let path = temp_path("replay");
+    let exporter = ArrowParquetExporter::default();
     exporter
         .export_replay_frames_parquet(&frames, path.to_str().expect("utf8 path"))
         .expect("export replay frames");
 
     let bytes = std::fs::read(&path).expect("read export file");
-    let decoded: Vec<ReplayFrame> = serde_json::from_slice(&bytes).expect("decode exported file");
+    assert_eq!(&bytes[..4], b"PAR1");
+    let decoded: Vec<ReplayFrame> = read_parquet_table(&path).expect("read replay frames");
     assert_eq!(decoded, frames);
 
     let _ = std::fs::remove_file(path);
 }
+
+#[test]
+fn parquet_export_round_trips_flattened_events() {
+    let events = sample_events();
+    let path = temp_path("events");
+    ArrowParquetExporter::default()
+        .export_events_parquet(&events, path.to_str().expect("utf8 path"))
+        .expect("export events");
+
+    let decoded: Vec<EventEnvelope> = read_parquet_table(&path).expect("read events");
+    assert_eq!(decoded, events);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn parquet_export_round_trips_projection_records() {
+    let tx_full = vec![
+        TxFullRecord {
+            hash: hash(1),
+            tx_type: 2,
+            sender: [3; 20],
+            nonce: 4,
+            to: Some([5; 20]),
+            chain_id: Some(1),
+            value_wei: Some(1_000),
+            gas_limit: Some(21_000),
+            gas_price_wei: None,
+            max_fee_per_gas_wei: Some(30_000_000_000),
+            max_priority_fee_per_gas_wei: Some(1_000_000_000),
+            max_fee_per_blob_gas_wei: None,
+            calldata_len: Some(4),
+            raw_tx: vec![0x02, 0xf8, 0x6b],
+            l2_fields: None,
+        },
+        TxFullRecord {
+            hash: hash(2),
+            tx_type: 0x7e,
+            sender: [6; 20],
+            nonce: 0,
+            to: None,
+            chain_id: Some(10),
+            value_wei: None,
+            gas_limit: None,
+            gas_price_wei: None,
+            max_fee_per_gas_wei: None,
+            max_priority_fee_per_gas_wei: None,
+            max_fee_per_blob_gas_wei: None,
+            calldata_len: None,
+            raw_tx: Vec::new(),
+            l2_fields: Some(Box::new(L2TxFields {
+                source_hash: Some(hash(0x11)),
+                mint_wei: Some(5),
+                is_system_tx: Some(false),
+                l1_base_fee_scalar: Some(1_368),
+                ..L2TxFields::default()
+            })),
+        },
+    ];
+    let features = vec![TxFeaturesRecord {
+        hash: hash(1),
+        chain_id: Some(1),
+        protocol: "uniswap-v3".to_owned(),
+        category: "swap".to_owned(),
+        mev_score: 80,
+        urgency_score: 20,
+        method_selector: Some([0x41, 0x4b, 0xf3, 0x89]),
+        feature_engine_version: "fe-1".to_owned(),
+    }];
+    let opportunities = vec![OpportunityRecord {
+        tx_hash: hash(1),
+        chain_id: None,
+        strategy: "backrun".to_owned(),
+        score: 1_200,
+        protocol: "uniswap-v3".to_owned(),
+        category: "swap".to_owned(),
+        feature_engine_version: "fe-1".to_owned(),
+        scorer_version: "sc-1".to_owned(),
+        strategy_version: "st-1".to_owned(),
+        reasons: Vec::new(),
+        detected_unix_ms: 1_700_000_000_000,
+    }];
+    let exporter = ArrowParquetExporter::default();
+    let (full_path, features_path, opps_path) = (
+        temp_path("tx-full"),
+        temp_path("tx-features"),
+        temp_path("opps"),
+    );
+    exporter
+        .export_tx_full_parquet(&tx_full, full_path.to_str().expect("utf8 path"))
+        .expect("export tx full");
+    exporter
+        .export_tx_features_parquet(&features, features_path.to_str().expect("utf8 path"))
+        .expect("export tx features");
+    exporter
+        .export_opportunities_parquet(&opportunities, opps_path.to_str().expect("utf8 path"))
+        .expect("export opportunities");
+
+    assert_eq!(
+        read_parquet_table::<TxFullRecord>(&full_path).expect("read tx full"),
+        tx_full
+    );
+    assert_eq!(
+        read_parquet_table::<TxFeaturesRecord>(&features_path).expect("read tx features"),
+        features
+    );
+    assert_eq!(
+        read_parquet_table::<OpportunityRecord>(&opps_path).expect("read opportunities"),
+        opportunities
+    );
+    let err = read_parquet_table::<TxFeaturesRecord>(&full_path)
+        .expect_err("schema mismatch must be rejected");
+    assert!(err.to_string().contains("Parquet export failed:"));
+
+    for path in [full_path, features_path, opps_path] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[test]
+fn streaming_writer_splits_row_groups_and_honours_compression() {
+    let template = sample_events();
+    let events = (0..250_u64)
+        .map(|seq_id| {
+            let mut event = template[(seq_id % template.len() as u64) as usize].clone();
+            event.seq_id = seq_id;
+            event
+        })
+        .collect::<Vec<_>>();
+    let path = temp_path("row-groups");
+    let options = ParquetExportOptions {
+        row_group_size: 100,
+        compression: ParquetCompression::Zstd(3),
+    };
+    let mut writer = ParquetTableWriter::<EventEnvelope>::create(&path, &options)
+        .expect("create streaming writer");
+    for event in &events {
+        writer.write(event).expect("write event");
+    }
+    let summary = writer.finish().expect("finish export");
+    assert_eq!(summary.rows, 250);
+    assert_eq!(summary.row_groups, 3);
+
+    let decoded: Vec<EventEnvelope> = read_parquet_table(&path).expect("read events");
+    assert_eq!(decoded, events);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn wei_amounts_beyond_decimal_range_are_rejected() {
+    let mut events = sample_events();
+    if let EventPayload::TxDecoded(decoded) = &mut events[1].payload {
+        decoded.value_wei = Some(u128::MAX);
+    }
+    let path = temp_path("overflow");
+    let err = ArrowParquetExporter::default()
+        .export_events_parquet(&events, path.to_str().expect("utf8 path"))
+        .expect_err("u128::MAX does not fit Decimal128(38, 0)");
+    assert!(err.to_string().contains("tx_decoded_value_wei"));
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn unsupported_exporter_rejects_every_table() {
+    let err = UnsupportedParquetExporter
+        .export_events_parquet(&sample_events(), "/tmp/unsupported-events.parquet")
+        .expect_err("unsupported exporter must error");
+    assert!(err.to_string().contains("not wired"));
+}
@@ -0,0 +1,119 @@
+use common::SourceId;
+use event_log::{DropReason, EventEnvelope, EventPayload, TxDropped, TxSeen};
+use storage::{EventStore, InMemoryStorage, ObserverGroup, WalHead};
+
+const BASE_UNIX_MS: i64 = 1_700_000_000_000;
+
+fn seen_event(seq_id: u64, source: &str, hash_seed: u8, offset_ms: i64) -> EventEnvelope {
+    EventEnvelope {
+        seq_id,
+        ingest_ts_unix_ms: BASE_UNIX_MS + offset_ms,
+        ingest_ts_mono_ns: seq_id * 1_000_000,
+        source_id: SourceId::new(source),
+        payload: EventPayload::TxSeen(TxSeen {
+            hash: [hash_seed; 32],
+            peer_id: "rpc-ws".to_owned(),
+            seen_at_unix_ms: BASE_UNIX_MS + offset_ms,
+            seen_at_mono_ns: seq_id * 1_000_000,
+        }),
+        chain_id: Some(1),
+        chain_seq_id: Some(seq_id),
+        hash_link: None,
+    }
+}
+
+fn dropped_event(seq_id: u64, source: &str, hash_seed: u8, reason: DropReason) -> EventEnvelope {
+    EventEnvelope {
+        seq_id,
+        ingest_ts_unix_ms: BASE_UNIX_MS + seq_id as i64,
+        ingest_ts_mono_ns: seq_id * 1_000_000,
+        source_id: SourceId::new(source),
+        payload: EventPayload::TxDropped(TxDropped::new([hash_seed; 32], reason)),
+        chain_id: Some(1),
+        chain_seq_id: Some(seq_id),
+        hash_link: None,
+    }
+}
+
+fn two_source_storage() -> InMemoryStorage {
+    let mut storage = InMemoryStorage::default();
+    // `alchemy` is first for hashes 1-3 and `infura` trails it; `infura`
+    // alone reports hash 4.
+    storage.append_event(seen_event(1, "alchemy", 1, 0));
+    storage.append_event(seen_event(2, "infura", 1, 30));
+    storage.append_event(seen_event(3, "alchemy", 2, 100));
+    storage.append_event(seen_event(4, "infura", 2, 110));
+    storage.append_event(seen_event(5, "alchemy", 3, 200));
+    storage.append_event(seen_event(6, "infura", 3, 250));
+    storage.append_event(seen_event(7, "infura", 4, 300));
+    storage.append_event(dropped_event(8, "infura", 4, DropReason::QueueFull));
+    storage
+}
+
+#[test]
+fn rpc_sources_are_ranked_by_first_seen_share_and_lag() {
+    let storage = two_source_storage();
+
+    let stats = storage.peer_observation_stats(ObserverGroup::Source, 60_000);
+    let observers = stats
+        .iter()
+        .map(|row| row.observer.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(observers, vec!["alchemy", "infura"]);
+    let (alchemy, infura) = (&stats[0], &stats[1]);
+    assert_eq!(alchemy.first_seen_share_bps, 7_500);
+    assert_eq!(alchemy.median_lag_ms, None);
+    assert_eq!(alchemy.unique_hashes, 0);
+    assert_eq!(alchemy.drop_ratio_bps, 0);
+    assert_eq!(infura.first_seen_share_bps, 2_500);
+    assert_eq!(infura.median_lag_ms, Some(30));
+    assert_eq!(infura.unique_hashes, 1);
+    assert_eq!((infura.dropped, infura.drop_ratio_bps), (1, 2_500));
+
+    let edges = storage.observed_propagation_edges(ObserverGroup::Source, 60_000);
+    assert_eq!(edges.len(), 1);
+    assert_eq!(
+        (edges[0].source.as_str(), edges[0].destination.as_str()),
+        ("alchemy", "infura")
+    );
+    assert_eq!(
+        (edges[0].samples, edges[0].p50_lag_ms, edges[0].p99_lag_ms),
+        (3, 30, 50)
+    );
+
+    // Every source labels its peer `rpc-ws`, so by peer nothing propagates.
+    let by_peer = storage.peer_observation_stats(ObserverGroup::Peer, 60_000);
+    assert_eq!(by_peer.len(), 1);
+    assert_eq!(by_peer[0].announcements, 7);
+    assert!(
+        storage
+            .observed_propagation_edges(ObserverGroup::Peer, 60_000)
+            .is_empty()
+    );
+}
+
+#[test]
+fn duplicate_drops_are_not_counted_as_drops() {
+    let mut storage = two_source_storage();
+    storage.append_event(dropped_event(9, "alchemy", 1, DropReason::Duplicate));
+
+    let stats = storage.peer_observation_stats(ObserverGroup::Source, 60_000);
+    assert_eq!(stats[0].dropped, 0);
+    assert_eq!(
+        stats[0].announcements, 4,
+        "duplicate counts as an announcement"
+    );
+}
+
+#[test]
+fn restored_checkpoint_rebuilds_observations_from_events() {
+    let storage = two_source_storage();
+
+    let mut restored = InMemoryStorage::new();
+    restored.restore_checkpoint(storage.checkpoint(WalHead::default(), 0));
+
+    assert_eq!(
+        restored.peer_observation_stats(ObserverGroup::Source, 60_000),
+        storage.peer_observation_stats(ObserverGroup::Source, 60_000)
+    );
+}
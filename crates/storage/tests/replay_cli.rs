@@ -0,0 +1,851 @@
+use common::{Address, SourceId, TxHash};
+use event_log::{
+    CURRENT_EVENT_SCHEMA_VERSION, CheckpointSigner, DropReason, EventEncoding, EventEnvelope,
+    EventHashChain, EventPayload, GlobalSequencer, HashChainConfig, SCHEMA_VERSION_KEY, TxDecoded,
+    TxDropped, TxReorged, decode_event_stream, encode_event,
+};
+use replay::{CheckpointHash, ReplayFrame, ReplayMode, replay_frames};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::time::{SystemTime, UNIX_EPOCH};
+use storage::{StorageWal, TxFullRecord, WalDurability, read_parquet_table};
+
+fn hash(v: u8) -> TxHash {
+    [v; 32]
+}
+
+fn address(v: u8) -> Address {
+    [v; 20]
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    std::env::temp_dir().join(format!("prototype03-{name}-{nanos}.json"))
+}
+
+#[test]
+fn replay_cli_writes_output_file() {
+    let input_path = temp_file("in");
+    let output_path = temp_file("out");
+
+    let events = vec![EventEnvelope {
+        seq_id: 1,
+        ingest_ts_unix_ms: 1_700_000_000_000,
+        ingest_ts_mono_ns: 10,
+        source_id: SourceId::new("test"),
+        payload: EventPayload::TxDecoded(TxDecoded {
+            hash: hash(1),
+            tx_type: 2,
+            sender: address(9),
+            nonce: 1,
+            chain_id: Some(1),
+            to: None,
+            value_wei: None,
+            gas_limit: None,
+            gas_price_wei: None,
+            max_fee_per_gas_wei: None,
+            max_priority_fee_per_gas_wei: None,
+            max_fee_per_blob_gas_wei: None,
+            calldata_len: None,
+        }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
+    }];
+    fs::write(
+        &input_path,
+        serde_json::to_vec(&events).expect("json events"),
+    )
+    .expect("write input");
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_storage-replay-cli"))
+        .args([
+            "--input",
+            input_path.to_str().expect("input path"),
+            "--out",
+            output_path.to_str().expect("output path"),
+            "--mode",
+            "deterministic",
+        ])
+        .status()
+        .expect("run replay-cli");
+
+    assert!(status.success());
+    let output = fs::read_to_string(&output_path).expect("read output");
+    assert!(output.contains("seq_hi"));
+
+    let _ = fs::remove_file(input_path);
+    let _ = fs::remove_file(output_path);
+}
+
+#[test]
+fn replay_cli_reads_binary_event_frames() {
+    let input_path = temp_file("in-binary");
+    let output_path = temp_file("out-binary");
+
+    let events = (1..=2_u8)
+        .map(|seed| EventEnvelope {
+            seq_id: u64::from(seed),
+            ingest_ts_unix_ms: 1_700_000_000_000 + i64::from(seed),
+            ingest_ts_mono_ns: u64::from(seed) * 10,
+            source_id: SourceId::new("test"),
+            payload: EventPayload::TxDecoded(TxDecoded {
+                hash: hash(seed),
+                tx_type: 2,
+                sender: address(9),
+                nonce: u64::from(seed),
+                chain_id: Some(1),
+                to: None,
+                value_wei: None,
+                gas_limit: None,
+                gas_price_wei: None,
+                max_fee_per_gas_wei: None,
+                max_priority_fee_per_gas_wei: None,
+                max_fee_per_blob_gas_wei: None,
+                calldata_len: None,
+            }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        })
+        .collect::<Vec<_>>();
+    fs::write(
+        &input_path,
+        events.iter().flat_map(encode_event).collect::<Vec<_>>(),
+    )
+    .expect("write input");
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_storage-replay-cli"))
+        .args([
+            "--input",
+            input_path.to_str().expect("input path"),
+            "--out",
+            output_path.to_str().expect("output path"),
+        ])
+        .status()
+        .expect("run replay-cli");
+
+    assert!(status.success());
+    let frames: serde_json::Value =
+        serde_json::from_slice(&fs::read(&output_path).expect("read output")).expect("frames");
+    assert_eq!(frames.as_array().map(Vec::len), Some(2));
+
+    let _ = fs::remove_file(input_path);
+    let _ = fs::remove_file(output_path);
+}
+
+#[test]
+fn replay_cli_chain_id_replays_only_that_chain() {
+    let input_path = temp_file("in-chain");
+    let output_path = temp_file("out-chain");
+
+    let events = [(1_u8, 1_u64), (2, 8453), (3, 1)]
+        .into_iter()
+        .scan(GlobalSequencer::default(), |sequencer, (seed, chain_id)| {
+            Some(sequencer.assign(EventEnvelope {
+                seq_id: 0,
+                ingest_ts_unix_ms: 1_700_000_000_000 + i64::from(seed),
+                ingest_ts_mono_ns: u64::from(seed) * 10,
+                source_id: SourceId::new("test"),
+                payload: EventPayload::TxDecoded(TxDecoded {
+                    hash: hash(seed),
+                    tx_type: 2,
+                    sender: address(seed),
+                    nonce: 0,
+                    chain_id: Some(chain_id),
+                    to: None,
+                    value_wei: None,
+                    gas_limit: None,
+                    gas_price_wei: None,
+                    max_fee_per_gas_wei: None,
+                    max_priority_fee_per_gas_wei: None,
+                    max_fee_per_blob_gas_wei: None,
+                    calldata_len: None,
+                }),
+                chain_id: Some(chain_id),
+                chain_seq_id: None,
+                hash_link: None,
+            }))
+        })
+        .collect::<Vec<_>>();
+    fs::write(
+        &input_path,
+        serde_json::to_vec(&events).expect("json events"),
+    )
+    .expect("write input");
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_storage-replay-cli"))
+        .args([
+            "--input",
+            input_path.to_str().expect("input path"),
+            "--out",
+            output_path.to_str().expect("output path"),
+            "--chain-id",
+            "8453",
+        ])
+        .status()
+        .expect("run replay-cli");
+
+    assert!(status.success());
+    let frames: serde_json::Value =
+        serde_json::from_slice(&fs::read(&output_path).expect("read output")).expect("frames");
+    let frames = frames.as_array().expect("frame array");
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0]["seq_hi"], serde_json::json!(2));
+
+    let _ = fs::remove_file(input_path);
+    let _ = fs::remove_file(output_path);
+}
+
+#[test]
+fn replay_cli_rewrite_upcasts_legacy_log_to_current_schema() {
+    let input_path = temp_file("in-legacy");
+    let output_path = temp_file("out-rewrite");
+
+    // Schema v1 record: no schema_version and no member_tx_hashes.
+    let legacy = serde_json::json!({
+        "seq_id": 1,
+        "ingest_ts_unix_ms": 1_700_000_000_000_i64,
+        "ingest_ts_mono_ns": 10,
+        "source_id": "legacy",
+        "payload": {
+            "type": "SimDispatched",
+            "data": {
+                "candidate_id": "cand-1",
+                "tx_hash": hash(5),
+                "block_number": 19_000_000
+            }
+        }
+    });
+    fs::write(&input_path, format!("{legacy}\n")).expect("write input");
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_storage-replay-cli"))
+        .args([
+            "rewrite",
+            "--input",
+            input_path.to_str().expect("input path"),
+            "--out",
+            output_path.to_str().expect("output path"),
+        ])
+        .status()
+        .expect("run replay-cli rewrite");
+
+    assert!(status.success());
+    let output = fs::read(&output_path).expect("read output");
+    let record: serde_json::Value = serde_json::from_slice(&output).expect("rewritten json");
+    assert_eq!(
+        record[SCHEMA_VERSION_KEY],
+        serde_json::Value::from(CURRENT_EVENT_SCHEMA_VERSION)
+    );
+    let events = decode_event_stream(&output).expect("decode rewritten log");
+    let EventPayload::SimDispatched(dispatched) = &events[0].payload else {
+        panic!("expected SimDispatched");
+    };
+    assert_eq!(dispatched.member_tx_hashes, vec![hash(5)]);
+
+    let _ = fs::remove_file(input_path);
+    let _ = fs::remove_file(output_path);
+}
+
+#[test]
+fn replay_cli_verify_chain_reports_first_broken_link() {
+    let intact_path = temp_file("chain-intact");
+    let tampered_path = temp_file("chain-tampered");
+    let key_path = temp_file("chain-public-key");
+    let signer = CheckpointSigner::from_seed("k1", &[3; 32]).expect("signing key");
+
+    let mut sequencer =
+        GlobalSequencer::default().with_hash_chain(EventHashChain::new(HashChainConfig {
+            checkpoint_interval: 2,
+            signer: Some(signer.clone()),
+        }));
+    let mut events = Vec::new();
+    for seed in 1..=3_u8 {
+        events.push(sequencer.assign(EventEnvelope {
+            seq_id: 0,
+            ingest_ts_unix_ms: 1_700_000_000_000 + i64::from(seed),
+            ingest_ts_mono_ns: u64::from(seed) * 10,
+            source_id: SourceId::new("test"),
+            payload: EventPayload::TxReorged(TxReorged {
+                hash: hash(seed),
+                old_block_hash: hash(0xa0),
+                new_block_hash: hash(0xb0),
+            }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        }));
+        events.extend(sequencer.next_checkpoint(0, 0, SourceId::new("hash-chain")));
+    }
+    fs::write(
+        &intact_path,
+        events.iter().flat_map(encode_event).collect::<Vec<_>>(),
+    )
+    .expect("write intact log");
+    if let EventPayload::TxReorged(reorged) = &mut events[1].payload {
+        reorged.new_block_hash = hash(0xff);
+    }
+    fs::write(
+        &tampered_path,
+        events.iter().flat_map(encode_event).collect::<Vec<_>>(),
+    )
+    .expect("write tampered log");
+    fs::write(&key_path, hex::encode(signer.verifier().public_key())).expect("write public key");
+
+    let run = |path: &PathBuf| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_storage-replay-cli"))
+            .args([
+                "verify-chain",
+                "--input",
+                path.to_str().expect("input path"),
+                "--key-id",
+                "k1",
+                "--public-key-file",
+                key_path.to_str().expect("key path"),
+            ])
+            .output()
+            .expect("run replay-cli verify-chain")
+    };
+
+    let intact = run(&intact_path);
+    assert!(intact.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&intact.stdout).expect("report");
+    assert_eq!(report["checkpoints_verified"], 1);
+
+    let tampered = run(&tampered_path);
+    assert_eq!(tampered.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&tampered.stderr);
+    assert!(stderr.contains("event #1 (seq_id 2)"), "{stderr}");
+
+    let _ = fs::remove_file(intact_path);
+    let _ = fs::remove_file(tampered_path);
+    let _ = fs::remove_file(key_path);
+}
+
+#[test]
+fn replay_cli_verify_chain_rejects_rehashed_and_foreign_signed_chains() {
+    let stripped_path = temp_file("chain-stripped");
+    let foreign_path = temp_file("chain-foreign");
+    let key_path = temp_file("chain-pinned-key");
+    let signer = CheckpointSigner::from_seed("k1", &[3; 32]).expect("signing key");
+    let foreign = CheckpointSigner::from_seed("k2", &[4; 32]).expect("foreign key");
+    fs::write(&key_path, hex::encode(signer.verifier().public_key())).expect("write public key");
+
+    let reorged = |seed: u8| EventEnvelope {
+        seq_id: 0,
+        ingest_ts_unix_ms: 1_700_000_000_000 + i64::from(seed),
+        ingest_ts_mono_ns: u64::from(seed) * 10,
+        source_id: SourceId::new("test"),
+        payload: EventPayload::TxReorged(TxReorged {
+            hash: hash(seed),
+            old_block_hash: hash(0xa0),
+            new_block_hash: hash(0xb0),
+        }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
+    };
+    let write_chain = |path: &PathBuf, config: HashChainConfig| {
+        let mut sequencer = GlobalSequencer::default().with_hash_chain(EventHashChain::new(config));
+        let mut events = Vec::new();
+        for seed in 1..=4_u8 {
+            events.push(sequencer.assign(reorged(seed)));
+            events.extend(sequencer.next_checkpoint(0, 0, SourceId::new("hash-chain")));
+        }
+        fs::write(
+            path,
+            events.iter().flat_map(encode_event).collect::<Vec<_>>(),
+        )
+        .expect("write chain");
+    };
+    // A forger re-hashes from genesis and drops the checkpoints they cannot sign.
+    write_chain(&stripped_path, HashChainConfig::default());
+    write_chain(
+        &foreign_path,
+        HashChainConfig {
+            checkpoint_interval: 2,
+            signer: Some(foreign),
+        },
+    );
+
+    let run = |path: &PathBuf| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_storage-replay-cli"))
+            .args([
+                "verify-chain",
+                "--input",
+                path.to_str().expect("input path"),
+                "--key-id",
+                "k1",
+                "--public-key-file",
+                key_path.to_str().expect("key path"),
+                "--checkpoint-interval",
+                "2",
+            ])
+            .output()
+            .expect("run replay-cli verify-chain")
+    };
+
+    let stripped = run(&stripped_path);
+    assert_eq!(stripped.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&stripped.stderr);
+    assert!(stderr.contains("without a verified checkpoint"), "{stderr}");
+
+    let foreign = run(&foreign_path);
+    assert_eq!(foreign.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&foreign.stderr);
+    assert!(stderr.contains("unexpected key 'k2'"), "{stderr}");
+
+    let _ = fs::remove_file(stripped_path);
+    let _ = fs::remove_file(foreign_path);
+    let _ = fs::remove_file(key_path);
+}
+
+#[test]
+fn replay_cli_filter_replays_only_matching_events() {
+    let input_path = temp_file("in-filter");
+    let output_path = temp_file("out-filter");
+
+    let events = (1_u8..=3)
+        .map(|seed| EventEnvelope {
+            seq_id: u64::from(seed),
+            ingest_ts_unix_ms: 1_700_000_000_000 + i64::from(seed),
+            ingest_ts_mono_ns: u64::from(seed) * 10,
+            source_id: SourceId::new("test"),
+            payload: EventPayload::TxDecoded(TxDecoded {
+                hash: hash(seed),
+                tx_type: 2,
+                sender: address(seed),
+                nonce: u64::from(seed),
+                chain_id: Some(1),
+                to: None,
+                value_wei: None,
+                gas_limit: None,
+                gas_price_wei: None,
+                max_fee_per_gas_wei: None,
+                max_priority_fee_per_gas_wei: None,
+                max_fee_per_blob_gas_wei: None,
+                calldata_len: None,
+            }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        })
+        .collect::<Vec<_>>();
+    fs::write(
+        &input_path,
+        serde_json::to_vec(&events).expect("json events"),
+    )
+    .expect("write input");
+
+    let run = |filter: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_storage-replay-cli"))
+            .args([
+                "--input",
+                input_path.to_str().expect("input path"),
+                "--out",
+                output_path.to_str().expect("output path"),
+                "--filter",
+                filter,
+            ])
+            .output()
+            .expect("run replay-cli")
+    };
+
+    assert!(run("type = TxDecoded and nonce >= 2").status.success());
+    let frames: serde_json::Value =
+        serde_json::from_slice(&fs::read(&output_path).expect("read output")).expect("frames");
+    let seq_his = frames
+        .as_array()
+        .expect("frame array")
+        .iter()
+        .map(|frame| frame["seq_hi"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(seq_his, vec![serde_json::json!(2), serde_json::json!(3)]);
+
+    let rejected = run("nonce >= two");
+    assert!(!rejected.status.success());
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("invalid --filter value"));
+
+    let _ = fs::remove_file(input_path);
+    let _ = fs::remove_file(output_path);
+}
+
+#[test]
+fn replay_cli_streams_unordered_json_lines_and_fails_on_a_torn_tail() {
+    let input_path = temp_file("in-ndjson");
+    let output_path = temp_file("out-ndjson");
+
+    let events = (1..=6_u8)
+        .map(|seed| EventEnvelope {
+            seq_id: u64::from(seed),
+            ingest_ts_unix_ms: 1_700_000_000_000 + i64::from(seed),
+            ingest_ts_mono_ns: u64::from(seed) * 10,
+            source_id: SourceId::new("test"),
+            payload: EventPayload::TxDecoded(TxDecoded {
+                hash: hash(seed),
+                tx_type: 2,
+                sender: address(seed),
+                nonce: 0,
+                chain_id: Some(1),
+                to: None,
+                value_wei: None,
+                gas_limit: None,
+                gas_price_wei: None,
+                max_fee_per_gas_wei: None,
+                max_priority_fee_per_gas_wei: None,
+                max_fee_per_blob_gas_wei: None,
+                calldata_len: None,
+            }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
+        })
+        .collect::<Vec<_>>();
+    let mut shuffled = events.clone();
+    shuffled.swap(0, 1);
+    shuffled.swap(3, 4);
+    let mut input = Vec::new();
+    for event in &shuffled {
+        EventEncoding::Json
+            .encode_into(event, &mut input)
+            .expect("encode event");
+    }
+    fs::write(&input_path, &input).expect("write input");
+
+    let run = |extra: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_storage-replay-cli"))
+            .args([
+                "--input",
+                input_path.to_str().expect("input path"),
+                "--out",
+                output_path.to_str().expect("output path"),
+                "--stride",
+                "4",
+            ])
+            .args(extra)
+            .status()
+            .expect("run replay-cli")
+    };
+
+    assert!(run(&["--reorder-window", "2"]).success());
+    let frames: Vec<ReplayFrame> =
+        serde_json::from_slice(&fs::read(&output_path).expect("read output")).expect("frames");
+    assert_eq!(
+        frames,
+        replay_frames(&events, ReplayMode::DeterministicEventReplay, 4)
+    );
+
+    input.truncate(input.len() - 8);
+    fs::write(&input_path, &input).expect("write torn input");
+    assert!(!run(&[]).success());
+
+    let _ = fs::remove_file(input_path);
+    let _ = fs::remove_file(output_path);
+}
+
+fn decoded_event(seq_id: u64, seed: u8) -> EventEnvelope {
+    EventEnvelope {
+        seq_id,
+        ingest_ts_unix_ms: 1_700_000_000_000 + seq_id as i64,
+        ingest_ts_mono_ns: seq_id * 10,
+        source_id: SourceId::new("test"),
+        payload: EventPayload::TxDecoded(TxDecoded {
+            hash: hash(seed),
+            tx_type: 2,
+            sender: address(seed),
+            nonce: 0,
+            chain_id: Some(1),
+            to: None,
+            value_wei: None,
+            gas_limit: None,
+            gas_price_wei: None,
+            max_fee_per_gas_wei: None,
+            max_priority_fee_per_gas_wei: None,
+            max_fee_per_blob_gas_wei: None,
+            calldata_len: None,
+        }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
+    }
+}
+
+fn write_wal(dir: &Path, events: &[EventEnvelope]) {
+    let wal = StorageWal::with_segment_size(dir.join("events.wal"), 2 * 1024).expect("create wal");
+    let mut writer = wal.writer(WalDurability::None);
+    for event in events {
+        writer.append(event).expect("append event");
+    }
+    writer.commit().expect("commit wal");
+}
+
+fn replay_cli(args: &[&str]) -> Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_storage-replay-cli"))
+        .args(args)
+        .output()
+        .expect("run replay-cli")
+}
+
+fn path_arg(path: &Path) -> &str {
+    path.to_str().expect("utf-8 path")
+}
+
+#[test]
+fn replay_cli_subcommands_read_wal_dirs_and_exit_by_check_outcome() {
+    let dir = temp_file("wal-dir").with_extension("");
+    let tampered_dir = temp_file("wal-dir-tampered").with_extension("");
+    let checkpoints_path = temp_file("checkpoints");
+    let checkpoints_csv_path = temp_file("checkpoints-csv");
+    let frames_path = temp_file("frames-parquet");
+    let export_path = temp_file("export-parquet");
+
+    let mut events = (1..=40_u64)
+        .map(|seq_id| decoded_event(seq_id, seq_id as u8))
+        .collect::<Vec<_>>();
+    events[29].payload = EventPayload::TxDropped(TxDropped::new(hash(3), DropReason::Evicted));
+    write_wal(&dir, &events);
+    let mut tampered = events.clone();
+    tampered[34] = decoded_event(35, 0xee);
+    write_wal(&tampered_dir, &tampered);
+    let wal = path_arg(&dir);
+
+    let frames = replay_cli(&["frames", "--wal", wal, "--stride", "8"]);
+    assert!(frames.status.success(), "{frames:?}");
+    let frames: Vec<ReplayFrame> = serde_json::from_slice(&frames.stdout).expect("frames");
+    assert_eq!(
+        frames,
+        replay_frames(&events, ReplayMode::DeterministicEventReplay, 8)
+    );
+
+    let checkpoints = replay_cli(&[
+        "checkpoints",
+        "--wal",
+        wal,
+        "--stride",
+        "10",
+        "--out",
+        path_arg(&checkpoints_path),
+    ]);
+    assert!(checkpoints.status.success(), "{checkpoints:?}");
+    let written: Vec<CheckpointHash> =
+        serde_json::from_slice(&fs::read(&checkpoints_path).expect("read checkpoints"))
+            .expect("checkpoints");
+    assert_eq!(
+        written.iter().map(|row| row.seq_id).collect::<Vec<_>>(),
+        vec![10, 20, 30, 40]
+    );
+    let checkpoints_csv = replay_cli(&[
+        "checkpoints",
+        "--wal",
+        wal,
+        "--seq",
+        "20,40",
+        "--format",
+        "csv",
+        "--out",
+        path_arg(&checkpoints_csv_path),
+    ]);
+    assert!(checkpoints_csv.status.success(), "{checkpoints_csv:?}");
+    let missing = replay_cli(&["checkpoints", "--wal", wal, "--seq", "20,99"]);
+    assert_eq!(missing.status.code(), Some(1));
+
+    for expected in [&checkpoints_path, &checkpoints_csv_path] {
+        let expected = path_arg(expected);
+        let verify = replay_cli(&["verify", "--wal", wal, "--expected", expected]);
+        assert!(verify.status.success(), "{verify:?}");
+        let verify = replay_cli(&[
+            "verify",
+            "--wal",
+            path_arg(&tampered_dir),
+            "--expected",
+            expected,
+        ]);
+        assert_eq!(verify.status.code(), Some(1));
+        let report: serde_json::Value = serde_json::from_slice(&verify.stdout).expect("report");
+        assert_eq!(report["mismatches"][0]["seq_id"], 40);
+    }
+
+    let diff = replay_cli(&["diff", "--wal", wal, "--from", "20", "--to", "30"]);
+    assert_eq!(diff.status.code(), Some(1));
+    let summary: serde_json::Value = serde_json::from_slice(&diff.stdout).expect("summary");
+    assert_eq!(summary["to_pending_count"], 28);
+    let unchanged = replay_cli(&["diff", "--wal", wal, "--from", "7", "--to", "7"]);
+    assert!(unchanged.status.success(), "{unchanged:?}");
+    assert_eq!(
+        replay_cli(&["diff", "--wal", wal, "--from", "7", "--to", "99"])
+            .status
+            .code(),
+        Some(2)
+    );
+
+    let tampered_wal = path_arg(&tampered_dir);
+    let parity = replay_cli(&[
+        "parity",
+        "--wal",
+        wal,
+        "--candidate-wal",
+        tampered_wal,
+        "--stride",
+        "5",
+    ]);
+    assert_eq!(parity.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&parity.stdout).expect("parity");
+    assert_eq!(report["first_mismatch_seq_id"], 35);
+    assert_eq!(report["parity_percent"], 75.0);
+    let lenient = replay_cli(&[
+        "parity",
+        "--wal",
+        wal,
+        "--candidate-wal",
+        tampered_wal,
+        "--stride",
+        "5",
+        "--min-percent",
+        "75",
+    ]);
+    assert!(lenient.status.success(), "{lenient:?}");
+
+    let hash_arg = format!("0x{}", hex::encode(hash(3)));
+    let lifecycle = replay_cli(&["lifecycle", &hash_arg, "--wal", wal]);
+    assert!(lifecycle.status.success(), "{lifecycle:?}");
+    let status: serde_json::Value = serde_json::from_slice(&lifecycle.stdout).expect("status");
+    assert_eq!(status["status"], "dropped");
+    let unknown = format!("0x{}", hex::encode(hash(0xab)));
+    assert_eq!(
+        replay_cli(&["lifecycle", &unknown, "--wal", wal])
+            .status
+            .code(),
+        Some(1)
+    );
+
+    let frames_parquet = replay_cli(&[
+        "frames",
+        "--wal",
+        wal,
+        "--stride",
+        "8",
+        "--format",
+        "parquet",
+        "--out",
+        path_arg(&frames_path),
+    ]);
+    assert!(frames_parquet.status.success(), "{frames_parquet:?}");
+    assert_eq!(
+        read_parquet_table::<ReplayFrame>(&frames_path).expect("read frames"),
+        frames
+    );
+    let export = replay_cli(&[
+        "export",
+        "--wal",
+        wal,
+        "--format",
+        "parquet",
+        "--out",
+        path_arg(&export_path),
+    ]);
+    assert!(export.status.success(), "{export:?}");
+    assert_eq!(
+        read_parquet_table::<EventEnvelope>(&export_path).expect("read export"),
+        events
+    );
+
+    for path in [
+        checkpoints_path,
+        checkpoints_csv_path,
+        frames_path,
+        export_path,
+    ] {
+        let _ = fs::remove_file(path);
+    }
+    let _ = fs::remove_dir_all(dir);
+    let _ = fs::remove_dir_all(tampered_dir);
+}
+
+#[test]
+fn replay_cli_counterfactual_reports_outcomes_a_tuned_config_changes() {
+    let dir = temp_file("counterfactual-wal").with_extension("");
+    let tx_full_path = temp_file("counterfactual-tx-full");
+    let config_path = temp_file("counterfactual-config");
+    let report_path = temp_file("counterfactual-report");
+
+    // Two pending transfers from one sender; neither is a searcher candidate.
+    let events = (1..=2_u64)
+        .map(|seq_id| {
+            let mut event = decoded_event(seq_id, seq_id as u8);
+            if let EventPayload::TxDecoded(decoded) = &mut event.payload {
+                decoded.sender = address(7);
+                decoded.nonce = seq_id - 1;
+            }
+            event
+        })
+        .collect::<Vec<_>>();
+    fs::create_dir_all(&dir).expect("create wal dir");
+    write_wal(&dir, &events);
+    let tx_full = events
+        .iter()
+        .filter_map(|event| match &event.payload {
+            EventPayload::TxDecoded(decoded) => Some(TxFullRecord {
+                hash: decoded.hash,
+                tx_type: decoded.tx_type,
+                sender: decoded.sender,
+                nonce: decoded.nonce,
+                to: decoded.to,
+                chain_id: decoded.chain_id,
+                value_wei: decoded.value_wei,
+                gas_limit: decoded.gas_limit,
+                gas_price_wei: decoded.gas_price_wei,
+                max_fee_per_gas_wei: decoded.max_fee_per_gas_wei,
+                max_priority_fee_per_gas_wei: decoded.max_priority_fee_per_gas_wei,
+                max_fee_per_blob_gas_wei: decoded.max_fee_per_blob_gas_wei,
+                calldata_len: decoded.calldata_len,
+                raw_tx: Vec::new(),
+                l2_fields: None,
+            }),
+            _ => None,
+        })
+        .map(|record| serde_json::to_string(&record).expect("json record") + "\n")
+        .collect::<String>();
+    fs::write(&tx_full_path, tx_full).expect("write tx_full");
+    let wal = path_arg(&dir);
+    let tx_full = path_arg(&tx_full_path);
+
+    let unchanged = replay_cli(&["counterfactual", "--wal", wal, "--tx-full", tx_full]);
+    assert!(unchanged.status.success(), "{unchanged:?}");
+    let report: serde_json::Value = serde_json::from_slice(&unchanged.stdout).expect("report");
+    assert_eq!(report["replayed_transactions"], 2);
+    assert_eq!(report["admissions_changed"], serde_json::json!([]));
+
+    fs::write(
+        &config_path,
+        r#"{"scheduler":{"max_pending_per_sender":1}}"#,
+    )
+    .expect("write config");
+    let changed = replay_cli(&[
+        "counterfactual",
+        "--wal",
+        wal,
+        "--tx-full",
+        tx_full,
+        "--config",
+        path_arg(&config_path),
+        "--out",
+        path_arg(&report_path),
+    ]);
+    assert_eq!(changed.status.code(), Some(1), "{changed:?}");
+    let report: serde_json::Value =
+        serde_json::from_slice(&fs::read(&report_path).expect("read report")).expect("report");
+    assert_eq!(report["config"]["scheduler"]["max_pending_per_sender"], 1);
+    let admissions = report["admissions_changed"].as_array().expect("admissions");
+    assert_eq!(admissions.len(), 1);
+    assert_eq!(admissions[0]["production"], "admitted");
+    assert_eq!(admissions[0]["counterfactual"], "sender_limit_reached");
+
+    for path in [tx_full_path, config_path, report_path] {
+        let _ = fs::remove_file(path);
+    }
+    let _ = fs::remove_dir_all(dir);
+}
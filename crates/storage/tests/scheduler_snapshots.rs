@@ -47,6 +47,9 @@ fn decoded_event(seq_id: u64, tx: &ValidatedTransaction) -> EventEnvelope {
         ingest_ts_mono_ns: tx.observed_at_mono_ns,
         source_id: tx.source_id.clone(),
         payload: EventPayload::TxDecoded(tx.decoded.clone()),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -61,6 +64,9 @@ fn confirmed_final_event(seq_id: u64, unix_ms: i64) -> EventEnvelope {
             block_number: seq_id,
             block_hash: [seq_id as u8; 32],
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -0,0 +1,277 @@
+use common::{Address, TxHash};
+use storage::{
+    InMemoryStorage, IndexPageQuery, OpportunityRecord, StorageConfig, TxFeaturesRecord,
+    TxFullRecord, TxIndexKey, TxLifecycleRecord, TxSeenRecord, WalHead,
+};
+
+const ROUTER: Address = [0xaa; 20];
+
+fn hash(v: u8) -> TxHash {
+    [v; 32]
+}
+
+fn bounded_storage(table_capacity: usize) -> InMemoryStorage {
+    InMemoryStorage::with_config(StorageConfig {
+        table_capacity,
+        ..StorageConfig::default()
+    })
+}
+
+fn full_record(v: u8, sender: u8, to: Option<Address>, chain_id: u64) -> TxFullRecord {
+    TxFullRecord {
+        hash: hash(v),
+        tx_type: 2,
+        sender: [sender; 20],
+        nonce: u64::from(v),
+        to,
+        chain_id: Some(chain_id),
+        value_wei: None,
+        gas_limit: Some(21_000),
+        gas_price_wei: None,
+        max_fee_per_gas_wei: None,
+        max_priority_fee_per_gas_wei: None,
+        max_fee_per_blob_gas_wei: None,
+        calldata_len: None,
+        raw_tx: vec![v],
+        l2_fields: None,
+    }
+}
+
+fn features_record(v: u8, protocol: &str) -> TxFeaturesRecord {
+    TxFeaturesRecord {
+        hash: hash(v),
+        chain_id: Some(1),
+        protocol: protocol.to_owned(),
+        category: "swap".to_owned(),
+        mev_score: 50,
+        urgency_score: 10,
+        method_selector: None,
+        feature_engine_version: "feature-engine.v1".to_owned(),
+    }
+}
+
+fn observe(storage: &mut InMemoryStorage, v: u8, seen_unix_ms: i64, status: &str) {
+    storage.upsert_tx_seen(TxSeenRecord {
+        hash: hash(v),
+        peer: "peer-a".to_owned(),
+        first_seen_unix_ms: seen_unix_ms,
+        first_seen_mono_ns: u64::from(v),
+        seen_count: 1,
+    });
+    storage.upsert_tx_lifecycle(TxLifecycleRecord {
+        hash: hash(v),
+        status: status.to_owned(),
+        reason: None,
+        updated_unix_ms: seen_unix_ms,
+    });
+}
+
+fn opportunity(v: u8, protocol: &str) -> OpportunityRecord {
+    OpportunityRecord {
+        tx_hash: hash(v),
+        chain_id: Some(1),
+        strategy: "SandwichCandidate".to_owned(),
+        score: 1_000,
+        protocol: protocol.to_owned(),
+        category: "swap".to_owned(),
+        feature_engine_version: "feature-engine.v1".to_owned(),
+        scorer_version: "scorer.v1".to_owned(),
+        strategy_version: "strategy.sandwich.v1".to_owned(),
+        reasons: Vec::new(),
+        detected_unix_ms: 1_000 + i64::from(v),
+    }
+}
+
+fn page_query(limit: usize) -> IndexPageQuery {
+    IndexPageQuery {
+        limit,
+        ..IndexPageQuery::default()
+    }
+}
+
+#[test]
+fn sender_and_recipient_indexes_page_newest_first_and_apply_filters() {
+    let mut storage = bounded_storage(16);
+    for v in 1..=5 {
+        storage.upsert_tx_full(full_record(v, 0x01, Some(ROUTER), 1));
+        observe(
+            &mut storage,
+            v,
+            1_000 * i64::from(v),
+            if v % 2 == 0 { "confirmed" } else { "pending" },
+        );
+    }
+    storage.upsert_tx_full(full_record(6, 0x02, None, 10));
+
+    let sender = TxIndexKey::Sender([0x01; 20]);
+    let first = storage.transactions_by_index(&sender, &page_query(2));
+    assert_eq!(first.rows, vec![hash(5), hash(4)]);
+    let second = storage.transactions_by_index(
+        &sender,
+        &IndexPageQuery {
+            before: first.next_cursor,
+            ..page_query(2)
+        },
+    );
+    assert_eq!(second.rows, vec![hash(3), hash(2)]);
+    let last = storage.transactions_by_index(
+        &sender,
+        &IndexPageQuery {
+            before: second.next_cursor,
+            ..page_query(2)
+        },
+    );
+    assert_eq!(last.rows, vec![hash(1)]);
+    assert_eq!(last.next_cursor, None);
+
+    let pending = storage.transactions_by_index(
+        &sender,
+        &IndexPageQuery {
+            status: Some("PENDING".to_owned()),
+            ..page_query(10)
+        },
+    );
+    assert_eq!(pending.rows, vec![hash(5), hash(3), hash(1)]);
+
+    let window = storage.transactions_by_index(
+        &TxIndexKey::To(ROUTER),
+        &IndexPageQuery {
+            from_unix_ms: Some(2_000),
+            to_unix_ms: Some(4_000),
+            ..page_query(10)
+        },
+    );
+    assert_eq!(window.rows, vec![hash(4), hash(3), hash(2)]);
+
+    let chain = storage.transactions_by_index(&TxIndexKey::ChainId(10), &page_query(10));
+    assert_eq!(chain.rows, vec![hash(6)]);
+    assert!(
+        storage
+            .transactions_by_index(&TxIndexKey::To([0xbb; 20]), &page_query(10))
+            .rows
+            .is_empty()
+    );
+}
+
+#[test]
+fn indexes_follow_latest_rows_and_table_eviction() {
+    let mut storage = bounded_storage(3);
+    storage.upsert_tx_full(full_record(1, 0x01, Some(ROUTER), 1));
+    storage.upsert_tx_full(full_record(2, 0x01, Some(ROUTER), 1));
+    // A newer row for hash 1 moves it to a different sender and to the head.
+    storage.upsert_tx_full(full_record(1, 0x02, Some(ROUTER), 1));
+    assert_eq!(
+        storage
+            .transactions_by_index(&TxIndexKey::Sender([0x01; 20]), &page_query(10))
+            .rows,
+        vec![hash(2)]
+    );
+    assert_eq!(
+        storage
+            .transactions_by_index(&TxIndexKey::To(ROUTER), &page_query(10))
+            .rows,
+        vec![hash(1), hash(2)]
+    );
+
+    // Evicting the stale copy of hash 1 keeps it indexed; evicting the only
+    // row of hash 2 drops it.
+    storage.upsert_tx_full(full_record(3, 0x03, None, 1));
+    storage.upsert_tx_full(full_record(4, 0x03, None, 1));
+    assert!(storage.tx_full_by_hash(&hash(2)).is_none());
+    assert_eq!(
+        storage
+            .transactions_by_index(&TxIndexKey::To(ROUTER), &page_query(10))
+            .rows,
+        vec![hash(1)]
+    );
+    assert_eq!(
+        storage
+            .transactions_by_index(&TxIndexKey::ChainId(1), &page_query(10))
+            .rows,
+        vec![hash(4), hash(3), hash(1)]
+    );
+
+    for (v, protocol) in [(1, "uniswap-v3"), (2, "curve"), (3, "uniswap-v3")] {
+        storage.upsert_tx_features(features_record(v, protocol));
+    }
+    storage.upsert_tx_features(features_record(1, "curve"));
+    storage.upsert_tx_features(features_record(4, "balancer"));
+    let uniswap = TxIndexKey::Protocol("uniswap-v3".to_owned());
+    assert_eq!(
+        storage
+            .transactions_by_index(&uniswap, &page_query(10))
+            .rows,
+        vec![hash(3)]
+    );
+    assert_eq!(
+        storage
+            .transactions_by_index(&TxIndexKey::Protocol("curve".to_owned()), &page_query(10))
+            .rows,
+        vec![hash(1)]
+    );
+
+    // A restored checkpoint rebuilds the same indexes.
+    let mut restored = bounded_storage(3);
+    restored.restore_checkpoint(storage.checkpoint(WalHead::default(), 0));
+    for key in [
+        TxIndexKey::To(ROUTER),
+        TxIndexKey::ChainId(1),
+        TxIndexKey::Sender([0x03; 20]),
+        uniswap,
+    ] {
+        assert_eq!(
+            restored.transactions_by_index(&key, &page_query(10)).rows,
+            storage.transactions_by_index(&key, &page_query(10)).rows,
+            "{key:?}"
+        );
+    }
+}
+
+#[test]
+fn opportunities_by_protocol_page_and_evict_with_the_table() {
+    let mut storage = bounded_storage(4);
+    for v in 1..=6 {
+        let protocol = if v % 2 == 0 { "curve" } else { "uniswap-v3" };
+        storage.upsert_opportunity(opportunity(v, protocol));
+    }
+
+    let first = storage.opportunities_by_protocol("uniswap-v3", &page_query(1));
+    let hashes =
+        |rows: &[OpportunityRecord]| rows.iter().map(|row| row.tx_hash).collect::<Vec<_>>();
+    assert_eq!(hashes(&first.rows), vec![hash(5)]);
+    let rest = storage.opportunities_by_protocol(
+        "uniswap-v3",
+        &IndexPageQuery {
+            before: first.next_cursor,
+            ..page_query(10)
+        },
+    );
+    assert_eq!(
+        hashes(&rest.rows),
+        vec![hash(3)],
+        "opportunity 1 was evicted"
+    );
+    assert_eq!(rest.next_cursor, None);
+
+    let window = storage.opportunities_by_protocol(
+        "curve",
+        &IndexPageQuery {
+            to_unix_ms: Some(1_005),
+            ..page_query(10)
+        },
+    );
+    assert_eq!(hashes(&window.rows), vec![hash(4)]);
+    assert!(
+        storage
+            .opportunities_by_protocol("balancer", &page_query(10))
+            .rows
+            .is_empty()
+    );
+
+    let mut restored = bounded_storage(4);
+    restored.restore_checkpoint(storage.checkpoint(WalHead::default(), 0));
+    assert_eq!(
+        restored.opportunities_by_protocol("curve", &page_query(10)),
+        storage.opportunities_by_protocol("curve", &page_query(10))
+    );
+}
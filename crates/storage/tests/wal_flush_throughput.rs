@@ -1,12 +1,12 @@
 use async_trait::async_trait;
 use common::SourceId;
-use event_log::{EventEnvelope, EventPayload, TxDecoded};
+use event_log::{EventEncoding, EventEnvelope, EventPayload, TxDecoded};
 use parking_lot::RwLock;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use storage::{
-    ClickHouseBatchSink, EventStore, InMemoryStorage, StorageWal, StorageWriteOp,
-    StorageWriterConfig, spawn_single_writer,
+    ClickHouseBatchSink, EventSinkFanout, EventStore, InMemoryStorage, StorageWal, StorageWriteOp,
+    StorageWriterConfig, WalDurability, spawn_single_writer,
 };
 
 fn hash(seed: u8) -> [u8; 32] {
@@ -34,6 +34,9 @@ fn decoded_event(seq: u64, seed: u8) -> EventEnvelope {
             max_fee_per_blob_gas_wei: None,
             calldata_len: Some(4),
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -77,6 +80,11 @@ async fn wal_flush_throughput_keeps_large_batches_and_clears_wal_under_high_rate
         flush_batch_size: 64,
         flush_interval_ms: 20,
         wal_path: Some(wal_path.clone()),
+        wal_encoding: EventEncoding::Json,
+        wal_durability: WalDurability::EveryBatch,
+        hash_chain: None,
+        checkpoint: None,
+        event_sinks: EventSinkFanout::default(),
     };
     let handle = spawn_single_writer(storage.clone(), sink, writer_config);
 
@@ -107,3 +115,94 @@ async fn wal_flush_throughput_keeps_large_batches_and_clears_wal_under_high_rate
 
     let _ = std::fs::remove_file(wal_path);
 }
+
+#[tokio::test]
+async fn wal_flush_throughput_per_durability_mode() {
+    const EVENTS: u64 = 2_048;
+    const BATCH: usize = 128;
+
+    for durability in [
+        WalDurability::None,
+        WalDurability::Interval { interval_ms: 5 },
+        WalDurability::EveryBatch,
+    ] {
+        let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+        let sink = Arc::new(RecordingSink {
+            flush_sizes: Arc::new(RwLock::new(Vec::new())),
+        });
+        let wal_path = temp_wal_path(durability.as_str());
+        let handle = spawn_single_writer(
+            storage.clone(),
+            sink,
+            StorageWriterConfig {
+                queue_capacity: 4_096,
+                flush_batch_size: BATCH,
+                flush_interval_ms: 20,
+                wal_path: Some(wal_path.clone()),
+                wal_encoding: EventEncoding::Binary,
+                wal_durability: durability,
+                hash_chain: None,
+                checkpoint: None,
+                event_sinks: EventSinkFanout::default(),
+            },
+        );
+
+        let started = Instant::now();
+        for seq in 1..=EVENTS {
+            handle
+                .enqueue(StorageWriteOp::AppendEvent(decoded_event(
+                    seq,
+                    (seq % 255) as u8,
+                )))
+                .await
+                .expect("enqueue append event");
+        }
+        let metrics = handle.wal_metrics();
+        while metrics.snapshot().records_total < EVENTS
+            || storage.read().list_events().len() < EVENTS as usize
+        {
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "{} writer did not drain",
+                durability.as_str()
+            );
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let elapsed = started.elapsed();
+        let snapshot = metrics.snapshot();
+        eprintln!(
+            "wal durability={} events={EVENTS} elapsed_ms={} events_per_sec={:.0} commits={} fsyncs={} fsync_ns_max={} bytes={}",
+            durability.as_str(),
+            elapsed.as_millis(),
+            EVENTS as f64 / elapsed.as_secs_f64(),
+            snapshot.commits_total,
+            snapshot.fsync_total,
+            snapshot.fsync_latency_ns_max,
+            snapshot.bytes_written_total,
+        );
+
+        assert!(snapshot.bytes_written_total > 0);
+        // Group commit writes once per batch, not once per event.
+        assert!(snapshot.commits_total >= EVENTS / BATCH as u64);
+        assert!(snapshot.commits_total < EVENTS);
+        match durability {
+            WalDurability::None => assert_eq!(snapshot.fsync_total, 0),
+            WalDurability::Interval { .. } => {
+                assert!(snapshot.fsync_total <= snapshot.commits_total)
+            }
+            WalDurability::EveryBatch => {
+                assert!(snapshot.fsync_total >= EVENTS / BATCH as u64);
+                assert!(snapshot.fsync_latency_ns_max > 0);
+            }
+        }
+
+        drop(handle);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let wal = StorageWal::new(&wal_path).expect("open throughput wal");
+        assert!(
+            wal.recover_events().expect("recover wal").is_empty(),
+            "{} wal should be cleared after successful flushes",
+            durability.as_str()
+        );
+    }
+}
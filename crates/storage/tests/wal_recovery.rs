@@ -1,11 +1,17 @@
+use async_trait::async_trait;
 use common::SourceId;
-use event_log::{EventEnvelope, EventPayload, TxDecoded};
+use event_log::{
+    CheckpointSigner, EventEncoding, EventEnvelope, EventPayload, HashChainConfig, TxDecoded,
+    verify_hash_chain,
+};
 use parking_lot::RwLock;
+use replay::{ReplayMode, replay_frames, replay_frames_iter};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use storage::{
-    EventStore, InMemoryStorage, NoopClickHouseSink, StorageWal, StorageWriterConfig,
-    spawn_single_writer,
+    ClickHouseBatchSink, EventSinkFanout, EventStore, InMemoryStorage, NoopClickHouseSink,
+    StorageCheckpointConfig, StorageError, StorageWal, StorageWriteOp, StorageWriterConfig,
+    TxSeenRecord, WalDurability, WalHead, WalIssueKind, WalRetention, spawn_single_writer,
 };
 
 fn hash(v: u8) -> [u8; 32] {
@@ -20,6 +26,27 @@ fn temp_wal_path(suffix: &str) -> std::path::PathBuf {
     std::env::temp_dir().join(format!("prototype03-storage-wal-{suffix}-{now}.log"))
 }
 
+/// Acknowledges only batches at or below `acked_through`, so later committed
+/// batches stay in the WAL as they would after a crash before the flush.
+struct UnackedSink {
+    acked_through: u64,
+}
+
+#[async_trait]
+impl ClickHouseBatchSink for UnackedSink {
+    async fn flush_event_batch(&self, events: Vec<EventEnvelope>) -> Result<(), StorageError> {
+        if events
+            .iter()
+            .all(|event| event.seq_id <= self.acked_through)
+        {
+            return Ok(());
+        }
+        Err(StorageError::ClickHouseBatch(Arc::new(
+            std::io::Error::other("batch not acknowledged"),
+        )))
+    }
+}
+
 fn decoded_event(seq: u64, seed: u8) -> EventEnvelope {
     EventEnvelope {
         seq_id: seq,
@@ -41,6 +68,9 @@ fn decoded_event(seq: u64, seed: u8) -> EventEnvelope {
             max_fee_per_blob_gas_wei: None,
             calldata_len: Some(12),
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -63,6 +93,11 @@ async fn writer_recovers_unflushed_events_from_wal_on_restart() {
             flush_batch_size: 8,
             flush_interval_ms: 1_000,
             wal_path: Some(wal_path.clone()),
+            wal_encoding: EventEncoding::Json,
+            wal_durability: WalDurability::EveryBatch,
+            hash_chain: None,
+            checkpoint: None,
+            event_sinks: EventSinkFanout::default(),
         },
     );
 
@@ -149,3 +184,802 @@ fn wal_segments_roll_over_and_scan_across_segments() {
     }
     let _ = std::fs::remove_file(wal_path);
 }
+
+#[test]
+fn wal_recovers_mixed_json_and_binary_segments_after_encoding_switch() {
+    let wal_path = temp_wal_path("encoding-switch");
+    let json_wal = StorageWal::new(&wal_path).expect("create json wal");
+    json_wal
+        .append_event(&decoded_event(1, 1))
+        .expect("append json event");
+
+    let binary_wal = StorageWal::new(&wal_path)
+        .expect("reopen wal")
+        .with_encoding(EventEncoding::Binary);
+    binary_wal
+        .append_event(&decoded_event(2, 2))
+        .expect("append binary event");
+    binary_wal
+        .append_event(&decoded_event(3, 3))
+        .expect("append binary event");
+
+    let recovered = binary_wal.recover_events().expect("recover mixed wal");
+    assert_eq!(
+        recovered,
+        vec![
+            decoded_event(1, 1),
+            decoded_event(2, 2),
+            decoded_event(3, 3)
+        ]
+    );
+
+    binary_wal.clear().expect("clear wal");
+}
+
+#[test]
+fn legacy_wal_file_is_read_before_segments_by_scans_and_recovery() {
+    let wal_path = temp_wal_path("legacy-and-segments");
+    let mut legacy = Vec::new();
+    for seq in 1_u64..=3_u64 {
+        EventEncoding::Json
+            .encode_into(&decoded_event(seq, seq as u8), &mut legacy)
+            .expect("encode legacy event");
+    }
+    std::fs::write(&wal_path, legacy).expect("write legacy wal");
+
+    let wal = StorageWal::with_segment_size(&wal_path, 256).expect("open wal");
+    for seq in 4_u64..=9_u64 {
+        wal.append_event(&decoded_event(seq, seq as u8))
+            .expect("append segment event");
+    }
+
+    let seq_ids = |events: Vec<EventEnvelope>| {
+        events
+            .into_iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>()
+    };
+    let scanned = wal
+        .iter_from(0)
+        .expect("iterate wal")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("read wal");
+    assert_eq!(seq_ids(scanned.clone()), (1..=9).collect::<Vec<_>>());
+    assert_eq!(wal.recover_events().expect("recover wal"), scanned);
+    assert_eq!(
+        seq_ids(wal.scan(2, 3).expect("scan across legacy boundary")),
+        vec![3, 4, 5]
+    );
+    assert_eq!(
+        seq_ids(wal.recover_from(2).expect("recover tail").events),
+        (3..=9).collect::<Vec<_>>()
+    );
+    let report = wal.verify().expect("verify wal");
+    assert_eq!(report.records, 9);
+    assert!(report.issues.is_empty());
+
+    remove_wal_files(&wal);
+    let _ = std::fs::remove_file(wal_path);
+}
+
+#[test]
+fn indexed_wal_scans_seek_by_seq_id_and_time_range() {
+    let wal_path = temp_wal_path("indexed-scan");
+    let wal = StorageWal::with_segment_size(&wal_path, 4 * 1024)
+        .expect("create segmented wal")
+        .with_encoding(EventEncoding::Binary);
+    let mut writer = wal.writer(WalDurability::None);
+    for seq in 1_u64..=500 {
+        writer
+            .append(&decoded_event(seq, seq as u8))
+            .expect("append event");
+    }
+    writer.commit().expect("commit wal");
+
+    // Every rotated segment carries a sidecar index; the active one does not.
+    let indexes = wal.segment_indexes().expect("segment indexes");
+    assert!(indexes.len() > 2, "expected several segments");
+    let sidecars = indexes
+        .iter()
+        .filter(|(path, _)| {
+            path.with_file_name(format!(
+                "{}.idx",
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .expect("name")
+            ))
+            .exists()
+        })
+        .count();
+    assert_eq!(sidecars, indexes.len() - 1);
+    let mut next_seq_id = 1;
+    for (_, index) in &indexes {
+        assert_eq!(index.min_seq_id, Some(next_seq_id));
+        next_seq_id = index.max_seq_id.expect("non-empty segment") + 1;
+    }
+    assert_eq!(next_seq_id, 501);
+
+    let seq_ids = |events: Vec<EventEnvelope>| {
+        events
+            .into_iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>()
+    };
+    let tail = wal
+        .iter_from(321)
+        .expect("iterate from seq")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("read tail");
+    assert_eq!(seq_ids(tail), (322..=500).collect::<Vec<_>>());
+    assert_eq!(
+        seq_ids(wal.scan(321, 10).expect("scan page")),
+        (322..=331).collect::<Vec<_>>()
+    );
+
+    let window = wal
+        .iter_time_range(Some(1_700_000_000_100), Some(1_700_000_000_199))
+        .expect("iterate time range")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("read time range");
+    assert_eq!(seq_ids(window), (100..=199).collect::<Vec<_>>());
+    assert_eq!(
+        seq_ids(
+            wal.scan_time_range(Some(1_700_000_000_100), Some(1_700_000_000_199), 150, 5)
+                .expect("scan time range page")
+        ),
+        (151..=155).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        wal.seq_id_range_for_time(Some(1_700_000_000_100), Some(1_700_000_000_199))
+            .expect("map time to seq ids"),
+        Some(100..=199)
+    );
+    assert_eq!(
+        wal.ingest_ts_for_seq_id(321).expect("map seq id to time"),
+        Some(1_700_000_000_321)
+    );
+    assert_eq!(wal.ingest_ts_for_seq_id(501).expect("past the tail"), None);
+
+    // A stale sidecar is ignored and rebuilt from the segment.
+    let (first_segment, _) = &indexes[0];
+    let mut sidecar = first_segment.as_os_str().to_owned();
+    sidecar.push(".idx");
+    std::fs::write(&sidecar, b"{}").expect("overwrite sidecar");
+    assert_eq!(wal.scan(0, 500).expect("scan all").len(), 500);
+
+    wal.clear().expect("clear wal");
+    assert!(!std::path::Path::new(&sidecar).exists());
+    let _ = std::fs::remove_file(wal_path);
+}
+
+#[test]
+fn wal_segments_stream_into_replay_frames() {
+    let wal_path = temp_wal_path("replay-stream");
+    let wal = StorageWal::with_segment_size(&wal_path, 4 * 1024).expect("create segmented wal");
+    let mut writer = wal.writer(WalDurability::None);
+    for seq in 1_u64..=300 {
+        writer
+            .append(&decoded_event(seq, (seq % 7) as u8))
+            .expect("append event");
+    }
+    writer.commit().expect("commit wal");
+    assert!(wal.segment_indexes().expect("segment indexes").len() > 2);
+
+    let mut read_error = None;
+    let streamed = replay_frames_iter(
+        wal.iter_from(0)
+            .expect("iterate wal")
+            .map_while(|event| event.map_err(|err| read_error = Some(err)).ok()),
+        64,
+    )
+    .collect::<Vec<_>>();
+    assert!(read_error.is_none());
+
+    let recovered = wal.recover_events().expect("recover events");
+    assert_eq!(
+        streamed,
+        replay_frames(&recovered, ReplayMode::DeterministicEventReplay, 64)
+    );
+    assert_eq!(
+        streamed
+            .iter()
+            .map(|frame| frame.seq_hi)
+            .collect::<Vec<_>>(),
+        vec![64, 128, 192, 256, 300]
+    );
+
+    wal.clear().expect("clear wal");
+    let _ = std::fs::remove_file(wal_path);
+}
+
+#[tokio::test]
+async fn hash_chained_writer_resumes_chain_from_recovered_wal() {
+    let wal_path = temp_wal_path("hash-chain");
+    let signer = CheckpointSigner::from_seed("wal-test", &[7; 32]).expect("signing key");
+    let config = StorageWriterConfig {
+        queue_capacity: 8,
+        flush_batch_size: 1,
+        flush_interval_ms: 60_000,
+        wal_path: Some(wal_path.clone()),
+        wal_encoding: EventEncoding::Binary,
+        wal_durability: WalDurability::EveryBatch,
+        hash_chain: Some(HashChainConfig {
+            checkpoint_interval: 2,
+            signer: Some(signer.clone()),
+        }),
+        checkpoint: None,
+        event_sinks: EventSinkFanout::default(),
+    };
+    let sink = Arc::new(UnackedSink { acked_through: 0 });
+
+    // Every event is committed to the WAL but never acknowledged, so the WAL
+    // is not cleared; let each writer consume its immediate first flush tick
+    // before writing.
+    let first = spawn_single_writer(
+        Arc::new(RwLock::new(InMemoryStorage::default())),
+        sink.clone(),
+        config.clone(),
+    );
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    for seed in 1..=3 {
+        first
+            .enqueue(StorageWriteOp::AppendEvent(decoded_event(0, seed)))
+            .await
+            .expect("enqueue first writer event");
+    }
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let second = spawn_single_writer(storage.clone(), sink, config);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    second
+        .enqueue(StorageWriteOp::AppendEvent(decoded_event(0, 4)))
+        .await
+        .expect("enqueue second writer event");
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    let events = StorageWal::new(&wal_path)
+        .expect("open wal")
+        .recover_events()
+        .expect("recover chained wal");
+    // The checkpoint countdown survives the restart, so the fourth event
+    // completes the second interval.
+    assert_eq!(events.len(), 6);
+    assert!(matches!(
+        events[2].payload,
+        EventPayload::ChainCheckpoint(_)
+    ));
+    assert!(matches!(
+        events[5].payload,
+        EventPayload::ChainCheckpoint(_)
+    ));
+
+    let report = verify_hash_chain(&events, Some(&signer.verifier()));
+    assert!(report.is_intact(), "{report:?}");
+    assert_eq!(report.checkpoints_verified, 2);
+    assert_eq!(storage.read().list_events(), events);
+
+    drop((first, second));
+    let _ = std::fs::remove_file(wal_path);
+}
+
+#[tokio::test]
+async fn writer_resumes_sequences_and_chain_from_head_after_wal_clear() {
+    let wal_path = temp_wal_path("head");
+    let config = StorageWriterConfig {
+        queue_capacity: 8,
+        flush_batch_size: 2,
+        flush_interval_ms: 60_000,
+        wal_path: Some(wal_path.clone()),
+        wal_encoding: EventEncoding::Binary,
+        wal_durability: WalDurability::EveryBatch,
+        hash_chain: Some(HashChainConfig {
+            checkpoint_interval: 0,
+            signer: None,
+        }),
+        checkpoint: None,
+        event_sinks: EventSinkFanout::default(),
+    };
+
+    let first_storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let first = spawn_single_writer(
+        first_storage.clone(),
+        Arc::new(NoopClickHouseSink),
+        config.clone(),
+    );
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    for seed in 1..=2 {
+        first
+            .enqueue(StorageWriteOp::AppendEvent(EventEnvelope {
+                chain_id: Some(8453),
+                ..decoded_event(0, seed)
+            }))
+            .await
+            .expect("enqueue first writer event");
+    }
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    let wal = StorageWal::new(&wal_path).expect("open wal");
+    assert!(wal.recover_events().expect("recover wal").is_empty());
+
+    let second_storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let second = spawn_single_writer(second_storage.clone(), Arc::new(NoopClickHouseSink), config);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    second
+        .enqueue(StorageWriteOp::AppendEvent(EventEnvelope {
+            chain_id: Some(8453),
+            ..decoded_event(0, 3)
+        }))
+        .await
+        .expect("enqueue second writer event");
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    let mut events = first_storage.read().list_events();
+    events.extend(second_storage.read().list_events());
+    assert_eq!(
+        events.iter().map(|event| event.seq_id).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    assert_eq!(
+        events
+            .iter()
+            .map(|event| event.chain_seq_id)
+            .collect::<Vec<_>>(),
+        vec![Some(1), Some(2), Some(3)]
+    );
+    let report = verify_hash_chain(&events, None);
+    assert!(report.is_intact(), "{report:?}");
+
+    drop((first, second));
+    let _ = wal.clear();
+    let _ = std::fs::remove_file(wal_path.with_extension("log.head"));
+    let _ = std::fs::remove_file(wal_path);
+}
+
+#[test]
+fn checkpoint_round_trips_projections_through_the_wal_directory() {
+    let wal_path = temp_wal_path("checkpoint");
+    let wal = StorageWal::new(&wal_path).expect("create wal");
+    let mut storage = InMemoryStorage::default();
+    for seq in 1_u64..=5 {
+        storage.append_event(decoded_event(seq, seq as u8));
+    }
+    storage.upsert_tx_seen(TxSeenRecord {
+        hash: hash(2),
+        peer: "peer-a".to_owned(),
+        first_seen_unix_ms: 1_700_000_000_002,
+        first_seen_mono_ns: 20,
+        seen_count: 3,
+    });
+
+    let checkpoint = storage.checkpoint(WalHead::default(), 1_700_000_000_100);
+    assert_eq!(checkpoint.seq_id, 5);
+    assert_eq!(checkpoint.event_count(), 5);
+    wal.write_checkpoint(&checkpoint).expect("write checkpoint");
+    let loaded = wal
+        .read_checkpoint()
+        .expect("read checkpoint")
+        .expect("checkpoint present");
+    assert_eq!(loaded.seq_id, 5);
+    assert_eq!(loaded.created_unix_ms, 1_700_000_000_100);
+
+    let mut restored = InMemoryStorage::default();
+    restored.restore_checkpoint(loaded);
+    assert_eq!(restored.list_events(), storage.list_events());
+    assert_eq!(restored.scan_events(3, 10), storage.scan_events(3, 10));
+    assert_eq!(restored.tx_seen(), storage.tx_seen());
+    assert_eq!(
+        restored.tx_seen_by_hash(&hash(2)).map(|row| row.seen_count),
+        Some(3)
+    );
+    assert_eq!(
+        restored.recent_transactions(10),
+        storage.recent_transactions(10)
+    );
+    assert_eq!(
+        restored.market_stats_snapshot(),
+        storage.market_stats_snapshot()
+    );
+
+    let checkpoint_path = wal_path.with_extension("log.checkpoint");
+    let mut bytes = std::fs::read(&checkpoint_path).expect("read checkpoint file");
+    bytes.truncate(bytes.len() - 4);
+    std::fs::write(&checkpoint_path, &bytes).expect("truncate checkpoint file");
+    assert!(wal.read_checkpoint().is_err());
+
+    let _ = std::fs::remove_file(checkpoint_path);
+}
+
+#[test]
+fn compaction_removes_only_covered_segments_within_retention() {
+    let wal_path = temp_wal_path("compact");
+    let wal = StorageWal::with_segment_size(&wal_path, 256).expect("create segmented wal");
+    let mut writer = wal.writer(WalDurability::None);
+    for seq in 1_u64..=12 {
+        writer
+            .append(&decoded_event(seq, seq as u8))
+            .expect("append event");
+    }
+    writer.roll().expect("seal active segment");
+    let segments = wal.segment_indexes().expect("segment indexes");
+    let total_bytes = segments
+        .iter()
+        .map(|(_, index)| index.segment_len)
+        .sum::<u64>();
+    assert!(segments.len() >= 3, "expected several segments");
+
+    let now_unix_ms = 1_700_000_000_000 + 12;
+    let kept = wal
+        .compact(
+            6,
+            &WalRetention {
+                max_age_ms: Some(60_000),
+                max_bytes: Some(total_bytes),
+            },
+            now_unix_ms,
+        )
+        .expect("compact within retention");
+    assert_eq!(kept.segments_removed, 0);
+
+    let compacted = wal
+        .compact(6, &WalRetention::default(), now_unix_ms)
+        .expect("compact covered segments");
+    assert!(compacted.segments_removed > 0);
+    let remaining = wal.segment_indexes().expect("segment indexes");
+    assert!(
+        remaining
+            .iter()
+            .all(|(_, index)| index.max_seq_id.is_none_or(|max| max > 6))
+    );
+
+    let tail = wal.recover_from(6).expect("recover tail");
+    assert_eq!(
+        tail.events
+            .iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>(),
+        (7..=12).collect::<Vec<_>>()
+    );
+
+    remove_wal_files(&wal);
+}
+
+#[tokio::test]
+async fn checkpointing_writer_restarts_from_checkpoint_plus_wal_tail() {
+    let wal_path = temp_wal_path("checkpoint-restart");
+    let config = StorageWriterConfig {
+        queue_capacity: 8,
+        flush_batch_size: 1,
+        flush_interval_ms: 60_000,
+        wal_path: Some(wal_path.clone()),
+        wal_encoding: EventEncoding::Binary,
+        wal_durability: WalDurability::EveryBatch,
+        hash_chain: None,
+        checkpoint: Some(StorageCheckpointConfig {
+            interval_ms: 0,
+            retention: WalRetention::default(),
+        }),
+        event_sinks: EventSinkFanout::default(),
+    };
+
+    // The sink stops acknowledging after the fourth event, so the fifth stays
+    // committed in the WAL tail behind the last checkpoint.
+    let first = spawn_single_writer(
+        Arc::new(RwLock::new(InMemoryStorage::default())),
+        Arc::new(UnackedSink { acked_through: 4 }),
+        config.clone(),
+    );
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    for seed in 1..=5 {
+        first
+            .enqueue(StorageWriteOp::AppendEvent(decoded_event(0, seed)))
+            .await
+            .expect("enqueue first writer event");
+    }
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    let wal = StorageWal::new(&wal_path).expect("open wal");
+    let checkpoint = wal
+        .read_checkpoint()
+        .expect("read checkpoint")
+        .expect("checkpoint written after flush");
+    assert_eq!(checkpoint.seq_id, 4);
+    let tail = wal.recover_from(checkpoint.seq_id).expect("recover tail");
+    assert_eq!(
+        tail.events
+            .iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>(),
+        vec![5]
+    );
+    assert!(
+        wal.recover_events()
+            .expect("recover wal")
+            .iter()
+            .all(|event| event.seq_id == 5),
+        "covered segments should be compacted away"
+    );
+
+    let second_storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let second = spawn_single_writer(second_storage.clone(), Arc::new(NoopClickHouseSink), config);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    second
+        .enqueue(StorageWriteOp::AppendEvent(decoded_event(0, 6)))
+        .await
+        .expect("enqueue second writer event");
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    assert_eq!(
+        second_storage
+            .read()
+            .list_events()
+            .iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>(),
+        vec![1, 2, 3, 4, 5, 6]
+    );
+
+    drop((first, second));
+    remove_wal_files(&wal);
+    let _ = std::fs::remove_file(wal_path.with_extension("log.head"));
+    let _ = std::fs::remove_file(wal_path.with_extension("log.checkpoint"));
+}
+
+fn remove_wal_files(wal: &StorageWal) {
+    let base_name = wal
+        .path()
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("wal base filename")
+        .to_owned();
+    let parent = wal.path().parent().expect("wal parent directory");
+    for entry in std::fs::read_dir(parent)
+        .expect("list wal parent")
+        .flatten()
+    {
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        if name.starts_with(&format!("{base_name}.quarantine")) {
+            let _ = std::fs::remove_dir_all(entry.path());
+        } else if name.starts_with(&format!("{base_name}.seg.")) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn only_segment(wal: &StorageWal) -> std::path::PathBuf {
+    let base_name = wal
+        .path()
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("base");
+    let parent = wal.path().parent().expect("wal parent directory");
+    let mut segments = std::fs::read_dir(parent)
+        .expect("list wal parent")
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&format!("{base_name}.seg.")))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(segments.len(), 1);
+    segments.pop().expect("segment")
+}
+
+#[test]
+fn wal_recovery_truncates_a_torn_tail_and_keeps_appending() {
+    let wal_path = temp_wal_path("torn-tail");
+    let wal = StorageWal::new(&wal_path)
+        .expect("create wal")
+        .with_encoding(EventEncoding::Binary);
+    for seq in 1..=3 {
+        wal.append_event(&decoded_event(seq, seq as u8))
+            .expect("append event");
+    }
+    let segment = only_segment(&wal);
+    let full_len = std::fs::metadata(&segment).expect("stat segment").len();
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(&segment)
+        .expect("open segment");
+    file.set_len(full_len - 5).expect("tear last record");
+
+    let recovery = wal.recover().expect("recover torn wal");
+    assert_eq!(
+        recovery.events,
+        vec![decoded_event(1, 1), decoded_event(2, 2)]
+    );
+    assert_eq!(recovery.issues.len(), 1);
+    assert_eq!(recovery.issues[0].kind, WalIssueKind::TornTail);
+    assert!(recovery.truncated_bytes > 0);
+
+    wal.append_event(&decoded_event(4, 4))
+        .expect("append after recovery");
+    let recovery = wal.recover().expect("recover after append");
+    assert!(recovery.issues.is_empty(), "{:?}", recovery.issues);
+    assert_eq!(
+        recovery
+            .events
+            .iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>(),
+        vec![1, 2, 4]
+    );
+
+    remove_wal_files(&wal);
+}
+
+#[test]
+fn wal_recovery_keeps_a_complete_but_corrupt_last_record() {
+    let wal_path = temp_wal_path("corrupt-tail");
+    let wal = StorageWal::new(&wal_path)
+        .expect("create wal")
+        .with_encoding(EventEncoding::Binary);
+    for seq in 1..=3 {
+        wal.append_event(&decoded_event(seq, seq as u8))
+            .expect("append event");
+    }
+    let segment = only_segment(&wal);
+    let mut bytes = std::fs::read(&segment).expect("read segment");
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x01;
+    std::fs::write(&segment, &bytes).expect("rot last record");
+
+    let recovery = wal.recover().expect("recover corrupt wal");
+    assert_eq!(
+        recovery.events,
+        vec![decoded_event(1, 1), decoded_event(2, 2)]
+    );
+    assert_eq!(recovery.issues.len(), 1);
+    assert_eq!(recovery.issues[0].kind, WalIssueKind::ChecksumMismatch);
+    assert_eq!(recovery.truncated_bytes, 0);
+    assert_eq!(std::fs::read(&segment).expect("reread segment"), bytes);
+
+    remove_wal_files(&wal);
+}
+
+#[test]
+fn wal_writer_writes_appended_records_only_on_commit() {
+    let wal_path = temp_wal_path("group-commit");
+    let wal = StorageWal::new(&wal_path)
+        .expect("create wal")
+        .with_encoding(EventEncoding::Binary);
+    let mut writer = wal.writer(WalDurability::EveryBatch);
+    writer
+        .append(&decoded_event(1, 1))
+        .expect("append first event");
+    writer.commit().expect("commit first group");
+    let segment = only_segment(&wal);
+    let committed_len = std::fs::metadata(&segment).expect("stat segment").len();
+
+    for seq in 2..=3 {
+        writer
+            .append(&decoded_event(seq, seq as u8))
+            .expect("append event");
+    }
+    assert_eq!(
+        std::fs::metadata(&segment).expect("stat segment").len(),
+        committed_len
+    );
+    assert_eq!(
+        wal.recover_events().expect("recover wal"),
+        vec![decoded_event(1, 1)]
+    );
+
+    writer.commit().expect("commit second group");
+    let recovery = wal.recover().expect("recover wal");
+    assert!(recovery.issues.is_empty(), "{:?}", recovery.issues);
+    assert_eq!(
+        recovery.events,
+        vec![
+            decoded_event(1, 1),
+            decoded_event(2, 2),
+            decoded_event(3, 3)
+        ]
+    );
+    let metrics = writer.metrics().snapshot();
+    assert_eq!((metrics.records_total, metrics.commits_total), (3, 2));
+    assert_eq!(
+        metrics.bytes_written_total,
+        std::fs::metadata(&segment).expect("stat segment").len()
+    );
+
+    remove_wal_files(&wal);
+}
+
+#[test]
+fn wal_verify_reports_corruption_and_repair_quarantines_it() {
+    let wal_path = temp_wal_path("repair");
+    let wal = StorageWal::new(&wal_path).expect("create wal");
+    for seq in 1..=3 {
+        wal.append_event(&decoded_event(seq, seq as u8))
+            .expect("append event");
+    }
+    let segment = only_segment(&wal);
+    let mut bytes = std::fs::read(&segment).expect("read segment");
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0x01;
+    std::fs::write(&segment, &bytes).expect("corrupt segment");
+
+    let report = wal.verify().expect("verify wal");
+    assert!(!report.is_clean());
+    assert_eq!(report.segments, 1);
+    assert_eq!(report.records, 2);
+    assert_eq!(report.issues[0].kind, WalIssueKind::ChecksumMismatch);
+    // Recovery skips the damaged record instead of failing.
+    assert_eq!(wal.recover_events().expect("recover").len(), 2);
+
+    let repair = wal.repair().expect("repair wal");
+    assert_eq!(repair.segments_rewritten, 1);
+    assert_eq!(repair.issues, report.issues);
+    assert_eq!(repair.quarantined.len(), 1);
+    assert_eq!(
+        std::fs::metadata(&repair.quarantined[0])
+            .expect("quarantined bytes")
+            .len(),
+        report.issues[0].len
+    );
+    assert!(wal.verify().expect("verify repaired wal").is_clean());
+    assert_eq!(
+        wal.recover_events()
+            .expect("recover repaired wal")
+            .iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>(),
+        vec![1, 3]
+    );
+
+    remove_wal_files(&wal);
+}
+
+#[test]
+fn wal_recovery_resyncs_past_a_length_pointing_beyond_the_segment() {
+    let wal_path = temp_wal_path("bad-length");
+    let wal = StorageWal::new(&wal_path)
+        .expect("create wal")
+        .with_encoding(EventEncoding::Binary);
+    for seq in 1..=4 {
+        wal.append_event(&decoded_event(seq, seq as u8))
+            .expect("append event");
+    }
+    let segment = only_segment(&wal);
+    let mut bytes = std::fs::read(&segment).expect("read segment");
+    // Segment header, then `len | crc | payload` records.
+    let first_len = u32::from_le_bytes(bytes[8..12].try_into().expect("len")) as usize;
+    let second = 8 + 8 + first_len;
+    let past_eof = u32::try_from(bytes.len() * 4).expect("small segment");
+    bytes[second..second + 4].copy_from_slice(&past_eof.to_le_bytes());
+    std::fs::write(&segment, &bytes).expect("corrupt record length");
+
+    let recovery = wal.recover().expect("recover wal");
+    assert_eq!(recovery.issues.len(), 1);
+    assert_eq!(recovery.issues[0].kind, WalIssueKind::ChecksumMismatch);
+    assert_eq!(recovery.truncated_bytes, 0);
+    assert_eq!(
+        recovery
+            .events
+            .iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>(),
+        vec![1, 3, 4]
+    );
+    assert_eq!(
+        std::fs::metadata(&segment).expect("stat segment").len(),
+        bytes.len() as u64
+    );
+
+    wal.append_event(&decoded_event(5, 5))
+        .expect("append after recovery");
+    assert_eq!(
+        wal.recover_events()
+            .expect("recover after append")
+            .iter()
+            .map(|event| event.seq_id)
+            .collect::<Vec<_>>(),
+        vec![1, 3, 4, 5]
+    );
+
+    remove_wal_files(&wal);
+}
@@ -19,7 +19,10 @@ use axum::{Json, Router};
 use axum::{middleware, response::Response};
 use builder::{AssemblyMetrics, AssemblySnapshot, RelayDryRunResult, RelayDryRunStatus};
 use common::{AlertDecisions, AlertThresholdConfig, MetricSnapshot, evaluate_alerts};
-use event_log::{EventEnvelope, EventPayload};
+use event_log::{
+    EventEnvelope, EventFilter, EventPayload, FilterField, FilterParseError, FilterSubject,
+    FilterValue,
+};
 use futures::stream;
 use live_rpc::{
     LiveRpcChainStatus, LiveRpcConfig, LiveRpcDropMetricsSnapshot, LiveRpcSearcherMetricsSnapshot,
@@ -42,12 +45,22 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::convert::Infallible;
 use std::env;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
 use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use storage::{
-    ClickHouseBatchSink, ClickHouseHttpSink, EventStore, InMemoryStorage, MarketStatsSnapshot,
-    NoopClickHouseSink, StorageTryEnqueueError, StorageWriteHandle, StorageWriteOp,
-    StorageWriterConfig, TxFullRecord, spawn_single_writer,
+    BackfillJob, BackfillProgress, BackfillRange, BackfillState, CalldataBlobStore,
+    ClickHouseBackfillSource, ClickHouseBatchSink, ClickHouseHttpSink, ClickHouseRetryConfig,
+    ClickHouseSinkHealth, ClickHouseSinkStatus, ClickHouseSpillConfig, EventSinkFanout,
+    EventSinkSpec, EventSinkStats, EventStore, FilteredScan, InMemoryStorage, IndexPage,
+    IndexPageQuery, MarketStatsSnapshot, MemoryBudgetConfig, NoopClickHouseSink, ObserverGroup,
+    OpportunityRecord, PeerObservationStats, ResilientClickHouseSink,
+    ResilientClickHouseSinkConfig, SharedStorageLookup, StorageCheckpointConfig, StorageConfig,
+    StorageMemoryUsage, StorageTryEnqueueError, StorageWriteHandle, StorageWriteOp,
+    StorageWriterConfig, TableWeights, TxFullRecord, TxIndexKey, WalBackfillSource, WalDurability,
+    WalMetrics, WalMetricsSnapshot, WalRetention, spawn_single_writer,
+    spawn_single_writer_with_backfill,
 };
 use stream_broadcast::{DashboardStreamBroadcastEvent, DashboardStreamBroadcaster};
 use tokio::time::MissedTickBehavior;
@@ -58,6 +71,28 @@ const DEFAULT_SCHEDULER_SNAPSHOT_INTERVAL_MS: u64 = 5_000;
 const ENV_SCHEDULER_SNAPSHOT_MAX_FINALITY_AGE_MS: &str =
     "VIZ_API_SCHEDULER_SNAPSHOT_MAX_FINALITY_AGE_MS";
 const DEFAULT_SCHEDULER_SNAPSHOT_MAX_FINALITY_AGE_MS: u64 = 300_000;
+const DEFAULT_PROPAGATION_WINDOW_MS: u64 = 5 * 60 * 1_000;
+const ENV_STORAGE_WAL_PATH: &str = "VIZ_API_WAL_PATH";
+const ENV_STORAGE_WAL_DURABILITY: &str = "VIZ_API_WAL_DURABILITY";
+const ENV_STORAGE_CHECKPOINT_INTERVAL_MS: &str = "VIZ_API_WAL_CHECKPOINT_INTERVAL_MS";
+const ENV_STORAGE_WAL_RETENTION_MAX_AGE_MS: &str = "VIZ_API_WAL_RETENTION_MAX_AGE_MS";
+const ENV_STORAGE_WAL_RETENTION_MAX_BYTES: &str = "VIZ_API_WAL_RETENTION_MAX_BYTES";
+const ENV_STORAGE_MEMORY_BUDGET_BYTES: &str = "VIZ_API_STORAGE_MEMORY_BUDGET_BYTES";
+const ENV_STORAGE_TABLE_WEIGHTS: &str = "VIZ_API_STORAGE_TABLE_WEIGHTS";
+const ENV_CALLDATA_BLOB_PATH: &str = "VIZ_API_CALLDATA_BLOB_PATH";
+const ENV_BACKFILL_CLICKHOUSE_URL: &str = "VIZ_API_BACKFILL_CLICKHOUSE_URL";
+const ENV_BACKFILL_WAL_PATH: &str = "VIZ_API_BACKFILL_WAL_PATH";
+const ENV_BACKFILL_AFTER_SEQ_ID: &str = "VIZ_API_BACKFILL_AFTER_SEQ_ID";
+const ENV_BACKFILL_TO_SEQ_ID: &str = "VIZ_API_BACKFILL_TO_SEQ_ID";
+const ENV_BACKFILL_FROM_TS: &str = "VIZ_API_BACKFILL_FROM_TS";
+const ENV_BACKFILL_TO_TS: &str = "VIZ_API_BACKFILL_TO_TS";
+const ENV_BACKFILL_CURSOR_PATH: &str = "VIZ_API_BACKFILL_CURSOR_PATH";
+const ENV_EVENT_SINKS: &str = "VIZ_API_EVENT_SINKS";
+#[cfg(feature = "disk-store")]
+const ENV_DISK_STORE_PATH: &str = "VIZ_API_DISK_STORE_PATH";
+const ENV_CLICKHOUSE_MAX_ATTEMPTS: &str = "VIZ_API_CLICKHOUSE_MAX_ATTEMPTS";
+const ENV_CLICKHOUSE_SPILL_DIR: &str = "VIZ_API_CLICKHOUSE_SPILL_DIR";
+const ENV_CLICKHOUSE_SPILL_MAX_BYTES: &str = "VIZ_API_CLICKHOUSE_SPILL_MAX_BYTES";
 const ENV_SCHEDULER_HANDOFF_QUEUE_CAPACITY: &str = "VIZ_API_SCHEDULER_HANDOFF_QUEUE_CAPACITY";
 const ENV_SCHEDULER_MAX_PENDING_PER_SENDER: &str = "VIZ_API_SCHEDULER_MAX_PENDING_PER_SENDER";
 const ENV_SCHEDULER_REPLACEMENT_FEE_BUMP_BPS: &str = "VIZ_API_SCHEDULER_REPLACEMENT_FEE_BUMP_BPS";
@@ -317,7 +352,8 @@ pub struct ReplayPoint {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
-/// Synthetic propagation edge used by visualization endpoints.
+/// Propagation delay between two observers, either observed from `TxSeen`
+/// events or configured for visualization.
 pub struct PropagationEdge {
     pub source: String,
     pub destination: String,
@@ -404,10 +440,92 @@ pub struct TransactionDetail {
     pub feature_engine_version: Option<String>,
 }
 
+fn number_value<T: Into<i128>>(value: T) -> FilterValue<'static> {
+    FilterValue::Number(value.into())
+}
+
+fn hash_value(hex: &str) -> Option<FilterValue<'static>> {
+    live_rpc::parse_fixed_hex::<32>(hex).map(FilterValue::Hash)
+}
+
+fn address_value(hex: &str) -> Option<FilterValue<'static>> {
+    live_rpc::parse_fixed_hex::<20>(hex).map(FilterValue::Address)
+}
+
+impl FilterSubject for FeatureDetail {
+    fn filter_value(&self, field: FilterField) -> Option<FilterValue<'_>> {
+        match field {
+            FilterField::Hash => hash_value(&self.hash),
+            FilterField::ChainId => self.chain_id.map(number_value),
+            FilterField::Protocol => Some(FilterValue::Text(&self.protocol)),
+            FilterField::Category => Some(FilterValue::Text(&self.category)),
+            FilterField::Score => Some(number_value(self.mev_score)),
+            _ => None,
+        }
+    }
+}
+
+impl FilterSubject for OpportunityDetail {
+    fn filter_value(&self, field: FilterField) -> Option<FilterValue<'_>> {
+        match field {
+            FilterField::Hash => hash_value(&self.tx_hash),
+            FilterField::ChainId => self.chain_id.map(number_value),
+            FilterField::Status => Some(FilterValue::Text(&self.status)),
+            FilterField::Strategy => Some(FilterValue::Text(&self.strategy)),
+            FilterField::Score => Some(number_value(self.score)),
+            FilterField::Protocol => Some(FilterValue::Text(&self.protocol)),
+            FilterField::Category => Some(FilterValue::Text(&self.category)),
+            FilterField::Timestamp => Some(number_value(self.detected_unix_ms)),
+            _ => None,
+        }
+    }
+}
+
+impl FilterSubject for TransactionSummary {
+    fn filter_value(&self, field: FilterField) -> Option<FilterValue<'_>> {
+        match field {
+            FilterField::Hash => hash_value(&self.hash),
+            FilterField::Sender => address_value(&self.sender),
+            FilterField::Nonce => Some(number_value(self.nonce)),
+            FilterField::TxType => Some(number_value(self.tx_type)),
+            FilterField::Timestamp => Some(number_value(self.seen_unix_ms)),
+            FilterField::Source => Some(FilterValue::Text(&self.source_id)),
+            _ => None,
+        }
+    }
+}
+
+impl FilterSubject for TransactionDetail {
+    fn filter_value(&self, field: FilterField) -> Option<FilterValue<'_>> {
+        match field {
+            FilterField::Hash => hash_value(&self.hash),
+            FilterField::PeerId => Some(FilterValue::Text(&self.peer)),
+            FilterField::Timestamp => Some(number_value(self.first_seen_unix_ms)),
+            FilterField::TxType => self.tx_type.map(number_value),
+            FilterField::Sender => self.sender.as_deref().and_then(address_value),
+            FilterField::To => self.to.as_deref().and_then(address_value),
+            FilterField::ChainId => self.chain_id.map(number_value),
+            FilterField::Nonce => self.nonce.map(number_value),
+            FilterField::Value => self
+                .value_wei
+                .map(|value| FilterValue::Number(i128::try_from(value).unwrap_or(i128::MAX))),
+            FilterField::GasLimit => self.gas_limit.map(number_value),
+            FilterField::Status => self.lifecycle_status.as_deref().map(FilterValue::Text),
+            FilterField::Reason => self.lifecycle_reason.as_deref().map(FilterValue::Text),
+            FilterField::Protocol => self.protocol.as_deref().map(FilterValue::Text),
+            FilterField::Category => self.category.as_deref().map(FilterValue::Text),
+            FilterField::Score => self.mev_score.map(number_value),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 /// Health-check response payload.
 pub struct HealthResponse {
     pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clickhouse: Option<ClickHouseSinkHealth>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -615,6 +733,90 @@ fn map_market_stats(snapshot: MarketStatsSnapshot) -> MarketStats {
 pub trait VizDataProvider: Send + Sync {
     fn events(&self, after_seq_id: u64, event_types: &[String], limit: usize)
     -> Vec<EventEnvelope>;
+    /// Returns one chain's events strictly after a per-chain `chain_seq_id` cursor.
+    #[must_use]
+    fn chain_events(
+        &self,
+        chain_id: u64,
+        after_chain_seq_id: u64,
+        event_types: &[String],
+        limit: usize,
+    ) -> Vec<EventEnvelope>;
+    /// Returns up to `limit` events after `after_seq_id` that satisfy `filter`,
+    /// examining at most `scan_budget` events.
+    #[must_use]
+    fn filtered_events(
+        &self,
+        after_seq_id: u64,
+        filter: &EventFilter,
+        limit: usize,
+        scan_budget: usize,
+    ) -> FilteredScan {
+        let (limit, scan_budget) = (limit.max(1), scan_budget.max(1));
+        let mut scan = FilteredScan {
+            resume_after: after_seq_id,
+            ..FilteredScan::default()
+        };
+        let mut examined = 0;
+        while examined < scan_budget {
+            let page = self.events(scan.resume_after, &[], limit.min(scan_budget - examined));
+            if page.is_empty() {
+                return scan;
+            }
+            for event in page {
+                examined += 1;
+                scan.resume_after = event.seq_id;
+                if filter.matches(&event) {
+                    scan.events.push(event);
+                    if scan.events.len() == limit {
+                        return scan;
+                    }
+                }
+            }
+        }
+        scan.budget_exhausted = true;
+        scan
+    }
+    /// Chain-scoped [`VizDataProvider::filtered_events`], paged by `chain_seq_id`.
+    #[must_use]
+    fn filtered_chain_events(
+        &self,
+        chain_id: u64,
+        after_chain_seq_id: u64,
+        filter: &EventFilter,
+        limit: usize,
+        scan_budget: usize,
+    ) -> FilteredScan {
+        let (limit, scan_budget) = (limit.max(1), scan_budget.max(1));
+        let mut scan = FilteredScan {
+            resume_after: after_chain_seq_id,
+            ..FilteredScan::default()
+        };
+        let mut examined = 0;
+        while examined < scan_budget {
+            let page = self.chain_events(
+                chain_id,
+                scan.resume_after,
+                &[],
+                limit.min(scan_budget - examined),
+            );
+            if page.is_empty() {
+                return scan;
+            }
+            for event in page {
+                examined += 1;
+                scan.resume_after = event.chain_seq_id.unwrap_or(scan.resume_after);
+                if filter.matches(&event) {
+                    scan.events.push(event);
+                    if scan.events.len() == limit {
+                        return scan;
+                    }
+                }
+            }
+        }
+        scan.budget_exhausted = true;
+        scan
+    }
     #[must_use = "callers should inspect the latest sequence id when serving incremental data"]
     fn latest_seq_id(&self) -> Option<u64>;
     fn replay_points(&self) -> Vec<ReplayPoint>;
@@ -625,8 +827,80 @@ pub trait VizDataProvider: Send + Sync {
     fn recent_transactions(&self, limit: usize) -> Vec<TransactionSummary>;
     fn transaction_details(&self, limit: usize) -> Vec<TransactionDetail>;
     fn transaction_detail_by_hash(&self, hash: &str) -> Option<TransactionDetail>;
+    /// Lowest and highest `seq_id` of events ingested within an inclusive
+    /// unix-ms range.
+    fn seq_id_range_for_time(
+        &self,
+        from_ts_unix_ms: Option<i64>,
+        to_ts_unix_ms: Option<i64>,
+    ) -> Option<RangeInclusive<u64>> {
+        let scan = self.filtered_events(
+            0,
+            &EventFilter::time_range(from_ts_unix_ms, to_ts_unix_ms),
+            usize::MAX,
+            usize::MAX,
+        );
+        let first = scan.events.iter().map(|event| event.seq_id).min()?;
+        let last = scan.events.iter().map(|event| event.seq_id).max()?;
+        Some(first..=last)
+    }
+    /// One newest-first page of retained transactions matching a secondary
+    /// index key.
+    fn indexed_transactions(
+        &self,
+        _key: &TxIndexKey,
+        _query: &IndexPageQuery,
+    ) -> IndexPage<TransactionDetail> {
+        IndexPage::default()
+    }
+    /// One newest-first page of retained opportunities for a protocol.
+    fn opportunities_by_protocol(
+        &self,
+        _protocol: &str,
+        _query: &IndexPageQuery,
+    ) -> IndexPage<OpportunityDetail> {
+        IndexPage::default()
+    }
     fn market_stats(&self) -> MarketStats;
     fn dashboard_cache_metrics(&self) -> DashboardCacheMetrics;
+    /// WAL write and fsync counters of the storage writer feeding this provider.
+    fn storage_wal_metrics(&self) -> WalMetricsSnapshot {
+        WalMetricsSnapshot::default()
+    }
+    /// Delivery health of the ClickHouse sink, when one is configured.
+    fn clickhouse_sink_health(&self) -> Option<ClickHouseSinkHealth> {
+        None
+    }
+    /// Progress of the storage writer's backfill, when one was configured.
+    fn storage_backfill_progress(&self) -> Option<BackfillProgress> {
+        None
+    }
+    /// Delivery counters of the storage writer's event sinks.
+    fn event_sink_stats(&self) -> Vec<EventSinkStats> {
+        Vec::new()
+    }
+    /// Estimated bytes per storage table, when backed by in-memory storage.
+    fn storage_memory_usage(&self) -> Option<StorageMemoryUsage> {
+        None
+    }
+    /// First-seen share, lag, unique-hash and drop statistics per peer or
+    /// source over the last `window_ms`.
+    fn peer_observation_stats(
+        &self,
+        _group: ObserverGroup,
+        _window_ms: u64,
+    ) -> Vec<PeerObservationStats> {
+        Vec::new()
+    }
+    /// Propagation edges observed over the last `window_ms`; empty when no
+    /// hash was seen by more than one observer.
+    fn observed_propagation_edges(
+        &self,
+        _group: ObserverGroup,
+        _window_ms: u64,
+    ) -> Vec<PropagationEdge> {
+        Vec::new()
+    }
     #[must_use]
     fn dashboard_snapshot_v2(
         &self,
@@ -641,39 +915,34 @@ pub trait VizDataProvider: Send + Sync {
         let feature_limit = feature_limit.max(1);
         let opp_limit = opp_limit.max(1);
         let summary_limit = summary_limit.max(1);
-        let feature_scan_limit = chain_filter_scan_limit(feature_limit, chain_id);
-        let opp_scan_limit = chain_filter_scan_limit(opp_limit, chain_id);
-        let tx_scan_limit = chain_filter_scan_limit(tx_limit, chain_id);
+        let filter = chain_id.map(EventFilter::chain);
+        let filter = filter.as_ref();
+        let feature_scan_limit = row_scan_limit(feature_limit, filter);
+        let opp_scan_limit = row_scan_limit(opp_limit, filter);
+        let tx_scan_limit = row_scan_limit(tx_limit, filter);
         let latest_seq_id = self.latest_seq_id().unwrap_or(0);
         let feature_summary = self.feature_summary();
 
         DashboardSnapshotV2 {
             revision: latest_seq_id,
             latest_seq_id,
-            opportunities: filter_opportunities_by_chain(
+            opportunities: filter_rows(
                 self.opportunities(opp_scan_limit, min_score),
-                chain_id,
+                filter,
                 opp_limit,
             ),
             feature_summary: downsample(&feature_summary, summary_limit),
-            feature_details: filter_feature_details_by_chain(
+            feature_details: filter_rows(
                 self.feature_details(feature_scan_limit),
-                chain_id,
+                filter,
                 feature_limit,
             ),
-            transactions: self
-                .recent_transactions(tx_scan_limit)
-                .into_iter()
-                .filter(|row| {
-                    if chain_id.is_none() {
-                        return true;
-                    }
-                    self.transaction_detail_by_hash(&row.hash)
-                        .map(|detail| chain_matches_filter(detail.chain_id, chain_id))
-                        .unwrap_or(false)
-                })
-                .take(tx_limit)
-                .collect(),
+            transactions: filter_transaction_summaries(
+                self,
+                self.recent_transactions(tx_scan_limit),
+                filter,
+                tx_limit,
+            ),
             chain_ingest_status: Vec::new(),
             market_stats: self.market_stats(),
         }
@@ -688,8 +957,16 @@ pub struct InMemoryVizProvider {
     propagation: Arc<Vec<PropagationEdge>>,
     replay_stride: usize,
     dashboard_cache: Arc<RwLock<DashboardReadCache>>,
+    wal_metrics: Option<Arc<WalMetrics>>,
+    clickhouse_sink_health: Option<ClickHouseSinkHealthProvider>,
+    backfill_progress: Option<BackfillProgressProvider>,
+    event_sink_stats: Option<EventSinkStatsProvider>,
 }
 
+type ClickHouseSinkHealthProvider = Arc<dyn Fn() -> Option<ClickHouseSinkHealth> + Send + Sync>;
+type BackfillProgressProvider = Arc<dyn Fn() -> Option<BackfillProgress> + Send + Sync>;
+type EventSinkStatsProvider = Arc<dyn Fn() -> Vec<EventSinkStats> + Send + Sync>;
+
 #[derive(Clone, Debug, Default)]
 struct DashboardReadCache {
     revision: Option<u64>,
@@ -715,9 +992,38 @@ impl InMemoryVizProvider {
             propagation,
             replay_stride: replay_stride.max(1),
             dashboard_cache: Arc::new(RwLock::new(DashboardReadCache::default())),
+            wal_metrics: None,
+            clickhouse_sink_health: None,
+            backfill_progress: None,
+            event_sink_stats: None,
         }
     }
 
+    /// Reports the given storage writer WAL counters through `/metrics`.
+    pub fn with_wal_metrics(mut self, wal_metrics: Arc<WalMetrics>) -> Self {
+        self.wal_metrics = Some(wal_metrics);
+        self
+    }
+
+    /// Reports ClickHouse sink health through `/health` and `/metrics`.
+    pub fn with_clickhouse_sink_health(mut self, provider: ClickHouseSinkHealthProvider) -> Self {
+        self.clickhouse_sink_health = Some(provider);
+        self
+    }
+
+    /// Reports storage backfill progress through `/backfill/status` and
+    /// `/metrics`.
+    pub fn with_backfill_progress(mut self, provider: BackfillProgressProvider) -> Self {
+        self.backfill_progress = Some(provider);
+        self
+    }
+
+    /// Reports event sink delivery counters through `/metrics`.
+    pub fn with_event_sink_stats(mut self, provider: EventSinkStatsProvider) -> Self {
+        self.event_sink_stats = Some(provider);
+        self
+    }
+
     /// Returns how many times the dashboard read cache has been rebuilt.
     pub fn dashboard_cache_refreshes(&self) -> u64 {
         self.dashboard_cache.read().refreshes
@@ -878,23 +1184,27 @@ fn build_opportunities(storage: &InMemoryStorage) -> Vec<OpportunityDetail> {
     storage
         .opportunities()
         .into_iter()
-        .map(|row| OpportunityDetail {
-            tx_hash: format_bytes(&row.tx_hash),
-            status: "detected".to_owned(),
-            strategy: row.strategy,
-            score: row.score,
-            protocol: row.protocol,
-            category: row.category,
-            chain_id: row.chain_id,
-            feature_engine_version: row.feature_engine_version,
-            scorer_version: row.scorer_version,
-            strategy_version: row.strategy_version,
-            reasons: row.reasons,
-            detected_unix_ms: row.detected_unix_ms,
-        })
+        .map(opportunity_detail_from_record)
         .collect()
 }
 
+fn opportunity_detail_from_record(row: OpportunityRecord) -> OpportunityDetail {
+    OpportunityDetail {
+        tx_hash: format_bytes(&row.tx_hash),
+        status: "detected".to_owned(),
+        strategy: row.strategy,
+        score: row.score,
+        protocol: row.protocol,
+        category: row.category,
+        chain_id: row.chain_id,
+        feature_engine_version: row.feature_engine_version,
+        scorer_version: row.scorer_version,
+        strategy_version: row.strategy_version,
+        reasons: row.reasons,
+        detected_unix_ms: row.detected_unix_ms,
+    }
+}
+
 impl VizDataProvider for InMemoryVizProvider {
     fn events(
         &self,
@@ -902,26 +1212,71 @@ impl VizDataProvider for InMemoryVizProvider {
         event_types: &[String],
         limit: usize,
     ) -> Vec<EventEnvelope> {
-        let storage = self.storage.read();
-        storage
+        if event_types.is_empty() {
+            return self.storage.scan_events(after_seq_id, limit);
+        }
+        self.storage
             .scan_events(after_seq_id, limit.saturating_mul(2).max(limit))
             .into_iter()
-            .filter(|event| {
-                event_types.is_empty()
-                    || event_types
-                        .iter()
-                        .any(|kind| event_payload_type(&event.payload).eq_ignore_ascii_case(kind))
-            })
+            .filter(|event| event_matches_types(event, event_types))
+            .take(limit)
+            .collect()
+    }
+
+    fn chain_events(
+        &self,
+        chain_id: u64,
+        after_chain_seq_id: u64,
+        event_types: &[String],
+        limit: usize,
+    ) -> Vec<EventEnvelope> {
+        self.storage
+            .scan_chain_events(
+                chain_id,
+                after_chain_seq_id,
+                limit.saturating_mul(2).max(limit),
+            )
+            .into_iter()
+            .filter(|event| event_matches_types(event, event_types))
             .take(limit)
             .collect()
     }
 
+    fn filtered_events(
+        &self,
+        after_seq_id: u64,
+        filter: &EventFilter,
+        limit: usize,
+        scan_budget: usize,
+    ) -> FilteredScan {
+        self.storage
+            .read()
+            .scan_filtered_events(filter, after_seq_id, limit, scan_budget)
+    }
+
+    fn filtered_chain_events(
+        &self,
+        chain_id: u64,
+        after_chain_seq_id: u64,
+        filter: &EventFilter,
+        limit: usize,
+        scan_budget: usize,
+    ) -> FilteredScan {
+        self.storage.read().scan_filtered_chain_events(
+            chain_id,
+            filter,
+            after_chain_seq_id,
+            limit,
+            scan_budget,
+        )
+    }
+
     fn replay_points(&self) -> Vec<ReplayPoint> {
         self.dashboard_cache_snapshot().replay_points
     }
 
     fn latest_seq_id(&self) -> Option<u64> {
-        self.storage.read().latest_seq_id()
+        self.storage.latest_seq_id()
     }
 
     fn propagation_edges(&self) -> Vec<PropagationEdge> {
@@ -987,6 +1342,62 @@ impl VizDataProvider for InMemoryVizProvider {
         }
     }
 
+    fn storage_wal_metrics(&self) -> WalMetricsSnapshot {
+        self.wal_metrics
+            .as_ref()
+            .map(|metrics| metrics.snapshot())
+            .unwrap_or_default()
+    }
+
+    fn clickhouse_sink_health(&self) -> Option<ClickHouseSinkHealth> {
+        self.clickhouse_sink_health
+            .as_ref()
+            .and_then(|provider| provider())
+    }
+
+    fn storage_backfill_progress(&self) -> Option<BackfillProgress> {
+        self.backfill_progress
+            .as_ref()
+            .and_then(|provider| provider())
+    }
+
+    fn event_sink_stats(&self) -> Vec<EventSinkStats> {
+        self.event_sink_stats
+            .as_ref()
+            .map(|provider| provider())
+            .unwrap_or_default()
+    }
+
+    fn storage_memory_usage(&self) -> Option<StorageMemoryUsage> {
+        Some(self.storage.read().memory_usage())
+    }
+
+    fn peer_observation_stats(
+        &self,
+        group: ObserverGroup,
+        window_ms: u64,
+    ) -> Vec<PeerObservationStats> {
+        self.storage.read().peer_observation_stats(group, window_ms)
+    }
+
+    fn observed_propagation_edges(
+        &self,
+        group: ObserverGroup,
+        window_ms: u64,
+    ) -> Vec<PropagationEdge> {
+        self.storage
+            .read()
+            .observed_propagation_edges(group, window_ms)
+            .into_iter()
+            .map(|edge| PropagationEdge {
+                source: edge.source,
+                destination: edge.destination,
+                p50_delay_ms: u32::try_from(edge.p50_lag_ms).unwrap_or(u32::MAX),
+                p99_delay_ms: u32::try_from(edge.p99_lag_ms).unwrap_or(u32::MAX),
+            })
+            .collect()
+    }
+
     fn dashboard_snapshot_v2(
         &self,
         tx_limit: usize,
@@ -1000,9 +1411,11 @@ impl VizDataProvider for InMemoryVizProvider {
         let feature_limit = feature_limit.max(1);
         let opp_limit = opp_limit.max(1);
         let summary_limit = summary_limit.max(1);
-        let feature_scan_limit = chain_filter_scan_limit(feature_limit, chain_id);
-        let opp_scan_limit = chain_filter_scan_limit(opp_limit, chain_id);
-        let tx_scan_limit = chain_filter_scan_limit(tx_limit, chain_id);
+        let filter = chain_id.map(EventFilter::chain);
+        let filter = filter.as_ref();
+        let feature_scan_limit = row_scan_limit(feature_limit, filter);
+        let opp_scan_limit = row_scan_limit(opp_limit, filter);
+        let tx_scan_limit = row_scan_limit(tx_limit, filter);
 
         let storage = self.storage.read();
         let feature_summary = build_feature_summary(&storage)
@@ -1010,20 +1423,16 @@ impl VizDataProvider for InMemoryVizProvider {
             .take(summary_limit)
             .collect::<Vec<_>>();
 
-        let feature_details = build_feature_details(&storage)
-            .into_iter()
-            .take(feature_scan_limit)
-            .filter(|row| chain_matches_filter(row.chain_id, chain_id))
-            .take(feature_limit)
-            .collect::<Vec<_>>();
+        let mut feature_details = build_feature_details(&storage);
+        feature_details.truncate(feature_scan_limit);
+        let feature_details = filter_rows(feature_details, filter, feature_limit);
 
         let opportunities = build_opportunities(&storage)
             .into_iter()
             .filter(|row| row.score >= min_score)
             .take(opp_scan_limit)
-            .filter(|row| chain_matches_filter(row.chain_id, chain_id))
-            .take(opp_limit)
             .collect::<Vec<_>>();
+        let opportunities = filter_rows(opportunities, filter, opp_limit);
 
         let mut chain_id_by_hash = HashMap::new();
         for row in storage.tx_full() {
@@ -1033,18 +1442,25 @@ impl VizDataProvider for InMemoryVizProvider {
         let mut transactions = Vec::with_capacity(tx_limit);
         for row in storage.recent_transactions(tx_scan_limit) {
             let row_chain_id = chain_id_by_hash.get(&row.hash).copied().flatten();
-            if !chain_matches_filter(row_chain_id, chain_id) {
-                continue;
-            }
-
-            transactions.push(TransactionSummary {
+            let summary = TransactionSummary {
                 hash: format_bytes(&row.hash),
                 sender: format_bytes(&row.sender),
                 nonce: row.nonce,
                 tx_type: row.tx_type,
                 seen_unix_ms: row.seen_unix_ms,
                 source_id: row.source_id,
+            };
+            let matches = filter.is_none_or(|filter| {
+                filter.matches(&TransactionRow {
+                    summary: &summary,
+                    chain_id: row_chain_id,
+                    detail: None,
+                })
             });
+            if !matches {
+                continue;
+            }
+            transactions.push(summary);
             if transactions.len() >= tx_limit {
                 break;
             }
@@ -1104,7 +1520,7 @@ impl VizDataProvider for InMemoryVizProvider {
                 max_priority_fee_per_gas_wei: full.and_then(|row| row.max_priority_fee_per_gas_wei),
                 max_fee_per_blob_gas_wei: full.and_then(|row| row.max_fee_per_blob_gas_wei),
                 calldata_len: full.and_then(|row| row.calldata_len),
-                raw_tx_len: full.map(|row| row.raw_tx.len()),
+                raw_tx_len: full.and_then(|row| storage.tx_calldata_len(&row.hash)),
                 lifecycle_status: lifecycle.map(|row| row.status.clone()),
                 lifecycle_reason: lifecycle.and_then(|row| row.reason.clone()),
                 lifecycle_updated_unix_ms: lifecycle.map(|row| row.updated_unix_ms),
@@ -1124,23 +1540,12 @@ impl VizDataProvider for InMemoryVizProvider {
 
     fn transaction_detail_by_hash(&self, hash: &str) -> Option<TransactionDetail> {
         let hash = live_rpc::parse_fixed_hex::<32>(hash)?;
+        let lookup = self.storage.transaction_lookup(&hash);
         let storage = self.storage.read();
-        let seen = storage
-            .tx_seen()
-            .iter()
-            .rev()
-            .find(|row| row.hash == hash)?;
-        let full = storage.tx_full().iter().rev().find(|row| row.hash == hash);
-        let feature = storage
-            .tx_features()
-            .iter()
-            .rev()
-            .find(|row| row.hash == hash);
-        let lifecycle = storage
-            .tx_lifecycle()
-            .iter()
-            .rev()
-            .find(|row| row.hash == hash);
+        let seen = lookup.seen.as_ref()?;
+        let full = lookup.full.as_ref();
+        let feature = lookup.features.as_ref();
+        let lifecycle = lookup.lifecycle.as_ref();
         let fallback_lifecycle = lifecycle
             .is_none()
             .then(|| current_lifecycle(&storage.list_events(), hash));
@@ -1171,7 +1576,11 @@ impl VizDataProvider for InMemoryVizProvider {
             max_priority_fee_per_gas_wei: full.and_then(|row| row.max_priority_fee_per_gas_wei),
             max_fee_per_blob_gas_wei: full.and_then(|row| row.max_fee_per_blob_gas_wei),
             calldata_len: full.and_then(|row| row.calldata_len),
-            raw_tx_len: full.map(|row| row.raw_tx.len()),
+            // The lookup row may come from the cold store, which keeps
+            // calldata inline.
+            raw_tx_len: storage
+                .tx_calldata_len(&hash)
+                .or(full.map(|row| row.raw_tx.len())),
             lifecycle_status,
             lifecycle_reason,
             lifecycle_updated_unix_ms: lifecycle.map(|row| row.updated_unix_ms),
@@ -1183,6 +1592,51 @@ impl VizDataProvider for InMemoryVizProvider {
         })
     }
 
+    fn seq_id_range_for_time(
+        &self,
+        from_ts_unix_ms: Option<i64>,
+        to_ts_unix_ms: Option<i64>,
+    ) -> Option<RangeInclusive<u64>> {
+        self.storage
+            .read()
+            .seq_id_range_for_time(from_ts_unix_ms, to_ts_unix_ms)
+    }
+
+    fn indexed_transactions(
+        &self,
+        key: &TxIndexKey,
+        query: &IndexPageQuery,
+    ) -> IndexPage<TransactionDetail> {
+        let page = self.storage.read().transactions_by_index(key, query);
+        IndexPage {
+            rows: page
+                .rows
+                .iter()
+                .filter_map(|hash| self.transaction_detail_by_hash(&format_bytes(hash)))
+                .collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+
+    fn opportunities_by_protocol(
+        &self,
+        protocol: &str,
+        query: &IndexPageQuery,
+    ) -> IndexPage<OpportunityDetail> {
+        let page = self
+            .storage
+            .read()
+            .opportunities_by_protocol(protocol, query);
+        IndexPage {
+            rows: page
+                .rows
+                .into_iter()
+                .map(opportunity_detail_from_record)
+                .collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+
     fn metric_snapshot(&self) -> MetricSnapshot {
         let storage = self.storage.read();
         let tx_seen_len = storage.tx_seen().len() as u64;
@@ -1213,26 +1667,35 @@ pub fn build_router(state: AppState) -> Router {
 
     let protected = Router::new()
         .route("/events", get(events))
+        .route("/events/stream", get(events_stream))
+        .route("/events/seq-range", get(events_seq_range))
         .route("/replay", get(replay))
         .route("/propagation", get(propagation))
+        .route("/propagation/peers", get(propagation_peers))
         .route("/metrics/snapshot", get(metrics_snapshot))
         .route("/alerts/evaluate", get(alerts_evaluate))
         .route("/features", get(features))
         .route("/features/recent", get(features_recent))
         .route("/opps", get(opportunities))
         .route("/opps/recent", get(opportunities_recent))
+        .route("/opps/by/{field}/{value}", get(opportunities_by_index))
         .route("/dashboard/snapshot-v2", get(dashboard_snapshot_v2))
         .route("/tx/{hash}", get(transaction_by_hash))
         .route("/sim/{id}", get(sim_by_id))
         .route("/bundle/{id}", get(bundle_by_id))
         .route("/transactions", get(transactions))
         .route("/transactions/all", get(transactions_all))
+        .route(
+            "/transactions/by/{field}/{value}",
+            get(transactions_by_index),
+        )
         .route("/transactions/{hash}", get(transaction_by_hash))
         .route("/scheduler/snapshot", get(scheduler_snapshot))
         .route("/scheduler/metrics", get(scheduler_metrics))
         .route("/builder/snapshot", get(builder_snapshot))
         .route("/builder/metrics", get(builder_metrics))
         .route("/relay/dry-run/status", get(relay_dry_run_status))
+        .route("/backfill/status", get(backfill_status))
         .route("/dashboard/events-v1", get(events_v1))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
@@ -1249,16 +1712,51 @@ pub fn build_router(state: AppState) -> Router {
 
 /// Builds default application state together with the runtime bootstrap bundle.
 pub fn default_state_with_runtime() -> (AppState, RuntimeBootstrap) {
-    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let storage = Arc::new(RwLock::new(default_storage()));
     default_state_with_runtime_from_storage(storage)
 }
 
 /// Builds only the runtime bootstrap bundle with default storage.
 pub fn default_runtime_bootstrap() -> RuntimeBootstrap {
-    let storage = Arc::new(RwLock::new(InMemoryStorage::default()));
+    let storage = Arc::new(RwLock::new(default_storage()));
     runtime_bootstrap_from_storage(storage)
 }
 
+/// Default in-memory storage with the memory budget from the environment,
+/// offloading calldata to `VIZ_API_CALLDATA_BLOB_PATH` when set and put in
+/// front of the disk store named by `VIZ_API_DISK_STORE_PATH` when built with
+/// the `disk-store` feature.
+fn default_storage() -> InMemoryStorage {
+    let mut storage = InMemoryStorage::with_config(resolve_storage_config_from(
+        env::var(ENV_STORAGE_MEMORY_BUDGET_BYTES).ok().as_deref(),
+        env::var(ENV_STORAGE_TABLE_WEIGHTS).ok().as_deref(),
+    ));
+    if let Some(path) = env::var(ENV_CALLDATA_BLOB_PATH)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        match CalldataBlobStore::create(&path) {
+            Ok(blobs) => storage = storage.with_calldata_blobs(Arc::new(blobs)),
+            Err(err) => {
+                tracing::warn!(error = %err, path, "failed to create calldata blob file; keeping calldata in memory");
+            }
+        }
+    }
+    #[cfg(feature = "disk-store")]
+    if let Some(path) = env::var(ENV_DISK_STORE_PATH)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+    {
+        match storage::DiskEventStore::open(&path) {
+            Ok(disk) => return storage.with_cold_store(Arc::new(disk)),
+            Err(err) => {
+                tracing::warn!(error = %err, path, "failed to open disk store; keeping events in memory only");
+            }
+        }
+    }
+    storage
+}
+
 /// Builds application state and bootstrap wiring from an existing storage instance.
 pub fn default_state_with_runtime_from_storage(
     storage: Arc<RwLock<InMemoryStorage>>,
@@ -1318,8 +1816,15 @@ pub fn app_state_from_runtime_bootstrap(
     bootstrap: &RuntimeBootstrap,
     runtime_views: RuntimeCoreViewProviders,
 ) -> AppState {
+    let writer = bootstrap.writer.clone();
+    let backfill_writer = bootstrap.writer.clone();
+    let event_sink_writer = bootstrap.writer.clone();
     build_app_state(
         bootstrap.storage.clone(),
+        bootstrap.writer.wal_metrics(),
+        Arc::new(move || writer.sink_health()),
+        Arc::new(move || backfill_writer.backfill_progress()),
+        Arc::new(move || event_sink_writer.event_sink_stats()),
         runtime_views,
         replay_runtime_metrics_provider(bootstrap.replay_runtime_metrics_cache.clone()),
     )
@@ -1327,6 +1832,10 @@ pub fn app_state_from_runtime_bootstrap(
 
 fn build_app_state(
     storage: Arc<RwLock<InMemoryStorage>>,
+    wal_metrics: Arc<WalMetrics>,
+    clickhouse_sink_health: ClickHouseSinkHealthProvider,
+    backfill_progress: BackfillProgressProvider,
+    event_sink_stats: EventSinkStatsProvider,
     runtime_views: RuntimeCoreViewProviders,
     replay_runtime_metrics_provider: Arc<dyn Fn() -> ReplayRuntimeMetricsSnapshot + Send + Sync>,
 ) -> AppState {
@@ -1352,7 +1861,13 @@ fn build_app_state(
         );
     }
     let api_rate_limiter = ApiRateLimiter::new(api_auth.requests_per_minute);
-    let provider = Arc::new(InMemoryVizProvider::new(storage, Arc::new(propagation), 1));
+    let provider = Arc::new(
+        InMemoryVizProvider::new(storage, Arc::new(propagation), 1)
+            .with_wal_metrics(wal_metrics)
+            .with_clickhouse_sink_health(clickhouse_sink_health)
+            .with_backfill_progress(backfill_progress)
+            .with_event_sink_stats(event_sink_stats),
+    );
     let dashboard_stream_broadcaster = dashboard_stream_broadcaster(provider.clone());
     AppState {
         provider,
@@ -1438,14 +1953,30 @@ fn runtime_bootstrap_from_storage_and_rehydration_impl(
     rehydration: SchedulerRehydrationConfig,
 ) -> RuntimeBootstrap {
     let sink: Arc<dyn ClickHouseBatchSink> = match ClickHouseHttpSink::from_env() {
-        Ok(Some(sink)) => Arc::new(sink),
+        Ok(Some(sink)) => {
+            match ResilientClickHouseSink::spawn(sink, resolve_clickhouse_sink_config()) {
+                Ok(sink) => sink,
+                Err(err) => {
+                    tracing::warn!(error = %err, "failed to open clickhouse spill directory, falling back to noop sink");
+                    Arc::new(NoopClickHouseSink)
+                }
+            }
+        }
         Ok(None) => Arc::new(NoopClickHouseSink),
         Err(err) => {
             tracing::warn!(error = %err, "failed to initialize clickhouse sink, falling back to noop sink");
             Arc::new(NoopClickHouseSink)
         }
     };
-    let writer = spawn_single_writer(storage.clone(), sink, StorageWriterConfig::default());
+    let writer = match resolve_backfill_job() {
+        Some(job) => spawn_single_writer_with_backfill(
+            storage.clone(),
+            sink,
+            resolve_storage_writer_config(),
+            job,
+        ),
+        None => spawn_single_writer(storage.clone(), sink, resolve_storage_writer_config()),
+    };
     let rehydration_plan = storage
         .read()
         .scheduler_rehydration_plan(rehydration.snapshot_max_finality_age_ms);
@@ -1520,7 +2051,7 @@ pub fn spawn_scheduler_snapshot_writer(
             ticker.tick().await;
             match writer.try_reserve() {
                 Ok(permit) => {
-                    let event_seq_hi = storage.read().latest_seq_id().unwrap_or(0);
+                    let event_seq_hi = storage.latest_seq_id().unwrap_or(0);
                     let mut snapshot =
                         scheduler.persisted_snapshot(current_unix_ms(), runtime_core.mono_ns());
                     snapshot.event_seq_hi = event_seq_hi;
@@ -1634,7 +2165,9 @@ fn sanitize_scheduler_snapshot_for_rehydration(
             | EventPayload::AssemblyDecisionApplied(_)
             | EventPayload::BundleSubmitted(_)
             | EventPayload::TxReady(_)
-            | EventPayload::TxBlocked(_) => {}
+            | EventPayload::TxBlocked(_)
+            | EventPayload::UserOpSeen(_)
+            | EventPayload::ChainCheckpoint(_) => {}
         }
     }
 
@@ -1659,6 +2192,200 @@ fn sanitize_scheduler_snapshot_for_rehydration(
     snapshot
 }
 
+fn resolve_storage_writer_config() -> StorageWriterConfig {
+    StorageWriterConfig {
+        wal_path: env::var(ENV_STORAGE_WAL_PATH)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(PathBuf::from),
+        wal_durability: resolve_wal_durability(
+            env::var(ENV_STORAGE_WAL_DURABILITY).ok().as_deref(),
+        ),
+        checkpoint: resolve_storage_checkpoint_config(
+            env::var(ENV_STORAGE_CHECKPOINT_INTERVAL_MS).ok().as_deref(),
+            env::var(ENV_STORAGE_WAL_RETENTION_MAX_AGE_MS)
+                .ok()
+                .as_deref(),
+            env::var(ENV_STORAGE_WAL_RETENTION_MAX_BYTES)
+                .ok()
+                .as_deref(),
+        ),
+        event_sinks: resolve_event_sinks(env::var(ENV_EVENT_SINKS).ok().as_deref()),
+        ..StorageWriterConfig::default()
+    }
+}
+
+/// Registers the sinks of the JSON list in `VIZ_API_EVENT_SINKS`; a sink that
+/// fails to open is skipped, an unparsable list disables them all.
+fn resolve_event_sinks(raw: Option<&str>) -> EventSinkFanout {
+    let Some(raw) = raw.map(str::trim).filter(|value| !value.is_empty()) else {
+        return EventSinkFanout::default();
+    };
+    let specs = match serde_json::from_str::<Vec<EventSinkSpec>>(raw) {
+        Ok(specs) => specs,
+        Err(err) => {
+            tracing::warn!(error = %err, "invalid event sink list; starting without event sinks");
+            return EventSinkFanout::default();
+        }
+    };
+    specs
+        .into_iter()
+        .fold(EventSinkFanout::default(), |fanout, spec| {
+            match spec.open() {
+                Ok((sink, options)) => fanout.with_sink(sink, options),
+                Err(err) => {
+                    tracing::warn!(error = %err, "failed to open event sink; skipping it");
+                    fanout
+                }
+            }
+        })
+}
+
+/// Backfills from `VIZ_API_BACKFILL_CLICKHOUSE_URL`, or failing that from the
+/// WAL at `VIZ_API_BACKFILL_WAL_PATH`, when either is set.
+fn resolve_backfill_job() -> Option<BackfillJob> {
+    let env_value = |key: &str| {
+        env::var(key)
+            .ok()
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    };
+    let range = resolve_backfill_range_from(
+        env::var(ENV_BACKFILL_AFTER_SEQ_ID).ok().as_deref(),
+        env::var(ENV_BACKFILL_TO_SEQ_ID).ok().as_deref(),
+        env::var(ENV_BACKFILL_FROM_TS).ok().as_deref(),
+        env::var(ENV_BACKFILL_TO_TS).ok().as_deref(),
+    );
+    let job = if let Some(url) = env_value(ENV_BACKFILL_CLICKHOUSE_URL) {
+        ClickHouseBackfillSource::new(url, range).map(BackfillJob::new)
+    } else {
+        WalBackfillSource::open(env_value(ENV_BACKFILL_WAL_PATH)?, range).map(BackfillJob::new)
+    };
+    match job {
+        Ok(job) => Some(match env_value(ENV_BACKFILL_CURSOR_PATH) {
+            Some(path) => job.with_cursor_path(path),
+            None => job,
+        }),
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to open backfill source; starting without backfill");
+            None
+        }
+    }
+}
+
+/// Unset or unparsable bounds leave that side of the range open.
+fn resolve_backfill_range_from(
+    after_seq_id: Option<&str>,
+    to_seq_id: Option<&str>,
+    from_ts_unix_ms: Option<&str>,
+    to_ts_unix_ms: Option<&str>,
+) -> BackfillRange {
+    let seq_id = |raw: Option<&str>| raw.and_then(|value| value.trim().parse::<u64>().ok());
+    let ts = |raw: Option<&str>| raw.and_then(|value| value.trim().parse::<i64>().ok());
+    BackfillRange {
+        after_seq_id: seq_id(after_seq_id).unwrap_or(0),
+        to_seq_id: seq_id(to_seq_id),
+        from_ts_unix_ms: ts(from_ts_unix_ms),
+        to_ts_unix_ms: ts(to_ts_unix_ms),
+    }
+}
+
+/// Checkpointing is enabled by a positive interval; retention bounds only
+/// apply when it is.
+fn resolve_storage_checkpoint_config(
+    interval_ms: Option<&str>,
+    max_age_ms: Option<&str>,
+    max_bytes: Option<&str>,
+) -> Option<StorageCheckpointConfig> {
+    let parse = |raw: Option<&str>| raw.and_then(|value| value.trim().parse::<u64>().ok());
+    let interval_ms = parse(interval_ms).filter(|value| *value > 0)?;
+    Some(StorageCheckpointConfig {
+        interval_ms,
+        retention: WalRetention {
+            max_age_ms: parse(max_age_ms),
+            max_bytes: parse(max_bytes),
+        },
+    })
+}
+
+fn resolve_wal_durability(raw: Option<&str>) -> WalDurability {
+    match raw.map(str::parse::<WalDurability>) {
+        Some(Ok(durability)) => durability,
+        Some(Err(err)) => {
+            tracing::warn!(error = %err, "invalid WAL durability; using default");
+            WalDurability::default()
+        }
+        None => WalDurability::default(),
+    }
+}
+
+fn resolve_clickhouse_sink_config() -> ResilientClickHouseSinkConfig {
+    resolve_clickhouse_sink_config_from(
+        env::var(ENV_CLICKHOUSE_MAX_ATTEMPTS).ok().as_deref(),
+        env::var(ENV_CLICKHOUSE_SPILL_DIR).ok().as_deref(),
+        env::var(ENV_CLICKHOUSE_SPILL_MAX_BYTES).ok().as_deref(),
+    )
+}
+
+/// Spilling is enabled by a non-empty directory; the byte cap only applies
+/// when it is.
+fn resolve_clickhouse_sink_config_from(
+    max_attempts: Option<&str>,
+    spill_dir: Option<&str>,
+    spill_max_bytes: Option<&str>,
+) -> ResilientClickHouseSinkConfig {
+    let defaults = ClickHouseRetryConfig::default();
+    let spill = spill_dir
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| {
+            let spill = ClickHouseSpillConfig::new(dir);
+            ClickHouseSpillConfig {
+                max_bytes: spill_max_bytes
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+                    .filter(|value| *value > 0)
+                    .unwrap_or(spill.max_bytes),
+                ..spill
+            }
+        });
+    ResilientClickHouseSinkConfig {
+        retry: ClickHouseRetryConfig {
+            max_attempts: max_attempts
+                .and_then(|value| value.trim().parse::<u32>().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(defaults.max_attempts),
+            ..defaults
+        },
+        spill,
+    }
+}
+
+/// A positive byte budget enables budgeted retention; table weights that do
+/// not parse fall back to the defaults.
+fn resolve_storage_config_from(
+    memory_budget_bytes: Option<&str>,
+    table_weights: Option<&str>,
+) -> StorageConfig {
+    let memory_budget = memory_budget_bytes
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .map(|max_bytes| MemoryBudgetConfig {
+            max_bytes,
+            weights: table_weights
+                .map(|spec| {
+                    TableWeights::parse(spec).unwrap_or_else(|err| {
+                        tracing::warn!(error = %err, "invalid storage table weights; using defaults");
+                        TableWeights::default()
+                    })
+                })
+                .unwrap_or_default(),
+        });
+    StorageConfig {
+        memory_budget,
+        ..StorageConfig::default()
+    }
+}
+
 fn resolve_scheduler_snapshot_interval_ms(raw: Option<&str>) -> u64 {
     raw.and_then(|value| value.trim().parse::<u64>().ok())
         .filter(|value| *value > 0)
@@ -1707,10 +2434,7 @@ fn validated_transaction_from_event(
             source_id: event.source_id.clone(),
             observed_at_unix_ms: event.ingest_ts_unix_ms,
             observed_at_mono_ns: event.ingest_ts_mono_ns,
-            calldata: storage
-                .tx_full_by_hash(&decoded.hash)
-                .map(|row| row.raw_tx.clone())
-                .unwrap_or_default(),
+            calldata: storage.tx_calldata(&decoded.hash).unwrap_or_default(),
             decoded: decoded.clone(),
         }),
         EventPayload::TxReorged(reorged) => storage.tx_full_by_hash(&reorged.hash).map(|row| {
@@ -1721,6 +2445,7 @@ fn validated_transaction_from_event(
                 event.ingest_ts_unix_ms,
                 event.ingest_ts_mono_ns,
                 row,
+                storage.tx_calldata(&row.hash).unwrap_or_default(),
             )
         }),
         _ => None,
@@ -1732,12 +2457,16 @@ fn validated_transaction_from_storage_row(
     observed_at_unix_ms: i64,
     observed_at_mono_ns: u64,
     row: &TxFullRecord,
+    calldata: Vec<u8>,
 ) -> ValidatedTransaction {
+    let calldata_len = row
+        .calldata_len
+        .or(Some(calldata.len().min(u32::MAX as usize) as u32));
     ValidatedTransaction {
         source_id: source_id.clone(),
         observed_at_unix_ms,
         observed_at_mono_ns,
-        calldata: row.raw_tx.clone(),
+        calldata,
         decoded: event_log::TxDecoded {
             hash: row.hash,
             tx_type: row.tx_type,
@@ -1751,9 +2480,7 @@ fn validated_transaction_from_storage_row(
             max_fee_per_gas_wei: row.max_fee_per_gas_wei,
             max_priority_fee_per_gas_wei: row.max_priority_fee_per_gas_wei,
             max_fee_per_blob_gas_wei: row.max_fee_per_blob_gas_wei,
-            calldata_len: row
-                .calldata_len
-                .or(Some(row.raw_tx.len().min(u32::MAX as usize) as u32)),
+            calldata_len,
         },
     }
 }
@@ -1780,6 +2507,9 @@ fn seed_decoded_event(seq_id: u64, hash_seed: u64, nonce: u64) -> EventEnvelope
             max_fee_per_blob_gas_wei: None,
             calldata_len: None,
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -1814,7 +2544,7 @@ fn map_lifecycle_status(status: &TxLifecycleStatus) -> (String, Option<String>)
     match status {
         TxLifecycleStatus::Pending => ("pending".to_owned(), None),
         TxLifecycleStatus::Replaced { by } => ("replaced".to_owned(), Some(format_bytes(by))),
-        TxLifecycleStatus::Dropped { reason } => ("dropped".to_owned(), Some(reason.clone())),
+        TxLifecycleStatus::Dropped { reason } => ("dropped".to_owned(), Some(reason.to_string())),
         TxLifecycleStatus::ConfirmedProvisional {
             block_number,
             block_hash,
@@ -1833,24 +2563,7 @@ fn map_lifecycle_status(status: &TxLifecycleStatus) -> (String, Option<String>)
 }
 
 fn event_payload_type(payload: &EventPayload) -> &str {
-    match payload {
-        EventPayload::TxSeen(_) => "TxSeen",
-        EventPayload::TxFetched(_) => "TxFetched",
-        EventPayload::TxDecoded(_) => "TxDecoded",
-        EventPayload::TxReady(_) => "TxReady",
-        EventPayload::TxBlocked(_) => "TxBlocked",
-        EventPayload::CandidateQueued(_) => "CandidateQueued",
-        EventPayload::SimDispatched(_) => "SimDispatched",
-        EventPayload::OppDetected(_) => "OppDetected",
-        EventPayload::SimCompleted(_) => "SimCompleted",
-        EventPayload::AssemblyDecisionApplied(_) => "AssemblyDecisionApplied",
-        EventPayload::BundleSubmitted(_) => "BundleSubmitted",
-        EventPayload::TxReplaced(_) => "TxReplaced",
-        EventPayload::TxDropped(_) => "TxDropped",
-        EventPayload::TxConfirmedProvisional(_) => "TxConfirmedProvisional",
-        EventPayload::TxConfirmedFinal(_) => "TxConfirmedFinal",
-        EventPayload::TxReorged(_) => "TxReorged",
-    }
+    payload.type_name()
 }
 
 fn parse_event_type_filters(raw: Option<&str>) -> Vec<String> {
@@ -1903,15 +2616,33 @@ async fn require_api_key(
     next.run(request).await
 }
 
-async fn health() -> (StatusCode, Json<HealthResponse>) {
+/// Stays 200 while the ClickHouse sink is degraded or down so the API keeps
+/// serving; the status string and `clickhouse` block carry the detail.
+async fn health(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    let clickhouse = state.provider.clickhouse_sink_health();
+    let status = match clickhouse.as_ref().map(|health| health.status) {
+        None | Some(ClickHouseSinkStatus::Healthy) => "ok",
+        Some(ClickHouseSinkStatus::Degraded | ClickHouseSinkStatus::Down) => "degraded",
+    };
     (
         StatusCode::OK,
         Json(HealthResponse {
-            status: "ok".to_owned(),
+            status: status.to_owned(),
+            clickhouse,
         }),
     )
 }
 
+async fn backfill_status(
+    State(state): State<AppState>,
+) -> Result<Json<BackfillProgress>, StatusCode> {
+    state
+        .provider
+        .storage_backfill_progress()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
 async fn metrics_snapshot(State(state): State<AppState>) -> Json<MetricSnapshot> {
     Json(state.provider.metric_snapshot())
 }
@@ -1949,6 +2680,10 @@ async fn alerts_evaluate(State(state): State<AppState>) -> Json<AlertDecisions>
 struct ReplayQuery {
     from: Option<u64>,
     to: Option<u64>,
+    /// Inclusive ingest-time bounds, resolved to a seq range when `from` and
+    /// `to` are unset.
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
     stride: Option<usize>,
 }
 
@@ -1964,6 +2699,15 @@ async fn replay(
                 (to_seq_id, from_seq_id)
             }
         }
+        (None, None) if query.from_ts.is_some() || query.to_ts.is_some() => {
+            match state
+                .provider
+                .seq_id_range_for_time(query.from_ts, query.to_ts)
+            {
+                Some(seq_ids) => seq_ids.into_inner(),
+                None => return StatusCode::NOT_FOUND.into_response(),
+            }
+        }
         (None, None) => {
             let values = state.provider.replay_points();
             return Json(downsample(&values, state.downsample_limit)).into_response();
@@ -2023,22 +2767,205 @@ struct EventsQuery {
     after: Option<u64>,
     types: Option<String>,
     limit: Option<usize>,
-}
+    /// Scopes the stream to one chain, paged with `after_chain_seq_id`.
+    chain_id: Option<u64>,
+    /// Per-chain `chain_seq_id` cursor; only valid together with `chain_id`.
+    after_chain_seq_id: Option<u64>,
+    /// Filter expression, see [`EventFilter`].
+    filter: Option<String>,
+    /// Inclusive `ingest_ts_unix_ms` bounds.
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+}
+
+/// Events a filtered `/events` or `/events/stream` call examines before it
+/// returns what it has with a resume cursor.
+const EVENTS_FILTER_SCAN_BUDGET: usize = 50_000;
+/// Response header carrying a filtered `/events` call's resume cursor, to be
+/// sent back as `after` (or `after_chain_seq_id` for a chain scan).
+const EVENTS_RESUME_AFTER_HEADER: &str = "x-resume-after";
 
 async fn events(
     State(state): State<AppState>,
     Query(query): Query<EventsQuery>,
-) -> Json<Vec<EventEnvelope>> {
+) -> Result<Response, (StatusCode, String)> {
     let after_seq_id = query.after.unwrap_or(0);
+    let after_chain_seq_id = query.after_chain_seq_id.unwrap_or(0);
+    match (query.chain_id, query.after, query.after_chain_seq_id) {
+        (Some(_), Some(_), _) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "use after_chain_seq_id, not after, to page a chain_id stream".to_owned(),
+            ));
+        }
+        (None, _, Some(_)) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "after_chain_seq_id requires chain_id".to_owned(),
+            ));
+        }
+        _ => {}
+    }
     let limit = query.limit.unwrap_or(1_000).clamp(1, 5_000);
     let event_types = parse_event_type_filters(query.types.as_deref());
-    Json(state.provider.events(after_seq_id, &event_types, limit))
-}
+    let filter = match query.filter.as_deref() {
+        Some(filter) => compile_events_filter(Some(filter), &event_types)?,
+        None => None,
+    };
+    let filter = with_time_range(filter, query.from_ts, query.to_ts);
+    let Some(filter) = filter else {
+        return Ok(Json(match query.chain_id {
+            Some(chain_id) => {
+                state
+                    .provider
+                    .chain_events(chain_id, after_chain_seq_id, &event_types, limit)
+            }
+            None => state.provider.events(after_seq_id, &event_types, limit),
+        })
+        .into_response());
+    };
 
-async fn propagation(State(state): State<AppState>) -> Json<Vec<PropagationEdge>> {
-    let values = state.provider.propagation_edges();
-    Json(downsample(&values, state.downsample_limit))
-}
+    let scan = match query.chain_id {
+        Some(chain_id) => state.provider.filtered_chain_events(
+            chain_id,
+            after_chain_seq_id,
+            &filter,
+            limit,
+            EVENTS_FILTER_SCAN_BUDGET,
+        ),
+        None => {
+            state
+                .provider
+                .filtered_events(after_seq_id, &filter, limit, EVENTS_FILTER_SCAN_BUDGET)
+        }
+    };
+    let mut response = Json(scan.events).into_response();
+    response.headers_mut().insert(
+        HeaderName::from_static(EVENTS_RESUME_AFTER_HEADER),
+        HeaderValue::from(scan.resume_after),
+    );
+    Ok(response)
+}
+
+/// Compiles the `filter` and `types` query parameters into one filter, or
+/// `None` when neither is set.
+fn compile_events_filter(
+    filter: Option<&str>,
+    event_types: &[String],
+) -> Result<Option<EventFilter>, (StatusCode, String)> {
+    let bad_request = |err: FilterParseError| (StatusCode::BAD_REQUEST, err.to_string());
+    let filter = filter
+        .map(EventFilter::parse)
+        .transpose()
+        .map_err(bad_request)?;
+    if event_types.is_empty() {
+        return Ok(filter);
+    }
+    let types = EventFilter::event_types(event_types).map_err(bad_request)?;
+    Ok(Some(match filter {
+        Some(filter) => types.and(filter),
+        None => types,
+    }))
+}
+
+/// Adds the inclusive `from_ts` / `to_ts` query bounds to a filter.
+fn with_time_range(
+    filter: Option<EventFilter>,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+) -> Option<EventFilter> {
+    if from_ts.is_none() && to_ts.is_none() {
+        return filter;
+    }
+    let time = EventFilter::time_range(from_ts, to_ts);
+    Some(match filter {
+        Some(filter) => filter.and(time),
+        None => time,
+    })
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct SeqRangeQuery {
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+/// Seq ids bounding the events ingested within a time range.
+pub struct SeqRangeResponse {
+    pub from_seq_id: u64,
+    pub to_seq_id: u64,
+}
+
+async fn events_seq_range(
+    State(state): State<AppState>,
+    Query(query): Query<SeqRangeQuery>,
+) -> Result<Json<SeqRangeResponse>, StatusCode> {
+    let (from_seq_id, to_seq_id) = state
+        .provider
+        .seq_id_range_for_time(query.from_ts, query.to_ts)
+        .ok_or(StatusCode::NOT_FOUND)?
+        .into_inner();
+    Ok(Json(SeqRangeResponse {
+        from_seq_id,
+        to_seq_id,
+    }))
+}
+
+fn event_matches_types(event: &EventEnvelope, event_types: &[String]) -> bool {
+    event_types.is_empty()
+        || event_types
+            .iter()
+            .any(|kind| event_payload_type(&event.payload).eq_ignore_ascii_case(kind))
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct PropagationQuery {
+    /// `peer` or `source` (default).
+    group: Option<String>,
+    window_ms: Option<u64>,
+}
+
+impl PropagationQuery {
+    fn group(&self) -> Result<ObserverGroup, (StatusCode, String)> {
+        self.group
+            .as_deref()
+            .map_or(Ok(ObserverGroup::Source), str::parse)
+            .map_err(|err| (StatusCode::BAD_REQUEST, err))
+    }
+
+    fn window_ms(&self) -> u64 {
+        self.window_ms
+            .filter(|window_ms| *window_ms > 0)
+            .unwrap_or(DEFAULT_PROPAGATION_WINDOW_MS)
+    }
+}
+
+/// Observed edges when any hash reached more than one observer in the
+/// window, otherwise the configured graph.
+async fn propagation(
+    State(state): State<AppState>,
+    Query(query): Query<PropagationQuery>,
+) -> Result<Json<Vec<PropagationEdge>>, (StatusCode, String)> {
+    let mut values = state
+        .provider
+        .observed_propagation_edges(query.group()?, query.window_ms());
+    if values.is_empty() {
+        values = state.provider.propagation_edges();
+    }
+    Ok(Json(downsample(&values, state.downsample_limit)))
+}
+
+async fn propagation_peers(
+    State(state): State<AppState>,
+    Query(query): Query<PropagationQuery>,
+) -> Result<Json<Vec<PeerObservationStats>>, (StatusCode, String)> {
+    Ok(Json(
+        state
+            .provider
+            .peer_observation_stats(query.group()?, query.window_ms()),
+    ))
+}
 
 async fn features(State(state): State<AppState>) -> Json<Vec<FeatureSummary>> {
     let values = state.provider.feature_summary();
@@ -2048,15 +2975,15 @@ async fn features(State(state): State<AppState>) -> Json<Vec<FeatureSummary>> {
 async fn features_recent(
     State(state): State<AppState>,
     Query(query): Query<TransactionsQuery>,
-) -> Json<Vec<FeatureDetail>> {
+) -> Result<Json<Vec<FeatureDetail>>, (StatusCode, String)> {
     let limit = query.limit.unwrap_or(100).clamp(1, 5_000);
-    let chain_id = query.chain_id;
-    let scan_limit = chain_filter_scan_limit(limit, chain_id);
-    Json(filter_feature_details_by_chain(
+    let filter = compile_row_filter(query.chain_id, query.filter.as_deref())?;
+    let scan_limit = row_scan_limit(limit, filter.as_ref());
+    Ok(Json(filter_rows(
         state.provider.feature_details(scan_limit),
-        chain_id,
+        filter.as_ref(),
         limit,
-    ))
+    )))
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -2065,6 +2992,18 @@ struct TransactionsQuery {
     min_score: Option<u32>,
     status: Option<String>,
     chain_id: Option<u64>,
+    filter: Option<String>,
+    /// Inclusive bounds on first-seen (or detection) unix-ms time.
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+}
+
+impl TransactionsQuery {
+    /// Row filter for endpoints whose rows carry a timestamp.
+    fn timed_row_filter(&self) -> Result<Option<EventFilter>, (StatusCode, String)> {
+        let filter = compile_row_filter(self.chain_id, self.filter.as_deref())?;
+        Ok(with_time_range(filter, self.from_ts, self.to_ts))
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -2116,37 +3055,40 @@ async fn dashboard_snapshot_v2(
 async fn transactions(
     State(state): State<AppState>,
     Query(query): Query<TransactionsQuery>,
-) -> Json<Vec<TransactionSummary>> {
+) -> Result<Json<Vec<TransactionSummary>>, (StatusCode, String)> {
     let limit = query.limit.unwrap_or(25).clamp(1, 200);
-    let chain_id = query.chain_id;
-    let scan_limit = chain_filter_scan_limit(limit, chain_id);
-    Json(filter_transaction_summaries_by_chain(
+    let filter = query.timed_row_filter()?;
+    let scan_limit = row_scan_limit(limit, filter.as_ref());
+    Ok(Json(filter_transaction_summaries(
         state.provider.as_ref(),
         state.provider.recent_transactions(scan_limit),
-        chain_id,
+        filter.as_ref(),
         limit,
-    ))
+    )))
 }
 
 async fn opportunities_recent(
     State(state): State<AppState>,
     Query(query): Query<TransactionsQuery>,
-) -> Json<Vec<OpportunityDetail>> {
-    Json(filtered_opportunities(&state, &query))
+) -> Result<Json<Vec<OpportunityDetail>>, (StatusCode, String)> {
+    filtered_opportunities(&state, &query).map(Json)
 }
 
 async fn opportunities(
     State(state): State<AppState>,
     Query(query): Query<TransactionsQuery>,
-) -> Json<Vec<OpportunityDetail>> {
-    Json(filtered_opportunities(&state, &query))
+) -> Result<Json<Vec<OpportunityDetail>>, (StatusCode, String)> {
+    filtered_opportunities(&state, &query).map(Json)
 }
 
-fn filtered_opportunities(state: &AppState, query: &TransactionsQuery) -> Vec<OpportunityDetail> {
+fn filtered_opportunities(
+    state: &AppState,
+    query: &TransactionsQuery,
+) -> Result<Vec<OpportunityDetail>, (StatusCode, String)> {
     let limit = query.limit.unwrap_or(100).clamp(1, 5_000);
     let min_score = query.min_score.unwrap_or(0);
-    let chain_id = query.chain_id;
-    let scan_limit = chain_filter_scan_limit(limit, chain_id);
+    let filter = query.timed_row_filter()?;
+    let scan_limit = row_scan_limit(limit, filter.as_ref());
     let values = state.provider.opportunities(scan_limit, min_score);
     let status_filter = query
         .status
@@ -2161,76 +3103,85 @@ fn filtered_opportunities(state: &AppState, query: &TransactionsQuery) -> Vec<Op
             .collect::<Vec<_>>(),
         None => values,
     };
-    filter_opportunities_by_chain(filtered, chain_id, limit)
+    Ok(filter_rows(filtered, filter.as_ref(), limit))
+}
+
+/// Combines the `chain_id` and `filter` query parameters of a read-model
+/// endpoint into one filter, or `None` when neither is set.
+fn compile_row_filter(
+    chain_id: Option<u64>,
+    filter: Option<&str>,
+) -> Result<Option<EventFilter>, (StatusCode, String)> {
+    let filter = filter
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(EventFilter::parse)
+        .transpose()
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+    Ok(match (chain_id.map(EventFilter::chain), filter) {
+        (Some(chain), Some(filter)) => Some(chain.and(filter)),
+        (chain, filter) => chain.or(filter),
+    })
 }
 
-fn chain_filter_scan_limit(limit: usize, chain_id: Option<u64>) -> usize {
-    if chain_id.is_some() {
+fn row_scan_limit(limit: usize, filter: Option<&EventFilter>) -> usize {
+    if filter.is_some() {
         limit.saturating_mul(4).clamp(limit, 20_000)
     } else {
         limit
     }
 }
 
-fn chain_matches_filter(record_chain_id: Option<u64>, chain_id_filter: Option<u64>) -> bool {
-    match chain_id_filter {
-        Some(chain_id) => record_chain_id == Some(chain_id),
-        None => true,
-    }
-}
-
-fn filter_feature_details_by_chain(
-    values: Vec<FeatureDetail>,
-    chain_id: Option<u64>,
+fn filter_rows<T: FilterSubject>(
+    values: Vec<T>,
+    filter: Option<&EventFilter>,
     limit: usize,
-) -> Vec<FeatureDetail> {
+) -> Vec<T> {
     values
         .into_iter()
-        .filter(|row| chain_matches_filter(row.chain_id, chain_id))
+        .filter(|row| filter.is_none_or(|filter| filter.matches(row)))
         .take(limit)
         .collect()
 }
 
-fn filter_opportunities_by_chain(
-    values: Vec<OpportunityDetail>,
+/// Transaction summary joined with whatever the filter needs from its
+/// detail row; summaries alone carry no chain or lifecycle fields.
+struct TransactionRow<'a> {
+    summary: &'a TransactionSummary,
     chain_id: Option<u64>,
-    limit: usize,
-) -> Vec<OpportunityDetail> {
-    values
-        .into_iter()
-        .filter(|row| chain_matches_filter(row.chain_id, chain_id))
-        .take(limit)
-        .collect()
+    detail: Option<TransactionDetail>,
 }
 
-fn filter_transaction_details_by_chain(
-    values: Vec<TransactionDetail>,
-    chain_id: Option<u64>,
-    limit: usize,
-) -> Vec<TransactionDetail> {
-    values
-        .into_iter()
-        .filter(|row| chain_matches_filter(row.chain_id, chain_id))
-        .take(limit)
-        .collect()
+impl FilterSubject for TransactionRow<'_> {
+    fn filter_value(&self, field: FilterField) -> Option<FilterValue<'_>> {
+        if field == FilterField::ChainId {
+            return self.chain_id.map(number_value);
+        }
+        self.detail
+            .as_ref()
+            .and_then(|detail| detail.filter_value(field))
+            .or_else(|| self.summary.filter_value(field))
+    }
 }
 
-fn filter_transaction_summaries_by_chain(
-    provider: &dyn VizDataProvider,
+fn filter_transaction_summaries<P: VizDataProvider + ?Sized>(
+    provider: &P,
     values: Vec<TransactionSummary>,
-    chain_id: Option<u64>,
+    filter: Option<&EventFilter>,
     limit: usize,
 ) -> Vec<TransactionSummary> {
+    let Some(filter) = filter else {
+        return values.into_iter().take(limit).collect();
+    };
     values
         .into_iter()
-        .filter(|row| {
-            if chain_id.is_none() {
-                return true;
-            }
-            provider
-                .transaction_detail_by_hash(&row.hash)
-                .map(|detail| chain_matches_filter(detail.chain_id, chain_id))
-                .unwrap_or(false)
+        .filter(|summary| {
+            let detail = provider.transaction_detail_by_hash(&summary.hash);
+            filter.matches(&TransactionRow {
+                summary,
+                chain_id: detail.as_ref().and_then(|detail| detail.chain_id),
+                detail,
+            })
         })
         .take(limit)
         .collect()
@@ -2239,14 +3190,100 @@ fn filter_transaction_summaries_by_chain(
 async fn transactions_all(
     State(state): State<AppState>,
     Query(query): Query<TransactionsQuery>,
-) -> Json<Vec<TransactionDetail>> {
+) -> Result<Json<Vec<TransactionDetail>>, (StatusCode, String)> {
     let limit = query.limit.unwrap_or(1_000).clamp(1, 5_000);
-    let chain_id = query.chain_id;
-    let scan_limit = chain_filter_scan_limit(limit, chain_id);
-    Json(filter_transaction_details_by_chain(
+    let filter = query.timed_row_filter()?;
+    let scan_limit = row_scan_limit(limit, filter.as_ref());
+    Ok(Json(filter_rows(
         state.provider.transaction_details(scan_limit),
-        chain_id,
+        filter.as_ref(),
         limit,
+    )))
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct IndexPageParams {
+    limit: Option<usize>,
+    cursor: Option<u64>,
+    from_unix_ms: Option<i64>,
+    to_unix_ms: Option<i64>,
+    status: Option<String>,
+}
+
+impl IndexPageParams {
+    fn to_query(&self) -> IndexPageQuery {
+        IndexPageQuery {
+            before: self.cursor,
+            limit: self.limit.unwrap_or(100).clamp(1, 1_000),
+            from_unix_ms: self.from_unix_ms,
+            to_unix_ms: self.to_unix_ms,
+            status: self
+                .status
+                .as_deref()
+                .map(str::trim)
+                .filter(|status| !status.is_empty())
+                .map(str::to_owned),
+        }
+    }
+}
+
+/// Parses the `{field}/{value}` path of an indexed transaction query.
+fn parse_tx_index_key(field: &str, value: &str) -> Result<TxIndexKey, (StatusCode, String)> {
+    let bad_value = || {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("invalid {field} value: {value}"),
+        )
+    };
+    match field {
+        "sender" => live_rpc::parse_fixed_hex::<20>(value)
+            .map(TxIndexKey::Sender)
+            .ok_or_else(bad_value),
+        "to" => live_rpc::parse_fixed_hex::<20>(value)
+            .map(TxIndexKey::To)
+            .ok_or_else(bad_value),
+        "chain_id" => value
+            .parse()
+            .map(TxIndexKey::ChainId)
+            .map_err(|_| bad_value()),
+        "protocol" => Ok(TxIndexKey::Protocol(value.to_owned())),
+        _ => Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "unknown transaction index: {field} (expected sender, to, chain_id or protocol)"
+            ),
+        )),
+    }
+}
+
+async fn transactions_by_index(
+    State(state): State<AppState>,
+    Path((field, value)): Path<(String, String)>,
+    Query(params): Query<IndexPageParams>,
+) -> Result<Json<IndexPage<TransactionDetail>>, (StatusCode, String)> {
+    let key = parse_tx_index_key(&field, &value)?;
+    Ok(Json(
+        state
+            .provider
+            .indexed_transactions(&key, &params.to_query()),
+    ))
+}
+
+async fn opportunities_by_index(
+    State(state): State<AppState>,
+    Path((field, value)): Path<(String, String)>,
+    Query(params): Query<IndexPageParams>,
+) -> Result<Json<IndexPage<OpportunityDetail>>, (StatusCode, String)> {
+    if field != "protocol" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("unknown opportunity index: {field} (expected protocol)"),
+        ));
+    }
+    Ok(Json(
+        state
+            .provider
+            .opportunities_by_protocol(&value, &params.to_query()),
     ))
 }
 
@@ -2343,6 +3380,7 @@ async fn sim_by_id(
 fn render_prometheus_metrics(state: &AppState) -> String {
     let snapshot = state.provider.metric_snapshot();
     let dashboard_cache_metrics = state.provider.dashboard_cache_metrics();
+    let wal_metrics = state.provider.storage_wal_metrics();
     let drop_metrics = (state.live_rpc_drop_metrics_provider)();
     let scheduler_metrics = (state.scheduler_metrics_provider)();
     let builder_metrics = (state.builder_metrics_provider)();
@@ -2364,7 +3402,7 @@ fn render_prometheus_metrics(state: &AppState) -> String {
             / 1_000_000.0
     };
 
-    format!(
+    let mut body = format!(
         "\
 # TYPE mempulse_ingest_queue_depth gauge
 mempulse_ingest_queue_depth {ingest_queue_depth}
@@ -2463,6 +3501,18 @@ mempulse_dashboard_cache_last_build_ms {cache_last_build_ms:.3}
 mempulse_dashboard_cache_avg_build_ms {avg_build_ms:.3}
 # TYPE mempulse_dashboard_cache_estimated_bytes gauge
 mempulse_dashboard_cache_estimated_bytes {cache_estimated_bytes}
+# TYPE mempulse_storage_wal_records_total counter
+mempulse_storage_wal_records_total {wal_records}
+# TYPE mempulse_storage_wal_bytes_written_total counter
+mempulse_storage_wal_bytes_written_total {wal_bytes_written}
+# TYPE mempulse_storage_wal_commits_total counter
+mempulse_storage_wal_commits_total {wal_commits}
+# TYPE mempulse_storage_wal_fsync_total counter
+mempulse_storage_wal_fsync_total {wal_fsync}
+# TYPE mempulse_storage_wal_fsync_latency_ns_total counter
+mempulse_storage_wal_fsync_latency_ns_total {wal_fsync_latency_ns_total}
+# TYPE mempulse_storage_wal_fsync_latency_ns_max gauge
+mempulse_storage_wal_fsync_latency_ns_max {wal_fsync_latency_ns_max}
 # TYPE mempulse_replay_lag_events gauge
 mempulse_replay_lag_events {replay_lag_events}
 # TYPE mempulse_replay_checkpoint_duration_ms gauge
@@ -2559,6 +3609,12 @@ mempulse_relay_bundle_filtered_total{{reason=\"dry_run_failed\"}} {relay_failed}
         cache_refresh_total = dashboard_cache_metrics.refresh_total,
         cache_last_build_ms = dashboard_cache_metrics.last_build_duration_ns as f64 / 1_000_000.0,
         cache_estimated_bytes = dashboard_cache_metrics.estimated_bytes,
+        wal_records = wal_metrics.records_total,
+        wal_bytes_written = wal_metrics.bytes_written_total,
+        wal_commits = wal_metrics.commits_total,
+        wal_fsync = wal_metrics.fsync_total,
+        wal_fsync_latency_ns_total = wal_metrics.fsync_latency_ns_total,
+        wal_fsync_latency_ns_max = wal_metrics.fsync_latency_ns_max,
         replay_lag_events = replay_metrics.lag_events,
         replay_checkpoint_ms = replay_metrics.checkpoint_duration_ms,
         replay_reorg_depth = replay_metrics.reorg_depth,
@@ -2586,6 +3642,192 @@ mempulse_relay_bundle_filtered_total{{reason=\"dry_run_failed\"}} {relay_failed}
         sim_drop_stale = sim_metrics.stale_drop_total,
         relay_accepted = relay.total_accepted,
         relay_failed = relay.total_failed,
+    );
+    if let Some(health) = state.provider.clickhouse_sink_health() {
+        body.push_str(&render_clickhouse_sink_metrics(&health));
+    }
+    if let Some(usage) = state.provider.storage_memory_usage() {
+        body.push_str(&render_storage_memory_metrics(&usage));
+    }
+    if let Some(progress) = state.provider.storage_backfill_progress() {
+        body.push_str(&render_backfill_metrics(&progress));
+    }
+    let event_sinks = state.provider.event_sink_stats();
+    if !event_sinks.is_empty() {
+        body.push_str(&render_event_sink_metrics(&event_sinks));
+    }
+    body
+}
+
+fn render_event_sink_metrics(sinks: &[EventSinkStats]) -> String {
+    let mut body = String::new();
+    let mut series = |name: &str, kind: &str, value: fn(&EventSinkStats) -> u64| {
+        body.push_str(&format!("# TYPE mempulse_event_sink_{name} {kind}\n"));
+        for stats in sinks {
+            body.push_str(&format!(
+                "mempulse_event_sink_{name}{{sink=\"{}\"}} {}\n",
+                stats.name,
+                value(stats)
+            ));
+        }
+    };
+    series("queued_batches", "gauge", |stats| stats.queued_batches);
+    series("delivered_batches_total", "counter", |stats| {
+        stats.delivered_batches
+    });
+    series("delivered_events_total", "counter", |stats| {
+        stats.delivered_events
+    });
+    series("failed_batches_total", "counter", |stats| {
+        stats.failed_batches
+    });
+    series("dropped_events_total", "counter", |stats| {
+        stats.dropped_events
+    });
+    body
+}
+
+fn render_backfill_metrics(progress: &BackfillProgress) -> String {
+    let state_lines = [
+        BackfillState::Starting,
+        BackfillState::Running,
+        BackfillState::Completed,
+        BackfillState::Failed,
+    ]
+    .into_iter()
+    .map(|state| {
+        format!(
+            "mempulse_backfill_state{{state=\"{}\"}} {}\n",
+            state.as_str(),
+            u8::from(state == progress.state)
+        )
+    })
+    .collect::<String>();
+    format!(
+        r#"# TYPE mempulse_backfill_state gauge
+{state_lines}# TYPE mempulse_backfill_head_seq_id gauge
+mempulse_backfill_head_seq_id {head_seq_id}
+# TYPE mempulse_backfill_last_seq_id gauge
+mempulse_backfill_last_seq_id {last_seq_id}
+# TYPE mempulse_backfill_applied_total counter
+mempulse_backfill_applied_total {applied}
+# TYPE mempulse_backfill_skipped_duplicates_total counter
+mempulse_backfill_skipped_duplicates_total {skipped}
+# TYPE mempulse_backfill_batches_total counter
+mempulse_backfill_batches_total {batches}
+"#,
+        head_seq_id = progress.head_seq_id.unwrap_or(0),
+        last_seq_id = progress.last_seq_id.unwrap_or(0),
+        applied = progress.applied,
+        skipped = progress.skipped_duplicates,
+        batches = progress.batches,
+    )
+}
+
+fn render_storage_memory_metrics(usage: &StorageMemoryUsage) -> String {
+    let mut body = String::from("# TYPE mempulse_storage_table_bytes gauge\n");
+    for table in &usage.tables {
+        body.push_str(&format!(
+            "mempulse_storage_table_bytes{{table=\"{}\"}} {}\n",
+            table.table, table.bytes
+        ));
+    }
+    body.push_str("# TYPE mempulse_storage_table_rows gauge\n");
+    for table in &usage.tables {
+        body.push_str(&format!(
+            "mempulse_storage_table_rows{{table=\"{}\"}} {}\n",
+            table.table, table.rows
+        ));
+    }
+    body.push_str(&format!(
+        "# TYPE mempulse_storage_memory_bytes gauge\nmempulse_storage_memory_bytes {}\n",
+        usage.total_bytes
+    ));
+    if let Some(budget_bytes) = usage.budget_bytes {
+        body.push_str(&format!(
+            "# TYPE mempulse_storage_memory_budget_bytes gauge\nmempulse_storage_memory_budget_bytes {budget_bytes}\n"
+        ));
+        body.push_str("# TYPE mempulse_storage_table_budget_bytes gauge\n");
+        for table in &usage.tables {
+            body.push_str(&format!(
+                "mempulse_storage_table_budget_bytes{{table=\"{}\"}} {}\n",
+                table.table,
+                table.budget_bytes.unwrap_or_default()
+            ));
+        }
+        body.push_str("# TYPE mempulse_storage_budget_evictions_total counter\n");
+        for table in &usage.tables {
+            body.push_str(&format!(
+                "mempulse_storage_budget_evictions_total{{table=\"{}\"}} {}\n",
+                table.table, table.budget_evictions_total
+            ));
+        }
+    }
+    if let Some(blobs) = usage.calldata_blobs {
+        body.push_str(&format!(
+            r#"# TYPE mempulse_storage_calldata_blobs gauge
+mempulse_storage_calldata_blobs {blobs_count}
+# TYPE mempulse_storage_calldata_blob_live_bytes gauge
+mempulse_storage_calldata_blob_live_bytes {live_bytes}
+# TYPE mempulse_storage_calldata_blob_file_bytes gauge
+mempulse_storage_calldata_blob_file_bytes {file_bytes}
+# TYPE mempulse_storage_calldata_blob_compactions_total counter
+mempulse_storage_calldata_blob_compactions_total {compactions}
+"#,
+            blobs_count = blobs.blobs,
+            live_bytes = blobs.live_bytes,
+            file_bytes = blobs.file_bytes,
+            compactions = blobs.compactions_total,
+        ));
+    }
+    body
+}
+
+fn render_clickhouse_sink_metrics(health: &ClickHouseSinkHealth) -> String {
+    let status_lines = [
+        ClickHouseSinkStatus::Healthy,
+        ClickHouseSinkStatus::Degraded,
+        ClickHouseSinkStatus::Down,
+    ]
+    .into_iter()
+    .map(|status| {
+        format!(
+            "mempulse_clickhouse_sink_status{{status=\"{}\"}} {}\n",
+            status.as_str(),
+            u8::from(status == health.status)
+        )
+    })
+    .collect::<String>();
+    format!(
+        r#"# TYPE mempulse_clickhouse_sink_status gauge
+{status_lines}# TYPE mempulse_clickhouse_sink_consecutive_failures gauge
+mempulse_clickhouse_sink_consecutive_failures {consecutive_failures}
+# TYPE mempulse_clickhouse_sink_spilled_batches gauge
+mempulse_clickhouse_sink_spilled_batches {spilled_batches}
+# TYPE mempulse_clickhouse_sink_spilled_bytes gauge
+mempulse_clickhouse_sink_spilled_bytes {spilled_bytes}
+# TYPE mempulse_clickhouse_sink_batches_delivered_total counter
+mempulse_clickhouse_sink_batches_delivered_total {delivered}
+# TYPE mempulse_clickhouse_sink_retries_total counter
+mempulse_clickhouse_sink_retries_total {retries}
+# TYPE mempulse_clickhouse_sink_failures_total counter
+mempulse_clickhouse_sink_failures_total {failures}
+# TYPE mempulse_clickhouse_sink_batches_spilled_total counter
+mempulse_clickhouse_sink_batches_spilled_total {spilled}
+# TYPE mempulse_clickhouse_sink_batches_drained_total counter
+mempulse_clickhouse_sink_batches_drained_total {drained}
+# TYPE mempulse_clickhouse_sink_batches_dropped_total counter
+mempulse_clickhouse_sink_batches_dropped_total {dropped}
+"#,
+        consecutive_failures = health.consecutive_failures,
+        spilled_batches = health.spilled_batches,
+        spilled_bytes = health.spilled_bytes,
+        delivered = health.batches_delivered_total,
+        retries = health.retries_total,
+        failures = health.failures_total,
+        spilled = health.batches_spilled_total,
+        drained = health.batches_drained_total,
+        dropped = health.batches_dropped_total,
     )
 }
 
@@ -2647,6 +3889,8 @@ fn sim_id_for_result(result: &RelayDryRunResult) -> String {
 struct StreamQuery {
     after: Option<u64>,
     interval_ms: Option<u64>,
+    chain_id: Option<u64>,
+    filter: Option<String>,
 }
 
 const DASHBOARD_STREAM_BROADCAST_REPLAY_CAPACITY: usize = 256;
@@ -2702,7 +3946,8 @@ async fn events_v1(
     State(state): State<AppState>,
     Query(query): Query<StreamQuery>,
     headers: HeaderMap,
-) -> impl IntoResponse {
+) -> Result<Response, (StatusCode, String)> {
+    let filter = compile_row_filter(query.chain_id, query.filter.as_deref())?;
     let after_seq_id = resolve_dashboard_events_v1_after(
         headers
             .get("last-event-id")
@@ -2718,6 +3963,20 @@ async fn events_v1(
         receiver: tokio::sync::broadcast::Receiver<DashboardStreamBroadcastEvent>,
         pending: VecDeque<DashboardStreamBroadcastEvent>,
         heartbeat_interval: Duration,
+        provider: Arc<dyn VizDataProvider>,
+        filter: Option<EventFilter>,
+    }
+
+    impl EventsV1LoopState {
+        fn frame(&self, event: DashboardStreamBroadcastEvent) -> DashboardEventsV1Frame {
+            let event = match &self.filter {
+                Some(filter) => {
+                    filter_dashboard_stream_event(self.provider.as_ref(), filter, event)
+                }
+                None => event,
+            };
+            build_dashboard_events_v1_frame(&event)
+        }
     }
 
     let stream = stream::unfold(
@@ -2726,10 +3985,12 @@ async fn events_v1(
             receiver,
             pending: VecDeque::from(initial_events),
             heartbeat_interval: Duration::from_millis(interval_ms),
+            provider: state.provider.clone(),
+            filter,
         },
         |mut loop_state| async move {
             if let Some(event) = loop_state.pending.pop_front() {
-                let frame = build_dashboard_events_v1_frame(&event);
+                let frame = loop_state.frame(event);
                 return Some((
                     Ok::<SseEvent, Infallible>(dashboard_events_v1_frame_to_event(frame)),
                     loop_state,
@@ -2741,7 +4002,7 @@ async fn events_v1(
                     .await;
             let sse_event = match next {
                 Ok(Ok(event)) => {
-                    let frame = build_dashboard_events_v1_frame(&event);
+                    let frame = loop_state.frame(event);
                     dashboard_events_v1_frame_to_event(frame)
                 }
                 Ok(Err(tokio::sync::broadcast::error::RecvError::Lagged(_skipped))) => {
@@ -2765,7 +4026,123 @@ async fn events_v1(
         HeaderName::from_static("x-accel-buffering"),
         HeaderValue::from_static("no"),
     );
+    Ok(response)
+}
+
+/// Narrows a broadcast delta to the rows one subscriber's filter accepts;
+/// resets pass through untouched.
+fn filter_dashboard_stream_event(
+    provider: &dyn VizDataProvider,
+    filter: &EventFilter,
+    event: DashboardStreamBroadcastEvent,
+) -> DashboardStreamBroadcastEvent {
+    let DashboardStreamBroadcastEvent::Delta(mut dispatch) = event else {
+        return event;
+    };
+    let patch = &mut dispatch.patch;
+    patch.upsert = filter_transaction_summaries(
+        provider,
+        std::mem::take(&mut patch.upsert),
+        Some(filter),
+        usize::MAX,
+    );
+    patch.feature_upsert.retain(|row| filter.matches(row));
+    patch.opportunity_upsert.retain(|row| filter.matches(row));
+    DashboardStreamBroadcastEvent::Delta(dispatch)
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct EventsStreamQuery {
+    after: Option<u64>,
+    types: Option<String>,
+    filter: Option<String>,
+    interval_ms: Option<u64>,
+}
+
+const EVENTS_STREAM_PAGE_LIMIT: usize = 256;
+
+/// Streams raw envelopes matching `filter`, resuming from `Last-Event-ID` or `after`.
+async fn events_stream(
+    State(state): State<AppState>,
+    Query(query): Query<EventsStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let event_types = parse_event_type_filters(query.types.as_deref());
+    let filter = compile_events_filter(query.filter.as_deref(), &event_types)?;
+    let after_seq_id = resolve_dashboard_events_v1_after(
+        headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok()),
+        query.after,
+    );
+    let interval_ms = query.interval_ms.unwrap_or(1_000).clamp(50, 5_000);
+
+    struct EventsStreamLoopState {
+        provider: Arc<dyn VizDataProvider>,
+        filter: Option<EventFilter>,
+        after_seq_id: u64,
+        pending: VecDeque<EventEnvelope>,
+        poll_interval: Duration,
+    }
+
+    let stream = stream::unfold(
+        EventsStreamLoopState {
+            provider: state.provider.clone(),
+            filter,
+            after_seq_id,
+            pending: VecDeque::new(),
+            poll_interval: Duration::from_millis(interval_ms),
+        },
+        |mut loop_state| async move {
+            while loop_state.pending.is_empty() {
+                let scan = match &loop_state.filter {
+                    Some(filter) => loop_state.provider.filtered_events(
+                        loop_state.after_seq_id,
+                        filter,
+                        EVENTS_STREAM_PAGE_LIMIT,
+                        EVENTS_FILTER_SCAN_BUDGET,
+                    ),
+                    None => {
+                        let events = loop_state.provider.events(
+                            loop_state.after_seq_id,
+                            &[],
+                            EVENTS_STREAM_PAGE_LIMIT,
+                        );
+                        FilteredScan {
+                            resume_after: events
+                                .last()
+                                .map_or(loop_state.after_seq_id, |last| last.seq_id),
+                            events,
+                            budget_exhausted: false,
+                        }
+                    }
+                };
+                // Resume from the last examined event even when nothing
+                // matched, so a selective filter does not rescan the log.
+                loop_state.after_seq_id = loop_state.after_seq_id.max(scan.resume_after);
+                if scan.events.is_empty() && !scan.budget_exhausted {
+                    tokio::time::sleep(loop_state.poll_interval).await;
+                }
+                loop_state.pending.extend(scan.events);
+            }
+            let event = loop_state.pending.pop_front()?;
+            let sse_event = SseEvent::default()
+                .id(event.seq_id.to_string())
+                .event("event")
+                .data(serde_json::to_string(&event).unwrap_or_default());
+            Some((Ok::<SseEvent, Infallible>(sse_event), loop_state))
+        },
+    );
+    let sse = Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)));
+    let mut response = sse.into_response();
     response
+        .headers_mut()
+        .insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response.headers_mut().insert(
+        HeaderName::from_static("x-accel-buffering"),
+        HeaderValue::from_static("no"),
+    );
+    Ok(response)
 }
 
 fn resolve_dashboard_events_v1_after(last_event_id: Option<&str>, query_after: Option<u64>) -> u64 {
@@ -2789,6 +4166,16 @@ fn build_dashboard_events_v1_delta_frame(
         let latest_seq_id = dispatch.seq.max(dispatch.watermark.latest_ingest_seq);
         return Some(build_dashboard_events_v1_reset_frame(latest_seq_id, "gap"));
     }
+    let patch = &dispatch.patch;
+    if patch.upsert.is_empty()
+        && patch.remove.is_empty()
+        && patch.feature_upsert.is_empty()
+        && patch.opportunity_upsert.is_empty()
+    {
+        // Everything was filtered out for this subscriber; the caller turns
+        // this into a keepalive so its cursor still advances.
+        return None;
+    }
 
     Some(DashboardEventsV1Frame {
         id: dispatch.seq.to_string(),
@@ -3049,6 +4436,8 @@ mod tests {
     use axum::http::Request;
     use axum::http::header::{ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN};
     use axum::http::{HeaderValue, Method};
+    use event_log::chain_events_after;
+    use storage::StorageTable;
     use tower::util::ServiceExt;
 
     #[derive(Clone)]
@@ -3073,6 +4462,9 @@ mod tests {
                         seen_at_unix_ms: 1_700_000_000_000,
                         seen_at_mono_ns: 1_700_000_000_000_000_000,
                     }),
+                    chain_id: Some(1),
+                    chain_seq_id: Some(1),
+                    hash_link: None,
                 },
                 EventEnvelope {
                     seq_id: 2,
@@ -3083,6 +4475,9 @@ mod tests {
                         hash: [0x02; 32],
                         fetched_at_unix_ms: 1_700_000_000_050,
                     }),
+                    chain_id: Some(8453),
+                    chain_seq_id: Some(1),
+                    hash_link: None,
                 },
                 EventEnvelope {
                     seq_id: 3,
@@ -3104,6 +4499,9 @@ mod tests {
                         max_fee_per_blob_gas_wei: None,
                         calldata_len: Some(4),
                     }),
+                    chain_id: Some(1),
+                    chain_seq_id: Some(2),
+                    hash_link: None,
                 },
             ];
             values
@@ -3119,6 +4517,19 @@ mod tests {
                 .collect()
         }
 
+        fn chain_events(
+            &self,
+            chain_id: u64,
+            after_chain_seq_id: u64,
+            event_types: &[String],
+            limit: usize,
+        ) -> Vec<EventEnvelope> {
+            let events = self.events(0, event_types, usize::MAX);
+            let mut chain_events = chain_events_after(&events, chain_id, after_chain_seq_id);
+            chain_events.truncate(limit);
+            chain_events
+        }
+
         fn replay_points(&self) -> Vec<ReplayPoint> {
             vec![
                 ReplayPoint {
@@ -3601,47 +5012,246 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn metrics_prometheus_route_returns_prometheus_text_series() {
-        let app = build_router(test_state(100));
+    async fn health_route_reports_degraded_clickhouse_sink() {
+        let mut state = test_state(100);
+        state.provider = Arc::new(
+            InMemoryVizProvider::new(
+                Arc::new(RwLock::new(InMemoryStorage::default())),
+                Arc::new(Vec::new()),
+                1,
+            )
+            .with_clickhouse_sink_health(Arc::new(|| {
+                Some(ClickHouseSinkHealth {
+                    status: ClickHouseSinkStatus::Degraded,
+                    consecutive_failures: 3,
+                    spilled_batches: 2,
+                    spilled_bytes: 512,
+                    ..ClickHouseSinkHealth::default()
+                })
+            })),
+        );
+        let app = build_router(state);
 
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/metrics")
+                    .uri("/health")
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-
         assert_eq!(response.status(), StatusCode::OK);
-        let content_type = response
-            .headers()
-            .get(axum::http::header::CONTENT_TYPE)
-            .and_then(|value| value.to_str().ok())
-            .unwrap_or_default()
-            .to_owned();
-        assert!(content_type.starts_with("text/plain; version=0.0.4"));
+        let body = to_bytes(response.into_body(), 4096).await.unwrap();
+        let payload: HealthResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload.status, "degraded");
+        let clickhouse = payload.clickhouse.expect("clickhouse health");
+        assert_eq!(clickhouse.spilled_batches, 2);
 
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
         let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
-        let payload = String::from_utf8(body.to_vec()).unwrap();
-        assert!(payload.contains("mempulse_ingest_queue_depth"));
-        assert!(payload.contains("mempulse_ingest_drops_total{reason=\"decode_fail\"}"));
-        assert!(payload.contains("mempulse_ingest_lag_ms"));
-        assert!(payload.contains("mempulse_ingest_decode_total"));
-        assert!(payload.contains("mempulse_ingest_tx_per_sec_current"));
-        assert!(payload.contains("mempulse_dashboard_cache_refresh_total"));
-        assert!(payload.contains("mempulse_dashboard_cache_last_build_ms"));
-        assert!(payload.contains("mempulse_dashboard_cache_estimated_bytes"));
-        assert!(payload.contains("mempulse_replay_lag_events"));
-        assert!(payload.contains("mempulse_replay_tail_reorged_tx_total"));
-        assert!(payload.contains("mempulse_sim_queue_depth"));
-        assert!(payload.contains("mempulse_sim_drop_total{reason=\"stale\"}"));
-        assert!(payload.contains("mempulse_sim_fail_total{category=\"unknown\"}"));
-        assert!(payload.contains("mempulse_relay_success_rate"));
-        assert!(payload.contains(
-            "mempulse_builder_rejected_reason_total{reason=\"simulation_not_approved\"}"
-        ));
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("mempulse_clickhouse_sink_status{status=\"degraded\"} 1"));
+        assert!(text.contains("mempulse_clickhouse_sink_status{status=\"healthy\"} 0"));
+        assert!(text.contains("mempulse_clickhouse_sink_spilled_bytes 512"));
+        assert!(text.contains("mempulse_clickhouse_sink_consecutive_failures 3"));
+    }
+
+    #[tokio::test]
+    async fn backfill_status_route_and_metrics_report_writer_progress() {
+        let app = build_router(test_state(100));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/backfill/status")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let mut state = test_state(100);
+        state.provider = Arc::new(
+            InMemoryVizProvider::new(
+                Arc::new(RwLock::new(InMemoryStorage::default())),
+                Arc::new(Vec::new()),
+                1,
+            )
+            .with_backfill_progress(Arc::new(|| {
+                Some(BackfillProgress {
+                    source: "wal:/data/history.wal".to_owned(),
+                    state: BackfillState::Running,
+                    head_seq_id: Some(900),
+                    last_seq_id: Some(400),
+                    applied: 380,
+                    skipped_duplicates: 20,
+                    batches: 4,
+                    ..BackfillProgress::default()
+                })
+            })),
+        );
+        let app = build_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/backfill/status")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 4096).await.unwrap();
+        let progress: BackfillProgress = serde_json::from_slice(&body).unwrap();
+        assert_eq!(progress.state, BackfillState::Running);
+        assert_eq!(progress.applied, 380);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("mempulse_backfill_state{state=\"running\"} 1"));
+        assert!(text.contains("mempulse_backfill_head_seq_id 900"));
+        assert!(text.contains("mempulse_backfill_applied_total 380"));
+        assert!(text.contains("mempulse_backfill_skipped_duplicates_total 20"));
+    }
+
+    #[tokio::test]
+    async fn metrics_route_breaks_storage_memory_down_by_table() {
+        let blob_path = std::env::temp_dir().join(format!(
+            "viz-api-calldata-blobs-{}-{}.bin",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos())
+        ));
+        let blobs = Arc::new(CalldataBlobStore::create(&blob_path).unwrap());
+        let mut storage = InMemoryStorage::with_config(StorageConfig {
+            memory_budget: Some(MemoryBudgetConfig::new(1024 * 1024)),
+            ..StorageConfig::default()
+        })
+        .with_calldata_blobs(blobs);
+        let hash = hash_from_seq(7);
+        storage.upsert_tx_seen(storage::TxSeenRecord {
+            hash,
+            peer: "rpc-ws".to_owned(),
+            first_seen_unix_ms: 1_700_000_000_000,
+            first_seen_mono_ns: 1_700_000_000_000_000_000,
+            seen_count: 1,
+        });
+        storage.upsert_tx_full(storage::TxFullRecord {
+            hash,
+            tx_type: 2,
+            sender: [7_u8; 20],
+            nonce: 1,
+            to: None,
+            chain_id: Some(1),
+            value_wei: None,
+            gas_limit: None,
+            gas_price_wei: None,
+            max_fee_per_gas_wei: None,
+            max_priority_fee_per_gas_wei: None,
+            max_fee_per_blob_gas_wei: None,
+            calldata_len: None,
+            raw_tx: vec![0xab; 2048],
+            l2_fields: None,
+        });
+        let mut state = test_state(100);
+        state.provider = Arc::new(InMemoryVizProvider::new(
+            Arc::new(RwLock::new(storage)),
+            Arc::new(Vec::new()),
+            1,
+        ));
+        let detail = state
+            .provider
+            .transaction_detail_by_hash(&format_bytes(&hash))
+            .expect("detail row");
+        assert_eq!(detail.raw_tx_len, Some(2048), "offloaded calldata length");
+        let app = build_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("mempulse_storage_table_rows{table=\"tx_full\"} 1"));
+        assert!(text.contains("mempulse_storage_table_bytes{table=\"events\"} 0"));
+        assert!(text.contains("mempulse_storage_memory_budget_bytes 1048576"));
+        assert!(text.contains("mempulse_storage_budget_evictions_total{table=\"tx_full\"} 0"));
+        assert!(text.contains("mempulse_storage_calldata_blobs 1"));
+
+        let _ = std::fs::remove_file(blob_path);
+    }
+
+    #[tokio::test]
+    async fn metrics_prometheus_route_returns_prometheus_text_series() {
+        let app = build_router(test_state(100));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response
+            .headers()
+            .get(axum::http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_owned();
+        assert!(content_type.starts_with("text/plain; version=0.0.4"));
+
+        let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let payload = String::from_utf8(body.to_vec()).unwrap();
+        assert!(payload.contains("mempulse_ingest_queue_depth"));
+        assert!(payload.contains("mempulse_ingest_drops_total{reason=\"decode_fail\"}"));
+        assert!(payload.contains("mempulse_ingest_lag_ms"));
+        assert!(payload.contains("mempulse_ingest_decode_total"));
+        assert!(payload.contains("mempulse_ingest_tx_per_sec_current"));
+        assert!(payload.contains("mempulse_dashboard_cache_refresh_total"));
+        assert!(payload.contains("mempulse_dashboard_cache_last_build_ms"));
+        assert!(payload.contains("mempulse_dashboard_cache_estimated_bytes"));
+        assert!(payload.contains("mempulse_storage_wal_bytes_written_total 0"));
+        assert!(payload.contains("mempulse_storage_wal_fsync_latency_ns_max"));
+        assert!(payload.contains("mempulse_replay_lag_events"));
+        assert!(payload.contains("mempulse_replay_tail_reorged_tx_total"));
+        assert!(payload.contains("mempulse_sim_queue_depth"));
+        assert!(payload.contains("mempulse_sim_drop_total{reason=\"stale\"}"));
+        assert!(payload.contains("mempulse_sim_fail_total{category=\"unknown\"}"));
+        assert!(payload.contains("mempulse_relay_success_rate"));
+        assert!(payload.contains(
+            "mempulse_builder_rejected_reason_total{reason=\"simulation_not_approved\"}"
+        ));
         assert!(payload.contains("mempulse_builder_last_decision_latency_ns"));
         assert!(payload.contains("mempulse_searcher_comparison_batches_total"));
         assert!(payload.contains("mempulse_searcher_executable_bundle_candidates_total"));
@@ -3874,6 +5484,188 @@ mod tests {
         assert!(matches!(payload[0].payload, EventPayload::TxDecoded(_)));
     }
 
+    #[tokio::test]
+    async fn events_route_uses_per_chain_cursor_when_chain_id_is_set() {
+        let app = build_router(test_state(100));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/events?chain_id=1&after_chain_seq_id=1&limit=10")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let payload: Vec<EventEnvelope> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload.len(), 1);
+        assert_eq!(payload[0].seq_id, 3);
+        assert_eq!(payload[0].chain_seq_id, Some(2));
+
+        for uri in ["/events?chain_id=1&after=1", "/events?after_chain_seq_id=1"] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn events_route_applies_filter_expression() {
+        let app = build_router(test_state(100));
+        let sender = format!("0x{}", "aa".repeat(20));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!(
+                        "/events?filter=sender%20%3D%20{sender}%20or%20type%20%3D%20TxFetched"
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let payload: Vec<EventEnvelope> = serde_json::from_slice(&body).unwrap();
+        let seqs = payload.iter().map(|event| event.seq_id).collect::<Vec<_>>();
+        assert_eq!(seqs, vec![2, 3]);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/events?after=1&filter=seq_id%20%3E%200&limit=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers().get(EVENTS_RESUME_AFTER_HEADER).unwrap(),
+            "2"
+        );
+        let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let payload: Vec<EventEnvelope> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload.len(), 1);
+        assert_eq!(payload[0].seq_id, 2);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/events?chain_id=1&after_chain_seq_id=1&filter=seq_id%20%3E%200&types=TxSeen")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let payload: Vec<EventEnvelope> = serde_json::from_slice(&body).unwrap();
+        assert!(payload.is_empty());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/events?filter=score%20%3E%20high")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("expected a number"));
+    }
+
+    #[tokio::test]
+    async fn time_range_params_scope_events_replay_and_rows() {
+        let app = build_router(test_state(100));
+        let get_json = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+                (
+                    status,
+                    serde_json::from_slice::<serde_json::Value>(&body)
+                        .unwrap_or(serde_json::Value::Null),
+                )
+            }
+        };
+        let seqs = |payload: &serde_json::Value| {
+            payload
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|event| event["seq_id"].as_u64().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let (_, payload) = get_json("/events?from_ts=1700000000050").await;
+        assert_eq!(seqs(&payload), vec![2, 3]);
+        let (_, payload) = get_json("/events?to_ts=1700000000050").await;
+        assert_eq!(seqs(&payload), vec![1, 2]);
+
+        let (status, payload) =
+            get_json("/events/seq-range?from_ts=1700000000040&to_ts=1700000000100").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(payload["from_seq_id"], serde_json::json!(2));
+        assert_eq!(payload["to_seq_id"], serde_json::json!(3));
+        let (status, _) = get_json("/events/seq-range?from_ts=1800000000000").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, payload) = get_json("/replay?from_ts=1700000000000&to_ts=1700000000100").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(payload["from_seq_id"], serde_json::json!(1));
+        assert_eq!(payload["to_seq_id"], serde_json::json!(3));
+
+        let (_, payload) = get_json("/transactions?from_ts=1700000000050").await;
+        let hashes = payload
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row["hash"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(hashes, vec!["0x02".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn events_stream_route_emits_matching_envelopes() {
+        use futures::StreamExt;
+
+        let app = build_router(test_state(100));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/events/stream?filter=chain_id%20%3D%201&after=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut body = response.into_body().into_data_stream();
+        let frame = body.next().await.expect("frame").expect("frame bytes");
+        let frame = String::from_utf8_lossy(&frame);
+        assert!(frame.contains("id: 3"), "{frame}");
+        assert!(frame.contains("event: event"), "{frame}");
+        assert!(frame.contains("\"seq_id\":3"), "{frame}");
+    }
+
     #[tokio::test]
     async fn default_state_initializes_live_rpc_without_env() {
         let state = default_state();
@@ -3902,6 +5694,154 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolve_storage_checkpoint_config_requires_a_positive_interval() {
+        assert_eq!(
+            resolve_storage_checkpoint_config(None, Some("1000"), None),
+            None
+        );
+        assert_eq!(
+            resolve_storage_checkpoint_config(Some("0"), None, None),
+            None
+        );
+        assert_eq!(
+            resolve_storage_checkpoint_config(Some("30000"), Some("600000"), Some("bogus")),
+            Some(StorageCheckpointConfig {
+                interval_ms: 30_000,
+                retention: WalRetention {
+                    max_age_ms: Some(600_000),
+                    max_bytes: None,
+                },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn event_sinks_are_registered_from_json_and_reported_in_metrics() {
+        let dir = std::env::temp_dir().join(format!(
+            "viz-api-event-sinks-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let raw = format!(
+            r#"[{{"kind": "ndjson", "dir": {:?}, "chain_ids": [8453]}},
+                {{"kind": "webhook", "url": "http://127.0.0.1:9/hook", "queue_capacity": 2}}]"#,
+            dir.display().to_string()
+        );
+        let fanout = resolve_event_sinks(Some(&raw));
+        let names = fanout
+            .stats()
+            .into_iter()
+            .map(|stats| stats.name)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                format!("ndjson:{}", dir.display()),
+                "webhook:http://127.0.0.1:9/hook".to_owned()
+            ]
+        );
+        assert!(resolve_event_sinks(Some("not json")).is_empty());
+        assert!(resolve_event_sinks(None).is_empty());
+
+        let mut state = test_state(100);
+        state.provider = Arc::new(
+            InMemoryVizProvider::new(
+                Arc::new(RwLock::new(InMemoryStorage::default())),
+                Arc::new(Vec::new()),
+                1,
+            )
+            .with_event_sink_stats(Arc::new(move || fanout.stats())),
+        );
+        let response = build_router(state)
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains(
+            "mempulse_event_sink_dropped_events_total{sink=\"webhook:http://127.0.0.1:9/hook\"} 0"
+        ));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn backfill_range_leaves_unset_or_invalid_bounds_open() {
+        assert_eq!(
+            resolve_backfill_range_from(None, None, None, None),
+            BackfillRange::default()
+        );
+        assert_eq!(
+            resolve_backfill_range_from(Some(" 100 "), Some("bogus"), Some("1700000000000"), None),
+            BackfillRange {
+                after_seq_id: 100,
+                to_seq_id: None,
+                from_ts_unix_ms: Some(1_700_000_000_000),
+                to_ts_unix_ms: None,
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_clickhouse_sink_config_spills_only_with_a_directory() {
+        let config = resolve_clickhouse_sink_config_from(None, Some("  "), Some("4096"));
+        assert_eq!(config, ResilientClickHouseSinkConfig::default());
+
+        let config = resolve_clickhouse_sink_config_from(Some("5"), Some("/var/spill"), Some("0"));
+        assert_eq!(config.retry.max_attempts, 5);
+        assert_eq!(config.spill, Some(ClickHouseSpillConfig::new("/var/spill")));
+
+        let config =
+            resolve_clickhouse_sink_config_from(Some("bogus"), Some("/var/spill"), Some("4096"));
+        assert_eq!(
+            config.retry.max_attempts,
+            ClickHouseRetryConfig::default().max_attempts
+        );
+        assert_eq!(config.spill.map(|spill| spill.max_bytes), Some(4096));
+    }
+
+    #[test]
+    fn resolve_storage_config_enables_the_budget_only_when_positive() {
+        assert_eq!(resolve_storage_config_from(None, None).memory_budget, None);
+        assert_eq!(
+            resolve_storage_config_from(Some("0"), Some("tx_full=8")).memory_budget,
+            None
+        );
+
+        let budget = resolve_storage_config_from(Some("1024"), Some("tx_full=8"))
+            .memory_budget
+            .expect("budget");
+        assert_eq!(budget.max_bytes, 1024);
+        assert_eq!(budget.weights.get(StorageTable::TxFull), 8);
+
+        let budget = resolve_storage_config_from(Some("1024"), Some("mempool=3"))
+            .memory_budget
+            .expect("budget");
+        assert_eq!(budget.weights, TableWeights::default());
+    }
+
+    #[test]
+    fn resolve_wal_durability_falls_back_to_every_batch() {
+        assert_eq!(resolve_wal_durability(None), WalDurability::EveryBatch);
+        assert_eq!(resolve_wal_durability(Some("none")), WalDurability::None);
+        assert_eq!(
+            resolve_wal_durability(Some("interval:200")),
+            WalDurability::Interval { interval_ms: 200 }
+        );
+        assert_eq!(
+            resolve_wal_durability(Some("sometimes")),
+            WalDurability::EveryBatch
+        );
+    }
+
     #[tokio::test]
     async fn replay_preflight_returns_cors_headers() {
         let app = build_router(test_state(100));
@@ -4286,6 +6226,104 @@ mod tests {
         assert!(payload.is_empty());
     }
 
+    #[tokio::test]
+    async fn indexed_routes_page_transactions_by_sender_and_opps_by_protocol() {
+        let mut storage = InMemoryStorage::default();
+        for v in 1..=3_u8 {
+            storage.upsert_tx_seen(storage::TxSeenRecord {
+                hash: [v; 32],
+                peer: "peer-a".to_owned(),
+                first_seen_unix_ms: 1_700_000_000_000 + i64::from(v),
+                first_seen_mono_ns: u64::from(v),
+                seen_count: 1,
+            });
+            storage.upsert_tx_full(TxFullRecord {
+                hash: [v; 32],
+                tx_type: 2,
+                sender: [0x11; 20],
+                nonce: u64::from(v),
+                to: Some([0x22; 20]),
+                chain_id: Some(1),
+                value_wei: None,
+                gas_limit: None,
+                gas_price_wei: None,
+                max_fee_per_gas_wei: None,
+                max_priority_fee_per_gas_wei: None,
+                max_fee_per_blob_gas_wei: None,
+                calldata_len: None,
+                raw_tx: Vec::new(),
+                l2_fields: None,
+            });
+            storage.upsert_opportunity(OpportunityRecord {
+                tx_hash: [v; 32],
+                chain_id: Some(1),
+                strategy: "SandwichCandidate".to_owned(),
+                score: 1_000,
+                protocol: if v == 2 { "curve" } else { "uniswap-v3" }.to_owned(),
+                category: "swap".to_owned(),
+                feature_engine_version: "feature-engine.v1".to_owned(),
+                scorer_version: "scorer.v1".to_owned(),
+                strategy_version: "strategy.sandwich.v1".to_owned(),
+                reasons: Vec::new(),
+                detected_unix_ms: 1_700_000_000_000 + i64::from(v),
+            });
+        }
+        let mut state = test_state(100);
+        state.provider = Arc::new(InMemoryVizProvider::new(
+            Arc::new(RwLock::new(storage)),
+            Arc::new(Vec::new()),
+            1,
+        ));
+        let app = build_router(state);
+        let get_json = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+                (status, body)
+            }
+        };
+
+        let sender = format_bytes(&[0x11; 20]);
+        let (status, body) = get_json(format!("/transactions/by/sender/{sender}?limit=2")).await;
+        assert_eq!(status, StatusCode::OK);
+        let first: IndexPage<TransactionDetail> = serde_json::from_slice(&body).unwrap();
+        let hashes = first
+            .rows
+            .iter()
+            .map(|row| row.hash.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(hashes, vec![format_bytes(&[3; 32]), format_bytes(&[2; 32])]);
+        let cursor = first.next_cursor.expect("second page");
+
+        let (_, body) = get_json(format!(
+            "/transactions/by/sender/{sender}?limit=2&cursor={cursor}"
+        ))
+        .await;
+        let second: IndexPage<TransactionDetail> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(second.rows.len(), 1);
+        assert_eq!(second.rows[0].hash, format_bytes(&[1; 32]));
+        assert_eq!(second.next_cursor, None);
+
+        let (_, body) = get_json("/opps/by/protocol/uniswap-v3".to_owned()).await;
+        let opps: IndexPage<OpportunityDetail> = serde_json::from_slice(&body).unwrap();
+        let hashes = opps
+            .rows
+            .iter()
+            .map(|row| row.tx_hash.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(hashes, vec![format_bytes(&[3; 32]), format_bytes(&[1; 32])]);
+
+        let (status, _) = get_json("/transactions/by/sender/not-an-address".to_owned()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = get_json("/transactions/by/nonce/1".to_owned()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn relay_dry_run_status_route_returns_default_state() {
         let app = build_router(test_state(100));
@@ -4763,6 +6801,89 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn propagation_routes_serve_stats_observed_from_tx_seen() {
+        let mut storage = InMemoryStorage::default();
+        for (seq_id, source, hash_seq, offset_ms) in [
+            (1, "rpc-alchemy", 1, 0),
+            (2, "rpc-infura", 1, 25),
+            (3, "rpc-alchemy", 2, 100),
+            (4, "rpc-infura", 2, 175),
+            (5, "rpc-infura", 3, 200),
+        ] {
+            storage.append_event(EventEnvelope {
+                seq_id,
+                ingest_ts_unix_ms: 1_700_000_000_000 + offset_ms,
+                ingest_ts_mono_ns: seq_id,
+                source_id: common::SourceId::new(source),
+                payload: EventPayload::TxSeen(TxSeen {
+                    hash: hash_from_seq(hash_seq),
+                    peer_id: "rpc-ws".to_owned(),
+                    seen_at_unix_ms: 1_700_000_000_000 + offset_ms,
+                    seen_at_mono_ns: seq_id,
+                }),
+                chain_id: Some(1),
+                chain_seq_id: Some(seq_id),
+                hash_link: None,
+            });
+        }
+        let mut state = test_state(100);
+        state.provider = Arc::new(InMemoryVizProvider::new(
+            Arc::new(RwLock::new(storage)),
+            Arc::new(vec![PropagationEdge {
+                source: "peer-a".to_owned(),
+                destination: "peer-b".to_owned(),
+                p50_delay_ms: 8,
+                p99_delay_ms: 24,
+            }]),
+            1,
+        ));
+        let app = build_router(state);
+        let get = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+                (status, body)
+            }
+        };
+
+        let (status, body) = get("/propagation").await;
+        assert_eq!(status, StatusCode::OK);
+        let edges: Vec<PropagationEdge> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            edges,
+            vec![PropagationEdge {
+                source: "rpc-alchemy".to_owned(),
+                destination: "rpc-infura".to_owned(),
+                p50_delay_ms: 25,
+                p99_delay_ms: 75,
+            }]
+        );
+
+        // Every source reports peer `rpc-ws`, so nothing propagated between
+        // peers and the configured graph is served instead.
+        let (_, body) = get("/propagation?group=peer").await;
+        let edges: Vec<PropagationEdge> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(edges[0].source, "peer-a");
+
+        let (status, body) = get("/propagation/peers?window_ms=60000").await;
+        assert_eq!(status, StatusCode::OK);
+        let stats: Vec<PeerObservationStats> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].observer, "rpc-alchemy");
+        assert_eq!(stats[0].first_seen_share_bps, 6_666);
+        assert_eq!(stats[1].median_lag_ms, Some(25));
+        assert_eq!(stats[1].unique_hashes, 1);
+
+        let (status, _) = get("/propagation/peers?group=region").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
     #[test]
     fn in_memory_provider_propagation_edges_are_sorted_deterministically() {
         let provider = InMemoryVizProvider::new(
@@ -4833,6 +6954,7 @@ mod tests {
                 max_fee_per_blob_gas_wei: None,
                 calldata_len: Some(3),
                 raw_tx: vec![0xaa, 0xbb, 0xcc],
+                l2_fields: None,
             });
             guard.upsert_tx_features(storage::TxFeaturesRecord {
                 hash,
@@ -4894,6 +7016,9 @@ mod tests {
                     max_fee_per_blob_gas_wei: None,
                     calldata_len: None,
                 }),
+                chain_id: None,
+                chain_seq_id: None,
+                hash_link: None,
             });
             guard.append_event(EventEnvelope {
                 seq_id: 2,
@@ -4905,6 +7030,9 @@ mod tests {
                     block_number: 1_234_567,
                     block_hash: [7_u8; 32],
                 }),
+                chain_id: None,
+                chain_seq_id: None,
+                hash_link: None,
             });
             guard.append_event(EventEnvelope {
                 seq_id: 3,
@@ -4916,6 +7044,9 @@ mod tests {
                     old_block_hash: [7_u8; 32],
                     new_block_hash: [8_u8; 32],
                 }),
+                chain_id: None,
+                chain_seq_id: None,
+                hash_link: None,
             });
 
             guard.upsert_tx_seen(storage::TxSeenRecord {
@@ -4940,6 +7071,7 @@ mod tests {
                 max_fee_per_blob_gas_wei: None,
                 calldata_len: Some(0),
                 raw_tx: Vec::new(),
+                l2_fields: None,
             });
         }
 
@@ -5004,6 +7136,9 @@ mod tests {
                 seen_at_unix_ms: 1_700_000_000_000,
                 seen_at_mono_ns: 1_700_000_000_000_000_000,
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         };
 
         assert!(transaction_summary_from_event(&event).is_none());
@@ -5095,6 +7230,9 @@ mod tests {
                 strategy_version: "strategy.sandwich.v1".to_owned(),
                 reasons: vec!["mev_score=95*130".to_owned()],
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         };
 
         let detail = opportunity_detail_from_event(&MockProvider, &event).expect("opp detail");
@@ -5227,6 +7365,63 @@ mod tests {
         assert!(payload["patch"]["opportunity_upsert"].is_array());
     }
 
+    #[test]
+    fn dashboard_events_v1_filter_narrows_rows_per_subscriber() {
+        let summary = |hash: &str| TransactionSummary {
+            hash: hash.to_owned(),
+            sender: "0xaa".to_owned(),
+            nonce: 1,
+            tx_type: 2,
+            seen_unix_ms: 1_700_000_000_000,
+            source_id: "mock".to_owned(),
+        };
+        let opportunities = MockProvider.opportunities(10, 0);
+        let event = DashboardStreamBroadcastEvent::Delta(Box::new(StreamV2Dispatch {
+            op: "DISPATCH".to_owned(),
+            event_type: "DELTA_BATCH".to_owned(),
+            seq: 84,
+            channel: "tx.main".to_owned(),
+            has_gap: false,
+            patch: StreamV2Patch {
+                upsert: vec![summary("0x01"), summary("0x02")],
+                remove: Vec::new(),
+                feature_upsert: Vec::new(),
+                opportunity_upsert: opportunities.clone(),
+            },
+            watermark: StreamV2Watermark {
+                latest_ingest_seq: 84,
+            },
+            market_stats: MockProvider.market_stats(),
+        }));
+
+        let chain = EventFilter::chain(1);
+        let DashboardStreamBroadcastEvent::Delta(filtered) =
+            filter_dashboard_stream_event(&MockProvider, &chain, event.clone())
+        else {
+            panic!("deltas stay deltas");
+        };
+        let hashes = filtered
+            .patch
+            .upsert
+            .iter()
+            .map(|row| row.hash.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(hashes, vec!["0x01"]);
+        assert_eq!(
+            filtered.patch.opportunity_upsert,
+            opportunities
+                .into_iter()
+                .filter(|row| row.chain_id == Some(1))
+                .collect::<Vec<_>>()
+        );
+
+        let nothing = EventFilter::parse("protocol = none").expect("parse");
+        let filtered = filter_dashboard_stream_event(&MockProvider, &nothing, event);
+        let frame = build_dashboard_events_v1_frame(&filtered);
+        assert_eq!(frame.event, "keepalive");
+        assert_eq!(frame.id, "84");
+    }
+
     #[test]
     fn dashboard_events_v1_resume_after_prefers_last_event_id_header() {
         assert_eq!(resolve_dashboard_events_v1_after(Some("42"), Some(9)), 42);
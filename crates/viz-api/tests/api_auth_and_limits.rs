@@ -29,6 +29,16 @@ impl VizDataProvider for EmptyProvider {
         Vec::new()
     }
 
+    fn chain_events(
+        &self,
+        _chain_id: u64,
+        _after_chain_seq_id: u64,
+        _event_types: &[String],
+        _limit: usize,
+    ) -> Vec<event_log::EventEnvelope> {
+        Vec::new()
+    }
+
     fn replay_points(&self) -> Vec<ReplayPoint> {
         Vec::new()
     }
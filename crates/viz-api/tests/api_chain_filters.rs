@@ -48,6 +48,9 @@ fn decoded_event(seq_id: u64, hash_seed: u8, chain_id: u64) -> EventEnvelope {
             max_fee_per_blob_gas_wei: None,
             calldata_len: Some(4),
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -81,6 +84,7 @@ fn seed_storage() -> Arc<RwLock<InMemoryStorage>> {
             max_fee_per_blob_gas_wei: None,
             calldata_len: Some(4),
             raw_tx: vec![0xde, 0xad, 0xbe, 0xef],
+            l2_fields: None,
         });
         guard.upsert_tx_features(TxFeaturesRecord {
             hash: hash(hash_seed),
@@ -276,3 +280,55 @@ async fn api_chain_filters_apply_to_dashboard_snapshot_v2() {
     assert_eq!(payload.transactions.len(), 1);
     assert!(payload.revision > 0);
 }
+
+#[tokio::test]
+async fn api_filter_expressions_apply_to_read_model_rows() {
+    let app = build_test_app();
+
+    let opps_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/opps/recent?filter=protocol%20%3D%20aerodrome%20and%20score%20%3E%2050&limit=10")
+                .body(Body::empty())
+                .expect("opps request"),
+        )
+        .await
+        .expect("opps response");
+    assert_eq!(opps_response.status(), StatusCode::OK);
+    let opps_body = axum::body::to_bytes(opps_response.into_body(), 1024 * 1024)
+        .await
+        .expect("opps body");
+    let opps: Vec<OpportunityDetail> = serde_json::from_slice(&opps_body).expect("opps payload");
+    assert_eq!(opps.len(), 1);
+    assert_eq!(opps[0].chain_id, Some(8453));
+
+    let tx_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/transactions?chain_id=8453&filter=protocol%20%3D%20aerodrome&limit=10")
+                .body(Body::empty())
+                .expect("transactions request"),
+        )
+        .await
+        .expect("transactions response");
+    let tx_body = axum::body::to_bytes(tx_response.into_body(), 1024 * 1024)
+        .await
+        .expect("transactions body");
+    let tx_rows: Vec<TransactionSummary> =
+        serde_json::from_slice(&tx_body).expect("transactions payload");
+    assert_eq!(tx_rows.len(), 1);
+    assert_eq!(tx_rows[0].hash, format!("0x{}", "02".repeat(32)));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/features/recent?filter=score%20%3E%20high")
+                .body(Body::empty())
+                .expect("features request"),
+        )
+        .await
+        .expect("features response");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -21,6 +21,9 @@ fn seen_event(seq_id: u64, hash_seed: u8) -> EventEnvelope {
             seen_at_unix_ms: 1_700_000_000_000 + seq_id as i64,
             seen_at_mono_ns: seq_id * 1_000_000,
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -29,6 +29,7 @@ fn opportunity_zero_copy_accepts_borrowed_calldata_without_clone() {
     let batch = vec![SearcherInputTx {
         decoded: decoded_with_calldata_len(calldata.len()),
         calldata: calldata.into(),
+        l1_data_fee_wei: None,
     }];
 
     let ranked = rank_opportunity_batch(
@@ -36,6 +37,7 @@ fn opportunity_zero_copy_accepts_borrowed_calldata_without_clone() {
         SearcherConfig {
             min_score: 0,
             max_candidates: 8,
+            ..SearcherConfig::default()
         },
     )
     .candidates;
@@ -48,6 +48,9 @@ fn decoded_event(seq_id: u64, tx: &ValidatedTransaction) -> EventEnvelope {
         ingest_ts_mono_ns: tx.observed_at_mono_ns,
         source_id: tx.source_id.clone(),
         payload: EventPayload::TxDecoded(tx.decoded.clone()),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -62,6 +65,9 @@ fn reorg_event(seq_id: u64, tx: &ValidatedTransaction) -> EventEnvelope {
             old_block_hash: [0x44; 32],
             new_block_hash: [0x55; 32],
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -81,6 +87,7 @@ fn tx_full_record(tx: &ValidatedTransaction) -> TxFullRecord {
         max_fee_per_blob_gas_wei: tx.decoded.max_fee_per_blob_gas_wei,
         calldata_len: Some(tx.calldata.len() as u32),
         raw_tx: tx.calldata.clone(),
+        l2_fields: None,
     }
 }
 
@@ -48,6 +48,9 @@ fn decoded_event(seq_id: u64, tx: &ValidatedTransaction) -> EventEnvelope {
         ingest_ts_mono_ns: tx.observed_at_mono_ns,
         source_id: tx.source_id.clone(),
         payload: EventPayload::TxDecoded(tx.decoded.clone()),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -67,6 +70,7 @@ fn tx_full_record(tx: &ValidatedTransaction) -> TxFullRecord {
         max_fee_per_blob_gas_wei: tx.decoded.max_fee_per_blob_gas_wei,
         calldata_len: Some(tx.calldata.len() as u32),
         raw_tx: tx.calldata.clone(),
+        l2_fields: None,
     }
 }
 
@@ -81,6 +85,9 @@ fn confirmed_final_event(seq_id: u64, tx: &ValidatedTransaction) -> EventEnvelop
             block_number: 100 + seq_id,
             block_hash: [seq_id as u8; 32],
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -98,6 +105,9 @@ fn replaced_event(
             hash: replaced.hash(),
             replaced_by: replacement.hash(),
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -112,6 +122,9 @@ fn reorg_event(seq_id: u64, tx: &ValidatedTransaction, old_block_hash: [u8; 32])
             old_block_hash,
             new_block_hash: [seq_id as u8; 32],
         }),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
@@ -350,6 +363,9 @@ async fn binary_bootstrap_ignores_snapshot_when_finalized_gap_is_stale() {
                 block_number: 100,
                 block_hash: [0x42; 32],
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         });
     }
 
@@ -45,6 +45,9 @@ fn decoded_event(seq_id: u64, tx: &ValidatedTransaction) -> EventEnvelope {
         ingest_ts_mono_ns: tx.observed_at_mono_ns,
         source_id: tx.source_id.clone(),
         payload: EventPayload::TxDecoded(tx.decoded.clone()),
+        chain_id: None,
+        chain_seq_id: None,
+        hash_link: None,
     }
 }
 
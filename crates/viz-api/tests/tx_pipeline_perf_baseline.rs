@@ -195,6 +195,9 @@ fn seed_storage(storage: &mut InMemoryStorage, seeded_transactions: usize) -> Se
                 seen_at_unix_ms: seen_unix_ms,
                 seen_at_mono_ns: next_seq_id.saturating_mul(1_000_000),
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         });
         next_seq_id = next_seq_id.saturating_add(1);
 
@@ -207,6 +210,9 @@ fn seed_storage(storage: &mut InMemoryStorage, seeded_transactions: usize) -> Se
                 hash,
                 fetched_at_unix_ms: seen_unix_ms + 1,
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         });
         next_seq_id = next_seq_id.saturating_add(1);
 
@@ -230,6 +236,9 @@ fn seed_storage(storage: &mut InMemoryStorage, seeded_transactions: usize) -> Se
                 max_fee_per_blob_gas_wei: None,
                 calldata_len: Some(raw_tx.len() as u32),
             }),
+            chain_id: None,
+            chain_seq_id: None,
+            hash_link: None,
         });
         next_seq_id = next_seq_id.saturating_add(1);
 
@@ -255,6 +264,7 @@ fn seed_storage(storage: &mut InMemoryStorage, seeded_transactions: usize) -> Se
             max_fee_per_blob_gas_wei: None,
             calldata_len: Some(raw_tx.len() as u32),
             raw_tx: raw_tx.clone(),
+            l2_fields: None,
         });
         storage.upsert_tx_features(TxFeaturesRecord {
             hash,
@@ -16,7 +16,7 @@ echo "[verify] running replay checkpoint parity gate"
 cargo test -p replay checkpoint_hash_parity_meets_slo_for_reordered_input -- --nocapture
 
 echo "[verify] building replay, node-runtime, and viz binaries"
-cargo build -p replay --bin replay-cli
+cargo build -p storage --bin storage-replay-cli
 cargo build -p node-runtime
 cargo build -p viz-api --bin viz-api
 